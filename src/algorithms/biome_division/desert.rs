@@ -2,36 +2,45 @@
 
 use crate::core::biome::{BiomeMap, BIOME_UNASSIGNED};
 use crate::core::geometry::{self, Ellipse, Rect, Shape, ShapeParams, ShapeRecord};
+use crate::core::layer::fraction_to_row;
+use crate::core::log;
 use crate::generation::algorithm::RuntimeContext;
+use crate::generation::error::GenerationError;
+use rand::seq::SliceRandom;
 use rand::Rng;
 
+use super::interval;
 use super::BiomeDivisionAlgorithm;
 
-pub fn execute(algo: &BiomeDivisionAlgorithm, ctx: &mut RuntimeContext) -> Result<(), String> {
-    let desert_surface_id = algo.get_biome_id("desert")
-        .ok_or("未找到 desert 环境定义")?;
-    let desert_true_id = algo.get_biome_id("desert_true")
-        .ok_or("未找到 desert_true 环境定义")?;
+pub fn execute(algo: &BiomeDivisionAlgorithm, ctx: &mut RuntimeContext) -> Result<(), GenerationError> {
+    let desert_surface_id = algo.require_biome("desert")?;
+    let desert_true_id = algo.require_biome("desert_true")?;
     
-    let bm = ctx.biome_map.as_mut().ok_or("需先执行海洋生成")?;
+    // 地狱层顶部（在取可变借用之前读取），真沙漠椭圆不得越过此行
+    let hell_top = ctx.require_layer_px("hell")?.0 as f64;
+    
+    let bm = ctx.biome_map.as_mut().ok_or(GenerationError::MapNotInitialized)?;
     let w = bm.width as i32;
     let h = bm.height as i32;
     
     // ── 阶段 1：预计算常量 ─────────────────────────────
-    let surface_top_y = (h as f64 * algo.params.desert_surface_top_limit) as i32;
-    let surface_bottom_y = (h as f64 * algo.params.desert_surface_bottom_limit) as i32;
+    let surface_top_y = fraction_to_row(h as u32, algo.params.desert_surface_top_limit) as i32;
+    let surface_bottom_y = fraction_to_row(h as u32, algo.params.desert_surface_bottom_limit) as i32;
     let world_center_x = w / 2;
     
     // 真沙漠椭圆数学参数
     let true_top = h as f64 * algo.params.desert_true_top_limit;
-    let true_bottom = h as f64 * algo.params.desert_true_bottom_limit
-        * algo.params.desert_true_depth_factor;
+    // 下边界 × 深度因子在部分参数组合下会伸入地狱层；椭圆包含 cy + ry 这一行，
+    // 因此钳制到地狱层顶部的上一行，而不是依赖步长 2 的空白采样去发现越界
+    let true_bottom = (h as f64 * algo.params.desert_true_bottom_limit
+        * algo.params.desert_true_depth_factor)
+        .min(hell_top - 1.0);
     let ell_cy = (true_top + true_bottom) / 2.0;
     let ell_ry = (true_bottom - true_top) / 2.0;
     let junction_y = h as f64 * algo.params.desert_surface_bottom_limit;
     
-    // 扫描地表层中间高度的空白区段
-    let scan_y = ((surface_top_y + surface_bottom_y) / 2) as u32;
+    // 扫描地表层中间高度的空白区段；上下限都取 1.0 时中线落在 h，钳制到最后一行
+    let scan_y = ((surface_top_y + surface_bottom_y) / 2).min(h - 1) as u32;
     let mut empty_ranges: Vec<(i32, i32)> = Vec::new();
     {
         let mut range_start: Option<i32> = None;
@@ -49,6 +58,17 @@ pub fn execute(algo: &BiomeDivisionAlgorithm, ctx: &mut RuntimeContext) -> Resul
             empty_ranges.push((start, w));
         }
     }
+    // 出生点安全区不放沙漠：扣除后真沙漠从安全区边缘向外尝试
+    let safe_zone = algo.spawn_safe_zone(w);
+    if let Some(zone) = safe_zone {
+        empty_ranges = interval::subtract_zones(&empty_ranges, &[zone], 0);
+    }
+    // 全局最小间距：此前登记的环境（丛林、雪原等）向两侧扩展后视为禁区
+    let global_gap = algo.global_gap(w);
+    let foreign = interval::occupied_spans(ctx.shared);
+    if let Some(gap) = global_gap {
+        empty_ranges = interval::subtract_zones(&empty_ranges, &foreign, gap);
+    }
     
     // 辅助：验证矩形区域全空白（采样步长 2）—— 使用 geometry API
     let rect_all_empty = |bm: &BiomeMap, xl: i32, xr: i32, yt: i32, yb: i32| -> bool {
@@ -103,16 +123,23 @@ pub fn execute(algo: &BiomeDivisionAlgorithm, ctx: &mut RuntimeContext) -> Resul
     };
     
     // ── 阶段 2：优先放置真沙漠 ────────────────────────
+    // desert_true_rule 决定区段的尝试顺序与区段内的起始位置：
+    // center=离世界中心最近, largest=最宽的区段, random=随机, left / right=靠近该侧世界边缘
     if true_count > 0 && ell_ry > 0.0 {
-        let mut ranges_by_center: Vec<(i32, i32)> = empty_ranges.clone();
-        ranges_by_center.sort_by_key(|&(s, e)| {
-            let mid = (s + e) / 2;
-            (mid - world_center_x).abs()
-        });
+        let rule = algo.params.desert_true_rule.as_str();
+        let mut ordered_ranges: Vec<(i32, i32)> = empty_ranges.clone();
+        match rule {
+            "center" => ordered_ranges.sort_by_key(|&(s, e)| ((s + e) / 2 - world_center_x).abs()),
+            "largest" => ordered_ranges.sort_by_key(|&(s, e)| std::cmp::Reverse(e - s)),
+            "random" => ordered_ranges.shuffle(&mut ctx.rng),
+            "left" => ordered_ranges.sort_by_key(|&(s, _)| s),
+            "right" => ordered_ranges.sort_by_key(|&(_, e)| std::cmp::Reverse(e)),
+            other => return Err(GenerationError::invalid_param("desert_true_rule", other, "未知的真沙漠选址规则")),
+        }
         
         let mut true_placed = 0;
         
-        for &(range_start, range_end) in &ranges_by_center {
+        for &(range_start, range_end) in &ordered_ranges {
             if true_placed >= true_count { break; }
             
             let avg_width_ratio = (algo.params.desert_surface_width_min
@@ -126,21 +153,27 @@ pub fn execute(algo: &BiomeDivisionAlgorithm, ctx: &mut RuntimeContext) -> Resul
             let max_cx = range_end - half_width;
             if min_cx >= max_cx { continue; }
             
-            // 在区段内按离中心距离升序尝试多个位置
-            let closest_to_center = world_center_x.clamp(min_cx, max_cx);
+            // 在区段内从起始位置向两侧按距离升序尝试多个位置
+            let anchor = match rule {
+                "largest" => (min_cx + max_cx) / 2,
+                "random" => ctx.rng.gen_range(min_cx..=max_cx),
+                "left" => min_cx,
+                "right" => max_cx,
+                _ => world_center_x.clamp(min_cx, max_cx),
+            };
             let scan_step = (width / 2).max(4);
             
             let mut try_positions: Vec<i32> = Vec::new();
-            try_positions.push(closest_to_center);
+            try_positions.push(anchor);
             let mut offset = scan_step;
-            while closest_to_center - offset >= min_cx
-                || closest_to_center + offset <= max_cx
+            while anchor - offset >= min_cx
+                || anchor + offset <= max_cx
             {
-                if closest_to_center - offset >= min_cx {
-                    try_positions.push(closest_to_center - offset);
+                if anchor - offset >= min_cx {
+                    try_positions.push(anchor - offset);
                 }
-                if closest_to_center + offset <= max_cx {
-                    try_positions.push(closest_to_center + offset);
+                if anchor + offset <= max_cx {
+                    try_positions.push(anchor + offset);
                 }
                 offset += scan_step;
             }
@@ -158,7 +191,16 @@ pub fn execute(algo: &BiomeDivisionAlgorithm, ctx: &mut RuntimeContext) -> Resul
                 
                 let surface_half_width = width as f64 / 2.0;
                 if let Some(rx) = compute_rx(surface_half_width) {
-                    if ellipse_all_empty(bm, cx, rx) {
+                    // 椭圆在地下比地表更宽，同样不得伸入安全区
+                    let in_safe_zone = safe_zone.is_some_and(|(zl, zr)| {
+                        cx as f64 - rx < zr as f64 && cx as f64 + rx > zl as f64
+                    });
+                    let too_close = global_gap.is_some_and(|gap| {
+                        foreign.iter().any(|&(zl, zr)| {
+                            cx as f64 - rx < (zr + gap) as f64 && cx as f64 + rx > (zl - gap) as f64
+                        })
+                    });
+                    if !in_safe_zone && !too_close && ellipse_all_empty(bm, cx, rx) {
                         slots.push(DesertSlot {
                             center_x: cx,
                             width,
@@ -173,48 +215,47 @@ pub fn execute(algo: &BiomeDivisionAlgorithm, ctx: &mut RuntimeContext) -> Resul
     }
     
     // ── 阶段 3：放置剩余普通地表沙漠 ──────────────────
+    // 一维区间分配：在整列空白的区段中放置，并与真沙漠保持最小间距
     let remaining = surface_count.saturating_sub(slots.len());
-    let mut attempts = 0u32;
-    let max_attempts = (remaining as u32 + 1) * 30;
-    let mut surface_placed = 0;
-    
-    while surface_placed < remaining && attempts < max_attempts {
-        attempts += 1;
-        
-        let width_ratio = ctx.rng.gen_range(
-            algo.params.desert_surface_width_min..=algo.params.desert_surface_width_max
-        );
-        let width = (w as f64 * width_ratio) as i32;
-        let half_width = width / 2;
-        
-        let valid_ranges: Vec<_> = empty_ranges.iter()
-            .filter(|&&(s, e)| e - s >= width)
+    if remaining > 0 {
+        let widths: Vec<i32> = (0..remaining)
+            .map(|_| {
+                let ratio = ctx.rng.gen_range(
+                    algo.params.desert_surface_width_min..=algo.params.desert_surface_width_max
+                );
+                (w as f64 * ratio) as i32
+            })
             .collect();
-        if valid_ranges.is_empty() { break; }
-        
-        let range_idx = ctx.rng.gen_range(0..valid_ranges.len());
-        let &(rs, re) = valid_ranges[range_idx];
+        let min_width = (w as f64 * algo.params.desert_surface_width_min) as i32;
         
-        let min_cx = rs + half_width;
-        let max_cx = re - half_width;
-        if min_cx >= max_cx { continue; }
-        let cx = ctx.rng.gen_range(min_cx..max_cx);
-        
-        if !spacing_ok(&slots, cx, width, min_spacing) { continue; }
-        
-        let xl = (cx - half_width).max(0);
-        let xr = (cx + half_width).min(w);
-        if !rect_all_empty(bm, xl, xr, surface_top_y, surface_bottom_y.min(h)) {
-            continue;
+        let free = interval::free_column_spans(bm, surface_top_y, surface_bottom_y.min(h));
+        let occupied: Vec<interval::Span> = slots.iter()
+            .map(|slot| (slot.center_x - slot.width / 2, slot.center_x + slot.width / 2))
+            .collect();
+        let mut spans = interval::subtract_zones(&free, &occupied, min_spacing);
+        if let Some(zone) = safe_zone {
+            spans = interval::subtract_zones(&spans, &[zone], 0);
+        }
+        if let Some(gap) = global_gap {
+            spans = interval::subtract_zones(&spans, &foreign, gap);
         }
         
-        slots.push(DesertSlot {
-            center_x: cx,
-            width,
-            has_true: false,
-            rx: 0.0,
-        });
-        surface_placed += 1;
+        for (xl, width) in interval::allocate(&spans, &widths, min_width, min_spacing, &mut ctx.rng) {
+            slots.push(DesertSlot {
+                center_x: xl + width / 2,
+                width,
+                has_true: false,
+                rx: 0.0,
+            });
+        }
+    }
+    
+    let true_placed = slots.iter().filter(|slot| slot.has_true).count();
+    if true_placed < true_count {
+        log::warn(format!("真沙漠只放置了 {true_placed}/{true_count} 个（空间不足）"));
+    }
+    if slots.len() < surface_count {
+        log::warn(format!("地表沙漠只放置了 {}/{surface_count} 个（空间不足）", slots.len()));
     }
     
     // ── 阶段 4：一次性绘制 + 保存槽位信息 ──────────────
@@ -229,6 +270,7 @@ pub fn execute(algo: &BiomeDivisionAlgorithm, ctx: &mut RuntimeContext) -> Resul
         // 绘制地表沙漠矩形 —— geometry API
         let surface_rect = Rect::new(xl, surface_top_y, xr, surface_bottom_y.min(h));
         geometry::fill_biome_if(&surface_rect, bm, desert_surface_id, |c| c == BIOME_UNASSIGNED);
+        interval::register_occupied(ctx.shared, (xl, xr));
         ctx.shape_log.push(ShapeRecord {
             label: format!("地表沙漠 #{}", slot_data.len() + 1),
             bbox: surface_rect.bounding_box(),
@@ -239,12 +281,17 @@ pub fn execute(algo: &BiomeDivisionAlgorithm, ctx: &mut RuntimeContext) -> Resul
         // 绘制真沙漠完整椭圆（覆写其内部的地表沙漠）—— geometry API
         if slot.has_true {
             let ell = Ellipse::new(slot.center_x as f64, ell_cy, slot.rx, ell_ry);
-            geometry::fill_biome_if(&ell, bm, desert_true_id, |c| {
-                c == BIOME_UNASSIGNED || c == desert_surface_id
-            });
+            let fill_filter = |c| c == BIOME_UNASSIGNED || c == desert_surface_id;
+            if algo.params.desert_soft_edge {
+                geometry::fill_biome_soft_if(&ell, bm, desert_true_id, &mut ctx.rng, fill_filter);
+            } else {
+                geometry::fill_biome_if(&ell, bm, desert_true_id, fill_filter);
+            }
+            let bbox = ell.bounding_box();
+            interval::register_occupied(ctx.shared, (bbox.x_min, bbox.x_max));
             ctx.shape_log.push(ShapeRecord {
                 label: "真沙漠椭圆".into(),
-                bbox: ell.bounding_box(),
+                bbox,
                 color: algo.biome_color(desert_true_id),
                 params: ShapeParams::from_ellipse(&ell),
             });
@@ -254,9 +301,79 @@ pub fn execute(algo: &BiomeDivisionAlgorithm, ctx: &mut RuntimeContext) -> Resul
         slot_data.push((slot.center_x, slot.width));
     }
     
-    // 保存槽位信息到 shared，供森林填充步骤判断真沙漠候选
+    // 保存槽位信息到 shared，供森林填充步骤判断真沙漠候选。
+    // 按中心 x 排序：放置顺序（真沙漠优先、随后按区间分配）不属于跨步骤约定
+    slot_data.sort_by_key(|&(center_x, _)| center_x);
+    true_slot_data.sort_by_key(|&(center_x, _)| center_x);
     ctx.shared.insert("desert_slots".into(), Box::new(slot_data));
     ctx.shared.insert("desert_true_slots".into(), Box::new(true_slot_data));
     
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::generation::headless::HeadlessContext;
+
+    #[test]
+    fn stored_slots_are_sorted_by_center() {
+        let context = HeadlessContext::load("small", None).unwrap();
+        for seed in 0..8 {
+            let pipeline = context.generate(seed, |p| p.set_step_logging(false)).unwrap();
+            for key in ["desert_slots", "desert_true_slots"] {
+                let slots = pipeline.shared::<Vec<(i32, i32)>>(key).expect(key);
+                assert!(slots.is_sorted_by_key(|s| s.0), "seed {seed}: {key} 未按中心排序: {slots:?}");
+            }
+            let evil = pipeline
+                .shared::<Vec<(i32, i32, crate::core::biome::BiomeId)>>("evil_slots")
+                .expect("evil_slots");
+            assert!(evil.is_sorted_by_key(|s| s.0), "seed {seed}: evil_slots 未按中心排序: {evil:?}");
+        }
+    }
+
+    #[test]
+    fn surface_limits_at_world_bottom_stay_in_bounds() {
+        // 上下限都为 1.0 时扫描行落在 h；debug 构建下 BiomeMap::get 越界会 panic
+        let context = HeadlessContext::load("small", None).unwrap();
+        for (top, bottom) in [(1.0, 1.0), (0.0, 1.0), (0.0, 0.0)] {
+            context
+                .generate(3, |p| {
+                    p.set_step_logging(false);
+                    let algo = p.algorithm_by_id_mut("biome_division").unwrap();
+                    let mut params = algo.get_params();
+                    params["desert_surface_top_limit"] = serde_json::json!(top);
+                    params["desert_surface_bottom_limit"] = serde_json::json!(bottom);
+                    algo.set_params(&params);
+                })
+                .unwrap_or_else(|e| panic!("上下限 ({top}, {bottom}) 生成失败: {e}"));
+        }
+    }
+
+    #[test]
+    fn true_desert_stays_above_hell_at_maximum_depth() {
+        // 下边界与深度因子都取上限时椭圆下沿落在世界底部，应被钳制在地狱层顶部之上
+        let context = HeadlessContext::load("small", None).unwrap();
+        let hell_top = context.profile.layers.iter()
+            .find(|l| l.key == "hell")
+            .map(|l| l.bounds_for_height(context.profile.size.height).0)
+            .expect("hell 层级");
+        let desert_true = context.biomes.iter().find(|b| b.key == "desert_true").unwrap().id;
+        for seed in 0..4 {
+            let pipeline = context
+                .generate(seed, |p| {
+                    p.set_step_logging(false);
+                    let algo = p.algorithm_by_id_mut("biome_division").unwrap();
+                    let mut params = algo.get_params();
+                    params["desert_true_bottom_limit"] = serde_json::json!(1.0);
+                    params["desert_true_depth_factor"] = serde_json::json!(1.0);
+                    algo.set_params(&params);
+                })
+                .unwrap();
+            let bm = pipeline.biome_map().unwrap();
+            let deepest = (0..bm.height).rev()
+                .find(|&y| (0..bm.width).any(|x| bm.get(x, y) == desert_true));
+            assert!(deepest.is_some(), "seed {seed}: 应放置真沙漠");
+            assert!(deepest < Some(hell_top), "seed {seed}: 真沙漠伸到第 {deepest:?} 行，地狱层从 {hell_top} 行开始");
+        }
+    }
+}
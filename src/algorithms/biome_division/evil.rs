@@ -0,0 +1,151 @@
+//! 邪恶环境生成步骤
+//!
+//! 在地表空白区段放置 `evil_count` 个邪恶环境矩形（猩红 / 腐化），彼此保持最小间距。
+//! 由一维区间分配器在整列空白的区段中放置，因此不会与海洋、丛林、沙漠等已生成环境重叠；
+//! 只要几何上放得下 `evil_count` 个最小宽度的区域就一定全部放置。
+//! 出生点安全区（`spawn_safe_radius`）从可用区段中扣除；`global_min_gap` > 0 时，
+//! 此前登记的所有环境（丛林、雪原、沙漠）向两侧扩展该间距后同样扣除。
+
+use crate::core::biome::{BiomeId, BIOME_UNASSIGNED};
+use crate::core::geometry::{self, Rect, Shape, ShapeParams, ShapeRecord};
+use crate::core::layer::fraction_to_row;
+use crate::core::log;
+use crate::generation::algorithm::RuntimeContext;
+use crate::generation::error::GenerationError;
+use rand::Rng;
+
+use super::interval;
+use super::BiomeDivisionAlgorithm;
+
+pub fn execute(algo: &BiomeDivisionAlgorithm, ctx: &mut RuntimeContext) -> Result<(), GenerationError> {
+    // 按 evil_type 确定候选环境；random 时每个区域独立抽取
+    let candidates: Vec<BiomeId> = match algo.params.evil_type.as_str() {
+        "crimson" => vec![algo.require_biome("crimson")?],
+        "corruption" => vec![algo.require_biome("corruption")?],
+        "random" => vec![
+            algo.require_biome("crimson")?,
+            algo.require_biome("corruption")?,
+        ],
+        other => return Err(GenerationError::invalid_param("evil_type", other, "未知的邪恶环境类型")),
+    };
+    
+    let bm = ctx.biome_map.as_mut().ok_or(GenerationError::MapNotInitialized)?;
+    let w = bm.width as i32;
+    let h = bm.height as i32;
+    
+    let surface_top_y = fraction_to_row(h as u32, algo.params.evil_top_limit) as i32;
+    let surface_bottom_y = fraction_to_row(h as u32, algo.params.evil_bottom_limit) as i32;
+    let min_spacing = (w as f64 * algo.params.evil_min_spacing) as i32;
+    let count = algo.params.evil_count as usize;
+    
+    if count == 0 { return Ok(()); }
+    
+    // 一维区间分配：宽度先随机抽取，再在整列空白的区段中确定性地放置
+    let widths: Vec<i32> = (0..count)
+        .map(|_| {
+            let ratio = ctx.rng.gen_range(algo.params.evil_width_min..=algo.params.evil_width_max);
+            (w as f64 * ratio) as i32
+        })
+        .collect();
+    let min_width = (w as f64 * algo.params.evil_width_min) as i32;
+    let mut spans = interval::free_column_spans(bm, surface_top_y, surface_bottom_y.min(h));
+    // 出生点安全区不参与分配，区域自然落到安全区外侧
+    if let Some(zone) = algo.spawn_safe_zone(w) {
+        spans = interval::subtract_zones(&spans, &[zone], 0);
+    }
+    // 全局最小间距：与先放置的任意类型环境保持距离
+    if let Some(gap) = algo.global_gap(w) {
+        spans = interval::subtract_zones(&spans, &interval::occupied_spans(ctx.shared), gap);
+    }
+    
+    // 槽位记录
+    struct EvilSlot {
+        center_x: i32,
+        width: i32,
+        biome: BiomeId,
+    }
+    let slots: Vec<EvilSlot> = interval::allocate(&spans, &widths, min_width, min_spacing, &mut ctx.rng)
+        .into_iter()
+        .map(|(xl, width)| EvilSlot {
+            center_x: xl + width / 2,
+            width,
+            // 只有一种候选时不抽随机数，保持与单一邪恶类型时代的种子序列一致
+            biome: if candidates.len() > 1 {
+                candidates[ctx.rng.gen_range(0..candidates.len())]
+            } else {
+                candidates[0]
+            },
+        })
+        .collect();
+    
+    if slots.len() < count {
+        log::warn(format!("邪恶环境只放置了 {}/{count} 个（空间不足）", slots.len()));
+    }
+    
+    // 一次性绘制 + 保存槽位信息 —— geometry API
+    let mut slot_data: Vec<(i32, i32, BiomeId)> = Vec::new();
+    for (i, slot) in slots.iter().enumerate() {
+        let half_width = slot.width / 2;
+        let xl = (slot.center_x - half_width).max(0);
+        let xr = (slot.center_x + half_width).min(w);
+        
+        let rect = Rect::new(xl, surface_top_y, xr, surface_bottom_y.min(h));
+        geometry::fill_biome_if(&rect, bm, slot.biome, |c| c == BIOME_UNASSIGNED);
+        ctx.shape_log.push(ShapeRecord {
+            label: format!("{} #{}", algo.biome_name(slot.biome), i + 1),
+            bbox: rect.bounding_box(),
+            color: algo.biome_color(slot.biome),
+            params: ShapeParams::from_rect(&rect),
+        });
+        interval::register_occupied(ctx.shared, (xl, xr));
+        slot_data.push((slot.center_x, slot.width, slot.biome));
+    }
+    
+    // 保存槽位信息到 shared: (中心 x, 宽度, 环境 ID)，按中心 x 排序
+    slot_data.sort_by_key(|&(center_x, _, _)| center_x);
+    ctx.shared.insert("evil_slots".into(), Box::new(slot_data));
+    
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::interval::OCCUPIED_SPANS_KEY;
+    use crate::generation::headless::HeadlessContext;
+
+    #[test]
+    fn global_min_gap_separates_evil_from_other_biomes() {
+        let context = HeadlessContext::load("small", None).unwrap();
+        let mut checked = 0;
+        for seed in 0..8 {
+            let pipeline = context
+                .generate(seed, |p| {
+                    p.set_step_logging(false);
+                    let algo = p.algorithm_by_id_mut("biome_division").unwrap();
+                    let mut params = algo.get_params();
+                    params["global_min_gap"] = serde_json::json!(0.02);
+                    algo.set_params(&params);
+                })
+                .unwrap();
+            let gap = (pipeline.biome_map().unwrap().width as f64 * 0.02) as i32;
+            let spans = pipeline
+                .shared::<Vec<(i32, i32)>>(OCCUPIED_SPANS_KEY)
+                .expect("occupied_spans");
+            let evil_count = pipeline
+                .shared::<Vec<(i32, i32, crate::core::biome::BiomeId)>>("evil_slots")
+                .map_or(0, Vec::len);
+            // 邪恶环境最后放置，登记在末尾
+            let (others, evil) = spans.split_at(spans.len() - evil_count);
+            checked += evil.len();
+            for &(el, er) in evil {
+                for &(ol, or) in others {
+                    assert!(
+                        el >= or + gap || er + gap <= ol,
+                        "seed {seed}: 邪恶环境 {:?} 与 {:?} 间距不足 {gap}", (el, er), (ol, or),
+                    );
+                }
+            }
+        }
+        assert!(checked > 0, "至少应有种子放置了邪恶环境");
+    }
+}
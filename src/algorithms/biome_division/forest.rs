@@ -3,22 +3,23 @@
 use crate::core::biome::BIOME_UNASSIGNED;
 use crate::core::geometry::{self, Rect, Shape, ShapeParams, ShapeRecord};
 use crate::generation::algorithm::RuntimeContext;
+use crate::generation::error::GenerationError;
 
 use super::BiomeDivisionAlgorithm;
 
-pub fn execute(algo: &BiomeDivisionAlgorithm, ctx: &mut RuntimeContext) -> Result<(), String> {
-    let forest_id = algo.get_biome_id("forest")
-        .ok_or("未找到 forest 环境定义")?;
+pub fn execute(algo: &BiomeDivisionAlgorithm, ctx: &mut RuntimeContext) -> Result<(), GenerationError> {
+    let forest_id = algo.require_biome("forest")?;
     
     // 读取层级边界（在取可变借用之前）
-    let y_top = ctx.layer_start_px("surface").ok_or("未找到 surface 层级定义")? as i32;
-    let y_bottom = ctx.layer_end_px("underground").ok_or("未找到 underground 层级定义")? as i32;
+    let y_top = ctx.require_layer_px("surface")?.0 as i32;
+    let y_bottom = ctx.require_layer_px("underground")?.1 as i32;
     
-    let bm = ctx.biome_map.as_mut().ok_or("需先执行海洋生成")?;
+    let bm = ctx.biome_map.as_mut().ok_or(GenerationError::MapNotInitialized)?;
     let w = bm.width as i32;
     let _h = bm.height as i32;
     let center_x = w / 2;
-    let half_width = (w as f64 * algo.params.forest_width_ratio) as i32;
+    // 填充宽度与丛林 / 雪原避让的保留走廊（forest_width_ratio）相互独立
+    let half_width = (w as f64 * algo.params.forest_surface_extent) as i32;
     
     let shape = Rect::new(
         center_x - half_width, y_top,
@@ -34,3 +35,37 @@ pub fn execute(algo: &BiomeDivisionAlgorithm, ctx: &mut RuntimeContext) -> Resul
     
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::generation::build_pipeline;
+    use crate::generation::headless::HeadlessContext;
+
+    #[test]
+    fn old_preset_without_surface_extent_uses_corridor_width() {
+        let context = HeadlessContext::load("small", None).unwrap();
+        let mut pipeline = build_pipeline(1, context.biomes.clone(), &context.profile.layers);
+        let algo = pipeline.algorithm_by_id_mut("biome_division").unwrap();
+
+        // 拆分前的预设：只有 forest_width_ratio，它同时决定走廊与森林填充宽度
+        let mut old = algo.get_params();
+        old.as_object_mut().unwrap().remove("forest_surface_extent");
+        old["forest_width_ratio"] = serde_json::json!(0.08);
+        algo.set_params(&old);
+        assert_eq!(algo.get_params()["forest_surface_extent"], serde_json::json!(0.08));
+
+        // 新预设两者独立
+        let mut new = algo.get_params();
+        new["forest_surface_extent"] = serde_json::json!(0.03);
+        algo.set_params(&new);
+        assert_eq!(algo.get_params()["forest_surface_extent"], serde_json::json!(0.03));
+        assert_eq!(algo.get_params()["forest_width_ratio"], serde_json::json!(0.08));
+
+        // 快照恢复时不把它报告为缺失参数
+        let mut snapshot = pipeline.collect_snapshot("small", &context.profile.layers);
+        let state = snapshot.algorithms.iter_mut().find(|s| s.algorithm_id == "biome_division").unwrap();
+        state.params = old;
+        let missing = pipeline.restore_from_snapshot(&snapshot);
+        assert!(!missing.iter().any(|m| m.ends_with("forest_surface_extent")), "{missing:?}");
+    }
+}
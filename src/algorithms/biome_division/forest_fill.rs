@@ -3,26 +3,45 @@
 use crate::core::biome::{BiomeId, BIOME_UNASSIGNED};
 use crate::core::geometry::{self, Rect, Shape, ShapeParams, ShapeRecord};
 use crate::generation::algorithm::RuntimeContext;
+use crate::generation::error::GenerationError;
 
 use super::BiomeDivisionAlgorithm;
 
-pub fn execute(algo: &BiomeDivisionAlgorithm, ctx: &mut RuntimeContext) -> Result<(), String> {
-    let forest_id = algo.get_biome_id("forest")
-        .ok_or("未找到 forest 环境定义")?;
-    let desert_surface_id = algo.get_biome_id("desert")
-        .ok_or("未找到 desert 环境定义")?;
-    let crimson_id = algo.get_biome_id("crimson")
-        .ok_or("未找到 crimson 环境定义")?;
+/// `shared` 中记录扩散任务的键（值为 `Vec<DiffusionTrace>`），供调试覆盖层绘制扩散箭头
+pub const DIFFUSION_TRACE_KEY: &str = "forest_fill_diffusion";
+
+/// 一次沙漠 / 邪恶环境边缘扩散的记录
+#[derive(Debug, Clone)]
+pub struct DiffusionTrace {
+    /// 扫描线所在行（决定是否扩散的那一行）
+    pub scan_y: i32,
+    /// -1 向左, +1 向右
+    pub direction: i32,
+    pub fill_id: BiomeId,
+    /// 每行实际扩散的 `(y, 起始 x, 填充格数)`（只含填充格数 > 0 的行）
+    pub rows: Vec<(i32, i32, i32)>,
+}
+
+pub fn execute(algo: &BiomeDivisionAlgorithm, ctx: &mut RuntimeContext) -> Result<(), GenerationError> {
+    let forest_id = algo.require_biome("forest")?;
+    let desert_surface_id = algo.require_biome("desert")?;
+    // 邪恶环境：两种都可能出现（evil_type = random 时混合），缺失的定义不参与扩散
+    let evil_ids: Vec<BiomeId> = ["crimson", "corruption"]
+        .iter()
+        .filter_map(|key| algo.get_biome_id(key))
+        .collect();
     
     // 读取层级边界（在取可变借用之前）
-    let layer_top = ctx.layer_start_px("surface").ok_or("未找到 surface 层级定义")? as i32;
-    let layer_bottom = ctx.layer_end_px("underground").ok_or("未找到 underground 层级定义")? as i32;
+    let layer_top = ctx.require_layer_px("surface")?.0 as i32;
+    let layer_bottom = ctx.require_layer_px("underground")?.1 as i32;
     let scan_y = ((layer_top as u32) + (layer_bottom as u32)) / 2;
     
-    let bm = ctx.biome_map.as_mut().ok_or("需先执行海洋生成")?;
+    let bm = ctx.biome_map.as_mut().ok_or(GenerationError::MapNotInitialized)?;
     let w = bm.width as i32;
     let h = bm.height as i32;
     
+    // 层级贴到世界底部时中线可能落在 h，钳制到最后一行
+    let scan_y = scan_y.min(h as u32 - 1);
     let threshold = algo.params.forest_fill_merge_threshold as i32;
     
     // ── 计算真沙漠槽位，排除其上方地表沙漠参与扩散 ──
@@ -42,10 +61,10 @@ pub fn execute(algo: &BiomeDivisionAlgorithm, ctx: &mut RuntimeContext) -> Resul
     };
     
     let can_expand = |bid: BiomeId| -> bool {
-        bid == desert_surface_id || bid == crimson_id
+        bid == desert_surface_id || evil_ids.contains(&bid)
     };
     
-    // ── 阶段 1：在 y=中间扫描线 扫描，判断哪些沙漠/猩红需要扩散 ──
+    // ── 阶段 1：在 y=中间扫描线 扫描，判断哪些沙漠/邪恶环境需要扩散 ──
     // scan_y 已在上方从层级配置计算
     
     struct Seg {
@@ -66,7 +85,7 @@ pub fn execute(algo: &BiomeDivisionAlgorithm, ctx: &mut RuntimeContext) -> Resul
         }
     }
     
-    // 记录扩散任务：(沙漠/猩红区段的边缘x, 方向, biome_id)
+    // 记录扩散任务：(沙漠/邪恶环境区段的边缘x, 方向, biome_id)
     // direction: -1=向左扩散, +1=向右扩散
     struct ExpandTask {
         edge_x: i32,    // 扩散起始边缘
@@ -93,7 +112,7 @@ pub fn execute(algo: &BiomeDivisionAlgorithm, ctx: &mut RuntimeContext) -> Resul
             let gap_width = segs[i - 1].end - segs[i - 1].start;
             if gap_width < threshold {
                 tasks.push(ExpandTask {
-                    edge_x: segs[i].start, // 沙漠/猩红的左边缘
+                    edge_x: segs[i].start, // 沙漠/邪恶环境的左边缘
                     direction: -1,
                     fill_id,
                 });
@@ -105,7 +124,7 @@ pub fn execute(algo: &BiomeDivisionAlgorithm, ctx: &mut RuntimeContext) -> Resul
             let gap_width = segs[i + 1].end - segs[i + 1].start;
             if gap_width < threshold {
                 tasks.push(ExpandTask {
-                    edge_x: segs[i].end, // 沙漠/猩红的右边缘
+                    edge_x: segs[i].end, // 沙漠/邪恶环境的右边缘
                     direction: 1,
                     fill_id,
                 });
@@ -114,9 +133,11 @@ pub fn execute(algo: &BiomeDivisionAlgorithm, ctx: &mut RuntimeContext) -> Resul
     }
     
     // ── 阶段 2：逐行从实际边缘向外扩散，直到碰到非空像素 ──
+    let mut traces = Vec::with_capacity(tasks.len());
     for task in &tasks {
+        let mut rows = Vec::new();
         for y in layer_top..layer_bottom {
-            // 从扫描线的 edge_x 向内搜索，找到该行实际的沙漠/猩红边缘
+            // 从扫描线的 edge_x 向内搜索，找到该行实际的沙漠/邪恶环境边缘
             // 这样避免扫描线位置和实际边缘错位导致夹缝
             let inward = -task.direction; // 向内方向
             let mut actual_edge = task.edge_x;
@@ -138,8 +159,19 @@ pub fn execute(algo: &BiomeDivisionAlgorithm, ctx: &mut RuntimeContext) -> Resul
                 }
                 bm.set(x as u32, y as u32, task.fill_id);
             }
+            let filled = (x - actual_edge).abs() - 1;
+            if filled > 0 {
+                rows.push((y, actual_edge, filled));
+            }
         }
+        traces.push(DiffusionTrace {
+            scan_y: scan_y as i32,
+            direction: task.direction,
+            fill_id: task.fill_id,
+            rows,
+        });
     }
+    ctx.shared.insert(DIFFUSION_TRACE_KEY.into(), Box::new(traces));
     
     // ── 阶段 3：剩余空白填充为森林（仅地表+地下层）—— geometry API
     let fill_rect = Rect::new(0, layer_top, w, layer_bottom);
@@ -0,0 +1,284 @@
+//! 一维区间分配器
+//!
+//! 沙漠 / 邪恶环境都是"在地表横向空白区段里放 N 个带最小间距的矩形"。
+//! 这里把问题化为一维：给定空白区段、每个区域的宽度与最小间距，确定性地计算放置位置，
+//! 取代原先 O(attempts) 的随机拒绝采样（空间足够时也可能少放）。
+//!
+//! ## 保证
+//!
+//! 1. 区段之间的间距先被消解（相邻区段间隙不足 `spacing` 时截短后一段），
+//!    此后每个区段可独立装箱：一组宽度 `w₁..w_k` 能放进长度 `L` 的区段，
+//!    当且仅当 `Σw + (k-1)·spacing ≤ L`。
+//! 2. 宽度按降序逐个放入剩余容量最大的区段（worst-fit decreasing，即"从最宽区段开始贪心"）。
+//! 3. 放不下时把当前最宽的区域缩到 `min_width` 重试。全部缩到最小宽度后宽度相等，
+//!    贪心放置的数量即为最优——因此只要**消解间距后的区段**能放下 N 个最小宽度的区域，
+//!    就一定放满 N 个。
+//!
+//! 第 1 步总是截短后一段，不会在两段之间权衡截哪一侧，可能损失少量容量：相对原始区段
+//! 并非严格最优，原始几何恰好放得下时仍可能少放。
+//!
+//! 区段内剩余的空隙按随机切分分配到各区域之间，保持同一种子下结果可复现、又不会全部挤在左侧。
+
+use std::any::Any;
+use std::collections::HashMap;
+
+use crate::core::biome::{BiomeMap, BIOME_UNASSIGNED};
+use rand::Rng;
+
+/// 半开区间 `[start, end)`（像素列）
+pub type Span = (i32, i32);
+
+/// `shared` 中登记已放置环境列区间的键（值为 `Vec<Span>`）。
+///
+/// 丛林、雪原、沙漠、邪恶环境放置后各自登记包围盒的列范围，
+/// 后续步骤据此检查不分类型的全局最小间距（`global_min_gap`）。
+pub const OCCUPIED_SPANS_KEY: &str = "occupied_spans";
+
+/// 向 `shared` 登记一个已放置环境的列区间
+pub fn register_occupied(shared: &mut HashMap<String, Box<dyn Any>>, span: Span) {
+    let entry = shared
+        .entry(OCCUPIED_SPANS_KEY.into())
+        .or_insert_with(|| Box::new(Vec::<Span>::new()));
+    if let Some(spans) = entry.downcast_mut::<Vec<Span>>() {
+        spans.push(span);
+    }
+}
+
+/// 读取此前各步骤登记的列区间（无登记时为空）
+pub fn occupied_spans(shared: &HashMap<String, Box<dyn Any>>) -> Vec<Span> {
+    shared
+        .get(OCCUPIED_SPANS_KEY)
+        .and_then(|v| v.downcast_ref::<Vec<Span>>())
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// 扫描 `[y_top, y_bottom)` 行范围内整列都为空白的列区段。
+///
+/// 区段内任意矩形（同一行范围）都保证全空白，无需再逐个验证。
+/// 行范围钳制到地图内；钳制后为空时每一列都视为空白。
+pub fn free_column_spans(bm: &BiomeMap, y_top: i32, y_bottom: i32) -> Vec<Span> {
+    let y_top = y_top.max(0) as u32;
+    let y_bottom = (y_bottom.max(0) as u32).min(bm.height);
+    let column_free = |x: u32| (y_top..y_bottom).all(|y| bm.get(x, y) == BIOME_UNASSIGNED);
+
+    let mut spans = Vec::new();
+    let mut start: Option<i32> = None;
+    for x in 0..bm.width {
+        if column_free(x) {
+            start.get_or_insert(x as i32);
+        } else if let Some(s) = start.take() {
+            spans.push((s, x as i32));
+        }
+    }
+    if let Some(s) = start {
+        spans.push((s, bm.width as i32));
+    }
+    spans
+}
+
+/// 从区段中扣除已占用区域（各自向两侧扩展 `spacing`），用于与先放置的区域保持间距。
+///
+/// 扩展后仍为空（零宽且 `spacing` 为 0）或反向的区域不占任何列，直接忽略，
+/// 否则会把区段在该点切成两段、甚至产生相互重叠的区段。
+pub fn subtract_zones(spans: &[Span], occupied: &[Span], spacing: i32) -> Vec<Span> {
+    let mut result: Vec<Span> = spans.to_vec();
+    for &(zl, zr) in occupied {
+        let (zl, zr) = (zl - spacing, zr + spacing);
+        if zr <= zl {
+            continue;
+        }
+        result = result
+            .into_iter()
+            .flat_map(|(s, e)| [(s, e.min(zl)), (s.max(zr), e)])
+            .filter(|&(s, e)| e > s)
+            .collect();
+    }
+    result
+}
+
+/// 在 `spans` 中放置宽度为 `widths` 的区域，两两之间至少相隔 `spacing`。
+///
+/// 放不下时逐个把最宽的区域缩到 `min_width`；仍放不下则放置尽可能多的区域。
+/// 返回 `(左边界, 宽度)`，按左边界升序。
+pub fn allocate<R: Rng>(
+    spans: &[Span],
+    widths: &[i32],
+    min_width: i32,
+    spacing: i32,
+    rng: &mut R,
+) -> Vec<Span> {
+    let bins = decouple(spans, spacing);
+    if bins.is_empty() || widths.is_empty() {
+        return Vec::new();
+    }
+
+    let mut widths: Vec<i32> = widths.iter().map(|&w| w.max(1)).collect();
+    let min_width = min_width.max(1);
+    let assignment = loop {
+        let (assignment, placed) = pack(&bins, &widths, spacing);
+        if placed == widths.len() {
+            break assignment;
+        }
+        // 把最宽且仍可缩小的区域缩到最小宽度；已全部最小时接受部分结果
+        match widths
+            .iter_mut()
+            .filter(|w| **w > min_width)
+            .max_by_key(|w| **w)
+        {
+            Some(w) => *w = min_width,
+            None => break assignment,
+        }
+    };
+
+    // 区段内按随机切分的空隙布局
+    let mut placements = Vec::new();
+    for (bin, items) in bins.iter().zip(&assignment) {
+        if items.is_empty() {
+            continue;
+        }
+        let used: i32 = items.iter().map(|&i| widths[i]).sum::<i32>()
+            + (items.len() as i32 - 1) * spacing;
+        let slack = bin.1 - bin.0 - used;
+        let mut cuts: Vec<i32> = (0..items.len()).map(|_| rng.gen_range(0..=slack)).collect();
+        cuts.sort_unstable();
+
+        let mut cursor = bin.0;
+        for (&i, cut) in items.iter().zip(cuts) {
+            placements.push((cursor + cut, widths[i]));
+            cursor += widths[i] + spacing;
+        }
+    }
+    placements.sort_unstable_by_key(|&(x, _)| x);
+    placements
+}
+
+/// 消解区段之间的间距：后一段起点推到前一段终点 + `spacing` 之后，使各段可以独立装箱。
+fn decouple(spans: &[Span], spacing: i32) -> Vec<Span> {
+    let mut sorted = spans.to_vec();
+    sorted.sort_unstable_by_key(|&(s, _)| s);
+
+    let mut bins: Vec<Span> = Vec::with_capacity(sorted.len());
+    for (s, e) in sorted {
+        let s = match bins.last() {
+            Some(&(_, prev_end)) => s.max(prev_end + spacing),
+            None => s,
+        };
+        if e > s {
+            bins.push((s, e));
+        }
+    }
+    bins
+}
+
+/// worst-fit decreasing 装箱：返回每个区段分到的区域下标，以及成功放置的数量。
+fn pack(bins: &[Span], widths: &[i32], spacing: i32) -> (Vec<Vec<usize>>, usize) {
+    let mut order: Vec<usize> = (0..widths.len()).collect();
+    order.sort_by_key(|&i| std::cmp::Reverse(widths[i]));
+
+    // 每个区域占用 w + spacing，区段容量 L + spacing（最后一个区域后没有间距）
+    let mut remaining: Vec<i32> = bins.iter().map(|&(s, e)| e - s + spacing).collect();
+    let mut assignment: Vec<Vec<usize>> = vec![Vec::new(); bins.len()];
+    let mut placed = 0;
+
+    for i in order {
+        let need = widths[i] + spacing;
+        let best = (0..bins.len())
+            .filter(|&b| remaining[b] >= need)
+            .max_by_key(|&b| (remaining[b], std::cmp::Reverse(b)));
+        if let Some(b) = best {
+            remaining[b] -= need;
+            assignment[b].push(i);
+            placed += 1;
+        }
+    }
+    (assignment, placed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{allocate, free_column_spans, pack, subtract_zones, Span};
+    use crate::core::biome::{BiomeMap, BIOME_UNASSIGNED};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn free_column_spans_skip_columns_with_any_assigned_cell() {
+        let mut bm = BiomeMap::new_filled(12, 6, BIOME_UNASSIGNED);
+        bm.set(3, 2, 1);
+        bm.set(4, 5, 1);
+        bm.set(11, 0, 1);
+        assert_eq!(free_column_spans(&bm, 0, 6), vec![(0, 3), (5, 11)]);
+        // 行范围钳制到地图内：第 5 行的格子只在范围包含它时才占列
+        assert_eq!(free_column_spans(&bm, 1, 5), vec![(0, 3), (4, 12)]);
+        assert_eq!(free_column_spans(&bm, -10, 100), vec![(0, 3), (5, 11)]);
+        assert_eq!(free_column_spans(&bm, 4, 4), vec![(0, 12)], "空行范围时每列都空白");
+    }
+
+    #[test]
+    fn subtract_zones_handles_overlapping_and_zero_width_zones() {
+        let spans = [(0, 100), (150, 200)];
+        assert_eq!(subtract_zones(&spans, &[(20, 30)], 5), vec![(0, 15), (35, 100), (150, 200)]);
+        // 相互重叠的区域合并扣除，不留下碎片
+        assert_eq!(subtract_zones(&spans, &[(20, 40), (30, 60)], 0), vec![(0, 20), (60, 100), (150, 200)]);
+        // 跨越两个区段的区域同时截短两侧
+        assert_eq!(subtract_zones(&spans, &[(90, 160)], 0), vec![(0, 90), (160, 200)]);
+        // 零宽区域：无间距时不占列，有间距时按间距扣除
+        assert_eq!(subtract_zones(&spans, &[(50, 50)], 0), spans.to_vec());
+        assert_eq!(subtract_zones(&spans, &[(50, 50)], 3), vec![(0, 47), (53, 100), (150, 200)]);
+        // 反向区域同样忽略，不产生重叠区段
+        assert_eq!(subtract_zones(&spans, &[(60, 40)], 0), spans.to_vec());
+        // 覆盖整个区段时该区段消失
+        assert_eq!(subtract_zones(&spans, &[(140, 210)], 0), vec![(0, 100)]);
+    }
+
+    #[test]
+    fn pack_fills_the_widest_bin_first() {
+        let bins: [Span; 2] = [(0, 50), (100, 200)];
+        let (assignment, placed) = pack(&bins, &[30, 40, 20], 10);
+        assert_eq!(placed, 3);
+        // 容量含末尾间距：窄段 60、宽段 110。40 → 宽段（剩 60）；30 → 剩余相同，取靠左的窄段；20 → 宽段
+        assert_eq!(assignment, vec![vec![0], vec![1, 2]]);
+
+        let (assignment, placed) = pack(&bins, &[60, 60, 60], 0);
+        assert_eq!(placed, 1, "只有宽段放得下一个 60");
+        assert_eq!(assignment, vec![vec![], vec![0]]);
+        assert_eq!(pack(&bins, &[], 0).1, 0);
+    }
+
+    fn assert_valid(spans: &[Span], placements: &[Span], spacing: i32) {
+        for &(x, w) in placements {
+            assert!(spans.iter().any(|&(s, e)| x >= s && x + w <= e), "{:?} 不在任何区段内", (x, w));
+        }
+        for pair in placements.windows(2) {
+            let ((x0, w0), (x1, _)) = (pair[0], pair[1]);
+            assert!(x1 >= x0 + w0 + spacing, "{:?} 与 {:?} 间距不足 {spacing}", pair[0], pair[1]);
+        }
+    }
+
+    #[test]
+    fn allocate_shrinks_to_min_width_to_place_every_zone() {
+        let spans = [(0, 100), (105, 160)];
+        // 原宽度 3×60 放不下；缩到 20 后消解间距（第二段从 110 开始）仍能放下 3 个
+        let mut rng = StdRng::seed_from_u64(1);
+        let placements = allocate(&spans, &[60, 60, 60], 20, 10, &mut rng);
+        assert_eq!(placements.len(), 3, "{placements:?}");
+        assert_valid(&spans, &placements, 10);
+        assert!(placements.iter().any(|&(_, w)| w == 60), "能保持原宽度的区域不应被缩小: {placements:?}");
+
+        let again = allocate(&spans, &[60, 60, 60], 20, 10, &mut StdRng::seed_from_u64(1));
+        assert_eq!(placements, again, "同一种子结果应一致");
+
+        // 连最小宽度都放不满时放尽可能多（20 + 10 + 20 = 50）
+        let placements = allocate(&[(0, 50)], &[30, 30, 30], 20, 10, &mut rng);
+        assert_eq!(placements.len(), 2);
+        assert_valid(&[(0, 50)], &placements, 10);
+
+        assert!(allocate(&[], &[10], 5, 0, &mut rng).is_empty());
+        assert!(allocate(&spans, &[], 5, 0, &mut rng).is_empty());
+        // 非正宽度按 1 处理
+        let placements = allocate(&[(0, 3)], &[0, -4], 0, 1, &mut rng);
+        assert_eq!(placements.iter().map(|&(_, w)| w).collect::<Vec<_>>(), [1, 1]);
+        assert_valid(&[(0, 3)], &placements, 1);
+    }
+}
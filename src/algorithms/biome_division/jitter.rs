@@ -0,0 +1,128 @@
+//! 边界抖动步骤（后处理）
+//!
+//! 比噪声遮罩更轻量的直边处理：对指定环境对之间的竖直交界，逐行把交界点左右平移
+//! 不超过 `jitter_amplitude` 格，得到波浪形边缘。
+//!
+//! 每个环境对的两种朝向（A|B 与 B|A）各用一条有界随机游走作为逐行位移，
+//! 相邻行位移最多相差 1 格，因此边缘连续起伏而不是逐行噪点。
+//! 平移只改写交界另一侧的同一段连续格子，且最多占用该段的一半（两侧交界同时侵入时
+//! 仍至少保留 1 格），环境保持连通。
+
+use rand::Rng;
+
+use crate::core::biome::BiomeId;
+use crate::generation::algorithm::RuntimeContext;
+use crate::generation::error::GenerationError;
+
+use super::BiomeDivisionAlgorithm;
+
+pub fn execute(algo: &BiomeDivisionAlgorithm, ctx: &mut RuntimeContext) -> Result<(), GenerationError> {
+    let amplitude = algo.params.jitter_amplitude as i32;
+    if amplitude == 0 {
+        return Ok(());
+    }
+
+    // (左侧环境, 右侧环境)，每个环境对展开为两种朝向
+    let mut sides: Vec<(BiomeId, BiomeId)> = Vec::new();
+    for pair in algo.params.jitter_pairs.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let (a, b) = pair.split_once(':')
+            .ok_or_else(|| GenerationError::invalid_param("jitter_pairs", pair, "环境对格式应为 a:b"))?;
+        let lookup = |key: &str| {
+            let key = key.trim();
+            algo.get_biome_id(key)
+                .ok_or_else(|| GenerationError::invalid_param("jitter_pairs", key, "未知的环境 key"))
+        };
+        let (a, b) = (lookup(a)?, lookup(b)?);
+        if a == b {
+            return Err(GenerationError::invalid_param("jitter_pairs", pair, "环境对两侧相同"));
+        }
+        sides.push((a, b));
+        sides.push((b, a));
+    }
+    if sides.is_empty() {
+        return Ok(());
+    }
+
+    let bm = ctx.biome_map.as_mut().ok_or(GenerationError::MapNotInitialized)?;
+    let w = bm.width as usize;
+    let h = bm.height as usize;
+
+    // 逐行位移：每种朝向一条有界随机游走
+    let walks: Vec<Vec<i32>> = sides.iter()
+        .map(|_| {
+            let mut offset = ctx.rng.gen_range(-amplitude..=amplitude);
+            (0..h)
+                .map(|_| {
+                    offset = (offset + ctx.rng.gen_range(-1..=1)).clamp(-amplitude, amplitude);
+                    offset
+                })
+                .collect()
+        })
+        .collect();
+
+    let mut original = vec![0; w];
+    for (y, row) in bm.data_mut().chunks_mut(w).enumerate() {
+        original.copy_from_slice(row);
+        for x in 1..w {
+            let (left, right) = (original[x - 1], original[x]);
+            let Some(side) = sides.iter().position(|&s| s == (left, right)) else {
+                continue;
+            };
+            let shift = walks[side][y];
+            if shift > 0 {
+                // 交界右移：左侧环境侵入右侧连续段
+                let run = original[x..].iter().take_while(|&&b| b == right).count();
+                let n = (shift as usize).min((run - 1) / 2);
+                row[x..x + n].fill(left);
+            } else if shift < 0 {
+                // 交界左移：右侧环境侵入左侧连续段
+                let run = original[..x].iter().rev().take_while(|&&b| b == left).count();
+                let n = (shift.unsigned_abs() as usize).min((run - 1) / 2);
+                row[x - n..x].fill(right);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::generation::headless::HeadlessContext;
+
+    /// 以 `amplitude` 生成 small 世界；`run_step = false` 时禁用边界抖动步骤
+    fn generate(context: &HeadlessContext, seed: u64, amplitude: u32, run_step: bool) -> Vec<crate::core::biome::BiomeId> {
+        let pipeline = context
+            .generate(seed, |p| {
+                p.set_step_logging(false);
+                let algo = p.algorithm_by_id_mut("biome_division").unwrap();
+                let mut params = algo.get_params();
+                params["jitter_amplitude"] = serde_json::json!(amplitude);
+                algo.set_params(&params);
+                // 环境判定是第一个阶段，子步骤下标即 flat_index
+                let step = algo.meta().steps.iter().position(|s| s.name == "边界抖动").unwrap();
+                p.set_step_enabled(step, run_step);
+            })
+            .unwrap();
+        pipeline.biome_map().unwrap().data().to_vec()
+    }
+
+    #[test]
+    fn zero_amplitude_matches_the_unjittered_world() {
+        let context = HeadlessContext::load("small", None).unwrap();
+        for seed in [1, 42] {
+            assert!(
+                generate(&context, seed, 0, true) == generate(&context, seed, 0, false),
+                "seed {seed}: 幅度为 0 时应与跳过抖动步骤逐格相同",
+            );
+        }
+    }
+
+    #[test]
+    fn nonzero_amplitude_is_deterministic_per_seed() {
+        let context = HeadlessContext::load("small", None).unwrap();
+        let jittered = generate(&context, 42, 4, true);
+        assert!(jittered == generate(&context, 42, 4, true), "同一种子的抖动应逐格相同");
+        assert!(jittered != generate(&context, 42, 0, true), "非零幅度应改变交界");
+    }
+}
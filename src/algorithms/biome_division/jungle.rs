@@ -1,35 +1,43 @@
 //! 丛林生成步骤
 
-use crate::core::biome::BIOME_UNASSIGNED;
-use crate::core::geometry::{self, Ellipse, Rect, Shape, ShapeCombine, ShapeParams, ShapeRecord};
+use crate::core::biome::{pick_variant, BIOME_UNASSIGNED};
+use crate::core::geometry::{self, Ellipse, Rect, Shape, ShapeCombine, ShapeParams, ShapeRecord, Trapezoid};
+use crate::core::layer::fraction_to_row;
 use crate::generation::algorithm::RuntimeContext;
+use crate::generation::error::GenerationError;
 use rand::Rng;
 
+use super::interval;
 use super::BiomeDivisionAlgorithm;
 
-pub fn execute(algo: &BiomeDivisionAlgorithm, ctx: &mut RuntimeContext) -> Result<(), String> {
-    let jungle_id = algo.get_biome_id("jungle")
-        .ok_or("未找到 jungle 环境定义")?;
+pub fn execute(algo: &BiomeDivisionAlgorithm, ctx: &mut RuntimeContext) -> Result<(), GenerationError> {
+    let base_jungle_id = algo.require_biome("jungle")?;
     
-    let bm = ctx.biome_map.as_mut().ok_or("需先执行海洋生成")?;
+    let bm = ctx.biome_map.as_mut().ok_or(GenerationError::MapNotInitialized)?;
     let w = bm.width as i32;
     let h = bm.height as i32;
     
-    // 基于 RNG 随机选择左/右
-    let place_on_left = ctx.rng.gen_bool(0.5);
+    // 按 jungle_side 固定左/右；auto 时基于 RNG 随机选择
+    let place_on_left = match algo.params.jungle_side.as_str() {
+        "left" => true,
+        "right" => false,
+        "auto" => ctx.rng.gen_bool(0.5),
+        other => return Err(GenerationError::invalid_param("jungle_side", other, "未知的丛林位置")),
+    };
     
     // 保存到 shared 供雪原生成使用
     ctx.shared.insert("jungle_on_left".into(), Box::new(place_on_left));
     
-    // 计算森林边界（水平居中，半宽 = forest_width_ratio）
+    // 计算中心保留走廊边界（水平居中，半宽 = forest_width_ratio；与森林实际填充宽度无关）
     let forest_center = w / 2;
     let forest_half_width = (w as f64 * algo.params.forest_width_ratio) as i32;
     let forest_left = forest_center - forest_half_width;
     let forest_right = forest_center + forest_half_width;
     
-    // 计算海洋边界
-    let ocean_left_right = (w as f64 * algo.params.ocean_left_width) as i32;
-    let ocean_right_left = w - (w as f64 * algo.params.ocean_right_width) as i32;
+    // 计算海洋边界（ocean_mode 省略的一侧以世界边缘为界）
+    let (ocean_left_ratio, ocean_right_ratio) = algo.ocean_widths()?;
+    let ocean_left_right = (w as f64 * ocean_left_ratio) as i32;
+    let ocean_right_left = w - (w as f64 * ocean_right_ratio) as i32;
     
     // 计算丛林可用空间和基础中心点
     let (jungle_cx_base, available_width) = if place_on_left {
@@ -53,30 +61,77 @@ pub fn execute(algo: &BiomeDivisionAlgorithm, ctx: &mut RuntimeContext) -> Resul
     let offset = ctx.rng.gen_range(-max_offset..=max_offset);
     let jungle_cx = jungle_cx_base + offset;
     
-    // 丛林椭圆参数
+    // 丛林最大半宽
     let jungle_rx = (w as f64 * algo.params.jungle_width_ratio / 2.0) as i32;
-    let jungle_cy = h / 2;  // 椭圆中心在世界垂直中心
-    let jungle_ry = h / 2;  // 椭圆半径覆盖整个世界高度
     
     // 实际写入范围（裁剪）
-    let top_y = (h as f64 * algo.params.jungle_top_limit) as i32;
-    let bottom_y = (h as f64 * algo.params.jungle_bottom_limit) as i32;
-    
-    // 丛林椭圆 + y范围裁剪（椭圆 ∩ 矩形）
-    let ell = Ellipse::new(
-        jungle_cx as f64, jungle_cy as f64,
-        jungle_rx as f64, jungle_ry as f64,
-    );
-    let ell_params = ShapeParams::from_ellipse(&ell);
+    let top_y = fraction_to_row(h as u32, algo.params.jungle_top_limit) as i32;
+    let bottom_y = fraction_to_row(h as u32, algo.params.jungle_bottom_limit) as i32;
     let clip = Rect::new(0, top_y, w, bottom_y);
-    let shape = ell.intersect(clip);
-    geometry::fill_biome_if(&shape, bm, jungle_id, |c| c == BIOME_UNASSIGNED);
+    
+    let (shape, shape_params) = jungle_shape(algo, jungle_cx, jungle_rx, h, clip)?;
+
+    // 按权重抽取丛林变体（biome.json 中 jungle 的 variants），记录到 shared 供后续步骤与调试查看
+    let jungle_id = pick_variant(ctx.biomes, base_jungle_id, ctx.rng);
+    ctx.shared.insert("jungle_variant".into(), Box::new(jungle_id));
+    if algo.params.jungle_soft_edge {
+        geometry::fill_biome_soft_if(shape.as_ref(), bm, jungle_id, &mut ctx.rng, |c| c == BIOME_UNASSIGNED);
+    } else {
+        geometry::fill_biome_if(shape.as_ref(), bm, jungle_id, |c| c == BIOME_UNASSIGNED);
+    }
+    let bbox = shape.bounding_box();
+    interval::register_occupied(ctx.shared, (bbox.x_min, bbox.x_max));
     ctx.shape_log.push(ShapeRecord {
-        label: "丛林".into(),
-        bbox: shape.bounding_box(),
+        label: algo.biome_name(jungle_id).into(),
+        bbox,
         color: algo.biome_color(jungle_id),
-        params: ell_params,
+        params: shape_params,
     });
     
     Ok(())
 }
+
+/// 水滴形顶部的半宽（相对最大半宽）
+const TEARDROP_TOP_RATIO: f64 = 0.35;
+/// 水滴形底部椭圆的竖直半径（相对裁剪范围高度）
+const TEARDROP_BULB_RATIO: f64 = 0.35;
+
+/// 按 `jungle_shape` 构造丛林形状（已与 `clip` 的 y 范围求交）
+fn jungle_shape(
+    algo: &BiomeDivisionAlgorithm,
+    cx: i32,
+    rx: i32,
+    h: i32,
+    clip: Rect,
+) -> Result<(Box<dyn Shape>, ShapeParams), GenerationError> {
+    match algo.params.jungle_shape.as_str() {
+        "ellipse" => {
+            // 竖直椭圆：中心在世界垂直中心，半径覆盖整个世界高度，再裁剪到 y 范围
+            let ell = Ellipse::new(cx as f64, (h / 2) as f64, rx as f64, (h / 2) as f64);
+            let params = ShapeParams::from_ellipse(&ell);
+            Ok((Box::new(ell.intersect(clip)), params))
+        }
+        "teardrop" => {
+            // 底部椭圆占据裁剪范围的下部，顶部梯形从窄口逐渐放宽到椭圆中线的最大宽度
+            let span = (clip.y1 - clip.y0) as f64;
+            let bulb_ry = (span * TEARDROP_BULB_RATIO).max(1.0);
+            let bulb_cy = clip.y1 as f64 - bulb_ry;
+            let bulb = Ellipse::new(cx as f64, bulb_cy, rx as f64, bulb_ry);
+            let neck = Trapezoid::from_center(
+                cx as f64,
+                clip.y0,
+                bulb_cy.round() as i32,
+                rx as f64 * TEARDROP_TOP_RATIO,
+                rx as f64,
+            );
+            let params = ShapeParams::Composite {
+                description: format!(
+                    "水滴：梯形 (上半宽 {:.0} → {rx}) ∪ 椭圆 (cy={bulb_cy:.0}, ry={bulb_ry:.0})",
+                    rx as f64 * TEARDROP_TOP_RATIO,
+                ),
+            };
+            Ok((Box::new(neck.union(bulb).intersect(clip)), params))
+        }
+        other => Err(GenerationError::invalid_param("jungle_shape", other, "未知的丛林形状")),
+    }
+}
@@ -0,0 +1,78 @@
+//! 对称镜像步骤（后处理）
+//!
+//! 把世界左半边逐行镜像到右半边（`x` 与 `w - 1 - x` 相同），得到左右对称的布局：
+//!
+//! - `mirror_mode = full`：整幅世界对称
+//! - `mirror_mode = layers`：只镜像 `mirror_layers` 列出的层级所占的行，
+//!   例如默认的 `cavern,hell` 让深层探索左右平衡，地表仍按种子自由生成
+//!
+//! 镜像在所有放置与后处理之后执行，右半边原有的环境被整体覆盖；
+//! 行范围取自层级定义，与层级边界逐行对齐。
+
+use crate::generation::algorithm::RuntimeContext;
+use crate::generation::error::GenerationError;
+
+use super::BiomeDivisionAlgorithm;
+
+pub fn execute(algo: &BiomeDivisionAlgorithm, ctx: &mut RuntimeContext) -> Result<(), GenerationError> {
+    let bm = ctx.biome_map.as_mut().ok_or(GenerationError::MapNotInitialized)?;
+    let h = bm.height;
+
+    // 参与镜像的行区间 [start, end)
+    let ranges: Vec<(u32, u32)> = match algo.params.mirror_mode.as_str() {
+        "none" => return Ok(()),
+        "full" => vec![(0, h)],
+        "layers" => algo.params.mirror_layers
+            .split(',')
+            .map(str::trim)
+            .filter(|k| !k.is_empty())
+            .map(|key| {
+                algo.layer_definitions.iter()
+                    .find(|l| l.key == key)
+                    .map(|l| l.bounds_for_height(h))
+                    .ok_or_else(|| GenerationError::invalid_param("mirror_layers", key, "未知的层级 key"))
+            })
+            .collect::<Result<_, _>>()?,
+        other => return Err(GenerationError::invalid_param("mirror_mode", other, "未知的镜像模式")),
+    };
+
+    let w = bm.width as usize;
+    let data = bm.data_mut();
+    for (start, end) in ranges {
+        for row in data[start as usize * w..end.min(h) as usize * w].chunks_mut(w) {
+            let (left, right) = row.split_at_mut(w / 2);
+            // 奇数宽度时中间一列属于右半，保持不变
+            for (dst, src) in right.iter_mut().rev().zip(left.iter()) {
+                *dst = *src;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::generation::headless::HeadlessContext;
+
+    #[test]
+    fn layers_mode_mirrors_only_the_listed_layers() {
+        let context = HeadlessContext::load("small", None).unwrap();
+        let pipeline = context.generate(7, |p| {
+            p.set_step_logging(false);
+            p.algorithm_by_id_mut("biome_division").unwrap().set_params(&serde_json::json!({
+                "mirror_mode": "layers",
+                "mirror_layers": "cavern,hell",
+            }));
+        }).unwrap();
+        let bm = pipeline.biome_map().unwrap();
+        let symmetric = |y: u32| (0..bm.width).all(|x| bm.get(x, y) == bm.get(bm.width - 1 - x, y));
+
+        let layer = |key: &str| context.profile.layers.iter().find(|l| l.key == key).unwrap().bounds_for_height(bm.height);
+        let (cavern_start, _) = layer("cavern");
+        let (_, hell_end) = layer("hell");
+        assert!((cavern_start..hell_end).all(symmetric), "洞穴层与地狱层应左右对称");
+
+        let (surface_start, surface_end) = layer("surface");
+        assert!(!(surface_start..surface_end).all(symmetric), "地表层不应被镜像");
+    }
+}
@@ -5,24 +5,32 @@
 
 use crate::core::biome::{BiomeDefinition, BiomeId};
 use crate::core::layer::LayerDefinition;
+use crate::core::log;
 use crate::generation::algorithm::{
-    ParamDef, ParamType, PhaseAlgorithm, PhaseMeta, RuntimeContext, StepMeta,
+    OverwritePolicy, ParamDef, ParamType, PhaseAlgorithm, PhaseMeta, RuntimeContext, StepMeta,
 };
+use crate::generation::error::GenerationError;
 
 // 模块声明
 mod params;
+mod interval;
 mod space_hell;
 mod ocean;
 mod forest;
 mod jungle;
 mod snow;
 mod desert;
-mod crimson;
+mod pyramid;
+mod evil;
 mod forest_fill;
 mod stone_fill;
+mod smoothing;
+mod jitter;
+mod mirror;
 
 // 导出参数
 pub use params::BiomeDivisionParams;
+pub use forest_fill::{DiffusionTrace, DIFFUSION_TRACE_KEY};
 
 // ═══════════════════════════════════════════════════════════
 // 辅助函数
@@ -59,7 +67,38 @@ impl BiomeDivisionAlgorithm {
     pub fn get_biome_id(&self, key: &str) -> Option<BiomeId> {
         biome_id_by_key(&self.biome_definitions, key)
     }
+
+    /// 同 [`get_biome_id`](Self::get_biome_id)，环境缺失时返回 [`GenerationError::MissingBiome`]
+    pub fn require_biome(&self, key: &str) -> Result<BiomeId, GenerationError> {
+        self.get_biome_id(key)
+            .ok_or_else(|| GenerationError::MissingBiome(key.to_string()))
+    }
     
+    /// 出生点安全区的列区间（世界中心 ±`spawn_safe_radius`）；半径为 0 时返回 `None`
+    pub fn spawn_safe_zone(&self, width: i32) -> Option<interval::Span> {
+        let radius = (width as f64 * self.params.spawn_safe_radius) as i32;
+        (radius > 0).then(|| (width / 2 - radius, width / 2 + radius))
+    }
+
+    /// 不同环境之间的全局最小间距（像素列）；`global_min_gap` 为 0 时返回 `None`
+    pub fn global_gap(&self, width: i32) -> Option<i32> {
+        let gap = (width as f64 * self.params.global_min_gap) as i32;
+        (gap > 0).then_some(gap)
+    }
+    
+    /// 按 `ocean_mode` 生效的左右海洋宽度（占世界宽度的比例）；被省略的一侧为 0，
+    /// 相邻的丛林 / 雪原可用空间因此一直延伸到世界边缘
+    pub fn ocean_widths(&self) -> Result<(f64, f64), GenerationError> {
+        let p = &self.params;
+        match p.ocean_mode.as_str() {
+            "both" => Ok((p.ocean_left_width, p.ocean_right_width)),
+            "left" => Ok((p.ocean_left_width, 0.0)),
+            "right" => Ok((0.0, p.ocean_right_width)),
+            "none" => Ok((0.0, 0.0)),
+            other => Err(GenerationError::invalid_param("ocean_mode", other, "未知的海洋模式")),
+        }
+    }
+
     /// 根据 biome ID 获取 overlay_color
     pub fn biome_color(&self, id: BiomeId) -> [u8; 4] {
         self.biome_definitions.iter()
@@ -68,43 +107,67 @@ impl BiomeDivisionAlgorithm {
             .unwrap_or([128, 128, 128, 120])
     }
 
+    /// 根据 biome ID 获取显示名称
+    pub fn biome_name(&self, id: BiomeId) -> &str {
+        self.biome_definitions.iter()
+            .find(|b| b.id == id)
+            .map(|b| b.name.as_str())
+            .unwrap_or("未知环境")
+    }
+
     // ── 各子步骤实现（调用对应模块） ────────────────────
 
-    fn step_space_hell(&self, ctx: &mut RuntimeContext) -> Result<(), String> {
+    fn step_space_hell(&self, ctx: &mut RuntimeContext) -> Result<(), GenerationError> {
         space_hell::execute(self, ctx)
     }
 
-    fn step_ocean(&self, ctx: &mut RuntimeContext) -> Result<(), String> {
+    fn step_ocean(&self, ctx: &mut RuntimeContext) -> Result<(), GenerationError> {
         ocean::execute(self, ctx)
     }
 
-    fn step_forest(&self, ctx: &mut RuntimeContext) -> Result<(), String> {
+    fn step_forest(&self, ctx: &mut RuntimeContext) -> Result<(), GenerationError> {
         forest::execute(self, ctx)
     }
 
-    fn step_jungle(&self, ctx: &mut RuntimeContext) -> Result<(), String> {
+    fn step_jungle(&self, ctx: &mut RuntimeContext) -> Result<(), GenerationError> {
         jungle::execute(self, ctx)
     }
 
-    fn step_snow(&self, ctx: &mut RuntimeContext) -> Result<(), String> {
+    fn step_snow(&self, ctx: &mut RuntimeContext) -> Result<(), GenerationError> {
         snow::execute(self, ctx)
     }
 
-    fn step_desert(&self, ctx: &mut RuntimeContext) -> Result<(), String> {
+    fn step_desert(&self, ctx: &mut RuntimeContext) -> Result<(), GenerationError> {
         desert::execute(self, ctx)
     }
 
-    fn step_crimson(&self, ctx: &mut RuntimeContext) -> Result<(), String> {
-        crimson::execute(self, ctx)
+    fn step_evil(&self, ctx: &mut RuntimeContext) -> Result<(), GenerationError> {
+        evil::execute(self, ctx)
     }
 
-    fn step_forest_fill(&self, ctx: &mut RuntimeContext) -> Result<(), String> {
+    fn step_forest_fill(&self, ctx: &mut RuntimeContext) -> Result<(), GenerationError> {
         forest_fill::execute(self, ctx)
     }
 
-    fn step_stone_fill(&self, ctx: &mut RuntimeContext) -> Result<(), String> {
+    fn step_stone_fill(&self, ctx: &mut RuntimeContext) -> Result<(), GenerationError> {
         stone_fill::execute(self, ctx)
     }
+
+    fn step_smoothing(&self, ctx: &mut RuntimeContext) -> Result<(), GenerationError> {
+        smoothing::execute(self, ctx)
+    }
+
+    fn step_jitter(&self, ctx: &mut RuntimeContext) -> Result<(), GenerationError> {
+        jitter::execute(self, ctx)
+    }
+
+    fn step_mirror(&self, ctx: &mut RuntimeContext) -> Result<(), GenerationError> {
+        mirror::execute(self, ctx)
+    }
+
+    fn step_pyramid(&self, ctx: &mut RuntimeContext) -> Result<(), GenerationError> {
+        pyramid::execute(self, ctx)
+    }
 }
 
 // ═══════════════════════════════════════════════════════════
@@ -116,61 +179,111 @@ impl PhaseAlgorithm for BiomeDivisionAlgorithm {
         PhaseMeta {
             id: "biome_division".to_string(),
             name: "环境判定".to_string(),
-            description: "将世界划分为不同的环境区域（海洋、森林、丛林、雪原、沙漠、猩红）".to_string(),
+            description: "将世界划分为不同的环境区域（海洋、森林、丛林、雪原、沙漠、猩红/腐化）".to_string(),
             steps: vec![
                 StepMeta {
                     display_index: 1,
                     name: "太空/地狱填充".to_string(),
                     description: "初始化世界并填充太空层(0-10%)和地狱层(85-100%)".to_string(),
                     doc_url: None,
+                    scatter: false,
+                    biome: None,
                 },
                 StepMeta {
                     display_index: 2,
                     name: "海洋生成".to_string(),
                     description: "在世界两侧生成海洋区域".to_string(),
                     doc_url: None,
+                    scatter: false,
+                    biome: Some("ocean".to_string()),
                 },
                 StepMeta {
                     display_index: 3,
                     name: "森林生成".to_string(),
                     description: "在世界中心生成森林".to_string(),
                     doc_url: None,
+                    scatter: false,
+                    biome: None,
                 },
                 StepMeta {
                     display_index: 4,
                     name: "丛林生成".to_string(),
                     description: "在世界一侧生成丛林".to_string(),
                     doc_url: None,
+                    scatter: true,
+                    biome: Some("jungle".to_string()),
                 },
                 StepMeta {
                     display_index: 5,
                     name: "雪原生成".to_string(),
                     description: "在世界另一侧生成雪原".to_string(),
                     doc_url: None,
+                    scatter: true,
+                    biome: Some("snow".to_string()),
                 },
                 StepMeta {
                     display_index: 6,
                     name: "沙漠生成".to_string(),
-                    description: "在世界空白区域随机生成沙漠地表".to_string(),
+                    description: "在世界空白区域随机生成沙漠地表与深层真沙漠".to_string(),
                     doc_url: None,
+                    scatter: true,
+                    biome: Some("desert".to_string()),
                 },
                 StepMeta {
                     display_index: 7,
-                    name: "猩红生成".to_string(),
-                    description: "在世界空白区域随机生成猩红".to_string(),
+                    name: "邪恶环境生成".to_string(),
+                    description: "在世界空白区域随机生成猩红/腐化".to_string(),
                     doc_url: None,
+                    scatter: true,
+                    biome: Some("crimson".to_string()),
                 },
                 StepMeta {
                     display_index: 8,
                     name: "森林填充".to_string(),
-                    description: "沙漠/猩红扩散 + 剩余空白填充为森林".to_string(),
+                    description: "沙漠/邪恶环境扩散 + 剩余空白填充为森林".to_string(),
                     doc_url: None,
+                    scatter: false,
+                    biome: None,
                 },
                 StepMeta {
                     display_index: 9,
                     name: "地块填充".to_string(),
-                    description: "将所有剩余空白区域填充为岩石地块".to_string(),
+                    description: "将所有剩余空白区域填充为岩石地块，或由最近的环境扩张吸收".to_string(),
+                    doc_url: None,
+                    scatter: false,
+                    biome: None,
+                },
+                StepMeta {
+                    display_index: 10,
+                    name: "边界平滑".to_string(),
+                    description: "多数表决元胞自动机磨圆环境交界处的锯齿（迭代次数为 0 时跳过）".to_string(),
+                    doc_url: None,
+                    scatter: false,
+                    biome: None,
+                },
+                StepMeta {
+                    display_index: 11,
+                    name: "边界抖动".to_string(),
+                    description: "按种子逐行左右平移指定环境对之间的竖直交界，得到波浪形边缘（幅度为 0 时跳过）".to_string(),
                     doc_url: None,
+                    scatter: false,
+                    biome: None,
+                },
+                StepMeta {
+                    display_index: 12,
+                    name: "对称镜像".to_string(),
+                    description: "把左半边镜像到右半边：整幅世界或仅指定层级（镜像模式为 none 时跳过）".to_string(),
+                    doc_url: None,
+                    scatter: false,
+                    biome: None,
+                },
+                StepMeta {
+                    display_index: 13,
+                    name: "金字塔标记".to_string(),
+                    description: "每个真沙漠按概率在地表沙漠顶部标记金字塔（概率为 0 时跳过）".to_string(),
+                    doc_url: None,
+                    scatter: false,
+                    biome: None,
                 },
             ],
             params: {
@@ -192,6 +305,16 @@ impl PhaseAlgorithm for BiomeDivisionAlgorithm {
                     default: serde_json::json!(0.05),
                     group: Some("海洋生成".to_string()),
                 },
+                ParamDef {
+                    key: "ocean_mode".to_string(),
+                    name: "海洋模式".to_string(),
+                    description: "both=两侧海洋, left=仅左侧, right=仅右侧, none=无海洋；省略的一侧由相邻环境一直延伸到世界边缘".to_string(),
+                    param_type: ParamType::Enum {
+                        options: vec!["both".to_string(), "left".to_string(), "right".to_string(), "none".to_string()],
+                    },
+                    default: serde_json::json!("both"),
+                    group: Some("海洋生成".to_string()),
+                },
                 ParamDef {
                     key: "ocean_top_limit".to_string(),
                     name: "海洋上边界".to_string(),
@@ -208,14 +331,66 @@ impl PhaseAlgorithm for BiomeDivisionAlgorithm {
                     default: serde_json::json!(d.ocean_bottom_limit),
                     group: Some("海洋生成".to_string()),
                 },
+                ParamDef {
+                    key: "ocean_shape".to_string(),
+                    name: "海洋形状".to_string(),
+                    description: "rect=矩形（竖直墙壁）, trapezoid=斜坡海底, ellipse=近岸四分之一椭圆（平滑抬升）".to_string(),
+                    param_type: ParamType::Enum {
+                        options: vec!["rect".to_string(), "trapezoid".to_string(), "ellipse".to_string()],
+                    },
+                    default: serde_json::json!("rect"),
+                    group: Some("海洋生成".to_string()),
+                },
+                ParamDef {
+                    key: "ocean_beach_slope".to_string(),
+                    name: "海滩坡度".to_string(),
+                    description: "海洋底部比顶部窄的比例（0 = 竖直，1 = 从海滩一直斜到世界边缘）；让出的区域由相邻环境填充".to_string(),
+                    param_type: ParamType::Float { min: 0.0, max: 1.0 },
+                    default: serde_json::json!(0.5),
+                    group: Some("海洋生成".to_string()),
+                },
                 ParamDef {
                     key: "forest_width_ratio".to_string(),
-                    name: "森林宽度比例".to_string(),
-                    description: "出生点森林的水平半宽（从中心向两侧延伸，相对世界宽度）".to_string(),
+                    name: "中心走廊半宽".to_string(),
+                    description: "世界中心保留走廊的水平半宽（相对世界宽度）：丛林与雪原只在走廊两侧放置，不影响森林实际填充的宽度".to_string(),
                     param_type: ParamType::Float { min: 0.0, max: 1.0 },
                     default: serde_json::json!(0.15),
                     group: Some("森林生成".to_string()),
                 },
+                ParamDef {
+                    key: "forest_surface_extent".to_string(),
+                    name: "森林填充半宽".to_string(),
+                    description: "中心森林实际填充的水平半宽（从中心向两侧延伸，相对世界宽度），可宽于或窄于中心走廊".to_string(),
+                    param_type: ParamType::Float { min: 0.0, max: 1.0 },
+                    default: serde_json::json!(0.05),
+                    group: Some("森林生成".to_string()),
+                },
+                ParamDef {
+                    key: "spawn_safe_radius".to_string(),
+                    name: "出生点安全区半宽".to_string(),
+                    description: "沙漠与邪恶环境不会放在世界中心 ±此宽度内，已放不下的位置向外侧顺延（相对世界宽度，0=关闭）".to_string(),
+                    param_type: ParamType::Float { min: 0.0, max: 0.5 },
+                    default: serde_json::json!(0.0),
+                    group: Some("森林生成".to_string()),
+                },
+                ParamDef {
+                    key: "global_min_gap".to_string(),
+                    name: "环境间全局最小间距".to_string(),
+                    description: "沙漠与邪恶环境与先放置的任意环境（丛林、雪原、沙漠、邪恶环境，不限类型）至少相隔此距离；同类间距仍由各自参数控制（相对世界宽度，0=关闭）".to_string(),
+                    param_type: ParamType::Float { min: 0.0, max: 0.2 },
+                    default: serde_json::json!(0.0),
+                    group: Some("环境间距".to_string()),
+                },
+                ParamDef {
+                    key: "jungle_side".to_string(),
+                    name: "丛林位置".to_string(),
+                    description: "auto=随机左右, left=固定左侧, right=固定右侧（雪原总在另一侧）".to_string(),
+                    param_type: ParamType::Enum {
+                        options: vec!["auto".to_string(), "left".to_string(), "right".to_string()],
+                    },
+                    default: serde_json::json!("auto"),
+                    group: Some("丛林生成".to_string()),
+                },
                 ParamDef {
                     key: "jungle_width_ratio".to_string(),
                     name: "丛林宽度比例".to_string(),
@@ -248,6 +423,24 @@ impl PhaseAlgorithm for BiomeDivisionAlgorithm {
                     default: serde_json::json!(0.20),
                     group: Some("丛林生成".to_string()),
                 },
+                ParamDef {
+                    key: "jungle_shape".to_string(),
+                    name: "丛林形状".to_string(),
+                    description: "ellipse=竖直椭圆, teardrop=水滴（顶部收窄的梯形 ∪ 底部椭圆，上窄下宽，更接近泰拉瑞亚的丛林轮廓）".to_string(),
+                    param_type: ParamType::Enum {
+                        options: vec!["ellipse".to_string(), "teardrop".to_string()],
+                    },
+                    default: serde_json::json!("ellipse"),
+                    group: Some("丛林生成".to_string()),
+                },
+                ParamDef {
+                    key: "jungle_soft_edge".to_string(),
+                    name: "软边".to_string(),
+                    description: "丛林椭圆边界上的格子按子像素覆盖率随机填充，边缘抖散到相邻环境（消除阶梯状锯齿）".to_string(),
+                    param_type: ParamType::Bool,
+                    default: serde_json::json!(false),
+                    group: Some("丛林生成".to_string()),
+                },
                 ParamDef {
                     key: "snow_top_width_ratio".to_string(),
                     name: "雪原上边宽度".to_string(),
@@ -296,6 +489,14 @@ impl PhaseAlgorithm for BiomeDivisionAlgorithm {
                     default: serde_json::json!(0.12),
                     group: Some("雪原生成".to_string()),
                 },
+                ParamDef {
+                    key: "snow_depth_variation".to_string(),
+                    name: "底边起伏幅度".to_string(),
+                    description: "雪原底边逐列上下起伏的幅度（相对世界高度，0=平底，0.05=±5%）；按种子生成平滑的深度剖面，最深不进入地狱层".to_string(),
+                    param_type: ParamType::Float { min: 0.0, max: 0.2 },
+                    default: serde_json::json!(0.0),
+                    group: Some("雪原生成".to_string()),
+                },
                 ParamDef {
                     key: "desert_surface_count".to_string(),
                     name: "沙漠地表数量".to_string(),
@@ -347,7 +548,7 @@ impl PhaseAlgorithm for BiomeDivisionAlgorithm {
                 ParamDef {
                     key: "desert_true_count".to_string(),
                     name: "真沙漠数量".to_string(),
-                    description: "生成的真沙漠数量，选择离中心最近的地表沙漠".to_string(),
+                    description: "生成的真沙漠数量，位置由真沙漠选址规则决定（默认离中心最近）".to_string(),
                     param_type: ParamType::Int { min: 0, max: 5 },
                     default: serde_json::json!(1),
                     group: Some("沙漠生成".to_string()),
@@ -377,67 +578,179 @@ impl PhaseAlgorithm for BiomeDivisionAlgorithm {
                     group: Some("沙漠生成".to_string()),
                 },
                 ParamDef {
-                    key: "crimson_count".to_string(),
-                    name: "猩红数量".to_string(),
-                    description: "生成的猩红区域数量".to_string(),
+                    key: "desert_true_rule".to_string(),
+                    name: "真沙漠选址规则".to_string(),
+                    description: "center=离世界中心最近, largest=最宽的空白区段, random=随机区段与位置, left / right=靠近该侧世界边缘".to_string(),
+                    param_type: ParamType::Enum {
+                        options: ["center", "largest", "random", "left", "right"].map(String::from).to_vec(),
+                    },
+                    default: serde_json::json!("center"),
+                    group: Some("沙漠生成".to_string()),
+                },
+                ParamDef {
+                    key: "desert_soft_edge".to_string(),
+                    name: "真沙漠软边".to_string(),
+                    description: "真沙漠椭圆边界上的格子按子像素覆盖率随机填充，边缘抖散到相邻环境（消除阶梯状锯齿）".to_string(),
+                    param_type: ParamType::Bool,
+                    default: serde_json::json!(false),
+                    group: Some("沙漠生成".to_string()),
+                },
+                ParamDef {
+                    key: "pyramid_chance".to_string(),
+                    name: "金字塔概率".to_string(),
+                    description: "每个真沙漠按种子抽签标记金字塔的概率（0 = 不生成，默认）".to_string(),
+                    param_type: ParamType::Float { min: 0.0, max: 1.0 },
+                    default: serde_json::json!(0.0),
+                    group: Some("沙漠生成".to_string()),
+                },
+                ParamDef {
+                    key: "pyramid_size".to_string(),
+                    name: "金字塔大小".to_string(),
+                    description: "金字塔底边宽度占真沙漠地表宽度的比例；顶点位于地表上边界，斜面 45° 向下展开".to_string(),
+                    param_type: ParamType::Float { min: 0.0, max: 1.0 },
+                    default: serde_json::json!(0.5),
+                    group: Some("沙漠生成".to_string()),
+                },
+                ParamDef {
+                    key: "evil_type".to_string(),
+                    name: "邪恶环境类型".to_string(),
+                    description: "crimson=猩红, corruption=腐化, random=每个区域独立随机（可混合出现）".to_string(),
+                    param_type: ParamType::Enum {
+                        options: vec!["crimson".to_string(), "corruption".to_string(), "random".to_string()],
+                    },
+                    default: serde_json::json!("crimson"),
+                    group: Some("邪恶环境生成".to_string()),
+                },
+                ParamDef {
+                    key: "evil_count".to_string(),
+                    name: "邪恶环境数量".to_string(),
+                    description: "生成的邪恶环境区域数量（大世界可适当调高）".to_string(),
                     param_type: ParamType::Int { min: 0, max: 10 },
                     default: serde_json::json!(3),
-                    group: Some("猩红生成".to_string()),
+                    group: Some("邪恶环境生成".to_string()),
                 },
                 ParamDef {
-                    key: "crimson_width_min".to_string(),
-                    name: "猩红最小宽度".to_string(),
-                    description: "猩红矩形最小宽度（相对世界宽度）".to_string(),
+                    key: "evil_width_min".to_string(),
+                    name: "邪恶环境最小宽度".to_string(),
+                    description: "邪恶环境矩形最小宽度（相对世界宽度）".to_string(),
                     param_type: ParamType::Float { min: 0.0, max: 1.0 },
                     default: serde_json::json!(0.025),
-                    group: Some("猩红生成".to_string()),
+                    group: Some("邪恶环境生成".to_string()),
                 },
                 ParamDef {
-                    key: "crimson_width_max".to_string(),
-                    name: "猩红最大宽度".to_string(),
-                    description: "猩红矩形最大宽度（相对世界宽度）".to_string(),
+                    key: "evil_width_max".to_string(),
+                    name: "邪恶环境最大宽度".to_string(),
+                    description: "邪恶环境矩形最大宽度（相对世界宽度）".to_string(),
                     param_type: ParamType::Float { min: 0.0, max: 1.0 },
                     default: serde_json::json!(0.05),
-                    group: Some("猩红生成".to_string()),
+                    group: Some("邪恶环境生成".to_string()),
                 },
                 ParamDef {
-                    key: "crimson_top_limit".to_string(),
-                    name: "猩红上边界".to_string(),
-                    description: "猩红顶部边界（默认值=地表层起点）".to_string(),
+                    key: "evil_top_limit".to_string(),
+                    name: "邪恶环境上边界".to_string(),
+                    description: "邪恶环境顶部边界（默认值=地表层起点）".to_string(),
                     param_type: ParamType::Float { min: 0.0, max: 1.0 },
-                    default: serde_json::json!(d.crimson_top_limit),
-                    group: Some("猩红生成".to_string()),
+                    default: serde_json::json!(d.evil_top_limit),
+                    group: Some("邪恶环境生成".to_string()),
                 },
                 ParamDef {
-                    key: "crimson_bottom_limit".to_string(),
-                    name: "猩红下边界".to_string(),
-                    description: "猩红底部边界（默认值=地下层终点）".to_string(),
+                    key: "evil_bottom_limit".to_string(),
+                    name: "邪恶环境下边界".to_string(),
+                    description: "邪恶环境底部边界（默认值=地下层终点）".to_string(),
                     param_type: ParamType::Float { min: 0.0, max: 1.0 },
-                    default: serde_json::json!(d.crimson_bottom_limit),
-                    group: Some("猩红生成".to_string()),
+                    default: serde_json::json!(d.evil_bottom_limit),
+                    group: Some("邪恶环境生成".to_string()),
                 },
                 ParamDef {
-                    key: "crimson_min_spacing".to_string(),
-                    name: "猩红最小间距".to_string(),
-                    description: "相邻猩红之间的最小间距（相对世界宽度）".to_string(),
+                    key: "evil_min_spacing".to_string(),
+                    name: "邪恶环境最小间距".to_string(),
+                    description: "相邻邪恶环境之间的最小间距（相对世界宽度）".to_string(),
                     param_type: ParamType::Float { min: 0.0, max: 1.0 },
                     default: serde_json::json!(0.15),
-                    group: Some("猩红生成".to_string()),
+                    group: Some("邪恶环境生成".to_string()),
                 },
                 ParamDef {
                     key: "forest_fill_merge_threshold".to_string(),
                     name: "扩散阈值".to_string(),
-                    description: "沙漠/猩红边缘到邻居环境的空隙小于此像素数时，扩散填充而非生成森林".to_string(),
+                    description: "沙漠/邪恶环境边缘到邻居环境的空隙小于此像素数时，扩散填充而非生成森林".to_string(),
                     param_type: ParamType::Int { min: 0, max: 500 },
                     default: serde_json::json!(100),
                     group: Some("森林填充".to_string()),
                 },
+                ParamDef {
+                    key: "final_fill_mode".to_string(),
+                    name: "剩余区域填充方式".to_string(),
+                    description: "stone=统一填充为岩石, nearest=每格取最近的已分配环境（多源 BFS 扩张）".to_string(),
+                    param_type: ParamType::Enum {
+                        options: vec!["stone".to_string(), "nearest".to_string()],
+                    },
+                    default: serde_json::json!("stone"),
+                    group: Some("地块填充".to_string()),
+                },
+                ParamDef {
+                    key: "smooth_iterations".to_string(),
+                    name: "平滑迭代次数".to_string(),
+                    description: "边界多数表决的轮数，0 = 关闭；每轮边界最多移动 1 格".to_string(),
+                    param_type: ParamType::Int { min: 0, max: 50 },
+                    default: serde_json::json!(0),
+                    group: Some("边界平滑".to_string()),
+                },
+                ParamDef {
+                    key: "smooth_radius".to_string(),
+                    name: "平滑邻域半径".to_string(),
+                    description: "多数表决的方形邻域半径（格），越大圆角越明显".to_string(),
+                    param_type: ParamType::Int { min: 1, max: 16 },
+                    default: serde_json::json!(3),
+                    group: Some("边界平滑".to_string()),
+                },
+                ParamDef {
+                    key: "smooth_biomes".to_string(),
+                    name: "参与平滑的环境".to_string(),
+                    description: "逗号分隔的环境 key，只在这些环境之间互相改写（space / hell / ocean 始终不参与）".to_string(),
+                    param_type: ParamType::Text,
+                    default: serde_json::json!(d.smooth_biomes),
+                    group: Some("边界平滑".to_string()),
+                },
+                ParamDef {
+                    key: "jitter_amplitude".to_string(),
+                    name: "抖动幅度".to_string(),
+                    description: "交界逐行平移的最大格数，0 = 关闭".to_string(),
+                    param_type: ParamType::Int { min: 0, max: 64 },
+                    default: serde_json::json!(0),
+                    group: Some("边界抖动".to_string()),
+                },
+                ParamDef {
+                    key: "jitter_pairs".to_string(),
+                    name: "抖动的环境交界".to_string(),
+                    description: "逗号分隔的环境对 a:b（如 forest:desert），只处理这些环境之间的竖直交界（两种朝向都处理）".to_string(),
+                    param_type: ParamType::Text,
+                    default: serde_json::json!(d.jitter_pairs),
+                    group: Some("边界抖动".to_string()),
+                },
+                ParamDef {
+                    key: "mirror_mode".to_string(),
+                    name: "镜像模式".to_string(),
+                    description: "none=不镜像, full=整幅世界左右对称, layers=只镜像下方列出的层级（如只让洞穴与地狱对称，地表照常生成）".to_string(),
+                    param_type: ParamType::Enum {
+                        options: vec!["none".to_string(), "full".to_string(), "layers".to_string()],
+                    },
+                    default: serde_json::json!("none"),
+                    group: Some("对称镜像".to_string()),
+                },
+                ParamDef {
+                    key: "mirror_layers".to_string(),
+                    name: "参与镜像的层级".to_string(),
+                    description: "逗号分隔的层级 key（space / surface / underground / cavern / hell），仅 layers 模式生效".to_string(),
+                    param_type: ParamType::Text,
+                    default: serde_json::json!(d.mirror_layers),
+                    group: Some("对称镜像".to_string()),
+                },
             ]
             },
         }
     }
 
-    fn execute(&mut self, step_index: usize, ctx: &mut RuntimeContext) -> Result<(), String> {
+    fn execute(&mut self, step_index: usize, ctx: &mut RuntimeContext) -> Result<(), GenerationError> {
         match step_index {
             0 => self.step_space_hell(ctx),
             1 => self.step_ocean(ctx),
@@ -445,10 +758,14 @@ impl PhaseAlgorithm for BiomeDivisionAlgorithm {
             3 => self.step_jungle(ctx),
             4 => self.step_snow(ctx),
             5 => self.step_desert(ctx),
-            6 => self.step_crimson(ctx),
+            6 => self.step_evil(ctx),
             7 => self.step_forest_fill(ctx),
             8 => self.step_stone_fill(ctx),
-            _ => Err(format!("无效步骤索引: {step_index}")),
+            9 => self.step_smoothing(ctx),
+            10 => self.step_jitter(ctx),
+            11 => self.step_mirror(ctx),
+            12 => self.step_pyramid(ctx),
+            _ => Err(GenerationError::InvalidStep(step_index)),
         }
     }
 
@@ -457,11 +774,74 @@ impl PhaseAlgorithm for BiomeDivisionAlgorithm {
     }
 
     fn set_params(&mut self, params: &serde_json::Value) {
-        if let Ok(p) = serde_json::from_value::<BiomeDivisionParams>(params.clone()) {
+        // 先在 JSON 上修正取值：越界的整数（如负数）会让整组参数反序列化失败
+        let mut raw = params.clone();
+        let changes = BiomeDivisionParams::validate_and_clamp(&mut raw, &self.meta().params);
+        if let Ok(mut p) = serde_json::from_value::<BiomeDivisionParams>(raw.clone()) {
+            p.derive_missing(&raw);
+            for change in changes {
+                log::warn(format!("环境判定参数超出范围，已修正 {change}"));
+            }
             self.params = p;
         }
     }
 
+    fn params_type_name(&self) -> Option<&'static str> {
+        Some("BiomeDivisionParams")
+    }
+
+    fn param_aliases(&self) -> &[(&'static str, &'static str)] {
+        params::PARAM_ALIASES
+    }
+
+    fn would_be_noop(&self, step_index: usize) -> Option<String> {
+        let p = &self.params;
+        let reason = match step_index {
+            1 if p.ocean_mode == "none" => "海洋模式为 none",
+            1 if p.ocean_left_width <= 0.0 && p.ocean_right_width <= 0.0 => "左右海洋宽度均为 0",
+            1 if p.ocean_top_limit >= p.ocean_bottom_limit => "海洋上边界不在下边界之上",
+            2 if p.forest_surface_extent <= 0.0 => "森林填充半宽为 0",
+            3 if p.jungle_width_ratio <= 0.0 => "丛林宽度为 0",
+            3 if p.jungle_top_limit >= p.jungle_bottom_limit => "丛林上边界不在下边界之上",
+            4 if p.snow_top_width_ratio <= 0.0 && p.snow_bottom_width_ratio <= 0.0 => "雪原顶部与底部宽度均为 0",
+            4 if p.snow_top_limit >= p.snow_bottom_limit => "雪原上边界不在下边界之上",
+            5 if p.desert_surface_count == 0 => "沙漠数量为 0",
+            5 if p.desert_surface_width_max <= 0.0 => "沙漠最大宽度为 0",
+            6 if p.evil_count == 0 => "邪恶环境数量为 0",
+            6 if p.evil_width_max <= 0.0 => "邪恶环境最大宽度为 0",
+            9 if p.smooth_iterations == 0 => "平滑迭代次数为 0",
+            9 if p.smooth_biomes.trim().is_empty() => "未指定参与平滑的环境",
+            10 if p.jitter_amplitude == 0 => "抖动幅度为 0",
+            10 if p.jitter_pairs.trim().is_empty() => "未指定抖动的环境对",
+            11 if p.mirror_mode == "none" => "镜像模式为 none",
+            11 if p.mirror_mode == "layers" && p.mirror_layers.trim().is_empty() => "未指定参与镜像的层级",
+            12 if p.pyramid_chance <= 0.0 => "金字塔概率为 0",
+            12 if p.pyramid_size <= 0.0 => "金字塔大小为 0",
+            _ => return None,
+        };
+        Some(reason.to_string())
+    }
+
+    fn overwrite_policy(&self, step_index: usize) -> OverwritePolicy {
+        // (原环境 key, 新环境 key)
+        let pairs: &[(&str, &str)] = match step_index {
+            // 海洋无条件填充，顶部 / 底部可能压到太空或地狱
+            1 => &[("space", "ocean"), ("hell", "ocean")],
+            // 真沙漠椭圆覆盖沙漠地表
+            5 => &[("desert", "desert_true")],
+            // 边界平滑 / 边界抖动本身就是改写交界；镜像整体覆盖右半边
+            9..=11 => return OverwritePolicy::Any,
+            // 金字塔只覆盖沙漠与真沙漠
+            12 => &[("desert", "pyramid"), ("desert_true", "pyramid")],
+            _ => &[],
+        };
+        OverwritePolicy::Allow(
+            pairs.iter()
+                .filter_map(|&(from, to)| Some((self.get_biome_id(from)?, self.get_biome_id(to)?)))
+                .collect(),
+        )
+    }
+
     fn on_reset(&mut self) {
         // 无需清理运行时状态（当前无跨步骤状态）
     }
@@ -1,42 +1,119 @@
 //! 海洋生成步骤
+//!
+//! `ocean_shape` 决定海洋的内陆边缘：
+//! - `rect`：矩形，海洋两侧为竖直墙壁
+//! - `trapezoid`：内陆边缘为斜线，海底向海滩方向抬升
+//! - `ellipse`：近岸一段为四分之一椭圆，海底平滑抬升到海滩
+//!
+//! 坡度由 `ocean_beach_slope` 控制：海洋底部比顶部窄的比例。让出的楔形区域保持未分配，
+//! 由后续步骤（森林填充 / 地块填充）填入相邻环境，内陆边缘因此与旁边的环境自然衔接。
 
-use crate::core::geometry::{self, Rect, Shape, ShapeParams, ShapeRecord};
+use crate::core::geometry::{self, Ellipse, Rect, Shape, ShapeCombine, ShapeParams, ShapeRecord, Trapezoid};
+use crate::core::layer::fraction_to_row;
 use crate::generation::algorithm::RuntimeContext;
+use crate::generation::error::GenerationError;
 
 use super::BiomeDivisionAlgorithm;
 
-pub fn execute(algo: &BiomeDivisionAlgorithm, ctx: &mut RuntimeContext) -> Result<(), String> {
-    let ocean_id = algo.get_biome_id("ocean")
-        .ok_or("未找到 ocean 环境定义")?;
+pub fn execute(algo: &BiomeDivisionAlgorithm, ctx: &mut RuntimeContext) -> Result<(), GenerationError> {
+    let ocean_id = algo.require_biome("ocean")?;
     
-    let bm = ctx.biome_map.as_mut().ok_or("需先执行太空/地狱填充")?;
+    let bm = ctx.biome_map.as_mut().ok_or(GenerationError::MapNotInitialized)?;
     let w = bm.width as i32;
     let h = bm.height as i32;
     
-    let y_top = (h as f64 * algo.params.ocean_top_limit) as i32;
-    let y_bottom = (h as f64 * algo.params.ocean_bottom_limit) as i32;
+    let y_top = fraction_to_row(h as u32, algo.params.ocean_top_limit) as i32;
+    let y_bottom = fraction_to_row(h as u32, algo.params.ocean_bottom_limit) as i32;
+    // ocean_mode 省略的一侧宽度为 0，不生成
+    let (left_ratio, right_ratio) = algo.ocean_widths()?;
     
-    // 左侧海洋
-    let left_width = (w as f64 * algo.params.ocean_left_width) as i32;
-    let left_rect = Rect::new(0, y_top, left_width, y_bottom);
-    geometry::fill_biome(&left_rect, bm, ocean_id);
-    ctx.shape_log.push(ShapeRecord {
-        label: "左侧海洋".into(),
-        bbox: left_rect.bounding_box(),
-        color: algo.biome_color(ocean_id),
-        params: ShapeParams::from_rect(&left_rect),
-    });
+    // 左侧海洋（海滩在右侧）
+    let left_width = (w as f64 * left_ratio) as i32;
+    if left_width > 0 {
+        let (left, left_params) = ocean_shape(algo, 0, left_width, y_top, y_bottom, false)?;
+        geometry::fill_biome(left.as_ref(), bm, ocean_id);
+        ctx.shape_log.push(ShapeRecord {
+            label: "左侧海洋".into(),
+            bbox: left.bounding_box(),
+            color: algo.biome_color(ocean_id),
+            params: left_params,
+        });
+    }
     
-    // 右侧海洋
-    let right_width = (w as f64 * algo.params.ocean_right_width) as i32;
-    let right_rect = Rect::new(w - right_width, y_top, w, y_bottom);
-    geometry::fill_biome(&right_rect, bm, ocean_id);
-    ctx.shape_log.push(ShapeRecord {
-        label: "右侧海洋".into(),
-        bbox: right_rect.bounding_box(),
-        color: algo.biome_color(ocean_id),
-        params: ShapeParams::from_rect(&right_rect),
-    });
+    // 右侧海洋（海滩在左侧）
+    let right_width = (w as f64 * right_ratio) as i32;
+    if right_width > 0 {
+        let (right, right_params) = ocean_shape(algo, w - right_width, w, y_top, y_bottom, true)?;
+        geometry::fill_biome(right.as_ref(), bm, ocean_id);
+        ctx.shape_log.push(ShapeRecord {
+            label: "右侧海洋".into(),
+            bbox: right.bounding_box(),
+            color: algo.biome_color(ocean_id),
+            params: right_params,
+        });
+    }
     
     Ok(())
 }
+
+/// 构造一侧海洋的形状。`x0..x1` 为顶部（海面）的水平范围，`beach_on_left` 表示海滩在左侧
+fn ocean_shape(
+    algo: &BiomeDivisionAlgorithm,
+    x0: i32,
+    x1: i32,
+    y_top: i32,
+    y_bottom: i32,
+    beach_on_left: bool,
+) -> Result<(Box<dyn Shape>, ShapeParams), GenerationError> {
+    let width = (x1 - x0) as f64;
+    let slope = algo.params.ocean_beach_slope.clamp(0.0, 1.0);
+    // 海底（全深度）部分的内陆边界
+    let floor_edge = if beach_on_left {
+        x1 as f64 - width * (1.0 - slope)
+    } else {
+        x0 as f64 + width * (1.0 - slope)
+    };
+    let beach_edge = if beach_on_left { x0 as f64 } else { x1 as f64 };
+
+    match algo.params.ocean_shape.as_str() {
+        "rect" => {
+            let rect = Rect::new(x0, y_top, x1, y_bottom);
+            let params = ShapeParams::from_rect(&rect);
+            Ok((Box::new(rect), params))
+        }
+        "trapezoid" => {
+            let trap = if beach_on_left {
+                Trapezoid::new(y_top, y_bottom, beach_edge, x1 as f64, floor_edge, x1 as f64)
+            } else {
+                Trapezoid::new(y_top, y_bottom, x0 as f64, beach_edge, x0 as f64, floor_edge)
+            };
+            let params = ShapeParams::from_trapezoid(&trap);
+            Ok((Box::new(trap), params))
+        }
+        "ellipse" => {
+            // 全深度矩形 ∪ 近岸四分之一椭圆（圆心在海面与海底边界的交点）
+            let depth = (y_bottom - y_top) as f64;
+            let shelf = Ellipse::new(floor_edge, y_top as f64, width * slope, depth);
+            let (deep, shelf_clip) = if beach_on_left {
+                (
+                    Rect::new(floor_edge.round() as i32, y_top, x1, y_bottom),
+                    Rect::new(x0, y_top, floor_edge.round() as i32, y_bottom),
+                )
+            } else {
+                (
+                    Rect::new(x0, y_top, floor_edge.round() as i32, y_bottom),
+                    Rect::new(floor_edge.round() as i32, y_top, x1, y_bottom),
+                )
+            };
+            let params = ShapeParams::Composite {
+                description: format!(
+                    "海底矩形 ∪ 近岸四分之一椭圆 (rx={:.0}, ry={:.0})",
+                    width * slope,
+                    depth,
+                ),
+            };
+            Ok((Box::new(deep.union(shelf.intersect(shelf_clip))), params))
+        }
+        other => Err(GenerationError::invalid_param("ocean_shape", other, "未知的海洋形状")),
+    }
+}
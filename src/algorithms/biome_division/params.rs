@@ -2,6 +2,7 @@
 
 use serde::{Deserialize, Serialize};
 use crate::core::layer::LayerDefinition;
+use crate::generation::algorithm::{ParamDef, ParamType};
 
 /// 辅助函数：从层级列表中查找指定 key 的起始百分比（0.0~1.0）
 fn layer_start(layers: &[LayerDefinition], key: &str) -> f64 {
@@ -20,21 +21,42 @@ fn layer_end(layers: &[LayerDefinition], key: &str) -> f64 {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct BiomeDivisionParams {
     // 海洋生成
     pub ocean_left_width: f64,
     pub ocean_right_width: f64,
+    /// "both" / "left"（仅左侧）/ "right"（仅右侧）/ "none"
+    pub ocean_mode: String,
     pub ocean_top_limit: f64,
     pub ocean_bottom_limit: f64,
+    /// 海洋形状：rect / trapezoid / ellipse
+    pub ocean_shape: String,
+    /// 海底坡度：海洋底部比顶部窄的比例（rect 时忽略）
+    pub ocean_beach_slope: f64,
     
     // 森林生成
+    /// 中心保留走廊的半宽（相对世界宽度）：丛林与雪原的可用空间止于走廊两侧
     pub forest_width_ratio: f64,
+    /// 中心森林实际填充的半宽（相对世界宽度），与保留走廊相互独立
+    pub forest_surface_extent: f64,
+    /// 出生点安全区半宽（相对世界宽度）：沙漠与邪恶环境不得与中心 ±此宽度的区域重叠，0 表示关闭
+    pub spawn_safe_radius: f64,
+    /// 不同环境之间的全局最小间距（相对世界宽度）：沙漠与邪恶环境与先放置的任意环境
+    /// （丛林、雪原、沙漠、邪恶环境）至少相隔此距离，0 表示关闭
+    pub global_min_gap: f64,
     
     // 丛林生成
+    /// "auto"（随机）/ "left" / "right"；雪原总在丛林的另一侧
+    pub jungle_side: String,
     pub jungle_width_ratio: f64,
     pub jungle_top_limit: f64,
     pub jungle_bottom_limit: f64,
     pub jungle_center_offset_range: f64,
+    /// 丛林形状："ellipse"（竖直椭圆）/ "teardrop"（上窄下宽的水滴：梯形 ∪ 椭圆）
+    pub jungle_shape: String,
+    /// 丛林椭圆边缘按子像素覆盖率随机抖散
+    pub jungle_soft_edge: bool,
     
     // 雪原生成
     pub snow_top_width_ratio: f64,
@@ -43,6 +65,8 @@ pub struct BiomeDivisionParams {
     pub snow_bottom_limit: f64,
     pub snow_bottom_depth_factor: f64,
     pub snow_center_offset_range: f64,
+    /// 雪原底边逐列起伏的幅度（相对世界高度），0 表示平底
+    pub snow_depth_variation: f64,
     
     // 沙漠生成
     pub desert_surface_count: u32,
@@ -55,20 +79,115 @@ pub struct BiomeDivisionParams {
     pub desert_true_top_limit: f64,
     pub desert_true_bottom_limit: f64,
     pub desert_true_depth_factor: f64,
+    /// 真沙漠选址规则："center"（离中心最近）/ "largest"（最宽的空白区段）/ "random" / "left" / "right"
+    pub desert_true_rule: String,
+    /// 真沙漠椭圆边缘按子像素覆盖率随机抖散
+    pub desert_soft_edge: bool,
+    /// 每个真沙漠标记金字塔的概率（0 = 不生成）
+    pub pyramid_chance: f64,
+    /// 金字塔底边宽度占所在真沙漠地表宽度的比例（斜面 45°，高度为底宽的一半）
+    pub pyramid_size: f64,
     
-    // 猩红生成
-    pub crimson_count: u32,
-    pub crimson_width_min: f64,
-    pub crimson_width_max: f64,
-    pub crimson_top_limit: f64,
-    pub crimson_bottom_limit: f64,
-    pub crimson_min_spacing: f64,
+    // 邪恶环境生成（旧存档中的 crimson_* 字段仍可读取）
+    /// "crimson" / "corruption" / "random"（每个区域独立随机，可混合出现）
+    pub evil_type: String,
+    #[serde(alias = "crimson_count")]
+    pub evil_count: u32,
+    #[serde(alias = "crimson_width_min")]
+    pub evil_width_min: f64,
+    #[serde(alias = "crimson_width_max")]
+    pub evil_width_max: f64,
+    #[serde(alias = "crimson_top_limit")]
+    pub evil_top_limit: f64,
+    #[serde(alias = "crimson_bottom_limit")]
+    pub evil_bottom_limit: f64,
+    #[serde(alias = "crimson_min_spacing")]
+    pub evil_min_spacing: f64,
     
     // 森林填充
     pub forest_fill_merge_threshold: u32,
+    
+    // 地块填充
+    /// "stone"（剩余空白统一填岩石）/ "nearest"（每格取最近的已分配环境）
+    pub final_fill_mode: String,
+
+    // 边界平滑
+    /// 多数表决迭代次数，0 表示关闭
+    pub smooth_iterations: u32,
+    /// 多数表决的方形邻域半径（格）
+    pub smooth_radius: u32,
+    /// 参与平滑的环境 key（逗号分隔）；space / hell / ocean 始终不参与
+    pub smooth_biomes: String,
+
+    // 边界抖动
+    /// 交界逐行平移的最大格数，0 表示关闭
+    pub jitter_amplitude: u32,
+    /// 参与抖动的环境对（`a:b`，逗号分隔）
+    pub jitter_pairs: String,
+
+    // 对称镜像
+    /// "none" / "full"（整幅世界）/ "layers"（仅 `mirror_layers` 所列层级）
+    pub mirror_mode: String,
+    /// 参与镜像的层级 key（逗号分隔）
+    pub mirror_layers: String,
 }
 
+/// 旧键名 → 现键名：存档只有旧键名时，现参数由它的值得到。
+///
+/// 邪恶环境由 crimson_* 改名（对应上面的 `#[serde(alias)]`）；`forest_surface_extent`
+/// 从 `forest_width_ratio` 拆出，旧预设缺少它时取走廊宽度（见 [`BiomeDivisionParams::derive_missing`]）。
+pub const PARAM_ALIASES: &[(&str, &str)] = &[
+    ("crimson_count", "evil_count"),
+    ("crimson_width_min", "evil_width_min"),
+    ("crimson_width_max", "evil_width_max"),
+    ("crimson_top_limit", "evil_top_limit"),
+    ("crimson_bottom_limit", "evil_bottom_limit"),
+    ("crimson_min_spacing", "evil_min_spacing"),
+    ("forest_width_ratio", "forest_surface_extent"),
+];
+
 impl BiomeDivisionParams {
+    /// 补全从旧参数拆出的新参数：`raw` 为反序列化前的 JSON。
+    ///
+    /// 旧预设中森林填充宽度就是保留走廊宽度，缺少 `forest_surface_extent` 时沿用
+    /// `forest_width_ratio`，而不是落到与旧世界不符的默认值。
+    pub fn derive_missing(&mut self, raw: &serde_json::Value) {
+        if raw.get("forest_surface_extent").is_none()
+            && let Some(ratio) = raw.get("forest_width_ratio").and_then(serde_json::Value::as_f64)
+        {
+            self.forest_surface_extent = ratio;
+        }
+    }
+
+    /// 反序列化前按 `meta()` 声明的参数定义修正 JSON 中的取值，防止手改的 runtime.json 或导入的快照
+    /// 带入越界值：数值钳制到范围内（负整数不会再让整组参数反序列化失败），整数参数给了小数时取整，
+    /// 非数值（含 NaN / ±∞ 序列化出的 null）恢复为默认值。
+    /// 返回每处修正的说明（`键: 原值 → 新值`）。
+    pub fn validate_and_clamp(raw: &mut serde_json::Value, defs: &[ParamDef]) -> Vec<String> {
+        let Some(map) = raw.as_object_mut() else {
+            return Vec::new();
+        };
+        let mut changes = Vec::new();
+        for def in defs {
+            let Some(value) = map.get_mut(&def.key) else { continue };
+            let fixed = match def.param_type {
+                ParamType::Float { .. } if !value.is_number() => Some(def.default.clone()),
+                ParamType::Int { min, max } if value.as_i64().is_none() => Some(value.as_f64().map_or_else(
+                    || def.default.clone(),
+                    |v| serde_json::json!((v.round() as i64).clamp(min, max)),
+                )),
+                ParamType::Float { .. } | ParamType::Int { .. } => def.clamp_value(value),
+                // 未知的枚举项留给各步骤报 InvalidParam，不在这里静默改掉
+                _ => None,
+            };
+            if let Some(fixed) = fixed {
+                changes.push(format!("{}: {value} → {fixed}", def.key));
+                *value = fixed;
+            }
+        }
+        changes
+    }
+
     /// 从层级配置构建参数默认值（所有层级边界从配置读取，不硬编码）
     pub fn from_layers(layers: &[LayerDefinition]) -> Self {
         let surface_start = layer_start(layers, "surface");
@@ -79,19 +198,29 @@ impl BiomeDivisionParams {
         Self {
             ocean_left_width: 0.05,
             ocean_right_width: 0.05,
+            ocean_mode: "both".to_string(),
             ocean_top_limit: surface_start,
             ocean_bottom_limit: underground_end,
+            ocean_shape: "rect".to_string(),
+            ocean_beach_slope: 0.5,
             forest_width_ratio: 0.05,
+            forest_surface_extent: 0.05,
+            spawn_safe_radius: 0.0,
+            global_min_gap: 0.0,
+            jungle_side: "auto".to_string(),
             jungle_width_ratio: 0.12,
             jungle_top_limit: surface_start,
             jungle_bottom_limit: cavern_end,
             jungle_center_offset_range: 0.20,
+            jungle_shape: "ellipse".to_string(),
+            jungle_soft_edge: false,
             snow_top_width_ratio: 0.08,
             snow_bottom_width_ratio: 0.20,
             snow_top_limit: surface_start,
             snow_bottom_limit: cavern_end,
             snow_bottom_depth_factor: 0.8,
             snow_center_offset_range: 0.12,
+            snow_depth_variation: 0.0,
             desert_surface_count: 3,
             desert_surface_width_min: 0.03,
             desert_surface_width_max: 0.05,
@@ -102,13 +231,26 @@ impl BiomeDivisionParams {
             desert_true_top_limit: underground_start,
             desert_true_bottom_limit: cavern_end,
             desert_true_depth_factor: 0.90,
-            crimson_count: 3,
-            crimson_width_min: 0.025,
-            crimson_width_max: 0.1,
-            crimson_top_limit: surface_start,
-            crimson_bottom_limit: underground_end,
-            crimson_min_spacing: 0.15,
+            desert_true_rule: "center".to_string(),
+            desert_soft_edge: false,
+            pyramid_chance: 0.0,
+            pyramid_size: 0.5,
+            evil_type: "crimson".to_string(),
+            evil_count: 3,
+            evil_width_min: 0.025,
+            evil_width_max: 0.1,
+            evil_top_limit: surface_start,
+            evil_bottom_limit: underground_end,
+            evil_min_spacing: 0.15,
             forest_fill_merge_threshold: 100,
+            final_fill_mode: "stone".to_string(),
+            smooth_iterations: 0,
+            smooth_radius: 3,
+            smooth_biomes: "forest,desert,desert_true,snow,jungle,crimson,corruption".to_string(),
+            jitter_amplitude: 0,
+            jitter_pairs: "forest:desert,forest:snow,forest:jungle,forest:crimson,forest:corruption".to_string(),
+            mirror_mode: "none".to_string(),
+            mirror_layers: "cavern,hell".to_string(),
         }
     }
 }
@@ -119,19 +261,29 @@ impl Default for BiomeDivisionParams {
         Self {
             ocean_left_width: 0.05,
             ocean_right_width: 0.05,
+            ocean_mode: "both".to_string(),
             ocean_top_limit: 0.10,
             ocean_bottom_limit: 0.40,
+            ocean_shape: "rect".to_string(),
+            ocean_beach_slope: 0.5,
             forest_width_ratio: 0.05,
+            forest_surface_extent: 0.05,
+            spawn_safe_radius: 0.0,
+            global_min_gap: 0.0,
+            jungle_side: "auto".to_string(),
             jungle_width_ratio: 0.12,
             jungle_top_limit: 0.10,
             jungle_bottom_limit: 0.85,
             jungle_center_offset_range: 0.20,
+            jungle_shape: "ellipse".to_string(),
+            jungle_soft_edge: false,
             snow_top_width_ratio: 0.08,
             snow_bottom_width_ratio: 0.20,
             snow_top_limit: 0.10,
             snow_bottom_limit: 0.85,
             snow_bottom_depth_factor: 0.8,
             snow_center_offset_range: 0.12,
+            snow_depth_variation: 0.0,
             desert_surface_count: 3,
             desert_surface_width_min: 0.03,
             desert_surface_width_max: 0.05,
@@ -142,13 +294,59 @@ impl Default for BiomeDivisionParams {
             desert_true_top_limit: 0.30,
             desert_true_bottom_limit: 0.85,
             desert_true_depth_factor: 0.90,
-            crimson_count: 3,
-            crimson_width_min: 0.025,
-            crimson_width_max: 0.1,
-            crimson_top_limit: 0.10,
-            crimson_bottom_limit: 0.40,
-            crimson_min_spacing: 0.15,
+            desert_true_rule: "center".to_string(),
+            desert_soft_edge: false,
+            pyramid_chance: 0.0,
+            pyramid_size: 0.5,
+            evil_type: "crimson".to_string(),
+            evil_count: 3,
+            evil_width_min: 0.025,
+            evil_width_max: 0.1,
+            evil_top_limit: 0.10,
+            evil_bottom_limit: 0.40,
+            evil_min_spacing: 0.15,
             forest_fill_merge_threshold: 100,
+            final_fill_mode: "stone".to_string(),
+            smooth_iterations: 0,
+            smooth_radius: 3,
+            smooth_biomes: "forest,desert,desert_true,snow,jungle,crimson,corruption".to_string(),
+            jitter_amplitude: 0,
+            jitter_pairs: "forest:desert,forest:snow,forest:jungle,forest:crimson,forest:corruption".to_string(),
+            mirror_mode: "none".to_string(),
+            mirror_layers: "cavern,hell".to_string(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::BiomeDivisionParams;
+    use crate::generation::build_pipeline;
+    use crate::generation::headless::HeadlessContext;
+
+    #[test]
+    fn out_of_range_params_are_clamped_before_deserializing() {
+        let context = HeadlessContext::load("small", None).unwrap();
+        let mut pipeline = build_pipeline(1, context.biomes.clone(), &context.profile.layers);
+        let algo = pipeline.algorithm_by_id_mut("biome_division").unwrap();
+        let mut params = algo.get_params();
+        params["jungle_width_ratio"] = serde_json::json!(0.2);
+        params["evil_count"] = serde_json::json!(-3);
+        params["desert_surface_count"] = serde_json::json!(2.6);
+        params["desert_true_depth_factor"] = serde_json::json!(7.0);
+        params["snow_top_width_ratio"] = serde_json::Value::Null;
+
+        let mut raw = params.clone();
+        let changes = BiomeDivisionParams::validate_and_clamp(&mut raw, &algo.meta().params);
+        assert_eq!(changes.len(), 4, "{changes:?}");
+
+        algo.set_params(&params);
+        let applied = algo.get_params();
+        assert_eq!(applied["evil_count"], serde_json::json!(0), "负整数钳制到下限，而不是让整组参数失效");
+        assert_eq!(applied["desert_surface_count"], serde_json::json!(3));
+        assert_eq!(applied["desert_true_depth_factor"], serde_json::json!(1.0));
+        assert_eq!(applied["jungle_width_ratio"], serde_json::json!(0.2), "合法的参数照常生效");
+        let defaults = serde_json::to_value(BiomeDivisionParams::default()).unwrap();
+        assert_eq!(applied["snow_top_width_ratio"], defaults["snow_top_width_ratio"]);
+    }
+}
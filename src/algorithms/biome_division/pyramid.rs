@@ -0,0 +1,93 @@
+//! 金字塔标记步骤（环境判定的最后一个子步骤，默认关闭）
+//!
+//! 每个真沙漠按 `pyramid_chance` 独立抽签，命中时在其地表沙漠顶部标记一个三角形的
+//! 砂岩砖区域：顶点位于地表上边界，斜面 45° 向下展开，只覆盖沙漠 / 真沙漠。
+//! 位置写入 `shared["pyramid_slots"]`（`(中心 x, 顶点 y, 底宽, 高度)`），供今后的房间挖掘使用。
+//!
+//! 作为独立子步骤排在对称镜像之后，不占用沙漠步骤的随机数，之前各步骤的结果与概率无关。
+
+use crate::core::geometry::{self, Shape, ShapeParams, ShapeRecord, Trapezoid};
+use crate::core::layer::fraction_to_row;
+use crate::generation::algorithm::RuntimeContext;
+use crate::generation::error::GenerationError;
+use rand::Rng;
+
+use super::BiomeDivisionAlgorithm;
+
+pub fn execute(algo: &BiomeDivisionAlgorithm, ctx: &mut RuntimeContext) -> Result<(), GenerationError> {
+    let chance = algo.params.pyramid_chance;
+    let size = algo.params.pyramid_size;
+    let true_slots: Vec<(i32, i32)> = ctx.shared.get("desert_true_slots")
+        .and_then(|v| v.downcast_ref::<Vec<(i32, i32)>>())
+        .cloned()
+        .unwrap_or_default();
+
+    let mut pyramids: Vec<(i32, i32, i32, i32)> = Vec::new();
+    if chance > 0.0 && size > 0.0 && !true_slots.is_empty() {
+        let pyramid_id = algo.require_biome("pyramid")?;
+        let desert_surface_id = algo.require_biome("desert")?;
+        let desert_true_id = algo.require_biome("desert_true")?;
+
+        let bm = ctx.biome_map.as_mut().ok_or(GenerationError::MapNotInitialized)?;
+        let h = bm.height as i32;
+        let top_y = fraction_to_row(h as u32, algo.params.desert_surface_top_limit) as i32;
+
+        for &(cx, width) in &true_slots {
+            // 每个真沙漠独立抽签一次
+            if !ctx.rng.gen_bool(chance.min(1.0)) {
+                continue;
+            }
+            let half_base = (width as f64 * size / 2.0).max(1.0);
+            let bottom_y = (top_y + half_base.round() as i32).min(h);
+            let tri = Trapezoid::from_center(cx as f64, top_y, bottom_y, 0.5, half_base);
+            geometry::fill_biome_if(&tri, bm, pyramid_id, |c| c == desert_surface_id || c == desert_true_id);
+            ctx.shape_log.push(ShapeRecord {
+                label: format!("金字塔 #{}", pyramids.len() + 1),
+                bbox: tri.bounding_box(),
+                color: algo.biome_color(pyramid_id),
+                params: ShapeParams::from_trapezoid(&tri),
+            });
+            pyramids.push((cx, top_y, (half_base * 2.0).round() as i32, bottom_y - top_y));
+        }
+    }
+
+    ctx.shared.insert("pyramid_slots".into(), Box::new(pyramids));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::generation::headless::HeadlessContext;
+
+    #[test]
+    fn pyramids_only_replace_desert_cells() {
+        let context = HeadlessContext::load("small", None).unwrap();
+        let id = |key: &str| context.biomes.iter().find(|b| b.key == key).unwrap().id;
+        let (desert, desert_true, pyramid) = (id("desert"), id("desert_true"), id("pyramid"));
+        let mut placed = 0;
+        for seed in 0..4 {
+            let run = |chance: f64| {
+                context.generate(seed, |p| {
+                    p.set_step_logging(false);
+                    let algo = p.algorithm_by_id_mut("biome_division").unwrap();
+                    let mut params = algo.get_params();
+                    params["pyramid_chance"] = serde_json::json!(chance);
+                    algo.set_params(&params);
+                }).unwrap()
+            };
+            let (without, with) = (run(0.0), run(1.0));
+            let before = without.biome_map().unwrap().data();
+            let after = with.biome_map().unwrap().data();
+            assert!(!before.contains(&pyramid), "seed {seed}: 概率为 0 时不应有金字塔");
+            for (i, (&old, &new)) in before.iter().zip(after).enumerate() {
+                if old != new {
+                    assert_eq!(new, pyramid, "seed {seed}: 第 {i} 格被改成了金字塔以外的环境");
+                    assert!(old == desert || old == desert_true, "seed {seed}: 金字塔覆盖了沙漠以外的第 {i} 格");
+                }
+            }
+            let slots = with.shared::<Vec<(i32, i32, i32, i32)>>("pyramid_slots").unwrap();
+            placed += slots.len();
+        }
+        assert!(placed > 0, "概率为 1 时应至少放置一个金字塔");
+    }
+}
@@ -0,0 +1,164 @@
+//! 边界平滑步骤（后处理）
+//!
+//! 对环境地图做若干轮多数表决元胞自动机：位于边界上的参与环境格子，
+//! 若半径 `smooth_radius` 的方形邻域中某个其他参与环境超过半数，就改为该环境。
+//! 3×3 邻域下矩形是稳定形，只有较大的半径才能把直角磨成圆角（圆角半径约等于邻域半径）。
+//!
+//! 每轮只重新判定边界格（4 邻接中有不同环境），内部格的邻域必然以自身为主，无需计算。
+//! 只在参与环境之间互相改写；太空 / 地狱 / 海洋始终不参与，即使写进了列表。
+
+use rayon::prelude::*;
+
+use crate::core::biome::{BiomeId, BiomeMap};
+use crate::core::log;
+use crate::generation::algorithm::RuntimeContext;
+use crate::generation::error::GenerationError;
+
+use super::BiomeDivisionAlgorithm;
+
+/// 永远不参与平滑的环境（层级边界 / 世界边缘，形状由规则决定）
+const PROTECTED_KEYS: &[&str] = &["space", "hell", "ocean"];
+
+pub fn execute(algo: &BiomeDivisionAlgorithm, ctx: &mut RuntimeContext) -> Result<(), GenerationError> {
+    let iterations = algo.params.smooth_iterations;
+    if iterations == 0 {
+        return Ok(());
+    }
+    let radius = algo.params.smooth_radius.max(1) as usize;
+
+    let mut participants = [false; 256];
+    for key in algo.params.smooth_biomes.split(',').map(str::trim).filter(|k| !k.is_empty()) {
+        if PROTECTED_KEYS.contains(&key) {
+            log::warn(format!("[biome_division] 边界平滑: {key} 不参与平滑，已忽略"));
+            continue;
+        }
+        let id = algo.get_biome_id(key)
+            .ok_or_else(|| GenerationError::invalid_param("smooth_biomes", key, "未知的环境 key"))?;
+        participants[id as usize] = true;
+    }
+
+    let bm = ctx.biome_map.as_mut().ok_or(GenerationError::MapNotInitialized)?;
+    smooth(bm, iterations, radius, &participants);
+    Ok(())
+}
+
+/// 对 `bm` 做至多 `iterations` 轮多数表决，没有格子变化时提前结束
+fn smooth(bm: &mut BiomeMap, iterations: u32, radius: usize, participants: &[bool; 256]) {
+    let w = bm.width as usize;
+    let h = bm.height as usize;
+
+    // 只有窗口内发生过变化的行才可能再变化：每轮后把变化行向上下扩张 radius 行作为下一轮的待算行
+    let mut next = bm.data().to_vec();
+    let mut dirty = vec![true; h];
+    for _ in 0..iterations {
+        let current = bm.data();
+        let changed: Vec<bool> = next.par_chunks_mut(w)
+            .enumerate()
+            .map(|(y, row)| {
+                if !dirty[y] {
+                    return false;
+                }
+                let mut any = false;
+                for (x, cell) in row.iter_mut().enumerate() {
+                    *cell = majority_vote(current, w, h, x, y, radius, participants);
+                    any |= *cell != current[y * w + x];
+                }
+                any
+            })
+            .collect();
+        if !changed.contains(&true) {
+            break;
+        }
+        bm.data_mut().copy_from_slice(&next);
+
+        dirty.fill(false);
+        for y in changed.iter().enumerate().filter(|&(_, &c)| c).map(|(y, _)| y) {
+            let lo = y.saturating_sub(radius);
+            let hi = (y + radius).min(h - 1);
+            dirty[lo..=hi].fill(true);
+        }
+    }
+}
+
+/// 计算 (x, y) 的下一状态：非参与格、内部格保持不变；
+/// 边界上的参与格若邻域中有其他参与环境超过半数则改写为它
+fn majority_vote(
+    data: &[BiomeId],
+    w: usize,
+    h: usize,
+    x: usize,
+    y: usize,
+    radius: usize,
+    participants: &[bool; 256],
+) -> BiomeId {
+    let own = data[y * w + x];
+    if !participants[own as usize] {
+        return own;
+    }
+    let on_border = (x > 0 && data[y * w + x - 1] != own)
+        || (x + 1 < w && data[y * w + x + 1] != own)
+        || (y > 0 && data[(y - 1) * w + x] != own)
+        || (y + 1 < h && data[(y + 1) * w + x] != own);
+    if !on_border {
+        return own;
+    }
+
+    let x0 = x.saturating_sub(radius);
+    let x1 = (x + radius).min(w - 1);
+    let y0 = y.saturating_sub(radius);
+    let y1 = (y + radius).min(h - 1);
+    let window = (x1 - x0 + 1) * (y1 - y0 + 1);
+
+    let mut counts = [0u32; 256];
+    for ny in y0..=y1 {
+        for &b in &data[ny * w + x0..=ny * w + x1] {
+            counts[b as usize] += 1;
+        }
+    }
+
+    counts.iter()
+        .enumerate()
+        .filter(|&(id, _)| id != own as usize && participants[id])
+        .find(|&(_, &n)| n as usize * 2 > window)
+        .map_or(own, |(id, _)| id as BiomeId)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::smooth;
+    use crate::core::biome::{BiomeId, BiomeMap};
+
+    const FOREST: BiomeId = 2;
+    const DESERT: BiomeId = 3;
+    const OCEAN: BiomeId = 1;
+
+    fn participants() -> [bool; 256] {
+        let mut participants = [false; 256];
+        participants[FOREST as usize] = true;
+        participants[DESERT as usize] = true;
+        participants
+    }
+
+    #[test]
+    fn isolated_spike_is_removed() {
+        let mut bm = BiomeMap::new_filled(9, 9, FOREST);
+        bm.set(4, 4, DESERT);
+        smooth(&mut bm, 1, 1, &participants());
+        assert!(bm.data().iter().all(|&b| b == FOREST), "单格突起应被周围环境吞并");
+    }
+
+    #[test]
+    fn smooth_regions_and_non_participants_are_unchanged() {
+        // 左半森林、右半沙漠的竖直直边，外加一个不参与平滑的海洋单格
+        let mut bm = BiomeMap::new_filled(12, 8, FOREST);
+        for y in 0..8 {
+            for x in 6..12 {
+                bm.set(x, y, DESERT);
+            }
+        }
+        bm.set(2, 2, OCEAN);
+        let before = bm.data().to_vec();
+        smooth(&mut bm, 4, 1, &participants());
+        assert_eq!(bm.data(), &before[..], "直边与非参与环境不应被改写");
+    }
+}
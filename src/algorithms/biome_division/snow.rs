@@ -1,17 +1,32 @@
 //! 雪原生成步骤
+//!
+//! 上窄下宽的梯形。`snow_depth_variation > 0` 时底边不再平直：按种子生成一条平滑的
+//! 逐列深度剖面（若干随机控制点之间余弦插值），梯形两腰按原斜率向下延伸，
+//! 每列截断在各自的深度处，最深不进入地狱层。
+
+use std::f64::consts::PI;
 
 use crate::core::biome::BIOME_UNASSIGNED;
-use crate::core::geometry::{self, Shape, ShapeParams, ShapeRecord, Trapezoid};
+use crate::core::geometry::{self, BoundingBox, Shape, ShapeParams, ShapeRecord, Trapezoid};
+use crate::core::layer::fraction_to_row;
 use crate::generation::algorithm::RuntimeContext;
+use crate::generation::error::GenerationError;
 use rand::Rng;
 
+use super::interval;
 use super::BiomeDivisionAlgorithm;
 
-pub fn execute(algo: &BiomeDivisionAlgorithm, ctx: &mut RuntimeContext) -> Result<(), String> {
-    let snow_id = algo.get_biome_id("snow")
-        .ok_or("未找到 snow 环境定义")?;
+pub fn execute(algo: &BiomeDivisionAlgorithm, ctx: &mut RuntimeContext) -> Result<(), GenerationError> {
+    let snow_id = algo.require_biome("snow")?;
+    let variation = algo.params.snow_depth_variation;
+    // 起伏底边最深不进入地狱层（平底时不需要地狱层）
+    let hell_top = if variation > 0.0 {
+        Some(ctx.require_layer_px("hell")?.0 as i32)
+    } else {
+        None
+    };
     
-    let bm = ctx.biome_map.as_mut().ok_or("需先执行海洋生成")?;
+    let bm = ctx.biome_map.as_mut().ok_or(GenerationError::MapNotInitialized)?;
     let w = bm.width as i32;
     let h = bm.height as i32;
     
@@ -22,15 +37,16 @@ pub fn execute(algo: &BiomeDivisionAlgorithm, ctx: &mut RuntimeContext) -> Resul
         .unwrap_or(false);
     let place_on_left = !jungle_on_left;
     
-    // 计算森林边界
+    // 计算中心保留走廊边界（半宽 = forest_width_ratio）
     let forest_center = w / 2;
     let forest_half_width = (w as f64 * algo.params.forest_width_ratio) as i32;
     let forest_left = forest_center - forest_half_width;
     let forest_right = forest_center + forest_half_width;
     
-    // 计算海洋边界
-    let ocean_left_right = (w as f64 * algo.params.ocean_left_width) as i32;
-    let ocean_right_left = w - (w as f64 * algo.params.ocean_right_width) as i32;
+    // 计算海洋边界（ocean_mode 省略的一侧以世界边缘为界）
+    let (ocean_left_ratio, ocean_right_ratio) = algo.ocean_widths()?;
+    let ocean_left_right = (w as f64 * ocean_left_ratio) as i32;
+    let ocean_right_left = w - (w as f64 * ocean_right_ratio) as i32;
     
     // 计算雪原可用空间和基础中心点
     let (snow_cx_base, available_width) = if place_on_left {
@@ -56,8 +72,8 @@ pub fn execute(algo: &BiomeDivisionAlgorithm, ctx: &mut RuntimeContext) -> Resul
     let top_half_width = (w as f64 * algo.params.snow_top_width_ratio / 2.0) as i32;
     let bottom_half_width = (w as f64 * algo.params.snow_bottom_width_ratio / 2.0) as i32;
     
-    let top_y = (h as f64 * algo.params.snow_top_limit) as i32;
-    let bottom_y = (h as f64 * algo.params.snow_bottom_limit * algo.params.snow_bottom_depth_factor) as i32;
+    let top_y = fraction_to_row(h as u32, algo.params.snow_top_limit) as i32;
+    let bottom_y = fraction_to_row(h as u32, algo.params.snow_bottom_limit * algo.params.snow_bottom_depth_factor) as i32;
     
     // 梯形填充（只替换 BIOME_UNASSIGNED）
     let shape = Trapezoid::new(
@@ -67,13 +83,141 @@ pub fn execute(algo: &BiomeDivisionAlgorithm, ctx: &mut RuntimeContext) -> Resul
         (snow_cx - bottom_half_width) as f64,
         (snow_cx + bottom_half_width) as f64,
     );
-    geometry::fill_biome_if(&shape, bm, snow_id, |c| c == BIOME_UNASSIGNED);
+
+    let Some(hell_top) = hell_top.filter(|_| shape.y_bot > top_y) else {
+        geometry::fill_biome_if(&shape, bm, snow_id, |c| c == BIOME_UNASSIGNED);
+        let bbox = shape.bounding_box();
+        interval::register_occupied(ctx.shared, (bbox.x_min, bbox.x_max));
+        ctx.shape_log.push(ShapeRecord {
+            label: "雪原".into(),
+            bbox,
+            color: algo.biome_color(snow_id),
+            params: ShapeParams::from_trapezoid(&shape),
+        });
+        return Ok(());
+    };
+
+    // 起伏底边：每列深度 = 基准底边 ± 幅度，钳制在顶边之下、地狱层之上
+    let floor = hell_top.min(h);
+    let amplitude = variation * h as f64;
+    let max_bottom = ((shape.y_bot as f64 + amplitude).ceil() as i32).min(floor);
+    let extended = extend_trapezoid(&shape, max_bottom);
+    let bbox = extended.bounding_box();
+    let profile = depth_profile(&mut ctx.rng, (bbox.x_max - bbox.x_min).max(1) as usize);
+    let bottoms = profile.iter()
+        .map(|&n| ((shape.y_bot as f64 + n * amplitude).round() as i32).clamp(top_y + 1, floor))
+        .collect();
+    let undulating = UndulatingBottom { shape: extended, x0: bbox.x_min, bottoms };
+
+    geometry::fill_biome_if(&undulating, bm, snow_id, |c| c == BIOME_UNASSIGNED);
+    let bbox = undulating.bounding_box();
+    interval::register_occupied(ctx.shared, (bbox.x_min, bbox.x_max));
     ctx.shape_log.push(ShapeRecord {
         label: "雪原".into(),
-        bbox: shape.bounding_box(),
+        bbox,
         color: algo.biome_color(snow_id),
-        params: ShapeParams::from_trapezoid(&shape),
+        params: ShapeParams::from_trapezoid(&undulating.shape),
     });
-    
+
     Ok(())
 }
+
+/// 把梯形的两腰按原斜率延伸到 `y_bot` 行（上半部分与原梯形逐格一致）
+fn extend_trapezoid(t: &Trapezoid, y_bot: i32) -> Trapezoid {
+    if y_bot <= t.y_bot {
+        return t.clone();
+    }
+    let scale = (y_bot - t.y_top) as f64 / (t.y_bot - t.y_top) as f64;
+    Trapezoid::new(
+        t.y_top, y_bot,
+        t.top_x0, t.top_x1,
+        t.top_x0 + (t.bot_x0 - t.top_x0) * scale,
+        t.top_x1 + (t.bot_x1 - t.top_x1) * scale,
+    )
+}
+
+/// 平滑的逐列深度剖面，取值 [-1, 1]：约每 `PROFILE_SEGMENT` 列一个随机控制点，控制点之间余弦插值
+fn depth_profile(rng: &mut impl Rng, columns: usize) -> Vec<f64> {
+    const PROFILE_SEGMENT: usize = 48;
+    let segments = columns.div_ceil(PROFILE_SEGMENT).max(2);
+    let points: Vec<f64> = (0..=segments).map(|_| rng.gen_range(-1.0..=1.0)).collect();
+    (0..columns)
+        .map(|x| {
+            let t = x as f64 / columns as f64 * segments as f64;
+            let i = (t as usize).min(segments - 1);
+            let f = (1.0 - ((t - i as f64) * PI).cos()) / 2.0;
+            points[i] + (points[i + 1] - points[i]) * f
+        })
+        .collect()
+}
+
+/// 底边逐列起伏的梯形：`shape` 内且位于该列深度之上的格
+struct UndulatingBottom {
+    shape: Trapezoid,
+    /// `bottoms[0]` 对应的列
+    x0: i32,
+    /// 每列的底边（不含）
+    bottoms: Vec<i32>,
+}
+
+impl Shape for UndulatingBottom {
+    fn contains(&self, x: i32, y: i32) -> bool {
+        let Some(&bottom) = usize::try_from(x - self.x0).ok().and_then(|i| self.bottoms.get(i)) else {
+            return false;
+        };
+        y < bottom && self.shape.contains(x, y)
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        let bb = self.shape.bounding_box();
+        let deepest = self.bottoms.iter().copied().max().unwrap_or(bb.y_max);
+        BoundingBox::new(bb.x_min, bb.y_min, bb.x_max, deepest.min(bb.y_max))
+    }
+
+    fn type_name(&self) -> &'static str { "梯形" }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::generation::headless::HeadlessContext;
+
+    #[test]
+    fn undulating_bottom_stays_above_hell_at_maximum_amplitude() {
+        // 下边界、深度因子与起伏幅度都取上限时底边最深处落在世界之外，应被钳制在地狱层顶部之上
+        let context = HeadlessContext::load("small", None).unwrap();
+        let hell_top = context.profile.layers.iter()
+            .find(|l| l.key == "hell")
+            .map(|l| l.bounds_for_height(context.profile.size.height).0)
+            .expect("hell 层级");
+        let snow = context.biomes.iter().find(|b| b.key == "snow").unwrap().id;
+        for seed in 0..4 {
+            let pipeline = context
+                .generate(seed, |p| {
+                    p.set_step_logging(false);
+                    let algo = p.algorithm_by_id_mut("biome_division").unwrap();
+                    let mut params = algo.get_params();
+                    params["snow_bottom_limit"] = serde_json::json!(1.0);
+                    params["snow_bottom_depth_factor"] = serde_json::json!(1.0);
+                    params["snow_depth_variation"] = serde_json::json!(0.2);
+                    algo.set_params(&params);
+                })
+                .unwrap();
+            let bm = pipeline.biome_map().unwrap();
+            let deepest = (0..bm.height).rev()
+                .find(|&y| (0..bm.width).any(|x| bm.get(x, y) == snow));
+            assert!(deepest.is_some(), "seed {seed}: 应放置雪原");
+            assert!(deepest < Some(hell_top), "seed {seed}: 雪原伸到第 {deepest:?} 行，地狱层从 {hell_top} 行开始");
+            // 地狱层可能先于雪原分配而挡住填充，因此还要检查形状本身的底边（不含）
+            let record = (0..pipeline.total_sub_steps())
+                .filter_map(|i| pipeline.shape_log(i))
+                .flatten()
+                .find(|r| r.label == "雪原")
+                .expect("雪原形状记录");
+            assert!(
+                record.bbox.y_max <= hell_top as i32,
+                "seed {seed}: 雪原底边在第 {} 行，地狱层从 {hell_top} 行开始",
+                record.bbox.y_max,
+            );
+        }
+    }
+}
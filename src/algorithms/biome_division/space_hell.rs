@@ -3,21 +3,20 @@
 use crate::core::biome::{BiomeMap, BIOME_UNASSIGNED};
 use crate::core::geometry::{self, Rect, Shape, ShapeParams, ShapeRecord};
 use crate::generation::algorithm::RuntimeContext;
+use crate::generation::error::GenerationError;
 
 use super::BiomeDivisionAlgorithm;
 
-pub fn execute(algo: &BiomeDivisionAlgorithm, ctx: &mut RuntimeContext) -> Result<(), String> {
-    let space_id = algo.get_biome_id("space")
-        .ok_or("未找到 space 环境定义")?;
-    let hell_id = algo.get_biome_id("hell")
-        .ok_or("未找到 hell 环境定义")?;
+pub fn execute(algo: &BiomeDivisionAlgorithm, ctx: &mut RuntimeContext) -> Result<(), GenerationError> {
+    let space_id = algo.require_biome("space")?;
+    let hell_id = algo.require_biome("hell")?;
     
     let w = ctx.world.width as i32;
     let h = ctx.world.height as i32;
     
     // 读取层级边界（在取可变借用之前）
-    let space_bottom = ctx.layer_end_px("space").ok_or("未找到 space 层级定义")? as i32;
-    let hell_top = ctx.layer_start_px("hell").ok_or("未找到 hell 层级定义")? as i32;
+    let space_bottom = ctx.require_layer_px("space")?.1 as i32;
+    let hell_top = ctx.require_layer_px("hell")?.0 as i32;
     
     // 初始化 BiomeMap（全部填充为 UNASSIGNED）
     *ctx.biome_map = Some(BiomeMap::new_filled(w as u32, h as u32, BIOME_UNASSIGNED));
@@ -1,19 +1,37 @@
 //! 地块填充步骤
+//!
+//! 两种模式（`final_fill_mode`）：
+//! - `stone`：剩余空白统一填充为岩石地块
+//! - `nearest`：多源 BFS，每个空白格取距离最近（4 邻接步数）的已分配环境，
+//!   效果类似 Voronoi 扩张，空洞被周围环境自然吸收
 
-use crate::core::biome::BIOME_UNASSIGNED;
+use std::collections::VecDeque;
+
+use crate::core::biome::{BiomeMap, BIOME_UNASSIGNED};
 use crate::core::geometry::{self, Rect, Shape, ShapeParams, ShapeRecord};
 use crate::generation::algorithm::RuntimeContext;
+use crate::generation::error::GenerationError;
 
 use super::BiomeDivisionAlgorithm;
 
-pub fn execute(algo: &BiomeDivisionAlgorithm, ctx: &mut RuntimeContext) -> Result<(), String> {
-    let stone_id = algo.get_biome_id("stone")
-        .ok_or("未找到 stone 环境定义")?;
-    
-    let bm = ctx.biome_map.as_mut().ok_or("需先执行前置步骤")?;
+pub fn execute(algo: &BiomeDivisionAlgorithm, ctx: &mut RuntimeContext) -> Result<(), GenerationError> {
+    let stone_id = algo.require_biome("stone")?;
+
+    let bm = ctx.biome_map.as_mut().ok_or(GenerationError::MapNotInitialized)?;
     let w = bm.width as i32;
     let h = bm.height as i32;
-    
+
+    match algo.params.final_fill_mode.as_str() {
+        "stone" => {}
+        "nearest" => {
+            // 没有任何已分配格子时无源可扩张，退回岩石填充
+            if fill_nearest(bm) {
+                return Ok(());
+            }
+        }
+        other => return Err(GenerationError::invalid_param("final_fill_mode", other, "未知的剩余区域填充方式")),
+    }
+
     let world_rect = Rect::new(0, 0, w, h);
     geometry::fill_biome_if(&world_rect, bm, stone_id, |c| c == BIOME_UNASSIGNED);
     ctx.shape_log.push(ShapeRecord {
@@ -22,6 +40,81 @@ pub fn execute(algo: &BiomeDivisionAlgorithm, ctx: &mut RuntimeContext) -> Resul
         color: algo.biome_color(stone_id),
         params: ShapeParams::from_rect(&world_rect),
     });
-    
+
     Ok(())
 }
+
+/// 多源 BFS：所有已分配格子同时作为源，逐层向空白格扩张。
+///
+/// 同一距离上的竞争按入队顺序（行优先）决出，结果确定。
+/// 返回 `false` 表示地图中没有任何已分配格子。
+fn fill_nearest(bm: &mut BiomeMap) -> bool {
+    let w = bm.width as usize;
+    let h = bm.height as usize;
+
+    let mut queue: VecDeque<usize> = bm.data()
+        .iter()
+        .enumerate()
+        .filter(|&(_, &b)| b != BIOME_UNASSIGNED)
+        .map(|(i, _)| i)
+        .collect();
+    if queue.is_empty() {
+        return false;
+    }
+
+    let data = bm.data_mut();
+    while let Some(i) = queue.pop_front() {
+        let biome = data[i];
+        let (x, y) = (i % w, i / w);
+        let neighbors = [
+            (x > 0).then(|| i - 1),
+            (x + 1 < w).then(|| i + 1),
+            (y > 0).then(|| i - w),
+            (y + 1 < h).then(|| i + w),
+        ];
+        for n in neighbors.into_iter().flatten() {
+            if data[n] == BIOME_UNASSIGNED {
+                data[n] = biome;
+                queue.push_back(n);
+            }
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fill_nearest;
+    use crate::core::biome::{BiomeId, BiomeMap, BIOME_UNASSIGNED};
+
+    #[test]
+    fn unassigned_cells_take_the_nearest_assigned_biome() {
+        let sources = [(1, 1, 2), (8, 6, 3), (2, 7, 4)];
+        let mut bm = BiomeMap::new_filled(10, 8, BIOME_UNASSIGNED);
+        for &(x, y, biome) in &sources {
+            bm.set(x, y, biome);
+        }
+        assert!(fill_nearest(&mut bm));
+
+        for y in 0..8u32 {
+            for x in 0..10u32 {
+                let mut by_distance: Vec<(u32, BiomeId)> = sources.iter()
+                    .map(|&(sx, sy, biome)| (x.abs_diff(sx) + y.abs_diff(sy), biome))
+                    .collect();
+                by_distance.sort_unstable();
+                // 等距竞争按入队顺序决出，这里只核对唯一最近的格子
+                if by_distance[0].0 < by_distance[1].0 {
+                    assert_eq!(bm.get(x, y), by_distance[0].1, "({x}, {y}) 应取最近的环境");
+                }
+            }
+        }
+        assert!(bm.data().iter().all(|&b| b != BIOME_UNASSIGNED), "不应留下空白格");
+    }
+
+    #[test]
+    fn map_without_sources_is_left_for_stone() {
+        let mut bm = BiomeMap::new_filled(4, 4, BIOME_UNASSIGNED);
+        assert!(!fill_nearest(&mut bm));
+        assert!(bm.data().iter().all(|&b| b == BIOME_UNASSIGNED));
+    }
+}
@@ -0,0 +1,206 @@
+//! # 自定义特征算法模块
+//!
+//! 在环境判定之后，按用户提供的特征文件（JSON）放置额外的环境形状，
+//! 无需改代码即可加入一次性区域（如保证存在的中央高塔）。
+//!
+//! 每个特征描述一个形状、目标环境和可选约束（层级范围 / 可覆盖的环境），
+//! 执行时构造对应的 [`Shape`](crate::core::geometry::Shape) 并调用
+//! [`fill_biome_if`](crate::core::geometry::fill_biome_if)。文件格式见 [`spec`]。
+//!
+//! 特征文件路径为空时本阶段不做任何事。快照只记录路径，不记录文件内容。
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::biome::{BiomeDefinition, BiomeId, BIOME_UNASSIGNED};
+use crate::core::geometry::{self, Rect, Shape, ShapeCombine, ShapeRecord};
+use crate::generation::algorithm::{
+    OverwritePolicy, ParamDef, ParamType, PhaseAlgorithm, PhaseMeta, RuntimeContext, StepMeta,
+};
+use crate::generation::error::GenerationError;
+use crate::storage::paths::data_dir;
+
+mod spec;
+
+use spec::{FeatureSpec, FeaturesFile};
+
+/// `replace` 中表示空白格的 key
+const UNASSIGNED_KEY: &str = "unassigned";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CustomFeaturesParams {
+    /// 特征文件路径；相对路径基于应用数据目录，空字符串表示不启用
+    pub features_file: String,
+}
+
+pub struct CustomFeaturesAlgorithm {
+    biome_definitions: Vec<BiomeDefinition>,
+    pub params: CustomFeaturesParams,
+}
+
+impl CustomFeaturesAlgorithm {
+    pub fn new(biome_definitions: &[BiomeDefinition]) -> Self {
+        Self {
+            biome_definitions: biome_definitions.to_vec(),
+            params: CustomFeaturesParams::default(),
+        }
+    }
+
+    fn biome_by_key(&self, key: &str) -> Result<&BiomeDefinition, GenerationError> {
+        self.biome_definitions.iter()
+            .find(|b| b.key == key)
+            .ok_or_else(|| GenerationError::MissingBiome(key.to_string()))
+    }
+
+    fn resolve_path(&self) -> PathBuf {
+        let path = PathBuf::from(self.params.features_file.trim());
+        if path.is_relative() {
+            data_dir().join(path)
+        } else {
+            path
+        }
+    }
+
+    fn load_features(&self) -> Result<Vec<FeatureSpec>, GenerationError> {
+        let path = self.resolve_path();
+        let file_error = |reason: String| GenerationError::File {
+            path: path.display().to_string(),
+            reason,
+        };
+        let text = std::fs::read_to_string(&path)
+            .map_err(|e| file_error(format!("读取特征文件失败: {e}")))?;
+        let file: FeaturesFile = serde_json::from_str(&text)
+            .map_err(|e| file_error(format!("解析特征文件失败: {e}")))?;
+        Ok(file.features)
+    }
+
+    fn step_place_features(&self, ctx: &mut RuntimeContext) -> Result<(), GenerationError> {
+        if self.params.features_file.trim().is_empty() {
+            return Ok(());
+        }
+        let features = self.load_features()?;
+
+        let w = ctx.world.width;
+        let h = ctx.world.height;
+
+        for (i, feature) in features.iter().enumerate() {
+            let label = if feature.name.is_empty() {
+                format!("特征 #{}", i + 1)
+            } else {
+                feature.name.clone()
+            };
+            let biome = self.biome_by_key(&feature.biome)
+                .map_err(|e| e.context(&label))?;
+            let biome_id = biome.id;
+            let color = biome.overlay_color;
+
+            // 可覆盖的环境集合（None = 全部覆盖）
+            let replace: Option<Vec<BiomeId>> = match &feature.replace {
+                Some(keys) => Some(
+                    keys.iter()
+                        .map(|k| {
+                            if k == UNASSIGNED_KEY {
+                                Ok(BIOME_UNASSIGNED)
+                            } else {
+                                self.biome_by_key(k).map(|b| b.id)
+                            }
+                        })
+                        .collect::<Result<_, _>>()
+                        .map_err(|e| e.context(&label))?,
+                ),
+                None => None,
+            };
+            let filter = |c: BiomeId| replace.as_ref().is_none_or(|ids| ids.contains(&c));
+
+            // 层级约束：逐层取行范围，与形状求交后填充
+            let rows: Vec<(u32, u32)> = match &feature.layers {
+                Some(keys) => keys.iter()
+                    .map(|k| ctx.require_layer_px(k).map_err(|e| e.context(&label)))
+                    .collect::<Result<_, _>>()?,
+                None => vec![(0, h)],
+            };
+
+            let shape = feature.shape.resolve(w, h);
+            let bm = ctx.biome_map.as_mut().ok_or(GenerationError::MapNotInitialized)?;
+            for &(top, bottom) in &rows {
+                let clipped = shape.clone().intersect(Rect::new(0, top as i32, w as i32, bottom as i32));
+                geometry::fill_biome_if(&clipped, bm, biome_id, filter);
+            }
+
+            let bbox = rows.iter()
+                .map(|&(top, bottom)| {
+                    shape.bounding_box()
+                        .intersect(Rect::new(0, top as i32, w as i32, bottom as i32).bounding_box())
+                })
+                .reduce(|a, b| a.union(b))
+                .unwrap_or_else(|| shape.bounding_box());
+            ctx.shape_log.push(ShapeRecord {
+                label,
+                bbox,
+                color,
+                params: shape.params(),
+            });
+        }
+
+        crate::core::log::info(format!("[custom_features] 已放置 {} 个自定义特征", features.len()));
+        Ok(())
+    }
+}
+
+impl PhaseAlgorithm for CustomFeaturesAlgorithm {
+    fn meta(&self) -> PhaseMeta {
+        PhaseMeta {
+            id: "custom_features".to_string(),
+            name: "自定义特征".to_string(),
+            description: "按特征文件（JSON）放置额外的环境形状".to_string(),
+            steps: vec![
+                StepMeta {
+                    display_index: 1,
+                    name: "特征放置".to_string(),
+                    description: "读取特征文件，逐个构造形状并填充指定环境（未设置文件时跳过）".to_string(),
+                    doc_url: None,
+                    scatter: false,
+                    biome: None,
+                },
+            ],
+            params: vec![
+                ParamDef {
+                    key: "features_file".to_string(),
+                    name: "特征文件".to_string(),
+                    description: "特征 JSON 路径，相对路径基于应用数据目录（如 features.json）；留空不启用".to_string(),
+                    param_type: ParamType::Text,
+                    default: serde_json::json!(""),
+                    group: Some("特征放置".to_string()),
+                },
+            ],
+        }
+    }
+
+    fn execute(&mut self, step_index: usize, ctx: &mut RuntimeContext) -> Result<(), GenerationError> {
+        match step_index {
+            0 => self.step_place_features(ctx),
+            _ => Err(GenerationError::InvalidStep(step_index)),
+        }
+    }
+
+    fn get_params(&self) -> serde_json::Value {
+        serde_json::to_value(&self.params).unwrap_or_default()
+    }
+
+    fn set_params(&mut self, params: &serde_json::Value) {
+        if let Ok(p) = serde_json::from_value::<CustomFeaturesParams>(params.clone()) {
+            self.params = p;
+        }
+    }
+
+    fn would_be_noop(&self, _step_index: usize) -> Option<String> {
+        self.params.features_file.trim().is_empty().then(|| "未设置特征文件".to_string())
+    }
+
+    fn overwrite_policy(&self, _step_index: usize) -> OverwritePolicy {
+        // 可覆盖的环境由特征文件逐个声明
+        OverwritePolicy::Any
+    }
+}
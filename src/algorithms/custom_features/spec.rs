@@ -0,0 +1,116 @@
+//! 特征文件格式定义
+//!
+//! 坐标均为相对世界尺寸的比例（0.0~1.0），与其他算法参数一致，
+//! 同一份文件可用于任意世界尺寸。
+
+use serde::Deserialize;
+
+use crate::core::geometry::{
+    BoundingBox, Column, Ellipse, Rect, Shape, ShapeParams, Trapezoid,
+};
+
+/// 特征文件根结构
+#[derive(Debug, Clone, Deserialize)]
+pub struct FeaturesFile {
+    #[serde(default)]
+    pub features: Vec<FeatureSpec>,
+}
+
+/// 单个特征：一个形状 + 目标环境 + 可选约束
+#[derive(Debug, Clone, Deserialize)]
+pub struct FeatureSpec {
+    /// 显示名称（几何预览 / 日志）
+    #[serde(default)]
+    pub name: String,
+    /// 填充的环境 key（见 biome.json）
+    pub biome: String,
+    pub shape: ShapeSpec,
+    /// 仅在这些层级的行范围内填充；缺省为不限制
+    #[serde(default)]
+    pub layers: Option<Vec<String>>,
+    /// 仅覆盖这些环境 key（`"unassigned"` 表示空白格）；缺省为全部覆盖
+    #[serde(default)]
+    pub replace: Option<Vec<String>>,
+}
+
+/// 形状描述（比例坐标）
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ShapeSpec {
+    Rect { x0: f64, y0: f64, x1: f64, y1: f64 },
+    Ellipse { cx: f64, cy: f64, rx: f64, ry: f64 },
+    Trapezoid { y_top: f64, y_bot: f64, top_x0: f64, top_x1: f64, bot_x0: f64, bot_x1: f64 },
+    Column { x: f64, y_start: f64, y_end: f64 },
+}
+
+impl ShapeSpec {
+    /// 换算为像素坐标的几何形状
+    pub fn resolve(&self, width: u32, height: u32) -> FeatureShape {
+        let w = width as f64;
+        let h = height as f64;
+        let px = |v: f64| (v * w).round() as i32;
+        let py = |v: f64| (v * h).round() as i32;
+        match *self {
+            ShapeSpec::Rect { x0, y0, x1, y1 } => {
+                FeatureShape::Rect(Rect::new(px(x0), py(y0), px(x1), py(y1)))
+            }
+            ShapeSpec::Ellipse { cx, cy, rx, ry } => {
+                FeatureShape::Ellipse(Ellipse::new(cx * w, cy * h, rx * w, ry * h))
+            }
+            ShapeSpec::Trapezoid { y_top, y_bot, top_x0, top_x1, bot_x0, bot_x1 } => {
+                FeatureShape::Trapezoid(Trapezoid::new(
+                    py(y_top), py(y_bot),
+                    top_x0 * w, top_x1 * w,
+                    bot_x0 * w, bot_x1 * w,
+                ))
+            }
+            ShapeSpec::Column { x, y_start, y_end } => {
+                FeatureShape::Column(Column::new(px(x), py(y_start), py(y_end)))
+            }
+        }
+    }
+}
+
+/// 像素坐标下的特征形状（按类型分派到基础形状）
+#[derive(Debug, Clone)]
+pub enum FeatureShape {
+    Rect(Rect),
+    Ellipse(Ellipse),
+    Trapezoid(Trapezoid),
+    Column(Column),
+}
+
+impl FeatureShape {
+    fn inner(&self) -> &dyn Shape {
+        match self {
+            FeatureShape::Rect(s) => s,
+            FeatureShape::Ellipse(s) => s,
+            FeatureShape::Trapezoid(s) => s,
+            FeatureShape::Column(s) => s,
+        }
+    }
+
+    /// 几何预览用的形状参数
+    pub fn params(&self) -> ShapeParams {
+        match self {
+            FeatureShape::Rect(s) => ShapeParams::from_rect(s),
+            FeatureShape::Ellipse(s) => ShapeParams::from_ellipse(s),
+            FeatureShape::Trapezoid(s) => ShapeParams::from_trapezoid(s),
+            FeatureShape::Column(s) => ShapeParams::from_column(s),
+        }
+    }
+}
+
+impl Shape for FeatureShape {
+    fn contains(&self, x: i32, y: i32) -> bool {
+        self.inner().contains(x, y)
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        self.inner().bounding_box()
+    }
+
+    fn type_name(&self) -> &'static str {
+        self.inner().type_name()
+    }
+}
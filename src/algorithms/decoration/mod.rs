@@ -0,0 +1,173 @@
+//! # 地表装饰算法模块
+//!
+//! 按各环境的 `decoration_density`（见 [`BiomeDefinition::decoration_density`]）沿地表
+//! 放置装饰标记。目前只计算位置、不写入世界，结果登记在 `shared[DECORATION_MARKERS_KEY]`，
+//! 供今后的树木、草丛等具体装饰步骤使用。
+//!
+//! 每列固定消耗一次 RNG，无论该列是否放置标记——调整某个环境的密度只影响该环境内的标记，
+//! 不会让其它环境的标记整体错位。位置完全由种子与参数决定，快照记录参数即可复现。
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::core::biome::{BiomeDefinition, BiomeId};
+use crate::generation::algorithm::{
+    OverwritePolicy, ParamDef, ParamType, PhaseAlgorithm, PhaseMeta, RuntimeContext, StepMeta,
+};
+use crate::generation::error::GenerationError;
+
+/// `shared` 中登记装饰标记的键（值为 `Vec<DecorationMarker>`，按 `x` 递增）
+pub const DECORATION_MARKERS_KEY: &str = "decoration_markers";
+
+/// 一个装饰标记：地表某列上的一格
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecorationMarker {
+    pub x: u32,
+    pub y: u32,
+    /// 标记所在格的环境
+    pub biome: BiomeId,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DecorationParams {
+    /// 全局密度倍率，乘在各环境的 `decoration_density` 上
+    pub density_scale: f64,
+}
+
+impl Default for DecorationParams {
+    fn default() -> Self {
+        Self { density_scale: 1.0 }
+    }
+}
+
+pub struct DecorationAlgorithm {
+    /// 按环境 ID 索引的装饰密度
+    densities: [f32; 256],
+    pub params: DecorationParams,
+}
+
+impl DecorationAlgorithm {
+    pub fn new(biome_definitions: &[BiomeDefinition]) -> Self {
+        let mut densities = [0.0; 256];
+        for biome in biome_definitions {
+            densities[biome.id as usize] = biome.decoration_density;
+        }
+        Self {
+            densities,
+            params: DecorationParams::default(),
+        }
+    }
+
+    fn step_place_markers(&self, ctx: &mut RuntimeContext) -> Result<(), GenerationError> {
+        let (surface_top, _) = ctx.require_layer_px("surface")?;
+        let bm = ctx.biome_map.as_ref().ok_or(GenerationError::MapNotInitialized)?;
+        let y = surface_top.min(bm.height.saturating_sub(1));
+        let scale = self.params.density_scale.max(0.0);
+
+        let mut markers = Vec::new();
+        for x in 0..bm.width {
+            let roll = ctx.rng.gen_range(0.0..1.0);
+            let biome = bm.get(x, y);
+            let density = self.densities[biome as usize] as f64 * scale;
+            if roll < density {
+                markers.push(DecorationMarker { x, y, biome });
+            }
+        }
+
+        crate::core::log::info(format!("[decoration] 已放置 {} 个装饰标记", markers.len()));
+        ctx.shared.insert(DECORATION_MARKERS_KEY.into(), Box::new(markers));
+        Ok(())
+    }
+}
+
+impl PhaseAlgorithm for DecorationAlgorithm {
+    fn meta(&self) -> PhaseMeta {
+        PhaseMeta {
+            id: "decoration".to_string(),
+            name: "地表装饰".to_string(),
+            description: "按各环境的装饰密度沿地表放置装饰标记".to_string(),
+            steps: vec![
+                StepMeta {
+                    display_index: 1,
+                    name: "装饰标记".to_string(),
+                    description: "逐列按所在环境的装饰密度决定是否放置标记（目前只记录位置，不修改世界）".to_string(),
+                    doc_url: None,
+                    scatter: true,
+                    biome: None,
+                },
+            ],
+            params: vec![
+                ParamDef {
+                    key: "density_scale".to_string(),
+                    name: "密度倍率".to_string(),
+                    description: "乘在 biome.json 各环境 decoration_density 上的全局倍率；0 表示不放置".to_string(),
+                    param_type: ParamType::Float { min: 0.0, max: 5.0 },
+                    default: serde_json::json!(1.0),
+                    group: Some("装饰标记".to_string()),
+                },
+            ],
+        }
+    }
+
+    fn execute(&mut self, step_index: usize, ctx: &mut RuntimeContext) -> Result<(), GenerationError> {
+        match step_index {
+            0 => self.step_place_markers(ctx),
+            _ => Err(GenerationError::InvalidStep(step_index)),
+        }
+    }
+
+    fn get_params(&self) -> serde_json::Value {
+        serde_json::to_value(&self.params).unwrap_or_default()
+    }
+
+    fn set_params(&mut self, params: &serde_json::Value) {
+        if let Ok(p) = serde_json::from_value::<DecorationParams>(params.clone()) {
+            self.params = p;
+        }
+    }
+
+    fn would_be_noop(&self, _step_index: usize) -> Option<String> {
+        if self.params.density_scale <= 0.0 {
+            Some("密度倍率为 0".to_string())
+        } else if self.densities.iter().all(|&d| d <= 0.0) {
+            Some("没有环境设置装饰密度".to_string())
+        } else {
+            None
+        }
+    }
+
+    fn overwrite_policy(&self, _step_index: usize) -> OverwritePolicy {
+        // 只登记标记，不写环境地图
+        OverwritePolicy::Allow(vec![])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DecorationMarker, DECORATION_MARKERS_KEY};
+    use crate::generation::headless::HeadlessContext;
+
+    fn markers(context: &HeadlessContext, seed: u64, density_scale: f64) -> Vec<DecorationMarker> {
+        let pipeline = context.generate(seed, |p| {
+            p.set_step_logging(false);
+            p.algorithm_by_id_mut("decoration").unwrap().set_params(&serde_json::json!({
+                "density_scale": density_scale,
+            }));
+        }).unwrap();
+        pipeline.shared::<Vec<DecorationMarker>>(DECORATION_MARKERS_KEY).cloned().unwrap()
+    }
+
+    #[test]
+    fn markers_are_deterministic_and_follow_biome_density() {
+        let context = HeadlessContext::load("small", None).unwrap();
+        let first = markers(&context, 11, 1.0);
+        assert!(!first.is_empty(), "默认密度下应放置标记");
+        assert_eq!(first, markers(&context, 11, 1.0), "同一种子的标记应完全一致");
+
+        let density = |id| context.biomes.iter().find(|b| b.id == id).map_or(0.0, |b| b.decoration_density);
+        assert!(first.iter().all(|m| density(m.biome) > 0.0), "标记只应落在装饰密度大于 0 的环境");
+
+        assert!(markers(&context, 11, 0.0).is_empty(), "密度倍率为 0 时不应放置标记");
+    }
+}
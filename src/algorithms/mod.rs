@@ -1 +1,26 @@
 pub mod biome_division;
+pub mod custom_features;
+pub mod decoration;
+
+use std::sync::Once;
+
+use crate::generation::phase_registry::{register_phase_factory, PhaseFactory};
+
+use self::decoration::DecorationAlgorithm;
+
+/// 启动时登记的扩展阶段 `(阶段 ID, 工厂)`：实验性阶段在此追加即可，不必修改 `build_pipeline`。
+///
+/// 地表装饰（`decoration`）即经此登记，追加在内置阶段之后。
+const EXTENSION_PHASES: &[(&str, PhaseFactory)] = &[
+    ("decoration", |biomes, _layers| Box::new(DecorationAlgorithm::new(biomes))),
+];
+
+/// 把 [`EXTENSION_PHASES`] 登记到阶段注册表；应在构建第一条流水线之前调用，重复调用无效果
+pub fn register_extension_phases() {
+    static REGISTERED: Once = Once::new();
+    REGISTERED.call_once(|| {
+        for &(id, factory) in EXTENSION_PHASES {
+            register_phase_factory(id, factory);
+        }
+    });
+}
@@ -11,7 +11,38 @@ pub struct BiomeConfig {
     pub key: String,
     pub name: String,
     pub overlay_color: [u8; 4],
+    /// 泰拉瑞亚游戏内全图地图的颜色（RGB，缺省时"泰拉瑞亚配色"下仍用 `overlay_color`）
+    #[serde(default)]
+    pub map_color: Option<[u8; 3]>,
     pub description: String,
+    /// 字符画中代表该环境的字符（缺省时取 key 首字母）
+    #[serde(default)]
+    pub ascii_char: Option<char>,
+    /// 变体：生成步骤按权重从种子 RNG 中抽取，用变体环境替代本环境（缺省为无变体）
+    #[serde(default)]
+    pub variants: Vec<BiomeVariantConfig>,
+    /// 本环境自身参与变体抽取的权重（仅在有变体时使用）
+    #[serde(default = "default_base_weight")]
+    pub base_weight: u32,
+    /// 画布标签优先级：碰撞时高优先级先放置，再按区域大小（缺省 0）
+    #[serde(default)]
+    pub label_priority: i32,
+    /// 地表装饰密度：地表每列放置一个装饰标记的概率（0 ~ 1，缺省 0 表示不放置）
+    #[serde(default)]
+    pub decoration_density: f32,
+}
+
+/// 环境变体：引用另一个已定义的环境
+#[derive(Debug, Clone, Deserialize)]
+pub struct BiomeVariantConfig {
+    /// 变体环境的 key（需在 biome.json 中另有定义，拥有自己的覆盖色）
+    pub biome: String,
+    /// 相对权重（与基础环境的 `base_weight` 比较）；全部为 0 时不抽取、不消耗 RNG
+    pub weight: u32,
+}
+
+fn default_base_weight() -> u32 {
+    100
 }
 
 pub type BiomesConfig = BTreeMap<u8, BiomeConfig>;
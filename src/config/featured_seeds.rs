@@ -0,0 +1,21 @@
+use std::collections::BTreeMap;
+
+use crate::config::ConfigError;
+
+/// 精选种子：按世界尺寸列出布局较好的种子（十六进制，与种子输入框格式一致）。
+/// 以默认参数批量生成后挑选——校验无警告、环境齐全且碎片最少。
+const FEATURED_SEEDS_JSON: &str = include_str!("../assets/featured_seeds.json");
+
+pub fn load_featured_seeds() -> Result<BTreeMap<String, Vec<u64>>, ConfigError> {
+    let raw: BTreeMap<String, Vec<String>> = serde_json::from_str(FEATURED_SEEDS_JSON)?;
+    Ok(raw
+        .into_iter()
+        .map(|(size, seeds)| {
+            let seeds = seeds
+                .iter()
+                .filter_map(|s| u64::from_str_radix(s.trim(), 16).ok())
+                .collect();
+            (size, seeds)
+        })
+        .collect())
+}
@@ -0,0 +1,28 @@
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+
+use crate::config::ConfigError;
+
+const LANG_JSON: &str = include_str!("../assets/lang.json");
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LanguageConfig {
+    /// 语言代码（如 zh-CN / en）
+    pub code: String,
+    /// 在语言选择器中显示的名称
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LangConfig {
+    /// 可选语言，第一项为默认语言（也是缺失翻译时的回退）
+    pub languages: Vec<LanguageConfig>,
+    /// 字符串 key → (语言代码 → 译文)
+    pub strings: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+pub fn load_lang_config() -> Result<LangConfig, ConfigError> {
+    let config: LangConfig = serde_json::from_str(LANG_JSON)?;
+    Ok(config)
+}
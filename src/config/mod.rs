@@ -1,5 +1,7 @@
 pub mod biome;
 pub mod blocks;
+pub mod featured_seeds;
+pub mod lang;
 pub mod world;
 
 use std::error::Error;
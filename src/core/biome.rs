@@ -1,7 +1,11 @@
+use std::collections::BTreeMap;
+
+use rand::Rng;
 use rayon::prelude::*;
 
 use crate::config::biome::BiomesConfig;
 use crate::core::layer::LayerDefinition;
+use crate::core::log;
 
 // ── Biome ID 和定义 ────────────────────────────────────────
 
@@ -16,7 +20,32 @@ pub struct BiomeDefinition {
     pub key: String,
     pub name: String,
     pub overlay_color: [u8; 4],
+    /// 游戏内地图颜色（RGB），见 [`display_color`](Self::display_color)
+    pub map_color: Option<[u8; 3]>,
     pub description: String,
+    /// 字符画中代表该环境的字符
+    pub ascii_char: char,
+    /// 变体 `(环境 ID, 权重)`，见 [`pick_variant`]
+    pub variants: Vec<(BiomeId, u32)>,
+    /// 本环境自身参与变体抽取的权重
+    pub base_weight: u32,
+    /// 画布标签优先级（越大越先放置，小区域也不会被大区域的标签挤掉）
+    pub label_priority: i32,
+    /// 地表装饰密度（每列放置标记的概率），见 [`DecorationAlgorithm`](crate::algorithms::decoration::DecorationAlgorithm)
+    pub decoration_density: f32,
+}
+
+impl BiomeDefinition {
+    /// 覆盖层实际使用的颜色（RGBA，未预乘）。
+    ///
+    /// `terraria_colors` 为真且定义了 `map_color` 时取游戏内地图色（不透明，与游戏全图一致），
+    /// 否则取 `overlay_color`。
+    pub fn display_color(&self, terraria_colors: bool) -> [u8; 4] {
+        match self.map_color {
+            Some([r, g, b]) if terraria_colors => [r, g, b, 255],
+            _ => self.overlay_color,
+        }
+    }
 }
 
 pub fn build_biome_definitions(config: &BiomesConfig) -> Vec<BiomeDefinition> {
@@ -27,16 +56,72 @@ pub fn build_biome_definitions(config: &BiomesConfig) -> Vec<BiomeDefinition> {
             key: biome.key.clone(),
             name: biome.name.clone(),
             overlay_color: biome.overlay_color,
+            map_color: biome.map_color,
             description: biome.description.clone(),
+            ascii_char: biome
+                .ascii_char
+                .or_else(|| biome.key.chars().next())
+                .unwrap_or('?'),
+            variants: biome.variants.iter()
+                .filter_map(|variant| {
+                    let found = config.iter().find(|(_, b)| b.key == variant.biome);
+                    if found.is_none() {
+                        log::warn(format!("环境 {} 的变体 {} 未定义，已忽略", biome.key, variant.biome));
+                    }
+                    found.map(|(&variant_id, _)| (variant_id, variant.weight))
+                })
+                .collect(),
+            base_weight: biome.base_weight,
+            label_priority: biome.label_priority,
+            decoration_density: biome.decoration_density.clamp(0.0, 1.0),
         })
         .collect()
 }
 
+/// 按权重从基础环境与它的变体中抽取一个环境 ID。
+///
+/// 基础环境没有变体、变体权重全为 0（或找不到定义）时直接返回 `base`，不消耗 RNG——
+/// 未启用变体的世界与引入变体之前完全一致。抽取来自步骤的确定性 RNG，同一种子回放结果不变。
+pub fn pick_variant(biomes: &[BiomeDefinition], base: BiomeId, rng: &mut impl Rng) -> BiomeId {
+    let Some(bdef) = biomes.iter().find(|b| b.id == base) else {
+        return base;
+    };
+    let variant_total = bdef.variants.iter().map(|&(_, w)| w).sum::<u32>();
+    if variant_total == 0 {
+        return base;
+    }
+    let total = bdef.base_weight + variant_total;
+    let mut roll = rng.gen_range(0..total);
+    if roll < bdef.base_weight {
+        return base;
+    }
+    roll -= bdef.base_weight;
+    for &(variant, weight) in &bdef.variants {
+        if roll < weight {
+            return variant;
+        }
+        roll -= weight;
+    }
+    base
+}
+
 // ── 2D 环境地图 ────────────────────────────────────────
 
 /// 二维环境地图：每个格子都有一个 BiomeId。
 ///
 /// 支持有形状的环境区域（梯形、椭圆等），而不仅仅是水平条带。
+/// 环境地图中的一个连通块（4 邻接），见 [`BiomeMap::components`]
+#[derive(Debug, Clone)]
+pub struct BiomeComponent {
+    pub biome: BiomeId,
+    /// 格数
+    pub size: usize,
+    /// 包围盒 `[x0, y0, x1, y1]`（右下不含）
+    pub bbox: [u32; 4],
+    /// 质心（世界坐标，格子中心为 +0.5）；凹形区域的质心可能落在块外
+    pub centroid: (f32, f32),
+}
+
 #[derive(Debug, Clone)]
 pub struct BiomeMap {
     pub width: u32,
@@ -56,21 +141,43 @@ impl BiomeMap {
         }
     }
 
+    /// (x, y) 是否在地图范围内
+    #[inline]
+    pub fn in_bounds(&self, x: u32, y: u32) -> bool {
+        x < self.width && y < self.height
+    }
+
     /// 获取 (x, y) 处的 biome
+    ///
+    /// 调用方负责保证坐标在 `[0, width) × [0, height)` 内（步骤中的 `as u32` 转换
+    /// 会把负坐标变成极大值）。debug 构建下越界直接 panic 并报告坐标与地图尺寸；
+    /// release 构建下越界返回 `BIOME_UNASSIGNED`。
     pub fn get(&self, x: u32, y: u32) -> BiomeId {
-        if x >= self.width || y >= self.height {
+        debug_assert!(self.in_bounds(x, y), "{}", self.out_of_bounds_message("get", x, y));
+        if !self.in_bounds(x, y) {
             return BIOME_UNASSIGNED;
         }
         self.data[(y * self.width + x) as usize]
     }
 
     /// 设置 (x, y) 处的 biome
+    ///
+    /// 坐标约定同 [`get`](Self::get)：debug 构建下越界 panic，release 构建下忽略。
     pub fn set(&mut self, x: u32, y: u32, biome: BiomeId) {
-        if x < self.width && y < self.height {
+        debug_assert!(self.in_bounds(x, y), "{}", self.out_of_bounds_message("set", x, y));
+        if self.in_bounds(x, y) {
             self.data[(y * self.width + x) as usize] = biome;
         }
     }
 
+    fn out_of_bounds_message(&self, op: &str, x: u32, y: u32) -> String {
+        // 负坐标经 `as u32` 后会变成接近 u32::MAX 的值，按 i32 还原便于定位
+        format!(
+            "BiomeMap::{op} 越界: ({}, {}) 超出地图 {}×{}",
+            x as i32, y as i32, self.width, self.height
+        )
+    }
+
     /// 返回底层数据的只读引用（用于渲染）
     pub fn data(&self) -> &[BiomeId] {
         &self.data
@@ -81,6 +188,20 @@ impl BiomeMap {
         &mut self.data
     }
 
+    /// 环境地图内容的 FNV-1a 哈希（含宽高），同一张地图总得到同一个值，任意一格不同即改变
+    pub fn content_hash(&self) -> u64 {
+        let header = self.width.to_le_bytes().into_iter().chain(self.height.to_le_bytes());
+        header.chain(self.data.iter().copied()).fold(0xcbf2_9ce4_8422_2325u64, |hash, b| {
+            (hash ^ b as u64).wrapping_mul(0x0100_0000_01b3)
+        })
+    }
+
+    /// [`content_hash`](Self::content_hash) 折叠为 8 位十六进制，用于界面与报告中快速比对两个世界
+    pub fn short_hash(&self) -> String {
+        let hash = self.content_hash();
+        format!("{:08X}", (hash ^ (hash >> 32)) as u32)
+    }
+
     /// 统计指定 biome 在某个 x 范围内的格子数（用于判定密度）
     ///
     /// 使用 rayon 并行按行统计。
@@ -96,6 +217,142 @@ impl BiomeMap {
             })
             .sum()
     }
+
+    /// 统计每种 biome 的格子数（下标为 BiomeId）
+    pub fn histogram(&self) -> [u64; 256] {
+        self.data
+            .par_chunks(self.width.max(1) as usize)
+            .fold(|| [0u64; 256], |mut counts, row| {
+                for &b in row {
+                    counts[b as usize] += 1;
+                }
+                counts
+            })
+            .reduce(|| [0u64; 256], |mut a, b| {
+                for (x, y) in a.iter_mut().zip(b) {
+                    *x += y;
+                }
+                a
+            })
+    }
+
+    /// 统计环境之间的邻接关系：4 邻接的两格 biome 不同即记 1 格边界。
+    ///
+    /// 返回 `((较小 id, 较大 id), 边界长度)`，按 id 对排序。
+    pub fn adjacency(&self) -> Vec<((BiomeId, BiomeId), u64)> {
+        let w = self.width as usize;
+        let h = self.height as usize;
+        if w == 0 || h == 0 {
+            return Vec::new();
+        }
+        let pair = |a: BiomeId, b: BiomeId| if a < b { (a, b) } else { (b, a) };
+
+        let counts = (0..h)
+            .into_par_iter()
+            .fold(BTreeMap::new, |mut counts, y| {
+                let row = &self.data[y * w..(y + 1) * w];
+                // 与右侧 / 下方相邻格比较，每条边界只统计一次
+                for x in 1..w {
+                    if row[x - 1] != row[x] {
+                        *counts.entry(pair(row[x - 1], row[x])).or_insert(0u64) += 1;
+                    }
+                }
+                if y + 1 < h {
+                    let below = &self.data[(y + 1) * w..(y + 2) * w];
+                    for (&a, &b) in row.iter().zip(below) {
+                        if a != b {
+                            *counts.entry(pair(a, b)).or_insert(0u64) += 1;
+                        }
+                    }
+                }
+                counts
+            })
+            .reduce(BTreeMap::new, |mut a, b| {
+                for (k, n) in b {
+                    *a.entry(k).or_insert(0) += n;
+                }
+                a
+            });
+        counts.into_iter().collect()
+    }
+
+    /// 所有已分配格的 4 邻接连通块，按首格（行优先）顺序。
+    ///
+    /// 每块记录格数、包围盒与质心；未分配格不参与。
+    pub fn components(&self) -> Vec<BiomeComponent> {
+        let (w, h) = (self.width as usize, self.height as usize);
+        let data = &self.data;
+        let mut components = Vec::new();
+        let mut visited = vec![false; w * h];
+        let mut stack = Vec::new();
+        for start in 0..w * h {
+            if visited[start] || data[start] == BIOME_UNASSIGNED {
+                continue;
+            }
+            let biome = data[start];
+            visited[start] = true;
+            stack.push(start);
+            let mut size = 0usize;
+            let (mut sum_x, mut sum_y) = (0u64, 0u64);
+            let mut bbox = [u32::MAX, u32::MAX, 0, 0];
+            while let Some(i) = stack.pop() {
+                let (x, y) = (i % w, i / w);
+                size += 1;
+                sum_x += x as u64;
+                sum_y += y as u64;
+                bbox = [
+                    bbox[0].min(x as u32), bbox[1].min(y as u32),
+                    bbox[2].max(x as u32 + 1), bbox[3].max(y as u32 + 1),
+                ];
+                let neighbors = [
+                    (x > 0).then(|| i - 1),
+                    (x + 1 < w).then(|| i + 1),
+                    (y > 0).then(|| i - w),
+                    (y + 1 < h).then(|| i + w),
+                ];
+                for n in neighbors.into_iter().flatten() {
+                    if !visited[n] && data[n] == biome {
+                        visited[n] = true;
+                        stack.push(n);
+                    }
+                }
+            }
+            components.push(BiomeComponent {
+                biome,
+                size,
+                bbox,
+                // 取格子中心
+                centroid: (
+                    sum_x as f32 / size as f32 + 0.5,
+                    sum_y as f32 / size as f32 + 0.5,
+                ),
+            });
+        }
+        components
+    }
+
+    /// 环境边界总长：4 邻接的两格 biome 不同即记 1 格边界（等于 [`Self::adjacency`] 各项之和）。
+    ///
+    /// 单遍扫描，每格只与右侧 / 下方比较；碎片化程度越高数值越大。
+    pub fn boundary_length(&self) -> u64 {
+        let w = self.width as usize;
+        let h = self.height as usize;
+        if w == 0 || h == 0 {
+            return 0;
+        }
+        (0..h)
+            .into_par_iter()
+            .map(|y| {
+                let row = &self.data[y * w..(y + 1) * w];
+                let mut n = row.windows(2).filter(|p| p[0] != p[1]).count() as u64;
+                if y + 1 < h {
+                    let below = &self.data[(y + 1) * w..(y + 2) * w];
+                    n += row.iter().zip(below).filter(|(a, b)| a != b).count() as u64;
+                }
+                n
+            })
+            .sum()
+    }
 }
 
 // ── 环境上下文（组合信息）──────────────────────────────
@@ -179,3 +436,67 @@ fn get_layer_at(y: u32, layers: &[LayerDefinition], world_height: u32) -> Option
     }
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::{RngCore, SeedableRng};
+
+    use super::{build_biome_definitions, pick_variant, BiomeDefinition, BiomeId, BiomeMap};
+    use crate::config::biome::load_biomes_config;
+
+    fn biomes_with_jungle_variant(base_weight: u32, variant_weight: u32) -> (Vec<BiomeDefinition>, BiomeId, BiomeId) {
+        let mut biomes = build_biome_definitions(&load_biomes_config().unwrap());
+        let id = |key: &str| biomes.iter().find(|b| b.key == key).unwrap().id;
+        let (jungle, wet) = (id("jungle"), id("jungle_wet"));
+        let bdef = biomes.iter_mut().find(|b| b.id == jungle).unwrap();
+        bdef.base_weight = base_weight;
+        bdef.variants = vec![(wet, variant_weight)];
+        (biomes, jungle, wet)
+    }
+
+    #[test]
+    fn no_rng_draw_without_active_variants() {
+        let (biomes, jungle, _) = biomes_with_jungle_variant(100, 0);
+        let forest = biomes.iter().find(|b| b.key == "forest").unwrap().id;
+        for base in [forest, jungle] {
+            let mut rng = StdRng::seed_from_u64(9);
+            let mut untouched = rng.clone();
+            assert_eq!(pick_variant(&biomes, base, &mut rng), base);
+            assert_eq!(rng.next_u64(), untouched.next_u64(), "环境 {base} 没有启用的变体，不应消耗 RNG");
+        }
+    }
+
+    #[test]
+    fn variants_are_drawn_by_weight() {
+        let (biomes, jungle, wet) = biomes_with_jungle_variant(300, 100);
+        let mut rng = StdRng::seed_from_u64(1);
+        let draws = 20_000;
+        let wet_count = (0..draws).filter(|_| pick_variant(&biomes, jungle, &mut rng) == wet).count();
+        let share = wet_count as f64 / draws as f64;
+        assert!((share - 0.25).abs() < 0.015, "权重 300:100 时变体占比应约为 25%，实际 {share}");
+
+        let (all_variant, jungle, wet) = biomes_with_jungle_variant(0, 1);
+        assert!((0..100).all(|_| pick_variant(&all_variant, jungle, &mut rng) == wet), "基础权重为 0 时总是抽到变体");
+    }
+
+    #[test]
+    fn boundary_length_is_the_sum_of_adjacency_counts() {
+        let mut bm = BiomeMap::new_filled(7, 5, 1);
+        assert_eq!(bm.boundary_length(), 0, "单一环境没有边界");
+
+        // 一个 2×2 方块（周长 8 条格边）+ 右下角一格（2 条）
+        for (x, y) in [(1, 1), (2, 1), (1, 2), (2, 2)] {
+            bm.set(x, y, 2);
+        }
+        bm.set(6, 4, 3);
+        assert_eq!(bm.boundary_length(), 10);
+
+        let mut rng = StdRng::seed_from_u64(4);
+        for tile in bm.data_mut() {
+            *tile = (rng.next_u32() % 4) as BiomeId;
+        }
+        let total: u64 = bm.adjacency().iter().map(|(_, n)| n).sum();
+        assert_eq!(bm.boundary_length(), total);
+    }
+}
@@ -0,0 +1,159 @@
+//! # 游程编码环境地图
+//!
+//! [`BiomeMap`] 每格 1 字节，大世界约 20 MB。本生成器的环境都是大块连续区域，
+//! 每行只有几十段，按行游程编码后通常只需几 KB～几百 KB。
+//!
+//! `RleBiomeMap` 提供与 `BiomeMap` 相同语义的 `get` / `set`，以及与平铺形式互相转换
+//! （渲染与现有步骤仍使用平铺形式）。`Lwd --bench` 会对每个尺寸报告两种形式的内存、转换与逐格读写耗时。
+
+use super::biome::{BiomeId, BiomeMap, BIOME_UNASSIGNED};
+
+/// 按行游程编码的环境地图
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RleBiomeMap {
+    pub width: u32,
+    pub height: u32,
+    /// 每行的游程 `(起始 x, 环境)`：按起始 x 升序，首段从 0 开始，相邻两段环境不同
+    rows: Vec<Vec<(u32, BiomeId)>>,
+}
+
+impl RleBiomeMap {
+    /// 创建一个全部填充为指定 biome 的地图
+    pub fn new_filled(width: u32, height: u32, fill: BiomeId) -> Self {
+        Self {
+            width,
+            height,
+            rows: (0..height).map(|_| vec![(0, fill)]).collect(),
+        }
+    }
+
+    /// 从平铺形式编码
+    pub fn from_flat(map: &BiomeMap) -> Self {
+        let rows = map.data()
+            .chunks(map.width.max(1) as usize)
+            .take(map.height as usize)
+            .map(|row| {
+                let mut runs: Vec<(u32, BiomeId)> = Vec::new();
+                for (x, &biome) in row.iter().enumerate() {
+                    if runs.last().is_none_or(|&(_, last)| last != biome) {
+                        runs.push((x as u32, biome));
+                    }
+                }
+                runs
+            })
+            .collect();
+        Self { width: map.width, height: map.height, rows }
+    }
+
+    /// 解码为平铺形式（用于渲染与现有步骤）
+    pub fn to_flat(&self) -> BiomeMap {
+        let mut map = BiomeMap::new_filled(self.width, self.height, BIOME_UNASSIGNED);
+        let width = self.width as usize;
+        if width == 0 {
+            return map;
+        }
+        for (row, runs) in map.data_mut().chunks_mut(width).zip(&self.rows) {
+            for (i, &(start, biome)) in runs.iter().enumerate() {
+                let end = runs.get(i + 1).map_or(width, |&(next, _)| next as usize);
+                row[start as usize..end].fill(biome);
+            }
+        }
+        map
+    }
+
+    /// (x, y) 是否在地图范围内
+    #[inline]
+    pub fn in_bounds(&self, x: u32, y: u32) -> bool {
+        x < self.width && y < self.height
+    }
+
+    /// 第 `y` 行中包含 `x` 的游程下标
+    fn run_index(runs: &[(u32, BiomeId)], x: u32) -> usize {
+        runs.partition_point(|&(start, _)| start <= x) - 1
+    }
+
+    /// 获取 (x, y) 处的 biome（行内二分查找）
+    ///
+    /// 越界约定同 [`BiomeMap::get`]：debug 构建下 panic，release 构建下返回 `BIOME_UNASSIGNED`。
+    pub fn get(&self, x: u32, y: u32) -> BiomeId {
+        debug_assert!(self.in_bounds(x, y), "RleBiomeMap::get 越界: ({x}, {y}) 超出地图 {}×{}", self.width, self.height);
+        if !self.in_bounds(x, y) {
+            return BIOME_UNASSIGNED;
+        }
+        let runs = &self.rows[y as usize];
+        runs[Self::run_index(runs, x)].1
+    }
+
+    /// 设置 (x, y) 处的 biome：拆分所在游程，并与相同环境的相邻游程合并
+    ///
+    /// 越界约定同 [`BiomeMap::set`]：debug 构建下 panic，release 构建下忽略。
+    pub fn set(&mut self, x: u32, y: u32, biome: BiomeId) {
+        debug_assert!(self.in_bounds(x, y), "RleBiomeMap::set 越界: ({x}, {y}) 超出地图 {}×{}", self.width, self.height);
+        if !self.in_bounds(x, y) {
+            return;
+        }
+        let width = self.width;
+        let runs = &mut self.rows[y as usize];
+        let i = Self::run_index(runs, x);
+        let (start, old) = runs[i];
+        if old == biome {
+            return;
+        }
+        let end = runs.get(i + 1).map_or(width, |&(next, _)| next);
+
+        // 原游程拆成「前段 + 新格 + 后段」，前后段可能为空；用定长数组避免每次写入都分配
+        let replacement = [
+            (x > start).then_some((start, old)),
+            Some((x, biome)),
+            (x + 1 < end).then_some((x + 1, old)),
+        ];
+        let j = i + usize::from(x > start);
+        runs.splice(i..=i, replacement.into_iter().flatten());
+
+        // 与相邻的同环境游程合并，保持「相邻两段环境不同」
+        if runs.get(j + 1).is_some_and(|&(_, next)| next == biome) {
+            runs.remove(j + 1);
+        }
+        if j > 0 && runs[j - 1].1 == biome {
+            runs.remove(j);
+        }
+    }
+
+    /// 游程总数
+    pub fn run_count(&self) -> usize {
+        self.rows.iter().map(Vec::len).sum()
+    }
+
+    /// 估算占用的堆内存（字节）：每行的 `Vec` 头与各段游程
+    pub fn memory_bytes(&self) -> usize {
+        self.rows.len() * size_of::<Vec<(u32, BiomeId)>>()
+            + self.rows.iter().map(|r| r.capacity() * size_of::<(u32, BiomeId)>()).sum::<usize>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_and_get_match_the_flat_map() {
+        let (w, h) = (37, 5);
+        let mut flat = BiomeMap::new_filled(w, h, 1);
+        let mut rle = RleBiomeMap::new_filled(w, h, 1);
+        // 伪随机写入若干格，覆盖拆分、合并与行首行尾的情况
+        let mut state = 12345u32;
+        for _ in 0..2000 {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            let (x, y, biome) = ((state >> 8) % w, (state >> 16) % h, (state >> 4 & 3) as BiomeId);
+            flat.set(x, y, biome);
+            rle.set(x, y, biome);
+        }
+        assert_eq!(rle.to_flat().data(), flat.data());
+        assert_eq!(RleBiomeMap::from_flat(&flat), rle, "逐格写入后应与重新编码的结果一致（游程已合并）");
+        for y in 0..h {
+            for x in 0..w {
+                assert_eq!(rle.get(x, y), flat.get(x, y));
+            }
+        }
+    }
+}
@@ -0,0 +1,51 @@
+//! # 泰拉瑞亚坐标换算
+//!
+//! 本工具的坐标以左上角为原点、单位为格（tile）。游戏内的罗盘 / 深度计读数则是：
+//!
+//! - 水平：`东西英尺 = (x - 世界宽度 / 2) × 每格英尺数`，正数为东，负数为西
+//! - 垂直：`深度英尺 = (y - 地表基准行) × 每格英尺数`，正数在地表以下
+//!
+//! 地表基准行对应游戏中的 `worldSurface`，即地下层（`underground`）的起始行。
+//! 泰拉瑞亚中每格为 2 英尺。
+
+/// 游戏默认的每格英尺数
+pub const DEFAULT_FEET_PER_TILE: f32 = 2.0;
+
+/// 作为地表基准的层级 key（其起始行即 `worldSurface`）
+pub const SURFACE_REFERENCE_LAYER: &str = "underground";
+
+/// 出生点：世界水平中心、地表基准行，即罗盘 / 深度计读数的零点。
+///
+/// 目前生成只划分环境、不放置地形方块，因此不按地表高度修正。
+pub fn spawn_point(world_width: u32, surface_row: u32) -> (u32, u32) {
+    (world_width / 2, surface_row)
+}
+
+/// 将格坐标换算为 (东西英尺, 深度英尺)
+pub fn to_terraria_feet(
+    x: u32,
+    y: u32,
+    world_width: u32,
+    surface_row: u32,
+    feet_per_tile: f32,
+) -> (i64, i64) {
+    let fpt = feet_per_tile as f64;
+    let east = (x as f64 - world_width as f64 / 2.0) * fpt;
+    let depth = (y as f64 - surface_row as f64) * fpt;
+    (east.round() as i64, depth.round() as i64)
+}
+
+/// 按游戏罗盘 / 深度计的样式格式化，例如 `120' 东 · 36' 地下`
+pub fn format_terraria_feet(east: i64, depth: i64) -> String {
+    let horizontal = match east {
+        0 => "中心".to_string(),
+        e if e > 0 => format!("{e}' 东"),
+        e => format!("{}' 西", -e),
+    };
+    let vertical = match depth {
+        0 => "地平面".to_string(),
+        d if d > 0 => format!("{d}' 地下"),
+        d => format!("{}' 地表", -d),
+    };
+    format!("{horizontal} · {vertical}")
+}
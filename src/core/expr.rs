@@ -0,0 +1,407 @@
+//! # 迷你表达式
+//!
+//! 沙箱「隐函数」形状使用的安全表达式语言：只有数字、变量、运算符与少量数学函数，
+//! 没有赋值、循环或外部访问；源码长度与嵌套深度都有上限，求值不会失控。
+//!
+//! 语法（优先级从低到高）：
+//! - `||`、`&&`：逻辑或 / 与（非零为真）
+//! - `==` `!=` `<` `<=` `>` `>=`：比较，结果为 1 或 0
+//! - `+` `-`，`*` `/` `%`
+//! - 一元 `-` `!`
+//! - `^`：乘方（右结合，`-x^2` 即 `-(x^2)`）
+//! - 数字、变量、常量 `pi` / `e`、括号、函数调用
+//!
+//! 函数：`abs` `sqrt` `sin` `cos` `tan` `floor` `ceil` `round` `exp` `ln`（单参数），
+//! `min` `max` `atan2`（双参数）。
+
+/// 源码最大长度（字符）
+const MAX_SOURCE_LEN: usize = 256;
+/// 最大嵌套深度（括号 / 一元运算 / 函数调用）
+const MAX_DEPTH: usize = 32;
+
+/// 已解析的表达式；变量在解析时绑定为 `vars` 中的下标
+#[derive(Debug, Clone)]
+pub struct Expr {
+    root: Node,
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Num(f64),
+    Var(usize),
+    Neg(Box<Node>),
+    Not(Box<Node>),
+    Bin(BinOp, Box<Node>, Box<Node>),
+    Call1(fn(f64) -> f64, Box<Node>),
+    Call2(fn(f64, f64) -> f64, Box<Node>, Box<Node>),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum BinOp {
+    Or,
+    And,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Pow,
+}
+
+impl Expr {
+    /// 解析 `source`；`vars` 为允许出现的变量名，求值时按同样顺序传值。
+    pub fn parse(source: &str, vars: &[&str]) -> Result<Self, String> {
+        if source.chars().count() > MAX_SOURCE_LEN {
+            return Err(format!("表达式过长（最多 {MAX_SOURCE_LEN} 个字符）"));
+        }
+        let tokens = tokenize(source)?;
+        if tokens.is_empty() {
+            return Err("表达式为空".to_string());
+        }
+        let mut parser = Parser { tokens, pos: 0, depth: 0, vars };
+        let root = parser.parse_or()?;
+        if let Some(token) = parser.tokens.get(parser.pos) {
+            return Err(format!("多余的 `{}`", token.text()));
+        }
+        Ok(Self { root })
+    }
+
+    /// 以 `values`（与解析时的 `vars` 一一对应）求值
+    pub fn eval(&self, values: &[f64]) -> f64 {
+        eval(&self.root, values)
+    }
+
+    /// 求值结果是否为真（非零且不是 NaN）
+    pub fn is_true(&self, values: &[f64]) -> bool {
+        let v = self.eval(values);
+        v != 0.0 && !v.is_nan()
+    }
+}
+
+fn truth(v: f64) -> bool {
+    v != 0.0 && !v.is_nan()
+}
+
+fn flag(b: bool) -> f64 {
+    if b { 1.0 } else { 0.0 }
+}
+
+fn eval(node: &Node, values: &[f64]) -> f64 {
+    match node {
+        Node::Num(v) => *v,
+        Node::Var(i) => values.get(*i).copied().unwrap_or(f64::NAN),
+        Node::Neg(a) => -eval(a, values),
+        Node::Not(a) => flag(!truth(eval(a, values))),
+        Node::Call1(f, a) => f(eval(a, values)),
+        Node::Call2(f, a, b) => f(eval(a, values), eval(b, values)),
+        Node::Bin(op, a, b) => {
+            let a = eval(a, values);
+            // 逻辑运算短路
+            match op {
+                BinOp::Or if truth(a) => return 1.0,
+                BinOp::And if !truth(a) => return 0.0,
+                _ => {}
+            }
+            let b = eval(b, values);
+            match op {
+                BinOp::Or | BinOp::And => flag(truth(b)),
+                BinOp::Eq => flag(a == b),
+                BinOp::Ne => flag(a != b),
+                BinOp::Lt => flag(a < b),
+                BinOp::Le => flag(a <= b),
+                BinOp::Gt => flag(a > b),
+                BinOp::Ge => flag(a >= b),
+                BinOp::Add => a + b,
+                BinOp::Sub => a - b,
+                BinOp::Mul => a * b,
+                BinOp::Div => a / b,
+                BinOp::Rem => a.rem_euclid(b),
+                BinOp::Pow => a.powf(b),
+            }
+        }
+    }
+}
+
+// ── 词法 ─────────────────────────────────────────────────
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Num(f64),
+    Ident(String),
+    Op(&'static str),
+    LParen,
+    RParen,
+    Comma,
+}
+
+impl Token {
+    fn text(&self) -> String {
+        match self {
+            Token::Num(v) => v.to_string(),
+            Token::Ident(name) => name.clone(),
+            Token::Op(op) => op.to_string(),
+            Token::LParen => "(".to_string(),
+            Token::RParen => ")".to_string(),
+            Token::Comma => ",".to_string(),
+        }
+    }
+}
+
+/// 运算符（长的在前，保证 `<=` 不被拆成 `<` `=`）
+const OPERATORS: [&str; 17] = [
+    "||", "&&", "==", "!=", "<=", ">=", "<", ">", "+", "-", "*", "/", "%", "^", "!", "(", ")",
+];
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() || c == '.' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            let value = text.parse::<f64>().map_err(|_| format!("无效的数字 `{text}`"))?;
+            tokens.push(Token::Num(value));
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else if c == ',' {
+            tokens.push(Token::Comma);
+            i += 1;
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+            let Some(op) = OPERATORS.iter().find(|op| rest.starts_with(**op)) else {
+                return Err(format!("无法识别的字符 `{c}`"));
+            };
+            tokens.push(match *op {
+                "(" => Token::LParen,
+                ")" => Token::RParen,
+                op => Token::Op(op),
+            });
+            i += op.chars().count();
+        }
+    }
+    Ok(tokens)
+}
+
+// ── 语法 ─────────────────────────────────────────────────
+
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+    vars: &'a [&'a str],
+}
+
+impl Parser<'_> {
+    fn peek_op(&self) -> Option<&'static str> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Op(op)) => Some(op),
+            _ => None,
+        }
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), String> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => Err(format!("应为 `{}`，实际为 `{}`", expected.text(), token.text())),
+            None => Err(format!("缺少 `{}`", expected.text())),
+        }
+    }
+
+    fn enter(&mut self) -> Result<(), String> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(format!("嵌套过深（最多 {MAX_DEPTH} 层）"));
+        }
+        Ok(())
+    }
+
+    /// 左结合的二元运算层：`next` 解析更高优先级的一层
+    fn binary(
+        &mut self,
+        ops: &[(&str, BinOp)],
+        next: fn(&mut Self) -> Result<Node, String>,
+    ) -> Result<Node, String> {
+        let mut left = next(self)?;
+        while let Some(&(_, op)) = self.peek_op().and_then(|p| ops.iter().find(|(s, _)| *s == p)) {
+            self.pos += 1;
+            let right = next(self)?;
+            left = Node::Bin(op, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_or(&mut self) -> Result<Node, String> {
+        self.binary(&[("||", BinOp::Or)], Self::parse_and)
+    }
+
+    fn parse_and(&mut self) -> Result<Node, String> {
+        self.binary(&[("&&", BinOp::And)], Self::parse_cmp)
+    }
+
+    fn parse_cmp(&mut self) -> Result<Node, String> {
+        self.binary(
+            &[
+                ("==", BinOp::Eq),
+                ("!=", BinOp::Ne),
+                ("<", BinOp::Lt),
+                ("<=", BinOp::Le),
+                (">", BinOp::Gt),
+                (">=", BinOp::Ge),
+            ],
+            Self::parse_add,
+        )
+    }
+
+    fn parse_add(&mut self) -> Result<Node, String> {
+        self.binary(&[("+", BinOp::Add), ("-", BinOp::Sub)], Self::parse_mul)
+    }
+
+    fn parse_mul(&mut self) -> Result<Node, String> {
+        self.binary(&[("*", BinOp::Mul), ("/", BinOp::Div), ("%", BinOp::Rem)], Self::parse_unary)
+    }
+
+    fn parse_unary(&mut self) -> Result<Node, String> {
+        match self.peek_op() {
+            Some(op @ ("-" | "!")) => {
+                self.pos += 1;
+                self.enter()?;
+                let inner = self.parse_unary()?;
+                self.depth -= 1;
+                Ok(if op == "-" { Node::Neg(Box::new(inner)) } else { Node::Not(Box::new(inner)) })
+            }
+            _ => self.parse_pow(),
+        }
+    }
+
+    fn parse_pow(&mut self) -> Result<Node, String> {
+        let base = self.parse_primary()?;
+        if self.peek_op() != Some("^") {
+            return Ok(base);
+        }
+        self.pos += 1;
+        self.enter()?;
+        // 右结合：指数可以带一元负号（`2^-1`）
+        let exponent = self.parse_unary()?;
+        self.depth -= 1;
+        Ok(Node::Bin(BinOp::Pow, Box::new(base), Box::new(exponent)))
+    }
+
+    fn parse_primary(&mut self) -> Result<Node, String> {
+        match self.next() {
+            Some(Token::Num(v)) => Ok(Node::Num(v)),
+            Some(Token::LParen) => {
+                self.enter()?;
+                let inner = self.parse_or()?;
+                self.expect(Token::RParen)?;
+                self.depth -= 1;
+                Ok(inner)
+            }
+            Some(Token::Ident(name)) => {
+                if self.tokens.get(self.pos) == Some(&Token::LParen) {
+                    self.pos += 1;
+                    return self.parse_call(&name);
+                }
+                if let Some(i) = self.vars.iter().position(|v| *v == name) {
+                    return Ok(Node::Var(i));
+                }
+                match name.as_str() {
+                    "pi" => Ok(Node::Num(std::f64::consts::PI)),
+                    "e" => Ok(Node::Num(std::f64::consts::E)),
+                    _ => Err(format!("未知的变量 `{name}`（可用: {}）", self.vars.join(", "))),
+                }
+            }
+            Some(token) => Err(format!("意外的 `{}`", token.text())),
+            None => Err("表达式不完整".to_string()),
+        }
+    }
+
+    /// 函数调用（已消耗左括号）
+    fn parse_call(&mut self, name: &str) -> Result<Node, String> {
+        self.enter()?;
+        let unary: Option<fn(f64) -> f64> = match name {
+            "abs" => Some(f64::abs),
+            "sqrt" => Some(f64::sqrt),
+            "sin" => Some(f64::sin),
+            "cos" => Some(f64::cos),
+            "tan" => Some(f64::tan),
+            "floor" => Some(f64::floor),
+            "ceil" => Some(f64::ceil),
+            "round" => Some(f64::round),
+            "exp" => Some(f64::exp),
+            "ln" => Some(f64::ln),
+            _ => None,
+        };
+        let binary: Option<fn(f64, f64) -> f64> = match name {
+            "min" => Some(f64::min),
+            "max" => Some(f64::max),
+            "atan2" => Some(f64::atan2),
+            _ => None,
+        };
+        let node = if let Some(f) = unary {
+            let a = self.parse_or()?;
+            Node::Call1(f, Box::new(a))
+        } else if let Some(f) = binary {
+            let a = self.parse_or()?;
+            self.expect(Token::Comma)?;
+            let b = self.parse_or()?;
+            Node::Call2(f, Box::new(a), Box::new(b))
+        } else {
+            return Err(format!("未知的函数 `{name}`"));
+        };
+        self.expect(Token::RParen)?;
+        self.depth -= 1;
+        Ok(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval_xy(source: &str, x: f64, y: f64) -> f64 {
+        Expr::parse(source, &["x", "y"]).unwrap().eval(&[x, y])
+    }
+
+    #[test]
+    fn precedence_and_functions() {
+        assert_eq!(eval_xy("1 + 2 * 3", 0.0, 0.0), 7.0);
+        assert_eq!(eval_xy("-x^2", 3.0, 0.0), -9.0);
+        assert_eq!(eval_xy("2^3^2", 0.0, 0.0), 512.0);
+        assert_eq!(eval_xy("max(x, y) - min(x, y)", 2.0, 5.0), 3.0);
+        assert_eq!(eval_xy("x*x + y*y < 25", 3.0, 3.0), 1.0);
+        assert_eq!(eval_xy("x < 0 || !(y >= 1)", 1.0, 0.0), 1.0);
+        assert_eq!(eval_xy("-7 % 3", 0.0, 0.0), 2.0);
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        for source in ["", "x +", "(x", "x y", "foo(x)", "z", "x $ y", "min(x)"] {
+            assert!(Expr::parse(source, &["x", "y"]).is_err(), "`{source}` 应当解析失败");
+        }
+        let deep = format!("{}x{}", "(".repeat(40), ")".repeat(40));
+        assert!(Expr::parse(&deep, &["x"]).is_err());
+    }
+}
@@ -30,10 +30,13 @@
 //! });
 //! ```
 
+use rand::Rng;
 use rayon::prelude::*;
+use std::cell::Cell;
 use std::sync::atomic::{AtomicI64, Ordering};
 
 use super::biome::{BiomeId, BiomeMap};
+use super::expr::Expr;
 
 /// 全局可配置的并行化像素阈值（由 EngineConfig 在启动时设置）
 static PARALLEL_PIXEL_THRESHOLD: AtomicI64 = AtomicI64::new(50_000);
@@ -48,6 +51,43 @@ pub fn set_parallel_threshold(value: i64) {
     PARALLEL_PIXEL_THRESHOLD.store(value, Ordering::Relaxed);
 }
 
+/// 填充函数走并行 / 串行路径的次数与面积（像素），用于观察 `parallel_threshold` 的实际效果
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FillPathStats {
+    pub parallel: u32,
+    pub serial: u32,
+    pub parallel_area: u64,
+    pub serial_area: u64,
+}
+
+thread_local! {
+    /// 按线程计数：后台生成（A/B 对比、参数拟合等）与前台流水线互不干扰
+    static FILL_STATS: Cell<FillPathStats> = const { Cell::new(FillPathStats {
+        parallel: 0, serial: 0, parallel_area: 0, serial_area: 0,
+    }) };
+}
+
+/// 记录一次填充走的路径
+pub fn record_fill_path(area: i64, parallel: bool) {
+    FILL_STATS.with(|stats| {
+        let mut s = stats.get();
+        let area = area.max(0) as u64;
+        if parallel {
+            s.parallel += 1;
+            s.parallel_area += area;
+        } else {
+            s.serial += 1;
+            s.serial_area += area;
+        }
+        stats.set(s);
+    });
+}
+
+/// 取出当前线程累计的填充路径统计并清零（流水线在每步前后各调用一次）
+pub fn take_fill_stats() -> FillPathStats {
+    FILL_STATS.with(|stats| stats.take())
+}
+
 // ═══════════════════════════════════════════════════════════
 // 形状记录（用于几何预览窗口）
 // ═══════════════════════════════════════════════════════════
@@ -186,6 +226,13 @@ pub trait Shape: Sync {
 
     /// 返回形状类型名称（用于 UI 显示）
     fn type_name(&self) -> &'static str;
+
+    /// 格子 (x, y) 被形状覆盖的比例（0.0~1.0），用于软边填充。
+    ///
+    /// 默认按 `contains` 取 0 或 1；曲线边界的形状（椭圆）覆写为子像素估计。
+    fn coverage(&self, x: i32, y: i32) -> f64 {
+        if self.contains(x, y) { 1.0 } else { 0.0 }
+    }
 }
 
 // ═══════════════════════════════════════════════════════════
@@ -264,6 +311,34 @@ impl Shape for Ellipse {
     }
 
     fn type_name(&self) -> &'static str { "椭圆" }
+
+    /// 以格子坐标为中心的单位方格内 4×4 子采样；远离边界的格子直接返回 0 / 1
+    fn coverage(&self, x: i32, y: i32) -> f64 {
+        if self.rx <= 0.0 || self.ry <= 0.0 {
+            return 0.0;
+        }
+        let norm = |px: f64, py: f64, grow: f64| {
+            let dx = (px - self.cx) / (self.rx + grow);
+            let dy = (py - self.cy) / (self.ry + grow);
+            dx * dx + dy * dy
+        };
+        let (fx, fy) = (x as f64, y as f64);
+        if self.rx > 1.0 && self.ry > 1.0 && norm(fx, fy, -1.0) <= 1.0 {
+            return 1.0;
+        }
+        if norm(fx, fy, 1.0) > 1.0 {
+            return 0.0;
+        }
+        const N: usize = 4;
+        let inside = (0..N * N)
+            .filter(|i| {
+                let sx = fx - 0.5 + ((i % N) as f64 + 0.5) / N as f64;
+                let sy = fy - 0.5 + ((i / N) as f64 + 0.5) / N as f64;
+                norm(sx, sy, 0.0) <= 1.0
+            })
+            .count();
+        inside as f64 / (N * N) as f64
+    }
 }
 
 /// 梯形（左右边界随 y 线性变化）
@@ -344,6 +419,103 @@ impl Shape for Column {
     fn type_name(&self) -> &'static str { "列" }
 }
 
+/// 隐函数区域：表达式在 `(x - cx, y - cy)` 处为真的格，限定在 `bounds`（通常为整个世界）内。
+///
+/// 表达式可用变量见 [`Implicit::VARS`]：`x` / `y` 为相对原点的坐标，`r` 为可调参数，
+/// `w` / `h` 为 `bounds` 的宽高。
+#[derive(Debug, Clone)]
+pub struct Implicit {
+    pub expr: Expr,
+    pub cx: f64,
+    pub cy: f64,
+    pub r: f64,
+    pub bounds: BoundingBox,
+}
+
+impl Implicit {
+    /// 表达式中可用的变量（按求值时传值的顺序）
+    pub const VARS: [&'static str; 5] = ["x", "y", "r", "w", "h"];
+
+    /// 解析 `source` 创建隐函数形状，语法错误时返回说明
+    pub fn new(source: &str, cx: f64, cy: f64, r: f64, bounds: BoundingBox) -> Result<Self, String> {
+        let expr = Expr::parse(source, &Self::VARS)?;
+        Ok(Self { expr, cx, cy, r, bounds })
+    }
+}
+
+impl Shape for Implicit {
+    fn contains(&self, x: i32, y: i32) -> bool {
+        let b = self.bounds;
+        if x < b.x_min || x >= b.x_max || y < b.y_min || y >= b.y_max {
+            return false;
+        }
+        self.expr.is_true(&[
+            x as f64 - self.cx,
+            y as f64 - self.cy,
+            self.r,
+            (b.x_max - b.x_min) as f64,
+            (b.y_max - b.y_min) as f64,
+        ])
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        self.bounds
+    }
+
+    fn type_name(&self) -> &'static str { "隐函数" }
+}
+
+/// 图片模板：把一张图片缩放到 `bounds` 上，亮度低于 `threshold` 的像素覆盖的格子在形状内。
+///
+/// 像素按最近邻采样；透明像素（alpha < 128）视为白色，永远不在形状内。
+#[derive(Debug, Clone)]
+pub struct Stencil {
+    /// 图片宽高（像素）
+    pub width: usize,
+    pub height: usize,
+    /// 每个像素的亮度（0 = 黑，255 = 白），行优先
+    pub luma: Vec<u8>,
+    /// 亮度阈值：`luma < threshold` 的像素参与填充
+    pub threshold: u8,
+    /// 图片在世界中的放置范围
+    pub bounds: BoundingBox,
+}
+
+impl Stencil {
+    /// 从 RGBA8 像素创建模板（`rgba.len()` 应为 `width * height * 4`）
+    pub fn from_rgba(width: usize, height: usize, rgba: &[u8], threshold: u8, bounds: BoundingBox) -> Self {
+        let luma = rgba.chunks_exact(4)
+            .take(width * height)
+            .map(|p| {
+                if p[3] < 128 {
+                    255
+                } else {
+                    ((p[0] as u32 * 299 + p[1] as u32 * 587 + p[2] as u32 * 114) / 1000) as u8
+                }
+            })
+            .collect();
+        Self { width, height, luma, threshold, bounds }
+    }
+}
+
+impl Shape for Stencil {
+    fn contains(&self, x: i32, y: i32) -> bool {
+        let b = self.bounds;
+        if x < b.x_min || x >= b.x_max || y < b.y_min || y >= b.y_max || self.luma.is_empty() {
+            return false;
+        }
+        let px = (x - b.x_min) as usize * self.width / (b.x_max - b.x_min) as usize;
+        let py = (y - b.y_min) as usize * self.height / (b.y_max - b.y_min) as usize;
+        self.luma.get(py * self.width + px).is_some_and(|&l| l < self.threshold)
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        self.bounds
+    }
+
+    fn type_name(&self) -> &'static str { "图片模板" }
+}
+
 // ═══════════════════════════════════════════════════════════
 // 组合形状
 // ═══════════════════════════════════════════════════════════
@@ -364,6 +536,10 @@ impl<A: Shape, B: Shape> Shape for Union<A, B> {
     }
 
     fn type_name(&self) -> &'static str { "并集" }
+
+    fn coverage(&self, x: i32, y: i32) -> f64 {
+        self.a.coverage(x, y).max(self.b.coverage(x, y))
+    }
 }
 
 /// 交集：A ∩ B
@@ -382,6 +558,10 @@ impl<A: Shape, B: Shape> Shape for Intersect<A, B> {
     }
 
     fn type_name(&self) -> &'static str { "交集" }
+
+    fn coverage(&self, x: i32, y: i32) -> f64 {
+        self.a.coverage(x, y).min(self.b.coverage(x, y))
+    }
 }
 
 /// 差集：A - B（在 A 内但不在 B 内）
@@ -400,6 +580,10 @@ impl<A: Shape, B: Shape> Shape for Subtract<A, B> {
     }
 
     fn type_name(&self) -> &'static str { "差集" }
+
+    fn coverage(&self, x: i32, y: i32) -> f64 {
+        self.a.coverage(x, y).min(1.0 - self.b.coverage(x, y))
+    }
 }
 
 // ═══════════════════════════════════════════════════════════
@@ -442,6 +626,7 @@ pub fn fill_biome(shape: &dyn Shape, bm: &mut BiomeMap, biome: BiomeId) {
     let y1 = bb.y_max.min(bm.height as i32);
 
     let area = (x1 - x0) as i64 * (y1 - y0) as i64;
+    record_fill_path(area, area >= parallel_threshold());
     if area >= parallel_threshold() {
         fill_biome_parallel(shape, bm, biome, x0, y0, x1, y1);
     } else {
@@ -511,6 +696,7 @@ pub fn fill_biome_if(
     let y1 = bb.y_max.min(bm.height as i32);
 
     let area = (x1 - x0) as i64 * (y1 - y0) as i64;
+    record_fill_path(area, area >= parallel_threshold());
     if area >= parallel_threshold() {
         fill_biome_if_parallel(shape, bm, biome, &filter, x0, y0, x1, y1);
     } else {
@@ -559,6 +745,94 @@ fn fill_biome_if_parallel(
     });
 }
 
+/// 软边条件填充：完全覆盖的格子照常填充，边界上部分覆盖的格子按覆盖比例以 `rng` 抽样填充，
+/// 使直角阶梯状的曲线边缘抖散到相邻环境中。
+///
+/// 逐行串行遍历（包围盒外扩 1 格），抽样顺序固定，同一 RNG 状态得到相同结果。
+pub fn fill_biome_soft_if(
+    shape: &dyn Shape,
+    bm: &mut BiomeMap,
+    biome: BiomeId,
+    rng: &mut impl Rng,
+    filter: impl Fn(BiomeId) -> bool,
+) {
+    let bb = shape.bounding_box();
+    let x0 = (bb.x_min - 1).max(0);
+    let y0 = (bb.y_min - 1).max(0);
+    let x1 = (bb.x_max + 1).min(bm.width as i32);
+    let y1 = (bb.y_max + 1).min(bm.height as i32);
+    record_fill_path((x1 - x0).max(0) as i64 * (y1 - y0).max(0) as i64, false);
+
+    for y in y0..y1 {
+        for x in x0..x1 {
+            let coverage = shape.coverage(x, y);
+            if coverage <= 0.0 || (coverage < 1.0 && !rng.gen_bool(coverage)) {
+                continue;
+            }
+            if filter(bm.get(x as u32, y as u32)) {
+                bm.set(x as u32, y as u32, biome);
+            }
+        }
+    }
+}
+
+/// 只填充形状的轮廓（描边）：形状内、且 `thickness` 格内存在形状外像素的格子
+///
+/// 距离按 Chebyshev 度量（8 邻接），`thickness = 1` 即"至少有一个邻居在形状外"，
+/// 矩形上得到宽度恰为 `thickness` 的环。地图边界之外只要仍在形状内就不算"外部"。
+/// 用于环境过渡边界、洞穴墙壁等（沙箱「应用到世界」的描边选项即调用此函数）。
+/// 每格需检查 `(2·thickness+1)²` 个点，宜用于较小的 `thickness`。
+///
+/// ```ignore
+/// // 在真沙漠椭圆内侧描 3 格宽的边
+/// fill_biome_outline(&ell, bm, border_id, 3, |c| c == desert_true_id);
+/// ```
+pub fn fill_biome_outline(
+    shape: &dyn Shape,
+    bm: &mut BiomeMap,
+    biome: BiomeId,
+    thickness: u32,
+    filter: impl Fn(BiomeId) -> bool + Sync,
+) {
+    let t = thickness.max(1) as i32;
+    let on_outline = |x: i32, y: i32| {
+        shape.contains(x, y)
+            && (-t..=t).any(|dy| (-t..=t).any(|dx| !shape.contains(x + dx, y + dy)))
+    };
+
+    let bb = shape.bounding_box();
+    let x0 = bb.x_min.max(0);
+    let y0 = bb.y_min.max(0);
+    let x1 = bb.x_max.min(bm.width as i32);
+    let y1 = bb.y_max.min(bm.height as i32);
+    if x0 >= x1 || y0 >= y1 {
+        return;
+    }
+
+    let w = bm.width as usize;
+    let fill_row = |y: i32, row: &mut [BiomeId]| {
+        for x in x0..x1 {
+            if filter(row[x as usize]) && on_outline(x, y) {
+                row[x as usize] = biome;
+            }
+        }
+    };
+
+    let rows = bm.data_mut()[y0 as usize * w..y1 as usize * w].chunks_mut(w);
+    let area = (x1 - x0) as i64 * (y1 - y0) as i64;
+    record_fill_path(area, area >= parallel_threshold());
+    if area >= parallel_threshold() {
+        rows.collect::<Vec<_>>()
+            .into_par_iter()
+            .enumerate()
+            .for_each(|(i, row)| fill_row(y0 + i as i32, row));
+    } else {
+        for (i, row) in rows.enumerate() {
+            fill_row(y0 + i as i32, row);
+        }
+    }
+}
+
 /// 检查形状区域内是否全部满足条件（用于放置前的空白验证）
 ///
 /// `step` 为采样步长（> 1 可加速大区域检查）
@@ -615,12 +889,14 @@ pub fn shape_all_match(
 // ═══════════════════════════════════════════════════════════
 
 /// 形状类型标识
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ShapeKind {
     Rect,
     Ellipse,
     Trapezoid,
     Column,
+    Implicit,
 }
 
 impl ShapeKind {
@@ -631,6 +907,7 @@ impl ShapeKind {
             ShapeKind::Ellipse,
             ShapeKind::Trapezoid,
             ShapeKind::Column,
+            ShapeKind::Implicit,
         ]
     }
 
@@ -641,6 +918,7 @@ impl ShapeKind {
             ShapeKind::Ellipse => "椭圆",
             ShapeKind::Trapezoid => "梯形",
             ShapeKind::Column => "列",
+            ShapeKind::Implicit => "隐函数",
         }
     }
 
@@ -651,6 +929,81 @@ impl ShapeKind {
             ShapeKind::Ellipse => "标准椭圆方程：(x-cx)²/rx² + (y-cy)²/ry² ≤ 1",
             ShapeKind::Trapezoid => "左右边界线性插值：t=(y-y_top)/h, x∈[lerp(top,bot,t))",
             ShapeKind::Column => "单像素宽垂直线段：x=固定, y∈[y_start,y_end)",
+            ShapeKind::Implicit => "表达式 f(x, y) 为真（非零）的格：x, y 相对原点，包围盒为整个世界",
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::biome::BIOME_UNASSIGNED;
+
+    const RING: BiomeId = 7;
+
+    fn outline_on_rect(thickness: u32) -> BiomeMap {
+        let mut bm = BiomeMap::new_filled(12, 12, BIOME_UNASSIGNED);
+        fill_biome_outline(&Rect::new(2, 3, 10, 11), &mut bm, RING, thickness, |_| true);
+        bm
+    }
+
+    #[test]
+    fn fill_stats_count_each_fill_once_and_reset_on_take() {
+        take_fill_stats();
+        let mut bm = BiomeMap::new_filled(12, 12, BIOME_UNASSIGNED);
+        fill_biome(&Rect::new(0, 0, 4, 5), &mut bm, RING);
+        fill_biome_if(&Rect::new(0, 0, 12, 12), &mut bm, RING, |id| id == BIOME_UNASSIGNED);
+        let stats = take_fill_stats();
+        assert_eq!(stats.parallel + stats.serial, 2);
+        assert_eq!(stats.parallel_area + stats.serial_area, 4 * 5 + 12 * 12);
+        assert_eq!(take_fill_stats(), FillPathStats::default());
+    }
+
+    #[test]
+    fn stencil_scales_dark_opaque_pixels_onto_bounds() {
+        // 2×1 图片：左黑右白；再加一行透明的黑像素
+        let rgba = [0, 0, 0, 255, 255, 255, 255, 255, 0, 0, 0, 0, 0, 0, 0, 0];
+        let stencil = Stencil::from_rgba(2, 2, &rgba, 128, BoundingBox::new(10, 10, 14, 14));
+        let mut bm = BiomeMap::new_filled(16, 16, BIOME_UNASSIGNED);
+        fill_biome_if(&stencil, &mut bm, RING, |_| true);
+        for y in 0..16 {
+            for x in 0..16 {
+                let expected = (10..12).contains(&x) && (10..12).contains(&y);
+                assert_eq!(bm.get(x, y) == RING, expected, "({x}, {y})");
+            }
+        }
+    }
+
+    #[test]
+    fn outline_fills_only_the_ring_of_a_rect() {
+        let bm = outline_on_rect(1);
+        for y in 0..12 {
+            for x in 0..12 {
+                let inside = (2..10).contains(&x) && (3..11).contains(&y);
+                let on_edge = x == 2 || x == 9 || y == 3 || y == 10;
+                let expected = if inside && on_edge { RING } else { BIOME_UNASSIGNED };
+                assert_eq!(bm.get(x, y), expected, "({x}, {y})");
+            }
+        }
+    }
+
+    #[test]
+    fn outline_thickness_widens_the_ring() {
+        let bm = outline_on_rect(2);
+        let filled = bm.data().iter().filter(|&&b| b == RING).count();
+        // 8×8 矩形减去内部 4×4
+        assert_eq!(filled, 8 * 8 - 4 * 4);
+        assert_eq!(bm.get(5, 6), BIOME_UNASSIGNED);
+        assert_eq!(bm.get(3, 4), RING);
+    }
+
+    #[test]
+    fn ellipse_coverage_is_binary_away_from_the_edge() {
+        let ell = Ellipse::new(20.0, 20.0, 10.0, 6.0);
+        assert_eq!(ell.coverage(20, 20), 1.0);
+        assert_eq!(ell.coverage(40, 20), 0.0);
+        // 边界格部分覆盖
+        let edge = ell.coverage(30, 20);
+        assert!(edge > 0.0 && edge < 1.0, "{edge}");
+    }
+}
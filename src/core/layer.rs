@@ -12,13 +12,27 @@ pub struct LayerDefinition {
 }
 
 impl LayerDefinition {
+    /// 层级占据的像素行 `[start, end)`。
+    ///
+    /// 与算法参数中的纵向比例一样经 [`fraction_to_row`] 换算，相邻层级共享同一个百分比时
+    /// 上一层的 `end` 恰好等于下一层的 `start`。
     pub fn bounds_for_height(&self, height: u32) -> (u32, u32) {
-        let start = height * self.start_percent as u32 / 100;
-        let end = height * self.end_percent as u32 / 100;
+        let start = fraction_to_row(height, self.start_percent as f64 / 100.0);
+        let end = fraction_to_row(height, self.end_percent as f64 / 100.0);
         (start, end)
     }
 }
 
+/// 高度比例（0.0~1.0）→ 像素行，四舍五入到最近的行。
+///
+/// 比例先量化到万分之一再做整数运算：`0.3` 这类无法精确表示的小数不会因为
+/// `(h as f64 * 0.3) as i32` 式的截断少算一行，层级边界和取自层级的算法参数（如
+/// `ocean_top_limit`）落在同一行上。
+pub fn fraction_to_row(height: u32, fraction: f64) -> u32 {
+    let basis_points = (fraction.max(0.0) * 10_000.0).round() as u64;
+    ((height as u64 * basis_points + 5_000) / 10_000) as u32
+}
+
 pub fn build_layers(config: &WorldConfig) -> Result<Vec<LayerDefinition>, CoreError> {
     let mut layers = Vec::with_capacity(config.layers.len());
 
@@ -42,3 +56,50 @@ pub fn build_layers(config: &WorldConfig) -> Result<Vec<LayerDefinition>, CoreEr
 
     Ok(layers)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::world::load_world_config;
+
+    #[test]
+    fn adjacent_layers_meet_at_every_world_height() {
+        let config = load_world_config().unwrap();
+        let mut layers = build_layers(&config).unwrap();
+        layers.sort_by_key(|l| l.start_percent);
+
+        for key in ["small", "medium", "large"] {
+            let height = config.world_sizes[key].height.unwrap();
+            let bounds: Vec<(u32, u32)> = layers.iter().map(|l| l.bounds_for_height(height)).collect();
+            assert_eq!(bounds.first().unwrap().0, 0, "{key}");
+            assert_eq!(bounds.last().unwrap().1, height, "{key}");
+            for (pair, names) in bounds.windows(2).zip(layers.windows(2)) {
+                assert_eq!(pair[0].1, pair[1].0, "{key}: {} / {}", names[0].key, names[1].key);
+            }
+        }
+
+        // 默认层级在小世界（1200 行）上的确切行号
+        let rows: Vec<(&str, (u32, u32))> = layers.iter().map(|l| (l.key.as_str(), l.bounds_for_height(1200))).collect();
+        assert_eq!(rows, [
+            ("space", (0, 120)),
+            ("surface", (120, 360)),
+            ("underground", (360, 480)),
+            ("cavern", (480, 1020)),
+            ("hell", (1020, 1200)),
+        ]);
+    }
+
+    #[test]
+    fn fraction_to_row_rounds_instead_of_truncating() {
+        // 1200 × 0.41 在 f64 中为 491.99999999999994，截断会少一行
+        assert_eq!((1200.0 * 0.41) as u32, 491);
+        assert_eq!(fraction_to_row(1200, 0.41), 492);
+        assert_eq!(fraction_to_row(2400, 0.57), 1368);
+        // 恰好半行时进到下一行
+        assert_eq!(fraction_to_row(1050, 0.03), 32);
+        assert_eq!(fraction_to_row(1800, 0.85), 1530);
+        assert_eq!(fraction_to_row(1800, 0.0), 0);
+        assert_eq!(fraction_to_row(1800, 1.0), 1800);
+        assert_eq!(fraction_to_row(1800, -0.2), 0, "负比例按 0 处理");
+    }
+}
@@ -0,0 +1,88 @@
+//! # 运行日志缓冲
+//!
+//! 进程内共享的日志环形缓冲：生成步骤、错误、状态消息都写入这里，
+//! 供 UI 日志控制台回看完整历史（状态栏只显示最新一条）。
+//! 每条消息同时输出到 stderr，保留原先 `eprintln!` 的行为。
+
+use std::collections::VecDeque;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// 缓冲区最多保留的条数（超出后丢弃最旧的）
+const MAX_ENTRIES: usize = 2000;
+
+/// 日志级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogLevel {
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    pub fn label(self) -> &'static str {
+        match self {
+            LogLevel::Info => "信息",
+            LogLevel::Warn => "警告",
+            LogLevel::Error => "错误",
+        }
+    }
+}
+
+/// 一条日志
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+    /// 自进程启动以来的时间
+    pub elapsed: Duration,
+    pub level: LogLevel,
+    pub message: String,
+}
+
+fn start_time() -> Instant {
+    static START: OnceLock<Instant> = OnceLock::new();
+    *START.get_or_init(Instant::now)
+}
+
+fn buffer() -> &'static Mutex<VecDeque<LogEntry>> {
+    static BUFFER: OnceLock<Mutex<VecDeque<LogEntry>>> = OnceLock::new();
+    BUFFER.get_or_init(|| Mutex::new(VecDeque::with_capacity(MAX_ENTRIES)))
+}
+
+/// 写入一条日志
+pub fn push(level: LogLevel, message: impl Into<String>) {
+    let message = message.into();
+    eprintln!("[{}] {message}", level.label());
+
+    let entry = LogEntry {
+        elapsed: start_time().elapsed(),
+        level,
+        message,
+    };
+    let mut buf = buffer().lock().unwrap();
+    if buf.len() >= MAX_ENTRIES {
+        buf.pop_front();
+    }
+    buf.push_back(entry);
+}
+
+pub fn info(message: impl Into<String>) {
+    push(LogLevel::Info, message);
+}
+
+pub fn warn(message: impl Into<String>) {
+    push(LogLevel::Warn, message);
+}
+
+pub fn error(message: impl Into<String>) {
+    push(LogLevel::Error, message);
+}
+
+/// 当前全部日志的副本
+pub fn entries() -> Vec<LogEntry> {
+    buffer().lock().unwrap().iter().cloned().collect()
+}
+
+/// 清空日志
+pub fn clear() {
+    buffer().lock().unwrap().clear();
+}
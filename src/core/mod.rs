@@ -1,8 +1,12 @@
 pub mod biome;
+pub mod biome_rle;
 pub mod block;
 pub mod color;
+pub mod coords;
+pub mod expr;
 pub mod geometry;
 pub mod layer;
+pub mod log;
 pub mod world;
 
 use std::error::Error;
@@ -1,7 +1,7 @@
 use rayon::prelude::*;
 
 use crate::config::world::{WorldConfig, WorldSize};
-use crate::core::geometry::parallel_threshold;
+use crate::core::geometry::{parallel_threshold, record_fill_path};
 use crate::core::layer::{build_layers, LayerDefinition};
 use crate::core::CoreError;
 
@@ -79,6 +79,7 @@ impl World {
         let w = self.width as usize;
 
         let area = (xe - xs) * (ye - ys);
+        record_fill_path(area as i64, area >= parallel_threshold() as usize);
         if area >= parallel_threshold() as usize {
             // 并行填充
             self.tiles[ys * w..ye * w]
@@ -13,14 +13,16 @@
 use std::any::Any;
 use std::collections::HashMap;
 
-use rand::rngs::StdRng;
 use serde::{Deserialize, Serialize};
 
-use crate::core::biome::{BiomeDefinition, BiomeMap};
+use crate::core::biome::{BiomeDefinition, BiomeId, BiomeMap};
 use crate::core::block::BlockDefinition;
 use crate::core::geometry::ShapeRecord;
 use crate::core::world::{World, WorldProfile};
 
+use super::error::GenerationError;
+use super::step_rng::StepRng;
+
 // ═══════════════════════════════════════════════════════════
 // 元数据结构 —— 算法用这些结构向引擎描述自身
 // ═══════════════════════════════════════════════════════════
@@ -58,6 +60,59 @@ pub struct ParamDef {
     pub group: Option<String>,
 }
 
+impl ParamDef {
+    /// 检查一个参数值是否符合类型与约束，不符合时返回原因（如「超出范围 [0, 1]」）
+    pub fn check_value(&self, value: &serde_json::Value) -> Result<(), String> {
+        match &self.param_type {
+            ParamType::Float { min, max } => {
+                let v = value.as_f64().ok_or("应为数值")?;
+                if v < *min || v > *max {
+                    return Err(format!("超出范围 [{min}, {max}]"));
+                }
+            }
+            ParamType::Int { min, max } => {
+                let v = value.as_i64().ok_or("应为整数")?;
+                if v < *min || v > *max {
+                    return Err(format!("超出范围 [{min}, {max}]"));
+                }
+            }
+            ParamType::Bool => {
+                value.as_bool().ok_or("应为布尔值")?;
+            }
+            ParamType::Text => {
+                value.as_str().ok_or("应为字符串")?;
+            }
+            ParamType::Enum { options } => {
+                let v = value.as_str().ok_or("应为字符串")?;
+                if !options.iter().any(|o| o == v) {
+                    return Err(format!("不是可选项之一（{}）", options.join(" / ")));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 类型正确但取值越界的参数修正为最接近的合法值（数值钳制到范围内，未知枚举项取默认值）；
+    /// 类型不符或本已合法时返回 `None`
+    pub fn clamp_value(&self, value: &serde_json::Value) -> Option<serde_json::Value> {
+        match &self.param_type {
+            ParamType::Float { min, max } => {
+                let v = value.as_f64()?;
+                (v < *min || v > *max).then(|| serde_json::json!(v.clamp(*min, *max)))
+            }
+            ParamType::Int { min, max } => {
+                let v = value.as_i64()?;
+                (v < *min || v > *max).then(|| serde_json::json!(v.clamp(*min, *max)))
+            }
+            ParamType::Enum { options } => {
+                let v = value.as_str()?;
+                (!options.iter().any(|o| o == v)).then(|| self.default.clone())
+            }
+            ParamType::Bool | ParamType::Text => None,
+        }
+    }
+}
+
 /// 单个子步骤的元数据
 #[derive(Debug, Clone)]
 pub struct StepMeta {
@@ -69,6 +124,24 @@ pub struct StepMeta {
     pub description: String,
     /// 算法文档链接（可选）
     pub doc_url: Option<String>,
+    /// 是否为散布特征：用 RNG 在空地中选位置的步骤（如沙漠、邪恶环境）。
+    /// 「重掷散布特征」只改变这类步骤的子种子
+    pub scatter: bool,
+    /// 该步骤生成的环境（biome key，如 "jungle"）。设置后出现在「环境开关」清单中，
+    /// 关闭该环境即禁用所有标记为它的步骤
+    pub biome: Option<String>,
+}
+
+/// 子步骤允许的环境改写（调试构建的防重叠检查用）
+///
+/// 环境判定依赖「后续步骤只写入空白格」的约定；检查开启时，管线在步骤前后比较环境地图，
+/// 出现策略之外的「环境 A → 环境 B」改写即报错。
+#[derive(Debug, Clone)]
+pub enum OverwritePolicy {
+    /// 只允许 `UNASSIGNED → X`，以及列出的 `(原环境, 新环境)` 转换
+    Allow(Vec<(BiomeId, BiomeId)>),
+    /// 可任意改写（如边界平滑等后处理步骤）
+    Any,
 }
 
 /// 一个 Phase（阶段）算法模块的完整元数据
@@ -104,7 +177,7 @@ pub struct RuntimeContext<'a> {
     /// 环境定义表（只读）
     pub biomes: &'a [BiomeDefinition],
     /// 每步独立的确定性 RNG
-    pub rng: &'a mut StdRng,
+    pub rng: &'a mut StepRng,
     /// 环境地图（共享状态，可读写）
     pub biome_map: &'a mut Option<BiomeMap>,
     /// 通用共享状态容器
@@ -183,14 +256,10 @@ impl<'a> RuntimeContext<'a> {
             .map(|l| l.bounds_for_height(self.world.height))
     }
 
-    /// 查询层级起始（像素格式）
-    pub fn layer_start_px(&self, key: &str) -> Option<u32> {
-        self.layer_range_px(key).map(|(s, _)| s)
-    }
-
-    /// 查询层级结束（像素格式）
-    pub fn layer_end_px(&self, key: &str) -> Option<u32> {
-        self.layer_range_px(key).map(|(_, e)| e)
+    /// 同 [`layer_range_px`](Self::layer_range_px)，层级缺失时返回 [`GenerationError::MissingLayer`]
+    pub fn require_layer_px(&self, key: &str) -> Result<(u32, u32), GenerationError> {
+        self.layer_range_px(key)
+            .ok_or_else(|| GenerationError::MissingLayer(key.to_string()))
     }
 }
 
@@ -215,16 +284,16 @@ impl<'a> RuntimeContext<'a> {
 ///             name: "我的算法".to_string(),
 ///             description: "示例算法".to_string(),
 ///             steps: vec![
-///                 StepMeta { display_index: 1, name: "步骤1".into(), description: "...".into(), doc_url: None },
+///                 StepMeta { display_index: 1, name: "步骤1".into(), description: "...".into(), doc_url: None, scatter: false, biome: None },
 ///             ],
 ///             params: vec![],
 ///         }
 ///     }
 ///
-///     fn execute(&self, step_index: usize, ctx: &mut RuntimeContext) -> Result<(), String> {
+///     fn execute(&mut self, step_index: usize, ctx: &mut RuntimeContext) -> Result<(), GenerationError> {
 ///         match step_index {
 ///             0 => { /* 步骤1的逻辑 */ Ok(()) }
-///             _ => Err("无效步骤".into())
+///             _ => Err(GenerationError::InvalidStep(step_index)),
 ///         }
 ///     }
 /// }
@@ -235,11 +304,15 @@ pub trait PhaseAlgorithm {
 
     /// 执行指定子步骤
     ///
-    /// `step_index`：子步骤索引（从 0 开始，对应 `meta().steps` 的下标）
-    fn execute(&mut self, step_index: usize, ctx: &mut RuntimeContext) -> Result<(), String>;
+    /// `step_index`：子步骤索引（从 0 开始，对应 `meta().steps` 的下标）。
+    /// 出错时返回结构化的 [`GenerationError`]，流水线负责附加步骤名并显示。
+    fn execute(&mut self, step_index: usize, ctx: &mut RuntimeContext) -> Result<(), GenerationError>;
 
     /// 返回当前参数值（用于持久化）
     ///
+    /// 应返回**完整**参数集（包括仍为默认值的参数），`.lwd` 快照直接保存此值；
+    /// 只保存改动过的参数会让旧存档在新增参数后悄悄改用新默认值。
+    ///
     /// 默认实现返回空对象 `{}`
     fn get_params(&self) -> serde_json::Value {
         serde_json::Value::Object(serde_json::Map::new())
@@ -252,6 +325,36 @@ pub trait PhaseAlgorithm {
         // 默认忽略
     }
 
+    /// 参数键名的历史别名 `(旧键名, 现键名)`
+    ///
+    /// 与 `#[serde(alias)]` 保持一致。恢复快照时据此判断旧存档中的参数是否已覆盖现有键，
+    /// 避免把改名后的参数误报为缺失。默认无别名。
+    fn param_aliases(&self) -> &[(&'static str, &'static str)] {
+        &[]
+    }
+
+    /// 参数结构体的 Rust 类型名（如 `BiomeDivisionParams`）
+    ///
+    /// 返回 `Some` 时参数配置窗口提供「复制为 Rust 代码」，把当前参数导出为该结构体的字面量，
+    /// 便于把偏好的配置写死为默认值。默认不提供。
+    fn params_type_name(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// 按当前参数判断子步骤是否注定不产生任何效果，返回原因（供执行前的预检使用）
+    ///
+    /// 只做廉价的参数检查，不访问世界。默认认为步骤总会生效。
+    fn would_be_noop(&self, _step_index: usize) -> Option<String> {
+        None
+    }
+
+    /// 子步骤允许的环境改写，供调试构建的防重叠检查使用
+    ///
+    /// 默认只允许写入空白格。有意覆盖已有环境的步骤应列出允许的转换或返回 `Any`。
+    fn overwrite_policy(&self, _step_index: usize) -> OverwritePolicy {
+        OverwritePolicy::Allow(Vec::new())
+    }
+
     /// 管线重置时调用，清理算法内部运行时状态
     ///
     /// 默认实现什么都不做。如果算法有步骤间传递的内部状态，应在此清理。
@@ -0,0 +1,50 @@
+//! # 生成完成钩子
+//!
+//! 「执行到底」/「一键生成」跑完最后一个子步骤时，应用依次调用已注册的钩子，
+//! 把完成后的世界、流水线（含性能统计与共享数据）交给它们做后处理——
+//! 性能日志、CSV 日志等都以钩子形式注册，新增的自动保存 / 导出只需再注册一个，
+//! 不必在更新循环里另加完成判定。
+
+use std::time::Instant;
+
+use crate::core::biome::BiomeDefinition;
+use crate::core::log;
+use crate::core::world::World;
+use crate::storage::engine_config::EngineConfig;
+
+use super::GenerationPipeline;
+
+/// 一次完整生成结束时交给钩子的上下文
+pub struct GenerationComplete<'a> {
+    pub pipeline: &'a GenerationPipeline,
+    pub world: &'a World,
+    pub biomes: &'a [BiomeDefinition],
+    pub config: &'a EngineConfig,
+    /// 本次生成总耗时（ms）
+    pub total_ms: f64,
+}
+
+/// 生成完成钩子
+pub type CompletionHook = Box<dyn FnMut(&GenerationComplete)>;
+
+/// 按注册顺序调用的钩子列表
+#[derive(Default)]
+pub struct CompletionHooks {
+    hooks: Vec<(&'static str, CompletionHook)>,
+}
+
+impl CompletionHooks {
+    /// 注册一个钩子；`name` 用于日志定位
+    pub fn register(&mut self, name: &'static str, hook: impl FnMut(&GenerationComplete) + 'static) {
+        self.hooks.push((name, Box::new(hook)));
+    }
+
+    /// 依次调用全部钩子，每个钩子的名称与耗时写入日志
+    pub fn fire(&mut self, event: &GenerationComplete) {
+        for (name, hook) in &mut self.hooks {
+            let start = Instant::now();
+            hook(event);
+            log::info(format!("生成完成钩子 {name}: {:.1}ms", start.elapsed().as_secs_f64() * 1000.0));
+        }
+    }
+}
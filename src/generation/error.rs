@@ -0,0 +1,135 @@
+//! # 生成错误
+//!
+//! 算法子步骤与流水线执行的结构化错误。UI 通过 `Display` 显示中文信息；
+//! 无界面调用方与测试可以按变体匹配（外层的步骤名 / 特征名用 [`GenerationError::root`] 剥离）。
+
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GenerationError {
+    /// 环境定义中没有该 key
+    MissingBiome(String),
+    /// 层级定义中没有该 key
+    MissingLayer(String),
+    /// 环境地图尚未创建（前置步骤未执行）
+    MapNotInitialized,
+    /// 参数取值非法
+    InvalidParam {
+        key: String,
+        value: String,
+        reason: String,
+    },
+    /// 子步骤索引超出 `meta().steps` 范围
+    InvalidStep(usize),
+    /// 外部文件（如自定义特征文件）读取或解析失败
+    File { path: String, reason: String },
+    /// 没有可重掷的散布步骤
+    NoScatterSteps,
+    /// 环境地图与世界尺寸不一致（调试构建的执行前检查）
+    MapSizeMismatch {
+        map: (u32, u32),
+        world: (u32, u32),
+    },
+    /// 已分配的环境被未声明地改写（防重叠检查）
+    UnexpectedOverwrite {
+        count: usize,
+        first: (u32, u32),
+        from: String,
+        to: String,
+    },
+    /// 附带所在步骤 / 特征名称的错误
+    Context {
+        label: String,
+        source: Box<GenerationError>,
+    },
+}
+
+impl GenerationError {
+    /// 参数取值非法
+    pub fn invalid_param(key: &str, value: impl Into<String>, reason: &str) -> Self {
+        Self::InvalidParam {
+            key: key.to_string(),
+            value: value.into(),
+            reason: reason.to_string(),
+        }
+    }
+
+    /// 为错误附加所在位置的名称（步骤名、特征名等）
+    pub fn context(self, label: impl Into<String>) -> Self {
+        Self::Context {
+            label: label.into(),
+            source: Box::new(self),
+        }
+    }
+
+    /// 剥去所有 `Context` 层后的原始错误
+    pub fn root(&self) -> &GenerationError {
+        match self {
+            Self::Context { source, .. } => source.root(),
+            other => other,
+        }
+    }
+}
+
+impl Display for GenerationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingBiome(key) => write!(f, "未找到 {key} 环境定义"),
+            Self::MissingLayer(key) => write!(f, "未找到 {key} 层级定义"),
+            Self::MapNotInitialized => write!(f, "环境地图尚未初始化，需先执行前置步骤"),
+            Self::InvalidParam { key, value, reason } => {
+                write!(f, "{reason}: {value}（参数 {key}）")
+            }
+            Self::InvalidStep(index) => write!(f, "无效步骤索引: {index}"),
+            Self::File { path, reason } => write!(f, "{reason} ({path})"),
+            Self::NoScatterSteps => write!(f, "没有可重掷的散布步骤"),
+            Self::MapSizeMismatch { map, world } => write!(
+                f,
+                "环境地图尺寸 {}×{} 与世界尺寸 {}×{} 不一致（修改世界尺寸后未重置生成状态？）",
+                map.0, map.1, world.0, world.1,
+            ),
+            Self::UnexpectedOverwrite { count, first, from, to } => write!(
+                f,
+                "{count} 格已分配的环境被改写，首处 ({}, {}): {from} → {to}",
+                first.0, first.1,
+            ),
+            Self::Context { label, source } => write!(f, "{label}: {source}"),
+        }
+    }
+}
+
+impl Error for GenerationError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Context { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generation::headless::HeadlessContext;
+
+    #[test]
+    fn step_errors_keep_their_variant() {
+        let context = HeadlessContext::load("custom", Some((600, 200))).unwrap();
+        let err = context
+            .generate(1, |p| {
+                p.set_step_logging(false);
+                let algo = p.algorithm_by_id_mut("biome_division").unwrap();
+                algo.set_params(&serde_json::json!({ "jungle_side": "middle" }));
+            })
+            .err()
+            .expect("非法的丛林位置应当报错");
+
+        assert!(matches!(err, GenerationError::Context { .. }));
+        assert_eq!(
+            err.root(),
+            &GenerationError::invalid_param("jungle_side", "middle", "未知的丛林位置"),
+        );
+        assert!(err.to_string().ends_with("未知的丛林位置: middle（参数 jungle_side）"));
+    }
+}
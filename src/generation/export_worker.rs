@@ -0,0 +1,255 @@
+//! # 后台导出队列
+//!
+//! PNG 导出（尤其是大世界 + 环境混合 / 缩放）与分环境图层导出放到独立线程执行，避免阻塞 UI。
+//! 导出任务按提交顺序排队，由单个工作线程逐个处理，通过 channel 回报进度：
+//!
+//! `Queued → Encoding { percent } → Writing → Done / Failed`
+//!
+//! 提交时复制一份世界与环境地图，之后继续生成或回退不会影响正在进行的导出。
+
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use egui::Color32;
+
+use crate::core::biome::{BiomeDefinition, BiomeMap};
+use crate::core::world::World;
+use crate::rendering::biome_layers::export_biome_layers;
+use crate::rendering::pattern::BiomePattern;
+
+use super::snapshot::{render_png_image, DepthShading, LayerAnnotations, PngLayout, ScaleBar};
+
+/// 导出任务状态
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExportJobState {
+    /// 等待前面的任务完成
+    Queued,
+    /// 正在构建像素（百分比）
+    Encoding { percent: u8 },
+    /// 正在编码 PNG 并写入文件
+    Writing,
+    Done,
+    Failed(String),
+}
+
+impl ExportJobState {
+    pub fn is_finished(&self) -> bool {
+        matches!(self, ExportJobState::Done | ExportJobState::Failed(_))
+    }
+}
+
+/// 一个导出任务（UI 侧视图）
+#[derive(Debug, Clone)]
+pub struct ExportJob {
+    pub id: u64,
+    /// 显示名称（如 "PNG (含环境)"）
+    pub label: String,
+    pub path: PathBuf,
+    pub state: ExportJobState,
+    /// 结束时间，用于完成提示停留一段时间后移除
+    finished_at: Option<Instant>,
+}
+
+/// 提交给工作线程的数据（世界等均为副本）
+struct ExportRequest {
+    id: u64,
+    path: PathBuf,
+    work: ExportWork,
+}
+
+/// 导出内容
+enum ExportWork {
+    /// 整张 PNG（`path` 为文件）
+    Png(Box<PngWork>),
+    /// 每个环境一张透明底 PNG（`path` 为目录）
+    BiomeLayers {
+        biome_map: BiomeMap,
+        biome_defs: Vec<BiomeDefinition>,
+        pattern: BiomePattern,
+    },
+}
+
+struct PngWork {
+    layout: PngLayout,
+    world: World,
+    color_lut: [Color32; 256],
+    biome_map: Option<BiomeMap>,
+    biome_defs: Vec<BiomeDefinition>,
+    pattern: BiomePattern,
+    shading: Option<DepthShading>,
+    annotations: Option<LayerAnnotations>,
+    scale_bar: Option<ScaleBar>,
+}
+
+/// 导出队列：持有工作线程的收发端与全部任务状态
+pub struct ExportQueue {
+    sender: Sender<ExportRequest>,
+    updates: Receiver<(u64, ExportJobState)>,
+    jobs: Vec<ExportJob>,
+    next_id: u64,
+}
+
+impl ExportQueue {
+    /// 创建队列并启动工作线程
+    pub fn new() -> Self {
+        let (sender, requests) = mpsc::channel::<ExportRequest>();
+        let (update_tx, updates) = mpsc::channel();
+
+        thread::Builder::new()
+            .name("png-export".into())
+            .spawn(move || {
+                for req in requests {
+                    run_export(req, &update_tx);
+                }
+            })
+            .expect("无法启动导出线程");
+
+        Self {
+            sender,
+            updates,
+            jobs: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    /// 提交一个导出任务。`biome_map` 为 `Some` 时混合环境覆盖色（按 `pattern` 叠加图案），
+    /// `shading` 为 `Some` 时叠加深度明暗，`annotations` 为 `Some` 时标注层级分界线与名称，
+    /// `scale_bar` 为 `Some` 时在一角画比例尺。
+    #[allow(clippy::too_many_arguments)]
+    pub fn submit(
+        &mut self,
+        label: &str,
+        path: PathBuf,
+        layout: PngLayout,
+        world: &World,
+        color_lut: &[Color32; 256],
+        biome_map: Option<&BiomeMap>,
+        biome_defs: &[BiomeDefinition],
+        pattern: BiomePattern,
+        shading: Option<DepthShading>,
+        annotations: Option<LayerAnnotations>,
+        scale_bar: Option<ScaleBar>,
+    ) {
+        let work = ExportWork::Png(Box::new(PngWork {
+            layout,
+            world: world.clone(),
+            color_lut: *color_lut,
+            biome_map: biome_map.cloned(),
+            biome_defs: biome_defs.to_vec(),
+            pattern,
+            shading,
+            annotations,
+            scale_bar,
+        }));
+        self.enqueue(label, path, work);
+    }
+
+    /// 提交分环境图层导出：`dir` 下为地图中出现的每个环境写出 `<key>.png`
+    pub fn submit_biome_layers(
+        &mut self,
+        label: &str,
+        dir: PathBuf,
+        biome_map: &BiomeMap,
+        biome_defs: &[BiomeDefinition],
+        pattern: BiomePattern,
+    ) {
+        let work = ExportWork::BiomeLayers {
+            biome_map: biome_map.clone(),
+            biome_defs: biome_defs.to_vec(),
+            pattern,
+        };
+        self.enqueue(label, dir, work);
+    }
+
+    fn enqueue(&mut self, label: &str, path: PathBuf, work: ExportWork) {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let request = ExportRequest { id, path: path.clone(), work };
+        let state = match self.sender.send(request) {
+            Ok(()) => ExportJobState::Queued,
+            Err(_) => ExportJobState::Failed("导出线程已退出".to_string()),
+        };
+        let finished_at = state.is_finished().then(Instant::now);
+        self.jobs.push(ExportJob {
+            id,
+            label: label.to_string(),
+            path,
+            state,
+            finished_at,
+        });
+    }
+
+    /// 接收工作线程的进度更新，返回本次新结束的任务；
+    /// 结束超过 `linger` 的任务从列表中移除。
+    pub fn poll(&mut self, linger: Duration) -> Vec<ExportJob> {
+        let mut finished = Vec::new();
+        while let Ok((id, state)) = self.updates.try_recv() {
+            if let Some(job) = self.jobs.iter_mut().find(|j| j.id == id) {
+                if state.is_finished() {
+                    job.finished_at = Some(Instant::now());
+                }
+                job.state = state;
+                if job.state.is_finished() {
+                    finished.push(job.clone());
+                }
+            }
+        }
+        self.jobs
+            .retain(|j| j.finished_at.is_none_or(|t| t.elapsed() < linger));
+        finished
+    }
+
+    /// 当前可见的任务（含停留中的已结束任务）
+    pub fn jobs(&self) -> &[ExportJob] {
+        &self.jobs
+    }
+}
+
+/// 在工作线程中执行单个导出，逐阶段回报状态
+fn run_export(req: ExportRequest, updates: &Sender<(u64, ExportJobState)>) {
+    let id = req.id;
+    let send = |state: ExportJobState| {
+        let _ = updates.send((id, state));
+    };
+
+    let mut last_percent = None;
+    let mut progress = |fraction: f32| {
+        let percent = (fraction * 100.0).round().clamp(0.0, 100.0) as u8;
+        if last_percent != Some(percent) {
+            last_percent = Some(percent);
+            send(ExportJobState::Encoding { percent });
+        }
+    };
+
+    let result = match req.work {
+        ExportWork::Png(png) => render_png_image(
+            &png.world,
+            &png.color_lut,
+            png.biome_map.as_ref(),
+            &png.biome_defs,
+            png.pattern,
+            png.shading.as_ref(),
+            png.annotations.as_ref(),
+            png.scale_bar.as_ref(),
+            png.layout,
+            &mut progress,
+        )
+        .and_then(|img| {
+            send(ExportJobState::Writing);
+            img.save(&req.path).map_err(|e| format!("保存 PNG 失败: {e}"))
+        }),
+        // 各图层并行编码并写出，没有逐像素进度
+        ExportWork::BiomeLayers { biome_map, biome_defs, pattern } => {
+            send(ExportJobState::Writing);
+            export_biome_layers(&biome_map, &biome_defs, pattern, &req.path).map(|_| ())
+        }
+    };
+
+    send(match result {
+        Ok(()) => ExportJobState::Done,
+        Err(e) => ExportJobState::Failed(e),
+    });
+}
@@ -0,0 +1,83 @@
+//! # 步骤帧录制
+//!
+//! 录制开启期间，每执行一个子步骤就把当前世界的降采样预览（含环境覆盖色）
+//! 保存为目录下按序编号的 PNG：`0001_step_1-1.png`、`0002_step_1-2.png`…
+//! 供外部工具拼接成视频或教程配图。
+//!
+//! 降采样图像在 UI 线程构建（rayon 并行，很快），PNG 编码与写盘交给后台线程，
+//! 按提交顺序逐帧处理，不阻塞生成。
+
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+use egui::ColorImage;
+
+/// 提交给写盘线程的一帧
+struct Frame {
+    path: PathBuf,
+    image: ColorImage,
+}
+
+/// 一次录制：目标目录、已提交帧数与写盘线程的收发端
+pub struct FrameRecorder {
+    dir: PathBuf,
+    frames: usize,
+    sender: Sender<Frame>,
+    errors: Receiver<String>,
+}
+
+impl FrameRecorder {
+    /// 开始录制到 `dir`（不存在时创建），启动写盘线程
+    pub fn start(dir: PathBuf) -> Result<Self, String> {
+        std::fs::create_dir_all(&dir).map_err(|e| format!("无法创建目录 {}: {e}", dir.display()))?;
+        let (sender, frames) = mpsc::channel::<Frame>();
+        let (error_tx, errors) = mpsc::channel();
+
+        thread::Builder::new()
+            .name("frame-export".into())
+            .spawn(move || {
+                for frame in frames {
+                    if let Err(e) = save_frame(&frame) {
+                        let _ = error_tx.send(e);
+                    }
+                }
+            })
+            .map_err(|e| format!("无法启动录制线程: {e}"))?;
+
+        Ok(Self { dir, frames: 0, sender, errors })
+    }
+
+    /// 提交一帧；`step_id` 为步骤显示 ID（如 `1.3`），写入文件名
+    pub fn record(&mut self, image: ColorImage, step_id: &str) {
+        self.frames += 1;
+        let name = format!("{:04}_step_{}.png", self.frames, step_id.replace('.', "-"));
+        let _ = self.sender.send(Frame { path: self.dir.join(name), image });
+    }
+
+    /// 已提交的帧数
+    pub fn frames(&self) -> usize {
+        self.frames
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// 取出写盘线程报告的第一条错误（如磁盘已满）
+    pub fn poll_error(&self) -> Option<String> {
+        self.errors.try_iter().next()
+    }
+}
+
+/// 把预览图编码为 PNG 写盘
+fn save_frame(frame: &Frame) -> Result<(), String> {
+    let [w, h] = frame.image.size;
+    let buf: Vec<u8> = frame.image.pixels.iter()
+        .flat_map(|c| c.to_srgba_unmultiplied())
+        .collect();
+    image::RgbaImage::from_raw(w as u32, h as u32, buf)
+        .ok_or_else(|| "创建图像缓冲区失败".to_string())?
+        .save(&frame.path)
+        .map_err(|e| format!("保存 {} 失败: {e}", frame.path.display()))
+}
@@ -0,0 +1,278 @@
+//! # 无界面生成
+//!
+//! 不依赖 egui / runtime.json，直接从嵌入的默认配置构建世界并一次性执行全部步骤。
+//! 供回归测试等命令行功能使用，结果只取决于 (seed, 尺寸)。
+//!
+//! `Lwd profile [--seed N] [--size KEY]`：生成后逐行输出每个步骤的耗时
+//! （`flat_index<TAB>名称<TAB>毫秒`），任一步骤缺少耗时记录时以非零状态退出。
+//!
+//! `Lwd --bench [--seed N]`：以固定种子依次生成 small / medium / large 世界，
+//! 输出每个步骤在各尺寸下的耗时与总耗时对照表，以及环境地图平铺 / 游程编码两种形式的内存、转换与逐格读写耗时。
+
+use std::time::Instant;
+
+use crate::config::biome::load_biomes_config;
+use crate::config::blocks::load_blocks_config;
+use crate::config::world::load_world_config;
+use crate::core::biome::{build_biome_definitions, BiomeDefinition, BiomeMap, BIOME_UNASSIGNED};
+use crate::core::biome_rle::RleBiomeMap;
+use crate::core::block::{build_block_definitions, BlockDefinition};
+use crate::core::world::WorldProfile;
+
+use super::{build_pipeline, GenerationError, GenerationPipeline};
+
+/// 一次无界面生成的结果
+pub struct HeadlessWorld {
+    pub biomes: Vec<BiomeDefinition>,
+    pub pipeline: GenerationPipeline,
+}
+
+/// 从嵌入配置加载的生成环境（方块 / 环境定义与世界参数）。
+///
+/// 需要反复生成同一尺寸世界的调用方（如参数拟合）只加载一次配置。
+pub struct HeadlessContext {
+    pub blocks: Vec<BlockDefinition>,
+    pub biomes: Vec<BiomeDefinition>,
+    pub profile: WorldProfile,
+}
+
+impl HeadlessContext {
+    /// `custom_size` 仅在 `size_key == "custom"` 时生效。
+    pub fn load(size_key: &str, custom_size: Option<(u32, u32)>) -> Result<Self, String> {
+        // 单元测试不经过 main，在此确保扩展阶段已登记
+        crate::algorithms::register_extension_phases();
+        let blocks_cfg = load_blocks_config().map_err(|e| e.to_string())?;
+        let biomes_cfg = load_biomes_config().map_err(|e| e.to_string())?;
+        let world_cfg = load_world_config().map_err(|e| e.to_string())?;
+
+        let profile = WorldProfile::from_config(&world_cfg, size_key, custom_size)
+            .map_err(|e| e.to_string())?;
+        Ok(Self {
+            blocks: build_block_definitions(&blocks_cfg),
+            biomes: build_biome_definitions(&biomes_cfg),
+            profile,
+        })
+    }
+
+    /// 以 `seed` 生成完整世界；`configure` 在执行前调整流水线（设置参数、开关检查等）。
+    pub fn generate(
+        &self,
+        seed: u64,
+        configure: impl FnOnce(&mut GenerationPipeline),
+    ) -> Result<GenerationPipeline, GenerationError> {
+        let mut world = self.profile.create_world();
+        let mut pipeline = build_pipeline(seed, self.biomes.clone(), &self.profile.layers);
+        configure(&mut pipeline);
+        pipeline.run_all(&mut world, &self.profile, &self.blocks)?;
+        Ok(pipeline)
+    }
+}
+
+/// 使用默认配置（world.json 默认层级、算法默认参数）生成完整世界。
+///
+/// `custom_size` 仅在 `size_key == "custom"` 时生效。
+pub fn generate_headless(
+    seed: u64,
+    size_key: &str,
+    custom_size: Option<(u32, u32)>,
+) -> Result<HeadlessWorld, String> {
+    let context = HeadlessContext::load(size_key, custom_size)?;
+    // 调试构建（cargo test）顺带校验环境不重叠约定
+    let pipeline = context.generate(seed, |p| p.set_overlap_check(true))
+        .map_err(|e| e.to_string())?;
+
+    Ok(HeadlessWorld {
+        biomes: context.biomes,
+        pipeline,
+    })
+}
+
+/// `profile` 子命令入口：打印每个步骤的耗时，所有步骤都有记录时返回 `true`。
+pub fn run_profile_cli(args: &[String]) -> bool {
+    let arg_value = |flag: &str| {
+        args.iter()
+            .position(|a| a == flag)
+            .and_then(|i| args.get(i + 1))
+            .cloned()
+    };
+    let seed = match arg_value("--seed").map(|s| s.parse::<u64>()) {
+        None => 42,
+        Some(Ok(seed)) => seed,
+        Some(Err(e)) => {
+            eprintln!("无效的种子: {e}");
+            return false;
+        }
+    };
+    let size_key = arg_value("--size").unwrap_or_else(|| "small".to_string());
+
+    let generated = match generate_headless(seed, &size_key, None) {
+        Ok(generated) => generated,
+        Err(e) => {
+            eprintln!("生成失败: {e}");
+            return false;
+        }
+    };
+
+    let pipeline = &generated.pipeline;
+    let durations = pipeline.profiler().step_durations();
+    for (idx, name, elapsed) in &durations {
+        println!("{idx}\t{name}\t{:.3}", elapsed.as_secs_f64() * 1000.0);
+    }
+
+    let expected = pipeline.total_sub_steps();
+    let covered = durations
+        .iter()
+        .map(|(idx, _, _)| *idx)
+        .eq(0..expected);
+    if !covered {
+        eprintln!("耗时记录不完整: 共 {expected} 个步骤，记录了 {} 个", durations.len());
+    }
+    covered
+}
+
+/// 基准测试覆盖的世界尺寸
+const BENCH_SIZES: [&str; 3] = ["small", "medium", "large"];
+
+/// `--bench` 中环境地图两种存储形式的对比（一个尺寸）
+struct RleBenchStats {
+    flat_kb: f64,
+    rle_kb: f64,
+    runs: usize,
+    /// 平铺 → 游程
+    encode_ms: f64,
+    /// 游程 → 平铺
+    decode_ms: f64,
+    /// 逐格 `get` 读遍整张游程地图
+    get_ms: f64,
+    /// 从空白地图逐格 `set` 写出整张地图（模拟步骤直接写游程形式）
+    set_ms: f64,
+}
+
+impl RleBenchStats {
+    fn measure(map: &BiomeMap) -> Self {
+        let elapsed_ms = |start: Instant| start.elapsed().as_secs_f64() * 1000.0;
+
+        let start = Instant::now();
+        let rle = RleBiomeMap::from_flat(map);
+        let encode_ms = elapsed_ms(start);
+
+        let start = Instant::now();
+        let decoded = rle.to_flat();
+        let decode_ms = elapsed_ms(start);
+        debug_assert_eq!(decoded.data(), map.data());
+
+        let start = Instant::now();
+        let mut checksum = 0u64;
+        for y in 0..rle.height {
+            for x in 0..rle.width {
+                checksum += rle.get(x, y) as u64;
+            }
+        }
+        let get_ms = elapsed_ms(start);
+        std::hint::black_box(checksum);
+
+        let start = Instant::now();
+        let mut rewritten = RleBiomeMap::new_filled(map.width, map.height, BIOME_UNASSIGNED);
+        for y in 0..map.height {
+            for x in 0..map.width {
+                rewritten.set(x, y, map.get(x, y));
+            }
+        }
+        let set_ms = elapsed_ms(start);
+        debug_assert_eq!(rewritten, rle);
+
+        Self {
+            flat_kb: map.data().len() as f64 / 1024.0,
+            rle_kb: rle.memory_bytes() as f64 / 1024.0,
+            runs: rle.run_count(),
+            encode_ms,
+            decode_ms,
+            get_ms,
+            set_ms,
+        }
+    }
+}
+
+/// `--bench` 入口：依次生成三种尺寸，打印每步耗时对照表，
+/// 以及最终环境地图平铺 / 游程两种形式的对比（只计时互转与逐格读写，生成始终用平铺形式）。
+pub fn run_bench_cli(args: &[String]) -> bool {
+    let seed = match args.iter().position(|a| a == "--seed").and_then(|i| args.get(i + 1)) {
+        None => 42,
+        Some(s) => match s.parse::<u64>() {
+            Ok(seed) => seed,
+            Err(e) => {
+                eprintln!("无效的种子: {e}");
+                return false;
+            }
+        },
+    };
+
+    // 每个尺寸一列：(flat_index, 名称, 毫秒)
+    let mut columns: Vec<Vec<(usize, String, f64)>> = Vec::new();
+    let mut rle_stats: Vec<Option<RleBenchStats>> = Vec::new();
+    for size_key in BENCH_SIZES {
+        eprintln!("生成 {size_key} (seed {seed})...");
+        let generated = match generate_headless(seed, size_key, None) {
+            Ok(generated) => generated,
+            Err(e) => {
+                eprintln!("{size_key} 生成失败: {e}");
+                return false;
+            }
+        };
+        rle_stats.push(generated.pipeline.biome_map().map(RleBenchStats::measure));
+        columns.push(
+            generated.pipeline.profiler().step_durations()
+                .into_iter()
+                .map(|(idx, name, elapsed)| (idx, name, elapsed.as_secs_f64() * 1000.0))
+                .collect(),
+        );
+    }
+
+    println!("seed {seed}");
+    print!("{:>4}", "#");
+    for size_key in BENCH_SIZES {
+        print!("  {size_key:>10}");
+    }
+    println!("  名称");
+
+    let steps = columns.iter().map(Vec::len).max().unwrap_or(0);
+    for row in 0..steps {
+        let Some((idx, name)) = columns.iter()
+            .find_map(|c| c.get(row).map(|(idx, name, _)| (*idx, name.as_str())))
+        else {
+            continue;
+        };
+        print!("{idx:>4}");
+        for column in &columns {
+            match column.get(row) {
+                Some((_, _, ms)) => print!("  {ms:>10.2}"),
+                None => print!("  {:>10}", "-"),
+            }
+        }
+        println!("  {name}");
+    }
+
+    print!("{:>4}", "Σ");
+    for column in &columns {
+        let total: f64 = column.iter().map(|(_, _, ms)| ms).sum();
+        print!("  {total:>10.2}");
+    }
+    println!("  总计");
+
+    println!();
+    println!("游程对比：生成步骤始终写平铺地图，下表只对最终地图计时互转与逐格读写，不含以游程形式生成");
+    println!(
+        "{:>10}  {:>10}  {:>10}  {:>8}  {:>8}  {:>8}  {:>10}  {:>10}",
+        "环境地图", "平铺 KB", "游程 KB", "游程数", "编码 ms", "解码 ms", "逐格读 ms", "逐格写 ms"
+    );
+    for (size_key, stats) in BENCH_SIZES.iter().zip(&rle_stats) {
+        match stats {
+            Some(s) => println!(
+                "{size_key:>10}  {:>10.1}  {:>10.1}  {:>8}  {:>8.2}  {:>8.2}  {:>10.2}  {:>10.2}",
+                s.flat_kb, s.rle_kb, s.runs, s.encode_ms, s.decode_ms, s.get_ms, s.set_ms
+            ),
+            None => println!("{size_key:>10}  {:>10}", "-"),
+        }
+    }
+    true
+}
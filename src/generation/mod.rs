@@ -1,16 +1,31 @@
 pub mod algorithm;
+pub mod completion;
+pub mod error;
+pub mod export_worker;
+pub mod frame_recorder;
+pub mod headless;
 pub mod optimizer;
+pub mod param_fit;
+pub mod param_jitter;
+pub mod phase_registry;
 pub mod pipeline;
+pub mod regression;
+pub mod size_strip;
 pub mod snapshot;
+pub mod step_rng;
+pub mod validation;
+pub mod variant;
 
 use crate::algorithms::biome_division::BiomeDivisionAlgorithm;
+use crate::algorithms::custom_features::CustomFeaturesAlgorithm;
 use crate::core::biome::BiomeDefinition;
 use crate::core::layer::LayerDefinition;
 
 pub use algorithm::{PhaseAlgorithm, PhaseMeta, StepMeta, ParamDef, ParamType};
-pub use optimizer::{AdaptiveBatchSize, PerfProfiler, TextureUpdateThrottle};
-pub use pipeline::{GenerationPipeline, PhaseInfo, StepStatus};
-pub use snapshot::{WorldSnapshot, export_png};
+pub use error::GenerationError;
+pub use optimizer::{AdaptiveBatchSize, ManualStepThrottle, PerfProfiler, TextureUpdateThrottle};
+pub use pipeline::{BiomeToggle, GenerationPipeline, NoopStep, PhaseInfo, StepSeedTrace, StepStatus};
+pub use snapshot::WorldSnapshot;
 
 /// 构建默认流水线，注册所有算法模块。
 ///
@@ -18,6 +33,9 @@ pub use snapshot::{WorldSnapshot, export_png};
 /// 1. 在 `src/algorithms/` 下创建实现 `PhaseAlgorithm` 的模块
 /// 2. 在此函数中 `pipeline.register(Box::new(YourAlgorithm::new(...)))`
 ///
+/// 第三方 / 实验性阶段也可以不改此函数：启动时调用
+/// [`phase_registry::register_phase_factory`] 登记工厂，构建时追加在内置阶段之后。
+///
 /// 引擎会自动：读取 meta() → 构建 UI 步骤列表 → 按顺序执行。
 pub fn build_pipeline(
     seed: u64,
@@ -29,7 +47,15 @@ pub fn build_pipeline(
     // ── Phase 1: 环境判定 ──
     pipeline.register(Box::new(BiomeDivisionAlgorithm::new(&biome_definitions, layer_definitions)));
 
-    // ── Phase 2+: 未来在此注册更多算法模块 ──
+    // ── Phase 2: 自定义特征（数据驱动的额外形状） ──
+    pipeline.register(Box::new(CustomFeaturesAlgorithm::new(&biome_definitions)));
+
+    // ── Phase 3+: 未来在此注册更多算法模块 ──
+
+    // ── 扩展阶段：按登记顺序追加（地表装饰经 `algorithms::register_extension_phases` 登记） ──
+    for phase in phase_registry::build_registered_phases(&biome_definitions, layer_definitions) {
+        pipeline.register(phase);
+    }
 
     pipeline
 }
@@ -5,12 +5,14 @@
 //! - **AdaptiveBatchSize**: 根据实际步骤执行时间动态调整每帧批量大小
 //! - **PerfProfiler**: 记录每步执行时间，识别瓶颈并输出分析报告
 //! - **TextureUpdateThrottle**: 智能纹理更新节流
+//! - **ManualStepThrottle**: 手动步进的纹理刷新合并（连续点击后空闲再刷新）
 //!
 //! 所有可调参数来自 `EngineConfig`，不再硬编码。
 
 use std::collections::HashMap;
 use std::time::{Duration, Instant};
 
+use crate::core::geometry::FillPathStats;
 use crate::storage::engine_config::EngineConfig;
 
 // ═══════════════════════════════════════════════════════════
@@ -18,15 +20,20 @@ use crate::storage::engine_config::EngineConfig;
 // ═══════════════════════════════════════════════════════════
 
 /// 自适应批量大小控制器
+///
+/// 带滞回：EMA 帧时间落在 `target ± band` 内时不调整；超出后每次只 ±1，
+/// 且调整后等待 EMA 跟上（约 `1/alpha` 帧）再判断，避免 batch 来回振荡。
 pub struct AdaptiveBatchSize {
     current_batch: usize,
-    target_min_ms: f64,
-    target_max_ms: f64,
+    target_ms: f64,
+    band_ms: f64,
     min_batch: usize,
     max_batch: usize,
     last_frame_duration: Duration,
     ema_frame_ms: f64,
     alpha: f64,
+    /// 距下次允许调整还需等待的帧数
+    settle_frames: u32,
     /// 初始 batch（用于 reset）
     initial_batch: usize,
 }
@@ -36,21 +43,22 @@ impl AdaptiveBatchSize {
     pub fn from_config(cfg: &EngineConfig) -> Self {
         Self {
             current_batch: cfg.batch_initial,
-            target_min_ms: cfg.batch_target_min_ms,
-            target_max_ms: cfg.batch_target_max_ms,
+            target_ms: cfg.batch_target_ms,
+            band_ms: cfg.batch_band_ms,
             min_batch: cfg.batch_min,
             max_batch: cfg.batch_max,
             last_frame_duration: Duration::ZERO,
-            ema_frame_ms: (cfg.batch_target_min_ms + cfg.batch_target_max_ms) / 2.0,
+            ema_frame_ms: cfg.batch_target_ms,
             alpha: cfg.batch_ema_alpha,
+            settle_frames: 0,
             initial_batch: cfg.batch_initial,
         }
     }
 
     /// 应用新配置（UI 修改参数后调用）
     pub fn apply_config(&mut self, cfg: &EngineConfig) {
-        self.target_min_ms = cfg.batch_target_min_ms;
-        self.target_max_ms = cfg.batch_target_max_ms;
+        self.target_ms = cfg.batch_target_ms;
+        self.band_ms = cfg.batch_band_ms;
         self.min_batch = cfg.batch_min;
         self.max_batch = cfg.batch_max;
         self.alpha = cfg.batch_ema_alpha;
@@ -66,15 +74,20 @@ impl AdaptiveBatchSize {
         let frame_ms = duration.as_secs_f64() * 1000.0;
         self.ema_frame_ms = self.alpha * frame_ms + (1.0 - self.alpha) * self.ema_frame_ms;
 
-        if self.ema_frame_ms < self.target_min_ms {
-            let ratio = (self.target_min_ms / self.ema_frame_ms).min(2.0);
-            self.current_batch = ((self.current_batch as f64 * ratio).ceil() as usize)
-                .max(self.current_batch + 1)
-                .min(self.max_batch);
-        } else if self.ema_frame_ms > self.target_max_ms {
-            let ratio = (self.target_max_ms / self.ema_frame_ms).max(0.5);
-            self.current_batch = ((self.current_batch as f64 * ratio).floor() as usize)
-                .max(self.min_batch);
+        if self.settle_frames > 0 {
+            self.settle_frames -= 1;
+            return;
+        }
+
+        let previous = self.current_batch;
+        if self.ema_frame_ms < self.target_ms - self.band_ms {
+            self.current_batch = (self.current_batch + 1).min(self.max_batch);
+        } else if self.ema_frame_ms > self.target_ms + self.band_ms {
+            self.current_batch = self.current_batch.saturating_sub(1).max(self.min_batch);
+        }
+        if self.current_batch != previous {
+            // 等待 EMA 反映新 batch 的帧时间后再判断
+            self.settle_frames = (1.0 / self.alpha.max(0.01)).ceil() as u32;
         }
     }
 
@@ -88,8 +101,9 @@ impl AdaptiveBatchSize {
 
     pub fn reset(&mut self) {
         self.current_batch = self.initial_batch;
-        self.ema_frame_ms = (self.target_min_ms + self.target_max_ms) / 2.0;
+        self.ema_frame_ms = self.target_ms;
         self.last_frame_duration = Duration::ZERO;
+        self.settle_frames = 0;
     }
 }
 
@@ -110,6 +124,10 @@ pub struct StepProfile {
     pub min_duration: Duration,
     /// 最大执行时间
     pub max_duration: Duration,
+    /// 最近一次执行时间
+    pub last_duration: Duration,
+    /// 最近一次执行中各填充操作走并行 / 串行路径的统计
+    pub last_fills: FillPathStats,
 }
 
 impl StepProfile {
@@ -120,11 +138,15 @@ impl StepProfile {
             total_duration: Duration::ZERO,
             min_duration: Duration::MAX,
             max_duration: Duration::ZERO,
+            last_duration: Duration::ZERO,
+            last_fills: FillPathStats::default(),
         }
     }
 
-    fn record(&mut self, duration: Duration) {
+    fn record(&mut self, duration: Duration, fills: FillPathStats) {
         self.run_count += 1;
+        self.last_duration = duration;
+        self.last_fills = fills;
         self.total_duration += duration;
         self.min_duration = self.min_duration.min(duration);
         self.max_duration = self.max_duration.max(duration);
@@ -154,6 +176,8 @@ pub struct PerfProfiler {
     total_duration: Duration,
     /// 启动时间
     start_time: Option<Instant>,
+    /// 最近一次执行的步骤（flat_index）
+    last_step: Option<usize>,
 }
 
 impl Default for PerfProfiler {
@@ -164,6 +188,7 @@ impl Default for PerfProfiler {
             max_recent_frames: 120,
             total_duration: Duration::ZERO,
             start_time: None,
+            last_step: None,
         }
     }
 }
@@ -178,13 +203,20 @@ impl PerfProfiler {
         self.start_time = Some(Instant::now());
     }
 
-    /// 记录一个步骤的执行时间
-    pub fn record_step(&mut self, flat_index: usize, name: &str, duration: Duration) {
+    /// 记录一个步骤的执行时间与填充路径统计
+    pub fn record_step(&mut self, flat_index: usize, name: &str, duration: Duration, fills: FillPathStats) {
         self.total_duration += duration;
+        self.last_step = Some(flat_index);
         self.steps
             .entry(flat_index)
             .or_insert_with(|| StepProfile::new(name))
-            .record(duration);
+            .record(duration, fills);
+    }
+
+    /// 最近一次执行的步骤 `(flat_index, 性能数据)`
+    pub fn last_step(&self) -> Option<(usize, &StepProfile)> {
+        let idx = self.last_step?;
+        self.steps.get(&idx).map(|sp| (idx, sp))
     }
 
     /// 记录一帧时间
@@ -229,6 +261,16 @@ impl PerfProfiler {
         entries
     }
 
+    /// 每个已执行步骤最近一次的耗时 `(flat_index, 名称, 耗时)`（按 flat_index 排序）
+    ///
+    /// 结构化数据，供测试与外部工具断言；展示用途请用 [`report`](Self::report)。
+    pub fn step_durations(&self) -> Vec<(usize, String, Duration)> {
+        self.all_steps_sorted()
+            .into_iter()
+            .map(|(idx, sp)| (idx, sp.name.clone(), sp.last_duration))
+            .collect()
+    }
+
     /// 生成性能报告字符串
     pub fn report(&self) -> String {
         let mut lines = Vec::new();
@@ -272,6 +314,7 @@ impl PerfProfiler {
         self.recent_frame_times.clear();
         self.total_duration = Duration::ZERO;
         self.start_time = None;
+        self.last_step = None;
     }
 }
 
@@ -337,3 +380,105 @@ impl TextureUpdateThrottle {
         }
     }
 }
+
+/// 手动步进的纹理刷新节流
+///
+/// 大世界上快速连点单步时，每步都重建小地图纹理会拖慢响应。启用后步进只推迟刷新，
+/// 最后一次步进后空闲满 `manual_refresh_idle_ms` 才刷新一次。
+/// 像素数低于 `throttle_small_threshold` 的小世界刷新开销低，不节流。
+#[derive(Default)]
+pub struct ManualStepThrottle {
+    /// 推迟的刷新到期时刻
+    deadline: Option<Instant>,
+}
+
+impl ManualStepThrottle {
+    /// 当前世界的空闲等待时间；为零表示不节流
+    pub fn idle_for(cfg: &EngineConfig, world_width: u32, world_height: u32) -> Duration {
+        let world_pixels = (world_width as usize) * (world_height as usize);
+        if world_pixels < cfg.throttle_small_threshold {
+            Duration::ZERO
+        } else {
+            Duration::from_millis(cfg.manual_refresh_idle_ms)
+        }
+    }
+
+    /// 手动步进后调用：返回是否应立即刷新（`idle` 为零时），否则把刷新推迟到空闲之后
+    pub fn defer(&mut self, idle: Duration) -> bool {
+        if idle.is_zero() {
+            self.deadline = None;
+            return true;
+        }
+        self.deadline = Some(Instant::now() + idle);
+        false
+    }
+
+    /// 每帧调用：推迟的刷新到期时返回 `true` 并清除
+    pub fn tick(&mut self) -> bool {
+        match self.deadline {
+            Some(deadline) if Instant::now() >= deadline => {
+                self.deadline = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// 距推迟的刷新到期还剩多久（供安排重绘）；没有推迟的刷新时为 `None`
+    pub fn remaining(&self) -> Option<Duration> {
+        self.deadline.map(|d| d.saturating_duration_since(Instant::now()))
+    }
+
+    /// 纹理已因其他原因刷新，放弃推迟的刷新
+    pub fn cancel(&mut self) {
+        self.deadline = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread::sleep;
+    use std::time::Duration;
+
+    use super::ManualStepThrottle;
+    use crate::storage::engine_config::EngineConfig;
+
+    #[test]
+    fn zero_idle_refreshes_immediately() {
+        let mut throttle = ManualStepThrottle::default();
+        assert!(throttle.defer(Duration::ZERO), "空闲时间为零时应立即刷新");
+        assert!(throttle.remaining().is_none());
+        assert!(!throttle.tick(), "立即刷新后不应再有推迟的刷新");
+
+        // 推迟中的刷新在切换为不节流后被立即刷新取代
+        throttle.defer(Duration::from_secs(60));
+        assert!(throttle.defer(Duration::ZERO));
+        assert!(!throttle.tick());
+
+        let cfg = EngineConfig::default();
+        let small = (cfg.throttle_small_threshold as f64).sqrt() as u32 / 2;
+        assert_eq!(ManualStepThrottle::idle_for(&cfg, small, small), Duration::ZERO, "小世界不节流");
+    }
+
+    #[test]
+    fn deadline_fires_once_and_can_be_cancelled() {
+        let mut throttle = ManualStepThrottle::default();
+        assert!(!throttle.defer(Duration::from_millis(20)));
+        assert!(!throttle.tick(), "到期前不应刷新");
+        assert!(throttle.remaining().is_some_and(|r| r <= Duration::from_millis(20)));
+
+        sleep(Duration::from_millis(30));
+        assert!(throttle.tick(), "到期后应刷新");
+        assert!(!throttle.tick(), "同一次推迟只刷新一次");
+        assert!(throttle.remaining().is_none());
+
+        // 连续步进把期限往后推；取消后不再刷新
+        throttle.defer(Duration::from_millis(20));
+        throttle.defer(Duration::from_secs(60));
+        sleep(Duration::from_millis(30));
+        assert!(!throttle.tick(), "再次步进应重新计时");
+        throttle.cancel();
+        assert!(throttle.remaining().is_none());
+        assert!(!throttle.tick(), "取消后不应刷新");
+    }
+}
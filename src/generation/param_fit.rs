@@ -0,0 +1,468 @@
+//! # 参数拟合
+//!
+//! 给定目标环境覆盖率（如「丛林 ≈ 8%、雪原 ≈ 6%」），对环境判定的尺寸类参数做简单的爬山搜索：
+//! 每轮随机扰动一个与目标环境相关的参数，无界面重新生成世界并统计环境直方图，
+//! 误差（各目标覆盖率偏差的均方根，单位为百分点）下降才接受，连续多轮无改进时缩小步长。
+//!
+//! 这只是粗搜索：结果取决于种子与世界尺寸，适合作为手动微调的起点。
+//!
+//! 覆盖率按基础环境统计：变体（如湿润丛林）计入它的基础环境（丛林）。
+//!
+//! 锁定的参数（`FitSettings::locked`，对应参数窗口中的「锁定」）保持起始值，不参与扰动。
+//!
+//! `Lwd fit jungle=8 snow=6 [--seed N] [--size KEY] [--iterations N] [--samples N] [--lock KEY,...]`：
+//! 打印每次改进、各目标的拟合前后覆盖率，以及最终参数 JSON。
+
+use std::collections::HashMap;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde_json::{json, Value};
+
+use crate::core::biome::BiomeId;
+use crate::core::layer::LayerDefinition;
+
+use super::algorithm::ParamType;
+use super::headless::HeadlessContext;
+use super::build_pipeline;
+use super::step_rng::RngKind;
+
+/// 被拟合的算法模块
+pub const FIT_PHASE_ID: &str = "biome_division";
+
+/// 各环境的覆盖率主要由哪些参数决定（只扰动这些参数）
+const TUNABLE: &[(&str, &[&str])] = &[
+    ("ocean", &["ocean_left_width", "ocean_right_width"]),
+    ("forest", &["forest_surface_extent", "forest_width_ratio"]),
+    ("jungle", &["jungle_width_ratio"]),
+    ("snow", &["snow_top_width_ratio", "snow_bottom_width_ratio"]),
+    ("desert", &["desert_surface_count", "desert_surface_width_min", "desert_surface_width_max"]),
+    ("desert_true", &["desert_true_count", "desert_true_depth_factor"]),
+    ("crimson", &["evil_count", "evil_width_min", "evil_width_max"]),
+    ("corruption", &["evil_count", "evil_width_min", "evil_width_max"]),
+];
+
+/// 成对的下限 / 上限参数：扰动后保持 `min ≤ max`
+const MIN_MAX_PAIRS: &[(&str, &str)] = &[
+    ("desert_surface_width_min", "desert_surface_width_max"),
+    ("evil_width_min", "evil_width_max"),
+];
+
+/// 默认迭代轮数
+pub const DEFAULT_ITERATIONS: usize = 60;
+/// 初始扰动幅度（相对参数当前值）
+const INITIAL_SCALE: f64 = 0.5;
+/// 步长下限
+const MIN_SCALE: f64 = 0.02;
+/// 连续多少轮没有改进后步长减半
+const PATIENCE: usize = 6;
+
+/// 单个环境的目标覆盖率
+#[derive(Debug, Clone, PartialEq)]
+pub struct FitTarget {
+    /// 环境 key（如 "jungle"）
+    pub biome_key: String,
+    /// 目标覆盖率（占全图格数的百分比）
+    pub percent: f64,
+}
+
+/// 一次拟合的设置
+#[derive(Debug, Clone)]
+pub struct FitSettings {
+    pub seed: u64,
+    pub size_key: String,
+    /// 仅在 `size_key == "custom"` 时生效
+    pub custom_size: Option<(u32, u32)>,
+    /// 覆盖 world.json 的默认层级（应用内传入当前层级配置）
+    pub layers: Option<Vec<LayerDefinition>>,
+    /// 迭代轮数（每轮生成 `samples` 个世界）
+    pub iterations: usize,
+    /// 每组参数取 `seed, seed+1, …` 共几个种子的平均覆盖率
+    pub samples: usize,
+    /// 子种子是否混入世界尺寸（与应用内的设置保持一致）
+    pub size_mixing: bool,
+    /// 每步 RNG 的算法（与应用内的设置保持一致）
+    pub rng_kind: RngKind,
+    /// 锁定的参数键：搜索中保持起始值不变
+    pub locked: Vec<String>,
+}
+
+/// 拟合结果
+#[derive(Debug, Clone)]
+pub struct FitResult {
+    /// 找到的最佳参数（完整的环境判定参数对象，可直接 `set_params`）
+    pub params: Value,
+    /// 相对起始参数有变化的项 `(键, 原值, 新值)`
+    pub changed: Vec<(String, Value, Value)>,
+    /// 起始参数下各目标的覆盖率（与目标列表一一对应）
+    pub initial_coverage: Vec<f64>,
+    /// 最佳参数下各目标的覆盖率
+    pub coverage: Vec<f64>,
+    pub initial_error: f64,
+    pub error: f64,
+    /// 实际完成的迭代轮数（中途停止时小于设置值）
+    pub iterations: usize,
+}
+
+/// 可以作为拟合目标的环境 key
+pub fn tunable_biome_keys() -> impl Iterator<Item = &'static str> {
+    TUNABLE.iter().map(|(key, _)| *key)
+}
+
+fn tunable_params(biome_key: &str) -> &'static [&'static str] {
+    TUNABLE.iter()
+        .find(|(key, _)| *key == biome_key)
+        .map(|(_, params)| *params)
+        .unwrap_or(&[])
+}
+
+/// 目标环境的可调参数中未被锁定的部分
+fn unlocked_params<'a>(biome_key: &str, locked: &'a [String]) -> impl Iterator<Item = &'static str> + 'a {
+    tunable_params(biome_key).iter()
+        .copied()
+        .filter(move |key| !locked.iter().any(|l| l == key))
+}
+
+/// 解析 `key=百分比` 形式的目标列表
+pub fn parse_targets(specs: &[String]) -> Result<Vec<FitTarget>, String> {
+    specs.iter()
+        .map(|spec| {
+            let (key, percent) = spec.split_once('=')
+                .ok_or_else(|| format!("目标格式应为 环境=百分比: {spec}"))?;
+            let percent = percent.trim().trim_end_matches('%').parse::<f64>()
+                .map_err(|e| format!("无效的百分比 {percent}: {e}"))?;
+            Ok(FitTarget { biome_key: key.trim().to_string(), percent })
+        })
+        .collect()
+}
+
+/// 均方根误差（百分点）
+fn rms_error(coverage: &[f64], targets: &[FitTarget]) -> f64 {
+    let sum: f64 = coverage.iter()
+        .zip(targets)
+        .map(|(c, t)| (c - t.percent).powi(2))
+        .sum();
+    (sum / targets.len().max(1) as f64).sqrt()
+}
+
+/// 在参数范围内扰动一个数值参数；非数值参数返回 `None`
+fn perturb(value: &Value, param_type: &ParamType, scale: f64, rng: &mut StdRng) -> Option<Value> {
+    match *param_type {
+        ParamType::Float { min, max } => {
+            let v = value.as_f64()?;
+            let span = v.abs().max((max - min) * 0.01);
+            let next = (v + rng.gen_range(-1.0..=1.0) * scale * span).clamp(min, max);
+            Some(json!((next * 1e4).round() / 1e4))
+        }
+        ParamType::Int { min, max } => {
+            let v = value.as_i64()?;
+            let step = if rng.gen_bool(0.5) { 1 } else { -1 };
+            Some(json!((v + step).clamp(min, max)))
+        }
+        _ => None,
+    }
+}
+
+/// 修改 `key` 后保持成对的下限 / 上限有序；另一端被锁定时改为把 `key` 本身收回到锁定值
+fn keep_min_max(params: &mut Value, key: &str, locked: &[String]) {
+    for &(lo, hi) in MIN_MAX_PAIRS {
+        let (Some(a), Some(b)) = (params[lo].as_f64(), params[hi].as_f64()) else {
+            continue;
+        };
+        if a <= b {
+            continue;
+        }
+        let is_locked = |k: &str| locked.iter().any(|l| l == k);
+        if key == lo {
+            if is_locked(hi) {
+                params[lo] = json!(b);
+            } else {
+                params[hi] = json!(a);
+            }
+        } else if key == hi {
+            if is_locked(lo) {
+                params[hi] = json!(a);
+            } else {
+                params[lo] = json!(b);
+            }
+        }
+    }
+}
+
+/// 从 `start` 出发拟合环境判定参数。
+///
+/// `on_progress(已完成轮数, 当前最佳误差)` 每轮回调一次，返回 `false` 时提前停止并返回目前的最佳结果。
+pub fn fit_biome_params(
+    settings: &FitSettings,
+    start: &Value,
+    targets: &[FitTarget],
+    mut on_progress: impl FnMut(usize, f64) -> bool,
+) -> Result<FitResult, String> {
+    if targets.is_empty() {
+        return Err("没有拟合目标".to_string());
+    }
+    let mut context = HeadlessContext::load(&settings.size_key, settings.custom_size)?;
+    if let Some(layers) = &settings.layers {
+        context.profile.layers = layers.clone();
+    }
+
+    // 目标环境 id（连同它的变体，如湿润丛林计入丛林）与参数范围
+    let mut target_ids: Vec<Vec<BiomeId>> = Vec::with_capacity(targets.len());
+    for target in targets {
+        let biome = context.biomes.iter()
+            .find(|b| b.key == target.biome_key)
+            .ok_or_else(|| format!("未知环境: {}", target.biome_key))?;
+        if tunable_params(&target.biome_key).is_empty() {
+            return Err(format!("{} 没有可调的尺寸参数", biome.name));
+        }
+        if unlocked_params(&target.biome_key, &settings.locked).next().is_none() {
+            return Err(format!("{} 的可调参数均已锁定", biome.name));
+        }
+        target_ids.push(
+            std::iter::once(biome.id)
+                .chain(biome.variants.iter().map(|&(variant, _)| variant))
+                .collect(),
+        );
+    }
+    let mut probe = build_pipeline(settings.seed, context.biomes.clone(), &context.profile.layers);
+    let algorithm = probe.algorithm_by_id_mut(FIT_PHASE_ID)
+        .ok_or_else(|| format!("流水线中没有 {FIT_PHASE_ID} 模块"))?;
+    let ranges: HashMap<String, ParamType> = algorithm.meta().params
+        .into_iter()
+        .map(|p| (p.key, p.param_type))
+        .collect();
+    // 起始参数先经算法规范化（补全缺失项）
+    algorithm.set_params(start);
+    let start = algorithm.get_params();
+
+    let samples = settings.samples.max(1);
+    let measure = |params: &Value| -> Result<Vec<f64>, String> {
+        let mut coverage = vec![0.0; targets.len()];
+        for sample in 0..samples {
+            let pipeline = context.generate(settings.seed.wrapping_add(sample as u64), |p| {
+                p.set_step_logging(false);
+                p.set_size_mixing(settings.size_mixing);
+                p.set_rng_kind(settings.rng_kind);
+                if let Some(algo) = p.algorithm_by_id_mut(FIT_PHASE_ID) {
+                    algo.set_params(params);
+                }
+            })
+            .map_err(|e| e.to_string())?;
+            let bm = pipeline.biome_map().ok_or("生成后没有环境地图")?;
+            let histogram = bm.histogram();
+            let total = histogram.iter().sum::<u64>().max(1) as f64;
+            for (c, ids) in coverage.iter_mut().zip(&target_ids) {
+                let count: u64 = ids.iter().map(|&id| histogram[id as usize]).sum();
+                *c += count as f64 / total * 100.0 / samples as f64;
+            }
+        }
+        Ok(coverage)
+    };
+
+    let initial_coverage = measure(&start)?;
+    let initial_error = rms_error(&initial_coverage, targets);
+    let mut best = start.clone();
+    let mut best_coverage = initial_coverage.clone();
+    let mut best_error = initial_error;
+
+    let mut rng = StdRng::seed_from_u64(settings.seed ^ 0xF17_0000);
+    let mut scale = INITIAL_SCALE;
+    let mut stale = 0;
+    let mut done = 0;
+    while done < settings.iterations && best_error > 0.0 {
+        // 偏差越大的目标越常被选中
+        let weights: Vec<f64> = best_coverage.iter()
+            .zip(targets)
+            .map(|(c, t)| (c - t.percent).abs() + 1e-3)
+            .collect();
+        let mut pick = rng.gen_range(0.0..weights.iter().sum::<f64>());
+        let target = weights.iter()
+            .position(|w| {
+                pick -= w;
+                pick < 0.0
+            })
+            .unwrap_or(targets.len() - 1);
+        let keys: Vec<&str> = unlocked_params(&targets[target].biome_key, &settings.locked).collect();
+        let key = keys[rng.gen_range(0..keys.len())];
+
+        let mut candidate = best.clone();
+        let next = ranges.get(key).and_then(|ty| perturb(&candidate[key], ty, scale, &mut rng));
+        if let Some(next) = next.filter(|v| *v != best[key]) {
+            candidate[key] = next;
+            keep_min_max(&mut candidate, key, &settings.locked);
+            let coverage = measure(&candidate)?;
+            let error = rms_error(&coverage, targets);
+            if error < best_error {
+                best = candidate;
+                best_coverage = coverage;
+                best_error = error;
+                stale = 0;
+            } else {
+                stale += 1;
+            }
+        } else {
+            stale += 1;
+        }
+        if stale >= PATIENCE {
+            scale = (scale * 0.5).max(MIN_SCALE);
+            stale = 0;
+        }
+
+        done += 1;
+        if !on_progress(done, best_error) {
+            break;
+        }
+    }
+
+    let changed = start.as_object()
+        .map(|obj| {
+            obj.iter()
+                .filter(|(key, value)| best[key.as_str()] != **value)
+                .map(|(key, value)| (key.clone(), value.clone(), best[key.as_str()].clone()))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(FitResult {
+        params: best,
+        changed,
+        initial_coverage,
+        coverage: best_coverage,
+        initial_error,
+        error: best_error,
+        iterations: done,
+    })
+}
+
+/// 命令行入口：拟合默认参数并打印结果，成功返回 `true`。
+pub fn run_cli(args: &[String]) -> bool {
+    let mut specs = Vec::new();
+    let mut settings = FitSettings {
+        seed: 42,
+        size_key: "small".to_string(),
+        custom_size: None,
+        layers: None,
+        iterations: DEFAULT_ITERATIONS,
+        samples: 1,
+        size_mixing: true,
+        rng_kind: RngKind::default(),
+        locked: Vec::new(),
+    };
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if !arg.starts_with("--") {
+            specs.push(arg.clone());
+            continue;
+        }
+        let Some(value) = iter.next() else {
+            eprintln!("{arg} 缺少取值");
+            return false;
+        };
+        let parsed = match arg.as_str() {
+            "--seed" => value.parse().map(|v| settings.seed = v).map_err(|e| e.to_string()),
+            "--size" => {
+                settings.size_key = value.clone();
+                Ok(())
+            }
+            "--iterations" => value.parse().map(|v| settings.iterations = v).map_err(|e| e.to_string()),
+            "--samples" => value.parse().map(|v| settings.samples = v).map_err(|e| e.to_string()),
+            "--lock" => {
+                settings.locked.extend(value.split(',').map(str::trim).filter(|k| !k.is_empty()).map(str::to_string));
+                Ok(())
+            }
+            _ => Err("未知选项".to_string()),
+        };
+        if let Err(e) = parsed {
+            eprintln!("{arg} {value}: {e}");
+            return false;
+        }
+    }
+
+    let targets = match parse_targets(&specs) {
+        Ok(targets) if !targets.is_empty() => targets,
+        Ok(_) => {
+            eprintln!("用法: Lwd fit <环境=百分比>... [--seed N] [--size KEY] [--iterations N] [--samples N] [--lock KEY,...]");
+            eprintln!("可拟合的环境: {}", tunable_biome_keys().collect::<Vec<_>>().join(", "));
+            return false;
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            return false;
+        }
+    };
+
+    let mut last_error = f64::INFINITY;
+    let result = fit_biome_params(&settings, &Value::Null, &targets, |done, error| {
+        if error < last_error {
+            eprintln!("第 {done} 轮: 误差 {error:.3}");
+            last_error = error;
+        }
+        true
+    });
+    let result = match result {
+        Ok(result) => result,
+        Err(e) => {
+            eprintln!("拟合失败: {e}");
+            return false;
+        }
+    };
+
+    println!("{:<12} {:>8} {:>8} {:>8}", "环境", "目标%", "初始%", "拟合%");
+    for (i, target) in targets.iter().enumerate() {
+        println!(
+            "{:<12} {:>8.2} {:>8.2} {:>8.2}",
+            target.biome_key, target.percent, result.initial_coverage[i], result.coverage[i],
+        );
+    }
+    println!("误差: {:.3} → {:.3}（{} 轮）", result.initial_error, result.error, result.iterations);
+    for (key, before, after) in &result.changed {
+        println!("  {key}: {before} → {after}");
+    }
+    println!("{}", serde_json::to_string_pretty(&result.params).unwrap_or_default());
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::{fit_biome_params, keep_min_max, FitSettings, FitTarget};
+    use crate::generation::step_rng::RngKind;
+
+    #[test]
+    fn keep_min_max_pulls_back_the_edited_key_when_the_other_end_is_locked() {
+        let locked = vec!["evil_width_max".to_string()];
+        let mut params = json!({ "evil_width_min": 0.3, "evil_width_max": 0.2 });
+        keep_min_max(&mut params, "evil_width_min", &locked);
+        assert_eq!(params, json!({ "evil_width_min": 0.2, "evil_width_max": 0.2 }), "锁定的上限不应被推高");
+
+        let mut params = json!({ "evil_width_min": 0.3, "evil_width_max": 0.2 });
+        keep_min_max(&mut params, "evil_width_min", &[]);
+        assert_eq!(params, json!({ "evil_width_min": 0.3, "evil_width_max": 0.3 }), "未锁定时推高上限");
+    }
+
+    #[test]
+    fn fit_leaves_locked_params_unchanged() {
+        let locked = vec!["desert_surface_width_max".to_string(), "desert_surface_count".to_string()];
+        let settings = FitSettings {
+            seed: 3,
+            size_key: "small".to_string(),
+            custom_size: None,
+            layers: None,
+            iterations: 12,
+            samples: 1,
+            size_mixing: true,
+            rng_kind: RngKind::default(),
+            locked: locked.clone(),
+        };
+        let targets = [FitTarget { biome_key: "desert".to_string(), percent: 40.0 }];
+        let result = fit_biome_params(&settings, &Value::Null, &targets, |_, _| true).unwrap();
+        for key in &locked {
+            assert!(result.changed.iter().all(|(k, _, _)| k != key), "锁定的参数 {key} 不应被修改");
+        }
+        assert!(
+            result.params["desert_surface_width_min"].as_f64() <= result.params["desert_surface_width_max"].as_f64(),
+            "下限不应超过锁定的上限",
+        );
+    }
+}
@@ -0,0 +1,125 @@
+//! # 参数抖动
+//!
+//! 同一组参数下，不同种子的世界只有位置不同、结构相同。开启抖动后，选定的数值参数
+//! （`算法ID.参数键`）在执行时按种子偏移 ±`strength`（如 0.1 = ±10%），结果仍限制在
+//! `ParamDef` 的范围内、整数参数四舍五入。
+//!
+//! 偏移量只由主种子与参数名决定，不写入参数本身：界面与存档中保存的始终是原始参数，
+//! 同一种子回放得到同样的抖动。散布盐、世界尺寸与步骤 RNG 算法都不影响抖动。
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::generation::algorithm::{ParamType, PhaseMeta};
+
+/// 抖动幅度上限（±50%）
+pub const MAX_JITTER_STRENGTH: f64 = 0.5;
+
+/// 参数 `entry`（`算法ID.参数键`）在种子 `seed` 下的抖动系数，范围 `[-1, 1]`
+fn jitter_unit(seed: u64, entry: &str) -> f64 {
+    let hash = entry.bytes().fold(0xcbf2_9ce4_8422_2325u64, |hash, b| {
+        (hash ^ b as u64).wrapping_mul(0x0100_0000_01b3)
+    });
+    StdRng::seed_from_u64(seed ^ hash).gen_range(-1.0..=1.0)
+}
+
+/// 对 `params` 中列在 `keys` 里的数值参数施加抖动，返回新的参数对象。
+///
+/// 非数值参数、未声明的键与缺失的值保持不变；`strength` 限制在 `0 ~ MAX_JITTER_STRENGTH`。
+pub fn jitter_params(
+    params: &serde_json::Value,
+    meta: &PhaseMeta,
+    keys: &[&str],
+    strength: f64,
+    seed: u64,
+) -> serde_json::Value {
+    let strength = strength.clamp(0.0, MAX_JITTER_STRENGTH);
+    let mut params = params.clone();
+    let Some(obj) = params.as_object_mut() else {
+        return params;
+    };
+    for def in meta.params.iter().filter(|d| keys.contains(&d.key.as_str())) {
+        let Some(value) = obj.get(&def.key).and_then(serde_json::Value::as_f64) else {
+            continue;
+        };
+        let factor = 1.0 + strength * jitter_unit(seed, &format!("{}.{}", meta.id, def.key));
+        let jittered = match def.param_type {
+            ParamType::Float { min, max } => serde_json::json!((value * factor).clamp(min, max)),
+            ParamType::Int { min, max } => serde_json::json!(((value * factor).round() as i64).clamp(min, max)),
+            _ => continue,
+        };
+        obj.insert(def.key.clone(), jittered);
+    }
+    params
+}
+
+#[cfg(test)]
+mod tests {
+    use super::jitter_params;
+    use crate::algorithms::decoration::{DecorationMarker, DECORATION_MARKERS_KEY};
+    use crate::generation::build_pipeline;
+    use crate::generation::headless::HeadlessContext;
+
+    #[test]
+    fn jitter_is_seeded_bounded_and_limited_to_selected_params() {
+        let context = HeadlessContext::load("small", None).unwrap();
+        let pipeline = build_pipeline(1, context.biomes.clone(), &context.profile.layers);
+        let algo = pipeline.algorithms()
+            .find(|a| a.meta().params.iter().filter(|d| d.default.is_f64()).count() >= 2)
+            .expect("至少有一个算法带两个浮点参数");
+        let meta = algo.meta();
+        let base = algo.get_params();
+        let floats: Vec<&str> = meta.params.iter()
+            .filter(|d| d.default.is_f64())
+            .map(|d| d.key.as_str())
+            .collect();
+        let (selected, untouched) = (floats[0], floats[1]);
+
+        let first = jitter_params(&base, &meta, &[selected], 0.1, 7);
+        assert_eq!(first, jitter_params(&base, &meta, &[selected], 0.1, 7), "同一种子的抖动应一致");
+        assert_eq!(first[untouched], base[untouched], "未选中的参数不应抖动");
+
+        let original = base[selected].as_f64().unwrap();
+        let value = first[selected].as_f64().unwrap();
+        assert!((value - original).abs() <= original.abs() * 0.1 + 1e-9, "抖动不应超过 ±10%");
+
+        let varied = (0..16).any(|seed| jitter_params(&base, &meta, &[selected], 0.1, seed)[selected] != first[selected]);
+        assert!(varied, "不同种子应得到不同的抖动");
+        assert_eq!(jitter_params(&base, &meta, &[selected], 0.0, 7), base, "强度为 0 时参数不变");
+    }
+
+    #[test]
+    fn pipeline_applies_jitter_without_changing_stored_params() {
+        let context = HeadlessContext::load("small", None).unwrap();
+        let run = |strength: f64| {
+            let pipeline = context.generate(5, |p| {
+                p.set_step_logging(false);
+                p.set_param_jittered("decoration", "density_scale", true);
+                p.set_jitter_strength(strength);
+            }).unwrap();
+            let markers = pipeline.shared::<Vec<DecorationMarker>>(DECORATION_MARKERS_KEY).cloned().unwrap();
+            (markers, pipeline.algorithm_by_id("decoration").unwrap().get_params())
+        };
+
+        let (plain, _) = run(0.0);
+        let (first, params) = run(0.5);
+        assert_eq!(first, run(0.5).0, "同一种子开启抖动后应可复现");
+        assert_ne!(first.len(), plain.len(), "抖动应改变装饰密度");
+        assert_eq!(params["density_scale"], serde_json::json!(1.0), "执行后应恢复原始参数");
+    }
+
+    #[test]
+    fn locked_params_are_not_jittered() {
+        let context = HeadlessContext::load("small", None).unwrap();
+        let markers = |jitter: bool| {
+            let pipeline = context.generate(5, |p| {
+                p.set_step_logging(false);
+                p.set_param_locked("decoration", "density_scale", true);
+                p.set_param_jittered("decoration", "density_scale", jitter);
+                p.set_jitter_strength(0.5);
+            }).unwrap();
+            pipeline.shared::<Vec<DecorationMarker>>(DECORATION_MARKERS_KEY).cloned().unwrap()
+        };
+        assert_eq!(markers(true), markers(false), "锁定的参数不应参与抖动");
+    }
+}
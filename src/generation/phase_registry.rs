@@ -0,0 +1,84 @@
+//! # 扩展阶段注册表
+//!
+//! 内置阶段（环境判定、自定义特征）在 [`build_pipeline`](super::build_pipeline) 中直接注册。
+//! 其余阶段无需修改该函数：启动时调用 [`register_phase_factory`] 登记一个工厂，
+//! 此后每次构建流水线都会在内置阶段之后按登记顺序追加它构造的阶段。
+//! 地表装饰阶段（`decoration`）即由 `algorithms::register_extension_phases` 经此登记。
+//!
+//! 工厂以阶段 ID 为键；与内置阶段或已登记阶段重名的登记会被拒绝并写入警告日志。
+
+use std::sync::{Mutex, OnceLock};
+
+use crate::core::biome::BiomeDefinition;
+use crate::core::layer::LayerDefinition;
+use crate::core::log;
+
+use super::algorithm::PhaseAlgorithm;
+
+/// 内置阶段的 ID，扩展阶段不得占用
+pub const BUILTIN_PHASE_IDS: [&str; 2] = ["biome_division", "custom_features"];
+
+/// 阶段工厂：按环境定义与层级定义构造一个阶段（与内置阶段的构造参数一致）
+pub type PhaseFactory = fn(&[BiomeDefinition], &[LayerDefinition]) -> Box<dyn PhaseAlgorithm>;
+
+fn registry() -> &'static Mutex<Vec<(String, PhaseFactory)>> {
+    static REGISTRY: OnceLock<Mutex<Vec<(String, PhaseFactory)>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+/// 登记一个扩展阶段工厂；ID 与内置阶段或已登记阶段重复时忽略并返回 `false`。
+///
+/// 应在启动时、构建第一条流水线之前调用（`main` 经 `algorithms::register_extension_phases` 登记）；
+/// 已构建的流水线不会补上新登记的阶段。
+pub fn register_phase_factory(id: &str, factory: PhaseFactory) -> bool {
+    if BUILTIN_PHASE_IDS.contains(&id) {
+        log::warn(format!("扩展阶段 {id} 与内置阶段重名，已忽略"));
+        return false;
+    }
+    let mut factories = registry().lock().unwrap();
+    if factories.iter().any(|(existing, _)| existing == id) {
+        log::warn(format!("扩展阶段 {id} 已登记，已忽略重复登记"));
+        return false;
+    }
+    factories.push((id.to_string(), factory));
+    true
+}
+
+/// 按登记顺序构造所有扩展阶段。
+///
+/// 构造出的阶段 `meta().id` 与登记 ID 不一致时照常追加，但写入警告日志——
+/// 快照与参数持久化按 `meta().id` 匹配阶段。
+pub(super) fn build_registered_phases(
+    biome_definitions: &[BiomeDefinition],
+    layer_definitions: &[LayerDefinition],
+) -> Vec<Box<dyn PhaseAlgorithm>> {
+    let factories = registry().lock().unwrap().clone();
+    factories
+        .into_iter()
+        .map(|(id, factory)| {
+            let phase = factory(biome_definitions, layer_definitions);
+            let meta_id = phase.meta().id;
+            if meta_id != id {
+                log::warn(format!("扩展阶段登记为 {id}，但其 meta().id 为 {meta_id}"));
+            }
+            phase
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::custom_features::CustomFeaturesAlgorithm;
+
+    #[test]
+    fn builtin_ids_cannot_be_registered() {
+        fn factory(biomes: &[BiomeDefinition], _: &[LayerDefinition]) -> Box<dyn PhaseAlgorithm> {
+            Box::new(CustomFeaturesAlgorithm::new(biomes))
+        }
+        for id in BUILTIN_PHASE_IDS {
+            assert!(!register_phase_factory(id, factory), "内置阶段 {id} 不应被覆盖");
+        }
+        assert!(registry().lock().unwrap().iter().all(|(id, _)| !BUILTIN_PHASE_IDS.contains(&id.as_str())));
+    }
+}
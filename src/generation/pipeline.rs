@@ -4,19 +4,21 @@
 //! 每个子步骤使用从主种子派生的确定性 RNG，因此从头回放总能复现相同的世界。
 
 use std::any::Any;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::time::Instant;
 
-use rand::rngs::StdRng;
-use rand::SeedableRng;
-
-use crate::core::biome::{BiomeDefinition, BiomeMap};
+use crate::core::biome::{BiomeDefinition, BiomeId, BiomeMap, BIOME_UNASSIGNED};
 use crate::core::block::BlockDefinition;
-use crate::core::geometry::ShapeRecord;
+use crate::core::geometry::{self, ShapeRecord};
+use crate::core::log;
 use crate::core::world::{World, WorldProfile};
 
-use super::algorithm::{PhaseAlgorithm, RuntimeContext};
+use super::algorithm::{OverwritePolicy, PhaseAlgorithm, RuntimeContext};
+use super::error::GenerationError;
 use super::optimizer::PerfProfiler;
+use super::param_jitter::{jitter_params, MAX_JITTER_STRENGTH};
+use super::snapshot::Bookmark;
+use super::step_rng::{RngKind, StepRng};
 
 // ═══════════════════════════════════════════════════════════
 // UI 信息快照（只读，供控制面板展示）
@@ -39,6 +41,10 @@ pub struct SubStepInfo {
     pub description: String,
     pub doc_url: Option<String>,
     pub status: StepStatus,
+    /// 扁平子步骤索引（用于切换启用状态）
+    pub flat_index: usize,
+    /// 是否启用；禁用的步骤在执行时被跳过
+    pub enabled: bool,
 }
 
 /// 单个阶段的 UI 快照
@@ -55,6 +61,39 @@ pub struct PhaseInfo {
     pub status: StepStatus,
 }
 
+/// 预检发现的无效子步骤（按当前参数不会产生任何效果）
+#[derive(Debug, Clone)]
+pub struct NoopStep {
+    /// 显示用 ID（如 "1.4"）
+    pub display_id: String,
+    pub name: String,
+    pub reason: String,
+}
+
+/// 「环境开关」清单中的一项：按环境整体启用/禁用其生成步骤
+#[derive(Debug, Clone)]
+pub struct BiomeToggle {
+    /// 环境 key（`StepMeta::biome`）
+    pub key: String,
+    /// 显示名称（取自环境定义，找不到时为 key）
+    pub name: String,
+    /// 是否启用：对应步骤中至少有一个未被禁用
+    pub enabled: bool,
+}
+
+/// 单个已执行子步骤的种子推导记录（调试用）
+#[derive(Debug, Clone)]
+pub struct StepSeedTrace {
+    /// 扁平子步骤索引
+    pub flat_index: usize,
+    /// 步骤名称（"阶段 - 子步骤"）
+    pub name: String,
+    /// `derive_step_seed` 派生出的子种子
+    pub step_seed: u64,
+    /// 本次执行耗时
+    pub elapsed: std::time::Duration,
+}
+
 // ═══════════════════════════════════════════════════════════
 // 流水线
 // ═══════════════════════════════════════════════════════════
@@ -85,6 +124,34 @@ pub struct GenerationPipeline {
     phase_info_dirty: bool,
     /// 性能分析器
     profiler: PerfProfiler,
+    /// 已执行子步骤的种子推导记录（按 flat_index 顺序）
+    seed_trace: Vec<StepSeedTrace>,
+    /// 被禁用的子步骤（flat_index），执行到时直接跳过
+    disabled_steps: HashSet<usize>,
+    /// 锁定的参数（`算法ID.参数键`）：参数拟合等自动调参不扰动这些参数，参数抖动也跳过它们
+    locked_params: BTreeSet<String>,
+    /// 参与抖动的参数（`算法ID.参数键`）：执行时按种子偏移 ±`jitter_strength`，见 [`param_jitter`](super::param_jitter)
+    jittered_params: BTreeSet<String>,
+    /// 参数抖动幅度（0 = 关闭；0.1 = ±10%）
+    jitter_strength: f64,
+    /// 书签（命名的世界坐标），随快照保存
+    bookmarks: Vec<Bookmark>,
+    /// 散布盐：非 0 时与主种子异或后用于散布步骤（`StepMeta::scatter`）的子种子
+    scatter_salt: u64,
+    /// 防重叠检查：步骤前后比较环境地图，出现未声明的环境改写即报错（仅调试构建生效）
+    overlap_check: bool,
+    /// 是否把每步耗时写入运行日志（批量生成时关闭，避免淹没日志控制台）
+    step_logging: bool,
+    /// 子种子是否混入世界尺寸（关闭后同一种子在各尺寸下得到相同的子种子序列）
+    size_mixing: bool,
+    /// 混入子种子的尺寸；`None` 时取世界尺寸（低分辨率预览用它沿用真实世界的子种子）
+    seed_size: Option<(u32, u32)>,
+    /// 每步 RNG 的算法（切换后所有种子的结果都会改变）
+    rng_kind: RngKind,
+    /// 是否记录每格的分配顺序（调试：生成顺序热力图）
+    order_tracking: bool,
+    /// 每格最后一次被改写时的步骤序号（flat_index + 1，0 = 未分配），与环境地图同尺寸
+    assign_order: Option<Vec<u16>>,
 }
 
 impl GenerationPipeline {
@@ -104,6 +171,20 @@ impl GenerationPipeline {
             cached_phase_info_executed: usize::MAX,
             phase_info_dirty: true,
             profiler: PerfProfiler::new(),
+            seed_trace: Vec::new(),
+            disabled_steps: HashSet::new(),
+            locked_params: BTreeSet::new(),
+            jittered_params: BTreeSet::new(),
+            jitter_strength: 0.0,
+            bookmarks: Vec::new(),
+            scatter_salt: 0,
+            overlap_check: false,
+            step_logging: true,
+            size_mixing: true,
+            seed_size: None,
+            rng_kind: RngKind::default(),
+            order_tracking: false,
+            assign_order: None,
         }
     }
 
@@ -123,6 +204,15 @@ impl GenerationPipeline {
         self.total_steps_cache
     }
 
+    /// 各阶段的名称与子步骤数（按执行顺序），用于把 flat 索引归到所属阶段
+    pub fn phase_step_counts(&self) -> Vec<(String, usize)> {
+        self.algorithms
+            .iter()
+            .zip(&self.step_counts)
+            .map(|(algo, &count)| (algo.meta().name, count))
+            .collect()
+    }
+
     /// 已执行子步骤数（O(1) 使用缓存的 step_counts）
     pub fn executed_sub_steps(&self) -> usize {
         let full: usize = self.step_counts[..self.current_phase].iter().sum();
@@ -147,6 +237,17 @@ impl GenerationPipeline {
         }
     }
 
+    /// 最后执行的步骤的显示 ID (如 "1.3")
+    pub fn last_executed_display_id(&self) -> Option<String> {
+        let executed = self.executed_sub_steps();
+        if executed == 0 {
+            return None;
+        }
+        let (p, s) = self.flat_to_position(executed - 1);
+        let display_idx = self.algorithms[p].meta().steps[s].display_index;
+        Some(format!("{}.{}", p + 1, display_idx))
+    }
+
     /// 最后执行的步骤名称
     pub fn last_executed_name(&self) -> Option<String> {
         let executed = self.executed_sub_steps();
@@ -162,8 +263,75 @@ impl GenerationPipeline {
         self.seed
     }
 
+    /// 设置主种子（同时清除散布盐）
     pub fn set_seed(&mut self, seed: u64) {
         self.seed = seed;
+        self.scatter_salt = 0;
+    }
+
+    pub fn set_scatter_salt(&mut self, salt: u64) {
+        self.scatter_salt = salt;
+    }
+
+    /// 开关防重叠检查（发布构建中无效）
+    pub fn set_overlap_check(&mut self, enabled: bool) {
+        self.overlap_check = enabled;
+    }
+
+    pub fn order_tracking(&self) -> bool {
+        self.order_tracking
+    }
+
+    /// 开关分配顺序记录；关闭时丢弃已记录的数据。只对之后执行的步骤生效，
+    /// 需要完整数据时由调用方回放到当前位置
+    pub fn set_order_tracking(&mut self, enabled: bool) {
+        self.order_tracking = enabled;
+        if !enabled {
+            self.assign_order = None;
+        }
+    }
+
+    /// 每格的分配顺序（步骤序号 flat_index + 1，0 = 未分配）；未开启记录时为 `None`
+    pub fn assign_order(&self) -> Option<&[u16]> {
+        self.assign_order.as_deref()
+    }
+
+    /// 开关逐步耗时日志（错误仍会记录）
+    pub fn set_step_logging(&mut self, enabled: bool) {
+        self.step_logging = enabled;
+    }
+
+    pub fn size_mixing(&self) -> bool {
+        self.size_mixing
+    }
+
+    /// 开关子种子的世界尺寸混入；改变后已执行的步骤需要重放才与新种子一致
+    pub fn set_size_mixing(&mut self, enabled: bool) {
+        self.size_mixing = enabled;
+    }
+
+    /// 指定混入子种子的尺寸，与实际生成尺寸分开；`None` 恢复为世界尺寸。
+    ///
+    /// 缩小尺寸生成预览时传入真实世界尺寸，预览与正式生成用同一套子种子。
+    pub fn set_seed_size(&mut self, size: Option<(u32, u32)>) {
+        self.seed_size = size;
+    }
+
+    pub fn rng_kind(&self) -> RngKind {
+        self.rng_kind
+    }
+
+    /// 切换每步 RNG 的算法；与 [`set_size_mixing`](Self::set_size_mixing) 一样，已执行的步骤需要重放
+    pub fn set_rng_kind(&mut self, kind: RngKind) {
+        self.rng_kind = kind;
+    }
+
+    /// 载入外部环境地图（如从 `.wld` 导入），替换当前环境地图。
+    ///
+    /// 调用方应先 `reset_all`；之后执行第一步会重新初始化环境地图。
+    pub fn load_biome_map(&mut self, biome_map: BiomeMap) {
+        self.biome_map = Some(biome_map);
+        self.assign_order = None;
     }
 
     /// 获取 biome_map 引用（供 UI 渲染 overlay）
@@ -171,6 +339,11 @@ impl GenerationPipeline {
         self.biome_map.as_ref()
     }
 
+    /// 获取 biome_map 可变引用（供手动编辑，如沙箱「应用到世界」）
+    pub fn biome_map_mut(&mut self) -> Option<&mut BiomeMap> {
+        self.biome_map.as_mut()
+    }
+
     /// 获取指定子步骤的形状记录（flat_index）
     pub fn shape_log(&self, flat_index: usize) -> Option<&[ShapeRecord]> {
         self.shape_logs.get(&flat_index).map(|v| v.as_slice())
@@ -191,11 +364,31 @@ impl GenerationPipeline {
         self.current_sub
     }
 
+    /// 读取算法写入共享状态的数据（键不存在或类型不符时返回 `None`）
+    pub fn shared<T: 'static>(&self, key: &str) -> Option<&T> {
+        self.shared_state.get(key).and_then(|v| v.downcast_ref::<T>())
+    }
+
     /// 获取指定阶段的算法模块的可变引用
     pub fn algorithm_mut(&mut self, phase_index: usize) -> Option<&mut Box<dyn PhaseAlgorithm>> {
         self.algorithms.get_mut(phase_index)
     }
 
+    /// 按注册顺序遍历全部算法模块（只读）
+    pub fn algorithms(&self) -> impl Iterator<Item = &dyn PhaseAlgorithm> {
+        self.algorithms.iter().map(|algo| algo.as_ref())
+    }
+
+    /// 按 `PhaseMeta::id` 查找算法模块（只读）
+    pub fn algorithm_by_id(&self, id: &str) -> Option<&dyn PhaseAlgorithm> {
+        self.algorithms.iter().find(|algo| algo.meta().id == id).map(|algo| algo.as_ref())
+    }
+
+    /// 按 `PhaseMeta::id` 查找算法模块
+    pub fn algorithm_by_id_mut(&mut self, id: &str) -> Option<&mut Box<dyn PhaseAlgorithm>> {
+        self.algorithms.iter_mut().find(|algo| algo.meta().id == id)
+    }
+
     /// 获取"当前"算法模块（可用于打开配置面板）
     /// 如果已执行了一些步骤，返回最后执行的那个算法模块
     pub fn current_algorithm_mut(&mut self) -> Option<(usize, &mut Box<dyn PhaseAlgorithm>)> {
@@ -218,24 +411,46 @@ impl GenerationPipeline {
         world: &mut World,
         profile: &WorldProfile,
         blocks: &[BlockDefinition],
-    ) -> Result<bool, String> {
+    ) -> Result<bool, GenerationError> {
         if self.is_complete() {
             return Ok(false);
         }
 
         let flat_index = self.executed_sub_steps();
-        let step_seed = derive_step_seed(self.seed, flat_index, profile.size.width, profile.size.height);
-        let mut rng = StdRng::seed_from_u64(step_seed);
-        let mut step_shapes: Vec<ShapeRecord> = Vec::new();
-
-        let step_count = self.step_counts[self.current_phase];
+        if !self.is_step_enabled(flat_index) {
+            let meta = self.algorithms[self.current_phase].meta();
+            log::info(format!("[{flat_index}] {} - {} — 已跳过", meta.name, meta.steps[self.current_sub].name));
+            self.shape_logs.insert(flat_index, Vec::new());
+            self.advance_position();
+            return Ok(true);
+        }
 
-        // 获取步骤名称用于性能记录
-        let step_name = {
+        // 获取步骤名称用于性能记录；散布步骤的主种子混入散布盐
+        let (step_name, scatter) = {
             let meta = self.algorithms[self.current_phase].meta();
-            format!("{} - {}", meta.name, meta.steps[self.current_sub].name)
+            let step = &meta.steps[self.current_sub];
+            (format!("{} - {}", meta.name, step.name), step.scatter)
+        };
+        let master = if scatter { self.seed ^ self.scatter_salt } else { self.seed };
+        let size = self.size_mixing
+            .then(|| self.seed_size.unwrap_or((profile.size.width, profile.size.height)));
+        let step_seed = derive_step_seed(master, flat_index, size);
+        let mut rng = StepRng::new(self.rng_kind, step_seed);
+        let mut step_shapes: Vec<ShapeRecord> = Vec::new();
+        if cfg!(debug_assertions) {
+            self.check_biome_map_size(world).map_err(|e| {
+                log::error(format!("[{flat_index}] {step_name} 执行前检查失败: {e}"));
+                e.context(&step_name)
+            })?;
+        }
+        let check_overlap = cfg!(debug_assertions) && self.overlap_check;
+        let before = if check_overlap || self.order_tracking {
+            self.biome_map.clone()
+        } else {
+            None
         };
 
+        let base_params = self.apply_param_jitter();
         let mut ctx = RuntimeContext {
             world,
             profile,
@@ -247,27 +462,47 @@ impl GenerationPipeline {
             shape_log: &mut step_shapes,
         };
 
-        // 带计时的步骤执行
+        // 带计时的步骤执行；先清空本线程残留的填充统计（如上一步失败时遗留）
+        geometry::take_fill_stats();
         let t0 = Instant::now();
-        self.algorithms[self.current_phase]
-            .execute(self.current_sub, &mut ctx)
-            .map_err(|e| {
-                let meta = self.algorithms[self.current_phase].meta();
-                format!("{}: {e}", meta.name)
-            })?;
+        let result = self.algorithms[self.current_phase].execute(self.current_sub, &mut ctx);
+        if let Some(base) = base_params {
+            self.algorithms[self.current_phase].set_params(&base);
+        }
+        result.map_err(|e| {
+            log::error(format!("[{flat_index}] {step_name} 失败: {e}"));
+            e.context(&step_name)
+        })?;
         let elapsed = t0.elapsed();
-        self.profiler.record_step(flat_index, &step_name, elapsed);
+        if self.step_logging {
+            log::info(format!(
+                "[{flat_index}] {step_name} — {:.2}ms",
+                elapsed.as_secs_f64() * 1000.0
+            ));
+        }
+        self.profiler.record_step(flat_index, &step_name, elapsed, geometry::take_fill_stats());
+        if self.order_tracking {
+            self.record_assign_order(before.as_ref(), flat_index);
+        }
+        if let (true, Some(before), Some(after)) = (check_overlap, &before, &self.biome_map) {
+            let policy = self.algorithms[self.current_phase].overwrite_policy(self.current_sub);
+            self.check_overwrites(before, after, &policy).map_err(|e| {
+                log::error(format!("[{flat_index}] {step_name} 防重叠检查失败: {e}"));
+                e.context(&step_name)
+            })?;
+        }
+        self.seed_trace.retain(|t| t.flat_index < flat_index);
+        self.seed_trace.push(StepSeedTrace {
+            flat_index,
+            name: step_name,
+            step_seed,
+            elapsed,
+        });
 
         // 保存此步骤的形状记录
         self.shape_logs.insert(flat_index, step_shapes);
 
-        // 推进位置
-        self.current_sub += 1;
-        if self.current_sub >= step_count {
-            self.current_phase += 1;
-            self.current_sub = 0;
-        }
-        self.phase_info_dirty = true;
+        self.advance_position();
 
         Ok(true)
     }
@@ -278,7 +513,7 @@ impl GenerationPipeline {
         world: &mut World,
         profile: &WorldProfile,
         blocks: &[BlockDefinition],
-    ) -> Result<bool, String> {
+    ) -> Result<bool, GenerationError> {
         if self.is_complete() {
             return Ok(false);
         }
@@ -300,7 +535,7 @@ impl GenerationPipeline {
         world: &mut World,
         profile: &WorldProfile,
         blocks: &[BlockDefinition],
-    ) -> Result<bool, String> {
+    ) -> Result<bool, GenerationError> {
         let executed = self.executed_sub_steps();
         if executed == 0 {
             return Ok(false);
@@ -317,7 +552,7 @@ impl GenerationPipeline {
         world: &mut World,
         profile: &WorldProfile,
         blocks: &[BlockDefinition],
-    ) -> Result<bool, String> {
+    ) -> Result<bool, GenerationError> {
         let executed = self.executed_sub_steps();
         if executed == 0 {
             return Ok(false);
@@ -340,12 +575,40 @@ impl GenerationPipeline {
         Ok(true)
     }
 
+    /// 跳转到指定子步骤：执行到该步骤为止（它是最后一个已执行的步骤）。
+    ///
+    /// 目标在当前位置之后时继续向前执行，否则从头回放。返回是否改变了位置。
+    pub fn goto_step(
+        &mut self,
+        flat_index: usize,
+        world: &mut World,
+        profile: &WorldProfile,
+        blocks: &[BlockDefinition],
+    ) -> Result<bool, GenerationError> {
+        let target = (flat_index + 1).min(self.total_sub_steps());
+        let executed = self.executed_sub_steps();
+        if target == executed {
+            return Ok(false);
+        }
+        if target < executed {
+            self.replay_to_flat(target, world, profile, blocks)?;
+            return Ok(true);
+        }
+        while self.executed_sub_steps() < target {
+            if !self.step_forward_sub(world, profile, blocks)? {
+                break;
+            }
+        }
+        Ok(true)
+    }
+
     /// 重置到第 0 步
     pub fn reset_all(&mut self, world: &mut World) {
         *world = World::new_air(world.width, world.height);
         self.current_phase = 0;
         self.current_sub = 0;
         self.biome_map = None;
+        self.assign_order = None;
         self.shared_state.clear();
         self.shape_logs.clear();
         for algo in &mut self.algorithms {
@@ -353,6 +616,242 @@ impl GenerationPipeline {
         }
         self.phase_info_dirty = true;
         self.profiler.reset();
+        self.seed_trace.clear();
+    }
+
+    // ── 步骤启用 ────────────────────────────────────────────
+
+    /// 子步骤是否启用
+    pub fn is_step_enabled(&self, flat_index: usize) -> bool {
+        !self.disabled_steps.contains(&flat_index)
+    }
+
+    /// 启用 / 禁用子步骤。返回 `true` 表示该步骤已执行过，需要调用
+    /// [`Self::replay_to_current`] 让世界反映新的启用状态。
+    pub fn set_step_enabled(&mut self, flat_index: usize, enabled: bool) -> bool {
+        let changed = if enabled {
+            self.disabled_steps.remove(&flat_index)
+        } else {
+            self.disabled_steps.insert(flat_index)
+        };
+        self.phase_info_dirty |= changed;
+        changed && flat_index < self.executed_sub_steps()
+    }
+
+    /// 标记了 `StepMeta::biome` 的子步骤：(flat_index, 环境 key)
+    fn biome_steps(&self) -> Vec<(usize, String)> {
+        self.algorithms
+            .iter()
+            .flat_map(|algo| algo.meta().steps)
+            .enumerate()
+            .filter_map(|(flat, step)| step.biome.map(|key| (flat, key)))
+            .collect()
+    }
+
+    /// 可按环境开关的环境（按首次出现的步骤顺序）
+    pub fn biome_toggles(&self) -> Vec<BiomeToggle> {
+        let mut toggles: Vec<BiomeToggle> = Vec::new();
+        for (flat, key) in self.biome_steps() {
+            let enabled = self.is_step_enabled(flat);
+            if let Some(toggle) = toggles.iter_mut().find(|t| t.key == key) {
+                toggle.enabled |= enabled;
+                continue;
+            }
+            let name = self.biome_definitions.iter()
+                .find(|b| b.key == key)
+                .map_or_else(|| key.clone(), |b| b.name.clone());
+            toggles.push(BiomeToggle { key, name, enabled });
+        }
+        toggles
+    }
+
+    /// 启用 / 禁用某个环境的全部生成步骤。返回值同 [`Self::set_step_enabled`]
+    pub fn set_biome_enabled(&mut self, key: &str, enabled: bool) -> bool {
+        let mut needs_replay = false;
+        for (flat, _) in self.biome_steps().into_iter().filter(|(_, k)| k == key) {
+            needs_replay |= self.set_step_enabled(flat, enabled);
+        }
+        needs_replay
+    }
+
+    /// 锁定 / 解锁单个参数
+    pub fn set_param_locked(&mut self, algorithm_id: &str, key: &str, locked: bool) {
+        let entry = format!("{algorithm_id}.{key}");
+        if locked {
+            self.locked_params.insert(entry);
+        } else {
+            self.locked_params.remove(&entry);
+        }
+    }
+
+    /// 指定算法模块中被锁定的参数键
+    pub fn locked_param_keys(&self, algorithm_id: &str) -> Vec<String> {
+        let prefix = format!("{algorithm_id}.");
+        self.locked_params.iter()
+            .filter_map(|entry| entry.strip_prefix(&prefix))
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// 让单个参数参与 / 退出抖动；改变后已执行的步骤需要重放
+    pub fn set_param_jittered(&mut self, algorithm_id: &str, key: &str, jittered: bool) {
+        let entry = format!("{algorithm_id}.{key}");
+        if jittered {
+            self.jittered_params.insert(entry);
+        } else {
+            self.jittered_params.remove(&entry);
+        }
+    }
+
+    /// 指定算法模块中参与抖动的参数键
+    pub fn jittered_param_keys(&self, algorithm_id: &str) -> Vec<String> {
+        let prefix = format!("{algorithm_id}.");
+        self.jittered_params.iter()
+            .filter_map(|entry| entry.strip_prefix(&prefix))
+            .map(str::to_string)
+            .collect()
+    }
+
+    pub fn jitter_strength(&self) -> f64 {
+        self.jitter_strength
+    }
+
+    /// 设置参数抖动幅度（限制在 `0 ~ MAX_JITTER_STRENGTH`）；改变后已执行的步骤需要重放
+    pub fn set_jitter_strength(&mut self, strength: f64) {
+        self.jitter_strength = strength.clamp(0.0, MAX_JITTER_STRENGTH);
+    }
+
+    /// 按主种子为当前算法模块施加参数抖动，返回需要在执行后恢复的原始参数；没有参数参与时为 `None`
+    fn apply_param_jitter(&mut self) -> Option<serde_json::Value> {
+        if self.jitter_strength <= 0.0 {
+            return None;
+        }
+        let algorithm = &mut self.algorithms[self.current_phase];
+        let meta = algorithm.meta();
+        let prefix = format!("{}.", meta.id);
+        // 锁定的参数保持原值，即使同时勾选了抖动
+        let keys: Vec<&str> = self.jittered_params.iter()
+            .filter(|entry| !self.locked_params.contains(*entry))
+            .filter_map(|entry| entry.strip_prefix(&prefix))
+            .collect();
+        if keys.is_empty() {
+            return None;
+        }
+        let base = algorithm.get_params();
+        algorithm.set_params(&jitter_params(&base, &meta, &keys, self.jitter_strength, self.seed));
+        Some(base)
+    }
+
+    /// 书签列表（按添加顺序）
+    pub fn bookmarks(&self) -> &[Bookmark] {
+        &self.bookmarks
+    }
+
+    /// 可编辑的书签列表（添加、改名、删除）
+    pub fn bookmarks_mut(&mut self) -> &mut Vec<Bookmark> {
+        &mut self.bookmarks
+    }
+
+    /// 已关闭的环境 key（按步骤顺序）
+    pub fn disabled_biomes(&self) -> Vec<String> {
+        self.biome_toggles().into_iter()
+            .filter(|t| !t.enabled)
+            .map(|t| t.key)
+            .collect()
+    }
+
+    /// 各子步骤的稳定标识 `算法ID.子步骤编号`（按 flat_index 顺序），用于存档
+    fn step_keys(&self) -> Vec<String> {
+        self.algorithms
+            .iter()
+            .flat_map(|algo| {
+                let meta = algo.meta();
+                meta.steps.iter()
+                    .map(|step| format!("{}.{}", meta.id, step.display_index))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    /// 单独禁用的子步骤标识（不含已由环境开关整体关闭的环境步骤），随快照保存
+    fn individually_disabled_steps(&self) -> Vec<String> {
+        let disabled_biomes = self.disabled_biomes();
+        let biome_steps = self.biome_steps();
+        self.step_keys()
+            .into_iter()
+            .enumerate()
+            .filter(|(flat, _)| !self.is_step_enabled(*flat))
+            .filter(|(flat, _)| {
+                !biome_steps.iter().any(|(f, key)| f == flat && disabled_biomes.contains(key))
+            })
+            .map(|(_, key)| key)
+            .collect()
+    }
+
+    /// 被禁用的子步骤（flat_index 升序）
+    pub fn disabled_steps(&self) -> Vec<usize> {
+        let mut steps: Vec<usize> = self.disabled_steps.iter().copied().collect();
+        steps.sort_unstable();
+        steps
+    }
+
+    /// 预检：不执行、不修改世界，列出按当前参数与启用状态不会产生效果的子步骤
+    pub fn dry_run(&self) -> Vec<NoopStep> {
+        let mut flat = 0;
+        let mut noops = Vec::new();
+        for (pi, algo) in self.algorithms.iter().enumerate() {
+            for (si, step) in algo.meta().steps.iter().enumerate() {
+                let reason = if self.is_step_enabled(flat) {
+                    algo.would_be_noop(si)
+                } else {
+                    Some("已禁用".to_string())
+                };
+                if let Some(reason) = reason {
+                    noops.push(NoopStep {
+                        display_id: format!("{}.{}", pi + 1, si),
+                        name: step.name.clone(),
+                        reason,
+                    });
+                }
+                flat += 1;
+            }
+        }
+        noops
+    }
+
+    /// 从头回放到当前位置（启用状态变化后调用）
+    pub fn replay_to_current(
+        &mut self,
+        world: &mut World,
+        profile: &WorldProfile,
+        blocks: &[BlockDefinition],
+    ) -> Result<(), GenerationError> {
+        let target = self.executed_sub_steps();
+        self.replay_to_flat(target, world, profile, blocks)
+    }
+
+    /// 重掷散布特征：换用新的散布盐，回放到当前位置。
+    ///
+    /// 只有散布步骤的子种子改变；第一个散布步骤之前的步骤不受影响，回放结果与原先一致，
+    /// 因此世界的确定性骨架（海洋、森林等）保持不变。当前位置尚未到达任何散布步骤时
+    /// 只记录新盐、不回放。返回是否执行了回放。
+    pub fn reseed_scatter(
+        &mut self,
+        salt: u64,
+        world: &mut World,
+        profile: &WorldProfile,
+        blocks: &[BlockDefinition],
+    ) -> Result<bool, GenerationError> {
+        let first = self.algorithms.iter()
+            .flat_map(|algo| algo.meta().steps)
+            .position(|step| step.scatter)
+            .ok_or(GenerationError::NoScatterSteps)?;
+        self.scatter_salt = salt;
+        if first >= self.executed_sub_steps() {
+            return Ok(false);
+        }
+        self.replay_to_current(world, profile, blocks)?;
+        Ok(true)
     }
 
     /// 从当前位置执行到底
@@ -361,7 +860,7 @@ impl GenerationPipeline {
         world: &mut World,
         profile: &WorldProfile,
         blocks: &[BlockDefinition],
-    ) -> Result<(), String> {
+    ) -> Result<(), GenerationError> {
         self.profiler.start_generation();
         while !self.is_complete() {
             self.step_forward_sub(world, profile, blocks)?;
@@ -376,6 +875,11 @@ impl GenerationPipeline {
         &self.profiler
     }
 
+    /// 已执行子步骤的种子推导记录（回退后只保留仍有效的步骤）
+    pub fn seed_trace(&self) -> &[StepSeedTrace] {
+        &self.seed_trace
+    }
+
     /// 获取性能分析报告
     pub fn performance_report(&self) -> String {
         self.profiler.report()
@@ -394,20 +898,73 @@ impl GenerationPipeline {
             world_size,
             layers,
             &self.algorithms,
+            super::snapshot::SnapshotExtras {
+                scatter_salt: self.scatter_salt,
+                disabled_biomes: self.disabled_biomes(),
+                disabled_steps: self.individually_disabled_steps(),
+                locked_params: self.locked_params.iter().cloned().collect(),
+                jitter_strength: self.jitter_strength,
+                jittered_params: self.jittered_params.iter().cloned().collect(),
+                size_mixing: self.size_mixing,
+                rng_kind: self.rng_kind,
+                bookmarks: self.bookmarks.clone(),
+            },
         )
     }
 
-    /// 从快照恢复算法参数（seed 和 world_size 由调用方处理）
-    pub fn restore_from_snapshot(&mut self, snapshot: &super::snapshot::WorldSnapshot) {
-        for algo_state in &snapshot.algorithms {
-            // 按 algorithm_id 匹配并恢复参数
-            for algo in &mut self.algorithms {
-                if algo.meta().id == algo_state.algorithm_id {
-                    algo.set_params(&algo_state.params);
+    /// 从快照恢复算法参数、环境开关与子步骤启用状态、子种子模式与 RNG 算法（seed、散布盐和 world_size 由调用方处理）
+    ///
+    /// 返回当前参数结构中存在、但快照里缺失的参数（`算法ID.参数键`）。
+    /// 这些参数会使用当前默认值，可能导致重放结果与存档时不同（通常是存档之后新增的参数）。
+    pub fn restore_from_snapshot(&mut self, snapshot: &super::snapshot::WorldSnapshot) -> Vec<String> {
+        let mut missing = Vec::new();
+        for algo in &mut self.algorithms {
+            let id = algo.meta().id;
+            // 按 algorithm_id 匹配
+            let Some(algo_state) = snapshot.algorithms.iter().find(|s| s.algorithm_id == id) else {
+                missing.push(format!("{id}.*"));
+                continue;
+            };
+
+            let saved = algo_state.params.as_object();
+            let has_key = |key: &str| {
+                saved.is_some_and(|obj| {
+                    obj.contains_key(key)
+                        || algo.param_aliases().iter().any(|(old, new)| *new == key && obj.contains_key(*old))
+                })
+            };
+            if let Some(schema) = algo.get_params().as_object() {
+                missing.extend(
+                    schema.keys()
+                        .filter(|key| !has_key(key))
+                        .map(|key| format!("{id}.{key}")),
+                );
+            }
+
+            algo.set_params(&algo_state.params);
+        }
+        // 启用状态随存档恢复：先全部启用，再关闭存档中的环境与单独禁用的子步骤
+        self.disabled_steps.clear();
+        for key in &snapshot.disabled_biomes {
+            self.set_biome_enabled(key, false);
+        }
+        let step_keys = self.step_keys();
+        for key in &snapshot.disabled_steps {
+            match step_keys.iter().position(|k| k == key) {
+                Some(flat) => {
+                    self.set_step_enabled(flat, false);
                 }
+                None => log::warn(format!("存档中禁用的子步骤 {key} 在当前流水线中不存在，已忽略")),
             }
         }
+        self.locked_params = snapshot.locked_params.iter().cloned().collect();
+        self.jitter_strength = snapshot.jitter_strength.clamp(0.0, MAX_JITTER_STRENGTH);
+        self.jittered_params = snapshot.jittered_params.iter().cloned().collect();
+        self.size_mixing = snapshot.size_mixing;
+        self.rng_kind = snapshot.rng_kind;
+        self.bookmarks = snapshot.bookmarks.clone();
         self.phase_info_dirty = true;
+        missing
     }
 
     // ── UI 信息 ─────────────────────────────────────────────
@@ -440,14 +997,17 @@ impl GenerationPipeline {
                         } else {
                             StepStatus::Pending
                         };
-                        flat += 1;
-                        SubStepInfo {
+                        let info = SubStepInfo {
                             display_id: format!("{}.{}", pi + 1, si),
                             name: step_meta.name.clone(),
                             description: step_meta.description.clone(),
                             doc_url: step_meta.doc_url.clone(),
                             status,
-                        }
+                            flat_index: flat,
+                            enabled: self.is_step_enabled(flat),
+                        };
+                        flat += 1;
+                        info
                     })
                     .collect();
 
@@ -479,6 +1039,94 @@ impl GenerationPipeline {
 
     // ── 内部方法 ────────────────────────────────────────────
 
+    /// 环境地图（若已创建）的尺寸必须与世界一致，否则按世界坐标读写会错位
+    fn check_biome_map_size(&self, world: &World) -> Result<(), GenerationError> {
+        match &self.biome_map {
+            Some(bm) if (bm.width, bm.height) != (world.width, world.height) => {
+                Err(GenerationError::MapSizeMismatch {
+                    map: (bm.width, bm.height),
+                    world: (world.width, world.height),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// 步骤执行后，把环境改变的格标记为本步的序号（本步创建的地图按空白地图比较）
+    fn record_assign_order(&mut self, before: Option<&BiomeMap>, flat_index: usize) {
+        let Some(after) = &self.biome_map else { return };
+        let data = after.data();
+        let order = self.assign_order.get_or_insert_with(Vec::new);
+        if order.len() != data.len() {
+            order.clear();
+            order.resize(data.len(), 0);
+        }
+        let stamp = (flat_index + 1).min(u16::MAX as usize) as u16;
+        let mark = |slot: &mut u16, id: BiomeId| {
+            *slot = if id == BIOME_UNASSIGNED { 0 } else { stamp };
+        };
+        match before.map(|b| b.data()).filter(|b| b.len() == data.len()) {
+            Some(prev) => {
+                for ((slot, &id), &old) in order.iter_mut().zip(data).zip(prev) {
+                    if id != old {
+                        mark(slot, id);
+                    }
+                }
+            }
+            None => {
+                for (slot, &id) in order.iter_mut().zip(data) {
+                    if id != BIOME_UNASSIGNED {
+                        mark(slot, id);
+                    }
+                }
+            }
+        }
+    }
+
+    /// 比较步骤前后的环境地图，返回策略之外的改写（格数与第一处位置）
+    fn check_overwrites(
+        &self,
+        before: &BiomeMap,
+        after: &BiomeMap,
+        policy: &OverwritePolicy,
+    ) -> Result<(), GenerationError> {
+        let OverwritePolicy::Allow(allowed) = policy else {
+            return Ok(());
+        };
+        let w = before.width as usize;
+        let mut violations = before.data().iter()
+            .zip(after.data())
+            .enumerate()
+            .filter(|&(_, (&old, &new))| {
+                old != new && old != BIOME_UNASSIGNED && !allowed.contains(&(old, new))
+            });
+        let Some((first, (&old, &new))) = violations.next() else {
+            return Ok(());
+        };
+        let count = 1 + violations.count();
+        let name = |id: BiomeId| {
+            self.biome_definitions.iter()
+                .find(|b| b.id == id)
+                .map_or_else(|| format!("#{id}"), |b| b.name.clone())
+        };
+        Err(GenerationError::UnexpectedOverwrite {
+            count,
+            first: ((first % w) as u32, (first / w) as u32),
+            from: name(old),
+            to: name(new),
+        })
+    }
+
+    /// 执行位置后移一个子步骤
+    fn advance_position(&mut self) {
+        self.current_sub += 1;
+        if self.current_sub >= self.step_counts[self.current_phase] {
+            self.current_phase += 1;
+            self.current_sub = 0;
+        }
+        self.phase_info_dirty = true;
+    }
+
     fn flat_to_position(&self, flat: usize) -> (usize, usize) {
         let mut remaining = flat;
         for (pi, &count) in self.step_counts.iter().enumerate() {
@@ -496,12 +1144,14 @@ impl GenerationPipeline {
         world: &mut World,
         profile: &WorldProfile,
         blocks: &[BlockDefinition],
-    ) -> Result<(), String> {
+    ) -> Result<(), GenerationError> {
         *world = World::new_air(world.width, world.height);
         self.biome_map = None;
+        self.assign_order = None;
         self.shared_state.clear();
         self.current_phase = 0;
         self.current_sub = 0;
+        self.seed_trace.clear();
         for algo in &mut self.algorithms {
             algo.on_reset();
         }
@@ -516,9 +1166,10 @@ impl GenerationPipeline {
 
 /// 从主种子派生每步的确定性子种子
 ///
-/// 混入世界尺寸，使同一种子+不同世界尺寸得到不同的生成结果（与泰拉瑞亚行为一致）。
-fn derive_step_seed(master: u64, step_index: usize, world_width: u32, world_height: u32) -> u64 {
-    let size_mix = (world_width as u64) << 32 | (world_height as u64);
+/// `size` 为 `Some((宽, 高))` 时混入世界尺寸，使同一种子+不同世界尺寸得到不同的生成结果
+/// （与泰拉瑞亚行为一致）；为 `None` 时子种子与尺寸无关，各尺寸得到按比例缩放的相似布局。
+fn derive_step_seed(master: u64, step_index: usize, size: Option<(u32, u32)>) -> u64 {
+    let size_mix = size.map_or(0, |(w, h)| (w as u64) << 32 | (h as u64));
     master
         .wrapping_add(step_index as u64)
         .wrapping_mul(6_364_136_223_846_793_005)
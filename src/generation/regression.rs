@@ -0,0 +1,131 @@
+//! # 缩略图回归测试
+//!
+//! 以固定的 (seed, 尺寸) 组合无界面生成世界，渲染环境缩略图，
+//! 与 `tests/golden/` 下提交的金样图逐像素比较。
+//!
+//! - 检查：`Lwd regression`（`cargo test` 中的 `tests/thumbnail_regression.rs` 会调用它）
+//! - 有意修改算法后重新生成金样图：`Lwd regression --update`
+
+use std::path::{Path, PathBuf};
+
+use crate::rendering::thumbnail::biome_thumbnail;
+
+use super::headless::generate_headless;
+
+/// 缩略图最大宽度
+const THUMBNAIL_WIDTH: u32 = 420;
+
+/// 允许的差异像素比例（超过即视为回归）
+const DIFF_TOLERANCE: f64 = 0.001;
+
+/// 单个回归用例
+struct RegressionCase {
+    /// 金样图文件名（不含扩展名）
+    name: &'static str,
+    seed: u64,
+    size_key: &'static str,
+    custom_size: Option<(u32, u32)>,
+}
+
+const CASES: &[RegressionCase] = &[
+    RegressionCase { name: "small_a", seed: 0x0000_0000_0000_002A, size_key: "small", custom_size: None },
+    RegressionCase { name: "small_b", seed: 0xC0FF_EE00_DEAD_BEEF, size_key: "small", custom_size: None },
+    RegressionCase { name: "custom_wide", seed: 0x1234_5678_9ABC_DEF0, size_key: "custom", custom_size: Some((3000, 700)) },
+];
+
+/// 单个用例的比较结果
+pub struct CaseResult {
+    pub name: String,
+    /// 差异像素比例；`None` 表示金样图缺失或尺寸不一致
+    pub diff_ratio: Option<f64>,
+    pub passed: bool,
+}
+
+/// 默认金样图目录（相对仓库根目录）
+pub fn default_golden_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden")
+}
+
+fn golden_path(dir: &Path, name: &str) -> PathBuf {
+    dir.join(format!("{name}.png"))
+}
+
+/// 执行全部用例。`update = true` 时把当前结果写为新的金样图。
+pub fn run_regression(golden_dir: &Path, update: bool) -> Result<Vec<CaseResult>, String> {
+    if update {
+        std::fs::create_dir_all(golden_dir)
+            .map_err(|e| format!("创建金样图目录失败: {e}"))?;
+    }
+
+    let mut results = Vec::with_capacity(CASES.len());
+    for case in CASES {
+        let name = case.name;
+        let generated = generate_headless(case.seed, case.size_key, case.custom_size)?;
+        let bm = generated
+            .pipeline
+            .biome_map()
+            .ok_or_else(|| format!("{name}: 生成后没有环境地图"))?;
+        let thumb = biome_thumbnail(bm, &generated.biomes, THUMBNAIL_WIDTH);
+        let path = golden_path(golden_dir, name);
+
+        if update {
+            thumb
+                .save(&path)
+                .map_err(|e| format!("{name}: 保存金样图失败: {e}"))?;
+            results.push(CaseResult {
+                name: name.to_string(),
+                diff_ratio: Some(0.0),
+                passed: true,
+            });
+            continue;
+        }
+
+        let diff_ratio = image::open(&path).ok().map(|img| img.to_rgba8()).and_then(|golden| {
+            if golden.dimensions() != thumb.dimensions() {
+                return None;
+            }
+            let differing = golden
+                .pixels()
+                .zip(thumb.pixels())
+                .filter(|(a, b)| a != b)
+                .count();
+            Some(differing as f64 / (thumb.width() * thumb.height()).max(1) as f64)
+        });
+        results.push(CaseResult {
+            name: name.to_string(),
+            diff_ratio,
+            passed: diff_ratio.is_some_and(|r| r <= DIFF_TOLERANCE),
+        });
+    }
+
+    Ok(results)
+}
+
+/// 命令行入口：打印每个用例的结果，全部通过返回 `true`。
+pub fn run_cli(args: &[String]) -> bool {
+    let update = args.iter().any(|a| a == "--update");
+    let golden_dir = args
+        .iter()
+        .position(|a| a == "--golden-dir")
+        .and_then(|i| args.get(i + 1))
+        .map(PathBuf::from)
+        .unwrap_or_else(default_golden_dir);
+
+    match run_regression(&golden_dir, update) {
+        Ok(results) => {
+            for r in &results {
+                let detail = match r.diff_ratio {
+                    Some(ratio) => format!("差异 {:.4}%", ratio * 100.0),
+                    None => "金样图缺失或尺寸不一致".to_string(),
+                };
+                let mark = if update { "更新" } else if r.passed { "通过" } else { "失败" };
+                println!("[{mark}] {} — {detail}", r.name);
+            }
+            results.iter().all(|r| r.passed)
+        }
+        Err(e) => {
+            eprintln!("回归测试执行失败: {e}");
+            false
+        }
+    }
+}
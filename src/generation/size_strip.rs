@@ -0,0 +1,130 @@
+//! # 多尺寸对比图
+//!
+//! 以同一种子无界面生成小 / 中 / 大三种尺寸的世界，各自渲染环境缩略图并横向拼接成一张带标注的图。
+//! 世界尺寸参与子种子推导（`derive_step_seed`），同一种子在不同尺寸下的布局并不相同，
+//! 分享种子时用这张图说明差异。三张缩略图共用同一降采样倍率，保留尺寸之间的相对大小。
+//!
+//! `Lwd strip [--seed N] [--out PATH] [--no-size-mix]`：以默认参数生成并保存对比图；
+//! `--no-size-mix` 使子种子与尺寸无关。
+
+use std::path::PathBuf;
+
+use crate::core::layer::LayerDefinition;
+use crate::rendering::thumbnail::{biome_thumbnail_scaled, thumbnail_strip};
+
+use super::headless::HeadlessContext;
+use super::snapshot::WorldSnapshot;
+use super::step_rng::RngKind;
+
+/// 对比图包含的世界尺寸（从小到大）
+pub const STRIP_SIZES: [&str; 3] = ["small", "medium", "large"];
+
+/// 最宽一张缩略图的目标宽度（像素）
+const STRIP_MAX_WIDTH: u32 = 640;
+
+/// 对比图的生成设置
+pub struct StripSettings {
+    pub seed: u64,
+    /// 算法参数与散布盐来源（通常为当前流水线的快照）；`None` 使用默认参数
+    pub snapshot: Option<WorldSnapshot>,
+    /// 层级定义（百分比，与尺寸无关）；`None` 使用 world.json 默认层级
+    pub layers: Option<Vec<LayerDefinition>>,
+    /// 跳过的子步骤（flat 索引）
+    pub disabled_steps: Vec<usize>,
+    /// 子种子是否混入世界尺寸；关闭时三种尺寸得到按比例缩放的相似布局
+    pub size_mixing: bool,
+    /// 每步 RNG 的算法
+    pub rng_kind: RngKind,
+}
+
+/// 依次生成三种尺寸并拼接为对比图。每开始一个尺寸前调用 `on_progress(尺寸键)`。
+pub fn render_size_strip(
+    settings: &StripSettings,
+    font_data: &[u8],
+    mut on_progress: impl FnMut(&str),
+) -> Result<image::RgbaImage, String> {
+    let mut generated = Vec::with_capacity(STRIP_SIZES.len());
+    for size_key in STRIP_SIZES {
+        on_progress(size_key);
+        let mut context = HeadlessContext::load(size_key, None)?;
+        if let Some(layers) = &settings.layers {
+            context.profile.layers = layers.clone();
+        }
+        let pipeline = context.generate(settings.seed, |p| {
+            p.set_step_logging(false);
+            p.set_size_mixing(settings.size_mixing);
+            p.set_rng_kind(settings.rng_kind);
+            if let Some(snapshot) = &settings.snapshot {
+                p.restore_from_snapshot(snapshot);
+                p.set_scatter_salt(snapshot.scatter_salt);
+            }
+            for &index in &settings.disabled_steps {
+                p.set_step_enabled(index, false);
+            }
+        })
+        .map_err(|e| e.to_string())?;
+        generated.push((context, pipeline));
+    }
+
+    let widest = generated.iter().map(|(c, _)| c.profile.size.width).max().unwrap_or(1);
+    let factor = widest.div_ceil(STRIP_MAX_WIDTH);
+    let mut panels = Vec::with_capacity(generated.len());
+    for (context, pipeline) in &generated {
+        let size = &context.profile.size;
+        let bm = pipeline
+            .biome_map()
+            .ok_or_else(|| format!("{}: 生成后没有环境地图", size.key))?;
+        let label = format!("{} {}×{}", size.description, size.width, size.height);
+        panels.push((label, biome_thumbnail_scaled(bm, &context.biomes, factor)));
+    }
+    thumbnail_strip(&panels, font_data)
+}
+
+/// 默认的输出文件名
+pub fn default_file_name(seed: u64) -> String {
+    format!("sizes_{seed:016X}.png")
+}
+
+/// `strip` 子命令入口：以默认参数生成对比图并保存，成功返回 `true`。
+pub fn run_cli(args: &[String]) -> bool {
+    let arg_value = |flag: &str| {
+        args.iter()
+            .position(|a| a == flag)
+            .and_then(|i| args.get(i + 1))
+            .cloned()
+    };
+    let seed = match arg_value("--seed").map(|s| s.parse::<u64>()) {
+        None => 42,
+        Some(Ok(seed)) => seed,
+        Some(Err(e)) => {
+            eprintln!("无效的种子: {e}");
+            return false;
+        }
+    };
+    let path = arg_value("--out")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(default_file_name(seed)));
+
+    let settings = StripSettings {
+        seed,
+        snapshot: None,
+        layers: None,
+        disabled_steps: Vec::new(),
+        size_mixing: !args.iter().any(|a| a == "--no-size-mix"),
+        rng_kind: RngKind::default(),
+    };
+    let result = render_size_strip(&settings, crate::ui::app::CJK_FONT_BYTES, |size_key| {
+        eprintln!("生成 {size_key} (seed {seed})...");
+    })
+    .and_then(|img| img.save(&path).map_err(|e| format!("保存失败: {e}")));
+    match result {
+        Ok(()) => {
+            println!("{}", path.display());
+            true
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            false
+        }
+    }
+}
@@ -5,12 +5,19 @@
 //! **设计原则**：不存方块数据，只存 seed + params + 配置。
 //! 导入时 replay 整个 pipeline 即可还原（确定性 RNG 保证）。
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use serde::{Deserialize, Serialize};
 
+use crate::config::world::WorldConfig;
+use crate::generation::algorithm::PhaseAlgorithm;
+use crate::generation::param_jitter::MAX_JITTER_STRENGTH;
+use crate::generation::step_rng::RngKind;
+use crate::rendering::pattern::BiomePattern;
+use crate::rendering::scale_bar::ScaleBarCorner;
+
 /// 存档格式当前版本
 pub const SNAPSHOT_VERSION: u32 = 1;
 
@@ -30,6 +37,14 @@ pub struct AlgorithmState {
     pub params: serde_json::Value,
 }
 
+/// 世界坐标上的命名书签（画布图钉）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub name: String,
+    pub x: u32,
+    pub y: u32,
+}
+
 /// 世界快照 — 完整复现一个世界所需的全部信息
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorldSnapshot {
@@ -37,14 +52,77 @@ pub struct WorldSnapshot {
     pub version: u32,
     /// 主种子
     pub seed: u64,
-    /// 世界尺寸键名 ("small" / "medium" / "large")
+    /// 散布盐（未重掷散布特征时为 0；旧存档缺省为 0）
+    #[serde(default)]
+    pub scatter_salt: u64,
+    /// 世界尺寸键名 ("small" / "medium" / "large" / "custom")
     pub world_size: String,
+    /// 自定义尺寸的宽高：仅 `world_size` 没有固定宽高时写入（自定义尺寸、导入的 .wld）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom_size: Option<(u32, u32)>,
     /// 层级配置覆盖（key → LayerOverride）
     pub layers: HashMap<String, LayerOverride>,
     /// 各算法模块的参数快照（按注册顺序）
     pub algorithms: Vec<AlgorithmState>,
+    /// 关闭生成的环境 key（「环境开关」；旧存档缺省为全部启用）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disabled_biomes: Vec<String>,
+    /// 单独禁用的子步骤（`算法ID.子步骤编号`，不含已由环境开关关闭的步骤；旧存档缺省为全部启用）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disabled_steps: Vec<String>,
+    /// 锁定的参数（`算法ID.参数键`）：参数拟合不会扰动这些参数。不影响生成结果，不计入参数哈希
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub locked_params: Vec<String>,
+    /// 参数抖动幅度（0 = 关闭；旧存档缺省为 0）。开启且有参数参与时计入参数哈希
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub jitter_strength: f64,
+    /// 参与抖动的参数（`算法ID.参数键`）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub jittered_params: Vec<String>,
+    /// 子种子是否混入世界尺寸（旧存档缺省为开启）。关闭时计入参数哈希
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    pub size_mixing: bool,
+    /// 步骤 RNG 算法（旧存档缺省为 StdRng）。非默认时计入参数哈希
+    #[serde(default, skip_serializing_if = "is_default_rng")]
+    pub rng_kind: RngKind,
+    /// 书签（命名的世界坐标）。不影响生成结果，不计入参数哈希
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bookmarks: Vec<Bookmark>,
     /// 导出时的 Unix 时间戳（秒）
     pub timestamp: u64,
+    /// 已执行子步骤数：仅会话自动保存写入，恢复时回放到此进度；普通存档缺省，导入后执行到底
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub executed: Option<usize>,
+}
+
+/// [`WorldSnapshot::collect`] 的可选状态；缺省值与旧存档缺省这些字段时一致
+#[derive(Debug, Clone)]
+pub struct SnapshotExtras {
+    pub scatter_salt: u64,
+    pub disabled_biomes: Vec<String>,
+    pub disabled_steps: Vec<String>,
+    pub locked_params: Vec<String>,
+    pub jitter_strength: f64,
+    pub jittered_params: Vec<String>,
+    pub size_mixing: bool,
+    pub rng_kind: RngKind,
+    pub bookmarks: Vec<Bookmark>,
+}
+
+impl Default for SnapshotExtras {
+    fn default() -> Self {
+        Self {
+            scatter_salt: 0,
+            disabled_biomes: Vec::new(),
+            disabled_steps: Vec::new(),
+            locked_params: Vec::new(),
+            jitter_strength: 0.0,
+            jittered_params: Vec::new(),
+            size_mixing: true,
+            rng_kind: RngKind::default(),
+            bookmarks: Vec::new(),
+        }
+    }
 }
 
 impl WorldSnapshot {
@@ -66,11 +144,20 @@ impl WorldSnapshot {
     }
 
     /// 从 `.lwd` 文件加载
+    ///
+    /// 结构损坏（非 JSON、缺少字段、字段类型或层级百分比错误）时返回具体诊断，
+    /// 如「存档损坏: 层级 cavern 的 end_percent 超出范围」。
+    /// 与当前配置相关的检查（世界尺寸、算法参数）见 [`validate`](Self::validate)。
     pub fn load_lwd(path: &Path) -> Result<Self, String> {
         let content = std::fs::read_to_string(path)
             .map_err(|e| format!("读取文件失败: {e}"))?;
-        let snapshot: WorldSnapshot = serde_json::from_str(&content)
-            .map_err(|e| format!("解析存档失败: {e}"))?;
+        let value: serde_json::Value = serde_json::from_str(&content)
+            .map_err(|e| format!("存档损坏: 不是有效的 JSON（第 {} 行第 {} 列）", e.line(), e.column()))?;
+        if let Some(problem) = diagnose_structure(&value) {
+            return Err(format!("存档损坏: {problem}"));
+        }
+        let snapshot: WorldSnapshot = serde_json::from_value(value)
+            .map_err(|e| format!("存档损坏: {e}"))?;
         if snapshot.version > SNAPSHOT_VERSION {
             return Err(format!(
                 "存档版本 {} 高于当前支持的版本 {}",
@@ -80,13 +167,88 @@ impl WorldSnapshot {
         Ok(snapshot)
     }
 
+    /// 按当前配置校验快照：世界尺寸可复现，已注册算法的参数符合各自的参数定义。
+    ///
+    /// 只有结构与类型问题（未知 / 无法复现的世界尺寸、参数不是对象、参数类型不符）视为存档损坏，
+    /// 返回第一条诊断，其余问题只计数。取值越界（数值超出范围、未知枚举项、抖动幅度越界）
+    /// 就地修正为最接近的合法值，并作为警告返回，存档仍可导入。
+    /// 存档中没有对应算法的参数（如未加载的扩展阶段）不视为损坏。
+    pub fn validate<'a>(
+        &mut self,
+        world_cfg: &WorldConfig,
+        algorithms: impl IntoIterator<Item = &'a dyn PhaseAlgorithm>,
+    ) -> Result<Vec<String>, String> {
+        let mut problems = Vec::new();
+        let mut warnings = Vec::new();
+        match world_cfg.world_sizes.get(&self.world_size) {
+            None => problems.push(format!("未知的世界尺寸 {}", self.world_size)),
+            Some(size) if size.width.is_some() && size.height.is_some() => {}
+            Some(_) => match self.custom_size {
+                Some((w, h)) if w > 0 && h > 0 => {}
+                Some((w, h)) => problems.push(format!("自定义尺寸 {w}×{h} 无效")),
+                None => problems.push(format!("世界尺寸 {} 没有固定宽高，且存档未记录自定义宽高", self.world_size)),
+            },
+        }
+        if !(0.0..=MAX_JITTER_STRENGTH).contains(&self.jitter_strength) {
+            let clamped = if self.jitter_strength.is_nan() { 0.0 } else { self.jitter_strength.clamp(0.0, MAX_JITTER_STRENGTH) };
+            warnings.push(format!(
+                "参数抖动幅度 {} 超出范围 [0, {MAX_JITTER_STRENGTH}]，已修正为 {clamped}",
+                self.jitter_strength,
+            ));
+            self.jitter_strength = clamped;
+        }
+        for algo in algorithms {
+            let meta = algo.meta();
+            let Some(state) = self.algorithms.iter_mut().find(|s| s.algorithm_id == meta.id) else {
+                continue;
+            };
+            let Some(params) = state.params.as_object_mut() else {
+                problems.push(format!("算法 {} 的参数不是对象", meta.id));
+                continue;
+            };
+            for def in &meta.params {
+                let Some(value) = params.get_mut(&def.key) else {
+                    continue;
+                };
+                let Err(reason) = def.check_value(value) else {
+                    continue;
+                };
+                match def.clamp_value(value) {
+                    Some(fixed) => {
+                        warnings.push(format!("算法 {} 的参数 {} {reason}，已修正为 {fixed}", meta.id, def.key));
+                        *value = fixed;
+                    }
+                    None => problems.push(format!("算法 {} 的参数 {} {reason}", meta.id, def.key)),
+                }
+            }
+        }
+
+        match problems.split_first() {
+            None => Ok(warnings),
+            Some((first, [])) => Err(format!("存档损坏: {first}")),
+            Some((first, rest)) => Err(format!("存档损坏: {first}（另有 {} 处问题）", rest.len())),
+        }
+    }
+
     /// 从当前运行状态收集快照
     pub fn collect(
         seed: u64,
         world_size: &str,
         layers: &[crate::core::layer::LayerDefinition],
         algorithms: &[Box<dyn crate::generation::algorithm::PhaseAlgorithm>],
+        extras: SnapshotExtras,
     ) -> Self {
+        let SnapshotExtras {
+            scatter_salt,
+            disabled_biomes,
+            disabled_steps,
+            locked_params,
+            jitter_strength,
+            jittered_params,
+            size_mixing,
+            rng_kind,
+            bookmarks,
+        } = extras;
         let layer_overrides: HashMap<String, LayerOverride> = layers
             .iter()
             .map(|l| {
@@ -114,36 +276,511 @@ impl WorldSnapshot {
         Self {
             version: SNAPSHOT_VERSION,
             seed,
+            scatter_salt,
             world_size: world_size.to_string(),
+            custom_size: None,
             layers: layer_overrides,
             algorithms: algo_states,
+            disabled_biomes,
+            disabled_steps,
+            locked_params,
+            jitter_strength,
+            jittered_params,
+            size_mixing,
+            rng_kind,
+            bookmarks,
             timestamp: Self::now_timestamp(),
+            executed: None,
+        }
+    }
+
+    /// 参数集哈希：对世界尺寸、层级覆盖、全部算法参数、环境开关、禁用的子步骤、子种子模式与 RNG 算法的规范化 JSON 做 FNV-1a。
+    ///
+    /// 不含种子与时间戳——种子相同且哈希相同即可复现同一个世界。
+    pub fn params_hash(&self) -> u64 {
+        let layers: BTreeMap<&String, [u8; 2]> = self.layers.iter()
+            .map(|(key, l)| (key, [l.start_percent, l.end_percent]))
+            .collect();
+        let mut canonical = serde_json::json!({
+            "world_size": self.world_size,
+            "layers": layers,
+            "algorithms": self.algorithms,
+        });
+        // 固定尺寸不写宽高，保持旧存档的哈希不变
+        if let Some((width, height)) = self.custom_size {
+            canonical["custom_size"] = serde_json::json!([width, height]);
+        }
+        // 全部启用时不计入，保持旧存档的哈希不变
+        if !self.disabled_biomes.is_empty() {
+            canonical["disabled_biomes"] = serde_json::json!(self.disabled_biomes);
+        }
+        if !self.disabled_steps.is_empty() {
+            canonical["disabled_steps"] = serde_json::json!(self.disabled_steps);
+        }
+        // 同理，未开启抖动时不计入
+        if self.jitter_strength > 0.0 && !self.jittered_params.is_empty() {
+            canonical["jitter"] = serde_json::json!({
+                "strength": self.jitter_strength,
+                "params": self.jittered_params,
+            });
+        }
+        // 默认混入尺寸，只有关闭时计入
+        if !self.size_mixing {
+            canonical["size_mixing"] = serde_json::json!(false);
+        }
+        if self.rng_kind != RngKind::Std {
+            canonical["rng_kind"] = serde_json::json!(self.rng_kind);
+        }
+        canonical.to_string().bytes().fold(0xcbf2_9ce4_8422_2325u64, |hash, b| {
+            (hash ^ b as u64).wrapping_mul(0x0100_0000_01b3)
+        })
+    }
+}
+
+fn is_zero(value: &f64) -> bool {
+    *value == 0.0
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn is_true(value: &bool) -> bool {
+    *value
+}
+
+fn is_default_rng(kind: &RngKind) -> bool {
+    *kind == RngKind::Std
+}
+
+/// 反序列化之前的结构检查：必需字段是否存在、类型是否正确、层级百分比是否在 0 ~ 100 且首尾不颠倒。
+///
+/// 返回第一处问题；serde 的报错只有行列号，这里给出具体字段。
+fn diagnose_structure(value: &serde_json::Value) -> Option<String> {
+    let Some(obj) = value.as_object() else {
+        return Some("顶层不是 JSON 对象".to_string());
+    };
+    type IsValid = fn(&serde_json::Value) -> bool;
+    let fields: [(&str, IsValid, &str); 6] = [
+        ("version", serde_json::Value::is_u64, "非负整数"),
+        ("seed", serde_json::Value::is_u64, "非负整数"),
+        ("world_size", serde_json::Value::is_string, "字符串"),
+        ("layers", serde_json::Value::is_object, "对象"),
+        ("algorithms", serde_json::Value::is_array, "数组"),
+        ("timestamp", serde_json::Value::is_u64, "非负整数"),
+    ];
+    for (key, check, expected) in fields {
+        match obj.get(key) {
+            None => return Some(format!("缺少字段 {key}")),
+            Some(v) if !check(v) => return Some(format!("字段 {key} 应为{expected}")),
+            Some(_) => {}
+        }
+    }
+
+    for (key, layer) in obj["layers"].as_object()? {
+        let percent = |field: &str| -> Result<u64, String> {
+            let v = layer.get(field).ok_or_else(|| format!("层级 {key} 缺少 {field}"))?;
+            match v.as_u64() {
+                Some(p) if p <= 100 => Ok(p),
+                Some(_) => Err(format!("层级 {key} 的 {field} 超出范围（0 ~ 100）")),
+                None => Err(format!("层级 {key} 的 {field} 应为整数")),
+            }
+        };
+        let (start, end) = match (percent("start_percent"), percent("end_percent")) {
+            (Ok(start), Ok(end)) => (start, end),
+            (Err(e), _) | (_, Err(e)) => return Some(e),
+        };
+        if start > end {
+            return Some(format!("层级 {key} 的 start_percent ({start}) 大于 end_percent ({end})"));
+        }
+    }
+
+    for (i, algo) in obj["algorithms"].as_array()?.iter().enumerate() {
+        if !algo.get("algorithm_id").is_some_and(serde_json::Value::is_string) {
+            return Some(format!("第 {} 个算法缺少 algorithm_id", i + 1));
+        }
+        if algo.get("params").is_none() {
+            return Some(format!("算法 {} 缺少 params", algo["algorithm_id"].as_str().unwrap_or("?")));
         }
     }
+    None
+}
+
+/// PNG 导出的画面布局
+#[derive(Debug, Clone, Copy)]
+pub enum PngLayout {
+    /// 1:1 输出世界尺寸
+    Native,
+    /// 固定尺寸：世界图像等比缩放后居中放到 `target` 大小的画布上，其余区域填充 `background`。
+    /// 用于生成统一尺寸的缩略图（如 1920×1080）
+    Padded {
+        target: (u32, u32),
+        /// 未预乘的 RGBA
+        background: [u8; 4],
+    },
+}
+
+/// 导出用的深度明暗（2.5D 效果）：越深越暗，每个层级顶部加一道渐隐高光。
+///
+/// 只作用于导出图像，交互画布不受影响。
+#[derive(Debug, Clone)]
+pub struct DepthShading {
+    /// 各层级顶部所在行（像素，不含世界顶部）
+    pub layer_tops: Vec<u32>,
 }
 
-/// 将世界方块数据导出为 PNG 文件
-pub fn export_png(
+impl DepthShading {
+    /// 世界底部相对顶部的最大变暗比例
+    const DEPTH_DARKEN: f32 = 0.35;
+    /// 层级顶部高光的最大增亮比例
+    const HIGHLIGHT: f32 = 0.25;
+
+    /// 从层级定义取各层级顶部行
+    pub fn from_layers(layers: &[crate::core::layer::LayerDefinition], height: u32) -> Self {
+        let mut layer_tops: Vec<u32> = layers.iter()
+            .map(|l| l.bounds_for_height(height).0)
+            .filter(|&top| top > 0 && top < height)
+            .collect();
+        layer_tops.sort_unstable();
+        layer_tops.dedup();
+        Self { layer_tops }
+    }
+
+    /// 第 `y` 行的亮度系数（1.0 = 原色）
+    fn row_factor(&self, y: u32, height: u32) -> f32 {
+        let depth = y as f32 / height.max(1) as f32;
+        let mut factor = 1.0 - Self::DEPTH_DARKEN * depth;
+        // 高光宽度随世界高度缩放，小世界至少 2 行
+        let band = (height / 200).max(2);
+        if let Some(d) = self.layer_tops.iter().filter(|&&top| top <= y).map(|&top| y - top).min()
+            && d < band
+        {
+            factor += Self::HIGHLIGHT * (1.0 - d as f32 / band as f32);
+        }
+        factor
+    }
+
+    /// 按行调整图像亮度（alpha 不变）
+    fn apply(&self, img: &mut image::RgbaImage) {
+        let height = img.height();
+        for (y, row) in img.rows_mut().enumerate() {
+            let factor = self.row_factor(y as u32, height);
+            for px in row {
+                for c in &mut px.0[..3] {
+                    *c = (*c as f32 * factor).round().clamp(0.0, 255.0) as u8;
+                }
+            }
+        }
+    }
+}
+
+/// 导出图像上的层级标注：分界线 + 层级名称（字体随请求传入，供工作线程光栅化文字）
+#[derive(Debug, Clone)]
+pub struct LayerAnnotations {
+    /// `(起始行, 结束行, 短名称)`
+    pub layers: Vec<(u32, u32, String)>,
+    pub font_data: &'static [u8],
+}
+
+impl LayerAnnotations {
+    pub fn from_layers(layers: &[crate::core::layer::LayerDefinition], height: u32, font_data: &'static [u8]) -> Self {
+        let layers = layers.iter()
+            .map(|l| {
+                let (start, end) = l.bounds_for_height(height);
+                (start, end, l.short_name.clone())
+            })
+            .collect();
+        Self { layers, font_data }
+    }
+}
+
+/// 导出图像上的比例尺（字体随请求传入，供工作线程光栅化文字）
+#[derive(Debug, Clone)]
+pub struct ScaleBar {
+    /// 比例尺代表的格数，0 = 自动
+    pub tiles: u32,
+    pub corner: ScaleBarCorner,
+    pub font_data: &'static [u8],
+}
+
+/// 渲染导出用的世界 RGBA 图像（不写文件）。
+///
+/// `biome_map` 为 `Some` 时把环境覆盖色按其 alpha 混合进方块颜色，效果等同于开启
+/// 「环境覆盖色」时画布上的显示：覆盖色按 over 合成（`out_a = a_src + a_dst·(1 − a_src)`），方块处仍不透明，
+/// 空气处取覆盖色的不透明度，不会因为底下是空气而整片透明；为 `None`（或尚未生成）时为纯方块颜色。
+/// `pattern` 为环境图案模式（与画布覆盖层相同）。
+/// `shading` 为 `Some` 时在缩放前叠加深度明暗；`annotations` 为 `Some` 时在缩放后画层级分界线与名称；
+/// `scale_bar` 为 `Some` 时最后在世界区域一角画比例尺（长度按缩放后的每格像素数换算）。
+/// `progress` 在逐行构建像素时以 `0.0..=1.0` 回调，供导出进度显示。
+#[allow(clippy::too_many_arguments)]
+pub fn render_png_image(
+    world: &crate::core::world::World,
+    color_lut: &[egui::Color32; 256],
+    biome_map: Option<&crate::core::biome::BiomeMap>,
+    biome_defs: &[crate::core::biome::BiomeDefinition],
+    pattern: BiomePattern,
+    shading: Option<&DepthShading>,
+    annotations: Option<&LayerAnnotations>,
+    scale_bar: Option<&ScaleBar>,
+    layout: PngLayout,
+    progress: &mut dyn FnMut(f32),
+) -> Result<image::RgbaImage, String> {
+    use image::imageops::{self, FilterType};
+    use crate::rendering::legend::draw_layer_annotations;
+    use crate::rendering::scale_bar::draw_scale_bar;
+
+    let mut src = blended_world_image(world, color_lut, biome_map, biome_defs, pattern, progress)?;
+    if let Some(shading) = shading {
+        shading.apply(&mut src);
+    }
+    let (tw, th, background) = match layout {
+        PngLayout::Native => {
+            if let Some(a) = annotations {
+                draw_layer_annotations(&mut src, [0, 0, world.width, world.height], world.height, &a.layers, a.font_data)?;
+            }
+            if let Some(bar) = scale_bar {
+                draw_scale_bar(&mut src, [0, 0, world.width, world.height], world.width, bar.tiles, bar.corner, bar.font_data)?;
+            }
+            return Ok(src);
+        }
+        PngLayout::Padded { target, background } => (target.0, target.1, background),
+    };
+    if tw == 0 || th == 0 || world.width == 0 || world.height == 0 {
+        return Err("导出尺寸无效".to_string());
+    }
+
+    // 等比缩放到能放进目标画布的最大尺寸
+    let scale = (tw as f64 / world.width as f64).min(th as f64 / world.height as f64);
+    let sw = ((world.width as f64 * scale).round() as u32).clamp(1, tw);
+    let sh = ((world.height as f64 * scale).round() as u32).clamp(1, th);
+    // 缩小用三角滤波平滑，放大保持像素边缘
+    let filter = if scale < 1.0 { FilterType::Triangle } else { FilterType::Nearest };
+    let scaled = imageops::resize(&src, sw, sh, filter);
+
+    let mut canvas = image::RgbaImage::from_pixel(tw, th, image::Rgba(background));
+    let (ox, oy) = ((tw - sw) / 2, (th - sh) / 2);
+    imageops::overlay(&mut canvas, &scaled, ox as i64, oy as i64);
+    if let Some(a) = annotations {
+        draw_layer_annotations(&mut canvas, [ox, oy, sw, sh], world.height, &a.layers, a.font_data)?;
+    }
+    if let Some(bar) = scale_bar {
+        draw_scale_bar(&mut canvas, [ox, oy, sw, sh], world.width, bar.tiles, bar.corner, bar.font_data)?;
+    }
+    Ok(canvas)
+}
+
+/// 把未预乘的覆盖色 `top` 按 over 合成到（预乘的）方块颜色 `base` 上，返回未预乘的 RGBA
+fn composite_over(base: egui::Color32, top: [u8; 4]) -> [u8; 4] {
+    let a_src = top[3] as f32 / 255.0;
+    let a_dst = base.a() as f32 / 255.0;
+    let out_a = a_src + a_dst * (1.0 - a_src);
+    if out_a <= 0.0 {
+        return [0, 0, 0, 0];
+    }
+    // Color32 为预乘颜色：base 的通道已乘过 a_dst
+    let channel = |dst: u8, src: u8| {
+        let premultiplied = src as f32 * a_src + dst as f32 * (1.0 - a_src);
+        (premultiplied / out_a).round().clamp(0.0, 255.0) as u8
+    };
+    [
+        channel(base.r(), top[0]),
+        channel(base.g(), top[1]),
+        channel(base.b(), top[2]),
+        (out_a * 255.0).round() as u8,
+    ]
+}
+
+/// 构建世界 RGBA 图像，可选把环境覆盖色（按 `pattern` 叠加图案）按 alpha 混合进方块颜色
+fn blended_world_image(
     world: &crate::core::world::World,
     color_lut: &[egui::Color32; 256],
-    path: &Path,
-) -> Result<(), String> {
+    biome_map: Option<&crate::core::biome::BiomeMap>,
+    biome_defs: &[crate::core::biome::BiomeDefinition],
+    pattern: BiomePattern,
+    progress: &mut dyn FnMut(f32),
+) -> Result<image::RgbaImage, String> {
     let w = world.width;
     let h = world.height;
 
+    // 环境 LUT: [r, g, b, a]，未定义的 id 保持全透明
+    let mut biome_lut = [[0u8; 4]; 256];
+    for bdef in biome_defs {
+        biome_lut[bdef.id as usize] = bdef.overlay_color;
+    }
+
+    // 尺寸不一致的地图（如切换尺寸后尚未重新生成）视为无环境
+    let biome_data = biome_map
+        .filter(|bm| bm.width == w && bm.height == h)
+        .map(|bm| bm.data());
+
     let mut buf: Vec<u8> = Vec::with_capacity((w * h * 4) as usize);
-    for &tile in &world.tiles {
+    let row_len = w.max(1) as usize;
+    for (i, &tile) in world.tiles.iter().enumerate() {
+        if i % row_len == 0 {
+            progress(i as f32 / world.tiles.len().max(1) as f32);
+        }
         let c = color_lut[tile as usize];
-        buf.push(c.r());
-        buf.push(c.g());
-        buf.push(c.b());
-        buf.push(c.a());
+        let overlay = biome_data
+            .map(|data| {
+                let id = data[i];
+                pattern.shade(id, biome_lut[id as usize], i as u32 % w, i as u32 / w)
+            })
+            .unwrap_or([0, 0, 0, 0]);
+        buf.extend_from_slice(&composite_over(c, overlay));
     }
+    progress(1.0);
+
+    image::RgbaImage::from_raw(w, h, buf)
+        .ok_or_else(|| "创建图像缓冲区失败".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{blended_world_image, composite_over, diagnose_structure};
+    use crate::config::world::load_world_config;
+    use crate::generation::algorithm::ParamType;
+    use crate::generation::build_pipeline;
+    use crate::generation::headless::HeadlessContext;
+
+    #[test]
+    fn validation_pinpoints_the_corrupt_field() {
+        let context = HeadlessContext::load("small", None).unwrap();
+        let world_cfg = load_world_config().unwrap();
+        let pipeline = build_pipeline(1, context.biomes.clone(), &context.profile.layers);
+        let snapshot = pipeline.collect_snapshot("small", &context.profile.layers);
+        assert_eq!(snapshot.clone().validate(&world_cfg, pipeline.algorithms()), Ok(Vec::new()), "默认参数的快照应通过校验");
+
+        let mut value = serde_json::to_value(&snapshot).unwrap();
+        assert_eq!(diagnose_structure(&value), None);
+        value["layers"]["cavern"]["end_percent"] = serde_json::json!(140);
+        assert_eq!(diagnose_structure(&value).as_deref(), Some("层级 cavern 的 end_percent 超出范围（0 ~ 100）"));
+        value.as_object_mut().unwrap().remove("seed");
+        assert_eq!(diagnose_structure(&value).as_deref(), Some("缺少字段 seed"));
+
+        let mut bad = snapshot.clone();
+        bad.world_size = "custom".to_string();
+        let state = bad.algorithms.iter_mut().find(|s| s.algorithm_id == "decoration").unwrap();
+        state.params["density_scale"] = serde_json::json!("dense");
+        let err = bad.validate(&world_cfg, pipeline.algorithms()).unwrap_err();
+        assert_eq!(err, "存档损坏: 世界尺寸 custom 没有固定宽高，且存档未记录自定义宽高（另有 1 处问题）");
+
+        // 取值越界不算损坏：就地钳制到范围内并给出警告
+        let max = pipeline.algorithm_by_id("decoration").unwrap().meta().params.iter()
+            .find_map(|d| match (d.key.as_str(), &d.param_type) {
+                ("density_scale", ParamType::Float { max, .. }) => Some(*max),
+                _ => None,
+            })
+            .unwrap();
+        let mut out_of_range = snapshot.clone();
+        out_of_range.jitter_strength = 3.0;
+        let state = out_of_range.algorithms.iter_mut().find(|s| s.algorithm_id == "decoration").unwrap();
+        state.params["density_scale"] = serde_json::json!(max * 10.0);
+        let warnings = out_of_range.validate(&world_cfg, pipeline.algorithms()).unwrap();
+        assert_eq!(warnings.len(), 2, "{warnings:?}");
+        assert!(warnings[1].starts_with("算法 decoration 的参数 density_scale 超出范围"), "{warnings:?}");
+        let state = out_of_range.algorithms.iter().find(|s| s.algorithm_id == "decoration").unwrap();
+        assert_eq!(state.params["density_scale"], serde_json::json!(max));
+        assert_eq!(out_of_range.jitter_strength, super::MAX_JITTER_STRENGTH);
+
+        // 记录了宽高的自定义尺寸可以复现，宽高计入参数哈希
+        let mut custom = snapshot.clone();
+        custom.world_size = "custom".to_string();
+        custom.custom_size = Some((640, 360));
+        assert!(custom.validate(&world_cfg, pipeline.algorithms()).is_ok());
+        let mut other = custom.clone();
+        other.custom_size = Some((640, 400));
+        assert_ne!(custom.params_hash(), other.params_hash());
+    }
+
+    #[test]
+    fn size_mixing_round_trips_and_defaults_on() {
+        let context = HeadlessContext::load("small", None).unwrap();
+        let mut pipeline = build_pipeline(1, context.biomes.clone(), &context.profile.layers);
+        let snapshot = pipeline.collect_snapshot("small", &context.profile.layers);
+        let value = serde_json::to_value(&snapshot).unwrap();
+        assert!(value.get("size_mixing").is_none(), "默认开启时不写入，保持旧格式");
 
-    let img = image::RgbaImage::from_raw(w, h, buf)
-        .ok_or_else(|| "创建图像缓冲区失败".to_string())?;
-    img.save(path)
-        .map_err(|e| format!("保存 PNG 失败: {e}"))?;
+        pipeline.set_size_mixing(false);
+        let unmixed = pipeline.collect_snapshot("small", &context.profile.layers);
+        assert_ne!(unmixed.params_hash(), snapshot.params_hash());
+        let restored: super::WorldSnapshot = serde_json::from_value(serde_json::to_value(&unmixed).unwrap()).unwrap();
+        assert!(!restored.size_mixing);
 
-    Ok(())
+        let mut replay = build_pipeline(1, context.biomes.clone(), &context.profile.layers);
+        replay.restore_from_snapshot(&restored);
+        assert!(!replay.size_mixing(), "导入存档应恢复子种子模式");
+        replay.restore_from_snapshot(&serde_json::from_value(value).unwrap());
+        assert!(replay.size_mixing(), "旧存档缺省为开启");
+    }
+
+    #[test]
+    fn disabled_steps_round_trip_separately_from_biome_toggles() {
+        let context = HeadlessContext::load("small", None).unwrap();
+        let mut pipeline = build_pipeline(1, context.biomes.clone(), &context.profile.layers);
+        let snapshot = pipeline.collect_snapshot("small", &context.profile.layers);
+        let value = serde_json::to_value(&snapshot).unwrap();
+        assert!(value.get("disabled_steps").is_none(), "全部启用时不写入，保持旧格式");
+
+        let toggle = pipeline.biome_toggles().into_iter().next().expect("至少一个环境开关");
+        pipeline.set_biome_enabled(&toggle.key, false);
+        let plain = (0..pipeline.total_sub_steps())
+            .find(|&flat| pipeline.is_step_enabled(flat) && flat > 0)
+            .unwrap();
+        pipeline.set_step_enabled(plain, false);
+        let saved = pipeline.collect_snapshot("small", &context.profile.layers);
+        assert_eq!(saved.disabled_biomes, vec![toggle.key.clone()]);
+        assert_eq!(saved.disabled_steps.len(), 1, "环境开关关闭的步骤不重复记录: {:?}", saved.disabled_steps);
+        assert_ne!(saved.params_hash(), snapshot.params_hash());
+
+        let restored: super::WorldSnapshot = serde_json::from_value(serde_json::to_value(&saved).unwrap()).unwrap();
+        let mut replay = build_pipeline(1, context.biomes.clone(), &context.profile.layers);
+        replay.restore_from_snapshot(&restored);
+        assert_eq!(replay.disabled_steps(), pipeline.disabled_steps(), "导入存档应恢复步骤启用状态");
+        replay.restore_from_snapshot(&serde_json::from_value(value).unwrap());
+        assert!(replay.disabled_steps().is_empty(), "旧存档缺省为全部启用");
+    }
+
+    #[test]
+    fn rng_kind_round_trips_and_defaults_to_std() {
+        use crate::generation::step_rng::RngKind;
+
+        let context = HeadlessContext::load("small", None).unwrap();
+        let mut pipeline = build_pipeline(1, context.biomes.clone(), &context.profile.layers);
+        let snapshot = pipeline.collect_snapshot("small", &context.profile.layers);
+        let value = serde_json::to_value(&snapshot).unwrap();
+        assert!(value.get("rng_kind").is_none(), "默认算法不写入，保持旧格式");
+
+        pipeline.set_rng_kind(RngKind::DotNet);
+        let dotnet = pipeline.collect_snapshot("small", &context.profile.layers);
+        assert_ne!(dotnet.params_hash(), snapshot.params_hash());
+        let restored: super::WorldSnapshot = serde_json::from_value(serde_json::to_value(&dotnet).unwrap()).unwrap();
+
+        let mut replay = build_pipeline(1, context.biomes.clone(), &context.profile.layers);
+        replay.restore_from_snapshot(&restored);
+        assert_eq!(replay.rng_kind(), RngKind::DotNet, "导入存档应恢复 RNG 算法");
+        replay.restore_from_snapshot(&serde_json::from_value(value).unwrap());
+        assert_eq!(replay.rng_kind(), RngKind::Std, "旧存档缺省为 StdRng");
+    }
+
+    #[test]
+    fn biome_blend_is_not_transparent_over_air() {
+        use crate::core::biome::BiomeMap;
+        use crate::core::world::World;
+        use crate::rendering::pattern::BiomePattern;
+
+        let context = HeadlessContext::load("small", None).unwrap();
+        let bdef = &context.biomes[1];
+        let world = World::new_air(8, 4);
+        let lut = [egui::Color32::TRANSPARENT; 256];
+        let biome_map = BiomeMap::new_filled(8, 4, bdef.id);
+        let img = blended_world_image(&world, &lut, Some(&biome_map), &context.biomes, BiomePattern::Off, &mut |_| {})
+            .unwrap();
+        let [r, g, b, a] = bdef.overlay_color;
+        assert!(img.pixels().all(|p| p.0 == [r, g, b, a]), "空气上的覆盖色应保留其颜色与不透明度");
+
+        // 不透明方块上合成后仍不透明
+        let solid = egui::Color32::from_rgb(100, 50, 0);
+        assert_eq!(composite_over(solid, [200, 0, 0, 128])[3], 255);
+        assert_eq!(composite_over(solid, [0, 0, 0, 0]), [100, 50, 0, 255]);
+    }
 }
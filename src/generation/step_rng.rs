@@ -0,0 +1,191 @@
+//! # 步骤随机数生成器
+//!
+//! 每个子步骤用 `derive_step_seed` 派生的子种子构造一个 RNG（`RuntimeContext::rng`）。
+//! 具体算法由 [`RngKind`] 选择：不同算法对同一子种子给出不同、但各自确定的序列，
+//! 因此**切换算法会改变所有种子的生成结果**。所选算法随 .lwd 存档保存（旧存档缺省为 StdRng）。
+
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+/// 步骤 RNG 算法
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RngKind {
+    /// `rand` 的 `StdRng`（ChaCha12），默认
+    #[default]
+    Std,
+    /// ChaCha8：轮数更少、更快，统计质量对生成足够
+    ChaCha8,
+    /// .NET `System.Random`（Knuth 减法生成器），泰拉瑞亚世界生成使用的 PRNG
+    DotNet,
+}
+
+impl RngKind {
+    pub const ALL: [RngKind; 3] = [RngKind::Std, RngKind::ChaCha8, RngKind::DotNet];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            RngKind::Std => "StdRng (ChaCha12)",
+            RngKind::ChaCha8 => "ChaCha8",
+            RngKind::DotNet => ".NET System.Random",
+        }
+    }
+}
+
+/// 按 [`RngKind`] 构造的步骤 RNG
+pub enum StepRng {
+    Std(StdRng),
+    ChaCha8(ChaCha8Rng),
+    DotNet(DotNetRandom),
+}
+
+impl StepRng {
+    pub fn new(kind: RngKind, seed: u64) -> Self {
+        match kind {
+            RngKind::Std => StepRng::Std(StdRng::seed_from_u64(seed)),
+            RngKind::ChaCha8 => StepRng::ChaCha8(ChaCha8Rng::seed_from_u64(seed)),
+            RngKind::DotNet => StepRng::DotNet(DotNetRandom::new(fold_seed(seed))),
+        }
+    }
+}
+
+impl RngCore for StepRng {
+    fn next_u32(&mut self) -> u32 {
+        match self {
+            StepRng::Std(rng) => rng.next_u32(),
+            StepRng::ChaCha8(rng) => rng.next_u32(),
+            StepRng::DotNet(rng) => rng.next_u32(),
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        match self {
+            StepRng::Std(rng) => rng.next_u64(),
+            StepRng::ChaCha8(rng) => rng.next_u64(),
+            StepRng::DotNet(rng) => rng.next_u64(),
+        }
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        match self {
+            StepRng::Std(rng) => rng.fill_bytes(dest),
+            StepRng::ChaCha8(rng) => rng.fill_bytes(dest),
+            StepRng::DotNet(rng) => rng.fill_bytes(dest),
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// 64 位子种子折叠为 .NET `Random(int)` 接受的 32 位种子
+fn fold_seed(seed: u64) -> i32 {
+    (seed ^ (seed >> 32)) as u32 as i32
+}
+
+/// .NET `System.Random` 的种子构造与 `InternalSample`（Knuth 减法生成器，55 项滞后表）。
+///
+/// `InternalSample` 序列与 .NET 逐值一致；但 `gen_range` 等取值方式由 `rand` 决定，
+/// 因此生成的世界不会与泰拉瑞亚中同一种子的世界相同。
+pub struct DotNetRandom {
+    seed_array: [i32; 56],
+    inext: usize,
+    inextp: usize,
+}
+
+impl DotNetRandom {
+    const MSEED: i32 = 161_803_398;
+
+    pub fn new(seed: i32) -> Self {
+        let mut seed_array = [0i32; 56];
+        let subtraction = if seed == i32::MIN { i32::MAX } else { seed.abs() };
+        let mut mj = Self::MSEED.wrapping_sub(subtraction);
+        seed_array[55] = mj;
+        let mut mk = 1i32;
+        for i in 1..55 {
+            let ii = (21 * i) % 55;
+            seed_array[ii] = mk;
+            mk = mj.wrapping_sub(mk);
+            if mk < 0 {
+                mk = mk.wrapping_add(i32::MAX);
+            }
+            mj = seed_array[ii];
+        }
+        for _ in 1..5 {
+            for i in 1..56 {
+                seed_array[i] = seed_array[i].wrapping_sub(seed_array[1 + (i + 30) % 55]);
+                if seed_array[i] < 0 {
+                    seed_array[i] = seed_array[i].wrapping_add(i32::MAX);
+                }
+            }
+        }
+        Self { seed_array, inext: 0, inextp: 21 }
+    }
+
+    /// `[0, i32::MAX)` 内的下一个值（即 .NET 的 `Next()`）
+    pub fn internal_sample(&mut self) -> i32 {
+        self.inext = if self.inext + 1 >= 56 { 1 } else { self.inext + 1 };
+        self.inextp = if self.inextp + 1 >= 56 { 1 } else { self.inextp + 1 };
+        let mut ret = self.seed_array[self.inext].wrapping_sub(self.seed_array[self.inextp]);
+        if ret == i32::MAX {
+            ret -= 1;
+        }
+        if ret < 0 {
+            ret = ret.wrapping_add(i32::MAX);
+        }
+        self.seed_array[self.inext] = ret;
+        ret
+    }
+}
+
+impl RngCore for DotNetRandom {
+    /// 每个样本只有 31 位有效：取两个样本各 16 位拼成 32 位
+    fn next_u32(&mut self) -> u32 {
+        let hi = self.internal_sample() as u32 & 0xFFFF;
+        let lo = self.internal_sample() as u32 & 0xFFFF;
+        hi << 16 | lo
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        (self.next_u32() as u64) << 32 | self.next_u32() as u64
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(4) {
+            let bytes = self.next_u32().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dotnet_random_matches_reference_sequence() {
+        // new System.Random(0).Next() 的前 3 个值
+        let mut rng = DotNetRandom::new(0);
+        let values: Vec<i32> = (0..3).map(|_| rng.internal_sample()).collect();
+        assert_eq!(values, [1_559_595_546, 1_755_192_844, 1_649_316_166]);
+    }
+
+    #[test]
+    fn kinds_are_deterministic_and_distinct() {
+        let first = |kind| StepRng::new(kind, 42).next_u64();
+        for kind in RngKind::ALL {
+            assert_eq!(first(kind), first(kind), "{kind:?} 同一种子应得到相同序列");
+        }
+        assert_ne!(first(RngKind::Std), first(RngKind::ChaCha8));
+        assert_ne!(first(RngKind::Std), first(RngKind::DotNet));
+    }
+}
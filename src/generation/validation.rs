@@ -0,0 +1,309 @@
+//! # 世界校验报告
+//!
+//! 把分散的检查（环境覆盖率、连通性、邻接规则、层级约束、放置不足）汇总为一份
+//! [`WorldValidationReport`]，按需计算并导出为 JSON，每条问题带严重程度。
+//! QA 与 CI 可以直接读取这份文件判断世界是否异常，用户反馈"奇怪的世界"时也可附上它。
+//!
+//! `Lwd validate [--seed N] [--size KEY] [--out PATH]`：以默认参数生成并输出报告；
+//! 存在 error 级问题时以非零状态退出。
+
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use crate::core::biome::{BiomeDefinition, BiomeId, BiomeMap, BIOME_UNASSIGNED};
+use crate::core::layer::LayerDefinition;
+
+use super::headless::HeadlessContext;
+use super::pipeline::GenerationPipeline;
+
+/// 小于该格数的连通块视为碎片
+const MIN_FRAGMENT_TILES: usize = 64;
+
+/// 不应直接相邻的环境对（按 key）
+const FORBIDDEN_ADJACENCY: [(&str, &str); 4] = [
+    ("snow", "jungle"),
+    ("snow", "jungle_wet"),
+    ("crimson", "corruption"),
+    ("space", "hell"),
+];
+
+/// 只能出现在同名层级中的环境
+const LAYER_BOUND_BIOMES: [&str; 2] = ["space", "hell"];
+
+/// 问题的严重程度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+/// 一条校验问题
+#[derive(Debug, Clone, Serialize)]
+pub struct ValidationIssue {
+    pub severity: Severity,
+    /// 产生该问题的检查："pipeline" / "coverage" / "connectivity" / "adjacency" / "layer" / "placement"
+    pub check: &'static str,
+    pub message: String,
+}
+
+/// 单个环境的统计
+#[derive(Debug, Clone, Serialize)]
+pub struct BiomeStats {
+    pub key: String,
+    pub name: String,
+    /// 占世界面积的比例（0~1）
+    pub coverage: f64,
+    /// 4 邻接连通块数量
+    pub components: usize,
+}
+
+/// 世界校验报告
+#[derive(Debug, Clone, Serialize)]
+pub struct WorldValidationReport {
+    pub seed: u64,
+    pub width: u32,
+    pub height: u32,
+    /// 环境地图的短哈希（[`BiomeMap::short_hash`]），尚无地图时为空
+    pub biome_hash: String,
+    pub executed_steps: usize,
+    pub total_steps: usize,
+    pub biomes: Vec<BiomeStats>,
+    /// 按严重程度降序排列
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl WorldValidationReport {
+    /// 对流水线当前进度的环境地图执行全部检查。
+    pub fn compute(
+        pipeline: &GenerationPipeline,
+        biomes: &[BiomeDefinition],
+        layers: &[LayerDefinition],
+    ) -> Self {
+        let mut report = Self {
+            seed: pipeline.seed(),
+            width: 0,
+            height: 0,
+            biome_hash: String::new(),
+            executed_steps: pipeline.executed_sub_steps(),
+            total_steps: pipeline.total_sub_steps(),
+            biomes: Vec::new(),
+            issues: Vec::new(),
+        };
+        let complete = pipeline.is_complete();
+        if !complete {
+            report.push(
+                Severity::Warning,
+                "pipeline",
+                format!("流水线未执行完（{}/{}），结果仅反映当前进度", report.executed_steps, report.total_steps),
+            );
+        }
+        let Some(bm) = pipeline.biome_map() else {
+            report.push(Severity::Error, "pipeline", "尚未生成环境地图".to_string());
+            return report;
+        };
+        report.width = bm.width;
+        report.height = bm.height;
+        report.biome_hash = bm.short_hash();
+
+        report.check_coverage(bm, biomes, complete);
+        report.check_adjacency(bm, biomes);
+        report.check_layers(bm, biomes, layers);
+        report.check_placement(pipeline);
+
+        report.issues.sort_by_key(|issue| std::cmp::Reverse(issue.severity));
+        report
+    }
+
+    /// 最高的严重程度；没有问题时为 `None`
+    pub fn max_severity(&self) -> Option<Severity> {
+        self.issues.iter().map(|i| i.severity).max()
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.max_severity() == Some(Severity::Error)
+    }
+
+    /// 格式化为带缩进的 JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("校验报告序列化不会失败")
+    }
+
+    fn push(&mut self, severity: Severity, check: &'static str, message: String) {
+        self.issues.push(ValidationIssue { severity, check, message });
+    }
+
+    /// 覆盖率与连通性：未分配区域、未出现的环境、细小碎片
+    fn check_coverage(&mut self, bm: &BiomeMap, biomes: &[BiomeDefinition], complete: bool) {
+        let hist = bm.histogram();
+        let total = (bm.width as u64 * bm.height as u64).max(1) as f64;
+
+        let unassigned = hist[BIOME_UNASSIGNED as usize];
+        if unassigned > 0 {
+            let severity = if complete { Severity::Error } else { Severity::Info };
+            self.push(
+                severity,
+                "coverage",
+                format!("{unassigned} 格未分配环境（{:.2}%）", unassigned as f64 / total * 100.0),
+            );
+        }
+
+        let components = component_sizes(bm);
+        for bdef in biomes {
+            let count = hist[bdef.id as usize];
+            let sizes = &components[bdef.id as usize];
+            if count == 0 {
+                self.push(Severity::Info, "coverage", format!("{} 未出现在世界中", bdef.name));
+            }
+            let fragments = sizes.iter().filter(|&&n| n < MIN_FRAGMENT_TILES).count();
+            if fragments > 0 {
+                self.push(
+                    Severity::Warning,
+                    "connectivity",
+                    format!("{} 有 {fragments} 块小于 {MIN_FRAGMENT_TILES} 格的碎片", bdef.name),
+                );
+            }
+            self.biomes.push(BiomeStats {
+                key: bdef.key.clone(),
+                name: bdef.name.clone(),
+                coverage: count as f64 / total,
+                components: sizes.len(),
+            });
+        }
+    }
+
+    /// 邻接规则：禁止直接接壤的环境对
+    fn check_adjacency(&mut self, bm: &BiomeMap, biomes: &[BiomeDefinition]) {
+        let find = |key: &str| biomes.iter().find(|b| b.key == key);
+        let adjacency = bm.adjacency();
+        for (a, b) in FORBIDDEN_ADJACENCY {
+            let (Some(a), Some(b)) = (find(a), find(b)) else {
+                continue;
+            };
+            let pair = (a.id.min(b.id), a.id.max(b.id));
+            if let Some((_, len)) = adjacency.iter().find(|(p, _)| *p == pair) {
+                self.push(
+                    Severity::Warning,
+                    "adjacency",
+                    format!("{} 与 {} 直接接壤（边界 {len} 格）", a.name, b.name),
+                );
+            }
+        }
+    }
+
+    /// 层级约束：太空 / 地狱只能出现在同名层级的行范围内
+    fn check_layers(&mut self, bm: &BiomeMap, biomes: &[BiomeDefinition], layers: &[LayerDefinition]) {
+        for key in LAYER_BOUND_BIOMES {
+            let (Some(bdef), Some(layer)) = (
+                biomes.iter().find(|b| b.key == key),
+                layers.iter().find(|l| l.key == key),
+            ) else {
+                continue;
+            };
+            let (top, bottom) = layer.bounds_for_height(bm.height);
+            let outside: usize = (0..bm.height)
+                .filter(|y| !(top..bottom).contains(y))
+                .map(|y| (0..bm.width).filter(|&x| bm.get(x, y) == bdef.id).count())
+                .sum();
+            if outside > 0 {
+                self.push(
+                    Severity::Error,
+                    "layer",
+                    format!("{outside} 格{}位于 {} 层（{top}..{bottom} 行）之外", bdef.name, layer.key),
+                );
+            }
+        }
+    }
+
+    /// 放置不足：沙漠 / 真沙漠 / 邪恶环境的实际数量少于参数设定
+    fn check_placement(&mut self, pipeline: &GenerationPipeline) {
+        let Some(params) = pipeline.algorithm_by_id("biome_division").map(|a| a.get_params()) else {
+            return;
+        };
+        let wanted = |key: &str| params.get(key).and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+        let placed = [
+            ("地表沙漠", "desert_surface_count", pipeline.shared::<Vec<(i32, i32)>>("desert_slots").map(Vec::len)),
+            ("真沙漠", "desert_true_count", pipeline.shared::<Vec<(i32, i32)>>("desert_true_slots").map(Vec::len)),
+            ("邪恶环境", "evil_count", pipeline.shared::<Vec<(i32, i32, BiomeId)>>("evil_slots").map(Vec::len)),
+        ];
+        for (name, key, count) in placed {
+            // 对应步骤尚未执行时不检查
+            let Some(count) = count else {
+                continue;
+            };
+            let wanted = wanted(key);
+            if count < wanted {
+                self.push(
+                    Severity::Warning,
+                    "placement",
+                    format!("{name}只放置了 {count}/{wanted} 个（参数 {key}）"),
+                );
+            }
+        }
+    }
+}
+
+/// 每种环境各连通块（4 邻接）的格数，按环境 ID 索引
+fn component_sizes(bm: &BiomeMap) -> Vec<Vec<usize>> {
+    let mut sizes = vec![Vec::new(); 256];
+    for component in bm.components() {
+        sizes[component.biome as usize].push(component.size);
+    }
+    sizes
+}
+
+/// 默认的输出文件名
+pub fn default_file_name(seed: u64) -> String {
+    format!("validation_{seed:016X}.json")
+}
+
+/// `validate` 子命令入口：生成世界并输出校验报告（未指定 `--out` 时打印到标准输出），
+/// 没有 error 级问题时返回 `true`。
+pub fn run_cli(args: &[String]) -> bool {
+    let arg_value = |flag: &str| {
+        args.iter()
+            .position(|a| a == flag)
+            .and_then(|i| args.get(i + 1))
+            .cloned()
+    };
+    let seed = match arg_value("--seed").map(|s| s.parse::<u64>()) {
+        None => 42,
+        Some(Ok(seed)) => seed,
+        Some(Err(e)) => {
+            eprintln!("无效的种子: {e}");
+            return false;
+        }
+    };
+    let size_key = arg_value("--size").unwrap_or_else(|| "small".to_string());
+
+    let context = match HeadlessContext::load(&size_key, None) {
+        Ok(context) => context,
+        Err(e) => {
+            eprintln!("{e}");
+            return false;
+        }
+    };
+    let pipeline = match context.generate(seed, |p| p.set_step_logging(false)) {
+        Ok(pipeline) => pipeline,
+        Err(e) => {
+            eprintln!("生成失败: {e}");
+            return false;
+        }
+    };
+
+    let report = WorldValidationReport::compute(&pipeline, &context.biomes, &context.profile.layers);
+    let json = report.to_json();
+    match arg_value("--out").map(PathBuf::from) {
+        None => println!("{json}"),
+        Some(path) => {
+            if let Err(e) = std::fs::write(&path, json) {
+                eprintln!("保存失败: {e}");
+                return false;
+            }
+            println!("{}", path.display());
+        }
+    }
+    !report.has_errors()
+}
@@ -0,0 +1,100 @@
+//! # 单参数变体
+//!
+//! A/B 参数对比的生成端：以同一组种子、参数、层级与跳过的步骤无界面生成世界，
+//! 其中可选地把一个算法参数换成另一个值（B 侧）。只保留环境地图，由界面渲染并与 A 侧逐格比较。
+
+use crate::core::biome::BiomeMap;
+use crate::core::layer::LayerDefinition;
+
+use super::headless::HeadlessContext;
+use super::snapshot::WorldSnapshot;
+use super::step_rng::RngKind;
+
+/// 两侧共用的生成设置（通常取自当前流水线）
+#[derive(Clone)]
+pub struct VariantSettings {
+    pub seed: u64,
+    pub size_key: String,
+    /// 仅在 `size_key == "custom"` 时生效
+    pub custom_size: Option<(u32, u32)>,
+    /// 混入子种子的世界尺寸；`None` 时取生成尺寸。缩小尺寸预览时填真实世界尺寸，
+    /// 否则尺寸混入会让预览换成另一套子种子，与正式生成对不上
+    pub seed_size: Option<(u32, u32)>,
+    /// 算法参数与散布盐来源
+    pub snapshot: WorldSnapshot,
+    pub layers: Vec<LayerDefinition>,
+    /// 跳过的子步骤（flat 索引）
+    pub disabled_steps: Vec<usize>,
+    pub size_mixing: bool,
+    pub rng_kind: RngKind,
+}
+
+/// 替换单个算法参数
+#[derive(Debug, Clone)]
+pub struct ParamOverride {
+    pub algorithm_id: String,
+    pub key: String,
+    pub value: serde_json::Value,
+}
+
+/// 按设置生成完整世界并返回环境地图；`param` 为 `None` 时即 A 侧（原参数）。
+pub fn generate_variant(
+    settings: &VariantSettings,
+    param: Option<&ParamOverride>,
+) -> Result<BiomeMap, String> {
+    let mut context = HeadlessContext::load(&settings.size_key, settings.custom_size)?;
+    context.profile.layers = settings.layers.clone();
+    let pipeline = context
+        .generate(settings.seed, |p| {
+            p.set_step_logging(false);
+            p.set_size_mixing(settings.size_mixing);
+            p.set_seed_size(settings.seed_size);
+            p.set_rng_kind(settings.rng_kind);
+            p.restore_from_snapshot(&settings.snapshot);
+            p.set_scatter_salt(settings.snapshot.scatter_salt);
+            for &index in &settings.disabled_steps {
+                p.set_step_enabled(index, false);
+            }
+            if let Some(param) = param
+                && let Some(algo) = p.algorithm_by_id_mut(&param.algorithm_id)
+            {
+                let mut params = algo.get_params();
+                params[param.key.as_str()] = param.value.clone();
+                algo.set_params(&params);
+            }
+        })
+        .map_err(|e| e.to_string())?;
+    pipeline.biome_map().cloned().ok_or_else(|| "生成后没有环境地图".to_string())
+}
+
+/// 两张环境地图中环境不同的格子比例（0~1）；尺寸不同时为 1
+pub fn diff_ratio(a: &BiomeMap, b: &BiomeMap) -> f64 {
+    if a.width != b.width || a.height != b.height {
+        return 1.0;
+    }
+    let differing = a.data().iter().zip(b.data()).filter(|(x, y)| x != y).count();
+    differing as f64 / a.data().len().max(1) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::generation::headless::HeadlessContext;
+
+    #[test]
+    fn seed_size_keeps_step_seeds_of_the_real_world() {
+        let seeds = |context: &HeadlessContext, seed_size| {
+            let pipeline = context.generate(9, |p| {
+                p.set_step_logging(false);
+                p.set_seed_size(seed_size);
+            }).unwrap();
+            pipeline.seed_trace().iter().map(|t| t.step_seed).collect::<Vec<_>>()
+        };
+        let real = HeadlessContext::load("small", None).unwrap();
+        let size = (real.profile.size.width, real.profile.size.height);
+        let preview = HeadlessContext::load("custom", Some((size.0 / 6, size.1 / 6))).unwrap();
+
+        let expected = seeds(&real, None);
+        assert_ne!(seeds(&preview, None), expected, "尺寸混入下，缩小生成默认换一套子种子");
+        assert_eq!(seeds(&preview, Some(size)), expected, "指定真实尺寸后预览应沿用同一套子种子");
+    }
+}
@@ -9,6 +9,25 @@ mod ui;
 use ui::app::LianWorldApp;
 
 fn main() {
+    // 扩展阶段须在构建任何流水线之前登记（命令行子命令同样适用）
+    algorithms::register_extension_phases();
+
+    // 命令行子命令（无界面）
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let cli = match args.first().map(String::as_str) {
+        Some("regression") => Some(generation::regression::run_cli as fn(&[String]) -> bool),
+        Some("profile") => Some(generation::headless::run_profile_cli as fn(&[String]) -> bool),
+        Some("fit") => Some(generation::param_fit::run_cli as fn(&[String]) -> bool),
+        Some("strip") => Some(generation::size_strip::run_cli as fn(&[String]) -> bool),
+        Some("validate") => Some(generation::validation::run_cli as fn(&[String]) -> bool),
+        Some("--bench") => Some(generation::headless::run_bench_cli as fn(&[String]) -> bool),
+        _ => None,
+    };
+    if let Some(run) = cli {
+        let ok = run(&args[1..]);
+        std::process::exit(if ok { 0 } else { 1 });
+    }
+
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
             .with_title("Lian Terraria World Generator")
@@ -0,0 +1,87 @@
+//! # 环境字符画
+//!
+//! 把 BiomeMap 降采样为 `cols × rows` 的字符网格，每格取覆盖最多的环境（直方图取众数），
+//! 用 biome.json 中的 `ascii_char` 表示；未分配区域为 `.`。结果末尾附带出现过的环境图例，
+//! 方便直接粘贴到聊天中分享世界概览。
+
+use crate::core::biome::{BiomeDefinition, BiomeMap};
+
+/// 默认字符画列数
+pub const ASCII_COLS: u32 = 80;
+/// 默认字符画行数
+pub const ASCII_ROWS: u32 = 24;
+
+/// 未分配区域的字符
+const UNASSIGNED_CHAR: char = '.';
+/// 每格每个方向最多取样的点数（大世界下限制开销）
+const MAX_SAMPLES: u32 = 16;
+
+/// 生成环境字符画（网格 + 空行 + 图例），行间以 `\n` 分隔。
+pub fn biome_ascii_art(
+    biome_map: &BiomeMap,
+    biome_definitions: &[BiomeDefinition],
+    cols: u32,
+    rows: u32,
+) -> String {
+    let cols = cols.clamp(1, biome_map.width.max(1));
+    let rows = rows.clamp(1, biome_map.height.max(1));
+
+    let mut lut = [UNASSIGNED_CHAR; 256];
+    for bdef in biome_definitions {
+        lut[bdef.id as usize] = bdef.ascii_char;
+    }
+
+    let mut seen = [false; 256];
+    let mut out = String::with_capacity(((cols + 1) * rows) as usize + 256);
+    for row in 0..rows {
+        let (y0, y1) = cell_range(row, rows, biome_map.height);
+        for col in 0..cols {
+            let (x0, x1) = cell_range(col, cols, biome_map.width);
+            let dominant = dominant_biome(biome_map, x0..x1, y0..y1);
+            seen[dominant as usize] = true;
+            out.push(lut[dominant as usize]);
+        }
+        out.push('\n');
+    }
+
+    out.push('\n');
+    let legend: Vec<String> = biome_definitions
+        .iter()
+        .filter(|b| seen[b.id as usize])
+        .map(|b| format!("{} {}", b.ascii_char, b.name))
+        .collect();
+    out.push_str(&legend.join("  "));
+    if seen[0] {
+        out.push_str(&format!("  {UNASSIGNED_CHAR} 未分配"));
+    }
+    out.push('\n');
+    out
+}
+
+/// 第 `index` 格（共 `count` 格）覆盖的像素区间 `[start, end)`，至少 1 像素
+fn cell_range(index: u32, count: u32, len: u32) -> (u32, u32) {
+    let start = (index as u64 * len as u64 / count as u64) as u32;
+    let end = ((index as u64 + 1) * len as u64 / count as u64) as u32;
+    (start, end.max(start + 1).min(len.max(1)))
+}
+
+/// 区域内覆盖最多的环境（并列时取 ID 较小者）
+fn dominant_biome(
+    biome_map: &BiomeMap,
+    xs: std::ops::Range<u32>,
+    ys: std::ops::Range<u32>,
+) -> u8 {
+    let step_x = xs.len().div_ceil(MAX_SAMPLES as usize).max(1);
+    let step_y = ys.len().div_ceil(MAX_SAMPLES as usize).max(1);
+    let mut counts = [0u32; 256];
+    for y in ys.step_by(step_y) {
+        for x in xs.clone().step_by(step_x) {
+            counts[biome_map.get(x, y) as usize] += 1;
+        }
+    }
+    counts
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.cmp(b.1).then(b.0.cmp(&a.0)))
+        .map_or(0, |(id, _)| id as u8)
+}
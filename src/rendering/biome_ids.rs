@@ -0,0 +1,160 @@
+//! # 环境 ID 图导出 / 导入
+//!
+//! 把环境地图按原始 ID 无损写出：16 位灰度 PNG，每个像素的值就是该格的环境 ID
+//! （0 = 未分配），附同名 `.json` 图例列出 ID 与环境 key 的对应关系。
+//! 与着色的环境 PNG 不同，外部脚本可以逐像素精确读取。
+//!
+//! 导入时按图例中的 key 映射回当前的环境定义，环境 ID 调整过也能还原同一份地图；
+//! 图例缺失、尺寸不符或出现图例之外的值时报错，不做猜测。
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use image::{ImageBuffer, Luma};
+use serde::{Deserialize, Serialize};
+
+use crate::core::biome::{BiomeDefinition, BiomeId, BiomeMap, BIOME_UNASSIGNED};
+
+/// 图例的格式标识
+const LEGEND_FORMAT: &str = "lwd-biome-ids";
+/// 图例中表示未分配的 key
+const UNASSIGNED_KEY: &str = "unassigned";
+
+/// 图例中的一项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LegendEntry {
+    /// 像素值
+    pub id: u16,
+    pub key: String,
+    pub name: String,
+}
+
+/// `.json` 图例
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BiomeIdLegend {
+    pub format: String,
+    pub bit_depth: u8,
+    pub width: u32,
+    pub height: u32,
+    /// 地图中出现的环境（含未分配）
+    pub biomes: Vec<LegendEntry>,
+}
+
+/// 图例文件路径：与 PNG 同名的 `.json`
+pub fn legend_path(png_path: &Path) -> PathBuf {
+    png_path.with_extension("json")
+}
+
+/// 像素值即环境 ID 的 16 位灰度图像
+pub fn biome_id_image(biome_map: &BiomeMap) -> ImageBuffer<Luma<u16>, Vec<u16>> {
+    let data = biome_map.data().iter().map(|&id| id as u16).collect();
+    ImageBuffer::from_raw(biome_map.width, biome_map.height, data).expect("环境地图尺寸与数据长度一致")
+}
+
+/// 地图中出现的环境组成的图例
+pub fn biome_id_legend(biome_map: &BiomeMap, biomes: &[BiomeDefinition]) -> BiomeIdLegend {
+    let histogram = biome_map.histogram();
+    let entries = (0..histogram.len())
+        .filter(|&id| histogram[id] > 0)
+        .map(|id| {
+            let id = id as BiomeId;
+            let (key, name) = match biomes.iter().find(|b| b.id == id) {
+                Some(bdef) => (bdef.key.clone(), bdef.name.clone()),
+                None if id == BIOME_UNASSIGNED => (UNASSIGNED_KEY.to_string(), "未分配".to_string()),
+                None => (format!("unknown_{id}"), format!("未知环境 {id}")),
+            };
+            LegendEntry { id: id as u16, key, name }
+        })
+        .collect();
+    BiomeIdLegend {
+        format: LEGEND_FORMAT.to_string(),
+        bit_depth: 16,
+        width: biome_map.width,
+        height: biome_map.height,
+        biomes: entries,
+    }
+}
+
+/// 写出环境 ID 图 PNG 与同名 `.json` 图例
+pub fn export_biome_ids(biome_map: &BiomeMap, biomes: &[BiomeDefinition], path: &Path) -> Result<(), String> {
+    if biome_map.width == 0 || biome_map.height == 0 {
+        return Err("环境地图尺寸为空".to_string());
+    }
+    biome_id_image(biome_map)
+        .save(path)
+        .map_err(|e| format!("保存 PNG 失败: {e}"))?;
+
+    let legend_path = legend_path(path);
+    let text = serde_json::to_string_pretty(&biome_id_legend(biome_map, biomes)).map_err(|e| e.to_string())?;
+    std::fs::write(&legend_path, text).map_err(|e| format!("写入 {} 失败: {e}", legend_path.display()))
+}
+
+/// 读取环境 ID 图与同名图例，按 key 映射为当前环境定义的 ID
+pub fn import_biome_ids(path: &Path, biomes: &[BiomeDefinition]) -> Result<BiomeMap, String> {
+    let legend_path = legend_path(path);
+    let text = std::fs::read_to_string(&legend_path)
+        .map_err(|e| format!("读取图例 {} 失败: {e}", legend_path.display()))?;
+    let legend: BiomeIdLegend = serde_json::from_str(&text).map_err(|e| format!("解析图例失败: {e}"))?;
+    if legend.format != LEGEND_FORMAT {
+        return Err(format!("图例格式 {} 不是 {LEGEND_FORMAT}", legend.format));
+    }
+
+    // 像素值 → 当前环境 ID
+    let mut mapping: HashMap<u16, BiomeId> = HashMap::new();
+    for entry in &legend.biomes {
+        let id = if entry.key == UNASSIGNED_KEY {
+            BIOME_UNASSIGNED
+        } else {
+            biomes.iter()
+                .find(|b| b.key == entry.key)
+                .map(|b| b.id)
+                .ok_or_else(|| format!("图例中的环境 {} 未定义", entry.key))?
+        };
+        mapping.insert(entry.id, id);
+    }
+
+    let image = image::open(path)
+        .map_err(|e| format!("读取 PNG 失败: {e}"))?
+        .into_luma16();
+    if image.dimensions() != (legend.width, legend.height) {
+        return Err(format!(
+            "图像尺寸 {}×{} 与图例记录的 {}×{} 不符",
+            image.width(), image.height(), legend.width, legend.height,
+        ));
+    }
+
+    let mut biome_map = BiomeMap::new_filled(legend.width, legend.height, BIOME_UNASSIGNED);
+    for (dst, &value) in biome_map.data_mut().iter_mut().zip(image.as_raw()) {
+        *dst = *mapping.get(&value).ok_or_else(|| format!("像素值 {value} 不在图例中"))?;
+    }
+    Ok(biome_map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{export_biome_ids, import_biome_ids, legend_path};
+    use crate::core::biome::{BiomeMap, BIOME_UNASSIGNED};
+    use crate::generation::headless::HeadlessContext;
+
+    #[test]
+    fn biome_ids_round_trip_losslessly() {
+        let context = HeadlessContext::load("small", None).unwrap();
+        let mut biome_map = BiomeMap::new_filled(64, 48, BIOME_UNASSIGNED);
+        for (i, tile) in biome_map.data_mut().iter_mut().enumerate() {
+            // 留一部分未分配格
+            if i % 7 != 0 {
+                *tile = context.biomes[i % context.biomes.len()].id;
+            }
+        }
+
+        let path = std::env::temp_dir().join(format!("lwd_biome_ids_{}.png", std::process::id()));
+        export_biome_ids(&biome_map, &context.biomes, &path).unwrap();
+        let restored = import_biome_ids(&path, &context.biomes);
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(legend_path(&path));
+
+        let restored = restored.unwrap();
+        assert_eq!((restored.width, restored.height), (biome_map.width, biome_map.height));
+        assert_eq!(restored.data(), biome_map.data(), "导入后的环境地图应与导出前逐格一致");
+    }
+}
@@ -0,0 +1,100 @@
+//! # 分环境图层导出
+//!
+//! 供图像编辑器叠图层合成：每个环境一张与世界同尺寸的 PNG，只有该环境的格子着色，
+//! 其余全透明，文件名为环境 key（如 `jungle.png`）。
+//!
+//! 颜色与图案和其他导出一致（`overlay_color` + 导出设置中的 [`BiomePattern`]），不跟随画布上的
+//! 地图色开关；着色格子一律不透明——叠放时由图层不透明度控制效果。地图中没有格子的环境不输出文件。
+//! 应用内经后台导出队列执行（见 [`ExportQueue`](crate::generation::export_worker::ExportQueue)）。
+
+use std::path::Path;
+
+use image::{Rgba, RgbaImage};
+use rayon::prelude::*;
+
+use crate::core::biome::{BiomeDefinition, BiomeMap};
+use crate::rendering::pattern::BiomePattern;
+
+/// 只含 `biome` 格子的 RGBA 图像（其余透明）
+pub fn biome_layer_image(
+    biome_map: &BiomeMap,
+    biome: &BiomeDefinition,
+    pattern: BiomePattern,
+) -> RgbaImage {
+    let color = biome.overlay_color;
+    let data = biome_map.data();
+    let w = biome_map.width;
+    RgbaImage::from_fn(w, biome_map.height, |x, y| {
+        if data[(y * w + x) as usize] != biome.id {
+            return Rgba([0; 4]);
+        }
+        match pattern.shade(biome.id, color, x, y) {
+            [_, _, _, 0] => Rgba([0; 4]),
+            [r, g, b, _] => Rgba([r, g, b, 255]),
+        }
+    })
+}
+
+/// 把地图中出现的每个环境写成 `dir/<key>.png`，返回已写出的环境 key（按定义顺序）
+pub fn export_biome_layers(
+    biome_map: &BiomeMap,
+    biomes: &[BiomeDefinition],
+    pattern: BiomePattern,
+    dir: &Path,
+) -> Result<Vec<String>, String> {
+    let mut present = [false; 256];
+    for &id in biome_map.data() {
+        present[id as usize] = true;
+    }
+    let targets: Vec<&BiomeDefinition> = biomes.iter().filter(|b| present[b.id as usize]).collect();
+    if targets.is_empty() {
+        return Err("环境地图中没有已定义的环境".to_string());
+    }
+
+    targets
+        .par_iter()
+        .map(|biome| {
+            let path = dir.join(format!("{}.png", biome.key));
+            biome_layer_image(biome_map, biome, pattern)
+                .save(&path)
+                .map_err(|e| format!("保存 {} 失败: {e}", path.display()))?;
+            Ok(biome.key.clone())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{biome_layer_image, export_biome_layers};
+    use crate::core::biome::{BiomeMap, BIOME_UNASSIGNED};
+    use crate::generation::headless::HeadlessContext;
+    use crate::rendering::pattern::BiomePattern;
+
+    #[test]
+    fn layers_are_opaque_on_their_biome_and_written_only_when_present() {
+        let context = HeadlessContext::load("small", None).unwrap();
+        let (first, second) = (&context.biomes[1], &context.biomes[2]);
+        let mut biome_map = BiomeMap::new_filled(8, 4, BIOME_UNASSIGNED);
+        for (i, tile) in biome_map.data_mut().iter_mut().enumerate() {
+            if i % 8 < 3 {
+                *tile = first.id;
+            }
+        }
+
+        let layer = biome_layer_image(&biome_map, first, BiomePattern::Off);
+        let [r, g, b, _] = first.overlay_color;
+        assert_eq!(layer.dimensions(), (8, 4));
+        assert_eq!(layer.get_pixel(0, 0).0, [r, g, b, 255], "环境格子应为不透明的覆盖色");
+        assert_eq!(layer.get_pixel(5, 2).0, [0; 4], "其余格子应透明");
+        assert!(biome_layer_image(&biome_map, second, BiomePattern::Off).pixels().all(|p| p.0[3] == 0));
+
+        let dir = std::env::temp_dir().join(format!("lwd_biome_layers_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let keys = export_biome_layers(&biome_map, &context.biomes, BiomePattern::Off, &dir).unwrap();
+        assert_eq!(keys, vec![first.key.clone()], "只输出地图中出现的已定义环境");
+        let written = image::open(dir.join(format!("{}.png", first.key))).unwrap().to_rgba8();
+        assert_eq!(written, layer);
+        assert!(!dir.join(format!("{}.png", second.key)).exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -3,6 +3,7 @@ use std::collections::HashMap;
 use egui::{Color32, ColorImage};
 use rayon::prelude::*;
 
+use crate::core::biome::{BiomeDefinition, BiomeMap, BIOME_UNASSIGNED};
 use crate::core::block::BlockDefinition;
 use crate::core::world::World;
 
@@ -35,9 +36,9 @@ pub fn world_to_color_image(world: &World, lut: &[Color32; 256]) -> ColorImage {
 
     let mut pixels = vec![Color32::TRANSPARENT; total];
 
-    // 按行并行：每行独立做 LUT 查表
+    // 按行并行：每行独立做 LUT 查表（宽度为 0 时 chunk 大小取 1，空图像不会进入循环）
     pixels
-        .par_chunks_mut(w)
+        .par_chunks_mut(w.max(1))
         .enumerate()
         .for_each(|(y, row_pixels)| {
             let row_start = y * w;
@@ -61,7 +62,7 @@ pub fn world_to_color_image_downsampled(
     lut: &[Color32; 256],
     factor: u32,
 ) -> ColorImage {
-    if factor <= 1 {
+    if factor <= 1 || world.width == 0 || world.height == 0 {
         return world_to_color_image(world, lut);
     }
     let f = factor as usize;
@@ -93,6 +94,45 @@ pub fn world_to_color_image_downsampled(
     }
 }
 
+/// 小地图的环境来源：按 `factor` 降采样环境地图，已分配的格取环境覆盖色的 RGB（不透明），
+/// 未分配的格回退到方块颜色。取样位置与 [`world_to_color_image_downsampled`] 一致。
+/// `terraria_colors` 为真时改用游戏内地图色（见 [`BiomeDefinition::display_color`]）。
+pub fn biome_minimap_image(
+    world: &World,
+    lut: &[Color32; 256],
+    biome_map: &BiomeMap,
+    biomes: &[BiomeDefinition],
+    terraria_colors: bool,
+    factor: u32,
+) -> ColorImage {
+    let mut image = world_to_color_image_downsampled(world, lut, factor);
+    let mut biome_lut = [None; 256];
+    for bdef in biomes {
+        let [r, g, b, _] = bdef.display_color(terraria_colors);
+        biome_lut[bdef.id as usize] = Some(Color32::from_rgb(r, g, b));
+    }
+
+    let f = factor.max(1);
+    let out_w = image.size[0];
+    image
+        .pixels
+        .par_chunks_mut(out_w.max(1))
+        .enumerate()
+        .for_each(|(out_y, row_pixels)| {
+            let src_y = out_y as u32 * f;
+            for (out_x, pixel) in row_pixels.iter_mut().enumerate() {
+                let biome = biome_map.get(out_x as u32 * f, src_y);
+                if biome == BIOME_UNASSIGNED {
+                    continue;
+                }
+                if let Some(color) = biome_lut[biome as usize] {
+                    *pixel = color;
+                }
+            }
+        });
+    image
+}
+
 /// 仅更新指定行范围 [y_start, y_end) 的颜色图像区域。
 ///
 /// 返回 (y_start, 行像素数据) 用于局部纹理更新。
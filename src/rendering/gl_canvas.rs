@@ -6,6 +6,18 @@
 //! This eliminates the per-frame CPU cost of hundreds of `painter.rect_filled()`
 //! calls (checkerboard) and large-texture `painter.image()` passes that go
 //! through egui's Shape → tessellation → vertex upload path.
+//!
+//! Textures larger than `GL_MAX_TEXTURE_SIZE` (or the configured limit) are
+//! split into tiles; the world pass and the blended biome pass then draw the
+//! quad once per tile, each fragment sampling only the tile that contains it.
+//!
+//! Optional layer bands (one flat colour per depth layer) are blended between
+//! the world and the biome overlay, so biomes stay readable on top of them.
+//!
+//! Unassigned cells can be highlighted with an animated crosshatch or a pulsing
+//! fill, driven by a time uniform. Assignment comes from a separate one-channel
+//! mask texture uploaded alongside the overlay, so assigned biomes drawn fully
+//! transparent (opacity 0) are not mistaken for unassigned cells.
 
 use std::sync::{Arc, Mutex};
 
@@ -34,40 +46,86 @@ out vec4 frag_color;
 uniform vec2 u_viewport_size;     // viewport in physical pixels
 uniform vec4 u_world_rect;        // [left, top, right, bottom] normalised [0,1]
 uniform float u_checker_tile;     // checkerboard tile in physical pixels
-uniform float u_has_world;        // 1.0 = world texture ready
-uniform float u_has_biome;        // 1.0 = biome overlay ready
-uniform sampler2D u_world_tex;    // texture unit 0
-uniform sampler2D u_biome_tex;    // texture unit 1
+uniform vec3 u_checker_a;         // checkerboard colour A (sRGB byte / 255, written as-is)
+uniform vec3 u_checker_b;         // checkerboard colour B
+uniform vec4 u_tile_rect;         // tile [left, top, right, bottom] within the world image
+uniform float u_has_tile;         // 1.0 = a tile texture is bound
+uniform float u_pass;             // 0 = checkerboard + world tile, 1 = biome tile, 2 = layer band (GL-blended)
+uniform float u_draw_background;  // 1.0 = also paint the checkerboard outside the tile
+uniform sampler2D u_tex;          // tile texture (unit 0)
+uniform sampler2D u_mask;         // biome assignment mask tile (unit 1, pass 1)
+uniform vec4 u_band_color;        // layer band colour (pass 2)
+uniform float u_biome_visible;    // 0.0 = hide assigned biome texels (highlight only)
+uniform float u_unassigned_mode;  // 0 = off, 1 = crosshatch, 2 = pulse
+uniform vec3 u_unassigned_color;  // highlight colour for unassigned texels
+uniform float u_time;             // seconds, drives the highlight animation
+uniform float u_hatch_spacing;    // crosshatch period in physical pixels
 
 void main() {
+    vec2 span = u_world_rect.zw - u_world_rect.xy;
+    vec2 wuv = (v_uv - u_world_rect.xy) / max(span, vec2(0.0001));
+    vec2 tile_span = u_tile_rect.zw - u_tile_rect.xy;
+    vec2 tuv = (wuv - u_tile_rect.xy) / max(tile_span, vec2(0.0001));
+    bool in_tile = u_has_tile > 0.5
+        && all(greaterThanEqual(tuv, vec2(0.0)))
+        && all(lessThanEqual(tuv, vec2(1.0)));
+
+    // ── Layer band: flat colour over its rows (alpha blend done by GL) ──
+    if (u_pass > 1.5) {
+        if (!in_tile) {
+            discard;
+        }
+        frag_color = u_band_color;
+        return;
+    }
+
+    // ── Biome overlay tile (alpha blend done by GL) ──
+    if (u_pass > 0.5) {
+        if (!in_tile) {
+            discard;
+        }
+        vec4 biome = texture(u_tex, tuv);
+        if (texture(u_mask, tuv).r > 0.5) {
+            frag_color = vec4(biome.rgb, biome.a * u_biome_visible);
+            return;
+        }
+        if (u_unassigned_mode < 0.5) {
+            discard;
+        }
+        vec2 hpx = v_uv * u_viewport_size;
+        float pulse = 0.5 + 0.5 * sin(u_time * 5.0);
+        if (u_unassigned_mode < 1.5) {
+            // Two diagonal line sets drifting with time.
+            float shift = u_time * u_hatch_spacing * 0.5;
+            float d1 = mod(hpx.x + hpx.y + shift, u_hatch_spacing);
+            float d2 = mod(hpx.x - hpx.y + shift, u_hatch_spacing);
+            if (min(d1, d2) > u_hatch_spacing * 0.25) {
+                discard;
+            }
+            frag_color = vec4(u_unassigned_color, 0.55 + 0.35 * pulse);
+        } else {
+            frag_color = vec4(u_unassigned_color, 0.15 + 0.5 * pulse);
+        }
+        return;
+    }
+
+    if (!in_tile && u_draw_background < 0.5) {
+        discard;
+    }
+
     // ── Checkerboard background ──
     vec2 px = v_uv * u_viewport_size;
     float checker = mod(
         floor(px.x / u_checker_tile) + floor(px.y / u_checker_tile),
         2.0
     );
-    // gray(28) = 0.10980, gray(35) = 0.13725
-    vec3 bg = mix(vec3(0.10980), vec3(0.13725), checker);
+    vec3 bg = mix(u_checker_a, u_checker_b, checker);
     frag_color = vec4(bg, 1.0);
 
-    // ── World texture (alpha-blend over checkerboard) ──
-    if (u_has_world > 0.5) {
-        vec2 span = u_world_rect.zw - u_world_rect.xy;
-        vec2 wuv = (v_uv - u_world_rect.xy) / max(span, vec2(0.0001));
-        if (wuv.x >= 0.0 && wuv.x <= 1.0 && wuv.y >= 0.0 && wuv.y <= 1.0) {
-            vec4 world = texture(u_world_tex, wuv);
-            frag_color = vec4(mix(frag_color.rgb, world.rgb, world.a), 1.0);
-        }
-    }
-
-    // ── Biome overlay (alpha blend) ──
-    if (u_has_biome > 0.5) {
-        vec2 span = u_world_rect.zw - u_world_rect.xy;
-        vec2 wuv = (v_uv - u_world_rect.xy) / max(span, vec2(0.0001));
-        if (wuv.x >= 0.0 && wuv.x <= 1.0 && wuv.y >= 0.0 && wuv.y <= 1.0) {
-            vec4 ov = texture(u_biome_tex, wuv);
-            frag_color = vec4(mix(frag_color.rgb, ov.rgb, ov.a), 1.0);
-        }
+    // ── World tile (alpha-blend over checkerboard) ──
+    if (in_tile) {
+        vec4 world = texture(u_tex, tuv);
+        frag_color = vec4(mix(bg, world.rgb, world.a), 1.0);
     }
 }
 "#;
@@ -78,20 +136,83 @@ struct GlResources {
     program: glow::Program,
     vao: glow::VertexArray,
     vbo: glow::Buffer,
-    world_tex: glow::Texture,
-    biome_tex: glow::Texture,
+    world_tex: TiledTexture,
+    biome_tex: TiledTexture,
+    /// Biome assignment mask, same layout as `biome_tex`.
+    biome_mask_tex: TiledTexture,
+    /// `GL_MAX_TEXTURE_SIZE` queried at init.
+    max_texture_size: u32,
     // uniform locations
     loc_viewport_size: Option<glow::UniformLocation>,
     loc_world_rect: Option<glow::UniformLocation>,
     loc_checker_tile: Option<glow::UniformLocation>,
-    loc_has_world: Option<glow::UniformLocation>,
-    loc_has_biome: Option<glow::UniformLocation>,
-    loc_world_tex: Option<glow::UniformLocation>,
-    loc_biome_tex: Option<glow::UniformLocation>,
+    loc_checker_a: Option<glow::UniformLocation>,
+    loc_checker_b: Option<glow::UniformLocation>,
+    loc_tile_rect: Option<glow::UniformLocation>,
+    loc_has_tile: Option<glow::UniformLocation>,
+    loc_pass: Option<glow::UniformLocation>,
+    loc_draw_background: Option<glow::UniformLocation>,
+    loc_tex: Option<glow::UniformLocation>,
+    loc_mask: Option<glow::UniformLocation>,
+    loc_band_color: Option<glow::UniformLocation>,
+    loc_biome_visible: Option<glow::UniformLocation>,
+    loc_unassigned_mode: Option<glow::UniformLocation>,
+    loc_unassigned_color: Option<glow::UniformLocation>,
+    loc_time: Option<glow::UniformLocation>,
+    loc_hatch_spacing: Option<glow::UniformLocation>,
+}
+
+/// One GL texture holding the texel rect `[x, y, w, h]` of a larger image.
+struct TextureTile {
+    tex: glow::Texture,
+    rect: [u32; 4],
+}
+
+/// Texel layout of a [`TiledTexture`].
+#[derive(Clone, Copy)]
+enum TexelFormat {
+    Rgba,
+    /// One byte per texel (`R8`), used for the biome assignment mask.
+    Red,
+}
+
+impl TexelFormat {
+    fn bytes(self) -> usize {
+        match self {
+            TexelFormat::Rgba => 4,
+            TexelFormat::Red => 1,
+        }
+    }
+
+    /// `(internal format, pixel format)` for `tex_image_2d`.
+    fn gl_formats(self) -> (i32, u32) {
+        match self {
+            TexelFormat::Rgba => (glow::RGBA as i32, glow::RGBA),
+            TexelFormat::Red => (glow::R8 as i32, glow::RED),
+        }
+    }
+}
+
+/// An image uploaded as one or more tiles no larger than the size limit.
+struct TiledTexture {
+    tiles: Vec<TextureTile>,
+    width: u32,
+    height: u32,
+    format: TexelFormat,
 }
 
 struct PendingTexture {
-    rgba: Vec<u8>,
+    pixels: Vec<u8>,
+    width: u32,
+    height: u32,
+}
+
+/// A partial texture update applied with `tex_sub_image_2d`.
+struct SubUpload {
+    pixels: Vec<u8>,
+    /// Texel offset inside the full texture.
+    x: u32,
+    y: u32,
     width: u32,
     height: u32,
 }
@@ -106,6 +227,8 @@ pub struct GlCanvasState {
     resources: Option<GlResources>,
     world_data: Option<PendingTexture>,
     biome_data: Option<PendingTexture>,
+    /// Assignment mask for `biome_data` (255 = assigned), one byte per texel.
+    biome_mask: Option<PendingTexture>,
     world_dirty: bool,
     biome_dirty: bool,
     biome_valid: bool,
@@ -119,10 +242,26 @@ pub struct GlCanvasState {
     lod: u32,
     /// Current LOD level for the biome texture.
     biome_lod: u32,
+    /// World-space bbox `[x, y, w, h]` of biome cells changed since the
+    /// overlay was built; patched in place instead of rebuilding the region.
+    biome_changed: Option<[u32; 4]>,
+    /// Pending partial biome uploads `(overlay, mask)`, applied in order after any full upload.
+    biome_sub_uploads: Vec<(SubUpload, SubUpload)>,
     /// True when the underlying world tile data changed (generation step).
     /// Forces re-render of the current viewport region.
     world_data_version: u64,
     last_rendered_version: u64,
+    /// Configured maximum texture edge in texels (0 = GPU limit only).
+    texture_size_limit: u32,
+    /// `GL_MAX_TEXTURE_SIZE`, known after the first paint.
+    gpu_max_texture_size: Option<u32>,
+    /// Number of tiles the world texture is currently split into.
+    world_tile_count: usize,
+    /// Checkerboard tile size in logical points.
+    checker_tile: f32,
+    /// Checkerboard colours as normalised RGB.
+    checker_a: [f32; 3],
+    checker_b: [f32; 3],
 }
 
 impl GlCanvasState {
@@ -131,6 +270,7 @@ impl GlCanvasState {
             resources: None,
             world_data: None,
             biome_data: None,
+            biome_mask: None,
             world_dirty: false,
             biome_dirty: false,
             biome_valid: false,
@@ -140,9 +280,57 @@ impl GlCanvasState {
             biome_region: None,
             lod: 1,
             biome_lod: 1,
+            biome_changed: None,
+            biome_sub_uploads: Vec::new(),
             world_data_version: 0,
             last_rendered_version: 0,
+            texture_size_limit: 0,
+            gpu_max_texture_size: None,
+            world_tile_count: 0,
+            checker_tile: 48.0,
+            checker_a: rgb_to_f32([28, 28, 28]),
+            checker_b: rgb_to_f32([35, 35, 35]),
+        }
+    }
+
+    /// Configure the checkerboard drawn behind the world (tile size in
+    /// logical points). Equal colours give a solid background.
+    pub fn set_checker_style(&mut self, tile: f32, a: [u8; 3], b: [u8; 3]) {
+        self.checker_tile = tile.max(1.0);
+        self.checker_a = rgb_to_f32(a);
+        self.checker_b = rgb_to_f32(b);
+    }
+
+    /// Cap texture edges at `limit` texels (0 = use `GL_MAX_TEXTURE_SIZE`).
+    /// Larger world / biome textures are uploaded as tiles.
+    pub fn set_texture_size_limit(&mut self, limit: u32) {
+        if self.texture_size_limit == limit {
+            return;
         }
+        self.texture_size_limit = limit;
+        // Re-upload with the new tile layout.
+        self.world_dirty = self.world_data.is_some();
+        self.biome_dirty = self.biome_data.is_some();
+    }
+
+    /// `GL_MAX_TEXTURE_SIZE` reported by the driver (`None` before the first paint).
+    pub fn gpu_max_texture_size(&self) -> Option<u32> {
+        self.gpu_max_texture_size
+    }
+
+    /// Number of tiles the world texture is currently split into.
+    pub fn world_tile_count(&self) -> usize {
+        self.world_tile_count
+    }
+
+    /// Effective tile edge: the configured limit clamped to the GPU maximum.
+    fn tile_size(&self) -> u32 {
+        let gpu = self.gpu_max_texture_size.unwrap_or(u32::MAX);
+        match self.texture_size_limit {
+            0 => gpu,
+            limit => limit.min(gpu),
+        }
+        .max(1)
     }
 
     /// Store pixels for a world sub-region at given LOD.
@@ -155,7 +343,7 @@ impl GlCanvasState {
         lod: u32,
     ) {
         self.world_data = Some(PendingTexture {
-            rgba,
+            pixels: rgba,
             width: tex_w,
             height: tex_h,
         });
@@ -165,22 +353,27 @@ impl GlCanvasState {
         self.lod = lod;
         self.last_rendered_version = self.world_data_version;
         // World region changed → biome overlay for this region is stale
-        self.biome_valid = false;
-        self.has_biome = false;
-        self.biome_region = None;
+        self.invalidate_biome();
     }
 
-    /// Store biome overlay pixels for a sub-region at given LOD.
+    /// Store biome overlay pixels and the matching assignment mask (one byte
+    /// per texel, non-zero = assigned) for a sub-region at given LOD.
     pub fn set_biome_region_pixels(
         &mut self,
         rgba: Vec<u8>,
+        mask: Vec<u8>,
         tex_w: u32,
         tex_h: u32,
         region: [u32; 4],
         lod: u32,
     ) {
         self.biome_data = Some(PendingTexture {
-            rgba,
+            pixels: rgba,
+            width: tex_w,
+            height: tex_h,
+        });
+        self.biome_mask = Some(PendingTexture {
+            pixels: mask,
             width: tex_w,
             height: tex_h,
         });
@@ -189,6 +382,66 @@ impl GlCanvasState {
         self.has_biome = true;
         self.biome_region = Some(region);
         self.biome_lod = lod;
+        self.biome_changed = None;
+        self.biome_sub_uploads.clear();
+    }
+
+    /// Record that biome cells inside world rect `[x, y, w, h]` changed.
+    /// While the overlay is otherwise valid only this area is re-uploaded
+    /// (see [`Self::take_biome_patch`]); otherwise a full rebuild is pending anyway.
+    pub fn mark_biome_changed(&mut self, rect: [u32; 4]) {
+        if !self.biome_valid || rect[2] == 0 || rect[3] == 0 {
+            return;
+        }
+        self.biome_changed = Some(match self.biome_changed {
+            Some(prev) => union_rect(prev, rect),
+            None => rect,
+        });
+    }
+
+    /// Take the pending biome change as a world rect clipped to the buffered
+    /// biome region and snapped to its LOD texel grid, so that rendering it at
+    /// the current LOD yields exactly the texels to replace.
+    pub fn take_biome_patch(&mut self) -> Option<[u32; 4]> {
+        let [cx, cy, cw, ch] = self.biome_changed.take()?;
+        let [rx, ry, rw, rh] = self.biome_region?;
+        let x0 = cx.max(rx);
+        let y0 = cy.max(ry);
+        let x1 = (cx + cw).min(rx + rw);
+        let y1 = (cy + ch).min(ry + rh);
+        if x0 >= x1 || y0 >= y1 {
+            return None;
+        }
+        let f = self.biome_lod.max(1);
+        let sx = rx + (x0 - rx) / f * f;
+        let sy = ry + (y0 - ry) / f * f;
+        let ex = (rx + (x1 - rx).div_ceil(f) * f).min(rx + rw);
+        let ey = (ry + (y1 - ry).div_ceil(f) * f).min(ry + rh);
+        Some([sx, sy, ex - sx, ey - sy])
+    }
+
+    /// Store biome overlay pixels and mask for a rect returned by [`Self::take_biome_patch`].
+    pub fn set_biome_patch_pixels(&mut self, rgba: Vec<u8>, mask: Vec<u8>, tex_w: u32, tex_h: u32, rect: [u32; 4]) {
+        let Some([rx, ry, _, _]) = self.biome_region else {
+            return;
+        };
+        let f = self.biome_lod.max(1);
+        let x = (rect[0] - rx) / f;
+        let y = (rect[1] - ry) / f;
+        // Keep the CPU copies in sync with what the GPU will hold.
+        for (full, patch, bytes) in [(&mut self.biome_data, &rgba, 4), (&mut self.biome_mask, &mask, 1)] {
+            let Some(full) = full else { continue };
+            let row_bytes = tex_w as usize * bytes;
+            for row in 0..tex_h.min(full.height.saturating_sub(y)) as usize {
+                let dst = ((y as usize + row) * full.width as usize + x as usize) * bytes;
+                let src = row * row_bytes;
+                full.pixels[dst..dst + row_bytes].copy_from_slice(&patch[src..src + row_bytes]);
+            }
+        }
+        self.biome_sub_uploads.push((
+            SubUpload { pixels: rgba, x, y, width: tex_w, height: tex_h },
+            SubUpload { pixels: mask, x, y, width: tex_w, height: tex_h },
+        ));
     }
 
     /// Check whether the currently buffered region fully covers `visible`
@@ -247,23 +500,25 @@ impl GlCanvasState {
     }
 
     /// Explicitly invalidate biome overlay (e.g. when overlay toggle changes).
-    #[allow(dead_code)]
     pub fn invalidate_biome(&mut self) {
         self.biome_valid = false;
         self.has_biome = false;
         self.biome_region = None;
+        self.biome_changed = None;
+        self.biome_sub_uploads.clear();
     }
 
     /// Release GL resources.
     #[allow(dead_code)]
     pub fn destroy(&mut self, gl: &glow::Context) {
-        if let Some(res) = self.resources.take() {
+        if let Some(mut res) = self.resources.take() {
             unsafe {
                 gl.delete_program(res.program);
                 gl.delete_vertex_array(res.vao);
                 gl.delete_buffer(res.vbo);
-                gl.delete_texture(res.world_tex);
-                gl.delete_texture(res.biome_tex);
+                res.world_tex.destroy(gl);
+                res.biome_tex.destroy(gl);
+                res.biome_mask_tex.destroy(gl);
             }
         }
     }
@@ -331,8 +586,10 @@ fn init_resources(gl: &glow::Context) -> GlResources {
         gl.bind_buffer(glow::ARRAY_BUFFER, None);
 
         // ── placeholder textures ──
-        let world_tex = create_empty_texture(gl);
-        let biome_tex = create_empty_texture(gl);
+        let world_tex = TiledTexture::placeholder(gl, TexelFormat::Rgba);
+        let biome_tex = TiledTexture::placeholder(gl, TexelFormat::Rgba);
+        let biome_mask_tex = TiledTexture::placeholder(gl, TexelFormat::Red);
+        let max_texture_size = gl.get_parameter_i32(glow::MAX_TEXTURE_SIZE).max(1) as u32;
 
         // ── uniform locations ──
         let loc = |name: &str| gl.get_uniform_location(program, name);
@@ -343,18 +600,30 @@ fn init_resources(gl: &glow::Context) -> GlResources {
             vbo,
             world_tex,
             biome_tex,
+            biome_mask_tex,
+            max_texture_size,
             loc_viewport_size: loc("u_viewport_size"),
             loc_world_rect: loc("u_world_rect"),
             loc_checker_tile: loc("u_checker_tile"),
-            loc_has_world: loc("u_has_world"),
-            loc_has_biome: loc("u_has_biome"),
-            loc_world_tex: loc("u_world_tex"),
-            loc_biome_tex: loc("u_biome_tex"),
+            loc_checker_a: loc("u_checker_a"),
+            loc_checker_b: loc("u_checker_b"),
+            loc_tile_rect: loc("u_tile_rect"),
+            loc_has_tile: loc("u_has_tile"),
+            loc_pass: loc("u_pass"),
+            loc_draw_background: loc("u_draw_background"),
+            loc_tex: loc("u_tex"),
+            loc_mask: loc("u_mask"),
+            loc_band_color: loc("u_band_color"),
+            loc_biome_visible: loc("u_biome_visible"),
+            loc_unassigned_mode: loc("u_unassigned_mode"),
+            loc_unassigned_color: loc("u_unassigned_color"),
+            loc_time: loc("u_time"),
+            loc_hatch_spacing: loc("u_hatch_spacing"),
         }
     }
 }
 
-fn create_empty_texture(gl: &glow::Context) -> glow::Texture {
+fn create_empty_texture(gl: &glow::Context, format: TexelFormat) -> glow::Texture {
     unsafe {
         let tex = gl.create_texture().expect("GL: 创建纹理失败");
         gl.bind_texture(glow::TEXTURE_2D, Some(tex));
@@ -362,42 +631,219 @@ fn create_empty_texture(gl: &glow::Context) -> glow::Texture {
         gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_MAG_FILTER, glow::NEAREST as i32);
         gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_WRAP_S, glow::CLAMP_TO_EDGE as i32);
         gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_WRAP_T, glow::CLAMP_TO_EDGE as i32);
+        let (internal, pixel_format) = format.gl_formats();
+        gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 1);
         gl.tex_image_2d(
             glow::TEXTURE_2D,
             0,
-            glow::RGBA as i32,
+            internal,
             1,
             1,
             0,
-            glow::RGBA,
+            pixel_format,
             glow::UNSIGNED_BYTE,
-            Some(&[0u8; 4]),
+            Some(&[0u8; 4][..format.bytes()]),
         );
+        gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 4);
         gl.bind_texture(glow::TEXTURE_2D, None);
         tex
     }
 }
 
-fn upload_texture(gl: &glow::Context, tex: glow::Texture, data: &PendingTexture) {
-    unsafe {
-        gl.bind_texture(glow::TEXTURE_2D, Some(tex));
-        gl.tex_image_2d(
-            glow::TEXTURE_2D,
-            0,
-            glow::RGBA as i32,
-            data.width as i32,
-            data.height as i32,
-            0,
-            glow::RGBA,
-            glow::UNSIGNED_BYTE,
-            Some(&data.rgba),
-        );
-        gl.bind_texture(glow::TEXTURE_2D, None);
+/// Split a `width × height` image into `[x, y, w, h]` tiles of at most `max` texels per edge.
+fn tile_layout(width: u32, height: u32, max: u32) -> Vec<[u32; 4]> {
+    let max = max.max(1);
+    let mut rects = Vec::new();
+    for y in (0..height).step_by(max as usize) {
+        for x in (0..width).step_by(max as usize) {
+            rects.push([x, y, max.min(width - x), max.min(height - y)]);
+        }
+    }
+    rects
+}
+
+/// Copy the texel rect `[x, y, w, h]` out of a packed image `stride` texels
+/// wide with `bytes` bytes per texel.
+fn copy_rect(pixels: &[u8], stride: u32, bytes: usize, [x, y, w, h]: [u32; 4]) -> Vec<u8> {
+    let row_bytes = w as usize * bytes;
+    let mut out = Vec::with_capacity(row_bytes * h as usize);
+    for row in y..y + h {
+        let start = (row as usize * stride as usize + x as usize) * bytes;
+        out.extend_from_slice(&pixels[start..start + row_bytes]);
+    }
+    out
+}
+
+impl TiledTexture {
+    /// A single 1×1 transparent tile, used until real data arrives.
+    fn placeholder(gl: &glow::Context, format: TexelFormat) -> Self {
+        Self {
+            tiles: vec![TextureTile { tex: create_empty_texture(gl, format), rect: [0, 0, 1, 1] }],
+            width: 1,
+            height: 1,
+            format,
+        }
+    }
+
+    fn destroy(&mut self, gl: &glow::Context) {
+        for tile in self.tiles.drain(..) {
+            unsafe { gl.delete_texture(tile.tex) };
+        }
+    }
+
+    /// Upload a full image, re-tiling when its size or the tile size changed.
+    fn upload(&mut self, gl: &glow::Context, data: &PendingTexture, max: u32) {
+        let layout = tile_layout(data.width, data.height, max);
+        if !self.tiles.iter().map(|t| t.rect).eq(layout.iter().copied()) {
+            self.destroy(gl);
+            self.tiles = layout
+                .into_iter()
+                .map(|rect| TextureTile { tex: create_empty_texture(gl, self.format), rect })
+                .collect();
+        }
+        self.width = data.width;
+        self.height = data.height;
+
+        let (internal, pixel_format) = self.format.gl_formats();
+        let single = self.tiles.len() == 1;
+        unsafe { gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 1) };
+        for tile in &self.tiles {
+            let [_, _, w, h] = tile.rect;
+            let copied;
+            let pixels: &[u8] = if single {
+                &data.pixels
+            } else {
+                copied = copy_rect(&data.pixels, data.width, self.format.bytes(), tile.rect);
+                &copied
+            };
+            unsafe {
+                gl.bind_texture(glow::TEXTURE_2D, Some(tile.tex));
+                gl.tex_image_2d(
+                    glow::TEXTURE_2D,
+                    0,
+                    internal,
+                    w as i32,
+                    h as i32,
+                    0,
+                    pixel_format,
+                    glow::UNSIGNED_BYTE,
+                    Some(pixels),
+                );
+            }
+        }
+        unsafe {
+            gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 4);
+            gl.bind_texture(glow::TEXTURE_2D, None);
+        }
     }
+
+    /// Apply a partial update to every tile it overlaps.
+    fn upload_sub(&self, gl: &glow::Context, data: &SubUpload) {
+        for tile in &self.tiles {
+            let [tx, ty, tw, th] = tile.rect;
+            let x0 = data.x.max(tx);
+            let y0 = data.y.max(ty);
+            let x1 = (data.x + data.width).min(tx + tw);
+            let y1 = (data.y + data.height).min(ty + th);
+            if x0 >= x1 || y0 >= y1 {
+                continue;
+            }
+            let bytes = self.format.bytes();
+            let pixels = copy_rect(&data.pixels, data.width, bytes, [x0 - data.x, y0 - data.y, x1 - x0, y1 - y0]);
+            unsafe {
+                gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 1);
+                gl.bind_texture(glow::TEXTURE_2D, Some(tile.tex));
+                gl.tex_sub_image_2d(
+                    glow::TEXTURE_2D,
+                    0,
+                    (x0 - tx) as i32,
+                    (y0 - ty) as i32,
+                    (x1 - x0) as i32,
+                    (y1 - y0) as i32,
+                    self.format.gl_formats().1,
+                    glow::UNSIGNED_BYTE,
+                    glow::PixelUnpackData::Slice(&pixels),
+                );
+            }
+        }
+        unsafe {
+            gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 4);
+            gl.bind_texture(glow::TEXTURE_2D, None);
+        }
+    }
+
+    /// Tiles with their normalised `[left, top, right, bottom]` within the image.
+    fn normalised_tiles(&self) -> Vec<(glow::Texture, [f32; 4])> {
+        let (w, h) = (self.width.max(1) as f32, self.height.max(1) as f32);
+        self.tiles
+            .iter()
+            .map(|t| {
+                let [x, y, tw, th] = t.rect;
+                (t.tex, [x as f32 / w, y as f32 / h, (x + tw) as f32 / w, (y + th) as f32 / h])
+            })
+            .collect()
+    }
+}
+
+/// Bounding rect `[x, y, w, h]` covering both inputs.
+fn union_rect(a: [u32; 4], b: [u32; 4]) -> [u32; 4] {
+    let x0 = a[0].min(b[0]);
+    let y0 = a[1].min(b[1]);
+    let x1 = (a[0] + a[2]).max(b[0] + b[2]);
+    let y1 = (a[1] + a[3]).max(b[1] + b[3]);
+    [x0, y0, x1 - x0, y1 - y0]
 }
 
 // ─── Public API ─────────────────────────────────────────────────────────
 
+/// 未分配格子（分配掩码为 0 的纹素）的调试高亮样式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnassignedHighlight {
+    #[default]
+    Off,
+    /// 缓慢漂移的交叉斜线
+    Crosshatch,
+    /// 整体呼吸闪烁
+    Pulse,
+}
+
+impl UnassignedHighlight {
+    pub const ALL: [UnassignedHighlight; 3] = [
+        UnassignedHighlight::Off,
+        UnassignedHighlight::Crosshatch,
+        UnassignedHighlight::Pulse,
+    ];
+
+    /// runtime.json 中的键名
+    pub fn key(self) -> &'static str {
+        match self {
+            UnassignedHighlight::Off => "off",
+            UnassignedHighlight::Crosshatch => "crosshatch",
+            UnassignedHighlight::Pulse => "pulse",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|h| h.key() == key)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            UnassignedHighlight::Off => "关闭",
+            UnassignedHighlight::Crosshatch => "交叉斜线",
+            UnassignedHighlight::Pulse => "闪烁",
+        }
+    }
+
+    fn shader_mode(self) -> f32 {
+        match self {
+            UnassignedHighlight::Off => 0.0,
+            UnassignedHighlight::Crosshatch => 1.0,
+            UnassignedHighlight::Pulse => 2.0,
+        }
+    }
+}
+
 /// Per-frame parameters describing world-image placement within the canvas.
 pub struct GlCanvasParams {
     /// The egui rect allocated for the whole canvas area.
@@ -409,6 +855,18 @@ pub struct GlCanvasParams {
     pub has_world: bool,
     /// Whether to sample the biome overlay texture.
     pub has_biome: bool,
+    /// Normalised `[left, top, right, bottom]` of the full world image
+    /// (the world texture may only cover a buffered sub-region of it).
+    pub image_rect_norm: [f32; 4],
+    /// Layer bands as `([top, bottom] in world-height fractions, RGBA 0..1)`,
+    /// drawn under the biome overlay. Empty = off.
+    pub layer_bands: Vec<([f32; 2], [f32; 4])>,
+    /// Draw assigned biome texels; `false` leaves only the unassigned highlight.
+    pub biome_visible: bool,
+    /// Highlight style and colour for unassigned biome texels.
+    pub unassigned_highlight: (UnassignedHighlight, [u8; 3]),
+    /// Animation clock in seconds.
+    pub time: f32,
 }
 
 /// Build the [`egui::PaintCallback`] that renders the canvas via raw OpenGL.
@@ -419,43 +877,54 @@ pub fn make_canvas_callback(
     let world_rect_norm = params.world_rect_norm;
     let has_world = params.has_world;
     let has_biome = params.has_biome;
+    let image_rect_norm = params.image_rect_norm;
+    let layer_bands = params.layer_bands;
+    let biome_visible = params.biome_visible;
+    let (unassigned_mode, unassigned_color) = params.unassigned_highlight;
+    let time = params.time;
 
     let cb = egui_glow::CallbackFn::new(move |info, painter| {
         let gl = painter.gl();
-        let mut st = state.lock().unwrap();
+        let mut guard = state.lock().unwrap();
+        let st = &mut *guard;
 
         // ── lazy init ──
         if st.resources.is_none() {
-            st.resources = Some(init_resources(gl));
-        }
-        // Copy GL handles out so we can release the immutable borrow before mutating.
-        let res = st.resources.as_ref().unwrap();
-        let program = res.program;
-        let vao = res.vao;
-        let world_tex = res.world_tex;
-        let biome_tex = res.biome_tex;
-        let loc_viewport_size = res.loc_viewport_size.clone();
-        let loc_world_rect = res.loc_world_rect.clone();
-        let loc_checker_tile = res.loc_checker_tile.clone();
-        let loc_has_world = res.loc_has_world.clone();
-        let loc_has_biome = res.loc_has_biome.clone();
-        let loc_world_tex_u = res.loc_world_tex.clone();
-        let loc_biome_tex_u = res.loc_biome_tex.clone();
-        let _ = res; // release immutable borrow on `st`
+            let res = init_resources(gl);
+            st.gpu_max_texture_size = Some(res.max_texture_size);
+            st.resources = Some(res);
+        }
+        let tile_size = st.tile_size();
+        let res = st.resources.as_mut().unwrap();
 
         // ── upload dirty textures ──
         if st.world_dirty {
             if let Some(data) = &st.world_data {
-                upload_texture(gl, world_tex, data);
+                res.world_tex.upload(gl, data, tile_size);
+                st.world_tile_count = res.world_tex.tiles.len();
             }
             st.world_dirty = false;
         }
         if st.biome_dirty {
             if let Some(data) = &st.biome_data {
-                upload_texture(gl, biome_tex, data);
+                res.biome_tex.upload(gl, data, tile_size);
+            }
+            if let Some(mask) = &st.biome_mask {
+                res.biome_mask_tex.upload(gl, mask, tile_size);
             }
             st.biome_dirty = false;
+            // The full upload already contains every patch.
+            st.biome_sub_uploads.clear();
         }
+        for (patch, mask) in std::mem::take(&mut st.biome_sub_uploads) {
+            res.biome_tex.upload_sub(gl, &patch);
+            res.biome_mask_tex.upload_sub(gl, &mask);
+        }
+
+        let world_tiles = if has_world && st.has_world { res.world_tex.normalised_tiles() } else { Vec::new() };
+        let biome_tiles = if has_biome && st.has_biome { res.biome_tex.normalised_tiles() } else { Vec::new() };
+        // Same dimensions and tile size as the overlay, hence the same layout.
+        let mask_tiles = res.biome_mask_tex.normalised_tiles();
 
         // ── draw ──
         let vp = info.viewport_in_pixels();
@@ -463,58 +932,96 @@ pub fn make_canvas_callback(
             gl.disable(glow::SCISSOR_TEST);
             gl.disable(glow::BLEND);
 
-            gl.use_program(Some(program));
+            gl.use_program(Some(res.program));
 
             // viewport size (physical pixels)
             gl.uniform_2_f32(
-                loc_viewport_size.as_ref(),
+                res.loc_viewport_size.as_ref(),
                 vp.width_px as f32,
                 vp.height_px as f32,
             );
 
             // world image rect (normalised)
             gl.uniform_4_f32(
-                loc_world_rect.as_ref(),
+                res.loc_world_rect.as_ref(),
                 world_rect_norm[0],
                 world_rect_norm[1],
                 world_rect_norm[2],
                 world_rect_norm[3],
             );
 
-            // checkerboard tile (48 logical px → physical px)
+            // checkerboard tile (logical px → physical px) + colours
             gl.uniform_1_f32(
-                loc_checker_tile.as_ref(),
-                48.0 * info.pixels_per_point,
+                res.loc_checker_tile.as_ref(),
+                st.checker_tile * info.pixels_per_point,
             );
+            let [ar, ag, ab] = st.checker_a;
+            gl.uniform_3_f32(res.loc_checker_a.as_ref(), ar, ag, ab);
+            let [br, bg, bb] = st.checker_b;
+            gl.uniform_3_f32(res.loc_checker_b.as_ref(), br, bg, bb);
 
-            // feature flags
-            gl.uniform_1_f32(
-                loc_has_world.as_ref(),
-                if has_world && st.has_world { 1.0 } else { 0.0 },
-            );
-            gl.uniform_1_f32(
-                loc_has_biome.as_ref(),
-                if has_biome && st.has_biome { 1.0 } else { 0.0 },
-            );
-
-            // bind textures
             gl.active_texture(glow::TEXTURE0);
-            gl.bind_texture(glow::TEXTURE_2D, Some(world_tex));
-            gl.uniform_1_i32(loc_world_tex_u.as_ref(), 0);
+            gl.uniform_1_i32(res.loc_tex.as_ref(), 0);
+            gl.uniform_1_i32(res.loc_mask.as_ref(), 1);
+            gl.bind_vertex_array(Some(res.vao));
 
-            gl.active_texture(glow::TEXTURE1);
-            gl.bind_texture(glow::TEXTURE_2D, Some(biome_tex));
-            gl.uniform_1_i32(loc_biome_tex_u.as_ref(), 1);
+            // `rect` is the tile (or band) within the world image; `None` = no tile.
+            let draw_rect = |tex: Option<glow::Texture>, rect: Option<[f32; 4]>, pass: f32, background: bool| {
+                let [l, t, r, b] = rect.unwrap_or_default();
+                gl.uniform_4_f32(res.loc_tile_rect.as_ref(), l, t, r, b);
+                gl.uniform_1_f32(res.loc_has_tile.as_ref(), if rect.is_some() { 1.0 } else { 0.0 });
+                gl.uniform_1_f32(res.loc_pass.as_ref(), pass);
+                gl.uniform_1_f32(res.loc_draw_background.as_ref(), if background { 1.0 } else { 0.0 });
+                gl.bind_texture(glow::TEXTURE_2D, tex);
+                gl.draw_arrays(glow::TRIANGLE_STRIP, 0, 4);
+            };
+            let draw_tile = |tile: Option<(glow::Texture, [f32; 4])>, pass: f32, background: bool| {
+                let (tex, rect) = tile.unzip();
+                draw_rect(tex, rect, pass, background);
+            };
 
-            // draw fullscreen quad
-            gl.bind_vertex_array(Some(vao));
-            gl.draw_arrays(glow::TRIANGLE_STRIP, 0, 4);
-            gl.bind_vertex_array(None);
+            // checkerboard + world: the first draw also paints the background
+            // outside its tile; later tiles only touch their own fragments.
+            if world_tiles.is_empty() {
+                draw_tile(None, 0.0, true);
+            }
+            for (i, &tile) in world_tiles.iter().enumerate() {
+                draw_tile(Some(tile), 0.0, i == 0);
+            }
 
-            // unbind
+            // layer bands and biome overlay blended over the result, keeping destination alpha
+            gl.enable(glow::BLEND);
+            gl.blend_func_separate(glow::SRC_ALPHA, glow::ONE_MINUS_SRC_ALPHA, glow::ZERO, glow::ONE);
+            if !layer_bands.is_empty() {
+                // Bands span the whole world, not just the buffered region.
+                let [l, t, r, b] = image_rect_norm;
+                gl.uniform_4_f32(res.loc_world_rect.as_ref(), l, t, r, b);
+                for &([top, bottom], [r, g, b, a]) in &layer_bands {
+                    gl.uniform_4_f32(res.loc_band_color.as_ref(), r, g, b, a);
+                    draw_rect(None, Some([0.0, top, 1.0, bottom]), 2.0, false);
+                }
+                let [l, t, r, b] = world_rect_norm;
+                gl.uniform_4_f32(res.loc_world_rect.as_ref(), l, t, r, b);
+            }
+            gl.uniform_1_f32(res.loc_biome_visible.as_ref(), if biome_visible { 1.0 } else { 0.0 });
+            gl.uniform_1_f32(res.loc_unassigned_mode.as_ref(), unassigned_mode.shader_mode());
+            let [ur, ug, ub] = rgb_to_f32(unassigned_color);
+            gl.uniform_3_f32(res.loc_unassigned_color.as_ref(), ur, ug, ub);
+            gl.uniform_1_f32(res.loc_time.as_ref(), time);
+            gl.uniform_1_f32(res.loc_hatch_spacing.as_ref(), 10.0 * info.pixels_per_point);
+            for (&tile, &(mask, _)) in biome_tiles.iter().zip(&mask_tiles) {
+                gl.active_texture(glow::TEXTURE1);
+                gl.bind_texture(glow::TEXTURE_2D, Some(mask));
+                gl.active_texture(glow::TEXTURE0);
+                draw_tile(Some(tile), 1.0, false);
+            }
             gl.active_texture(glow::TEXTURE1);
             gl.bind_texture(glow::TEXTURE_2D, None);
             gl.active_texture(glow::TEXTURE0);
+            gl.disable(glow::BLEND);
+
+            // unbind
+            gl.bind_vertex_array(None);
             gl.bind_texture(glow::TEXTURE_2D, None);
             gl.use_program(None);
         }
@@ -526,6 +1033,11 @@ pub fn make_canvas_callback(
     }
 }
 
+/// sRGB bytes → 0..1 (not linearised; the shader writes them unchanged).
+fn rgb_to_f32(c: [u8; 3]) -> [f32; 3] {
+    c.map(|v| v as f32 / 255.0)
+}
+
 /// Convert a `&[Color32]` pixel buffer to packed RGBA `Vec<u8>`.
 ///
 /// This is a zero-copy reinterpret when possible; falls back to a
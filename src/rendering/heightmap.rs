@@ -0,0 +1,102 @@
+//! # 高度图导出
+//!
+//! 供 3D / 着色器工具使用的灰度高度图：逐列取最高的非空气方块作为地表，
+//! 整列没有方块时回退到地表层级的起始行。
+//!
+//! 目前的生成流程只划分环境、不写入方块，因此生成后的世界各列都会回退，
+//! 导出的是一张平坦的高度图（整幅同一灰度）；只有导入的 .wld 等带方块的世界才有起伏。
+//!
+//! 编码约定：16 位灰度 PNG，**白 = 高**，`value = 65535 × (H - 1 - row) / (H - 1)`，
+//! 其中 `row` 为地表行、`H` 为世界高度。图像宽度等于世界宽度，
+//! 高度固定为 [`STRIP_ROWS`] 行（各行相同，便于图像工具查看）。
+//! 同名 `.json` 附带约定说明与逐列地表行，方便脚本直接读取。
+
+use std::path::Path;
+
+use image::{ImageBuffer, Luma};
+use serde_json::json;
+
+use crate::core::world::{World, AIR_BLOCK_ID};
+
+/// 导出图像的行数
+pub const STRIP_ROWS: u32 = 64;
+
+/// 逐列地表行：最高的非空气方块所在行，整列为空时取 `fallback_row`
+pub fn surface_rows(world: &World, fallback_row: u32) -> Vec<u32> {
+    let w = world.width as usize;
+    (0..w)
+        .map(|x| {
+            (0..world.height)
+                .find(|&y| {
+                    let id = world.tiles[y as usize * w + x];
+                    id != AIR_BLOCK_ID && id != 0
+                })
+                .unwrap_or(fallback_row)
+        })
+        .collect()
+}
+
+/// 地表行 → 16 位高度值（白 = 高）
+fn encode_height(row: u32, world_height: u32) -> u16 {
+    let max_row = world_height.saturating_sub(1).max(1) as f64;
+    let h = (max_row - row.min(max_row as u32) as f64) / max_row;
+    (h * u16::MAX as f64).round() as u16
+}
+
+/// 由逐列地表行生成高度图图像
+pub fn heightmap_image(rows: &[u32], world_height: u32) -> ImageBuffer<Luma<u16>, Vec<u16>> {
+    let values: Vec<u16> = rows.iter().map(|&r| encode_height(r, world_height)).collect();
+    ImageBuffer::from_fn(rows.len().max(1) as u32, STRIP_ROWS, |x, _| {
+        Luma([values.get(x as usize).copied().unwrap_or(0)])
+    })
+}
+
+/// 写出高度图 PNG 与同名 `.json` 说明，返回回退到 `fallback_row` 的列数
+pub fn export_heightmap(world: &World, fallback_row: u32, path: &Path) -> Result<usize, String> {
+    if world.width == 0 || world.height == 0 {
+        return Err("世界尺寸为空".to_string());
+    }
+    let rows = surface_rows(world, fallback_row);
+    let fallback_columns = (0..world.width as usize)
+        .filter(|&x| (0..world.height as usize).all(|y| {
+            let id = world.tiles[y * world.width as usize + x];
+            id == AIR_BLOCK_ID || id == 0
+        }))
+        .count();
+    heightmap_image(&rows, world.height)
+        .save(path)
+        .map_err(|e| format!("保存 PNG 失败: {e}"))?;
+
+    let meta = json!({
+        "encoding": "white=high",
+        "bit_depth": 16,
+        "formula": "value = 65535 * (world_height - 1 - surface_row) / (world_height - 1)",
+        "world_width": world.width,
+        "world_height": world.height,
+        "image_rows": STRIP_ROWS,
+        "fallback_row": fallback_row,
+        "fallback_columns": fallback_columns,
+        "min_surface_row": rows.iter().min(),
+        "max_surface_row": rows.iter().max(),
+        "surface_rows": rows,
+    });
+    let meta_path = path.with_extension("json");
+    let text = serde_json::to_string_pretty(&meta).map_err(|e| e.to_string())?;
+    std::fs::write(&meta_path, text).map_err(|e| format!("写入 {} 失败: {e}", meta_path.display()))?;
+    Ok(fallback_columns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::encode_height;
+
+    #[test]
+    fn encode_height_maps_top_to_white_and_bottom_to_black() {
+        assert_eq!(encode_height(0, 1201), u16::MAX, "首行为最高");
+        assert_eq!(encode_height(1200, 1201), 0, "末行为最低");
+        assert_eq!(encode_height(600, 1201), 32768, "正中间约为半灰");
+        assert_eq!(encode_height(5000, 1201), 0, "越界行按末行处理");
+        assert!(encode_height(100, 1201) > encode_height(101, 1201), "行号越小越亮");
+        assert_eq!(encode_height(0, 1), u16::MAX, "单行世界不应除以 0");
+    }
+}
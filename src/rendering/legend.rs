@@ -0,0 +1,203 @@
+//! # 环境图例
+//!
+//! 为导出的环境 PNG 生成配套图例：每种出现在环境地图中的环境一行，
+//! 左侧为覆盖色色块（按图案模式叠加环境图案），右侧为名称与面积占比。文字用 ab_glyph 按传入的字体光栅化
+//! （应用内使用与界面相同的 CJK 字体），使导出图片脱离程序也能看懂。
+
+use ab_glyph::{point, Font, FontRef, PxScale, ScaleFont};
+
+use crate::core::biome::{BiomeDefinition, BiomeMap, BIOME_UNASSIGNED};
+
+use super::pattern::BiomePattern;
+
+const PADDING: u32 = 12;
+const ROW_HEIGHT: u32 = 28;
+const SWATCH: u32 = 20;
+const GAP: u32 = 10;
+const TEXT_PX: f32 = 18.0;
+const TITLE_PX: f32 = 20.0;
+const BACKGROUND: [u8; 3] = [30, 30, 40];
+const TEXT_COLOR: [u8; 3] = [235, 235, 245];
+/// 「仅图案」模式下色块的底色（让深色线条可见）
+const PATTERN_PAPER: [u8; 3] = [225, 225, 230];
+/// 层级分界线颜色与混合比例
+const LAYER_LINE_COLOR: [u8; 3] = [255, 255, 255];
+const LAYER_LINE_ALPHA: f32 = 0.5;
+/// 层级名称底板的不透明度
+const LAYER_LABEL_BACKING: f32 = 0.6;
+
+/// 生成环境图例图像（仅包含环境地图中出现过的环境，按 id 排序）。
+pub fn biome_legend(
+    biome_map: &BiomeMap,
+    biome_definitions: &[BiomeDefinition],
+    pattern: BiomePattern,
+    font_data: &[u8],
+) -> Result<image::RgbaImage, String> {
+    let font = FontRef::try_from_slice(font_data).map_err(|e| format!("字体解析失败: {e}"))?;
+
+    let histogram = biome_map.histogram();
+    let total: u64 = histogram.iter().sum();
+    let mut entries: Vec<(&BiomeDefinition, String)> = biome_definitions.iter()
+        .filter(|b| b.id != BIOME_UNASSIGNED && histogram[b.id as usize] > 0)
+        .map(|b| {
+            let percent = histogram[b.id as usize] as f64 / total.max(1) as f64 * 100.0;
+            (b, format!("{}  {percent:.1}%", b.name))
+        })
+        .collect();
+    entries.sort_by_key(|(b, _)| b.id);
+    if entries.is_empty() {
+        return Err("环境地图中没有已分配的环境".to_string());
+    }
+
+    let title = "环境图例";
+    let text_w = entries.iter()
+        .map(|(_, label)| text_width(&font, TEXT_PX, label))
+        .fold(text_width(&font, TITLE_PX, title) - SWATCH as f32 - GAP as f32, f32::max);
+    let width = PADDING * 2 + SWATCH + GAP + text_w.ceil() as u32;
+    let height = PADDING * 2 + ROW_HEIGHT * (entries.len() as u32 + 1);
+
+    let [br, bg, bb] = BACKGROUND;
+    let mut img = image::RgbaImage::from_pixel(width, height, image::Rgba([br, bg, bb, 255]));
+
+    draw_text(&mut img, &font, TITLE_PX, PADDING as f32, (PADDING + ROW_HEIGHT / 2) as f32, title);
+    for (row, (biome, label)) in entries.iter().enumerate() {
+        let top = PADDING + ROW_HEIGHT * (row as u32 + 1);
+        let sy = top + (ROW_HEIGHT - SWATCH) / 2;
+        let [r, g, b, _] = biome.overlay_color;
+        let paper = if pattern == BiomePattern::Only { PATTERN_PAPER } else { [r, g, b] };
+        for y in sy..sy + SWATCH {
+            for x in PADDING..PADDING + SWATCH {
+                let [tr, tg, tb, ta] = pattern.shade(biome.id, [r, g, b, 255], x - PADDING, y - sy);
+                let a = ta as f32 / 255.0;
+                let mix = |base: u8, top: u8| (base as f32 * (1.0 - a) + top as f32 * a).round() as u8;
+                img.put_pixel(x, y, image::Rgba([mix(paper[0], tr), mix(paper[1], tg), mix(paper[2], tb), 255]));
+            }
+        }
+        let text_x = (PADDING + SWATCH + GAP) as f32;
+        draw_text(&mut img, &font, TEXT_PX, text_x, (top + ROW_HEIGHT / 2) as f32, label);
+    }
+
+    Ok(img)
+}
+
+/// 在导出图像中世界所在的区域 `rect = [x, y, 宽, 高]` 内画层级分界线，并在每个层级左侧中部标注名称
+/// （与画布上的层级线 / 层级标签一致）。`layers` 为 `(起始行, 结束行, 名称)`，行号按 `world_height` 映射到 `rect`。
+pub fn draw_layer_annotations(
+    img: &mut image::RgbaImage,
+    rect: [u32; 4],
+    world_height: u32,
+    layers: &[(u32, u32, String)],
+    font_data: &[u8],
+) -> Result<(), String> {
+    let font = FontRef::try_from_slice(font_data).map_err(|e| format!("字体解析失败: {e}"))?;
+    let [x0, y0, w, h] = rect;
+    let x1 = (x0 + w).min(img.width());
+    let to_image_y = |row: u32| y0 + (row as u64 * h as u64 / world_height.max(1) as u64) as u32;
+
+    for &(start, _, _) in layers {
+        let y = to_image_y(start);
+        if start == 0 || y >= img.height() {
+            continue;
+        }
+        for x in x0..x1 {
+            blend_pixel(img.get_pixel_mut(x, y), LAYER_LINE_COLOR, LAYER_LINE_ALPHA);
+        }
+    }
+
+    // 字号随图像高度缩放，大图上仍清晰可读
+    let px = (h as f32 / 40.0).clamp(12.0, 48.0);
+    let pad = (px * 0.4).round() as u32;
+    for (start, end, name) in layers {
+        let center_y = (to_image_y(*start) + to_image_y(*end)) / 2;
+        let text_w = text_width(&font, px, name).ceil() as u32;
+        let (bx0, bx1) = (x0 + pad, (x0 + pad * 3 + text_w).min(x1));
+        let (by0, by1) = (center_y.saturating_sub(px as u32 / 2 + pad / 2), (center_y + px as u32 / 2 + pad / 2).min(img.height()));
+        for y in by0..by1 {
+            for x in bx0..bx1 {
+                blend_pixel(img.get_pixel_mut(x, y), BACKGROUND, LAYER_LABEL_BACKING);
+            }
+        }
+        draw_text(img, &font, px, (x0 + pad * 2) as f32, center_y as f32, name);
+    }
+    Ok(())
+}
+
+/// 一行文字的像素宽度（含字距调整）
+pub(super) fn text_width(font: &FontRef, px: f32, text: &str) -> f32 {
+    let scaled = font.as_scaled(PxScale::from(px));
+    let mut width = 0.0;
+    let mut prev = None;
+    for c in text.chars() {
+        let id = scaled.glyph_id(c);
+        if let Some(p) = prev {
+            width += scaled.kern(p, id);
+        }
+        width += scaled.h_advance(id);
+        prev = Some(id);
+    }
+    width
+}
+
+/// 把不透明度为 `alpha` 的 `color` 按 source-over 叠到未预乘的像素上；
+/// 透明底也会被盖上（alpha 向 255 靠拢），不透明底时等同于线性插值。
+pub(super) fn blend_pixel(dst: &mut image::Rgba<u8>, color: [u8; 3], alpha: f32) {
+    let a_src = alpha.clamp(0.0, 1.0);
+    let a_dst = dst.0[3] as f32 / 255.0;
+    let out_a = a_src + a_dst * (1.0 - a_src);
+    if out_a <= 0.0 {
+        return;
+    }
+    for (d, &t) in dst.0.iter_mut().zip(&color) {
+        *d = ((t as f32 * a_src + *d as f32 * a_dst * (1.0 - a_src)) / out_a).round().clamp(0.0, 255.0) as u8;
+    }
+    dst.0[3] = (out_a * 255.0).round() as u8;
+}
+
+/// 以 `(x, center_y)` 为左侧垂直居中点绘制一行文字，按覆盖率与背景混合
+pub(super) fn draw_text(img: &mut image::RgbaImage, font: &FontRef, px: f32, x: f32, center_y: f32, text: &str) {
+    let scaled = font.as_scaled(PxScale::from(px));
+    let baseline = center_y + (scaled.ascent() + scaled.descent()) / 2.0;
+    let mut caret = x;
+    let mut prev = None;
+    for c in text.chars() {
+        let id = scaled.glyph_id(c);
+        if let Some(p) = prev {
+            caret += scaled.kern(p, id);
+        }
+        let glyph = id.with_scale_and_position(scaled.scale(), point(caret, baseline));
+        caret += scaled.h_advance(id);
+        prev = Some(id);
+
+        let Some(outlined) = font.outline_glyph(glyph) else {
+            continue;
+        };
+        let bounds = outlined.px_bounds();
+        outlined.draw(|gx, gy, coverage| {
+            let px = bounds.min.x as i32 + gx as i32;
+            let py = bounds.min.y as i32 + gy as i32;
+            if px < 0 || py < 0 || px >= img.width() as i32 || py >= img.height() as i32 {
+                return;
+            }
+            blend_pixel(img.get_pixel_mut(px as u32, py as u32), TEXT_COLOR, coverage);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{draw_layer_annotations, LAYER_LINE_ALPHA, LAYER_LINE_COLOR};
+    use crate::ui::app::CJK_FONT_BYTES;
+
+    #[test]
+    fn layer_annotations_are_visible_on_transparent_images() {
+        let mut img = image::RgbaImage::new(400, 200);
+        let layers = vec![(0, 100, "地表".to_string()), (100, 200, "地下".to_string())];
+        draw_layer_annotations(&mut img, [0, 0, 400, 200], 200, &layers, CJK_FONT_BYTES).unwrap();
+
+        // 分界线右端没有标签底板，应是按线条不透明度盖上的线条色
+        let [r, g, b] = LAYER_LINE_COLOR;
+        assert_eq!(img.get_pixel(399, 100).0, [r, g, b, (LAYER_LINE_ALPHA * 255.0).round() as u8]);
+        assert!(img.get_pixel(10, 50).0[3] > 0, "标签底板应盖住透明底");
+        assert_eq!(img.get_pixel(399, 50).0, [0; 4], "线条与标签以外保持透明");
+    }
+}
@@ -1,3 +1,12 @@
+pub mod ascii;
+pub mod biome_ids;
+pub mod biome_layers;
 pub mod canvas;
 pub mod gl_canvas;
+pub mod heightmap;
+pub mod legend;
+pub mod pattern;
+pub mod scale_bar;
+pub mod summary_card;
+pub mod thumbnail;
 pub mod viewport;
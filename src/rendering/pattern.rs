@@ -0,0 +1,90 @@
+//! # 环境图案
+//!
+//! 无障碍 / 打印用的环境纹样：按环境 id 循环选取斜线、横线、圆点、网格等填充图案，
+//! 使环境不依赖 `overlay_color` 也能区分。画布覆盖层与 PNG 导出共用同一套图案。
+
+use crate::core::biome::{BiomeId, BIOME_UNASSIGNED};
+
+/// 图案周期（像素）
+const PERIOD: u32 = 8;
+/// 线条宽度（像素）
+const LINE: u32 = 2;
+/// 「仅图案」模式的线条颜色
+const INK: [u8; 4] = [0, 0, 0, 200];
+/// 「颜色 + 图案」模式下线条相对覆盖色的亮度
+const INK_SHADE: f32 = 0.4;
+
+/// 环境覆盖层的图案模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BiomePattern {
+    /// 纯覆盖色
+    #[default]
+    Off,
+    /// 覆盖色上叠加图案线条
+    WithColor,
+    /// 只画图案，不填覆盖色
+    Only,
+}
+
+impl BiomePattern {
+    pub const ALL: [BiomePattern; 3] = [BiomePattern::Off, BiomePattern::WithColor, BiomePattern::Only];
+
+    /// runtime.json 中的键名
+    pub fn key(self) -> &'static str {
+        match self {
+            BiomePattern::Off => "off",
+            BiomePattern::WithColor => "with_color",
+            BiomePattern::Only => "only",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|p| p.key() == key)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            BiomePattern::Off => "仅颜色",
+            BiomePattern::WithColor => "颜色 + 图案",
+            BiomePattern::Only => "仅图案",
+        }
+    }
+
+    /// 按模式给出 `(x, y)` 处的覆盖像素（RGBA，未混合）
+    pub fn shade(self, biome: BiomeId, color: [u8; 4], x: u32, y: u32) -> [u8; 4] {
+        match self {
+            BiomePattern::Off => color,
+            BiomePattern::WithColor if is_ink(biome, x, y) => {
+                let [r, g, b, a] = color;
+                let dark = |c: u8| (c as f32 * INK_SHADE) as u8;
+                [dark(r), dark(g), dark(b), a.max(INK[3])]
+            }
+            BiomePattern::WithColor => color,
+            BiomePattern::Only if is_ink(biome, x, y) => INK,
+            BiomePattern::Only => [0, 0, 0, 0],
+        }
+    }
+}
+
+/// `(x, y)` 是否落在该环境图案的线条上（未分配区域没有图案）
+pub fn is_ink(biome: BiomeId, x: u32, y: u32) -> bool {
+    if biome == BIOME_UNASSIGNED {
+        return false;
+    }
+    let (px, py) = (x % PERIOD, y % PERIOD);
+    let diagonal = (x + y) % PERIOD < LINE;
+    let anti_diagonal = (x + PERIOD - py) % PERIOD < LINE;
+    let dot = |ox: u32, oy: u32| (x + ox) % PERIOD < LINE && (y + oy) % PERIOD < LINE;
+    match (biome - 1) % 10 {
+        0 => diagonal,
+        1 => anti_diagonal,
+        2 => py < LINE,
+        3 => px < LINE,
+        4 => dot(0, 0),
+        5 => diagonal || anti_diagonal,
+        6 => px < LINE || py < LINE,
+        7 => (x / (PERIOD / 2) + y / (PERIOD / 2)).is_multiple_of(2),
+        8 => dot(0, 0) || dot(PERIOD / 2, PERIOD / 2),
+        _ => py < LINE && (x / PERIOD).is_multiple_of(2),
+    }
+}
@@ -0,0 +1,156 @@
+//! # 导出比例尺
+//!
+//! 在导出图像的一角画一段比例尺（横线 + 两端竖线 + 「N 格」文字），
+//! 长度按世界在图像中的实际缩放（导出分辨率 / 降采样）换算，使读者对世界大小有直观概念。
+//! 只烘焙进导出图像，画布上不显示。
+
+use ab_glyph::FontRef;
+
+use super::legend::{blend_pixel, draw_text, text_width};
+
+/// 比例尺与图像边缘的距离
+const MARGIN: u32 = 16;
+/// 底板内边距
+const PADDING: u32 = 8;
+/// 自动长度时比例尺大约占世界宽度的比例
+const AUTO_FRACTION: f64 = 0.2;
+const BACKING: [u8; 3] = [30, 30, 40];
+const BACKING_ALPHA: f32 = 0.6;
+const LINE_COLOR: [u8; 3] = [235, 235, 245];
+
+/// 比例尺所在的角
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScaleBarCorner {
+    BottomLeft,
+    BottomRight,
+    TopLeft,
+    TopRight,
+}
+
+impl ScaleBarCorner {
+    pub const ALL: [ScaleBarCorner; 4] = [
+        ScaleBarCorner::BottomLeft,
+        ScaleBarCorner::BottomRight,
+        ScaleBarCorner::TopLeft,
+        ScaleBarCorner::TopRight,
+    ];
+
+    /// 持久化用的键
+    pub fn key(self) -> &'static str {
+        match self {
+            ScaleBarCorner::BottomLeft => "bottom_left",
+            ScaleBarCorner::BottomRight => "bottom_right",
+            ScaleBarCorner::TopLeft => "top_left",
+            ScaleBarCorner::TopRight => "top_right",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.key() == key)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ScaleBarCorner::BottomLeft => "左下",
+            ScaleBarCorner::BottomRight => "右下",
+            ScaleBarCorner::TopLeft => "左上",
+            ScaleBarCorner::TopRight => "右上",
+        }
+    }
+}
+
+/// 不超过 `max` 的「整」格数（1 / 2 / 5 × 10ⁿ）
+fn nice_length(max: f64) -> u32 {
+    if max < 1.0 {
+        return 1;
+    }
+    let magnitude = 10f64.powi(max.log10().floor() as i32);
+    let step = [5.0, 2.0, 1.0].into_iter()
+        .map(|m| m * magnitude)
+        .find(|&v| v <= max)
+        .unwrap_or(magnitude);
+    step as u32
+}
+
+/// 在世界所在区域 `rect = [x, y, 宽, 高]` 的 `corner` 角画比例尺。
+///
+/// `tiles` 为比例尺代表的格数，0 表示自动取约 1/5 世界宽度的整数；
+/// 指定长度放不进区域时同样回退为自动长度。
+pub fn draw_scale_bar(
+    img: &mut image::RgbaImage,
+    rect: [u32; 4],
+    world_width: u32,
+    tiles: u32,
+    corner: ScaleBarCorner,
+    font_data: &[u8],
+) -> Result<(), String> {
+    let font = FontRef::try_from_slice(font_data).map_err(|e| format!("字体解析失败: {e}"))?;
+    let [x0, y0, w, h] = rect;
+    if world_width == 0 || w <= MARGIN * 2 + PADDING * 2 || h <= MARGIN * 2 {
+        return Ok(());
+    }
+    let px_per_tile = w as f64 / world_width as f64;
+    let max_tiles = (w - MARGIN * 2 - PADDING * 2) as f64 / px_per_tile;
+    let tiles = if tiles == 0 || tiles as f64 > max_tiles {
+        nice_length((world_width as f64 * AUTO_FRACTION).min(max_tiles))
+    } else {
+        tiles
+    };
+    let bar_w = ((tiles as f64 * px_per_tile).round() as u32).max(1);
+
+    // 字号与线宽随区域高度缩放，与层级标注一致
+    let px = (h as f32 / 40.0).clamp(12.0, 48.0);
+    let thickness = (px / 8.0).round().max(2.0) as u32;
+    let tick = (px / 2.0).round() as u32;
+    let label = format!("{tiles} 格");
+    let text_w = text_width(&font, px, &label).ceil() as u32;
+    let box_w = (bar_w.max(text_w) + PADDING * 2).min(w - MARGIN * 2);
+    let box_h = px.ceil() as u32 + tick + PADDING * 3;
+
+    let bx = match corner {
+        ScaleBarCorner::BottomLeft | ScaleBarCorner::TopLeft => x0 + MARGIN,
+        ScaleBarCorner::BottomRight | ScaleBarCorner::TopRight => x0 + w - MARGIN - box_w,
+    };
+    let by = match corner {
+        ScaleBarCorner::TopLeft | ScaleBarCorner::TopRight => y0 + MARGIN,
+        ScaleBarCorner::BottomLeft | ScaleBarCorner::BottomRight => (y0 + h).saturating_sub(MARGIN + box_h),
+    };
+
+    let mut blend_rect = |rx0: u32, ry0: u32, rw: u32, rh: u32, color: [u8; 3], alpha: f32| {
+        for y in ry0..(ry0 + rh).min(img.height()) {
+            for x in rx0..(rx0 + rw).min(img.width()) {
+                blend_pixel(img.get_pixel_mut(x, y), color, alpha);
+            }
+        }
+    };
+    blend_rect(bx, by, box_w, box_h, BACKING, BACKING_ALPHA);
+
+    // 横线贴底，两端竖线向上
+    let line_x = bx + PADDING;
+    let line_y = by + box_h - PADDING - thickness;
+    blend_rect(line_x, line_y, bar_w, thickness, LINE_COLOR, 1.0);
+    blend_rect(line_x, line_y + thickness - tick, thickness, tick, LINE_COLOR, 1.0);
+    blend_rect((line_x + bar_w).saturating_sub(thickness), line_y + thickness - tick, thickness, tick, LINE_COLOR, 1.0);
+
+    let text_y = by + PADDING + px as u32 / 2;
+    draw_text(img, &font, px, line_x as f32, text_y as f32, &label);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{draw_scale_bar, ScaleBarCorner, BACKING, BACKING_ALPHA, MARGIN};
+    use crate::ui::app::CJK_FONT_BYTES;
+
+    #[test]
+    fn scale_bar_is_visible_on_transparent_images() {
+        let mut img = image::RgbaImage::new(800, 400);
+        draw_scale_bar(&mut img, [0, 0, 800, 400], 4200, 0, ScaleBarCorner::TopLeft, CJK_FONT_BYTES).unwrap();
+
+        // 底板角落没有线条与文字，应是按底板不透明度盖上的底色
+        let corner = img.get_pixel(MARGIN, MARGIN).0;
+        assert_eq!(corner, [BACKING[0], BACKING[1], BACKING[2], (BACKING_ALPHA * 255.0).round() as u8]);
+        assert!(img.pixels().any(|p| p.0[3] == 255), "横线与文字应完全不透明");
+        assert_eq!(img.get_pixel(799, 399).0, [0; 4], "比例尺以外保持透明");
+    }
+}
@@ -0,0 +1,196 @@
+//! # 世界摘要卡片
+//!
+//! 把一个世界汇总成一张固定尺寸的分享图：顶部为标题与种子 / 尺寸 / 参数哈希，
+//! 左侧为缩放到适合大小的世界预览，右侧按覆盖率从高到低列出环境（色块 + 名称 + 占比 + 条形图）。
+//! 文字与 [`biome_legend`](super::legend::biome_legend) 一样用 ab_glyph 按传入的字体光栅化。
+//!
+//! 预览图由调用方提供（应用内为与录制帧相同的降采样世界图像，混合环境覆盖色），
+//! 这里只负责排版与缩放，不依赖界面状态。
+
+use ab_glyph::FontRef;
+use image::imageops::{self, FilterType};
+use image::{Rgba, RgbaImage};
+
+use crate::core::biome::{BiomeDefinition, BiomeMap, BIOME_UNASSIGNED};
+
+use super::legend::{draw_text, text_width};
+
+/// 卡片尺寸（16:9，适合直接发到聊天 / 社交平台）
+pub const CARD_WIDTH: u32 = 1200;
+pub const CARD_HEIGHT: u32 = 675;
+
+const MARGIN: u32 = 32;
+const HEADER_HEIGHT: u32 = 80;
+const FOOTER_HEIGHT: u32 = 24;
+const SIDEBAR_WIDTH: u32 = 340;
+const COLUMN_GAP: u32 = 24;
+const ROW_HEIGHT: u32 = 36;
+const SWATCH: u32 = 12;
+const BAR_HEIGHT: u32 = 6;
+const TITLE_PX: f32 = 34.0;
+const SUBTITLE_PX: f32 = 18.0;
+const HEADING_PX: f32 = 20.0;
+const TEXT_PX: f32 = 16.0;
+const FOOTER_PX: f32 = 13.0;
+const BACKGROUND: [u8; 3] = [30, 30, 40];
+const PANEL: [u8; 3] = [22, 22, 30];
+const BAR_TRACK: [u8; 3] = [55, 55, 70];
+const BORDER: [u8; 3] = [91, 206, 250];
+
+/// 卡片标题区的文字信息
+#[derive(Debug, Clone)]
+pub struct SummaryCardInfo {
+    pub seed: u64,
+    /// 世界尺寸键名（如 `small`）
+    pub size_key: String,
+    pub width: u32,
+    pub height: u32,
+    pub params_hash: u64,
+}
+
+fn fill_rect(img: &mut RgbaImage, [x, y, w, h]: [u32; 4], [r, g, b]: [u8; 3]) {
+    let x1 = (x + w).min(img.width());
+    let y1 = (y + h).min(img.height());
+    for py in y..y1 {
+        for px in x..x1 {
+            img.put_pixel(px, py, Rgba([r, g, b, 255]));
+        }
+    }
+}
+
+/// 1px 矩形边框，画在 `rect` 外侧
+fn stroke_rect(img: &mut RgbaImage, [x, y, w, h]: [u32; 4], color: [u8; 3]) {
+    let (x0, y0) = (x.saturating_sub(1), y.saturating_sub(1));
+    fill_rect(img, [x0, y0, w + 2, 1], color);
+    fill_rect(img, [x0, y + h, w + 2, 1], color);
+    fill_rect(img, [x0, y0, 1, h + 2], color);
+    fill_rect(img, [x + w, y0, 1, h + 2], color);
+}
+
+/// 按覆盖率降序排列的 `(环境, 占比 0~1)`，不含未分配
+fn coverage(biome_map: &BiomeMap, biome_definitions: &[BiomeDefinition]) -> Vec<(BiomeDefinition, f64)> {
+    let histogram = biome_map.histogram();
+    let total: u64 = histogram.iter().sum();
+    let mut entries: Vec<(BiomeDefinition, f64)> = biome_definitions.iter()
+        .filter(|b| b.id != BIOME_UNASSIGNED && histogram[b.id as usize] > 0)
+        .map(|b| (b.clone(), histogram[b.id as usize] as f64 / total.max(1) as f64))
+        .collect();
+    entries.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.id.cmp(&b.0.id)));
+    entries
+}
+
+/// 合成固定尺寸（[`CARD_WIDTH`] × [`CARD_HEIGHT`]）的世界摘要卡片。
+///
+/// `preview` 为世界预览图（任意尺寸，等比缩放后居中放入预览区）；环境条目放不下时，
+/// 末行合并为「其余 N 种」。
+pub fn world_summary_card(
+    preview: &RgbaImage,
+    biome_map: &BiomeMap,
+    biome_definitions: &[BiomeDefinition],
+    info: &SummaryCardInfo,
+    font_data: &[u8],
+) -> Result<RgbaImage, String> {
+    if preview.width() == 0 || preview.height() == 0 {
+        return Err("世界预览图为空".to_string());
+    }
+    let entries = coverage(biome_map, biome_definitions);
+    if entries.is_empty() {
+        return Err("环境地图中没有已分配的环境".to_string());
+    }
+    let font = FontRef::try_from_slice(font_data).map_err(|e| format!("字体解析失败: {e}"))?;
+
+    let [r, g, b] = BACKGROUND;
+    let mut card = RgbaImage::from_pixel(CARD_WIDTH, CARD_HEIGHT, Rgba([r, g, b, 255]));
+
+    // ── 标题 ──
+    draw_text(&mut card, &font, TITLE_PX, MARGIN as f32, (MARGIN + 18) as f32, "Lian World");
+    let subtitle = format!(
+        "种子 0x{:016X}  ·  {} {}×{}  ·  参数哈希 {:016X}",
+        info.seed, info.size_key, info.width, info.height, info.params_hash,
+    );
+    draw_text(&mut card, &font, SUBTITLE_PX, MARGIN as f32, (MARGIN + 56) as f32, &subtitle);
+
+    // ── 世界预览：等比缩放后在预览区居中 ──
+    let body_top = MARGIN + HEADER_HEIGHT;
+    let body_height = CARD_HEIGHT - body_top - MARGIN - FOOTER_HEIGHT;
+    let area = [MARGIN, body_top, CARD_WIDTH - MARGIN * 2 - SIDEBAR_WIDTH - COLUMN_GAP, body_height];
+    fill_rect(&mut card, area, PANEL);
+    let scale = (area[2] as f32 / preview.width() as f32).min(area[3] as f32 / preview.height() as f32);
+    let pw = ((preview.width() as f32 * scale).round() as u32).clamp(1, area[2]);
+    let ph = ((preview.height() as f32 * scale).round() as u32).clamp(1, area[3]);
+    let resized = imageops::resize(preview, pw, ph, FilterType::Triangle);
+    let (px, py) = (area[0] + (area[2] - pw) / 2, area[1] + (area[3] - ph) / 2);
+    imageops::overlay(&mut card, &resized, px as i64, py as i64);
+    stroke_rect(&mut card, [px, py, pw, ph], BORDER);
+
+    // ── 环境覆盖率 ──
+    let left = CARD_WIDTH - MARGIN - SIDEBAR_WIDTH;
+    draw_text(&mut card, &font, HEADING_PX, left as f32, (body_top + 12) as f32, "环境覆盖");
+    let rows_top = body_top + 32;
+    let max_rows = ((body_height - 32) / ROW_HEIGHT).max(1) as usize;
+    let mut rows: Vec<(String, [u8; 3], f64)> = entries.iter()
+        .map(|(bdef, share)| {
+            let [r, g, b, _] = bdef.overlay_color;
+            (bdef.name.clone(), [r, g, b], *share)
+        })
+        .collect();
+    if rows.len() > max_rows {
+        let rest = rows.split_off(max_rows - 1);
+        let share = rest.iter().map(|(_, _, s)| s).sum();
+        rows.push((format!("其余 {} 种", rest.len()), BAR_TRACK, share));
+    }
+    let max_share = rows.iter().map(|(_, _, s)| *s).fold(0.0, f64::max).max(f64::EPSILON);
+    for (i, (name, color, share)) in rows.iter().enumerate() {
+        let top = rows_top + ROW_HEIGHT * i as u32;
+        let text_y = top + 11;
+        fill_rect(&mut card, [left, text_y - SWATCH / 2, SWATCH, SWATCH], *color);
+        draw_text(&mut card, &font, TEXT_PX, (left + SWATCH + 8) as f32, text_y as f32, name);
+        let percent = format!("{:.1}%", share * 100.0);
+        let percent_x = (CARD_WIDTH - MARGIN) as f32 - text_width(&font, TEXT_PX, &percent);
+        draw_text(&mut card, &font, TEXT_PX, percent_x, text_y as f32, &percent);
+
+        // 条形长度相对最大占比，小环境也看得出差别
+        let bar_top = top + 24;
+        fill_rect(&mut card, [left, bar_top, SIDEBAR_WIDTH, BAR_HEIGHT], BAR_TRACK);
+        let bar_w = ((SIDEBAR_WIDTH as f64 * share / max_share).round() as u32).clamp(1, SIDEBAR_WIDTH);
+        fill_rect(&mut card, [left, bar_top, bar_w, BAR_HEIGHT], *color);
+    }
+
+    // ── 页脚 ──
+    let footer = format!("共 {} 种环境 · 由 Lian World 生成，导入同一种子与参数即可复现", entries.len());
+    draw_text(&mut card, &font, FOOTER_PX, MARGIN as f32, (CARD_HEIGHT - MARGIN - 4) as f32, &footer);
+
+    Ok(card)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{world_summary_card, SummaryCardInfo, CARD_HEIGHT, CARD_WIDTH};
+    use crate::core::biome::{BiomeMap, BIOME_UNASSIGNED};
+    use crate::generation::headless::HeadlessContext;
+    use crate::ui::app::CJK_FONT_BYTES;
+
+    #[test]
+    fn card_has_fixed_size_and_requires_assigned_biomes() {
+        let context = HeadlessContext::load("small", None).unwrap();
+        let info = SummaryCardInfo {
+            seed: 42,
+            size_key: "small".to_string(),
+            width: 400,
+            height: 120,
+            params_hash: 0,
+        };
+        let preview = image::RgbaImage::from_pixel(400, 120, image::Rgba([90, 140, 60, 255]));
+
+        let empty = BiomeMap::new_filled(400, 120, BIOME_UNASSIGNED);
+        assert!(world_summary_card(&preview, &empty, &context.biomes, &info, CJK_FONT_BYTES).is_err());
+
+        // 所有环境各占一列，多于侧栏行数时应合并为「其余 N 种」而不是越界
+        let mut biome_map = BiomeMap::new_filled(400, 120, BIOME_UNASSIGNED);
+        for (i, tile) in biome_map.data_mut().iter_mut().enumerate() {
+            *tile = context.biomes[(i % 400) % context.biomes.len()].id;
+        }
+        let card = world_summary_card(&preview, &biome_map, &context.biomes, &info, CJK_FONT_BYTES).unwrap();
+        assert_eq!(card.dimensions(), (CARD_WIDTH, CARD_HEIGHT));
+    }
+}
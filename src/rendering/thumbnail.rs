@@ -0,0 +1,77 @@
+//! # 环境缩略图
+//!
+//! 将 BiomeMap 按最近邻降采样为不透明的小图（每种环境取其覆盖色的 RGB），
+//! 未分配区域为黑色。用于回归测试的金样图对比，以及多尺寸对比图。
+
+use ab_glyph::FontRef;
+
+use crate::core::biome::{BiomeDefinition, BiomeMap};
+
+use super::legend::{draw_text, text_width};
+
+const STRIP_PADDING: u32 = 16;
+const STRIP_LABEL_HEIGHT: u32 = 30;
+const STRIP_TEXT_PX: f32 = 18.0;
+const STRIP_BACKGROUND: [u8; 3] = [30, 30, 40];
+
+/// 生成宽度不超过 `max_width` 的环境缩略图（保持宽高比）。
+pub fn biome_thumbnail(
+    biome_map: &BiomeMap,
+    biome_definitions: &[BiomeDefinition],
+    max_width: u32,
+) -> image::RgbaImage {
+    let factor = biome_map.width.div_ceil(max_width.max(1)).max(1);
+    biome_thumbnail_scaled(biome_map, biome_definitions, factor)
+}
+
+/// 按固定倍率 `factor` 最近邻降采样（多张缩略图共用倍率时保持相对大小）。
+pub fn biome_thumbnail_scaled(
+    biome_map: &BiomeMap,
+    biome_definitions: &[BiomeDefinition],
+    factor: u32,
+) -> image::RgbaImage {
+    let factor = factor.max(1);
+    let out_w = biome_map.width.div_ceil(factor);
+    let out_h = biome_map.height.div_ceil(factor);
+
+    let mut lut = [[0u8, 0, 0, 255]; 256];
+    for bdef in biome_definitions {
+        let c = bdef.overlay_color;
+        lut[bdef.id as usize] = [c[0], c[1], c[2], 255];
+    }
+
+    image::RgbaImage::from_fn(out_w, out_h, |x, y| {
+        image::Rgba(lut[biome_map.get(x * factor, y * factor) as usize])
+    })
+}
+
+/// 把若干缩略图横向拼接为一张图，底边对齐，每张正上方标注 `label`。
+pub fn thumbnail_strip(
+    panels: &[(String, image::RgbaImage)],
+    font_data: &[u8],
+) -> Result<image::RgbaImage, String> {
+    if panels.is_empty() {
+        return Err("没有可拼接的缩略图".to_string());
+    }
+    let font = FontRef::try_from_slice(font_data).map_err(|e| format!("字体解析失败: {e}"))?;
+
+    // 每列宽度取缩略图与标注文字的较大者
+    let columns: Vec<u32> = panels.iter()
+        .map(|(label, img)| img.width().max(text_width(&font, STRIP_TEXT_PX, label).ceil() as u32))
+        .collect();
+    let max_h = panels.iter().map(|(_, img)| img.height()).max().unwrap_or(0);
+    let width = STRIP_PADDING * (panels.len() as u32 + 1) + columns.iter().sum::<u32>();
+    let height = STRIP_PADDING * 2 + STRIP_LABEL_HEIGHT + max_h;
+
+    let [r, g, b] = STRIP_BACKGROUND;
+    let mut out = image::RgbaImage::from_pixel(width, height, image::Rgba([r, g, b, 255]));
+    let mut left = STRIP_PADDING;
+    for ((label, img), column) in panels.iter().zip(&columns) {
+        let top = STRIP_PADDING + STRIP_LABEL_HEIGHT + max_h - img.height();
+        let label_y = (top - STRIP_LABEL_HEIGHT / 2) as f32;
+        draw_text(&mut out, &font, STRIP_TEXT_PX, left as f32, label_y, label);
+        image::imageops::replace(&mut out, img, left as i64, top as i64);
+        left += column + STRIP_PADDING;
+    }
+    Ok(out)
+}
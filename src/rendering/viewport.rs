@@ -1,34 +1,272 @@
+use serde::{Deserialize, Serialize};
+
+/// 惯性平移速度的衰减时间常数（秒）：约 0.5 秒后降到初速的 3% 以下
+const INERTIA_TIME_CONSTANT: f32 = 0.14;
+/// 低于此速度（屏幕像素 / 秒）时停止惯性平移
+const INERTIA_MIN_SPEED: f32 = 20.0;
+/// 默认缩放（重置视口时使用）
+pub const DEFAULT_ZOOM: f32 = 0.3;
+/// 聚焦区域四周留白（占画布的比例）
+const FRAME_MARGIN: f32 = 0.1;
+
+/// 视口动画的缓动曲线
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ViewportEasing {
+    /// 匀速
+    Linear,
+    /// 慢启动、慢停止（三次缓入缓出），默认
+    #[default]
+    EaseInOut,
+}
+
+impl ViewportEasing {
+    pub const ALL: [ViewportEasing; 2] = [ViewportEasing::Linear, ViewportEasing::EaseInOut];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ViewportEasing::Linear => "线性",
+            ViewportEasing::EaseInOut => "缓入缓出",
+        }
+    }
+
+    /// 把线性进度 `t ∈ [0, 1]` 映射为缓动后的进度
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            ViewportEasing::Linear => t,
+            ViewportEasing::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (2.0 - 2.0 * t).powi(3) / 2.0
+                }
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ViewportState {
     pub zoom: f32,
     pub offset: [f32; 2],
+    /// `animate_to_rect` / `animate_to_center` 的动画时长（秒）；0 = 直接跳到目标
+    pub animation_duration: f32,
+    /// 新动画使用的缓动曲线
+    pub animation_easing: ViewportEasing,
+    /// 平移速度（屏幕像素 / 秒）：拖拽时按最近几帧估计，松手后用于惯性平移
+    velocity: [f32; 2],
+    /// 进行中的视口动画；手动平移 / 缩放时取消
+    animation: Option<Animation>,
+}
+
+/// 视口动画的目标
+#[derive(Debug, Clone, Copy)]
+enum FrameTarget {
+    /// 世界区域 `[x0, y0, x1, y1]` 充满画布（四周留白）
+    Rect([f32; 4]),
+    /// 保持缩放，把世界坐标 `(x, y)` 移到画布中心
+    Center(f32, f32),
+    /// 世界坐标 `center` 位于画布中心，缩放为 `zoom`
+    View((f32, f32), f32),
+}
+
+/// 视口姿态：(画布中心对应的世界坐标, 缩放)
+type ViewPose = ((f32, f32), f32);
+
+/// 进行中的视口动画
+#[derive(Debug, Clone, Copy)]
+struct Animation {
+    target: FrameTarget,
+    /// 起止姿态；目标可能依赖画布尺寸，在第一次推进时确定
+    endpoints: Option<(ViewPose, ViewPose)>,
+    elapsed: f32,
+    duration: f32,
+    easing: ViewportEasing,
 }
 
 impl Default for ViewportState {
     fn default() -> Self {
         Self {
-            zoom: 0.3,
+            zoom: DEFAULT_ZOOM,
             offset: [0.0, 0.0],
+            animation_duration: 0.4,
+            animation_easing: ViewportEasing::default(),
+            velocity: [0.0, 0.0],
+            animation: None,
         }
     }
 }
 
 impl ViewportState {
+    /// 整个世界在屏幕上占据的矩形：图像中心为画布中心加平移偏移，宽高为世界尺寸 × 缩放
+    pub fn image_rect(&self, canvas_rect: egui::Rect, world_size: (u32, u32)) -> egui::Rect {
+        egui::Rect::from_center_size(
+            canvas_rect.center() + egui::vec2(self.offset[0], self.offset[1]),
+            egui::vec2(world_size.0 as f32 * self.zoom, world_size.1 as f32 * self.zoom),
+        )
+    }
+
     pub fn zoom_in(&mut self) {
+        self.animation = None;
         self.zoom = (self.zoom * 1.2).min(20.0);
     }
 
     pub fn zoom_out(&mut self) {
+        self.animation = None;
         self.zoom = (self.zoom / 1.2).max(0.1);
     }
 
+    /// 设置精确缩放，保持画布中心对应的世界坐标不变
+    pub fn set_zoom(&mut self, zoom: f32) {
+        self.animation = None;
+        let zoom = zoom.clamp(0.05, 20.0);
+        let ratio = zoom / self.zoom;
+        self.offset[0] *= ratio;
+        self.offset[1] *= ratio;
+        self.zoom = zoom;
+    }
+
     pub fn reset(&mut self) {
-        self.zoom = 0.3;
+        self.zoom = DEFAULT_ZOOM;
         self.offset = [0.0, 0.0];
+        self.velocity = [0.0, 0.0];
+        self.animation = None;
     }
 
     pub fn pan(&mut self, delta_x: f32, delta_y: f32) {
         self.offset[0] += delta_x;
         self.offset[1] += delta_y;
     }
+
+    /// 拖拽平移：移动视口并更新速度估计（指数平滑，避免最后一帧的抖动决定惯性方向）
+    pub fn drag(&mut self, delta_x: f32, delta_y: f32, dt: f32) {
+        self.animation = None;
+        self.pan(delta_x, delta_y);
+        if dt > 0.0 {
+            let blend = 0.4;
+            self.velocity[0] += (delta_x / dt - self.velocity[0]) * blend;
+            self.velocity[1] += (delta_y / dt - self.velocity[1]) * blend;
+        }
+    }
+
+    /// 停止惯性平移
+    pub fn stop_inertia(&mut self) {
+        self.velocity = [0.0, 0.0];
+    }
+
+    /// 按剩余速度继续平移 `dt` 秒并衰减速度；仍在移动时返回 `true`（调用方应请求重绘）
+    pub fn step_inertia(&mut self, dt: f32) -> bool {
+        let [vx, vy] = self.velocity;
+        if vx.hypot(vy) < INERTIA_MIN_SPEED {
+            self.velocity = [0.0, 0.0];
+            return false;
+        }
+        self.pan(vx * dt, vy * dt);
+        let decay = (-dt / INERTIA_TIME_CONSTANT).exp();
+        self.velocity = [vx * decay, vy * decay];
+        true
+    }
+
+    /// 平移视口使世界坐标 (x, y) 位于画布中心（保持缩放不变）
+    pub fn center_on(&mut self, x: f32, y: f32, world_width: u32, world_height: u32) {
+        self.velocity = [0.0, 0.0];
+        self.animation = None;
+        self.offset[0] = (world_width as f32 / 2.0 - x) * self.zoom;
+        self.offset[1] = (world_height as f32 / 2.0 - y) * self.zoom;
+    }
+
+    /// 世界尺寸变化后保持缩放，把视图中心按比例映射到新世界的对应位置
+    pub fn reanchor(&mut self, old_size: (u32, u32), new_size: (u32, u32)) {
+        self.velocity = [0.0, 0.0];
+        self.animation = None;
+        let (cx, cy) = self.view_center(old_size.0, old_size.1);
+        let fx = cx / old_size.0.max(1) as f32;
+        let fy = cy / old_size.1.max(1) as f32;
+        self.center_on(fx * new_size.0 as f32, fy * new_size.1 as f32, new_size.0, new_size.1);
+    }
+
+    /// 开始平滑移动并缩放视口，使世界区域 `[x0, y0, x1, y1]` 充满画布（四周留白）
+    pub fn animate_to_rect(&mut self, rect: [f32; 4]) {
+        self.start_animation(FrameTarget::Rect(rect), self.animation_duration);
+    }
+
+    /// 开始平滑平移视口（保持缩放），使世界坐标 `(x, y)` 移到画布中心
+    pub fn animate_to_center(&mut self, x: f32, y: f32) {
+        self.start_animation(FrameTarget::Center(x, y), self.animation_duration);
+    }
+
+    /// 在 `duration` 秒内把视口移到世界坐标 `center` 并缩放到 `zoom`（按 `animation_easing` 缓动）；
+    /// `duration` ≤ 0 时在下一帧直接到位
+    pub fn animate_to(&mut self, center: (f32, f32), zoom: f32, duration: f32) {
+        self.start_animation(FrameTarget::View(center, zoom.clamp(0.05, 20.0)), duration);
+    }
+
+    fn start_animation(&mut self, target: FrameTarget, duration: f32) {
+        self.velocity = [0.0, 0.0];
+        self.animation = Some(Animation {
+            target,
+            endpoints: None,
+            elapsed: 0.0,
+            duration: duration.max(0.0),
+            easing: self.animation_easing,
+        });
+    }
+
+    /// 取消进行中的视口动画（手动缩放等）
+    pub fn cancel_animation(&mut self) {
+        self.animation = None;
+    }
+
+    /// 推进视口动画 `dt` 秒：缩放按对数插值、中心按线性插值，进度经缓动曲线映射；仍在移动时返回 `true`
+    pub fn step_animation(&mut self, canvas_size: [f32; 2], world_width: u32, world_height: u32, dt: f32) -> bool {
+        let current = (self.view_center(world_width, world_height), self.zoom);
+        let Some(animation) = self.animation.as_mut() else {
+            return false;
+        };
+        let (from, to) = *animation.endpoints.get_or_insert_with(|| {
+            let to = match animation.target {
+                FrameTarget::Rect([x0, y0, x1, y1]) => {
+                    let fill = 1.0 - 2.0 * FRAME_MARGIN;
+                    let zoom = (canvas_size[0] * fill / (x1 - x0).max(1.0))
+                        .min(canvas_size[1] * fill / (y1 - y0).max(1.0))
+                        .clamp(0.05, 20.0);
+                    (((x0 + x1) / 2.0, (y0 + y1) / 2.0), zoom)
+                }
+                FrameTarget::Center(x, y) => ((x, y), current.1),
+                FrameTarget::View(center, zoom) => (center, zoom),
+            };
+            (current, to)
+        });
+
+        animation.elapsed += dt;
+        let arrived = animation.elapsed >= animation.duration;
+        let ((center, zoom), done) = if arrived {
+            (to, true)
+        } else {
+            let t = animation.easing.apply(animation.elapsed / animation.duration);
+            let zoom = (from.1.ln() + (to.1.ln() - from.1.ln()) * t).exp();
+            let center = (
+                from.0.0 + (to.0.0 - from.0.0) * t,
+                from.0.1 + (to.0.1 - from.0.1) * t,
+            );
+            ((center, zoom), false)
+        };
+
+        self.zoom = zoom;
+        self.offset[0] = (world_width as f32 / 2.0 - center.0) * zoom;
+        self.offset[1] = (world_height as f32 / 2.0 - center.1) * zoom;
+        if done {
+            self.animation = None;
+        }
+        !done
+    }
+
+    /// 画布中心对应的世界坐标（可能超出世界范围）
+    pub fn view_center(&self, world_width: u32, world_height: u32) -> (f32, f32) {
+        (
+            world_width as f32 / 2.0 - self.offset[0] / self.zoom,
+            world_height as f32 / 2.0 - self.offset[1] / self.zoom,
+        )
+    }
 }
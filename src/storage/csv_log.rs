@@ -0,0 +1,161 @@
+//! # CSV 生成日志
+//!
+//! 每次完整生成向 `~/.local/share/lwd/logs/generation_log.csv` 追加一行：
+//! 种子、世界尺寸、总耗时、各步骤耗时，以及关键决策（丛林方位、沙漠 / 邪恶环境放置数量等）。
+//! 便于在表格软件中对多次运行做统计，对照参数调优。
+//!
+//! 表头由首行决定。步骤或决策列发生变化（例如算法增删了步骤）时，
+//! 旧文件改名为 `generation_log_<时间戳>.csv` 保留（同名时追加 `_2`、`_3`… 不覆盖），再开始新文件，保证每个文件内各列含义一致。
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use super::paths;
+
+/// CSV 日志文件名
+const FILE_NAME: &str = "generation_log.csv";
+
+/// 一次完整生成的 CSV 记录
+#[derive(Debug, Clone)]
+pub struct CsvRun {
+    pub timestamp: String,
+    pub seed: String,
+    pub world_size: String,
+    pub total_ms: f64,
+    /// 各步骤本次运行的 `(名称, 耗时 ms)`，按步骤顺序
+    pub steps: Vec<(String, f64)>,
+    /// 关键决策 `(列名, 值)`；步骤未执行时值为空
+    pub decisions: Vec<(&'static str, String)>,
+}
+
+impl CsvRun {
+    fn header(&self) -> Vec<String> {
+        let mut cols: Vec<String> = ["timestamp", "seed", "world_size", "total_ms"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        cols.extend(self.steps.iter().map(|(name, _)| format!("{name} (ms)")));
+        cols.extend(self.decisions.iter().map(|(key, _)| key.to_string()));
+        cols
+    }
+
+    fn row(&self) -> Vec<String> {
+        let mut cols = vec![
+            self.timestamp.clone(),
+            self.seed.clone(),
+            self.world_size.clone(),
+            format!("{:.3}", self.total_ms),
+        ];
+        cols.extend(self.steps.iter().map(|(_, ms)| format!("{ms:.3}")));
+        cols.extend(self.decisions.iter().map(|(_, v)| v.clone()));
+        cols
+    }
+}
+
+/// CSV 日志文件的完整路径
+pub fn log_path() -> PathBuf {
+    paths::logs_dir().join(FILE_NAME)
+}
+
+/// 追加一行记录；表头与已有文件不一致时先把旧文件改名保留
+pub fn append_run(run: &CsvRun) -> std::io::Result<PathBuf> {
+    let path = log_path();
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    append_to(&path, run)?;
+    Ok(path)
+}
+
+fn append_to(path: &Path, run: &CsvRun) -> std::io::Result<()> {
+    let header = encode_line(&run.header());
+    let existing = std::fs::read_to_string(path).ok();
+    let write_header = match existing.as_deref().and_then(|s| s.lines().next()) {
+        Some(first) if first == header => false,
+        Some(_) => {
+            std::fs::rename(path, rotated_path(path, &run.timestamp))?;
+            true
+        }
+        None => true,
+    };
+
+    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+    if write_header {
+        writeln!(file, "{header}")?;
+    }
+    writeln!(file, "{}", encode_line(&run.row()))
+}
+
+/// 轮换用的旧文件名：`generation_log_<时间戳>.csv`，已存在时依次追加序号，避免覆盖此前轮换的文件
+fn rotated_path(path: &Path, timestamp: &str) -> PathBuf {
+    let stem = format!("generation_log_{}", timestamp.replace([':', '-', ' '], ""));
+    (1..)
+        .map(|n| match n {
+            1 => path.with_file_name(format!("{stem}.csv")),
+            n => path.with_file_name(format!("{stem}_{n}.csv")),
+        })
+        .find(|candidate| !candidate.exists())
+        .expect("序号取之不尽")
+}
+
+/// 按 RFC 4180 拼接一行：含逗号、引号或换行的字段加引号，引号加倍
+fn encode_line(fields: &[String]) -> String {
+    fields
+        .iter()
+        .map(|f| {
+            if f.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", f.replace('"', "\"\""))
+            } else {
+                f.clone()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{append_to, encode_line, CsvRun};
+
+    fn run(timestamp: &str, steps: &[&str]) -> CsvRun {
+        CsvRun {
+            timestamp: timestamp.to_string(),
+            seed: "0000000000000001".to_string(),
+            world_size: "4200x1200".to_string(),
+            total_ms: 12.5,
+            steps: steps.iter().map(|name| (name.to_string(), 1.0)).collect(),
+            decisions: vec![("jungle_side", "left".to_string())],
+        }
+    }
+
+    #[test]
+    fn encode_line_quotes_only_special_fields() {
+        let fields = ["plain", "a,b", "say \"hi\"", "two\nlines", ""].map(String::from);
+        assert_eq!(encode_line(&fields), "plain,\"a,b\",\"say \"\"hi\"\"\",\"two\nlines\",");
+    }
+
+    #[test]
+    fn header_change_rotates_without_overwriting() {
+        let dir = std::env::temp_dir().join(format!("lwd_csv_log_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("generation_log.csv");
+        let _ = std::fs::remove_file(&path);
+
+        append_to(&path, &run("2026-01-01 00:00:00", &["a"])).unwrap();
+        append_to(&path, &run("2026-01-01 00:00:00", &["a"])).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 3, "表头相同时只追加数据行");
+
+        // 同一时间戳连续两次换表头，两份旧文件都应保留
+        append_to(&path, &run("2026-01-01 00:00:00", &["a", "b"])).unwrap();
+        append_to(&path, &run("2026-01-01 00:00:00", &["c"])).unwrap();
+        let first = dir.join("generation_log_20260101000000.csv");
+        let second = dir.join("generation_log_20260101000000_2.csv");
+        assert_eq!(std::fs::read_to_string(&first).unwrap().lines().count(), 3);
+        assert!(std::fs::read_to_string(&second).unwrap().starts_with("timestamp,seed,world_size,total_ms,a (ms),b (ms),"));
+        let current = std::fs::read_to_string(&path).unwrap();
+        assert!(current.starts_with("timestamp,seed,world_size,total_ms,c (ms),jungle_side\n"));
+        assert_eq!(current.lines().count(), 2);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -11,12 +11,18 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::generation::step_rng::RngKind;
+use crate::rendering::viewport::ViewportEasing;
+
 use super::runtime;
 
 // ═══════════════════════════════════════════════════════════
 // 配置结构
 // ═══════════════════════════════════════════════════════════
 
+/// 每格的估算内存（字节）：方块与环境各 1，世界与环境覆盖层的 RGBA 像素各 4，撤销备份 2
+const BYTES_PER_CELL: u64 = 12;
+
 /// 引擎调优参数——所有可调数值的唯一来源。
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
@@ -28,10 +34,10 @@ pub struct EngineConfig {
     // ── 自适应批量 ──
     /// 增量执行初始 batch 大小
     pub batch_initial: usize,
-    /// 目标帧时间下限（ms），低于此值增大 batch
-    pub batch_target_min_ms: f64,
-    /// 目标帧时间上限（ms），高于此值减小 batch
-    pub batch_target_max_ms: f64,
+    /// 目标帧时间（ms）
+    pub batch_target_ms: f64,
+    /// 目标帧时间的容差带半宽（ms）：EMA 帧时间超出 `目标 ± 容差` 时才调整 batch
+    pub batch_band_ms: f64,
     /// 最小 batch
     pub batch_min: usize,
     /// 最大 batch
@@ -50,10 +56,72 @@ pub struct EngineConfig {
     pub throttle_refresh_medium: usize,
     /// 大世界纹理刷新间隔（帧）
     pub throttle_refresh_large: usize,
+    /// 手动步进的刷新延迟（ms）：非小世界上连续单步 / 回退时，最后一步后空闲满此时长才刷新纹理；0 = 每步立即刷新
+    pub manual_refresh_idle_ms: u64,
 
     // ── 性能日志 ──
     /// 日志文件最大保留数量
     pub perf_log_max_files: usize,
+    /// 每次完整生成后向 `logs/generation_log.csv` 追加一行（步骤耗时 + 关键决策）
+    pub csv_log: bool,
+
+    // ── 画布背景 ──
+    /// 空白区域棋盘格边长（逻辑像素）
+    pub checker_tile: f32,
+    /// 棋盘格颜色 A（RGB）
+    pub checker_color_a: [u8; 3],
+    /// 棋盘格颜色 B（RGB）；与 A 相同即为纯色背景
+    pub checker_color_b: [u8; 3],
+    /// 单张画布纹理的最大边长（像素），超出时分块上传；0 = 使用 GPU 上限 `GL_MAX_TEXTURE_SIZE`
+    pub max_texture_size: u32,
+    /// 内存预算（MB）：切换世界尺寸、导入或恢复世界前估算占用，超出则拒绝；0 = 不限制
+    pub memory_budget_mb: u64,
+
+    // ── 交互 ──
+    /// 生成进行到一半时，「一键生成」/「重新初始化」前弹窗确认
+    pub confirm_destructive_reset: bool,
+    /// 固定种子模式：重置时使用 `fixed_seed` 而不是随机种子（开发调试用）
+    pub fixed_seed_mode: bool,
+    /// 固定种子模式下使用的种子
+    pub fixed_seed: u64,
+    /// 退出时把当前世界保存为 `last_session.lwd`，下次启动时询问是否恢复
+    pub auto_save_session: bool,
+    /// 生成过程中每隔多少秒把进度写入崩溃恢复检查点 `checkpoint.lwd`（进度未变时不写）；0 = 关闭
+    pub checkpoint_interval_secs: u64,
+    /// 松手后画布按拖拽速度继续滑行并在约 0.5 秒内减速停下；关闭则松手即停
+    pub pan_inertia: bool,
+    /// 滚轮缩放灵敏度倍率（1.0 = 每个滚轮刻度约 ±10%）；触控板用户可调高，鼠标用户可调低
+    pub zoom_sensitivity: f32,
+    /// 反转滚轮缩放方向（向下滚放大）
+    pub invert_zoom: bool,
+    /// 单步执行后把视口平滑移动并缩放到该步形状记录的包围盒（聚焦本步改动）
+    pub auto_frame_step: bool,
+    /// 切换世界尺寸或导入存档时重置视口；关闭则保持缩放，并按比例把视图中心映射到新世界的对应位置
+    pub reset_viewport_on_world_change: bool,
+    /// 视口动画（聚焦改动、回到出生点、书签跳转、查找环境）的时长（毫秒）；0 = 直接跳到目标
+    pub viewport_animation_ms: u32,
+    /// 视口动画的缓动曲线
+    pub viewport_easing: ViewportEasing,
+
+    // ── 调试 ──
+    /// 在画布上显示每个已执行子步骤的子种子推导（flat_index / seed / 耗时）
+    pub debug_seed_overlay: bool,
+    /// 森林填充执行后，在画布上用箭头标出沙漠 / 邪恶环境向窄缝扩散的方向与距离
+    pub debug_diffusion_arrows: bool,
+    /// 防重叠检查：每步前后比较环境地图，已分配的环境被未声明地改写时报错（仅调试构建生效）
+    pub debug_overlap_check: bool,
+    /// 记录每格由第几个子步骤分配，并把环境覆盖层换成生成顺序热力图（早 = 蓝，晚 = 红）
+    pub debug_assign_order: bool,
+    /// 未知环境高亮色（RGBA）：环境地图中已分配、但没有环境定义的 ID 在覆盖层上用此色显示；
+    /// 不透明度为 0 时与未分配一样透明
+    pub unknown_biome_color: [u8; 4],
+    /// 子种子混入世界尺寸（与泰拉瑞亚一致：同一种子在不同尺寸下布局不同）。
+    /// 关闭后各尺寸使用相同的子种子序列，得到按比例缩放的相似布局，便于对比；
+    /// 但 .lwd 存档不记录此项，关闭时导入的存档与他人用同一种子生成的世界都不再一致
+    pub seed_size_mixing: bool,
+    /// 每步 RNG 的算法（StdRng / ChaCha8 / .NET System.Random）。
+    /// 不同算法得到不同但各自确定的世界：**切换后所有种子的结果都会改变**，.lwd 存档不记录此项
+    pub rng_kind: RngKind,
 
     // ── 元数据 ──
     /// 是否已经过自校准
@@ -66,8 +134,8 @@ impl Default for EngineConfig {
             parallel_pixel_threshold: 50_000,
 
             batch_initial: 3,
-            batch_target_min_ms: 8.0,
-            batch_target_max_ms: 16.0,
+            batch_target_ms: 12.0,
+            batch_band_ms: 4.0,
             batch_min: 1,
             batch_max: 64,
             batch_ema_alpha: 0.3,
@@ -77,8 +145,37 @@ impl Default for EngineConfig {
             throttle_refresh_small: 3,
             throttle_refresh_medium: 5,
             throttle_refresh_large: 8,
+            manual_refresh_idle_ms: 120,
 
             perf_log_max_files: 100,
+            csv_log: false,
+
+            checker_tile: 48.0,
+            checker_color_a: [28, 28, 28],
+            checker_color_b: [35, 35, 35],
+            max_texture_size: 0,
+            memory_budget_mb: 2048,
+
+            confirm_destructive_reset: true,
+            fixed_seed_mode: false,
+            fixed_seed: 0,
+            auto_save_session: false,
+            checkpoint_interval_secs: 30,
+            pan_inertia: true,
+            zoom_sensitivity: 1.0,
+            invert_zoom: false,
+            auto_frame_step: false,
+            reset_viewport_on_world_change: true,
+            viewport_animation_ms: 400,
+            viewport_easing: ViewportEasing::default(),
+
+            debug_seed_overlay: false,
+            debug_diffusion_arrows: false,
+            debug_overlap_check: false,
+            debug_assign_order: false,
+            unknown_biome_color: [255, 0, 255, 220],
+            seed_size_mixing: true,
+            rng_kind: RngKind::default(),
 
             calibrated: false,
         }
@@ -153,10 +250,26 @@ impl EngineConfig {
         self.parallel_pixel_threshold = (threshold as f64 * 0.8) as i64;
         self.calibrated = true;
 
-        eprintln!(
+        crate::core::log::info(format!(
             "[engine] 自校准完成: parallel_pixel_threshold = {}",
             self.parallel_pixel_threshold
-        );
+        ));
+    }
+
+    /// 估算指定尺寸世界的内存占用（MB，向上取整）：方块、环境地图、画布纹理与撤销备份
+    pub fn estimate_world_memory_mb(width: u32, height: u32) -> u64 {
+        let bytes = width as u64 * height as u64 * BYTES_PER_CELL;
+        bytes.div_ceil(1024 * 1024)
+    }
+
+    /// 检查世界尺寸是否在内存预算内；超出时返回估算占用（MB）
+    pub fn check_memory_budget(&self, width: u32, height: u32) -> Result<(), u64> {
+        let estimate = Self::estimate_world_memory_mb(width, height);
+        if self.memory_budget_mb > 0 && estimate > self.memory_budget_mb {
+            Err(estimate)
+        } else {
+            Ok(())
+        }
     }
 
     /// 如果尚未校准，执行校准并保存。
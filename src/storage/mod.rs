@@ -1,4 +1,7 @@
+pub mod csv_log;
 pub mod engine_config;
 pub mod paths;
 pub mod perf_log;
 pub mod runtime;
+pub mod shape_library;
+pub mod wld;
@@ -30,6 +30,16 @@ pub fn runtime_json_path() -> PathBuf {
     data_dir().join("runtime.json")
 }
 
+/// 退出时自动保存的会话快照
+pub fn last_session_path() -> PathBuf {
+    data_dir().join("last_session.lwd")
+}
+
+/// 生成过程中定期写入的崩溃恢复检查点；正常退出时删除，启动时存在即说明上次异常退出
+pub fn crash_checkpoint_path() -> PathBuf {
+    data_dir().join("checkpoint.lwd")
+}
+
 /// 性能日志目录
 pub fn logs_dir() -> PathBuf {
     data_dir().join("logs")
@@ -86,11 +96,11 @@ fn migrate_legacy(new_dir: &PathBuf) {
             if let Ok(content) = std::fs::read_to_string(&candidate) {
                 if std::fs::write(&new_path, &content).is_ok() {
                     let _ = std::fs::remove_file(&candidate);
-                    eprintln!(
+                    crate::core::log::info(format!(
                         "[storage] 已迁移旧配置: {} → {}",
                         candidate.display(),
                         new_path.display()
-                    );
+                    ));
                 }
             }
             return;
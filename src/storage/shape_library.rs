@@ -0,0 +1,127 @@
+//! # 图形库
+//!
+//! 沙箱中调好的形状可按名称存入图形库（`shape_library.json`），之后在任意沙箱中插入复用，
+//! 例如标准的沙漠椭圆、丛林梯形。条目直接保存沙箱形状（[`SandboxShape`]），名称即形状标签。
+//!
+//! 文件不存在时视为空库；无法解析时把原文件改名为 `shape_library.json.bak`（已存在则追加序号）
+//! 保留并报告错误，再以空库继续，之后的保存不会覆盖用户原有的数据。
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::ui::shape_sandbox::SandboxShape;
+
+use super::paths;
+
+/// 图形库：按存入顺序排列，同名条目会被覆盖
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ShapeLibrary {
+    pub shapes: Vec<SandboxShape>,
+}
+
+impl ShapeLibrary {
+    /// 图形库文件路径
+    pub fn path() -> PathBuf {
+        paths::data_dir().join("shape_library.json")
+    }
+
+    /// 读取图形库；文件损坏时备份原文件，返回空库与错误说明
+    pub fn load() -> (Self, Option<String>) {
+        Self::load_from(&Self::path())
+    }
+
+    fn load_from(path: &Path) -> (Self, Option<String>) {
+        let Ok(text) = std::fs::read_to_string(path) else {
+            return (Self::default(), None);
+        };
+        match serde_json::from_str(&text) {
+            Ok(library) => (library, None),
+            Err(e) => {
+                let backup = backup_path(path);
+                let error = match std::fs::rename(path, &backup) {
+                    Ok(()) => format!("图形库解析失败（{e}），原文件已备份为 {}", backup.display()),
+                    Err(io) => format!("图形库解析失败（{e}），且备份失败: {io}"),
+                };
+                (Self::default(), Some(error))
+            }
+        }
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        self.save_to(&Self::path())
+    }
+
+    fn save_to(&self, path: &Path) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, json).map_err(|e| format!("保存图形库失败: {e}"))
+    }
+
+    /// 以 `name` 为标签存入形状；已有同名条目时原位替换
+    pub fn insert(&mut self, name: &str, shape: &SandboxShape) {
+        let shape = SandboxShape { label: name.to_string(), ..shape.clone() };
+        match self.shapes.iter_mut().find(|s| s.label == shape.label) {
+            Some(existing) => *existing = shape,
+            None => self.shapes.push(shape),
+        }
+    }
+}
+
+/// 损坏文件的备份名：`<文件名>.bak`，已存在时依次追加序号，不覆盖更早的备份
+fn backup_path(path: &Path) -> PathBuf {
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("shape_library.json");
+    (1..)
+        .map(|n| match n {
+            1 => path.with_file_name(format!("{name}.bak")),
+            n => path.with_file_name(format!("{name}.{n}.bak")),
+        })
+        .find(|candidate| !candidate.exists())
+        .expect("序号取之不尽")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ShapeLibrary;
+    use crate::core::geometry::ShapeKind;
+    use crate::ui::shape_sandbox::SandboxShape;
+
+    #[test]
+    fn shapes_round_trip_and_corrupt_files_are_backed_up() {
+        let dir = std::env::temp_dir().join(format!("lwd_shape_library_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("shape_library.json");
+
+        let mut library = ShapeLibrary::default();
+        let mut ellipse = SandboxShape::new_default(ShapeKind::Ellipse, 1, 4200, 1200);
+        ellipse.ell_rx = 123.5;
+        library.insert("沙漠椭圆", &ellipse);
+        let mut implicit = SandboxShape::new_default(ShapeKind::Implicit, 2, 4200, 1200);
+        implicit.impl_source = "abs(x) + abs(y) < r".to_string();
+        library.insert("菱形", &implicit);
+        library.insert("沙漠椭圆", &ellipse);
+        library.save_to(&path).unwrap();
+
+        let (loaded, error) = ShapeLibrary::load_from(&path);
+        assert!(error.is_none());
+        let labels: Vec<&str> = loaded.shapes.iter().map(|s| s.label.as_str()).collect();
+        assert_eq!(labels, ["沙漠椭圆", "菱形"], "同名条目原位替换");
+        assert_eq!(loaded.shapes[0].kind, ShapeKind::Ellipse);
+        assert_eq!(loaded.shapes[0].ell_rx, 123.5);
+        assert_eq!(loaded.shapes[0].color, ellipse.color);
+        assert_eq!(loaded.shapes[1].impl_source, implicit.impl_source);
+        assert_eq!(
+            serde_json::to_value(&loaded).unwrap(),
+            serde_json::to_value(&library).unwrap(),
+            "保存再读取应得到相同的图形库",
+        );
+
+        std::fs::write(&path, "{ not json").unwrap();
+        let (empty, error) = ShapeLibrary::load_from(&path);
+        assert!(empty.shapes.is_empty());
+        assert!(error.is_some_and(|e| e.contains("备份")));
+        assert!(!path.exists(), "损坏的文件应移走，之后的保存不会覆盖它");
+        assert_eq!(std::fs::read_to_string(dir.join("shape_library.json.bak")).unwrap(), "{ not json");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,468 @@
+//! # Terraria `.wld` 导入
+//!
+//! 读取泰拉瑞亚世界存档的文件头、世界头和方块区段，把方块类型映射回本工具的
+//! 方块 ID（`World.tiles`）和环境 ID（`BiomeMap`），用于和生成结果对照。
+//! 只解析方块网格，忽略箱子、NPC、实体等后续区段。
+//!
+//! 支持 1.3.5 及以上版本的存档（文件版本号 ≥ 194）。
+//!
+//! ## 文件结构（只列出用到的部分）
+//!
+//! - 文件头：版本号、`relogic` 魔数、区段指针表、`tileFrameImportant` 位图
+//! - 区段 0（世界头）：世界名、种子、GUID、ID、边界，随后是 `maxTilesY` / `maxTilesX`
+//! - 区段 1（方块）：按列优先存储，每格 1~4 个标志字节 + 可选数据，带纵向游程压缩
+//!
+//! 不在映射表中的方块（家具、装饰等）视为空气；无方块但有液体的格子映射为对应液体。
+//! 只有具有环境指示性的方块（沙、雪、泥、猩红石…）会写入环境，其余格子保持未分配。
+
+use std::path::Path;
+
+use crate::core::biome::{BiomeDefinition, BiomeMap, BIOME_UNASSIGNED};
+use crate::core::block::BlockId;
+use crate::core::world::{World, AIR_BLOCK_ID};
+
+/// 支持的最低文件版本（1.3.5）
+const MIN_VERSION: i32 = 194;
+/// 文件头魔数
+const MAGIC: &[u8; 7] = b"relogic";
+/// 文件类型：世界
+const FILE_TYPE_WORLD: u8 = 2;
+
+/// 导入结果
+pub struct WldImport {
+    /// 世界名
+    pub name: String,
+    /// 文件版本号
+    pub version: i32,
+    pub world: World,
+    pub biome_map: BiomeMap,
+}
+
+/// 泰拉瑞亚方块类型（TileID）→ 本工具方块 ID
+fn block_for_tile(tile: u16) -> Option<BlockId> {
+    let id = match tile {
+        0 => 2,                      // Dirt → 土块
+        1 | 25 | 117 => 3,           // Stone / Ebonstone / Pearlstone → 石块
+        2 | 23 | 60 | 70 | 109 | 199 => 14, // 各类草
+        5 | 30 | 191 => 10,          // Trees / Wood / LivingWood → 木材
+        192 => 11,                   // LeafBlock → 树叶
+        71 | 72 => 12,               // MushroomPlants / MushroomTrees → 发光蘑菇
+        80 => 13,                    // Cactus → 仙人掌
+        53 | 112 | 116 | 234 => 4,   // Sand / Ebonsand / Pearlsand / Crimsand → 沙块
+        40 => 5,                     // ClayBlock → 黏土
+        59 => 6,                     // Mud → 淤泥块
+        397 => 7,                    // HardenedSand → 硬化沙块
+        396 => 8,                    // Sandstone → 沙岩块
+        123 => 9,                    // Silt → 泥沙块
+        161 => 15,                   // IceBlock → 冰雪块
+        147 => 16,                   // SnowBlock → 雪块
+        224 => 17,                   // Slush → 雪泥
+        162 => 18,                   // BreakableIce → 薄冰
+        7 | 166 => 19,               // Copper / Tin → 铜矿
+        6 | 167 => 20,               // Iron / Lead → 铁矿
+        9 | 168 => 21,               // Silver / Tungsten → 银矿
+        8 | 169 => 22,               // Gold / Platinum → 金矿
+        57 => 23,                    // Ash → 灰烬块
+        58 => 24,                    // Hellstone → 狱石
+        75 => 25,                    // ObsidianBrick → 黑曜石砖
+        76 => 26,                    // HellstoneBrick → 狱石砖
+        226 => 27,                   // LihzahrdBrick → 神庙砖
+        225 => 28,                   // Hive → 蜂巢块
+        51 => 33,                    // Cobweb → 蛛丝
+        81 => 34,                    // Coral → 珊瑚
+        314 => 35,                   // MinecartTrack → 铁轨
+        189 => 36,                   // Cloud → 云块
+        196 => 37,                   // RainCloud → 雨云块
+        367 => 38,                   // Marble → 大理石块
+        368 => 39,                   // Granite → 花岗岩块
+        21 => 40,                    // Containers → 宝箱
+        203 => 42,                   // Crimstone → 猩红石块
+        _ => return None,
+    };
+    Some(id)
+}
+
+/// 液体类型（1 水 / 2 岩浆 / 3 蜂蜜 / 4 微光）→ 本工具方块 ID
+fn block_for_liquid(liquid: u8) -> Option<BlockId> {
+    match liquid {
+        1 => Some(30),
+        2 => Some(31),
+        3 => Some(32),
+        4 => Some(43),
+        _ => None,
+    }
+}
+
+/// 具有环境指示性的方块 → 环境 key
+fn biome_for_tile(tile: u16) -> Option<&'static str> {
+    let key = match tile {
+        2 | 5 => "forest",
+        1 => "stone",
+        53 | 80 | 396 | 397 => "desert",
+        147 | 161 | 162 | 224 => "snow",
+        59 | 60 | 225 | 226 => "jungle",
+        199 | 203 | 234 => "crimson",
+        23 | 25 | 112 => "corruption",
+        57 | 58 | 76 => "hell",
+        189 | 196 => "space",
+        _ => return None,
+    };
+    Some(key)
+}
+
+/// 读取并导入 `.wld` 文件
+pub fn load_wld(
+    path: &Path,
+    biome_defs: &[BiomeDefinition],
+    check_size: impl FnOnce(u32, u32) -> Result<(), String>,
+) -> Result<WldImport, String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("读取文件失败: {e}"))?;
+    parse_wld(&bytes, biome_defs, check_size)
+}
+
+/// 从内存中的 `.wld` 数据导入
+///
+/// 读出世界尺寸后、分配世界与环境地图前调用 `check_size`，返回错误时中止导入。
+pub fn parse_wld(
+    bytes: &[u8],
+    biome_defs: &[BiomeDefinition],
+    check_size: impl FnOnce(u32, u32) -> Result<(), String>,
+) -> Result<WldImport, String> {
+    let mut r = Reader::new(bytes);
+
+    // ── 文件头 ──
+    let version = r.i32()?;
+    if version < MIN_VERSION {
+        return Err(format!("不支持的存档版本 {version}（需要 ≥ {MIN_VERSION}，即 1.3.5 及以上）"));
+    }
+    if r.bytes(7)? != MAGIC {
+        return Err("不是泰拉瑞亚存档（缺少 relogic 标识）".to_string());
+    }
+    if r.u8()? != FILE_TYPE_WORLD {
+        return Err("不是世界存档（文件类型不符）".to_string());
+    }
+    r.skip(4 + 8)?; // revision + favorite
+    let section_count = r.i16()?;
+    if section_count < 2 {
+        return Err(format!("区段数量异常: {section_count}"));
+    }
+    let sections = (0..section_count)
+        .map(|_| r.i32().map(|p| p as usize))
+        .collect::<Result<Vec<_>, _>>()?;
+    let importance = read_importance(&mut r)?;
+
+    // ── 世界头 ──
+    r.seek(sections[0])?;
+    let name = r.string()?;
+    let _seed = r.string()?;
+    r.skip(8)?; // worldGenVersion
+    r.skip(16)?; // GUID（≥ 181）
+    r.skip(4 + 4 * 4)?; // worldId + left/right/top/bottom
+    let height = r.i32()?;
+    let width = r.i32()?;
+    if width <= 0 || height <= 0 {
+        return Err(format!("世界尺寸异常: {width}×{height}"));
+    }
+    let (width, height) = (width as u32, height as u32);
+    check_size(width, height)?;
+
+    // ── 方块 ──
+    let biome_lut: Vec<(&str, u8)> = biome_defs.iter().map(|b| (b.key.as_str(), b.id)).collect();
+    let biome_id = |tile: u16| {
+        biome_for_tile(tile)
+            .and_then(|key| biome_lut.iter().find(|(k, _)| *k == key))
+            .map_or(BIOME_UNASSIGNED, |&(_, id)| id)
+    };
+
+    r.seek(sections[1])?;
+    let mut world = World::new_air(width, height);
+    let mut biome_map = BiomeMap::new_filled(width, height, BIOME_UNASSIGNED);
+    for x in 0..width {
+        let mut y = 0;
+        while y < height {
+            let tile = read_tile(&mut r, version, &importance)?;
+            let block = match tile.tile_type {
+                Some(t) => block_for_tile(t).unwrap_or(AIR_BLOCK_ID),
+                None => block_for_liquid(tile.liquid).unwrap_or(AIR_BLOCK_ID),
+            };
+            let biome = tile.tile_type.map_or(BIOME_UNASSIGNED, biome_id);
+
+            let end = (y + 1 + tile.repeat).min(height);
+            for yy in y..end {
+                world.tiles[(yy * width + x) as usize] = block;
+                if biome != BIOME_UNASSIGNED {
+                    biome_map.set(x, yy, biome);
+                }
+            }
+            y = end;
+        }
+    }
+
+    Ok(WldImport { name, version, world, biome_map })
+}
+
+/// 读取 `tileFrameImportant` 位图：为 true 的方块类型带有 frameX / frameY
+fn read_importance(r: &mut Reader) -> Result<Vec<bool>, String> {
+    let count = r.i16()?.max(0) as usize;
+    let mut importance = vec![false; count];
+    let mut byte = 0u8;
+    for (i, flag) in importance.iter_mut().enumerate() {
+        if i % 8 == 0 {
+            byte = r.u8()?;
+        }
+        *flag = byte & (1 << (i % 8)) != 0;
+    }
+    Ok(importance)
+}
+
+/// 一个方块记录中本工具关心的部分
+struct RawTile {
+    tile_type: Option<u16>,
+    liquid: u8,
+    /// 向下重复的格数（游程压缩）
+    repeat: u32,
+}
+
+fn read_tile(r: &mut Reader, version: i32, importance: &[bool]) -> Result<RawTile, String> {
+    let h1 = r.u8()?;
+    let mut h3 = 0;
+    if h1 & 0x01 != 0 {
+        let h2 = r.u8()?; // 斜坡 / 电线标志，无附加数据
+        if h2 & 0x01 != 0 {
+            h3 = r.u8()?;
+            if version >= 269 && h3 & 0x01 != 0 {
+                r.u8()?; // header4：涂层标志，无附加数据
+            }
+        }
+    }
+
+    let mut tile_type = None;
+    if h1 & 0x02 != 0 {
+        let t = if h1 & 0x20 != 0 { r.u16()? } else { r.u8()? as u16 };
+        if importance.get(t as usize).copied().unwrap_or(false) {
+            r.skip(4)?; // frameX + frameY
+        }
+        if h3 & 0x08 != 0 {
+            r.u8()?; // 方块油漆
+        }
+        tile_type = Some(t);
+    }
+
+    if h1 & 0x04 != 0 {
+        r.u8()?; // 墙
+        if h3 & 0x10 != 0 {
+            r.u8()?; // 墙油漆
+        }
+    }
+
+    let mut liquid = (h1 & 0x18) >> 3;
+    if liquid != 0 {
+        r.u8()?; // 液体量
+        if h3 & 0x80 != 0 {
+            liquid = 4; // 微光
+        }
+    }
+
+    if h3 & 0x40 != 0 {
+        r.u8()?; // 墙类型高字节
+    }
+
+    let repeat = match (h1 & 0xC0) >> 6 {
+        0 => 0,
+        1 => r.u8()? as u32,
+        _ => r.i16()?.max(0) as u32,
+    };
+
+    Ok(RawTile { tile_type, liquid, repeat })
+}
+
+/// 小端字节读取器
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn bytes(&mut self, n: usize) -> Result<&'a [u8], String> {
+        let end = self.pos.checked_add(n).filter(|&e| e <= self.data.len())
+            .ok_or_else(|| format!("文件在偏移 {} 处意外结束", self.pos))?;
+        let slice = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn skip(&mut self, n: usize) -> Result<(), String> {
+        self.bytes(n).map(|_| ())
+    }
+
+    fn seek(&mut self, pos: usize) -> Result<(), String> {
+        if pos > self.data.len() {
+            return Err(format!("区段指针越界: {pos}"));
+        }
+        self.pos = pos;
+        Ok(())
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        let b = self.bytes(2)?;
+        Ok(u16::from_le_bytes([b[0], b[1]]))
+    }
+
+    fn i16(&mut self) -> Result<i16, String> {
+        self.u16().map(|v| v as i16)
+    }
+
+    fn i32(&mut self) -> Result<i32, String> {
+        let b = self.bytes(4)?;
+        Ok(i32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    /// .NET `BinaryReader.ReadString`：7 位变长长度前缀 + UTF-8
+    fn string(&mut self) -> Result<String, String> {
+        let mut len = 0usize;
+        let mut shift = 0;
+        loop {
+            let b = self.u8()?;
+            len |= ((b & 0x7F) as usize) << shift;
+            if b & 0x80 == 0 {
+                break;
+            }
+            shift += 7;
+            if shift > 28 {
+                return Err("字符串长度前缀异常".to_string());
+            }
+        }
+        let bytes = self.bytes(len)?;
+        Ok(String::from_utf8_lossy(bytes).into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn biome_defs() -> Vec<BiomeDefinition> {
+        ["forest", "desert", "snow", "crimson"]
+            .iter()
+            .enumerate()
+            .map(|(i, key)| BiomeDefinition {
+                id: i as u8 + 1,
+                key: key.to_string(),
+                name: key.to_string(),
+                overlay_color: [0, 0, 0, 0],
+                map_color: None,
+                description: String::new(),
+                ascii_char: '?',
+                variants: Vec::new(),
+                base_weight: 100,
+                label_priority: 0,
+                decoration_density: 0.0,
+            })
+            .collect()
+    }
+
+    fn push_string(buf: &mut Vec<u8>, s: &str) {
+        buf.push(s.len() as u8);
+        buf.extend_from_slice(s.as_bytes());
+    }
+
+    /// 构造一个最小的 `.wld`：`columns[x]` 为该列自上而下的 (标志, 数据) 记录
+    fn build_wld(version: i32, width: i32, height: i32, columns: &[Vec<u8>], importance: &[u16]) -> Vec<u8> {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&version.to_le_bytes());
+        buf.extend_from_slice(MAGIC);
+        buf.push(FILE_TYPE_WORLD);
+        buf.extend_from_slice(&[0; 12]);
+        buf.extend_from_slice(&2i16.to_le_bytes());
+        let pointers_at = buf.len();
+        buf.extend_from_slice(&[0; 8]);
+        let count = 400i16;
+        buf.extend_from_slice(&count.to_le_bytes());
+        let mut bits = vec![0u8; (count as usize).div_ceil(8)];
+        for &t in importance {
+            bits[t as usize / 8] |= 1 << (t % 8);
+        }
+        buf.extend_from_slice(&bits);
+
+        let header_at = buf.len() as i32;
+        push_string(&mut buf, "测试世界");
+        push_string(&mut buf, "12345");
+        buf.extend_from_slice(&[0; 8 + 16 + 4 + 16]);
+        buf.extend_from_slice(&height.to_le_bytes());
+        buf.extend_from_slice(&width.to_le_bytes());
+
+        let tiles_at = buf.len() as i32;
+        for column in columns {
+            buf.extend_from_slice(column);
+        }
+        buf[pointers_at..pointers_at + 4].copy_from_slice(&header_at.to_le_bytes());
+        buf[pointers_at + 4..pointers_at + 8].copy_from_slice(&tiles_at.to_le_bytes());
+        buf
+    }
+
+    #[test]
+    fn parses_tiles_liquids_and_run_lengths() {
+        let columns = vec![
+            // x=0：空气 ×2（游程 1），沙 ×2（游程 1）
+            vec![0x40, 1, 0x42, 53, 1],
+            // x=1：水（液体量 255），猩红石，雪块（16 位类型 + 游程字节）
+            vec![0x08, 255, 0x02, 203, 0x62, 147, 0, 1],
+        ];
+        let bytes = build_wld(279, 2, 4, &columns, &[]);
+        let import = parse_wld(&bytes, &biome_defs(), |_, _| Ok(())).unwrap();
+
+        assert_eq!(import.name, "测试世界");
+        assert_eq!((import.world.width, import.world.height), (2, 4));
+        let col = |x: u32| (0..4).map(|y| import.world.get_or_air(x, y)).collect::<Vec<_>>();
+        assert_eq!(col(0), vec![AIR_BLOCK_ID, AIR_BLOCK_ID, 4, 4]);
+        assert_eq!(col(1), vec![30, 42, 16, 16]);
+
+        let bm = &import.biome_map;
+        assert_eq!(bm.get(0, 0), BIOME_UNASSIGNED);
+        assert_eq!(bm.get(0, 3), 2); // desert
+        assert_eq!(bm.get(1, 1), 4); // crimson
+        assert_eq!(bm.get(1, 3), 3); // snow
+    }
+
+    #[test]
+    fn skips_frames_paint_and_walls() {
+        // 宝箱（frame important）带 frameX/frameY，墙带油漆，随后一格土块
+        let columns = vec![vec![
+            0x07, 0x01, 0x18, 21, 0, 0, 0, 0, 7, 4, 9, // 标志 1~3 + 方块 + 帧 + 油漆 + 墙 + 墙油漆
+            0x02, 0,
+        ]];
+        let bytes = build_wld(279, 1, 2, &columns, &[21]);
+        let import = parse_wld(&bytes, &biome_defs(), |_, _| Ok(())).unwrap();
+        assert_eq!(import.world.get_or_air(0, 0), 40);
+        assert_eq!(import.world.get_or_air(0, 1), 2);
+    }
+
+    #[test]
+    fn rejects_old_versions_and_truncated_files() {
+        let bytes = build_wld(100, 1, 1, &[vec![0x00]], &[]);
+        assert!(parse_wld(&bytes, &biome_defs(), |_, _| Ok(())).is_err());
+
+        let bytes = build_wld(279, 1, 3, &[vec![0x00]], &[]);
+        assert!(parse_wld(&bytes, &biome_defs(), |_, _| Ok(())).is_err());
+    }
+
+    #[test]
+    fn size_check_runs_before_reading_tiles() {
+        let bytes = build_wld(279, 2, 4, &[vec![0x40, 1], vec![0x40, 1]], &[]);
+        let mut seen = None;
+        let result = parse_wld(&bytes, &biome_defs(), |w, h| {
+            seen = Some((w, h));
+            Err("超出内存预算".to_string())
+        });
+        assert_eq!(result.err().as_deref(), Some("超出内存预算"));
+        assert_eq!(seen, Some((2, 4)));
+    }
+}
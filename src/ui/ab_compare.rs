@@ -0,0 +1,359 @@
+//! # A/B 参数对比
+//!
+//! 分屏比较单个参数的敏感度：左侧 A 为当前参数，右侧 B 只把选中的一个数值参数换成滑块上的值，
+//! 种子、散布盐、其余参数、层级与跳过的步骤完全相同。两侧都在后台线程无界面生成
+//! （[`generate_variant`]），不影响画布上的世界。
+//!
+//! 拖动滑块时 B 侧随之重新生成；生成中再次拖动只记下最新的设置，当前任务结束后立即接着生成。
+
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+use egui::{Color32, ColorImage, Context, TextureHandle, TextureOptions, Vec2};
+
+use crate::core::biome::{BiomeDefinition, BiomeMap};
+use crate::generation::variant::{diff_ratio, generate_variant, ParamOverride, VariantSettings};
+use crate::generation::{GenerationPipeline, ParamType};
+use crate::rendering::thumbnail::biome_thumbnail_scaled;
+use crate::ui::theme;
+
+/// 每侧缩略图的最大宽度（像素）
+const THUMBNAIL_WIDTH: u32 = 480;
+/// 高亮差异时相同格子的亮度系数
+const SAME_TILE_DIM: f32 = 0.25;
+
+/// 可对比的数值参数
+pub struct AbParam {
+    pub algorithm_id: String,
+    pub algorithm_name: String,
+    pub key: String,
+    pub name: String,
+    pub min: f64,
+    pub max: f64,
+    pub integer: bool,
+    /// 打开窗口 / 重新生成 A 侧时流水线中的值
+    pub current: f64,
+}
+
+impl AbParam {
+    fn json_value(&self, value: f64) -> serde_json::Value {
+        if self.integer {
+            serde_json::json!(value.round() as i64)
+        } else {
+            serde_json::json!(value)
+        }
+    }
+
+    fn format(&self, value: f64) -> String {
+        if self.integer {
+            format!("{}", value.round() as i64)
+        } else {
+            format!("{value:.3}")
+        }
+    }
+}
+
+/// 列出流水线中全部数值参数（浮点 / 整数）及其当前值
+pub fn numeric_params(pipeline: &GenerationPipeline) -> Vec<AbParam> {
+    let mut list = Vec::new();
+    for algo in pipeline.algorithms() {
+        let meta = algo.meta();
+        let values = algo.get_params();
+        for def in meta.params {
+            let (min, max, integer) = match def.param_type {
+                ParamType::Float { min, max } => (min, max, false),
+                ParamType::Int { min, max } => (min as f64, max as f64, true),
+                _ => continue,
+            };
+            let current = values.get(&def.key).and_then(|v| v.as_f64()).unwrap_or(min);
+            list.push(AbParam {
+                algorithm_id: meta.id.clone(),
+                algorithm_name: meta.name.clone(),
+                key: def.key,
+                name: def.name,
+                min,
+                max,
+                integer,
+                current,
+            });
+        }
+    }
+    list
+}
+
+/// 工作线程发回的消息
+enum AbMessage {
+    Base(Result<BiomeMap, String>),
+    Variant(Result<BiomeMap, String>),
+}
+
+/// 一侧的生成结果
+struct AbSide {
+    map: BiomeMap,
+    /// 生成时的参数值文字
+    label: String,
+    texture: Option<TextureHandle>,
+}
+
+/// A/B 对比窗口的持久状态
+#[derive(Default)]
+pub struct AbCompareState {
+    params: Vec<AbParam>,
+    selected: Option<usize>,
+    /// B 侧的参数值
+    value: f64,
+    highlight_diff: bool,
+    base: Option<AbSide>,
+    variant: Option<AbSide>,
+    job: Option<Receiver<AbMessage>>,
+    /// 生成中又收到的请求（设置, 是否重新生成 A 侧），只保留最新一次
+    pending: Option<(VariantSettings, bool)>,
+    error: Option<String>,
+}
+
+/// 窗口中的用户请求
+pub enum AbCompareRequest {
+    /// 按当前流水线重新生成两侧（参数列表与当前值一并刷新）
+    Refresh,
+    /// 只重新生成 B 侧
+    Variant,
+}
+
+impl AbCompareState {
+    pub fn is_running(&self) -> bool {
+        self.job.is_some()
+    }
+
+    /// 刷新参数列表，保留按 (算法, 键) 匹配的选中项；重新选中时 B 侧的值回到当前值
+    pub fn set_params(&mut self, params: Vec<AbParam>) {
+        let selected = self.selected
+            .and_then(|i| self.params.get(i))
+            .map(|p| (p.algorithm_id.clone(), p.key.clone()));
+        self.params = params;
+        let kept = selected
+            .and_then(|(id, key)| self.params.iter().position(|p| p.algorithm_id == id && p.key == key));
+        self.selected = kept.or_else(|| (!self.params.is_empty()).then_some(0));
+        if kept.is_none() {
+            self.value = self.selected.map_or(0.0, |i| self.params[i].current);
+            self.base = None;
+            self.variant = None;
+        }
+    }
+
+    /// 启动后台生成；已有任务时记为待处理，完成后用最新的设置接着生成
+    pub fn start(&mut self, settings: VariantSettings, with_base: bool) {
+        if self.job.is_some() {
+            let with_base = with_base || self.pending.as_ref().is_some_and(|(_, base)| *base);
+            self.pending = Some((settings, with_base));
+            return;
+        }
+        let Some(param) = self.selected.and_then(|i| self.params.get(i)) else {
+            return;
+        };
+        let overridden = ParamOverride {
+            algorithm_id: param.algorithm_id.clone(),
+            key: param.key.clone(),
+            value: param.json_value(self.value),
+        };
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            if with_base {
+                let _ = tx.send(AbMessage::Base(generate_variant(&settings, None)));
+            }
+            let _ = tx.send(AbMessage::Variant(generate_variant(&settings, Some(&overridden))));
+        });
+        self.job = Some(rx);
+        self.error = None;
+    }
+
+    /// 处理工作线程的消息，任务结束后启动待处理的请求
+    fn poll(&mut self) {
+        let Some(rx) = &self.job else { return };
+        let Some(param) = self.selected.and_then(|i| self.params.get(i)) else {
+            self.job = None;
+            return;
+        };
+        let mut finished = false;
+        for message in rx.try_iter() {
+            let (result, side, label) = match message {
+                AbMessage::Base(result) => (result, &mut self.base, param.format(param.current)),
+                AbMessage::Variant(result) => {
+                    finished = true;
+                    (result, &mut self.variant, param.format(self.value))
+                }
+            };
+            match result {
+                Ok(map) => *side = Some(AbSide { map, label, texture: None }),
+                Err(e) => self.error = Some(e),
+            }
+            // 任一侧更新后两侧的差异高亮都要重建
+            for side in [&mut self.base, &mut self.variant].into_iter().flatten() {
+                side.texture = None;
+            }
+        }
+        if finished {
+            self.job = None;
+            if let Some((settings, with_base)) = self.pending.take() {
+                self.start(settings, with_base);
+            }
+        }
+    }
+}
+
+/// 把环境地图渲染为缩略图纹理；`other` 非空时与之相同的格子调暗，只突出差异
+pub fn side_texture(
+    ctx: &Context,
+    name: &str,
+    map: &BiomeMap,
+    other: Option<&BiomeMap>,
+    biomes: &[BiomeDefinition],
+) -> TextureHandle {
+    let factor = map.width.div_ceil(THUMBNAIL_WIDTH).max(1);
+    let mut thumb = biome_thumbnail_scaled(map, biomes, factor);
+    if let Some(other) = other.filter(|o| o.width == map.width && o.height == map.height) {
+        for (x, y, pixel) in thumb.enumerate_pixels_mut() {
+            if map.get(x * factor, y * factor) == other.get(x * factor, y * factor) {
+                for c in &mut pixel.0[..3] {
+                    *c = (*c as f32 * SAME_TILE_DIM) as u8;
+                }
+            }
+        }
+    }
+    let size = [thumb.width() as usize, thumb.height() as usize];
+    let image = ColorImage::from_rgba_unmultiplied(size, thumb.as_raw());
+    ctx.load_texture(name, image, TextureOptions::NEAREST)
+}
+
+/// 显示 A/B 参数对比窗口。
+pub fn show_ab_compare_window(
+    ctx: &Context,
+    open: &mut bool,
+    state: &mut AbCompareState,
+    biomes: &[BiomeDefinition],
+) -> Option<AbCompareRequest> {
+    state.poll();
+    if state.is_running() {
+        ctx.request_repaint();
+    }
+
+    // 纹理按需重建（新结果或切换了差异高亮）
+    let highlight = state.highlight_diff;
+    let (base_map, variant_map) = (state.base.as_ref().map(|s| &s.map), state.variant.as_ref().map(|s| &s.map));
+    let base_texture = state.base.as_ref().filter(|s| s.texture.is_none())
+        .map(|s| side_texture(ctx, "ab_compare_a", &s.map, variant_map.filter(|_| highlight), biomes));
+    let variant_texture = state.variant.as_ref().filter(|s| s.texture.is_none())
+        .map(|s| side_texture(ctx, "ab_compare_b", &s.map, base_map.filter(|_| highlight), biomes));
+    let diff = base_map.zip(variant_map).map(|(a, b)| diff_ratio(a, b));
+    if let (Some(side), Some(texture)) = (&mut state.base, base_texture) {
+        side.texture = Some(texture);
+    }
+    if let (Some(side), Some(texture)) = (&mut state.variant, variant_texture) {
+        side.texture = Some(texture);
+    }
+
+    let running = state.is_running();
+    let mut request = None;
+    egui::Window::new("⚖ A/B 参数对比")
+        .open(open)
+        .resizable(true)
+        .default_width(760.0)
+        .show(ctx, |ui| {
+            ui.label("同一种子下只改一个参数：左侧 A 为当前参数，右侧 B 使用滑块上的值，两侧均在后台生成完整世界。");
+            ui.separator();
+
+            if state.params.is_empty() {
+                ui.label("流水线中没有可对比的数值参数。");
+                return;
+            }
+            let selected_text = |p: &AbParam| format!("{} · {}", p.algorithm_name, p.name);
+            ui.horizontal(|ui| {
+                ui.label("参数");
+                let current = state.selected.and_then(|i| state.params.get(i)).map(selected_text).unwrap_or_default();
+                let mut selected = state.selected;
+                egui::ComboBox::from_id_source("ab_compare_param")
+                    .selected_text(current)
+                    .width(280.0)
+                    .show_ui(ui, |ui| {
+                        for (i, p) in state.params.iter().enumerate() {
+                            ui.selectable_value(&mut selected, Some(i), selected_text(p))
+                                .on_hover_text(&p.key);
+                        }
+                    });
+                if selected != state.selected {
+                    state.selected = selected;
+                    if let Some(p) = selected.and_then(|i| state.params.get(i)) {
+                        state.value = p.current;
+                    }
+                    state.variant = None;
+                    request = Some(AbCompareRequest::Variant);
+                }
+                if ui.button("↻ 按当前参数重新生成").on_hover_text("重新读取当前流水线的参数并生成两侧").clicked() {
+                    request = Some(AbCompareRequest::Refresh);
+                }
+            });
+
+            let Some(param) = state.selected.and_then(|i| state.params.get(i)) else {
+                return;
+            };
+            ui.horizontal(|ui| {
+                ui.label("B 侧取值");
+                let mut slider = egui::Slider::new(&mut state.value, param.min..=param.max);
+                if param.integer {
+                    slider = slider.integer();
+                }
+                if ui.add(slider).changed() {
+                    request = Some(AbCompareRequest::Variant);
+                }
+                if ui.small_button("= A").on_hover_text("把 B 侧取值重置为当前值").clicked() {
+                    state.value = param.current;
+                    request = Some(AbCompareRequest::Variant);
+                }
+            });
+            ui.horizontal(|ui| {
+                if ui.checkbox(&mut state.highlight_diff, "高亮差异").changed() {
+                    for side in [&mut state.base, &mut state.variant].into_iter().flatten() {
+                        side.texture = None;
+                    }
+                }
+                if let Some(diff) = diff {
+                    ui.separator();
+                    ui.label(format!("{:.2}% 的格子环境不同", diff * 100.0));
+                }
+                if running {
+                    ui.separator();
+                    ui.spinner();
+                    ui.weak("生成中…");
+                }
+            });
+            if let Some(error) = &state.error {
+                ui.colored_label(Color32::from_rgb(230, 110, 110), format!("生成失败: {error}"));
+            }
+            ui.separator();
+
+            // ── 分屏：左 A / 右 B ──
+            let key = &param.key;
+            ui.columns(2, |columns| {
+                let sides = [
+                    (&state.base, format!("A · {key} = ")),
+                    (&state.variant, format!("B · {key} = ")),
+                ];
+                for (ui, (side, prefix)) in columns.iter_mut().zip(sides) {
+                    match side {
+                        Some(side) => {
+                            ui.colored_label(theme::BLUE_LIGHT, format!("{prefix}{}", side.label));
+                            if let Some(texture) = &side.texture {
+                                let width = ui.available_width();
+                                let size = texture.size_vec2();
+                                ui.image((texture.id(), Vec2::new(width, width * size.y / size.x)));
+                            }
+                        }
+                        None => {
+                            ui.weak(format!("{prefix}…"));
+                        }
+                    }
+                }
+            });
+        });
+
+    request
+}
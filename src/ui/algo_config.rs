@@ -2,10 +2,15 @@
 //!
 //! 根据算法模块的 `PhaseMeta.params` 自动生成 UI 控件。
 //! 参数按 `ParamDef.group` 分组，以可折叠面板呈现。
+//! 顶部搜索框按 `name` / `description` / `key` 过滤参数，没有匹配项的分组整体隐藏。
+//! 算法提供参数类型名时，可把当前参数复制为 Rust 结构体字面量。
+//! 数值参数旁的「锁定」复选框标记参数拟合等自动调参时保持不变的参数；
+//! 「抖动」复选框选择按种子随机偏移的参数，偏移幅度为顶部的全局「参数抖动」。
 
 use egui::{Context, Ui};
 
 use crate::generation::algorithm::{ParamDef, ParamType, PhaseAlgorithm, PhaseMeta};
+use crate::generation::param_jitter::MAX_JITTER_STRENGTH;
 use crate::ui::theme;
 
 /// 算法配置窗口的返回值
@@ -14,6 +19,19 @@ pub struct AlgoConfigResult {
     pub changed: bool,
     /// 用户是否请求重新执行当前步骤
     pub replay_requested: bool,
+    /// 切换某个参数的锁定状态 `(参数键, 是否锁定)`
+    pub toggle_lock: Option<(String, bool)>,
+    /// 切换某个参数是否参与抖动 `(参数键, 是否参与)`
+    pub toggle_jitter: Option<(String, bool)>,
+    /// 修改后的全局参数抖动幅度
+    pub jitter_strength: Option<f64>,
+}
+
+/// 参数行上的复选框操作
+#[derive(Default)]
+struct ParamToggles {
+    lock: Option<(String, bool)>,
+    jitter: Option<(String, bool)>,
 }
 
 /// 显示算法参数配置窗口。
@@ -21,11 +39,21 @@ pub fn show_algo_config_window(
     ctx: &Context,
     open: &mut bool,
     algorithm: &mut Box<dyn PhaseAlgorithm>,
+    locked: &[String],
+    jittered: &[String],
+    jitter_strength: f64,
 ) -> AlgoConfigResult {
     let meta = algorithm.meta();
+    let type_name = algorithm.params_type_name();
     let mut params = algorithm.get_params();
     let mut changed = false;
     let mut replay = false;
+    let mut toggles = ParamToggles::default();
+    let mut new_jitter_strength = None;
+
+    // 搜索文本跨帧保存在 egui 内存中
+    let search_id = egui::Id::new("algo_config_search");
+    let mut search = ctx.data_mut(|data| data.get_temp::<String>(search_id).unwrap_or_default());
 
     egui::Window::new(format!("⚙ {} — 参数配置", meta.name))
         .open(open)
@@ -40,10 +68,36 @@ pub fn show_algo_config_window(
             ui.label(&meta.description);
             ui.separator();
 
+            ui.horizontal(|ui| {
+                let mut percent = jitter_strength * 100.0;
+                let max = MAX_JITTER_STRENGTH * 100.0;
+                ui.label("🎲 参数抖动 ±");
+                if ui.add(egui::Slider::new(&mut percent, 0.0..=max).suffix("%").max_decimals(0))
+                    .on_hover_text("勾选「抖动」的数值参数在执行时按种子偏移最多 ±此比例（所有算法共用），\n同一种子结果不变，不同种子的世界结构更多样；0 = 关闭。修改后重新执行生效")
+                    .changed()
+                {
+                    new_jitter_strength = Some(percent / 100.0);
+                }
+            });
+            ui.add_space(4.0);
+
+            ui.horizontal(|ui| {
+                ui.label("🔍");
+                ui.add(
+                    egui::TextEdit::singleline(&mut search)
+                        .hint_text("搜索参数（名称 / 描述 / key）")
+                        .desired_width(220.0),
+                );
+                if !search.is_empty() && ui.small_button("✖").on_hover_text("清除搜索").clicked() {
+                    search.clear();
+                }
+            });
+            ui.add_space(4.0);
+
             egui::ScrollArea::vertical()
                 .max_height(ui.available_height() - 40.0)
                 .show(ui, |ui| {
-                    changed |= render_grouped_params(ui, &meta, &mut params);
+                    changed |= render_grouped_params(ui, &meta, &mut params, search.trim(), locked, jittered, &mut toggles);
                 });
 
             ui.separator();
@@ -63,9 +117,19 @@ pub fn show_algo_config_window(
                     }
                     changed = true;
                 }
+                if let Some(type_name) = type_name {
+                    let copy = ui.button("📋 复制为 Rust 代码")
+                        .on_hover_text(format!("把当前参数导出为 {type_name} {{ .. }} 字面量，可直接写入默认值"));
+                    if copy.clicked() {
+                        let code = rust_literal(type_name, &meta, &params);
+                        ui.output_mut(|o| o.copied_text = code);
+                    }
+                }
             });
         });
 
+    ctx.data_mut(|data| data.insert_temp(search_id, search));
+
     if changed {
         algorithm.set_params(&params);
     }
@@ -73,14 +137,62 @@ pub fn show_algo_config_window(
     AlgoConfigResult {
         changed,
         replay_requested: replay,
+        toggle_lock: toggles.lock,
+        toggle_jitter: toggles.jitter,
+        jitter_strength: new_jitter_strength,
     }
 }
 
+/// 把参数 JSON 对象写成 Rust 结构体字面量。
+///
+/// 字段按 `meta.params` 的声明顺序排列，未声明的字段按 key 排在最后；
+/// 浮点数总带小数点，字符串写成 `"…".to_string()`。
+fn rust_literal(type_name: &str, meta: &PhaseMeta, params: &serde_json::Value) -> String {
+    let Some(obj) = params.as_object() else {
+        return format!("{type_name}::default()");
+    };
+    let mut keys: Vec<&String> = meta.params.iter()
+        .filter_map(|def| obj.get_key_value(&def.key).map(|(k, _)| k))
+        .collect();
+    let mut rest: Vec<&String> = obj.keys().filter(|k| !keys.contains(k)).collect();
+    rest.sort();
+    keys.extend(rest);
+
+    let mut code = format!("{type_name} {{\n");
+    for key in keys {
+        let value = match &obj[key] {
+            serde_json::Value::Number(n) if n.is_f64() => format!("{:?}", n.as_f64().unwrap_or(0.0)),
+            serde_json::Value::String(s) => format!("{s:?}.to_string()"),
+            other => other.to_string(),
+        };
+        code.push_str(&format!("    {key}: {value},\n"));
+    }
+    code.push('}');
+    code
+}
+
+/// 参数是否匹配搜索文本（忽略大小写；空文本匹配全部）
+fn param_matches(def: &ParamDef, filter: &str) -> bool {
+    if filter.is_empty() {
+        return true;
+    }
+    let filter = filter.to_lowercase();
+    def.name.to_lowercase().contains(&filter)
+        || def.description.to_lowercase().contains(&filter)
+        || def.key.to_lowercase().contains(&filter)
+}
+
 /// 将参数按 group 分组渲染，有 group 的用 CollapsingHeader，无 group 的直接渲染。
+///
+/// `filter` 非空时只渲染匹配的参数，分组直接展开显示，空分组隐藏。
 fn render_grouped_params(
     ui: &mut Ui,
     meta: &PhaseMeta,
     params: &mut serde_json::Value,
+    filter: &str,
+    locked: &[String],
+    jittered: &[String],
+    toggles: &mut ParamToggles,
 ) -> bool {
     let mut changed = false;
 
@@ -97,16 +209,35 @@ fn render_grouped_params(
         let group_params: Vec<&ParamDef> = meta
             .params
             .iter()
-            .filter(|p| &p.group == group)
+            .filter(|p| &p.group == group && param_matches(p, filter))
             .collect();
 
+        if group_params.is_empty() {
+            continue;
+        }
+
         match group {
             None => {
                 // 无分组的参数直接渲染
                 for param_def in &group_params {
-                    changed |= render_param(ui, param_def, params);
+                    changed |= render_param(ui, param_def, params, locked, jittered, toggles);
                 }
             }
+            Some(group_name) if !filter.is_empty() => {
+                // 搜索中：分组直接展开，避免匹配项藏在折叠面板里
+                ui.horizontal(|ui| {
+                    ui.strong(group_name);
+                    ui.colored_label(
+                        theme::TEXT_MUTED,
+                        format!("({} 个匹配)", group_params.len()),
+                    );
+                });
+                ui.indent(group_name, |ui| {
+                    for param_def in &group_params {
+                        changed |= render_param(ui, param_def, params, locked, jittered, toggles);
+                    }
+                });
+            }
             Some(group_name) => {
                 // 有分组的参数用可折叠面板
                 let id = ui.make_persistent_id(group_name);
@@ -127,7 +258,7 @@ fn render_grouped_params(
                 .body(|ui| {
                     ui.indent(group_name, |ui| {
                         for param_def in &group_params {
-                            changed |= render_param(ui, param_def, params);
+                            changed |= render_param(ui, param_def, params, locked, jittered, toggles);
                         }
                     });
                 });
@@ -135,11 +266,22 @@ fn render_grouped_params(
         }
     }
 
+    if !filter.is_empty() && !meta.params.iter().any(|p| param_matches(p, filter)) {
+        ui.colored_label(theme::TEXT_MUTED, "没有匹配的参数");
+    }
+
     changed
 }
 
 /// 根据 ParamDef 的类型渲染对应的 UI 控件，返回是否发生了修改。
-fn render_param(ui: &mut Ui, def: &ParamDef, params: &mut serde_json::Value) -> bool {
+fn render_param(
+    ui: &mut Ui,
+    def: &ParamDef,
+    params: &mut serde_json::Value,
+    locked: &[String],
+    jittered: &[String],
+    toggles: &mut ParamToggles,
+) -> bool {
     let mut changed = false;
 
     ui.horizontal(|ui| {
@@ -147,6 +289,23 @@ fn render_param(ui: &mut Ui, def: &ParamDef, params: &mut serde_json::Value) ->
         if !def.description.is_empty() {
             ui.label("ℹ").on_hover_text(&def.description);
         }
+        // 只有数值参数会被自动调参扰动、参与抖动
+        if matches!(def.param_type, ParamType::Float { .. } | ParamType::Int { .. }) {
+            let mut is_locked = locked.contains(&def.key);
+            if ui.checkbox(&mut is_locked, "🔒 锁定")
+                .on_hover_text("参数拟合时保持此参数不变")
+                .changed()
+            {
+                toggles.lock = Some((def.key.clone(), is_locked));
+            }
+            let mut is_jittered = jittered.contains(&def.key);
+            if ui.checkbox(&mut is_jittered, "🎲 抖动")
+                .on_hover_text("执行时按种子在「参数抖动」幅度内随机偏移此参数（不修改这里保存的值）")
+                .changed()
+            {
+                toggles.jitter = Some((def.key.clone(), is_jittered));
+            }
+        }
     });
 
     let obj = match params.as_object_mut() {
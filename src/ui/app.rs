@@ -1,36 +1,82 @@
 use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver};
 use std::sync::{Arc, Mutex};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use eframe::egui;
-use egui::{Color32, FontData, FontDefinitions, FontFamily, TextureHandle};
+use egui::{Color32, FontData, FontDefinitions, FontFamily, Rect, TextureHandle};
 
+use crate::algorithms::biome_division::{DiffusionTrace, DIFFUSION_TRACE_KEY};
 use crate::config::biome::load_biomes_config;
+use crate::config::lang::load_lang_config;
 use crate::config::blocks::load_blocks_config;
+use crate::config::featured_seeds::load_featured_seeds;
 use crate::config::world::{load_world_config, WorldConfig};
-use crate::core::biome::{build_biome_definitions, get_biome_context, BiomeDefinition};
+use crate::core::biome::{build_biome_definitions, get_biome_context, BiomeDefinition, BiomeId, BIOME_UNASSIGNED};
 use crate::core::block::{build_block_definitions, BlockDefinition};
+use crate::core::coords::{format_terraria_feet, spawn_point, to_terraria_feet, SURFACE_REFERENCE_LAYER};
+use crate::core::geometry;
+use crate::core::log;
 use crate::core::world::{World, WorldProfile};
-use crate::generation::{build_pipeline, GenerationPipeline, WorldSnapshot, export_png,
-    AdaptiveBatchSize, TextureUpdateThrottle};
-use crate::rendering::canvas::{build_color_lut, build_color_map, world_to_color_image, world_to_color_image_downsampled};
-use crate::rendering::gl_canvas::GlCanvasState;
-use crate::rendering::viewport::ViewportState;
+use crate::generation::{build_pipeline, GenerationError, GenerationPipeline, WorldSnapshot,
+    AdaptiveBatchSize, ManualStepThrottle, TextureUpdateThrottle};
+use crate::rendering::ascii::{biome_ascii_art, ASCII_COLS, ASCII_ROWS};
+use crate::rendering::biome_ids::{export_biome_ids, import_biome_ids};
+use crate::rendering::canvas::{biome_minimap_image, build_color_lut, build_color_map, world_to_color_image, world_to_color_image_downsampled};
+use crate::rendering::gl_canvas::{GlCanvasState, UnassignedHighlight};
+use crate::rendering::heightmap::export_heightmap;
+use crate::rendering::legend::biome_legend;
+use crate::rendering::pattern::BiomePattern;
+use crate::rendering::scale_bar::ScaleBarCorner;
+use crate::rendering::summary_card::{world_summary_card, SummaryCardInfo};
+use crate::rendering::viewport::{ViewportState, DEFAULT_ZOOM};
 use crate::storage::engine_config::EngineConfig;
+use crate::storage::paths::{crash_checkpoint_path, last_session_path};
 use crate::storage::runtime as app_runtime;
+use crate::storage::shape_library::ShapeLibrary;
+use crate::storage::wld;
 use crate::ui::algo_config::show_algo_config_window;
-use crate::ui::canvas_view::show_canvas;
-use crate::ui::control_panel::{show_control_panel, ControlAction, WorldSizeSelection};
+use crate::ui::biome_search::{show_biome_search_window, BiomeSearchState};
+use crate::ui::biome_paint::{paint_disc, show_biome_paint_window, BiomePaintState};
+use crate::ui::bookmarks::{draw_bookmark_pins, show_bookmarks_window, BookmarkState};
+use crate::ui::canvas_view::{draw_partial_watermark, show_canvas, CanvasDisplayOptions, visible_biome_fractions};
+use crate::ui::confirm_dialog::show_confirm_dialog;
+use crate::ui::control_panel::{show_control_panel, ControlAction, ControlPanelState, WorldSizeSelection};
+use crate::ui::log_console::{show_log_console_window, LogConsoleState};
+use crate::generation::completion::{CompletionHooks, GenerationComplete};
+use crate::generation::export_worker::{ExportJobState, ExportQueue};
+use crate::generation::frame_recorder::FrameRecorder;
+use crate::generation::snapshot::{DepthShading, LayerAnnotations, PngLayout, ScaleBar};
+use crate::generation::param_fit::FIT_PHASE_ID;
+use crate::generation::size_strip::{self, StripSettings};
+use crate::generation::variant::VariantSettings;
+use crate::generation::validation::{self, WorldValidationReport};
+use crate::ui::export_progress::show_export_progress;
+use crate::ui::export_config::{show_padded_export_window, ExportOverlaySettings, PaddedExportSettings};
+use crate::ui::biome_graph::{show_biome_graph_window, BiomeGraph, BiomeGraphState};
+use crate::ui::block_palette::{show_block_palette_window, BlockCounts, BlockPaletteState};
+use crate::ui::diffusion_arrows::draw_diffusion_arrows;
+use crate::ui::dry_run::{show_dry_run_window, DryRunState};
 use crate::ui::geo_preview::{show_geo_preview_window, GeoPreviewState};
 use crate::ui::layer_config::show_layer_config_window;
-use crate::ui::overlay_config::{show_overlay_config_window, OverlaySettings};
+use crate::ui::overlay_config::{show_overlay_config_window, MinimapSource, OverlaySettings, SizeUnit};
+use crate::ui::param_fit::{show_param_fit_window, ParamFitRequest, ParamFitState};
+use crate::ui::ab_compare::{numeric_params, show_ab_compare_window, AbCompareRequest, AbCompareState};
 use crate::ui::perf_panel::show_perf_panel_window;
-use crate::ui::shape_sandbox::{show_shape_sandbox_window, ShapeSandboxState};
+use crate::ui::seed_trace::show_seed_trace_overlay;
+use crate::ui::step_preview::{preview_size, StepPreviewState};
+use crate::ui::shape_sandbox::{show_shape_sandbox_window, SandboxApply, ShapeSandboxState};
+use crate::ui::spawn_suggest::{
+    draw_spawn_markers, show_spawn_suggest_window, suggest_spawns, SpawnSuggestRequest, SpawnSuggestState,
+    SpawnSuggestions,
+};
 use crate::ui::splash::show_splash;
-use crate::ui::status_bar::show_status_bar;
+use crate::ui::status_bar::{show_status_bar, StatusInfo};
+use crate::ui::i18n::{self, tr_args};
 use crate::ui::theme;
 
-const CJK_FONT_BYTES: &[u8] = include_bytes!("../assets/fonts/NotoSansCJKsc-Regular.otf");
+pub(crate) const CJK_FONT_BYTES: &[u8] = include_bytes!("../assets/fonts/NotoSansCJKsc-Regular.otf");
 const SYMBOLS_FONT_BYTES: &[u8] = include_bytes!("../assets/fonts/NotoSansSymbols2-Regular.ttf");
 
 pub struct LianWorldApp {
@@ -47,6 +93,8 @@ pub struct LianWorldApp {
     custom_width: String,
     /// 自定义高度输入框内容
     custom_height: String,
+    /// 因超出内存预算被拒绝的自定义尺寸，输入不变时不再重复提示
+    rejected_custom_size: Option<(u32, u32)>,
     world: World,
     world_profile: WorldProfile,
 
@@ -54,10 +102,18 @@ pub struct LianWorldApp {
     pipeline: GenerationPipeline,
     /// 是否正在后台逐帧执行（替代同步 run_all 阻塞 UI）
     running_to_end: bool,
+    /// 是否正在播放：按 `play_interval_ms` 间隔自动小步前进
+    playing: bool,
+    /// 播放时每一小步的间隔（毫秒）
+    play_interval_ms: u32,
+    /// 播放模式上一次步进的时间
+    last_play_step: Instant,
     /// 自适应批量大小控制器（替代硬编码 STEPS_PER_FRAME）
     adaptive_batch: AdaptiveBatchSize,
     /// 智能纹理更新节流器
     texture_throttle: Option<TextureUpdateThrottle>,
+    /// 手动步进的纹理刷新节流（连续点击后空闲再刷新）
+    manual_throttle: ManualStepThrottle,
     /// 引擎调优配置
     engine_config: EngineConfig,
 
@@ -75,6 +131,12 @@ pub struct LianWorldApp {
     // ── UI ──
     last_status: String,
     hover_status: String,
+    /// 上一帧视口内各环境的占比（状态栏彩色条）
+    viewport_biomes: Vec<(BiomeId, f32)>,
+    /// 环境地图短哈希：地图变化时清空，需要显示时再计算
+    world_hash: Option<String>,
+    /// 环境边界总长与总格数（状态栏统计）：与哈希同时清空、同时计算
+    world_boundary: Option<(u64, u64)>,
     overlay: OverlaySettings,
     show_overlay_config: bool,
     show_layer_config: bool,
@@ -83,8 +145,40 @@ pub struct LianWorldApp {
     show_geo_preview: bool,
     /// 几何预览窗口状态
     geo_preview_state: GeoPreviewState,
+    /// 是否显示环境邻接图窗口
+    show_biome_graph: bool,
+    /// 环境邻接图统计结果
+    biome_graph_state: BiomeGraphState,
+    /// 是否显示方块调色板窗口
+    show_block_palette: bool,
+    /// 方块调色板的筛选、选中项与格子数统计
+    block_palette_state: BlockPaletteState,
+    /// 是否显示出生点建议窗口（打开时在画布上标出候选位置）
+    show_spawn_suggest: bool,
+    /// 书签窗口
+    show_bookmarks: bool,
+    bookmark_state: BookmarkState,
+    /// 环境画笔窗口
+    show_biome_paint: bool,
+    biome_paint_state: BiomePaintState,
+    show_biome_search: bool,
+    biome_search_state: BiomeSearchState,
+    /// 出生点建议结果
+    spawn_suggest_state: SpawnSuggestState,
+    /// 是否显示预检窗口
+    show_dry_run: bool,
+    /// 最近一次预检结果
+    dry_run_state: DryRunState,
+    show_param_fit: bool,
+    param_fit_state: ParamFitState,
+    show_ab_compare: bool,
+    ab_compare_state: AbCompareState,
+    /// 步骤启用复选框的悬停预览
+    step_preview: StepPreviewState,
     /// 图形 API 沙箱实例列表（支持多开）
     shape_sandboxes: Vec<ShapeSandboxState>,
+    /// 沙箱共用的图形库（shape_library.json）
+    shape_library: ShapeLibrary,
     /// 沙箱 ID 计数器
     next_sandbox_id: usize,
     /// 是否已经开始过生成（用于控制 splash 显示）
@@ -93,6 +187,77 @@ pub struct LianWorldApp {
     seed_input: String,
     /// 是否显示性能面板
     show_perf_panel: bool,
+    /// 是否显示日志控制台
+    show_log_console: bool,
+    log_console: LogConsoleState,
+    /// 后台 PNG 导出队列
+    export_queue: ExportQueue,
+    /// 是否显示固定尺寸导出窗口
+    show_padded_export: bool,
+    /// 固定尺寸导出设置
+    padded_export: PaddedExportSettings,
+    /// 导出专用的覆盖层设置（与画布可视化开关分离）
+    export_overlay: ExportOverlaySettings,
+    /// 后台生成中的尺寸对比图（完成后返回保存路径）
+    size_strip_job: Option<Receiver<Result<PathBuf, String>>>,
+    /// 步骤帧录制（开启时每执行一个子步骤保存一张预览 PNG）
+    frame_recorder: Option<FrameRecorder>,
+    /// 生成完成钩子（性能日志、CSV 日志等）
+    completion_hooks: CompletionHooks,
+    /// 上一次破坏性更改前的配置（单级撤销）
+    undo_backup: Option<ConfigBackup>,
+    /// 本次打开层级配置窗口后是否已记录撤销点
+    layer_undo_captured: bool,
+    /// 等待确认的重置操作（值为确认后是否接着执行到底）
+    pending_reset: Option<bool>,
+    /// 世界尚未生成完成时等待确认的导出
+    pending_export: Option<WorldExport>,
+    /// 步骤列表中键盘焦点所在的子步骤（flat 索引）
+    step_focus: Option<usize>,
+    /// 启动时读到的上次会话，等待用户确认是否恢复
+    pending_session: Option<WorldSnapshot>,
+    /// `pending_session` 来自崩溃恢复检查点（上次异常退出）而不是退出时保存的会话
+    pending_session_crashed: bool,
+    /// 上次写入崩溃恢复检查点的时刻与当时的已执行子步骤数
+    checkpoint_at: Instant,
+    checkpoint_steps: usize,
+}
+
+/// 导出当前世界画面的操作；世界未生成完成时先确认
+#[derive(Debug, Clone, Copy)]
+enum WorldExport {
+    Png,
+    PngBiome,
+    Padded,
+    Heightmap,
+    BiomeLayers,
+    BiomeIds,
+    SummaryCard,
+}
+
+impl WorldExport {
+    fn label(self) -> &'static str {
+        match self {
+            WorldExport::Png => "导出 PNG",
+            WorldExport::PngBiome => "导出 PNG (含环境)",
+            WorldExport::Padded => "固定尺寸导出",
+            WorldExport::Heightmap => "导出高度图",
+            WorldExport::BiomeLayers => "导出分环境图层",
+            WorldExport::BiomeIds => "导出环境 ID 图",
+            WorldExport::SummaryCard => "导出世界卡片",
+        }
+    }
+}
+
+/// 可撤销的世界配置：尺寸 + 层级 + 种子，以及当时已执行到的子步骤
+#[derive(Debug, Clone)]
+struct ConfigBackup {
+    size_key: String,
+    width: u32,
+    height: u32,
+    layers: Vec<crate::core::layer::LayerDefinition>,
+    seed: u64,
+    executed: usize,
 }
 
 impl LianWorldApp {
@@ -106,6 +271,7 @@ impl LianWorldApp {
         // 将校准后的阈值应用到全局原子变量
         crate::core::geometry::set_parallel_threshold(engine_config.parallel_pixel_threshold);
 
+        i18n::init(load_lang_config().expect("lang.json 加载失败"));
         let blocks_cfg = load_blocks_config().expect("blocks.json 加载失败");
         let biomes_cfg = load_biomes_config().expect("biome.json 加载失败");
         let world_cfg = load_world_config().expect("world.json 加载失败");
@@ -125,7 +291,11 @@ impl LianWorldApp {
         let world = world_profile.create_world();
 
         let seed = rand::random::<u64>();
-        let pipeline = build_pipeline(seed, biomes.clone(), &world_profile.layers);
+        let mut pipeline = build_pipeline(seed, biomes.clone(), &world_profile.layers);
+        pipeline.set_overlap_check(engine_config.debug_overlap_check);
+        pipeline.set_order_tracking(engine_config.debug_assign_order);
+        pipeline.set_size_mixing(engine_config.seed_size_mixing);
+        pipeline.set_rng_kind(engine_config.rng_kind);
 
         let image = world_to_color_image(&world, &color_lut);
         let texture = Some(cc.egui_ctx.load_texture(
@@ -135,9 +305,19 @@ impl LianWorldApp {
         ));
 
         let gl_canvas = Arc::new(Mutex::new(GlCanvasState::new()));
+        gl_canvas.lock().unwrap().set_checker_style(
+            engine_config.checker_tile,
+            engine_config.checker_color_a,
+            engine_config.checker_color_b,
+        );
+        gl_canvas.lock().unwrap().set_texture_size_limit(engine_config.max_texture_size);
 
         // 从 runtime.json 恢复 UI 状态
-        let (saved_size, saved_overlay) = load_runtime_ui_state();
+        let (saved_size, saved_overlay, saved_export_overlay) = load_runtime_ui_state();
+        let (shape_library, library_error) = ShapeLibrary::load();
+        if let Some(e) = library_error {
+            log::warn(e);
+        }
 
         let mut app = Self {
             world_cfg,
@@ -148,12 +328,17 @@ impl LianWorldApp {
             world_size: saved_size,
             custom_width: String::new(),
             custom_height: String::new(),
+            rejected_custom_size: None,
             world,
             world_profile,
             pipeline,
             running_to_end: false,
+            playing: false,
+            play_interval_ms: 300,
+            last_play_step: Instant::now(),
             adaptive_batch: AdaptiveBatchSize::from_config(&engine_config),
             texture_throttle: None,
+            manual_throttle: ManualStepThrottle::default(),
             engine_config,
             viewport: ViewportState::default(),
             texture,
@@ -163,26 +348,104 @@ impl LianWorldApp {
             gl_canvas,
             last_status: "世界初始化完成".to_string(),
             hover_status: String::new(),
+            viewport_biomes: Vec::new(),
+            world_hash: None,
+            world_boundary: None,
             overlay: saved_overlay,
             show_overlay_config: false,
             show_layer_config: false,
             show_algo_config: false,
             show_geo_preview: false,
             geo_preview_state: GeoPreviewState::default(),
+            show_biome_graph: false,
+            biome_graph_state: BiomeGraphState::default(),
+            show_block_palette: false,
+            block_palette_state: BlockPaletteState::default(),
+            show_spawn_suggest: false,
+            show_bookmarks: false,
+            bookmark_state: BookmarkState::default(),
+            show_biome_paint: false,
+            biome_paint_state: BiomePaintState::default(),
+            show_biome_search: false,
+            biome_search_state: BiomeSearchState::default(),
+            spawn_suggest_state: SpawnSuggestState::default(),
+            show_dry_run: false,
+            dry_run_state: DryRunState::default(),
+            show_param_fit: false,
+            param_fit_state: ParamFitState::default(),
+            show_ab_compare: false,
+            ab_compare_state: AbCompareState::default(),
+            step_preview: StepPreviewState::default(),
             shape_sandboxes: Vec::new(),
+            shape_library,
             next_sandbox_id: 0,
             has_started_generation: false,
             seed_input: String::new(),
             show_perf_panel: false,
+            show_log_console: false,
+            log_console: LogConsoleState::default(),
+            export_queue: ExportQueue::new(),
+            show_padded_export: false,
+            padded_export: PaddedExportSettings::default(),
+            export_overlay: saved_export_overlay,
+            size_strip_job: None,
+            frame_recorder: None,
+            completion_hooks: CompletionHooks::default(),
+            undo_backup: None,
+            layer_undo_captured: false,
+            pending_reset: None,
+            pending_export: None,
+            step_focus: None,
+            pending_session: None,
+            pending_session_crashed: false,
+            checkpoint_at: Instant::now(),
+            checkpoint_steps: 0,
         };
 
+        // 内置的生成完成钩子：性能日志、CSV 生成日志
+        app.register_completion_hook("perf_log", |done| {
+            save_perf_log(done.pipeline, done.world, done.total_ms, done.config.perf_log_max_files);
+        });
+        app.register_completion_hook("csv_log", |done| {
+            if done.config.csv_log {
+                save_csv_log(done.pipeline, done.world, done.biomes, done.total_ms);
+            }
+        });
+
         // 根据恢复的 world_size 切换
         app.apply_world_size_change();
+        // 启动时的尺寸恢复不是用户操作，不提供撤销
+        app.undo_backup = None;
+        // 检查点仍在说明上次没有正常退出：优先从检查点恢复，它比退出时的会话更新
+        let checkpoint = crash_checkpoint_path();
+        if checkpoint.exists() {
+            match app.load_snapshot(&checkpoint) {
+                Ok(snapshot) => {
+                    app.pending_session = Some(snapshot);
+                    app.pending_session_crashed = true;
+                }
+                Err(e) => log::warn(format!("崩溃恢复检查点读取失败: {e}")),
+            }
+        }
+        if app.pending_session.is_none() && app.engine_config.auto_save_session {
+            let path = last_session_path();
+            if path.exists() {
+                match app.load_snapshot(&path) {
+                    Ok(snapshot) => app.pending_session = Some(snapshot),
+                    Err(e) => log::warn(format!("上次会话读取失败: {e}")),
+                }
+            }
+        }
         app.refresh_texture_if_dirty(&cc.egui_ctx);
 
         app
     }
 
+    /// 注册生成完成钩子：每次「执行到底」跑完后按注册顺序调用，`name` 用于日志定位
+    pub fn register_completion_hook(&mut self, name: &'static str, hook: impl FnMut(&GenerationComplete) + 'static) {
+        self.completion_hooks.register(name, hook);
+    }
+
     // ── world size change ───────────────────────────────────
 
     fn world_size_key(&self) -> &str {
@@ -201,6 +464,25 @@ impl LianWorldApp {
         Some((w, h))
     }
 
+    /// 世界被替换后按偏好重置视口，或保持缩放并按比例重新定位视图中心
+    fn update_viewport_for_world_change(&mut self, old_size: (u32, u32)) {
+        if self.engine_config.reset_viewport_on_world_change {
+            self.viewport.reset();
+        } else {
+            self.viewport.reanchor(old_size, (self.world.width, self.world.height));
+        }
+    }
+
+    /// 检查世界尺寸是否在内存预算内；超出时返回「宽×高：预计占用与预算」说明
+    fn check_memory_budget(&self, width: u32, height: u32) -> Result<(), String> {
+        self.engine_config.check_memory_budget(width, height).map_err(|estimate| {
+            format!(
+                "{width}×{height}：预计占用约 {estimate} MB，超出内存预算 {} MB（可在性能面板调整）",
+                self.engine_config.memory_budget_mb
+            )
+        })
+    }
+
     fn apply_world_size_change(&mut self) {
         let target = self.world_size_key();
         let custom_size = if self.world_size == WorldSizeSelection::Custom {
@@ -229,50 +511,886 @@ impl LianWorldApp {
         let profile = match profile {
             Ok(p) => p,
             Err(e) => {
-                self.last_status = format!("尺寸配置无效: {e}");
+                self.set_error_status(format!("尺寸配置无效: {e}"));
                 return;
             }
         };
+        if custom_size.is_some() && custom_size == self.rejected_custom_size {
+            return;
+        }
+        if let Err(reason) = self.check_memory_budget(profile.size.width, profile.size.height) {
+            self.set_error_status(format!("未切换到 {reason}"));
+            // 预设尺寸退回当前世界的选项；自定义尺寸保留输入，记下以免每帧重复提示
+            if custom_size.is_some() {
+                self.rejected_custom_size = custom_size;
+            } else {
+                self.world_size = WorldSizeSelection::from_key(&self.world_profile.size.key);
+            }
+            return;
+        }
+        self.rejected_custom_size = None;
+        self.capture_undo_backup();
         self.world_profile = profile;
         // 重新加载 runtime.json 中的层级配置，避免切换尺寸后丢失
         load_runtime_layers(&mut self.world_profile.layers);
+        let old_size = (self.world.width, self.world.height);
         self.world = self.world_profile.create_world();
         self.pipeline.reset_all(&mut self.world);
-        self.viewport.reset();
+        self.update_viewport_for_world_change(old_size);
         self.texture_dirty = true;
-        self.last_status = format!(
+        self.set_status(format!(
             "已切换: {} ({}×{})",
             self.world_profile.size.description, self.world.width, self.world.height
-        );        // 保存 UI 状态
-        save_runtime_ui_state(self.world_size, &self.overlay);    }
+        ));
+        // 保存 UI 状态
+        save_runtime_ui_state(self.world_size, &self.overlay, &self.export_overlay);
+    }
+
+    /// 视口平滑聚焦到最后执行步骤的形状记录（包围盒之并，裁剪到世界范围）；没有记录时不动
+    fn frame_last_step(&mut self) {
+        let Some(bbox) = self.pipeline.last_executed_shape_log()
+            .and_then(|log| log.iter().map(|r| r.bbox).reduce(geometry::BoundingBox::union))
+        else {
+            return;
+        };
+        let bbox = bbox.intersect(geometry::BoundingBox::new(0, 0, self.world.width as i32, self.world.height as i32));
+        if bbox.is_empty() {
+            return;
+        }
+        self.viewport.animate_to_rect([
+            bbox.x_min as f32, bbox.y_min as f32,
+            bbox.x_max as f32, bbox.y_max as f32,
+        ]);
+    }
+
+    // ── status ──────────────────────────────────────────────
+
+    /// 更新状态栏并写入日志控制台
+    fn set_status(&mut self, message: String) {
+        log::info(message.as_str());
+        self.last_status = message;
+    }
+
+    /// 同 [`set_status`](Self::set_status)，以错误级别写入日志
+    fn set_error_status(&mut self, message: String) {
+        log::error(message.as_str());
+        self.last_status = message;
+    }
+
+    /// 显示生成步骤的错误，并按错误类型附上处理提示
+    fn set_step_error_status(&mut self, prefix: &str, error: &GenerationError) {
+        let hint = match error.root() {
+            GenerationError::InvalidParam { .. } => "，请在算法参数中修改",
+            GenerationError::MissingBiome(_) | GenerationError::MissingLayer(_) => "，请检查环境 / 层级配置",
+            GenerationError::File { .. } => "，请检查特征文件",
+            GenerationError::MapSizeMismatch { .. } => "，请重新初始化",
+            _ => "",
+        };
+        self.set_error_status(format!("{prefix}: {error}{hint}"));
+    }
+
+    // ── undo ────────────────────────────────────────────────
+
+    /// 记录当前配置，供「撤销上次更改」恢复
+    fn capture_undo_backup(&mut self) {
+        self.undo_backup = Some(ConfigBackup {
+            size_key: self.world_profile.size.key.clone(),
+            width: self.world.width,
+            height: self.world.height,
+            layers: self.world_profile.layers.clone(),
+            seed: self.pipeline.seed(),
+            executed: self.pipeline.executed_sub_steps(),
+        });
+    }
+
+    /// 恢复上次记录的配置并重新执行到当时的子步骤。
+    ///
+    /// 恢复前会把当前配置存为新的撤销点，因此再次撤销即可重做。
+    fn undo_config_change(&mut self) {
+        let Some(backup) = self.undo_backup.take() else {
+            return;
+        };
+        let custom_size = if backup.size_key == "custom" {
+            Some((backup.width, backup.height))
+        } else {
+            None
+        };
+        let profile = match WorldProfile::from_config(&self.world_cfg, &backup.size_key, custom_size) {
+            Ok(p) => p,
+            Err(e) => {
+                self.set_error_status(format!("撤销失败: {e}"));
+                return;
+            }
+        };
+
+        self.capture_undo_backup();
+        self.layer_undo_captured = false;
+
+        self.world_size = match backup.size_key.as_str() {
+            "medium" => WorldSizeSelection::Medium,
+            "large" => WorldSizeSelection::Large,
+            "custom" => WorldSizeSelection::Custom,
+            _ => WorldSizeSelection::Small,
+        };
+        if custom_size.is_some() {
+            self.custom_width = backup.width.to_string();
+            self.custom_height = backup.height.to_string();
+        }
+        self.world_profile = profile;
+        self.world_profile.layers = backup.layers;
+        self.world = self.world_profile.create_world();
+        self.pipeline.set_seed(backup.seed);
+        self.seed_input = format!("{:016X}", backup.seed);
+        self.pipeline.reset_all(&mut self.world);
+
+        for _ in 0..backup.executed {
+            match self.pipeline.step_forward_sub(
+                &mut self.world,
+                &self.world_profile,
+                &self.blocks,
+            ) {
+                Ok(true) => {}
+                Ok(false) => break,
+                Err(e) => {
+                    self.set_error_status(format!("撤销后重新执行失败: {e}"));
+                    return;
+                }
+            }
+        }
+
+        self.viewport.reset();
+        self.texture_dirty = true;
+        self.set_status(format!(
+            "已撤销: {} ({}×{}), seed 0x{:016X}, 子步骤 {}/{}",
+            self.world_profile.size.description,
+            self.world.width,
+            self.world.height,
+            backup.seed,
+            self.pipeline.executed_sub_steps(),
+            self.pipeline.total_sub_steps(),
+        ));
+        save_runtime_ui_state(self.world_size, &self.overlay, &self.export_overlay);
+    }
+
+    // ── snapshot restore ────────────────────────────────────
+
+    /// 收集当前世界的快照；自定义尺寸（含导入的 .wld）一并记录宽高，否则恢复时无法复现
+    fn collect_snapshot(&self) -> WorldSnapshot {
+        let mut snapshot = self.pipeline.collect_snapshot(self.world_size_key(), &self.world_profile.layers);
+        if self.world_size == WorldSizeSelection::Custom {
+            snapshot.custom_size = Some((self.world_profile.size.width, self.world_profile.size.height));
+        }
+        snapshot
+    }
+
+    /// 读取 `.lwd` 并按当前配置校验（世界尺寸、算法参数），损坏时返回具体诊断；
+    /// 越界参数钳制后照常返回，修正内容写入警告日志
+    fn load_snapshot(&self, path: &std::path::Path) -> Result<WorldSnapshot, String> {
+        let mut snapshot = WorldSnapshot::load_lwd(path)?;
+        for warning in snapshot.validate(&self.world_cfg, self.pipeline.algorithms())? {
+            log::warn(format!("{}: {warning}", path.display()));
+        }
+        Ok(snapshot)
+    }
+
+    /// 按快照恢复世界尺寸、层级、种子与算法参数并重新执行。
+    ///
+    /// 带 `executed` 的会话快照同步回放到保存时的进度；普通存档增量执行到底。
+    fn apply_snapshot(&mut self, snapshot: &WorldSnapshot) {
+        // 1) 恢复世界尺寸（超出内存预算时不分配世界，保持当前状态）
+        let profile = WorldProfile::from_config(
+            &self.world_cfg,
+            &snapshot.world_size,
+            snapshot.custom_size,
+        )
+        .expect("world size 配置非法");
+        if let Err(reason) = self.check_memory_budget(profile.size.width, profile.size.height) {
+            self.set_error_status(format!("未恢复存档 {reason}"));
+            return;
+        }
+        self.world_size = WorldSizeSelection::from_key(&snapshot.world_size);
+        if let Some((width, height)) = snapshot.custom_size {
+            self.custom_width = width.to_string();
+            self.custom_height = height.to_string();
+        }
+        self.world_profile = profile;
+
+        // 2) 恢复层级配置
+        for layer in &mut self.world_profile.layers {
+            if let Some(ov) = snapshot.layers.get(&layer.key) {
+                layer.start_percent = ov.start_percent;
+                layer.end_percent = ov.end_percent;
+            }
+        }
+
+        let old_size = (self.world.width, self.world.height);
+        self.world = self.world_profile.create_world();
+
+        // 3) 恢复种子 + 算法参数
+        self.pipeline.set_seed(snapshot.seed);
+        self.pipeline.set_scatter_salt(snapshot.scatter_salt);
+        self.seed_input = format!("{:016X}", snapshot.seed);
+        let missing = self.pipeline.restore_from_snapshot(snapshot);
+        // 子种子模式与 RNG 算法随存档恢复，性能面板同步显示
+        if self.engine_config.seed_size_mixing != self.pipeline.size_mixing()
+            || self.engine_config.rng_kind != self.pipeline.rng_kind()
+        {
+            self.engine_config.seed_size_mixing = self.pipeline.size_mixing();
+            self.engine_config.rng_kind = self.pipeline.rng_kind();
+            self.engine_config.save();
+        }
+        if !missing.is_empty() {
+            log::warn(format!(
+                "存档缺少 {} 个参数，将使用当前默认值（重放结果可能与存档时不同）: {}",
+                missing.len(),
+                missing.join(", ")
+            ));
+        }
+        let missing_note = if missing.is_empty() {
+            String::new()
+        } else {
+            format!(" ⚠ 缺少 {} 个参数已用默认值，详见日志", missing.len())
+        };
+
+        // 4) 重新执行：会话快照回放到保存时的进度，存档增量执行到底
+        self.pipeline.reset_all(&mut self.world);
+        self.texture_dirty = true;
+        self.update_viewport_for_world_change(old_size);
+        self.has_started_generation = true;
+        save_runtime_ui_state(self.world_size, &self.overlay, &self.export_overlay);
+        match snapshot.executed {
+            Some(executed) => {
+                for _ in 0..executed {
+                    match self.pipeline.step_forward_sub(
+                        &mut self.world,
+                        &self.world_profile,
+                        &self.blocks,
+                    ) {
+                        Ok(true) => {}
+                        Ok(false) => break,
+                        Err(e) => {
+                            self.set_error_status(format!("恢复会话时重新执行失败: {e}"));
+                            return;
+                        }
+                    }
+                }
+                self.set_status(format!(
+                    "已恢复上次会话 (seed: {}, 子步骤 {}/{}){missing_note}",
+                    snapshot.seed,
+                    self.pipeline.executed_sub_steps(),
+                    self.pipeline.total_sub_steps(),
+                ));
+            }
+            None => {
+                self.running_to_end = true;
+                self.set_status(format!("正在从存档恢复 (seed: {})…{missing_note}", snapshot.seed));
+            }
+        }
+    }
+
+    /// 退出时把当前世界写入会话文件（未开始生成时删除旧会话文件）
+    fn save_session(&self) {
+        // 尚未回答是否恢复：保留旧会话
+        if self.pending_session.is_some() {
+            return;
+        }
+        let path = last_session_path();
+        if !self.has_started_generation {
+            let _ = std::fs::remove_file(&path);
+            return;
+        }
+        let mut snapshot = self.collect_snapshot();
+        snapshot.executed = Some(self.pipeline.executed_sub_steps());
+        match snapshot.save_lwd(&path) {
+            Ok(()) => log::info(format!("会话已保存: {}", path.display())),
+            Err(e) => log::error(format!("会话保存失败: {e}")),
+        }
+    }
+
+    /// 显示「恢复上次会话」确认框，处理用户的选择
+    fn show_session_restore(&mut self, ctx: &egui::Context) {
+        let Some(snapshot) = &self.pending_session else {
+            return;
+        };
+        let total = self.pipeline.total_sub_steps();
+        let mut lines = vec![
+            format!("种子：0x{:016X}", snapshot.seed),
+            format!("世界尺寸：{}", snapshot.world_size),
+            format!("进度：子步骤 {}/{total}", snapshot.executed.unwrap_or(total)),
+        ];
+        if self.pending_session_crashed {
+            lines.push("检查点只记录种子与参数：沙箱应用、画笔绘制、导入的环境图等手动改动不会恢复。".to_string());
+        }
+        let title = if self.pending_session_crashed {
+            "上次生成异常中断，从检查点恢复？"
+        } else {
+            "恢复上次会话？"
+        };
+        let Some(restore) = show_confirm_dialog(ctx, title, &lines, "↺ 恢复") else {
+            return;
+        };
+        if let Some(snapshot) = self.pending_session.take()
+            && restore
+        {
+            self.apply_snapshot(&snapshot);
+        }
+        // 无论是否恢复，旧检查点都已处理完毕
+        if self.pending_session_crashed {
+            let _ = std::fs::remove_file(crash_checkpoint_path());
+            self.pending_session_crashed = false;
+        }
+    }
+
+    /// 生成进度变化且距上次写入超过 `checkpoint_interval_secs` 时，把当前进度写入崩溃恢复检查点。
+    ///
+    /// 与会话保存一样只记录种子、参数与已执行子步骤数，恢复时确定性回放到该进度。
+    /// 环境地图与流水线共享状态不写入检查点：回放只能重建生成步骤的结果，
+    /// 沙箱「应用到世界」、导入的环境 ID 图 / .wld 等不经生成步骤的改动无法恢复；
+    /// 大世界回放到后期进度也需要重新执行之前的全部步骤。
+    fn maybe_write_checkpoint(&mut self) {
+        let interval = self.engine_config.checkpoint_interval_secs;
+        // 尚未回答是否恢复时不能覆盖旧检查点
+        if interval == 0 || self.pending_session.is_some() {
+            return;
+        }
+        let executed = self.pipeline.executed_sub_steps();
+        if executed == 0
+            || executed == self.checkpoint_steps
+            || self.checkpoint_at.elapsed() < Duration::from_secs(interval)
+        {
+            return;
+        }
+        let mut snapshot = self.collect_snapshot();
+        snapshot.executed = Some(executed);
+        if let Err(e) = snapshot.save_lwd(&crash_checkpoint_path()) {
+            log::warn(format!("崩溃恢复检查点写入失败: {e}"));
+        }
+        self.checkpoint_at = Instant::now();
+        self.checkpoint_steps = executed;
+    }
+
+    // ── export ──────────────────────────────────────────────
+
+    /// 导出当前世界画面；世界尚未生成完成时先弹窗确认，避免误导出半成品
+    fn request_export(&mut self, export: WorldExport) {
+        if self.pipeline.is_complete() {
+            self.run_export(export);
+        } else {
+            self.pending_export = Some(export);
+        }
+    }
+
+    fn run_export(&mut self, export: WorldExport) {
+        match export {
+            WorldExport::Png => self.export_png(false),
+            WorldExport::PngBiome => self.export_png(true),
+            WorldExport::Padded => self.export_padded_png(),
+            WorldExport::Heightmap => self.export_heightmap(),
+            WorldExport::BiomeLayers => self.export_biome_layers(),
+            WorldExport::BiomeIds => self.export_biome_ids(),
+            WorldExport::SummaryCard => self.export_summary_card(),
+        }
+    }
+
+    /// 显示导出未完成世界的确认框，处理用户的选择
+    fn show_export_confirm(&mut self, ctx: &egui::Context, export: WorldExport) {
+        let lines = [
+            format!(
+                "当前进度：子步骤 {}/{}",
+                self.pipeline.executed_sub_steps(),
+                self.pipeline.total_sub_steps(),
+            ),
+            "世界尚未生成完成，导出的将是部分生成的画面。".to_string(),
+            "可先「执行到底」再导出；.lwd 存档不受影响（导入时总会回放到底）。".to_string(),
+        ];
+        match show_confirm_dialog(ctx, "导出未完成的世界？", &lines, export.label()) {
+            Some(true) => {
+                self.pending_export = None;
+                self.run_export(export);
+            }
+            Some(false) => {
+                self.pending_export = None;
+                self.set_status("已取消导出".to_string());
+            }
+            None => {}
+        }
+    }
+
+    /// 选择保存路径后把 PNG 导出加入后台队列；`include_biomes` 时混合环境覆盖色
+    fn export_png(&mut self, include_biomes: bool) {
+        let (title, file_name, label) = if include_biomes {
+            ("导出 PNG (含环境)", "world_export_biome.png", "PNG (含环境)")
+        } else {
+            ("导出 PNG", "world_export.png", "PNG")
+        };
+        let dialog = rfd::FileDialog::new()
+            .set_title(title)
+            .set_file_name(file_name)
+            .add_filter("PNG 图片", &["png"]);
+        if let Some(path) = dialog.save_file() {
+            self.queue_png_export(label, path, PngLayout::Native, include_biomes);
+        }
+    }
+
+    /// 按固定尺寸导出设置弹出保存对话框并导出
+    fn export_padded_png(&mut self) {
+        let settings = self.padded_export;
+        let dialog = rfd::FileDialog::new()
+            .set_title("固定尺寸导出")
+            .set_file_name(format!("world_{}x{}.png", settings.width, settings.height))
+            .add_filter("PNG 图片", &["png"]);
+        let Some(path) = dialog.save_file() else {
+            return;
+        };
+        self.queue_png_export(
+            &format!("PNG ({}×{})", settings.width, settings.height),
+            path,
+            PngLayout::Padded {
+                target: (settings.width, settings.height),
+                // Color32 为预乘颜色，PNG 像素需要未预乘的 RGBA
+                background: settings.background.to_srgba_unmultiplied(),
+            },
+            settings.include_biomes,
+        );
+    }
+
+    /// 把 PNG 导出加入后台队列（复制当前世界与环境地图）
+    fn queue_png_export(&mut self, label: &str, path: PathBuf, layout: PngLayout, include_biomes: bool) {
+        let biome_map = if include_biomes {
+            self.pipeline.biome_map()
+        } else {
+            None
+        };
+        let export_overlay = self.export_overlay;
+        let shading = export_overlay.depth_shading
+            .then(|| DepthShading::from_layers(&self.world_profile.layers, self.world.height));
+        let annotations = export_overlay.layer_annotations
+            .then(|| LayerAnnotations::from_layers(&self.world_profile.layers, self.world.height, CJK_FONT_BYTES));
+        let scale_bar = export_overlay.scale_bar.then_some(ScaleBar {
+            tiles: export_overlay.scale_bar_tiles,
+            corner: export_overlay.scale_bar_corner,
+            font_data: CJK_FONT_BYTES,
+        });
+        self.export_queue.submit(
+            label,
+            path.clone(),
+            layout,
+            &self.world,
+            &self.color_lut,
+            biome_map,
+            &self.biomes,
+            export_overlay.biome_pattern,
+            shading,
+            annotations,
+            scale_bar,
+        );
+        self.set_status(format!("{label} 已加入导出队列: {}", path.display()));
+        if include_biomes && export_overlay.biome_legend {
+            self.export_biome_legend(&path);
+        }
+    }
+
+    /// 在导出图旁写入环境图例 `<文件名>_legend.png`
+    fn export_biome_legend(&mut self, image_path: &std::path::Path) {
+        let Some(biome_map) = self.pipeline.biome_map() else {
+            self.set_error_status("图例导出失败: 尚未生成环境地图".to_string());
+            return;
+        };
+        let stem = image_path.file_stem().and_then(|s| s.to_str()).unwrap_or("world_export");
+        let legend_path = image_path.with_file_name(format!("{stem}_legend.png"));
+        let result = biome_legend(biome_map, &self.biomes, self.export_overlay.biome_pattern, CJK_FONT_BYTES)
+            .and_then(|img| img.save(&legend_path).map_err(|e| e.to_string()));
+        match result {
+            Ok(()) => self.set_status(format!("图例已导出: {}", legend_path.display())),
+            Err(e) => self.set_error_status(format!("图例导出失败: {e}")),
+        }
+    }
+
+    /// 把环境地图降采样为字符画（附种子与尺寸）复制到剪贴板
+    fn copy_ascii_art(&mut self, ctx: &egui::Context) {
+        let Some(biome_map) = self.pipeline.biome_map() else {
+            self.set_status("尚未生成环境地图，无法复制字符画".to_string());
+            return;
+        };
+        let art = biome_ascii_art(biome_map, &self.biomes, ASCII_COLS, ASCII_ROWS);
+        let text = format!(
+            "Lian World · Seed {:016X} · {}×{}\n{art}",
+            self.pipeline.seed(),
+            biome_map.width,
+            biome_map.height,
+        );
+        ctx.output_mut(|o| o.copied_text = text);
+        self.set_status("字符画已复制到剪贴板".to_string());
+    }
+
+    /// 开始（选择目录）或停止步骤帧录制
+    fn toggle_frame_recording(&mut self) {
+        if let Some(recorder) = self.frame_recorder.take() {
+            self.set_status(format!("已停止录制: {} 帧 → {}", recorder.frames(), recorder.dir().display()));
+            return;
+        }
+        let Some(dir) = rfd::FileDialog::new().set_title("选择步骤帧保存目录").pick_folder() else {
+            return;
+        };
+        match FrameRecorder::start(dir) {
+            Ok(recorder) => {
+                self.set_status(format!("开始录制步骤帧 → {}（每执行一个子步骤保存一张）", recorder.dir().display()));
+                self.frame_recorder = Some(recorder);
+            }
+            Err(e) => self.set_error_status(format!("无法开始录制: {e}")),
+        }
+    }
+
+    /// 录制中时把当前世界的降采样预览（含环境覆盖色）保存为下一帧
+    fn record_step_frame(&mut self) {
+        if self.frame_recorder.is_none() {
+            return;
+        }
+        let factor = self.downsample_factor();
+        let image = match self.pipeline.biome_map() {
+            Some(bm) => biome_minimap_image(
+                &self.world, &self.color_lut, bm, &self.biomes, self.overlay.terraria_map_colors, factor,
+            ),
+            None => world_to_color_image_downsampled(&self.world, &self.color_lut, factor),
+        };
+        let step_id = self.pipeline.last_executed_display_id().unwrap_or_default();
+        if let Some(recorder) = &mut self.frame_recorder {
+            recorder.record(image, &step_id);
+        }
+    }
+
+    /// 选择保存路径后在后台线程生成尺寸对比图（沿用当前参数、层级与跳过的步骤）
+    fn export_size_strip(&mut self) {
+        if self.size_strip_job.is_some() {
+            self.set_status("尺寸对比图仍在生成中".to_string());
+            return;
+        }
+        let seed = self.pipeline.seed();
+        let dialog = rfd::FileDialog::new()
+            .set_title("导出尺寸对比图")
+            .set_file_name(size_strip::default_file_name(seed))
+            .add_filter("PNG 图片", &["png"]);
+        let Some(path) = dialog.save_file() else {
+            return;
+        };
+        let settings = StripSettings {
+            seed,
+            snapshot: Some(self.collect_snapshot()),
+            layers: Some(self.world_profile.layers.clone()),
+            disabled_steps: self.pipeline.disabled_steps(),
+            size_mixing: self.pipeline.size_mixing(),
+            rng_kind: self.pipeline.rng_kind(),
+        };
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let result = size_strip::render_size_strip(&settings, CJK_FONT_BYTES, |_| {})
+                .and_then(|img| img.save(&path).map_err(|e| format!("保存 PNG 失败: {e}")))
+                .map(|()| path);
+            let _ = tx.send(result);
+        });
+        self.size_strip_job = Some(rx);
+        self.set_status("正在生成三种尺寸的对比图…".to_string());
+    }
+
+    /// 导出地表高度图（空列回退到地表层级起始行；生成流程不写入方块，生成的世界整幅都会回退）
+    fn export_heightmap(&mut self) {
+        let dialog = rfd::FileDialog::new()
+            .set_title("导出高度图")
+            .set_file_name("world_heightmap.png")
+            .add_filter("PNG 图片", &["png"]);
+        let Some(path) = dialog.save_file() else {
+            return;
+        };
+        match export_heightmap(&self.world, self.surface_row(), &path) {
+            Ok(fallback) if fallback == self.world.width as usize => self.set_status(format!(
+                "高度图已导出: {}（世界中没有方块：生成流程目前不写入方块，各列均取地表层级起始行，高度图是平坦的）",
+                path.display(),
+            )),
+            Ok(_) => self.set_status(format!("高度图已导出: {}（白 = 高，说明见同名 .json）", path.display())),
+            Err(e) => self.set_error_status(format!("高度图导出失败: {e}")),
+        }
+    }
+
+    /// 选择目录后把分环境图层导出加入后台队列（每个环境一张透明底 PNG，图案同导出设置）
+    fn export_biome_layers(&mut self) {
+        let Some(biome_map) = self.pipeline.biome_map() else {
+            self.set_error_status("分环境图层导出失败: 尚未生成环境地图".to_string());
+            return;
+        };
+        let Some(dir) = rfd::FileDialog::new().set_title("选择分环境图层保存目录").pick_folder() else {
+            return;
+        };
+        let label = "分环境图层";
+        self.export_queue.submit_biome_layers(
+            label,
+            dir.clone(),
+            biome_map,
+            &self.biomes,
+            self.export_overlay.biome_pattern,
+        );
+        self.set_status(format!("{label} 已加入导出队列: {}", dir.display()));
+    }
+
+    /// 导出 16 位环境 ID 图与同名 JSON 图例
+    fn export_biome_ids(&mut self) {
+        let Some(biome_map) = self.pipeline.biome_map() else {
+            self.set_error_status("环境 ID 图导出失败: 尚未生成环境地图".to_string());
+            return;
+        };
+        let dialog = rfd::FileDialog::new()
+            .set_title("导出环境 ID 图")
+            .set_file_name("world_biome_ids.png")
+            .add_filter("PNG 图片", &["png"]);
+        let Some(path) = dialog.save_file() else {
+            return;
+        };
+        match export_biome_ids(biome_map, &self.biomes, &path) {
+            Ok(()) => self.set_status(format!("环境 ID 图已导出: {}（图例见同名 .json）", path.display())),
+            Err(e) => self.set_error_status(format!("环境 ID 图导出失败: {e}")),
+        }
+    }
+
+    /// 导出世界摘要卡片：降采样预览（混合环境覆盖色）+ 种子 / 尺寸 / 参数哈希 + 环境覆盖率
+    fn export_summary_card(&mut self) {
+        let Some(biome_map) = self.pipeline.biome_map() else {
+            self.set_error_status("世界卡片导出失败: 尚未生成环境地图".to_string());
+            return;
+        };
+        let dialog = rfd::FileDialog::new()
+            .set_title("导出世界卡片")
+            .set_file_name(format!("world_card_{:016X}.png", self.pipeline.seed()))
+            .add_filter("PNG 图片", &["png"]);
+        let Some(path) = dialog.save_file() else {
+            return;
+        };
+        // 卡片上的预览区不到 800px 宽，按世界宽度取整数倍降采样即可
+        let factor = self.world.width.div_ceil(1600).max(1);
+        let preview = biome_minimap_image(
+            &self.world, &self.color_lut, biome_map, &self.biomes, self.overlay.terraria_map_colors, factor,
+        );
+        let preview = image::RgbaImage::from_fn(preview.width() as u32, preview.height() as u32, |x, y| {
+            let c = preview[(x as usize, y as usize)];
+            image::Rgba([c.r(), c.g(), c.b(), 255])
+        });
+        let info = SummaryCardInfo {
+            seed: self.pipeline.seed(),
+            size_key: self.world_size_key().to_string(),
+            width: self.world.width,
+            height: self.world.height,
+            params_hash: self.collect_snapshot().params_hash(),
+        };
+        let result = world_summary_card(&preview, biome_map, &self.biomes, &info, CJK_FONT_BYTES)
+            .and_then(|card| card.save(&path).map_err(|e| format!("保存 PNG 失败: {e}")));
+        match result {
+            Ok(()) => self.set_status(format!("世界卡片已导出: {}", path.display())),
+            Err(e) => self.set_error_status(format!("世界卡片导出失败: {e}")),
+        }
+    }
+
+    /// 读取环境 ID 图，替换当前环境地图（尺寸须与当前世界一致）
+    fn import_biome_ids(&mut self) {
+        let dialog = rfd::FileDialog::new()
+            .set_title("导入环境 ID 图")
+            .add_filter("PNG 图片", &["png"]);
+        let Some(path) = dialog.pick_file() else {
+            return;
+        };
+        let biome_map = match import_biome_ids(&path, &self.biomes) {
+            Ok(map) => map,
+            Err(e) => {
+                self.set_error_status(format!("环境 ID 图导入失败: {e}"));
+                return;
+            }
+        };
+        if (biome_map.width, biome_map.height) != (self.world.width, self.world.height) {
+            self.set_error_status(format!(
+                "环境 ID 图导入失败: 图像为 {}×{}，当前世界为 {}×{}",
+                biome_map.width, biome_map.height, self.world.width, self.world.height,
+            ));
+            return;
+        }
+        self.capture_undo_backup();
+        self.running_to_end = false;
+        self.playing = false;
+        self.pipeline.reset_all(&mut self.world);
+        self.pipeline.load_biome_map(biome_map);
+        self.texture_dirty = true;
+        self.has_started_generation = true;
+        self.set_status(format!("已导入环境 ID 图: {}；执行生成步骤会覆盖导入的环境", path.display()));
+    }
+
+    /// 「执行到底」跑完：换全分辨率纹理、输出性能报告，再依次调用生成完成钩子
+    fn finish_generation(&mut self, ctx: &egui::Context) {
+        // 降采样预览 → 全分辨率
+        self.ensure_full_resolution_texture(ctx);
+
+        log::info(self.pipeline.performance_report());
+
+        let total_ms = self.pipeline.profiler().total_generation_time().as_secs_f64() * 1000.0;
+        self.completion_hooks.fire(&GenerationComplete {
+            pipeline: &self.pipeline,
+            world: &self.world,
+            biomes: &self.biomes,
+            config: &self.engine_config,
+            total_ms,
+        });
+
+        self.set_status(format!(
+            "全部步骤已完成 ({}/{}) — 总耗时 {:.1}ms",
+            self.pipeline.executed_sub_steps(),
+            self.pipeline.total_sub_steps(),
+            total_ms,
+        ));
+    }
+
+    /// 处理后台导出的完成 / 失败消息
+    fn poll_export_queue(&mut self, ctx: &egui::Context) {
+        for job in self.export_queue.poll(Duration::from_secs(4)) {
+            match job.state {
+                ExportJobState::Done => {
+                    self.set_status(format!("{} 已导出: {}", job.label, job.path.display()));
+                }
+                ExportJobState::Failed(e) => {
+                    self.set_error_status(format!("{} 导出失败: {e}", job.label));
+                }
+                _ => {}
+            }
+        }
+        if let Some(result) = self.size_strip_job.as_ref().and_then(|rx| rx.try_recv().ok()) {
+            self.size_strip_job = None;
+            match result {
+                Ok(path) => self.set_status(format!("尺寸对比图已导出: {}", path.display())),
+                Err(e) => self.set_error_status(format!("尺寸对比图导出失败: {e}")),
+            }
+        }
+        if let Some(e) = self.frame_recorder.as_ref().and_then(FrameRecorder::poll_error) {
+            self.frame_recorder = None;
+            self.set_error_status(format!("步骤帧录制已停止: {e}"));
+        }
+        // 工作线程不会唤醒 UI，有任务（含停留中的完成提示）时定时重绘
+        if !self.export_queue.jobs().is_empty() || self.size_strip_job.is_some() {
+            ctx.request_repaint_after(Duration::from_millis(100));
+        }
+    }
 
     // ── texture management ──────────────────────────────────
 
     /// 根据世界大小计算预览降采样倍率
+    ///
+    /// 倍率不超过世界的短边，保证降采样后的图像至少 1×1；空世界返回 1。
     fn downsample_factor(&self) -> u32 {
+        let short_side = self.world.width.min(self.world.height);
+        if short_side == 0 {
+            return 1;
+        }
         let pixels = self.world.width as u64 * self.world.height as u64;
-        if pixels > 15_000_000 {
+        let factor = if pixels > 15_000_000 {
             4 // 大世界 8400×2400 = 20M → 预览 2100×600
         } else if pixels > 8_000_000 {
             2 // 中世界 6400×1800 = 11.5M → 预览 3200×900
         } else {
             1 // 小世界全分辨率
+        };
+        factor.min(short_side)
+    }
+
+    /// 状态栏的世界尺寸；开启尺寸详情时附加档位、换算单位与总格数
+    fn world_size_label(&self) -> String {
+        let (w, h) = (self.world.width, self.world.height);
+        if !self.overlay.world_size_details {
+            return format!("{w}×{h}");
         }
+        let unit = self.overlay.world_size_unit;
+        let dims = match unit {
+            SizeUnit::Feet => {
+                let fpt = self.overlay.feet_per_tile;
+                format!("{:.0}×{:.0} {}", w as f32 * fpt, h as f32 * fpt, unit.label())
+            }
+            _ => format!("{w}×{h} {}", unit.label()),
+        };
+        // 自定义尺寸按总格数取最接近的预设档位
+        let tiles = w as u64 * h as u64;
+        let class = match self.world_size {
+            WorldSizeSelection::Custom => self.world_cfg.world_sizes.values()
+                .filter_map(|s| Some((s.width? as u64 * s.height? as u64, &s.description)))
+                .min_by_key(|(n, _)| n.abs_diff(tiles))
+                .map(|(_, desc)| format!("接近{desc}")),
+            _ => self.world_cfg.world_sizes.get(self.world_size_key()).map(|s| s.description.clone()),
+        }
+        .unwrap_or_default();
+        let count_unit = if unit == SizeUnit::Blocks { "方块" } else { "格" };
+        format!("{dims} · {class} · 共 {} {count_unit}", group_thousands(tiles))
+    }
+
+    /// 地表基准行（`worldSurface`），层级缺失时取 0
+    fn surface_row(&self) -> u32 {
+        self.world_profile.layers.iter()
+            .find(|l| l.key == SURFACE_REFERENCE_LAYER)
+            .map(|l| l.bounds_for_height(self.world.height).0)
+            .unwrap_or(0)
+    }
+
+    fn spawn_point(&self) -> (u32, u32) {
+        spawn_point(self.world.width, self.surface_row())
+    }
+
+    /// 复现当前世界所需的信息：种子、尺寸与参数集哈希
+    fn repro_info(&self) -> String {
+        let snapshot = self.collect_snapshot();
+        let mut lines = vec![
+            format!("种子: 0x{:016X}", snapshot.seed),
+            format!("世界尺寸: {} ({} × {})", snapshot.world_size, self.world.width, self.world.height),
+            format!("参数哈希: {:016X}", snapshot.params_hash()),
+        ];
+        if snapshot.scatter_salt != 0 {
+            lines.push(format!("散布盐: 0x{:016X}", snapshot.scatter_salt));
+        }
+        lines.push(format!("进度: {}/{}", self.pipeline.executed_sub_steps(), self.pipeline.total_sub_steps()));
+        if let Some(hash) = &self.world_hash {
+            lines.push(format!("环境哈希: {hash}"));
+        }
+        let disabled = self.pipeline.disabled_steps();
+        if !disabled.is_empty() {
+            let list: Vec<String> = disabled.iter().map(usize::to_string).collect();
+            lines.push(format!("已跳过子步骤: {}", list.join(", ")));
+        }
+        lines.join("\n")
+    }
+
+    /// 状态栏读数：视口中心相对出生点的偏移（格）
+    fn spawn_offset_label(&self) -> String {
+        if !self.has_started_generation {
+            return String::new();
+        }
+        let (sx, sy) = self.spawn_point();
+        let (cx, cy) = self.viewport.view_center(self.world.width, self.world.height);
+        let dx = cx.floor() as i64 - sx as i64;
+        let dy = cy.floor() as i64 - sy as i64;
+        let distance = ((dx * dx + dy * dy) as f64).sqrt();
+        tr_args("status.spawn_offset", &[&format!("{dx:+}"), &format!("{dy:+}"), &format!("{distance:.0}")])
     }
 
     fn refresh_texture_if_dirty(&mut self, ctx: &egui::Context) {
         if !self.texture_dirty {
             return;
         }
+        self.world_hash = None;
+        self.world_boundary = None;
 
         // Bump version → GL region will re-render on next show_canvas
         self.gl_canvas.lock().unwrap().mark_world_changed();
 
         // Minimap texture: always use downsampled for efficiency
         let minimap_factor = self.downsample_factor().max(2); // at least 2× for minimap
-        let image = world_to_color_image_downsampled(
-            &self.world, &self.color_lut, minimap_factor,
-        );
+        let biome_map = (self.overlay.minimap_source == MinimapSource::Biomes)
+            .then(|| self.pipeline.biome_map())
+            .flatten();
+        let image = match biome_map {
+            Some(bm) => biome_minimap_image(
+                &self.world, &self.color_lut, bm, &self.biomes, self.overlay.terraria_map_colors, minimap_factor,
+            ),
+            None => world_to_color_image_downsampled(&self.world, &self.color_lut, minimap_factor),
+        };
 
         if self.running_to_end && !self.pipeline.is_complete() {
             self.preview_factor = minimap_factor;
@@ -289,6 +1407,19 @@ impl LianWorldApp {
             egui::TextureOptions::NEAREST,
         ));
         self.texture_dirty = false;
+        self.manual_throttle.cancel();
+    }
+
+    /// 手动步进 / 回退后标记纹理需要刷新：非小世界上推迟到连续操作停下之后（播放时不推迟）
+    fn mark_manual_step_dirty(&mut self) {
+        let idle = if self.playing {
+            Duration::ZERO
+        } else {
+            ManualStepThrottle::idle_for(&self.engine_config, self.world.width, self.world.height)
+        };
+        if self.manual_throttle.defer(idle) {
+            self.texture_dirty = true;
+        }
     }
 
     /// 生成完成后，如果当前纹理是预览版，强制重建全分辨率
@@ -301,6 +1432,36 @@ impl LianWorldApp {
 
     // ── action dispatch ─────────────────────────────────────
 
+    /// 播放模式：切换播放 / 暂停，到达间隔时为本帧置上 `step_forward_sub`。
+    ///
+    /// 执行到底、重置、导入或全部完成时自动停止播放。
+    fn update_playback(&mut self, ctx: &egui::Context, action: &mut ControlAction) {
+        if action.toggle_play {
+            self.playing = !self.playing && !self.pipeline.is_complete();
+            if self.playing {
+                // 开始播放后立即走第一步
+                self.last_play_step = Instant::now() - Duration::from_millis(self.play_interval_ms as u64);
+            }
+        }
+        if action.run_all || action.reset_and_step || action.import_lwd || action.import_wld
+            || self.running_to_end || self.pipeline.is_complete()
+        {
+            self.playing = false;
+        }
+        if !self.playing {
+            return;
+        }
+        let interval = Duration::from_millis(self.play_interval_ms as u64);
+        let elapsed = self.last_play_step.elapsed();
+        if elapsed >= interval {
+            action.step_forward_sub = true;
+            self.last_play_step = Instant::now();
+            ctx.request_repaint_after(interval);
+        } else {
+            ctx.request_repaint_after(interval - elapsed);
+        }
+    }
+
     fn handle_action(&mut self, action: &ControlAction) {
         if action.zoom_in {
             self.viewport.zoom_in();
@@ -309,7 +1470,20 @@ impl LianWorldApp {
             self.viewport.zoom_out();
         }
         if action.zoom_reset {
-            self.viewport.reset();
+            // 平滑回到默认缩放、世界居中
+            self.viewport.animate_to(
+                (self.world.width as f32 / 2.0, self.world.height as f32 / 2.0),
+                DEFAULT_ZOOM,
+                self.viewport.animation_duration,
+            );
+        }
+        if let Some(zoom) = action.set_zoom {
+            self.viewport.set_zoom(zoom);
+        }
+        if action.center_on_spawn {
+            let (sx, sy) = self.spawn_point();
+            // 格子中心对齐画布中心
+            self.viewport.animate_to_center(sx as f32 + 0.5, sy as f32 + 0.5);
         }
 
         if action.step_forward_sub {
@@ -319,16 +1493,21 @@ impl LianWorldApp {
                 &self.blocks,
             ) {
                 Ok(true) => {
-                    self.texture_dirty = true;
+                    self.mark_manual_step_dirty();
+                    self.record_step_frame();
+                    if self.engine_config.auto_frame_step {
+                        self.frame_last_step();
+                    }
                     if let Some(name) = self.pipeline.last_executed_name() {
-                        self.last_status = format!("已执行: {name}");
+                        self.set_status(format!("已执行: {name}"));
                     }
                 }
                 Ok(false) => {
-                    self.last_status = "所有步骤已完成".to_string();
+                    self.set_status("所有步骤已完成".to_string());
                 }
                 Err(e) => {
-                    self.last_status = format!("步骤失败: {e}");
+                    self.playing = false;
+                    self.set_step_error_status("步骤失败", &e);
                 }
             }
         }
@@ -340,16 +1519,16 @@ impl LianWorldApp {
                 &self.blocks,
             ) {
                 Ok(true) => {
-                    self.texture_dirty = true;
+                    self.mark_manual_step_dirty();
                     if let Some(name) = self.pipeline.last_executed_name() {
-                        self.last_status = format!("阶段完成: {name}");
+                        self.set_status(format!("阶段完成: {name}"));
                     }
                 }
                 Ok(false) => {
-                    self.last_status = "所有步骤已完成".to_string();
+                    self.set_status("所有步骤已完成".to_string());
                 }
                 Err(e) => {
-                    self.last_status = format!("步骤失败: {e}");
+                    self.set_step_error_status("步骤失败", &e);
                 }
             }
         }
@@ -361,18 +1540,18 @@ impl LianWorldApp {
                 &self.blocks,
             ) {
                 Ok(true) => {
-                    self.texture_dirty = true;
-                    self.last_status = format!(
+                    self.mark_manual_step_dirty();
+                    self.set_status(format!(
                         "已回退至子步骤 {}/{}",
                         self.pipeline.executed_sub_steps(),
                         self.pipeline.total_sub_steps()
-                    );
+                    ));
                 }
                 Ok(false) => {
-                    self.last_status = "已在起始状态".to_string();
+                    self.set_status("已在起始状态".to_string());
                 }
                 Err(e) => {
-                    self.last_status = format!("回退失败: {e}");
+                    self.set_error_status(format!("回退失败: {e}"));
                 }
             }
         }
@@ -384,146 +1563,519 @@ impl LianWorldApp {
                 &self.blocks,
             ) {
                 Ok(true) => {
-                    self.texture_dirty = true;
-                    self.last_status = format!(
+                    self.mark_manual_step_dirty();
+                    self.set_status(format!(
                         "已回退至子步骤 {}/{}",
                         self.pipeline.executed_sub_steps(),
                         self.pipeline.total_sub_steps()
-                    );
+                    ));
                 }
                 Ok(false) => {
-                    self.last_status = "已在起始状态".to_string();
+                    self.set_status("已在起始状态".to_string());
+                }
+                Err(e) => {
+                    self.set_error_status(format!("回退失败: {e}"));
+                }
+            }
+        }
+
+        if let Some((flat_index, enabled)) = action.toggle_step {
+            let state = if enabled { "已启用" } else { "已禁用" };
+            if self.pipeline.set_step_enabled(flat_index, enabled) {
+                // 已执行过的步骤改变启用状态：从头回放到当前位置
+                match self.pipeline.replay_to_current(
+                    &mut self.world,
+                    &self.world_profile,
+                    &self.blocks,
+                ) {
+                    Ok(()) => {
+                        self.texture_dirty = true;
+                        self.set_status(format!("{state}子步骤 #{flat_index}，已重新执行"));
+                    }
+                    Err(e) => self.set_error_status(format!("重新执行失败: {e}")),
+                }
+            } else {
+                self.set_status(format!("{state}子步骤 #{flat_index}"));
+            }
+        }
+
+        if let Some((key, enabled)) = &action.toggle_biome {
+            let state = if *enabled { "已启用" } else { "已关闭" };
+            if self.pipeline.set_biome_enabled(key, *enabled) {
+                match self.pipeline.replay_to_current(
+                    &mut self.world,
+                    &self.world_profile,
+                    &self.blocks,
+                ) {
+                    Ok(()) => {
+                        self.texture_dirty = true;
+                        self.set_status(format!("{state}环境 {key}，已重新执行"));
+                    }
+                    Err(e) => self.set_error_status(format!("重新执行失败: {e}")),
                 }
+            } else {
+                self.set_status(format!("{state}环境 {key}"));
+            }
+        }
+
+        if let Some(flat_index) = action.goto_step {
+            match self.pipeline.goto_step(
+                flat_index,
+                &mut self.world,
+                &self.world_profile,
+                &self.blocks,
+            ) {
+                Ok(true) => {
+                    self.texture_dirty = true;
+                    self.set_status(format!(
+                        "已跳转至子步骤 {}/{}",
+                        self.pipeline.executed_sub_steps(),
+                        self.pipeline.total_sub_steps()
+                    ));
+                }
+                Ok(false) => {}
                 Err(e) => {
-                    self.last_status = format!("回退失败: {e}");
+                    self.texture_dirty = true;
+                    self.set_step_error_status("跳转失败", &e);
                 }
             }
         }
 
+        if action.undo_config {
+            self.undo_config_change();
+        }
+
+        if action.language_changed {
+            save_runtime_ui_state(self.world_size, &self.overlay, &self.export_overlay);
+        }
+
         // ── "重新初始化" = new seed + reset to step 0
+        let mut run_all = action.run_all;
         if action.reset_and_step {
-            let new_seed = rand::random::<u64>();
-            self.pipeline.set_seed(new_seed);
-            self.pipeline.reset_all(&mut self.world);
-            self.texture_dirty = true;
-            self.seed_input = format!("{new_seed:016X}");
-            self.last_status = format!("已重置到第0步 (seed: {new_seed})");
+            let executed = self.pipeline.executed_sub_steps();
+            let partway = executed > 0 && executed < self.pipeline.total_sub_steps();
+            if partway && self.engine_config.confirm_destructive_reset {
+                // 生成进行到一半：先弹窗确认，确认后再重置（并按需执行到底）
+                self.pending_reset = Some(action.run_all);
+                run_all = false;
+            } else {
+                self.reset_with_new_seed();
+            }
         }
 
         // ── 手动设置种子
         if action.apply_seed {
             if let Some(new_seed) = parse_seed_input(&self.seed_input) {
+                self.capture_undo_backup();
                 self.pipeline.set_seed(new_seed);
                 self.pipeline.reset_all(&mut self.world);
                 self.texture_dirty = true;
-                self.last_status = format!("已应用种子: 0x{new_seed:016X}");
-            } else {
-                self.last_status = "种子格式无效（请输入十六进制或十进制数字）".to_string();
+                self.set_status(format!("已应用种子: 0x{new_seed:016X}"));
+            } else {
+                self.set_error_status("种子格式无效（请输入十六进制或十进制数字）".to_string());
+            }
+        }
+
+        // ── 精选种子：从当前尺寸的列表中随机挑一个并生成到底
+        if action.featured_seed && self.apply_featured_seed() {
+            run_all = true;
+        }
+
+        // ── 重掷散布特征：新散布盐 + 回放到当前位置
+        if action.reseed_scatter {
+            // 盐为 0 表示未重掷，随机值强制非 0
+            let salt = rand::random::<u64>() | 1;
+            match self.pipeline.reseed_scatter(salt, &mut self.world, &self.world_profile, &self.blocks) {
+                Ok(replayed) => {
+                    self.texture_dirty |= replayed;
+                    self.set_status(format!("已重掷散布特征 (散布盐: 0x{salt:016X})"));
+                }
+                Err(e) => self.set_error_status(format!("重掷散布特征失败: {e}")),
+            }
+        }
+
+        // ── "一键生成" or "执行到底": start incremental run
+        if run_all {
+            self.running_to_end = true;
+        }
+
+        // ── 导出 PNG
+        if action.export_png {
+            self.request_export(WorldExport::Png);
+        }
+
+        // ── 导出 PNG（混合环境覆盖色）
+        if action.export_png_biome {
+            self.request_export(WorldExport::PngBiome);
+        }
+
+        if action.open_padded_export {
+            self.show_padded_export = true;
+        }
+        if action.export_size_strip {
+            self.export_size_strip();
+        }
+        if action.export_heightmap {
+            self.request_export(WorldExport::Heightmap);
+        }
+        if action.export_biome_layers {
+            self.request_export(WorldExport::BiomeLayers);
+        }
+        if action.export_biome_ids {
+            self.request_export(WorldExport::BiomeIds);
+        }
+        if action.import_biome_ids {
+            self.import_biome_ids();
+        }
+        if action.export_summary_card {
+            self.request_export(WorldExport::SummaryCard);
+        }
+
+        // ── 步骤帧录制
+        if action.toggle_frame_recording {
+            self.toggle_frame_recording();
+        }
+
+        // ── 导出 .lwd
+        if action.export_lwd {
+            let snapshot = self.collect_snapshot();
+            let dialog = rfd::FileDialog::new()
+                .set_title("导出世界存档")
+                .set_file_name("world_export.lwd")
+                .add_filter("Lian World 存档", &["lwd"]);
+            if let Some(path) = dialog.save_file() {
+                match snapshot.save_lwd(&path) {
+                    Ok(()) => {
+                        self.set_status(format!("存档已导出: {}", path.display()));
+                    }
+                    Err(e) => {
+                        self.set_error_status(format!("存档导出失败: {e}"));
+                    }
+                }
+            }
+        }
+
+        // ── 导入 .lwd
+        if action.import_lwd {
+            let dialog = rfd::FileDialog::new()
+                .set_title("导入世界存档")
+                .add_filter("Lian World 存档", &["lwd"]);
+            if let Some(path) = dialog.pick_file() {
+                match self.load_snapshot(&path) {
+                    Ok(snapshot) => {
+                        self.capture_undo_backup();
+                        self.apply_snapshot(&snapshot);
+                    }
+                    Err(e) => {
+                        self.set_error_status(format!("存档导入失败: {e}"));
+                    }
+                }
+            }
+        }
+
+        // ── 导出校验报告
+        if action.export_validation {
+            let dialog = rfd::FileDialog::new()
+                .set_title("导出校验报告")
+                .set_file_name(validation::default_file_name(self.pipeline.seed()))
+                .add_filter("JSON", &["json"]);
+            if let Some(path) = dialog.save_file() {
+                let report = WorldValidationReport::compute(&self.pipeline, &self.biomes, &self.world_profile.layers);
+                match std::fs::write(&path, report.to_json()) {
+                    Ok(()) => self.set_status(format!(
+                        "校验报告已导出（{} 条问题）: {}",
+                        report.issues.len(),
+                        path.display(),
+                    )),
+                    Err(e) => self.set_error_status(format!("校验报告导出失败: {e}")),
+                }
+            }
+        }
+
+        if action.import_wld {
+            let dialog = rfd::FileDialog::new()
+                .set_title("导入泰拉瑞亚世界")
+                .add_filter("Terraria 世界", &["wld"]);
+            if let Some(path) = dialog.pick_file() {
+                let check_size = |width, height| self.check_memory_budget(width, height);
+                match wld::load_wld(&path, &self.biomes, check_size) {
+                    Ok(import) => self.apply_wld_import(import),
+                    Err(e) => self.set_error_status(format!(".wld 导入失败: {e}")),
+                }
+            }
+        }
+    }
+
+    /// 随机新种子（固定种子模式下使用配置的种子）并重置到第 0 步
+    fn reset_with_new_seed(&mut self) {
+        self.capture_undo_backup();
+        let new_seed = if self.engine_config.fixed_seed_mode {
+            self.engine_config.fixed_seed
+        } else {
+            rand::random::<u64>()
+        };
+        self.pipeline.set_seed(new_seed);
+        self.pipeline.reset_all(&mut self.world);
+        self.texture_dirty = true;
+        self.seed_input = format!("{new_seed:016X}");
+        if self.engine_config.fixed_seed_mode {
+            self.set_status(format!("已重置到第0步 (固定种子: {new_seed})"));
+        } else {
+            self.set_status(format!("已重置到第0步 (seed: {new_seed})"));
+        }
+    }
+
+    /// 换用随机一个精选种子（尽量避开当前种子）并重置到第 0 步；当前尺寸没有精选种子时返回 `false`
+    fn apply_featured_seed(&mut self) -> bool {
+        let seeds = load_featured_seeds()
+            .ok()
+            .and_then(|mut all| all.remove(self.world_size_key()))
+            .unwrap_or_default();
+        let current = self.pipeline.seed();
+        let candidates: Vec<u64> = seeds.iter().copied().filter(|&s| s != current).collect();
+        let pool = if candidates.is_empty() { &seeds } else { &candidates };
+        if pool.is_empty() {
+            self.set_error_status(format!("尺寸 {} 没有精选种子", self.world_size_key()));
+            return false;
+        }
+        let seed = pool[rand::random::<usize>() % pool.len()];
+        self.capture_undo_backup();
+        self.pipeline.set_seed(seed);
+        self.pipeline.reset_all(&mut self.world);
+        self.texture_dirty = true;
+        self.seed_input = format!("{seed:016X}");
+        self.set_status(format!("精选种子: 0x{seed:016X}"));
+        true
+    }
+
+    /// 显示重置确认框，处理用户的选择
+    fn show_reset_confirm(&mut self, ctx: &egui::Context, run_all: bool) {
+        let lines = [
+            format!(
+                "当前进度：子步骤 {}/{}（{}）",
+                self.pipeline.executed_sub_steps(),
+                self.pipeline.total_sub_steps(),
+                self.pipeline.last_executed_name().unwrap_or_default(),
+            ),
+            format!("当前种子：0x{:016X}", self.pipeline.seed()),
+            if self.engine_config.fixed_seed_mode {
+                format!("重置后将使用固定种子 0x{:016X}，当前进度会丢失（可用「撤销上次更改」恢复）。", self.engine_config.fixed_seed)
+            } else {
+                "重置后将使用新的随机种子，当前进度会丢失（可用「撤销上次更改」恢复）。".to_string()
+            },
+        ];
+        let confirm_label = if run_all { "✦ 一键生成" } else { "↻ 重新初始化" };
+        match show_confirm_dialog(ctx, "确认丢弃当前进度？", &lines, confirm_label) {
+            Some(true) => {
+                self.pending_reset = None;
+                self.reset_with_new_seed();
+                if run_all {
+                    self.running_to_end = true;
+                }
+            }
+            Some(false) => {
+                self.pending_reset = None;
+                self.set_status("已取消重置".to_string());
+            }
+            None => {}
+        }
+    }
+
+    /// 重新统计环境邻接图（尚无环境地图时清空）
+    fn refresh_biome_graph(&mut self) {
+        let executed = self.pipeline.executed_sub_steps();
+        self.biome_graph_state.graph = self.pipeline.biome_map()
+            .map(|bm| BiomeGraph::from_map(bm, executed));
+    }
+
+    fn refresh_block_counts(&mut self) {
+        let executed = self.pipeline.executed_sub_steps();
+        self.block_palette_state.counts = Some(BlockCounts::from_tiles(&self.world.tiles, executed));
+    }
+
+    fn refresh_spawn_suggestions(&mut self) {
+        let executed = self.pipeline.executed_sub_steps();
+        let state = &mut self.spawn_suggest_state;
+        state.result = self.pipeline.biome_map().map(|bm| SpawnSuggestions {
+            candidates: suggest_spawns(
+                bm,
+                &self.biomes,
+                &self.world_profile.layers,
+                state.count,
+                self.pipeline.seed() ^ state.salt,
+            ),
+            executed,
+        });
+    }
+
+    /// 以当前种子、尺寸与环境判定参数为起点启动参数拟合
+    fn start_param_fit(&mut self) {
+        let Some(algo) = self.pipeline.algorithm_by_id_mut(FIT_PHASE_ID) else {
+            self.set_error_status(format!("流水线中没有 {FIT_PHASE_ID} 模块"));
+            return;
+        };
+        let start = algo.get_params();
+        self.param_fit_state.start(
+            self.pipeline.seed(),
+            self.pipeline.size_mixing(),
+            self.pipeline.rng_kind(),
+            &self.world_profile,
+            start,
+            self.pipeline.locked_param_keys(FIT_PHASE_ID),
+        );
+    }
+
+    /// 按当前流水线（种子、参数、层级、跳过的步骤）启动 A/B 对比的后台生成；
+    /// `with_base` 时同时刷新参数列表并重新生成 A 侧
+    fn start_ab_compare(&mut self, with_base: bool) {
+        if with_base {
+            self.ab_compare_state.set_params(numeric_params(&self.pipeline));
+        }
+        let size = &self.world_profile.size;
+        let settings = VariantSettings {
+            seed: self.pipeline.seed(),
+            size_key: size.key.clone(),
+            custom_size: Some((size.width, size.height)),
+            seed_size: None,
+            snapshot: self.collect_snapshot(),
+            layers: self.world_profile.layers.clone(),
+            disabled_steps: self.pipeline.disabled_steps(),
+            size_mixing: self.pipeline.size_mixing(),
+            rng_kind: self.pipeline.rng_kind(),
+        };
+        self.ab_compare_state.start(settings, with_base);
+    }
+
+    /// 步骤开关预览的生成设置：当前流水线的种子、参数与启用集合，尺寸缩到低分辨率，
+    /// 子种子仍按真实世界尺寸混入
+    fn step_preview_settings(&self) -> VariantSettings {
+        let size = &self.world_profile.size;
+        VariantSettings {
+            seed: self.pipeline.seed(),
+            size_key: "custom".to_string(),
+            custom_size: Some(preview_size((size.width, size.height))),
+            seed_size: Some((size.width, size.height)),
+            snapshot: self.collect_snapshot(),
+            layers: self.world_profile.layers.clone(),
+            disabled_steps: self.pipeline.disabled_steps(),
+            size_mixing: self.pipeline.size_mixing(),
+            rng_kind: self.pipeline.rng_kind(),
+        }
+    }
+
+    /// 应用拟合得到的环境判定参数，已执行过步骤时重新执行到当前位置
+    fn apply_fitted_params(&mut self, params: &serde_json::Value) {
+        let Some(algo) = self.pipeline.algorithm_by_id_mut(FIT_PHASE_ID) else {
+            return;
+        };
+        algo.set_params(params);
+        if self.pipeline.executed_sub_steps() == 0 {
+            self.set_status("已应用拟合参数".to_string());
+            return;
+        }
+        match self.pipeline.replay_to_current(&mut self.world, &self.world_profile, &self.blocks) {
+            Ok(()) => {
+                self.texture_dirty = true;
+                self.set_status("已应用拟合参数并重新执行".to_string());
+            }
+            Err(e) => self.set_error_status(format!("重新执行失败: {e}")),
+        }
+    }
+
+    /// 子种子推导方式改变后，重新执行到当前进度使世界与新种子一致
+    fn replay_after_seed_change(&mut self) {
+        if self.pipeline.executed_sub_steps() == 0 {
+            return;
+        }
+        match self.pipeline.replay_to_current(&mut self.world, &self.world_profile, &self.blocks) {
+            Ok(()) => {
+                self.texture_dirty = true;
+                self.set_status("子种子推导已改变，已重新执行到当前步骤".to_string());
             }
+            Err(e) => self.set_error_status(format!("重新执行失败: {e}")),
         }
+    }
 
-        // ── "一键生成" or "执行到底": start incremental run
-        if action.run_all {
-            self.running_to_end = true;
-        }
+    fn refresh_dry_run(&mut self) {
+        self.dry_run_state = DryRunState {
+            noops: self.pipeline.dry_run(),
+            total: self.pipeline.total_sub_steps(),
+        };
+    }
 
-        // ── 导出 PNG
-        if action.export_png {
-            let dialog = rfd::FileDialog::new()
-                .set_title("导出 PNG")
-                .set_file_name("world_export.png")
-                .add_filter("PNG 图片", &["png"]);
-            if let Some(path) = dialog.save_file() {
-                match export_png(&self.world, &self.color_lut, &path) {
-                    Ok(()) => {
-                        self.last_status = format!("PNG 已导出: {}", path.display());
-                    }
-                    Err(e) => {
-                        self.last_status = format!("PNG 导出失败: {e}");
-                    }
-                }
-            }
+    /// 把沙箱中选中的形状以指定环境填充到当前环境地图
+    fn apply_sandbox_shape(&mut self, apply: SandboxApply) {
+        let Some(bm) = self.pipeline.biome_map_mut() else {
+            self.set_error_status("应用到世界失败: 需先执行生成步骤以创建环境地图".to_string());
+            return;
+        };
+        let filter = |c: BiomeId| !apply.only_unassigned || c == BIOME_UNASSIGNED;
+        if apply.outline > 0 {
+            geometry::fill_biome_outline(apply.shape.as_ref(), bm, apply.biome, apply.outline, filter);
+        } else {
+            geometry::fill_biome_if(apply.shape.as_ref(), bm, apply.biome, filter);
         }
+        // 只重传形状包围盒覆盖的那部分覆盖层纹理
+        let bb = apply.shape.bounding_box();
+        let x0 = bb.x_min.clamp(0, bm.width as i32) as u32;
+        let y0 = bb.y_min.clamp(0, bm.height as i32) as u32;
+        let x1 = bb.x_max.clamp(0, bm.width as i32) as u32;
+        let y1 = bb.y_max.clamp(0, bm.height as i32) as u32;
+        self.gl_canvas.lock().unwrap().mark_biome_changed([
+            x0, y0, x1.saturating_sub(x0), y1.saturating_sub(y0),
+        ]);
+
+        let name = self.biomes.iter()
+            .find(|b| b.id == apply.biome)
+            .map_or("?", |b| b.name.as_str());
+        let message = format!("已将「{}」以 {} 应用到世界", apply.label, name);
+        self.set_status(message);
+    }
 
-        // ── 导出 .lwd
-        if action.export_lwd {
-            let snapshot = self.pipeline.collect_snapshot(
-                self.world_size_key(),
-                &self.world_profile.layers,
-            );
-            let dialog = rfd::FileDialog::new()
-                .set_title("导出世界存档")
-                .set_file_name("world_export.lwd")
-                .add_filter("Lian World 存档", &["lwd"]);
-            if let Some(path) = dialog.save_file() {
-                match snapshot.save_lwd(&path) {
-                    Ok(()) => {
-                        self.last_status = format!("存档已导出: {}", path.display());
-                    }
-                    Err(e) => {
-                        self.last_status = format!("存档导出失败: {e}");
-                    }
-                }
-            }
-        }
+    /// 在 (x, y) 以当前画笔环境与半径绘制
+    fn paint_biome_at(&mut self, x: u32, y: u32) {
+        let Some(biome) = self.biome_paint_state.current(&self.biomes).map(|b| b.id) else {
+            return;
+        };
+        let Some(bm) = self.pipeline.biome_map_mut() else {
+            self.set_error_status("绘制失败: 需先执行生成步骤以创建环境地图".to_string());
+            return;
+        };
+        let region = paint_disc(bm, x, y, self.biome_paint_state.radius, biome);
+        self.gl_canvas.lock().unwrap().mark_biome_changed(region);
+        self.world_hash = None;
+        self.world_boundary = None;
+    }
 
-        // ── 导入 .lwd
-        if action.import_lwd {
-            let dialog = rfd::FileDialog::new()
-                .set_title("导入世界存档")
-                .add_filter("Lian World 存档", &["lwd"]);
-            if let Some(path) = dialog.pick_file() {
-                match WorldSnapshot::load_lwd(&path) {
-                    Ok(snapshot) => {
-                        // 1) 恢复世界尺寸
-                        self.world_size = match snapshot.world_size.as_str() {
-                            "medium" => WorldSizeSelection::Medium,
-                            "large" => WorldSizeSelection::Large,
-                            "custom" => WorldSizeSelection::Custom,
-                            _ => WorldSizeSelection::Small,
-                        };
-                        self.world_profile = WorldProfile::from_config(
-                            &self.world_cfg,
-                            &snapshot.world_size,
-                            None,
-                        )
-                        .expect("world size 配置非法");
-                        
-                        // 2) 恢复层级配置
-                        for layer in &mut self.world_profile.layers {
-                            if let Some(ov) = snapshot.layers.get(&layer.key) {
-                                layer.start_percent = ov.start_percent;
-                                layer.end_percent = ov.end_percent;
-                            }
-                        }
-                        
-                        self.world = self.world_profile.create_world();
-                        
-                        // 3) 恢复种子 + 算法参数
-                        self.pipeline.set_seed(snapshot.seed);
-                        self.pipeline.restore_from_snapshot(&snapshot);
-                        
-                        // 4) 增量重新执行全部步骤
-                        self.pipeline.reset_all(&mut self.world);
-                        self.running_to_end = true;
-                        self.texture_dirty = true;
-                        self.viewport.reset();
-                        self.last_status = format!(
-                            "正在从存档恢复 (seed: {})…",
-                            snapshot.seed
-                        );
-                        
-                        save_runtime_ui_state(
-                            self.world_size,
-                            &self.overlay,
-                        );
-                    }
-                    Err(e) => {
-                        self.last_status = format!("存档导入失败: {e}");
-                    }
-                }
+    /// 以导入的方块与环境替换当前世界（尺寸切换为自定义）
+    fn apply_wld_import(&mut self, import: wld::WldImport) {
+        let (width, height) = (import.world.width, import.world.height);
+        let profile = match WorldProfile::from_config(&self.world_cfg, "custom", Some((width, height))) {
+            Ok(p) => p,
+            Err(e) => {
+                self.set_error_status(format!(".wld 导入失败: {e}"));
+                return;
             }
-        }
+        };
+        self.capture_undo_backup();
+        self.world_size = WorldSizeSelection::Custom;
+        self.custom_width = width.to_string();
+        self.custom_height = height.to_string();
+        self.world_profile = profile;
+        load_runtime_layers(&mut self.world_profile.layers);
+
+        self.running_to_end = false;
+        self.pipeline.reset_all(&mut self.world);
+        let old_size = (self.world.width, self.world.height);
+        self.world = import.world;
+        self.pipeline.load_biome_map(import.biome_map);
+        self.update_viewport_for_world_change(old_size);
+        self.texture_dirty = true;
+        self.set_status(format!(
+            "已导入 .wld: {} ({}×{}, 版本 {})；执行生成步骤会覆盖导入的内容",
+            import.name, width, height, import.version
+        ));
+        save_runtime_ui_state(self.world_size, &self.overlay, &self.export_overlay);
     }
 }
 
@@ -595,10 +2147,11 @@ fn load_runtime_layers(layers: &mut [crate::core::layer::LayerDefinition]) {
     }
 }
 
-/// 从 runtime.json 加载 UI 状态 (world_size, overlay 开关)
-fn load_runtime_ui_state() -> (WorldSizeSelection, OverlaySettings) {
+/// 从 runtime.json 加载 UI 状态 (world_size, overlay 开关)，并恢复界面语言
+fn load_runtime_ui_state() -> (WorldSizeSelection, OverlaySettings, ExportOverlaySettings) {
     let mut size = WorldSizeSelection::Small;
     let mut overlay = OverlaySettings::default();
+    let mut export_overlay = ExportOverlaySettings::default();
     
     if let Some(ui_val) = app_runtime::load_field("ui") {
         if let Some(ui) = ui_val.as_object() {
@@ -622,6 +2175,84 @@ fn load_runtime_ui_state() -> (WorldSizeSelection, OverlaySettings) {
             if let Some(b) = ui.get("show_layer_labels").and_then(|v| v.as_bool()) {
                 overlay.show_layer_labels = b;
             }
+            if let Some(b) = ui.get("show_layer_bands").and_then(|v| v.as_bool()) {
+                overlay.show_layer_bands = b;
+            }
+            if let Some(f) = ui.get("layer_band_opacity").and_then(|v| v.as_f64()) {
+                overlay.layer_band_opacity = f.clamp(0.0, 1.0) as f32;
+            }
+            if let Some(b) = ui.get("biome_depth_tint").and_then(|v| v.as_bool()) {
+                overlay.biome_depth_tint = b;
+            }
+            if let Some(f) = ui.get("biome_depth_tint_strength").and_then(|v| v.as_f64()) {
+                overlay.biome_depth_tint_strength = f.clamp(0.0, 1.0) as f32;
+            }
+            if let Some(p) = ui.get("biome_pattern").and_then(|v| v.as_str()).and_then(BiomePattern::from_key) {
+                overlay.biome_pattern = p;
+            }
+            if let Some(b) = ui.get("terraria_map_colors").and_then(|v| v.as_bool()) {
+                overlay.terraria_map_colors = b;
+            }
+            if let Some(b) = ui.get("biome_label_background").and_then(|v| v.as_bool()) {
+                overlay.biome_label_background = b;
+            }
+            if let Some(c) = ui.get("biome_label_background_color").and_then(|v| serde_json::from_value::<[u8; 4]>(v.clone()).ok()) {
+                overlay.biome_label_background_color = c;
+            }
+            if let Some(h) = ui.get("unassigned_highlight").and_then(|v| v.as_str()).and_then(UnassignedHighlight::from_key) {
+                overlay.unassigned_highlight = h;
+            }
+            if let Some(c) = ui.get("unassigned_highlight_color").and_then(|v| serde_json::from_value::<[u8; 3]>(v.clone()).ok()) {
+                overlay.unassigned_highlight_color = c;
+            }
+            if let Some(s) = ui.get("minimap_source").and_then(|v| v.as_str()).and_then(MinimapSource::from_key) {
+                overlay.minimap_source = s;
+            }
+            if let Some(b) = ui.get("terraria_coords").and_then(|v| v.as_bool()) {
+                overlay.terraria_coords = b;
+            }
+            if let Some(f) = ui.get("feet_per_tile").and_then(|v| v.as_f64()) {
+                overlay.feet_per_tile = f.clamp(0.1, 16.0) as f32;
+            }
+            if let Some(b) = ui.get("show_crosshair").and_then(|v| v.as_bool()) {
+                overlay.show_crosshair = b;
+            }
+            if let Some(b) = ui.get("show_tile_boundaries").and_then(|v| v.as_bool()) {
+                overlay.show_tile_boundaries = b;
+            }
+            if let Some(z) = ui.get("tile_boundary_min_zoom").and_then(|v| v.as_f64()) {
+                overlay.tile_boundary_min_zoom = (z as f32).clamp(2.0, 20.0);
+            }
+            if let Some(b) = ui.get("world_size_details").and_then(|v| v.as_bool()) {
+                overlay.world_size_details = b;
+            }
+            if let Some(u) = ui.get("world_size_unit").and_then(|v| v.as_str()).and_then(SizeUnit::from_key) {
+                overlay.world_size_unit = u;
+            }
+            if let Some(p) = ui.get("export_biome_pattern").and_then(|v| v.as_str()).and_then(BiomePattern::from_key) {
+                export_overlay.biome_pattern = p;
+            }
+            if let Some(b) = ui.get("export_depth_shading").and_then(|v| v.as_bool()) {
+                export_overlay.depth_shading = b;
+            }
+            if let Some(b) = ui.get("export_layer_annotations").and_then(|v| v.as_bool()) {
+                export_overlay.layer_annotations = b;
+            }
+            if let Some(b) = ui.get("export_biome_legend").and_then(|v| v.as_bool()) {
+                export_overlay.biome_legend = b;
+            }
+            if let Some(b) = ui.get("export_scale_bar").and_then(|v| v.as_bool()) {
+                export_overlay.scale_bar = b;
+            }
+            if let Some(c) = ui.get("export_scale_bar_corner").and_then(|v| v.as_str()).and_then(ScaleBarCorner::from_key) {
+                export_overlay.scale_bar_corner = c;
+            }
+            if let Some(n) = ui.get("export_scale_bar_tiles").and_then(|v| v.as_u64()) {
+                export_overlay.scale_bar_tiles = n as u32;
+            }
+            if let Some(code) = ui.get("language").and_then(|v| v.as_str()) {
+                i18n::set_language(code);
+            }
             // 兼容旧配置
             if let Some(b) = ui.get("show_biome_overlay").and_then(|v| v.as_bool()) {
                 if !ui.contains_key("show_biome_color") {
@@ -638,13 +2269,14 @@ fn load_runtime_ui_state() -> (WorldSizeSelection, OverlaySettings) {
         }
     }
     
-    (size, overlay)
+    (size, overlay, export_overlay)
 }
 
-/// 保存 UI 状态 (world_size, overlay 开关) 到 runtime.json
+/// 保存 UI 状态 (world_size, overlay 开关, 导出覆盖层, 界面语言) 到 runtime.json
 fn save_runtime_ui_state(
     world_size: WorldSizeSelection,
     overlay: &OverlaySettings,
+    export_overlay: &ExportOverlaySettings,
 ) {
     use serde_json::json;
     
@@ -661,13 +2293,52 @@ fn save_runtime_ui_state(
         "show_biome_labels": overlay.show_biome_labels,
         "show_layer_lines": overlay.show_layer_lines,
         "show_layer_labels": overlay.show_layer_labels,
+        "show_layer_bands": overlay.show_layer_bands,
+        "layer_band_opacity": overlay.layer_band_opacity,
+        "biome_depth_tint": overlay.biome_depth_tint,
+        "biome_depth_tint_strength": overlay.biome_depth_tint_strength,
+        "biome_pattern": overlay.biome_pattern.key(),
+        "terraria_map_colors": overlay.terraria_map_colors,
+        "biome_label_background": overlay.biome_label_background,
+        "biome_label_background_color": overlay.biome_label_background_color,
+        "unassigned_highlight": overlay.unassigned_highlight.key(),
+        "unassigned_highlight_color": overlay.unassigned_highlight_color,
+        "minimap_source": overlay.minimap_source.key(),
+        "terraria_coords": overlay.terraria_coords,
+        "feet_per_tile": overlay.feet_per_tile,
+        "show_crosshair": overlay.show_crosshair,
+        "show_tile_boundaries": overlay.show_tile_boundaries,
+        "tile_boundary_min_zoom": overlay.tile_boundary_min_zoom,
+        "world_size_details": overlay.world_size_details,
+        "world_size_unit": overlay.world_size_unit.key(),
+        "export_biome_pattern": export_overlay.biome_pattern.key(),
+        "export_depth_shading": export_overlay.depth_shading,
+        "export_layer_annotations": export_overlay.layer_annotations,
+        "export_biome_legend": export_overlay.biome_legend,
+        "export_scale_bar": export_overlay.scale_bar,
+        "export_scale_bar_corner": export_overlay.scale_bar_corner.key(),
+        "export_scale_bar_tiles": export_overlay.scale_bar_tiles,
+        "language": i18n::language(),
     });
     
     let _ = app_runtime::merge_field("ui", ui_state);
 }
 
+/// 千分位分隔：`20160000` → `20,160,000`
+fn group_thousands(n: u64) -> String {
+    let digits = n.to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
 /// 将性能分析数据持久化到日志文件
-fn save_perf_log(pipeline: &GenerationPipeline, world: &World) {
+fn save_perf_log(pipeline: &GenerationPipeline, world: &World, total_ms: f64, max_files: usize) {
     use crate::storage::perf_log::{PerfEntry, StepEntry};
 
     let profiler = pipeline.profiler();
@@ -686,13 +2357,58 @@ fn save_perf_log(pipeline: &GenerationPipeline, world: &World) {
         timestamp: now,
         seed: format!("{:016X}", pipeline.seed()),
         world_size: format!("{}x{}", world.width, world.height),
-        total_ms: profiler.total_generation_time().as_secs_f64() * 1000.0,
+        total_ms,
         steps,
     };
 
-    // 使用 EngineConfig 中的 max_files 设置
-    let cfg = EngineConfig::load();
-    crate::storage::perf_log::save_entry(&entry, cfg.perf_log_max_files);
+    crate::storage::perf_log::save_entry(&entry, max_files);
+}
+
+/// 向 CSV 生成日志追加一行（各步骤耗时 + 关键决策）
+fn save_csv_log(pipeline: &GenerationPipeline, world: &World, biomes: &[BiomeDefinition], total_ms: f64) {
+    use crate::storage::csv_log::{self, CsvRun};
+
+    let profiler = pipeline.profiler();
+    let steps = profiler.step_durations().into_iter()
+        .map(|(_, name, last)| (name, last.as_secs_f64() * 1000.0))
+        .collect();
+
+    let params = pipeline.algorithm_by_id("biome_division").map(|a| a.get_params());
+    let wanted = |key: &str| {
+        params.as_ref()
+            .and_then(|p| p.get(key))
+            .and_then(|v| v.as_u64())
+            .map_or(String::new(), |n| n.to_string())
+    };
+    let count = |n: Option<usize>| n.map_or(String::new(), |n| n.to_string());
+    let jungle_side = pipeline.shared::<bool>("jungle_on_left")
+        .map_or(String::new(), |&left| if left { "left" } else { "right" }.to_string());
+    let jungle_variant = pipeline.shared::<BiomeId>("jungle_variant")
+        .map_or(String::new(), |&id| {
+            biomes.iter().find(|b| b.id == id).map_or(id.to_string(), |b| b.key.clone())
+        });
+    let decisions = vec![
+        ("jungle_side", jungle_side),
+        ("jungle_variant", jungle_variant),
+        ("desert_surface_placed", count(pipeline.shared::<Vec<(i32, i32)>>("desert_slots").map(Vec::len))),
+        ("desert_surface_wanted", wanted("desert_surface_count")),
+        ("desert_true_placed", count(pipeline.shared::<Vec<(i32, i32)>>("desert_true_slots").map(Vec::len))),
+        ("desert_true_wanted", wanted("desert_true_count")),
+        ("evil_placed", count(pipeline.shared::<Vec<(i32, i32, BiomeId)>>("evil_slots").map(Vec::len))),
+        ("evil_wanted", wanted("evil_count")),
+    ];
+
+    let run = CsvRun {
+        timestamp: chrono_timestamp(),
+        seed: format!("{:016X}", pipeline.seed()),
+        world_size: format!("{}x{}", world.width, world.height),
+        total_ms,
+        steps,
+        decisions,
+    };
+    if let Err(e) = csv_log::append_run(&run) {
+        log::warn(format!("写入 CSV 生成日志失败: {e}"));
+    }
 }
 
 /// 生成 YYYYMMDD_HHMMSS 格式的时间戳
@@ -751,6 +2467,11 @@ impl eframe::App for LianWorldApp {
         let is_zooming = ctx.input(|i| i.raw_scroll_delta.y.abs() > 0.5);
 
         self.apply_world_size_change();
+        self.poll_export_queue(ctx);
+
+        // 视口动画设置可能在性能面板中修改，每帧同步（只影响之后开始的动画）
+        self.viewport.animation_duration = self.engine_config.viewport_animation_ms as f32 / 1000.0;
+        self.viewport.animation_easing = self.engine_config.viewport_easing;
 
         // 生成进行中 + 正在缩放 → 延迟纹理更新（避免帧率锯齿）
         if !(self.running_to_end && is_zooming) {
@@ -760,6 +2481,7 @@ impl eframe::App for LianWorldApp {
         // ── left panel ──
         // 使用 pipeline 的缓存 phase_info（仅步骤变化时重建）
         let phase_info = self.pipeline.phase_info_list().to_vec();
+        let biome_toggles = self.pipeline.biome_toggles();
         let executed = self.pipeline.executed_sub_steps();
         let total = self.pipeline.total_sub_steps();
         let mut action = ControlAction::none();
@@ -775,9 +2497,18 @@ impl eframe::App for LianWorldApp {
                     &mut self.custom_height,
                     &self.world_cfg,
                     &mut self.seed_input,
-                    &phase_info,
-                    executed,
-                    total,
+                    ControlPanelState {
+                        phase_info: &phase_info,
+                        biome_toggles: &biome_toggles,
+                        executed,
+                        total,
+                        can_undo: self.undo_backup.is_some(),
+                        zoom: self.viewport.zoom,
+                        step_focus: &mut self.step_focus,
+                        playing: self.playing,
+                        play_interval_ms: &mut self.play_interval_ms,
+                        recorded_frames: self.frame_recorder.as_ref().map(FrameRecorder::frames),
+                    },
                 );
                 ui.separator();
                 ui.label(format!("缩放: {:.0}%", self.viewport.zoom * 100.0));
@@ -788,6 +2519,26 @@ impl eframe::App for LianWorldApp {
                 ));
             });
 
+        // Home 键：视口居中到出生点（文本框输入时不响应）
+        if self.has_started_generation
+            && !ctx.wants_keyboard_input()
+            && ctx.input(|i| i.key_pressed(egui::Key::Home))
+        {
+            action.center_on_spawn = true;
+        }
+
+        // ── 步骤开关预览 ──
+        match action.hover_step_toggle {
+            Some((flat_index, enabled)) => {
+                let settings = self.step_preview_settings();
+                self.step_preview.hover(ctx, flat_index, settings);
+                egui::show_tooltip_at_pointer(ctx, egui::Id::new("step_toggle_preview"), |ui| {
+                    self.step_preview.show(ui, flat_index, enabled, &self.biomes);
+                });
+            }
+            None => self.step_preview.clear_hover(),
+        }
+
         // ── algo config window ──
         if action.open_step_config {
             self.show_algo_config = true;
@@ -798,6 +2549,52 @@ impl eframe::App for LianWorldApp {
             self.show_geo_preview = true;
         }
 
+        // ── biome adjacency graph ──
+        if action.open_biome_graph {
+            self.show_biome_graph = true;
+            self.refresh_biome_graph();
+        }
+
+        // ── block palette ──
+        if action.open_block_palette {
+            self.show_block_palette = true;
+            self.refresh_block_counts();
+        }
+
+        // ── spawn suggestions ──
+        if action.open_spawn_suggest {
+            self.show_spawn_suggest = true;
+            self.refresh_spawn_suggestions();
+        }
+
+        // ── bookmarks ──
+        if action.open_bookmarks {
+            self.show_bookmarks = true;
+        }
+        if action.open_biome_paint {
+            self.show_biome_paint = true;
+        }
+        if action.open_biome_search {
+            self.show_biome_search = true;
+        }
+
+        // ── dry run ──
+        if action.check_steps {
+            self.show_dry_run = true;
+            self.refresh_dry_run();
+        }
+
+        // ── param fit ──
+        if action.open_param_fit {
+            self.show_param_fit = true;
+        }
+
+        // ── A/B 参数对比 ──
+        if action.open_ab_compare {
+            self.show_ab_compare = true;
+            self.start_ab_compare(true);
+        }
+
         // ── shape sandbox ──
         if action.open_shape_sandbox {
             let id = self.next_sandbox_id;
@@ -811,6 +2608,8 @@ impl eframe::App for LianWorldApp {
         }
 
         if self.show_overlay_config {
+            let minimap_source = self.overlay.minimap_source;
+            let terraria_map_colors = self.overlay.terraria_map_colors;
             let changed = show_overlay_config_window(
                 ctx,
                 &mut self.show_overlay_config,
@@ -819,17 +2618,40 @@ impl eframe::App for LianWorldApp {
             if changed {
                 // 切换 biome 覆盖色时重建 GL overlay 缓存
                 self.gl_canvas.lock().unwrap().invalidate_biome();
-                save_runtime_ui_state(self.world_size, &self.overlay);
+                if self.overlay.minimap_source != minimap_source
+                    || self.overlay.terraria_map_colors != terraria_map_colors
+                {
+                    self.texture_dirty = true;
+                }
+                save_runtime_ui_state(self.world_size, &self.overlay, &self.export_overlay);
             }
         }
 
         if self.show_algo_config {
+            let algo_id = self.pipeline.current_algorithm_mut()
+                .map(|(_, algo)| algo.meta().id)
+                .unwrap_or_default();
+            let locked = self.pipeline.locked_param_keys(&algo_id);
+            let jittered = self.pipeline.jittered_param_keys(&algo_id);
+            let jitter_strength = self.pipeline.jitter_strength();
             if let Some((_idx, algo)) = self.pipeline.current_algorithm_mut() {
                 let result = show_algo_config_window(
                     ctx,
                     &mut self.show_algo_config,
                     algo,
+                    &locked,
+                    &jittered,
+                    jitter_strength,
                 );
+                if let Some((key, lock)) = &result.toggle_lock {
+                    self.pipeline.set_param_locked(&algo_id, key, *lock);
+                }
+                if let Some((key, jitter)) = &result.toggle_jitter {
+                    self.pipeline.set_param_jittered(&algo_id, key, *jitter);
+                }
+                if let Some(strength) = result.jitter_strength {
+                    self.pipeline.set_jitter_strength(strength);
+                }
                 if result.replay_requested {
                     // 回退到当前阶段开头，然后重新执行到当前位置
                     let target = self.pipeline.executed_sub_steps();
@@ -848,16 +2670,16 @@ impl eframe::App for LianWorldApp {
                                         &self.world_profile,
                                         &self.blocks,
                                     ) {
-                                        self.last_status = format!("重新执行失败: {e}");
+                                        self.set_error_status(format!("重新执行失败: {e}"));
                                         break;
                                     }
                                 }
                                 self.texture_dirty = true;
-                                self.last_status = "已用新参数重新执行当前阶段".to_string();
+                                self.set_status("已用新参数重新执行当前阶段".to_string());
                             }
                             Ok(false) => {}
                             Err(e) => {
-                                self.last_status = format!("回退失败: {e}");
+                                self.set_error_status(format!("回退失败: {e}"));
                             }
                         }
                     }
@@ -868,6 +2690,7 @@ impl eframe::App for LianWorldApp {
         // ── layer config window ──
         if action.open_layer_config {
             self.show_layer_config = true;
+            self.layer_undo_captured = false;
         }
 
         // ── geo preview window ──
@@ -893,17 +2716,134 @@ impl eframe::App for LianWorldApp {
             );
         }
 
+        // ── biome adjacency graph window ──
+        if self.show_biome_graph {
+            let refresh = show_biome_graph_window(
+                ctx,
+                &mut self.show_biome_graph,
+                &mut self.biome_graph_state,
+                &self.biomes,
+                self.pipeline.executed_sub_steps(),
+            );
+            if refresh {
+                self.refresh_biome_graph();
+            }
+        }
+
+        // ── block palette window ──
+        if self.show_block_palette {
+            let refresh = show_block_palette_window(
+                ctx,
+                &mut self.show_block_palette,
+                &mut self.block_palette_state,
+                &self.blocks,
+                &self.color_lut,
+                self.pipeline.executed_sub_steps(),
+            );
+            if refresh {
+                self.refresh_block_counts();
+            }
+        }
+
+        // ── spawn suggestion window ──
+        if self.show_spawn_suggest {
+            let request = show_spawn_suggest_window(
+                ctx,
+                &mut self.show_spawn_suggest,
+                &mut self.spawn_suggest_state,
+                &self.biomes,
+                self.pipeline.executed_sub_steps(),
+            );
+            match request {
+                Some(SpawnSuggestRequest::Refresh) => self.refresh_spawn_suggestions(),
+                Some(SpawnSuggestRequest::Locate(x, y)) => {
+                    self.viewport.center_on(x as f32, y as f32, self.world.width, self.world.height);
+                }
+                None => {}
+            }
+        }
+
+        // ── biome brush window ──
+        if self.show_biome_paint {
+            show_biome_paint_window(ctx, &mut self.show_biome_paint, &mut self.biome_paint_state, &self.biomes);
+            if !self.show_biome_paint {
+                self.biome_paint_state.active = false;
+            }
+        }
+        self.biome_paint_state.handle_number_keys(ctx, &self.biomes);
+
+        // ── bookmark window ──
+        if self.show_bookmarks {
+            let jump = show_bookmarks_window(
+                ctx,
+                &mut self.show_bookmarks,
+                &mut self.bookmark_state,
+                self.pipeline.bookmarks_mut(),
+            );
+            if let Some((x, y)) = jump {
+                self.viewport.animate_to_center(x as f32, y as f32);
+                ctx.request_repaint();
+            }
+            if !self.show_bookmarks {
+                self.bookmark_state.placing = false;
+            }
+        }
+
+        // ── biome search window ──
+        if self.show_biome_search {
+            let target = show_biome_search_window(
+                ctx,
+                &mut self.show_biome_search,
+                &mut self.biome_search_state,
+                self.pipeline.biome_map(),
+                &self.biomes,
+                self.viewport.view_center(self.world.width, self.world.height),
+            );
+            if let Some((x, y)) = target {
+                self.viewport.animate_to_center(x, y);
+                ctx.request_repaint();
+            }
+        }
+
+        // ── dry run window ──
+        if self.show_dry_run && show_dry_run_window(ctx, &mut self.show_dry_run, &self.dry_run_state) {
+            self.refresh_dry_run();
+        }
+
+        // ── param fit window ──
+        if self.show_param_fit {
+            match show_param_fit_window(ctx, &mut self.show_param_fit, &mut self.param_fit_state, &self.biomes) {
+                Some(ParamFitRequest::Start) => self.start_param_fit(),
+                Some(ParamFitRequest::Apply(params)) => self.apply_fitted_params(&params),
+                None => {}
+            }
+        }
+
+        // ── A/B compare window ──
+        if self.show_ab_compare {
+            match show_ab_compare_window(ctx, &mut self.show_ab_compare, &mut self.ab_compare_state, &self.biomes) {
+                Some(AbCompareRequest::Refresh) => self.start_ab_compare(true),
+                Some(AbCompareRequest::Variant) => self.start_ab_compare(false),
+                None => {}
+            }
+        }
+
         // ── shape sandbox windows (多实例) ──
         let ws = (self.world.width, self.world.height);
+        let mut sandbox_applies = Vec::new();
         for sandbox in &mut self.shape_sandboxes {
             if sandbox.open {
-                show_shape_sandbox_window(ctx, sandbox, ws);
+                sandbox_applies.extend(show_shape_sandbox_window(ctx, sandbox, ws, &self.biomes, &mut self.shape_library));
             }
         }
         // 清理已关闭的沙箱
         self.shape_sandboxes.retain(|s| s.open);
+        for apply in sandbox_applies {
+            self.apply_sandbox_shape(apply);
+        }
         
         if self.show_layer_config {
+            let layers_before = self.world_profile.layers.clone();
             let changed = show_layer_config_window(
                 ctx,
                 &mut self.show_layer_config,
@@ -913,8 +2853,17 @@ impl eframe::App for LianWorldApp {
             
             // 如果层级配置改变，刷新纹理（虽然现在只影响可视化，但保持一致性）
             if changed {
+                // 层级加深覆盖色依赖层级边界，需要重建 overlay
+                self.gl_canvas.lock().unwrap().invalidate_biome();
+                // 每次打开窗口只在第一次修改前记录撤销点，整次编辑可一步撤销
+                if !self.layer_undo_captured {
+                    let edited = std::mem::replace(&mut self.world_profile.layers, layers_before);
+                    self.capture_undo_backup();
+                    self.world_profile.layers = edited;
+                    self.layer_undo_captured = true;
+                }
                 // 可以在这里触发重新生成或只是更新状态
-                self.last_status = "层级配置已更新".to_string();
+                self.set_status("层级配置已更新".to_string());
             }
         }
 
@@ -923,11 +2872,19 @@ impl eframe::App for LianWorldApp {
             self.show_perf_panel = true;
         }
         if self.show_perf_panel {
+            let texture_info = {
+                let gl = self.gl_canvas.lock().unwrap();
+                (gl.gpu_max_texture_size(), gl.world_tile_count())
+            };
+            let unknown_color_before = self.engine_config.unknown_biome_color;
             let cfg_changed = show_perf_panel_window(
                 ctx,
                 &mut self.show_perf_panel,
                 &mut self.engine_config,
                 self.pipeline.profiler(),
+                &self.pipeline.phase_step_counts(),
+                self.pipeline.seed(),
+                texture_info,
             );
             if cfg_changed {
                 // 应用配置到运行中的组件
@@ -935,12 +2892,76 @@ impl eframe::App for LianWorldApp {
                 crate::core::geometry::set_parallel_threshold(
                     self.engine_config.parallel_pixel_threshold,
                 );
+                self.pipeline.set_overlap_check(self.engine_config.debug_overlap_check);
+                if self.engine_config.unknown_biome_color != unknown_color_before {
+                    self.gl_canvas.lock().unwrap().invalidate_biome();
+                }
+                if self.pipeline.order_tracking() != self.engine_config.debug_assign_order {
+                    self.pipeline.set_order_tracking(self.engine_config.debug_assign_order);
+                    self.gl_canvas.lock().unwrap().invalidate_biome();
+                    // 开启时回放到当前位置，补全已执行步骤的分配顺序
+                    if self.engine_config.debug_assign_order && self.pipeline.executed_sub_steps() > 0 {
+                        match self.pipeline.replay_to_current(&mut self.world, &self.world_profile, &self.blocks) {
+                            Ok(()) => self.texture_dirty = true,
+                            Err(e) => self.set_error_status(format!("重新执行失败: {e}")),
+                        }
+                    }
+                }
+                if self.pipeline.size_mixing() != self.engine_config.seed_size_mixing
+                    || self.pipeline.rng_kind() != self.engine_config.rng_kind
+                {
+                    self.pipeline.set_size_mixing(self.engine_config.seed_size_mixing);
+                    self.pipeline.set_rng_kind(self.engine_config.rng_kind);
+                    self.replay_after_seed_change();
+                }
+                self.gl_canvas.lock().unwrap().set_checker_style(
+                    self.engine_config.checker_tile,
+                    self.engine_config.checker_color_a,
+                    self.engine_config.checker_color_b,
+                );
+                self.gl_canvas.lock().unwrap().set_texture_size_limit(self.engine_config.max_texture_size);
                 self.engine_config.save();
             }
         }
 
+        if action.open_log_console {
+            self.show_log_console = true;
+        }
+        if self.show_log_console {
+            show_log_console_window(ctx, &mut self.show_log_console, &mut self.log_console);
+        }
+
+        // ── padded export window ──
+        if self.show_padded_export {
+            let response = show_padded_export_window(
+                ctx,
+                &mut self.show_padded_export,
+                &mut self.padded_export,
+                &mut self.export_overlay,
+            );
+            if response.overlay_changed {
+                save_runtime_ui_state(self.world_size, &self.overlay, &self.export_overlay);
+            }
+            if response.export {
+                self.request_export(WorldExport::Padded);
+            }
+        }
+
+        // ── 播放：按间隔触发小步前进 ──
+        self.update_playback(ctx, &mut action);
+
         // ── dispatch actions ──
         self.handle_action(&action);
+        if action.copy_ascii {
+            self.copy_ascii_art(ctx);
+        }
+        if let Some(run_all) = self.pending_reset {
+            self.show_reset_confirm(ctx, run_all);
+        }
+        if let Some(export) = self.pending_export {
+            self.show_export_confirm(ctx, export);
+        }
+        self.show_session_restore(ctx);
 
         // ── incremental execution tick ──
         // 使用自适应批量大小控制器，自动调整每帧步骤数
@@ -963,10 +2984,11 @@ impl eframe::App for LianWorldApp {
                     &self.world_profile,
                     &self.blocks,
                 ) {
-                    Ok(_) => {}
+                    Ok(true) => self.record_step_frame(),
+                    Ok(false) => {}
                     Err(e) => {
                         self.running_to_end = false;
-                        self.last_status = format!("生成失败: {e}");
+                        self.set_step_error_status("生成失败", &e);
                         break;
                     }
                 }
@@ -984,6 +3006,7 @@ impl eframe::App for LianWorldApp {
             }
 
             let batch_ms = frame_elapsed.as_secs_f64() * 1000.0;
+            // 逐帧进度只更新状态栏，不写入日志
             self.last_status = format!(
                 "正在生成… {}/{} (batch={}, {:.1}ms/帧)",
                 self.pipeline.executed_sub_steps(),
@@ -998,25 +3021,20 @@ impl eframe::App for LianWorldApp {
                     throttle.reset();
                 }
 
-                // 降采样预览 → 全分辨率
-                self.ensure_full_resolution_texture(ctx);
-
-                let report = self.pipeline.performance_report();
-                eprintln!("{report}");
-
-                // 持久化性能日志
-                save_perf_log(&self.pipeline, &self.world);
-
-                self.last_status = format!(
-                    "全部步骤已完成 ({}/{}) — 总耗时 {:.1}ms",
-                    self.pipeline.executed_sub_steps(),
-                    self.pipeline.total_sub_steps(),
-                    self.pipeline.profiler().total_generation_time().as_secs_f64() * 1000.0,
-                );
+                self.finish_generation(ctx);
             }
             ctx.request_repaint(); // 确保下一帧继续处理
         }
 
+        self.maybe_write_checkpoint();
+
+        // 手动步进推迟的刷新：空闲满延迟后才标记
+        if self.manual_throttle.tick() {
+            self.texture_dirty = true;
+        } else if let Some(remaining) = self.manual_throttle.remaining() {
+            ctx.request_repaint_after(remaining);
+        }
+
         // 缩放时跳过纹理刷新（generation complete 除外，那个强制走 ensure_full_resolution）
         if !(self.running_to_end && is_zooming) {
             self.refresh_texture_if_dirty(ctx);
@@ -1024,17 +3042,24 @@ impl eframe::App for LianWorldApp {
 
         // 如果 overlay 开关变化，保存 UI 状态
         if action.open_overlay_config {
-            save_runtime_ui_state(self.world_size, &self.overlay);
+            save_runtime_ui_state(self.world_size, &self.overlay, &self.export_overlay);
         }
 
         // ── bottom bar ──
         let seed = self.pipeline.seed();
         let step_progress = match self.pipeline.current_step_display_id() {
             Some(id) => format!("Step {} ({}/{})", id, self.pipeline.executed_sub_steps(), self.pipeline.total_sub_steps()),
-            None if self.pipeline.is_complete() => format!("已完成 ({0}/{0})", self.pipeline.total_sub_steps()),
+            None if self.pipeline.is_complete() => tr_args("status.completed", &[&self.pipeline.total_sub_steps()]),
             None => format!("0/{}", self.pipeline.total_sub_steps()),
         };
-        let world_size_label = format!("{}×{}", self.world.width, self.world.height);
+        let world_size_label = self.world_size_label();
+        let spawn_offset = self.spawn_offset_label();
+        // 执行到底期间地图每帧都在变，结束后再算
+        if self.world_hash.is_none() && !self.running_to_end {
+            self.world_hash = self.pipeline.biome_map().map(|bm| bm.short_hash());
+            self.world_boundary = self.pipeline.biome_map()
+                .map(|bm| (bm.boundary_length(), bm.width as u64 * bm.height as u64));
+        }
         egui::TopBottomPanel::bottom("status_bar")
             .resizable(false)
             .min_height(28.0)
@@ -1049,20 +3074,47 @@ impl eframe::App for LianWorldApp {
                 let mem_mb = ((self.world.width as usize * self.world.height as usize * 4)
                     / (1024 * 1024))
                     .max(1);
-                show_status_bar(
-                    ui, fps, mem_mb,
-                    &self.last_status, &self.hover_status,
-                    seed, &step_progress, &world_size_label,
-                );
+                let viewport_biomes: Vec<(Color32, &str, f32)> = self
+                    .viewport_biomes
+                    .iter()
+                    .filter_map(|&(id, fraction)| {
+                        let bdef = self.biomes.iter().find(|b| b.id == id)?;
+                        let [r, g, b, _] = bdef.overlay_color;
+                        Some((Color32::from_rgb(r, g, b), bdef.name.as_str(), fraction))
+                    })
+                    .collect();
+                let status = StatusInfo {
+                    fps,
+                    memory_hint_mb: mem_mb,
+                    message: &self.last_status,
+                    hover: &self.hover_status,
+                    spawn_offset: &spawn_offset,
+                    paint_biome: self.biome_paint_state.active
+                        .then(|| self.biome_paint_state.current(&self.biomes))
+                        .flatten()
+                        .map(|bdef| {
+                            let [r, g, b, _] = bdef.overlay_color;
+                            (Color32::from_rgb(r, g, b), bdef.name.as_str())
+                        }),
+                    seed,
+                    step_progress: &step_progress,
+                    world_size_label: &world_size_label,
+                    viewport_biomes: &viewport_biomes,
+                    world_hash: self.world_hash.as_deref().unwrap_or(""),
+                    world_boundary: self.world_boundary,
+                };
+                show_status_bar(ui, &status, || self.repro_info());
             });
 
         // ── central canvas ──
+        let mut canvas_rect = Rect::NOTHING;
         egui::CentralPanel::default().show(ctx, |ui| {
+            canvas_rect = ui.max_rect();
             // 检查是否有生成操作发生（任何步进/重置/run_all 都算）
             if action.step_forward_sub || action.step_forward_phase
                 || action.step_backward_sub || action.step_backward_phase
                 || action.reset_and_step || action.run_all
-                || action.import_lwd
+                || action.import_lwd || action.import_wld
             {
                 self.has_started_generation = true;
             }
@@ -1072,21 +3124,53 @@ impl eframe::App for LianWorldApp {
                 show_splash(ui);
             } else if let Some(texture) = &self.texture {
                 let biome_map = self.pipeline.biome_map();
+                let mut place_bookmark = None;
+                let mut paint_at = None;
+                let canvas_options = CanvasDisplayOptions {
+                    biome_map,
+                    biome_definitions: &self.biomes,
+                    layers: &self.world_profile.layers,
+                    unknown_biome_color: self.engine_config.unknown_biome_color,
+                    terraria_colors: self.overlay.terraria_map_colors,
+                    show_biome_color: self.overlay.show_biome_color,
+                    biome_depth_tint: self.overlay.biome_depth_tint.then_some(self.overlay.biome_depth_tint_strength),
+                    biome_pattern: self.overlay.biome_pattern,
+                    assign_order: self.engine_config.debug_assign_order
+                        .then(|| self.pipeline.assign_order())
+                        .flatten()
+                        .map(|order| (order, self.pipeline.total_sub_steps())),
+                    show_biome_labels: self.overlay.show_biome_labels,
+                    biome_label_background: self.overlay.biome_label_background.then_some(self.overlay.biome_label_background_color),
+                    unassigned_highlight: (self.overlay.unassigned_highlight, self.overlay.unassigned_highlight_color),
+                    show_layer_lines: self.overlay.show_layer_lines,
+                    show_layer_labels: self.overlay.show_layer_labels,
+                    layer_band_opacity: self.overlay.show_layer_bands.then_some(self.overlay.layer_band_opacity),
+                    show_crosshair: self.overlay.show_crosshair,
+                    tile_boundary_min_zoom: self.overlay.show_tile_boundaries.then_some(self.overlay.tile_boundary_min_zoom),
+                    pan_inertia: self.engine_config.pan_inertia,
+                    zoom_sensitivity: self.engine_config.zoom_sensitivity,
+                    invert_zoom: self.engine_config.invert_zoom,
+                };
                 if let Some(hover) = show_canvas(
                     ui,
                     texture,
                     &self.world,
                     &self.color_lut,
                     &mut self.viewport,
-                    biome_map,
-                    &self.biomes,
-                    &self.world_profile.layers,
-                    self.overlay.show_biome_color,
-                    self.overlay.show_biome_labels,
-                    self.overlay.show_layer_lines,
-                    self.overlay.show_layer_labels,
+                    &canvas_options,
                     &self.gl_canvas,
                 ) {
+                    if self.bookmark_state.placing {
+                        ui.ctx().set_cursor_icon(egui::CursorIcon::Crosshair);
+                        if ui.input(|i| i.pointer.primary_clicked()) {
+                            place_bookmark = Some((hover.x, hover.y));
+                        }
+                    } else if self.biome_paint_state.active {
+                        ui.ctx().set_cursor_icon(egui::CursorIcon::Crosshair);
+                        if ui.input(|i| i.pointer.primary_clicked()) {
+                            paint_at = Some((hover.x, hover.y));
+                        }
+                    }
                     let idx = (hover.y * self.world.width + hover.x) as usize;
                     let block_id = self.world.tiles.get(idx).copied().unwrap_or(0);
                     let name = self
@@ -1113,15 +3197,115 @@ impl eframe::App for LianWorldApp {
                         String::new()
                     };
 
-                    self.hover_status =
-                        format!("{name}(ID:{block_id}) @ ({}, {}){biome_layer}", hover.x, hover.y);
+                    // 泰拉瑞亚游戏内坐标（罗盘 / 深度计）
+                    let terraria = if self.overlay.terraria_coords {
+                        let surface_row = self.surface_row();
+                        let (east, depth) = to_terraria_feet(
+                            hover.x, hover.y,
+                            self.world.width, surface_row,
+                            self.overlay.feet_per_tile,
+                        );
+                        format!(" | {}", format_terraria_feet(east, depth))
+                    } else {
+                        String::new()
+                    };
+
+                    self.hover_status = format!(
+                        "{name}(ID:{block_id}) @ ({}, {}){terraria}{biome_layer}",
+                        hover.x, hover.y
+                    );
                 } else {
                     self.hover_status.clear();
                 }
+
+                // ── 视口内环境占比（下一帧在状态栏显示） ──
+                self.viewport_biomes = biome_map
+                    .map(|bm| visible_biome_fractions(bm, canvas_rect, &self.viewport))
+                    .unwrap_or_default();
+
+                if let Some((x, y)) = place_bookmark {
+                    self.bookmark_state.place(self.pipeline.bookmarks_mut(), x, y);
+                }
+                if let Some((x, y)) = paint_at {
+                    self.paint_biome_at(x, y);
+                }
+
+                // ── debug: 森林填充扩散箭头 ──
+                let traces = self.engine_config.debug_diffusion_arrows
+                    .then(|| self.pipeline.shared::<Vec<DiffusionTrace>>(DIFFUSION_TRACE_KEY))
+                    .flatten();
+                if let Some(traces) = traces {
+                    draw_diffusion_arrows(
+                        ui.painter(),
+                        canvas_rect,
+                        &self.viewport,
+                        (self.world.width, self.world.height),
+                        traces,
+                        &self.biomes,
+                    );
+                }
+
+                // ── 出生点建议标记（窗口打开时） ──
+                let spawn_candidates = self.show_spawn_suggest
+                    .then_some(self.spawn_suggest_state.result.as_ref())
+                    .flatten();
+                if let Some(result) = spawn_candidates {
+                    draw_spawn_markers(
+                        ui.painter(),
+                        canvas_rect,
+                        &self.viewport,
+                        (self.world.width, self.world.height),
+                        &result.candidates,
+                    );
+                }
+
+                // ── 未完成提示水印 ──
+                if !self.pipeline.is_complete() {
+                    draw_partial_watermark(
+                        ui.painter(),
+                        canvas_rect,
+                        self.pipeline.executed_sub_steps(),
+                        self.pipeline.total_sub_steps(),
+                    );
+                }
+
+                // ── 书签图钉 ──
+                if self.bookmark_state.show_pins {
+                    draw_bookmark_pins(
+                        ui.painter(),
+                        canvas_rect,
+                        &self.viewport,
+                        (self.world.width, self.world.height),
+                        self.pipeline.bookmarks(),
+                    );
+                }
             } else {
                 ui.label("画布纹理尚未初始化");
             }
         });
 
+        // ── debug: 子种子推导覆盖层 ──
+        if self.engine_config.debug_seed_overlay && self.has_started_generation {
+            show_seed_trace_overlay(
+                ctx,
+                canvas_rect,
+                self.pipeline.seed(),
+                (self.world.width, self.world.height),
+                self.pipeline.seed_trace(),
+            );
+        }
+
+        // ── 后台导出进度 ──
+        show_export_progress(ctx, canvas_rect, self.export_queue.jobs());
+    }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        if self.engine_config.auto_save_session {
+            self.save_session();
+        }
+        // 正常退出：检查点只用于崩溃恢复（尚未回答是否恢复时保留，下次启动再问）
+        if !self.pending_session_crashed {
+            let _ = std::fs::remove_file(crash_checkpoint_path());
+        }
     }
 }
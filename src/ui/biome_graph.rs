@@ -0,0 +1,230 @@
+//! # 环境邻接图窗口
+//!
+//! 分析视图：扫描环境地图中相邻格的环境切换，统计哪些环境彼此接壤以及边界长度，
+//! 以节点-连线图展示（节点按 `overlay_color` 着色、面积反映格子数，连线粗细反映边界长度），
+//! 便于检查如「丛林从不与雪原接壤」之类的结构约束。
+
+use egui::{Color32, Context, Pos2, Sense, Stroke, Ui, Vec2};
+
+use crate::core::biome::{BiomeDefinition, BiomeId, BiomeMap, BIOME_UNASSIGNED};
+
+// ═══════════════════════════════════════════════════════════
+// 统计数据
+// ═══════════════════════════════════════════════════════════
+
+/// 一次邻接统计的结果
+pub struct BiomeGraph {
+    /// (环境, 格子数)，只含出现过的环境
+    pub nodes: Vec<(BiomeId, u64)>,
+    /// ((环境 a, 环境 b), 边界长度)
+    pub edges: Vec<((BiomeId, BiomeId), u64)>,
+    /// 统计时已执行的子步骤数（用于提示数据是否过期）
+    pub executed: usize,
+}
+
+impl BiomeGraph {
+    pub fn from_map(bm: &BiomeMap, executed: usize) -> Self {
+        let histogram = bm.histogram();
+        let nodes = histogram.iter()
+            .enumerate()
+            .filter(|&(_, &n)| n > 0)
+            .map(|(id, &n)| (id as BiomeId, n))
+            .collect();
+        Self {
+            nodes,
+            edges: bm.adjacency(),
+            executed,
+        }
+    }
+}
+
+/// 邻接图窗口的持久状态
+#[derive(Default)]
+pub struct BiomeGraphState {
+    pub graph: Option<BiomeGraph>,
+    /// 悬浮 / 选中的环境（高亮其连线）
+    pub focus: Option<BiomeId>,
+}
+
+// ═══════════════════════════════════════════════════════════
+// 公共接口
+// ═══════════════════════════════════════════════════════════
+
+/// 显示环境邻接图窗口。返回 `true` 表示用户请求重新统计。
+pub fn show_biome_graph_window(
+    ctx: &Context,
+    open: &mut bool,
+    state: &mut BiomeGraphState,
+    biomes: &[BiomeDefinition],
+    executed: usize,
+) -> bool {
+    let mut refresh = false;
+
+    egui::Window::new("◎ 环境邻接图")
+        .open(open)
+        .resizable(true)
+        .default_width(460.0)
+        .default_height(560.0)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if ui.button("↻ 重新统计").clicked() {
+                    refresh = true;
+                }
+                if let Some(graph) = &state.graph {
+                    if graph.executed != executed {
+                        ui.colored_label(Color32::from_rgb(230, 190, 90), "世界已变化，统计结果可能过期");
+                    } else {
+                        ui.weak(format!("{} 种环境 · {} 组邻接", graph.nodes.len(), graph.edges.len()));
+                    }
+                }
+            });
+            ui.separator();
+
+            let Some(graph) = &state.graph else {
+                ui.label("尚未生成环境地图。");
+                return;
+            };
+
+            draw_graph(ui, graph, biomes, &mut state.focus);
+            ui.separator();
+            draw_edge_table(ui, graph, biomes, state.focus);
+        });
+
+    refresh
+}
+
+// ═══════════════════════════════════════════════════════════
+// 节点-连线图
+// ═══════════════════════════════════════════════════════════
+
+fn biome_name(biomes: &[BiomeDefinition], id: BiomeId) -> &str {
+    if id == BIOME_UNASSIGNED {
+        return "未分配";
+    }
+    biomes.iter().find(|b| b.id == id).map_or("未知环境", |b| b.name.as_str())
+}
+
+fn biome_color(biomes: &[BiomeDefinition], id: BiomeId) -> Color32 {
+    biomes.iter()
+        .find(|b| b.id == id)
+        .map_or(Color32::GRAY, |b| {
+            let [r, g, bl, _] = b.overlay_color;
+            Color32::from_rgb(r, g, bl)
+        })
+}
+
+fn draw_graph(
+    ui: &mut Ui,
+    graph: &BiomeGraph,
+    biomes: &[BiomeDefinition],
+    focus: &mut Option<BiomeId>,
+) {
+    let size = Vec2::new(ui.available_width(), 300.0);
+    let (rect, response) = ui.allocate_exact_size(size, Sense::click());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 4.0, Color32::from_rgb(24, 24, 32));
+
+    let n = graph.nodes.len();
+    if n == 0 {
+        return;
+    }
+
+    // 节点均匀排在圆周上（按 id 顺序，结果稳定）
+    let center = rect.center();
+    let ring = (rect.width().min(rect.height()) * 0.5 - 40.0).max(20.0);
+    let position = |i: usize| -> Pos2 {
+        let angle = std::f32::consts::TAU * i as f32 / n as f32 - std::f32::consts::FRAC_PI_2;
+        center + Vec2::angled(angle) * ring
+    };
+    let index_of = |id: BiomeId| graph.nodes.iter().position(|&(b, _)| b == id);
+
+    // 节点半径 ∝ √格子数
+    let total: u64 = graph.nodes.iter().map(|&(_, c)| c).sum();
+    let radius = |count: u64| 6.0 + 18.0 * (count as f32 / total.max(1) as f32).sqrt();
+
+    // 连线粗细按对数缩放到 1~10 px
+    let max_len = graph.edges.iter().map(|&(_, l)| l).max().unwrap_or(1);
+    let thickness = |len: u64| 1.0 + 9.0 * ((len as f32).ln_1p() / (max_len as f32).ln_1p());
+
+    for &((a, b), len) in &graph.edges {
+        let (Some(ia), Some(ib)) = (index_of(a), index_of(b)) else {
+            continue;
+        };
+        let highlighted = focus.is_none_or(|f| f == a || f == b);
+        let alpha = if highlighted { 200 } else { 40 };
+        painter.line_segment(
+            [position(ia), position(ib)],
+            Stroke::new(thickness(len), Color32::from_white_alpha(alpha)),
+        );
+    }
+
+    let hover = response.hover_pos();
+    let mut hovered = None;
+    for (i, &(id, count)) in graph.nodes.iter().enumerate() {
+        let p = position(i);
+        let r = radius(count);
+        let color = biome_color(biomes, id);
+        let is_focus = *focus == Some(id);
+        painter.circle(p, r, color, Stroke::new(if is_focus { 2.5 } else { 1.0 }, Color32::WHITE));
+        painter.text(
+            p + Vec2::new(0.0, r + 8.0),
+            egui::Align2::CENTER_CENTER,
+            biome_name(biomes, id),
+            egui::FontId::proportional(12.0),
+            Color32::WHITE,
+        );
+        if hover.is_some_and(|h| h.distance(p) <= r.max(8.0)) {
+            hovered = Some((id, count));
+        }
+    }
+
+    if let Some((id, count)) = hovered {
+        response.clone().on_hover_text(format!(
+            "{}：{} 格（{:.1}%）",
+            biome_name(biomes, id),
+            count,
+            count as f64 / total.max(1) as f64 * 100.0,
+        ));
+    }
+    if response.clicked() {
+        // 点击节点切换高亮，点击空白处取消
+        *focus = hovered.map(|(id, _)| id).filter(|&id| *focus != Some(id));
+    }
+}
+
+// ═══════════════════════════════════════════════════════════
+// 邻接表
+// ═══════════════════════════════════════════════════════════
+
+fn draw_edge_table(
+    ui: &mut Ui,
+    graph: &BiomeGraph,
+    biomes: &[BiomeDefinition],
+    focus: Option<BiomeId>,
+) {
+    let mut edges: Vec<_> = graph.edges.iter()
+        .filter(|&&((a, b), _)| focus.is_none_or(|f| f == a || f == b))
+        .collect();
+    edges.sort_by_key(|e| std::cmp::Reverse(e.1));
+
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        egui::Grid::new("biome_graph_edges")
+            .num_columns(2)
+            .striped(true)
+            .spacing([12.0, 4.0])
+            .show(ui, |ui| {
+                ui.strong("相邻环境");
+                ui.strong("边界长度（格）");
+                ui.end_row();
+                for &&((a, b), len) in &edges {
+                    ui.horizontal(|ui| {
+                        ui.colored_label(biome_color(biomes, a), biome_name(biomes, a));
+                        ui.label("—");
+                        ui.colored_label(biome_color(biomes, b), biome_name(biomes, b));
+                    });
+                    ui.monospace(len.to_string());
+                    ui.end_row();
+                }
+            });
+    });
+}
@@ -0,0 +1,150 @@
+//! # 环境画笔
+//!
+//! 手动修改环境归属：开启画笔后在画布上单击，以所选环境填充以该格为中心、半径为 `radius` 的圆。
+//! 画笔模式下数字键 1~9 依次选择前 9 个环境定义，当前画笔环境显示在状态栏。
+//!
+//! 与沙箱应用形状一样只改环境地图，不进入存档：回放或执行生成步骤会覆盖画过的格子。
+
+use egui::{Color32, Context};
+
+use crate::core::biome::{BiomeDefinition, BiomeId, BiomeMap};
+use crate::core::geometry::{self, Ellipse, Shape};
+use crate::ui::i18n::{tr, tr_args};
+use crate::ui::theme;
+
+/// 画笔半径上限（格）
+pub const MAX_BRUSH_RADIUS: u32 = 64;
+
+/// 数字键 1~9，依次对应前 9 个环境定义
+const NUMBER_KEYS: [egui::Key; 9] = [
+    egui::Key::Num1, egui::Key::Num2, egui::Key::Num3,
+    egui::Key::Num4, egui::Key::Num5, egui::Key::Num6,
+    egui::Key::Num7, egui::Key::Num8, egui::Key::Num9,
+];
+
+/// 画笔窗口的持久状态
+pub struct BiomePaintState {
+    /// 画笔模式：单击画布时绘制
+    pub active: bool,
+    /// 当前画笔环境（`None` 时取第一个环境定义）
+    pub biome: Option<BiomeId>,
+    /// 画笔半径（格），0 为单格
+    pub radius: u32,
+}
+
+impl Default for BiomePaintState {
+    fn default() -> Self {
+        Self { active: false, biome: None, radius: 4 }
+    }
+}
+
+impl BiomePaintState {
+    /// 当前画笔环境的定义
+    pub fn current<'a>(&self, biomes: &'a [BiomeDefinition]) -> Option<&'a BiomeDefinition> {
+        self.biome
+            .and_then(|id| biomes.iter().find(|b| b.id == id))
+            .or_else(|| biomes.first())
+    }
+
+    /// 画笔模式下按数字键 1~9 时选择对应的环境（文本框有焦点时不处理）
+    pub fn handle_number_keys(&mut self, ctx: &Context, biomes: &[BiomeDefinition]) {
+        if !self.active || ctx.wants_keyboard_input() {
+            return;
+        }
+        let pressed = ctx.input(|i| NUMBER_KEYS.iter().position(|&key| i.key_pressed(key)));
+        if let Some(biome) = pressed.and_then(|index| biomes.get(index)) {
+            self.biome = Some(biome.id);
+        }
+    }
+}
+
+/// 以 `biome` 填充以 (x, y) 为中心、半径为 `radius` 的圆，返回需要重传覆盖层的区域 `[x, y, 宽, 高]`
+pub fn paint_disc(bm: &mut BiomeMap, x: u32, y: u32, radius: u32, biome: BiomeId) -> [u32; 4] {
+    // 半径加半格：半径 0 恰好覆盖中心格
+    let r = radius as f64 + 0.5;
+    let disc = Ellipse::new(x as f64, y as f64, r, r);
+    geometry::fill_biome(&disc, bm, biome);
+    let bb = disc.bounding_box();
+    let x0 = bb.x_min.clamp(0, bm.width as i32) as u32;
+    let y0 = bb.y_min.clamp(0, bm.height as i32) as u32;
+    let x1 = bb.x_max.clamp(0, bm.width as i32) as u32;
+    let y1 = bb.y_max.clamp(0, bm.height as i32) as u32;
+    [x0, y0, x1 - x0, y1 - y0]
+}
+
+/// 显示环境画笔窗口
+pub fn show_biome_paint_window(
+    ctx: &Context,
+    open: &mut bool,
+    state: &mut BiomePaintState,
+    biomes: &[BiomeDefinition],
+) {
+    egui::Window::new(tr("panel.biome_paint"))
+        .id(egui::Id::new("biome_paint_window"))
+        .open(open)
+        .resizable(false)
+        .default_width(260.0)
+        .show(ctx, |ui| {
+            let label = if state.active { tr("paint.stop") } else { tr("paint.start") };
+            if ui.button(label).on_hover_text(tr("paint.start.hint")).clicked() {
+                state.active = !state.active;
+            }
+            ui.horizontal(|ui| {
+                ui.label(tr("paint.radius"));
+                ui.add(egui::DragValue::new(&mut state.radius).clamp_range(0..=MAX_BRUSH_RADIUS));
+            });
+            ui.separator();
+
+            let current = state.current(biomes).map(|b| b.id);
+            egui::ScrollArea::vertical().max_height(280.0).show(ui, |ui| {
+                for (i, biome) in biomes.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        let [r, g, b, _] = biome.overlay_color;
+                        ui.colored_label(Color32::from_rgb(r, g, b), "■");
+                        let text = match i {
+                            0..=8 => format!("{} {}", i + 1, biome.name),
+                            _ => format!("  {}", biome.name),
+                        };
+                        if ui.selectable_label(current == Some(biome.id), text).clicked() {
+                            state.biome = Some(biome.id);
+                        }
+                    });
+                }
+            });
+            if state.active {
+                ui.colored_label(theme::BLUE_LIGHT, tr_args("paint.active", &[&biomes.len().min(NUMBER_KEYS.len())]));
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{paint_disc, BiomePaintState};
+    use crate::core::biome::{BiomeMap, BIOME_UNASSIGNED};
+    use crate::generation::headless::HeadlessContext;
+
+    #[test]
+    fn paint_disc_fills_a_clipped_circle() {
+        let mut bm = BiomeMap::new_filled(20, 10, BIOME_UNASSIGNED);
+        assert_eq!(paint_disc(&mut bm, 5, 5, 0, 3), [4, 4, 2, 2]);
+        assert_eq!(bm.histogram()[3], 1, "半径 0 只画中心格");
+
+        let region = paint_disc(&mut bm, 1, 1, 2, 4);
+        assert_eq!(region, [0, 0, 4, 4], "越界部分应被裁掉");
+        assert_eq!(bm.get(1, 1), 4);
+        assert_eq!(bm.get(3, 1), 4);
+        assert_eq!(bm.get(3, 3), BIOME_UNASSIGNED, "圆外的角不应被画");
+        assert_eq!(bm.get(5, 5), 3, "画笔只改圆内的格子");
+    }
+
+    #[test]
+    fn current_falls_back_to_the_first_biome() {
+        let context = HeadlessContext::load("small", None).unwrap();
+        let mut state = BiomePaintState::default();
+        assert_eq!(state.current(&context.biomes).unwrap().id, context.biomes[0].id);
+        state.biome = Some(context.biomes[2].id);
+        assert_eq!(state.current(&context.biomes).unwrap().id, context.biomes[2].id);
+        state.biome = Some(BIOME_UNASSIGNED);
+        assert_eq!(state.current(&context.biomes).unwrap().id, context.biomes[0].id, "未定义的环境回退到第一个");
+    }
+}
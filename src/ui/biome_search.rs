@@ -0,0 +1,169 @@
+//! # 查找环境
+//!
+//! 输入环境名称或 key，在环境地图的连通块（[`BiomeMap::components`]）中找到离视图中心
+//! 最近的一块，并把视口平滑移到它的质心。对同一环境重复查找时按距离由近到远依次循环。
+//!
+//! 连通块按地图内容哈希缓存，地图变化后下次查找时重新计算；循环顺序在首次查找时按当时的
+//! 视图中心确定，换了环境或地图变化后重新排序。
+
+use egui::Context;
+
+use crate::core::biome::{BiomeComponent, BiomeDefinition, BiomeId, BiomeMap};
+use crate::ui::theme;
+
+/// 小于该格数的连通块视为碎片，不参与查找（该环境只有碎片时除外）
+const MIN_COMPONENT_TILES: usize = 16;
+
+/// 查找窗口的持久状态
+#[derive(Default)]
+pub struct BiomeSearchState {
+    pub query: String,
+    /// 连通块缓存 (地图内容哈希, 连通块)
+    components: Option<(u64, Vec<BiomeComponent>)>,
+    /// 当前循环 (环境 ID, 按距离排序的连通块下标, 下一个位置)
+    cycle: Option<(BiomeId, Vec<usize>, usize)>,
+    /// 上一次查找的结果说明
+    message: Option<Result<String, String>>,
+}
+
+/// 按名称或 key 匹配环境：先精确匹配，再按包含匹配（不区分大小写）
+fn match_biome<'a>(query: &str, biomes: &'a [BiomeDefinition]) -> Option<&'a BiomeDefinition> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return None;
+    }
+    biomes.iter()
+        .find(|b| b.name.to_lowercase() == query || b.key == query)
+        .or_else(|| biomes.iter().find(|b| b.name.to_lowercase().contains(&query) || b.key.contains(&query)))
+}
+
+impl BiomeSearchState {
+    /// 查找下一个匹配的连通块，返回视口应移到的世界坐标
+    pub fn search(
+        &mut self,
+        biome_map: &BiomeMap,
+        biomes: &[BiomeDefinition],
+        view_center: (f32, f32),
+    ) -> Option<(f32, f32)> {
+        let result = self.find_next(biome_map, biomes, view_center);
+        let target = result.as_ref().ok().map(|&(_, point)| point);
+        self.message = Some(result.map(|(text, _)| text));
+        target
+    }
+
+    fn find_next(
+        &mut self,
+        biome_map: &BiomeMap,
+        biomes: &[BiomeDefinition],
+        view_center: (f32, f32),
+    ) -> Result<(String, (f32, f32)), String> {
+        let bdef = match_biome(&self.query, biomes)
+            .ok_or_else(|| format!("没有名称或 key 匹配「{}」的环境", self.query.trim()))?;
+
+        let hash = biome_map.content_hash();
+        if self.components.as_ref().is_none_or(|(h, _)| *h != hash) {
+            self.components = Some((hash, biome_map.components()));
+            self.cycle = None;
+        }
+        let components = &self.components.as_ref().expect("刚刚计算").1;
+
+        let restart = self.cycle.as_ref().is_none_or(|(id, _, _)| *id != bdef.id);
+        if restart {
+            let mut order: Vec<usize> = (0..components.len())
+                .filter(|&i| components[i].biome == bdef.id)
+                .collect();
+            if order.iter().any(|&i| components[i].size >= MIN_COMPONENT_TILES) {
+                order.retain(|&i| components[i].size >= MIN_COMPONENT_TILES);
+            }
+            let distance = |i: usize| {
+                let (x, y) = components[i].centroid;
+                (x - view_center.0).hypot(y - view_center.1)
+            };
+            order.sort_by(|&a, &b| distance(a).total_cmp(&distance(b)));
+            self.cycle = Some((bdef.id, order, 0));
+        }
+
+        let (_, order, next) = self.cycle.as_mut().expect("刚刚设置");
+        if order.is_empty() {
+            return Err(format!("当前环境地图中没有{}", bdef.name));
+        }
+        let index = *next % order.len();
+        *next = index + 1;
+        let component = &components[order[index]];
+        let (x, y) = component.centroid;
+        let [x0, y0, x1, y1] = component.bbox;
+        Ok((
+            format!(
+                "{} {}/{}：({:.0}, {:.0})，{} 格，范围 {}×{}",
+                bdef.name,
+                index + 1,
+                order.len(),
+                x, y,
+                component.size,
+                x1 - x0, y1 - y0,
+            ),
+            component.centroid,
+        ))
+    }
+}
+
+/// 显示查找环境窗口；查找成功时返回视口应移到的世界坐标。
+pub fn show_biome_search_window(
+    ctx: &Context,
+    open: &mut bool,
+    state: &mut BiomeSearchState,
+    biome_map: Option<&BiomeMap>,
+    biomes: &[BiomeDefinition],
+    view_center: (f32, f32),
+) -> Option<(f32, f32)> {
+    let mut target = None;
+
+    egui::Window::new("🔍 查找环境")
+        .open(open)
+        .resizable(false)
+        .default_width(300.0)
+        .show(ctx, |ui| {
+            let Some(biome_map) = biome_map else {
+                ui.label("尚未生成环境地图。");
+                return;
+            };
+            let mut search = false;
+            ui.horizontal(|ui| {
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut state.query)
+                        .hint_text("环境名称或 key，如 丛林 / jungle")
+                        .desired_width(180.0),
+                );
+                if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    search = true;
+                    response.request_focus();
+                }
+                if ui.button("查找").on_hover_text("再次查找同一环境时跳到下一处（由近到远循环）").clicked() {
+                    search = true;
+                }
+            });
+            ui.horizontal_wrapped(|ui| {
+                for bdef in biomes {
+                    if ui.small_button(&bdef.name).clicked() {
+                        state.query = bdef.name.clone();
+                        search = true;
+                    }
+                }
+            });
+            if search {
+                target = state.search(biome_map, biomes, view_center);
+            }
+            match &state.message {
+                Some(Ok(text)) => {
+                    ui.colored_label(theme::BLUE_LIGHT, text);
+                }
+                Some(Err(text)) => {
+                    ui.colored_label(theme::PINK, text);
+                }
+                None => {}
+            }
+        });
+
+    target
+}
+
@@ -0,0 +1,154 @@
+//! # 方块调色板窗口
+//!
+//! 内容检查视图：列出 `blocks.json` 中的全部方块定义（色块、ID、名称、分类、当前世界中的格子数），
+//! 可按名称 / 分类 / ID 筛选。点击一行选中方块，在窗口底部显示其描述与占比。
+
+use egui::{Color32, Context, Sense, Vec2};
+
+use crate::core::block::{BlockDefinition, BlockId};
+use crate::ui::i18n::tr;
+
+/// 各方块在世界中的格子数（按 ID 索引）
+pub struct BlockCounts {
+    pub counts: Vec<u64>,
+    /// 统计时已执行的子步骤数（用于提示数据是否过期）
+    pub executed: usize,
+}
+
+impl BlockCounts {
+    pub fn from_tiles(tiles: &[u8], executed: usize) -> Self {
+        let mut counts = vec![0u64; 256];
+        for &tile in tiles {
+            counts[tile as usize] += 1;
+        }
+        Self { counts, executed }
+    }
+}
+
+/// 调色板窗口的持久状态
+#[derive(Default)]
+pub struct BlockPaletteState {
+    /// 筛选文本（匹配名称、分类或 ID，不区分大小写）
+    pub filter: String,
+    /// 当前选中的方块（只用于窗口底部的详情）
+    selected: Option<BlockId>,
+    pub counts: Option<BlockCounts>,
+}
+
+/// 方块是否匹配筛选文本
+fn matches_filter(block: &BlockDefinition, filter: &str) -> bool {
+    let filter = filter.trim().to_lowercase();
+    filter.is_empty()
+        || block.name.to_lowercase().contains(&filter)
+        || block.category.to_lowercase().contains(&filter)
+        || block.id.to_string() == filter
+}
+
+/// 显示方块调色板窗口。返回 `true` 表示用户请求重新统计格子数。
+pub fn show_block_palette_window(
+    ctx: &Context,
+    open: &mut bool,
+    state: &mut BlockPaletteState,
+    blocks: &[BlockDefinition],
+    color_lut: &[Color32; 256],
+    executed: usize,
+) -> bool {
+    let mut refresh = false;
+
+    egui::Window::new(tr("panel.block_palette"))
+        .id(egui::Id::new("block_palette_window"))
+        .open(open)
+        .resizable(true)
+        .default_width(420.0)
+        .default_height(520.0)
+        .show(ctx, |ui| {
+            let visible: Vec<&BlockDefinition> = blocks.iter()
+                .filter(|b| matches_filter(b, &state.filter))
+                .collect();
+
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut state.filter)
+                        .hint_text("按名称 / 分类 / ID 筛选")
+                        .desired_width(180.0),
+                );
+                if !state.filter.is_empty() && ui.small_button("✕").on_hover_text("清除筛选").clicked() {
+                    state.filter.clear();
+                }
+                ui.weak(format!("{} / {} 种", visible.len(), blocks.len()));
+            });
+            ui.horizontal(|ui| {
+                if ui.button("↻ 统计格子数").clicked() {
+                    refresh = true;
+                }
+                if let Some(counts) = &state.counts
+                    && counts.executed != executed
+                {
+                    ui.colored_label(Color32::from_rgb(230, 190, 90), "世界已变化，格子数可能过期");
+                }
+            });
+            ui.separator();
+
+            let counts = state.counts.as_ref().map(|c| &c.counts);
+            let total: u64 = counts.map_or(0, |c| c.iter().sum());
+            egui::ScrollArea::vertical()
+                .max_height(ui.available_height() - 90.0)
+                .auto_shrink([false, false])
+                .show(ui, |ui| {
+                    egui::Grid::new("block_palette_grid")
+                        .num_columns(5)
+                        .striped(true)
+                        .spacing([10.0, 4.0])
+                        .show(ui, |ui| {
+                            ui.strong("");
+                            ui.strong("ID");
+                            ui.strong("名称");
+                            ui.strong("分类");
+                            ui.strong("格子数");
+                            ui.end_row();
+
+                            for block in &visible {
+                                let (rect, _) = ui.allocate_exact_size(Vec2::splat(16.0), Sense::hover());
+                                ui.painter().rect_filled(rect, 2.0, color_lut[block.id as usize]);
+                                ui.painter().rect_stroke(rect, 2.0, (1.0, Color32::from_gray(90)));
+                                ui.monospace(block.id.to_string());
+                                let is_selected = state.selected == Some(block.id);
+                                if ui.selectable_label(is_selected, &block.name)
+                                    .on_hover_text(&block.description)
+                                    .clicked()
+                                {
+                                    state.selected = (!is_selected).then_some(block.id);
+                                }
+                                ui.weak(&block.category);
+                                match counts {
+                                    Some(c) => ui.label(c[block.id as usize].to_string()),
+                                    None => ui.weak("-"),
+                                };
+                                ui.end_row();
+                            }
+                        });
+                });
+
+            ui.separator();
+            match state.selected.and_then(|id| blocks.iter().find(|b| b.id == id)) {
+                Some(block) => {
+                    ui.horizontal(|ui| {
+                        let (rect, _) = ui.allocate_exact_size(Vec2::splat(24.0), Sense::hover());
+                        ui.painter().rect_filled(rect, 3.0, color_lut[block.id as usize]);
+                        ui.strong(format!("#{} {}", block.id, block.name));
+                        ui.weak(&block.category);
+                    });
+                    ui.label(&block.description);
+                    if let Some(c) = counts {
+                        let n = c[block.id as usize];
+                        ui.weak(format!("当前世界 {n} 格（{:.2}%）", n as f64 / total.max(1) as f64 * 100.0));
+                    }
+                }
+                None => {
+                    ui.weak("点击一行选中方块");
+                }
+            }
+        });
+
+    refresh
+}
@@ -0,0 +1,138 @@
+//! # 书签
+//!
+//! 在世界坐标上放置命名图钉，便于在大世界中来回定位、标注感兴趣的地形。
+//! 书签保存在流水线中，随 .lwd 存档与会话自动保存（[`WorldSnapshot::bookmarks`]）一起保存；
+//! 不影响生成结果。
+//!
+//! [`WorldSnapshot::bookmarks`]: crate::generation::snapshot::WorldSnapshot::bookmarks
+
+use egui::{Align2, Color32, Context, FontId, Painter, Pos2, Rect, Stroke, Vec2};
+
+use crate::generation::snapshot::Bookmark;
+use crate::rendering::viewport::ViewportState;
+use crate::ui::theme;
+
+/// 书签窗口的持久状态
+pub struct BookmarkState {
+    /// 放置模式：下一次点击画布时在该处放下图钉
+    pub placing: bool,
+    /// 新书签的名称（留空时自动编号）
+    pub new_name: String,
+    /// 是否在画布上绘制图钉
+    pub show_pins: bool,
+}
+
+impl Default for BookmarkState {
+    fn default() -> Self {
+        Self {
+            placing: false,
+            new_name: String::new(),
+            show_pins: true,
+        }
+    }
+}
+
+impl BookmarkState {
+    /// 在 (x, y) 放下图钉并退出放置模式
+    pub fn place(&mut self, bookmarks: &mut Vec<Bookmark>, x: u32, y: u32) {
+        let name = match self.new_name.trim() {
+            "" => format!("书签 {}", bookmarks.len() + 1),
+            name => name.to_string(),
+        };
+        bookmarks.push(Bookmark { name, x, y });
+        self.new_name.clear();
+        self.placing = false;
+    }
+}
+
+/// 显示书签窗口；点击「定位」时返回目标世界坐标。
+pub fn show_bookmarks_window(
+    ctx: &Context,
+    open: &mut bool,
+    state: &mut BookmarkState,
+    bookmarks: &mut Vec<Bookmark>,
+) -> Option<(u32, u32)> {
+    let mut jump = None;
+
+    egui::Window::new("📌 书签")
+        .open(open)
+        .resizable(true)
+        .default_width(340.0)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("名称");
+                ui.add(egui::TextEdit::singleline(&mut state.new_name).hint_text("留空自动编号").desired_width(140.0));
+                let label = if state.placing { "✕ 取消放置" } else { "📌 放置" };
+                if ui.button(label).on_hover_text("之后在画布上单击一处放下图钉").clicked() {
+                    state.placing = !state.placing;
+                }
+            });
+            if state.placing {
+                ui.colored_label(theme::BLUE_LIGHT, "在画布上单击要标记的位置（拖拽仍可平移）");
+            }
+            ui.checkbox(&mut state.show_pins, "在画布上显示图钉");
+            ui.separator();
+
+            if bookmarks.is_empty() {
+                ui.label("还没有书签。");
+                return;
+            }
+            let mut remove = None;
+            egui::ScrollArea::vertical().max_height(280.0).show(ui, |ui| {
+                egui::Grid::new("bookmark_list")
+                    .num_columns(4)
+                    .striped(true)
+                    .spacing([8.0, 4.0])
+                    .show(ui, |ui| {
+                        for (i, bookmark) in bookmarks.iter_mut().enumerate() {
+                            ui.add(egui::TextEdit::singleline(&mut bookmark.name).desired_width(140.0));
+                            ui.monospace(format!("({}, {})", bookmark.x, bookmark.y));
+                            if ui.small_button("定位").clicked() {
+                                jump = Some((bookmark.x, bookmark.y));
+                            }
+                            if ui.small_button("✕").on_hover_text("删除书签").clicked() {
+                                remove = Some(i);
+                            }
+                            ui.end_row();
+                        }
+                    });
+            });
+            if let Some(i) = remove {
+                bookmarks.remove(i);
+            }
+        });
+
+    jump
+}
+
+/// 在画布上为书签画图钉与名称。`canvas_rect` 为画布所在区域（与 `show_canvas` 分配的区域相同）。
+pub fn draw_bookmark_pins(
+    painter: &Painter,
+    canvas_rect: Rect,
+    viewport: &ViewportState,
+    world_size: (u32, u32),
+    bookmarks: &[Bookmark],
+) {
+    let zoom = viewport.zoom;
+    let image_rect = viewport.image_rect(canvas_rect, world_size);
+    let painter = painter.with_clip_rect(canvas_rect);
+
+    for bookmark in bookmarks {
+        // 针尖落在书签格子的中心
+        let tip = Pos2::new(
+            image_rect.left() + (bookmark.x as f32 + 0.5) * zoom,
+            image_rect.top() + (bookmark.y as f32 + 0.5) * zoom,
+        );
+        if !canvas_rect.expand(80.0).contains(tip) {
+            continue;
+        }
+        let head = tip - Vec2::new(0.0, 14.0);
+        painter.line_segment([tip, head], Stroke::new(2.0, Color32::from_black_alpha(200)));
+        painter.circle(head, 6.0, theme::PINK, Stroke::new(1.5, Color32::from_black_alpha(200)));
+
+        let galley = painter.layout_no_wrap(bookmark.name.clone(), FontId::proportional(12.0), Color32::WHITE);
+        let bg = Align2::LEFT_CENTER.anchor_size(head + Vec2::new(10.0, 0.0), galley.size()).expand(2.0);
+        painter.rect_filled(bg, 2.0, Color32::from_rgba_unmultiplied(20, 20, 30, 200));
+        painter.galley(bg.min + Vec2::splat(2.0), galley, Color32::WHITE);
+    }
+}
@@ -3,11 +3,12 @@ use std::sync::{Arc, Mutex};
 use egui::{Color32, ColorImage, Pos2, Rect, Sense, Stroke, TextureHandle, Ui, Vec2};
 use rayon::prelude::*;
 
-use crate::core::biome::{BiomeDefinition, BiomeMap};
+use crate::core::biome::{BiomeDefinition, BiomeId, BiomeMap, BIOME_UNASSIGNED};
 use crate::core::layer::LayerDefinition;
 use crate::core::world::World;
 use crate::rendering::canvas::world_to_color_image_region_lod;
-use crate::rendering::gl_canvas::{GlCanvasParams, GlCanvasState, make_canvas_callback, pixels_to_rgba};
+use crate::rendering::gl_canvas::{GlCanvasParams, GlCanvasState, UnassignedHighlight, make_canvas_callback, pixels_to_rgba};
+use crate::rendering::pattern::BiomePattern;
 use crate::rendering::viewport::ViewportState;
 
 #[derive(Debug, Clone, Copy)]
@@ -16,20 +17,146 @@ pub struct HoverInfo {
     pub y: u32,
 }
 
+/// 画布上叠加显示的数据与显示 / 交互选项（由 app 每帧从覆盖层设置与引擎配置汇总一次）。
+///
+/// `Option` 开关字段为 `None` 时对应功能关闭，`Some` 携带其强度 / 颜色等设置。
+#[derive(Debug, Clone, Copy)]
+pub struct CanvasDisplayOptions<'a> {
+    pub biome_map: Option<&'a BiomeMap>,
+    pub biome_definitions: &'a [BiomeDefinition],
+    pub layers: &'a [LayerDefinition],
+    /// 未知环境 ID 的覆盖色
+    pub unknown_biome_color: [u8; 4],
+    /// 用泰拉瑞亚小地图配色代替自定义环境色
+    pub terraria_colors: bool,
+    pub show_biome_color: bool,
+    /// 按深度加深环境色的强度
+    pub biome_depth_tint: Option<f32>,
+    pub biome_pattern: BiomePattern,
+    /// 生成顺序热力图：`(每格的分配步骤序号, 子步骤总数)`
+    pub assign_order: Option<(&'a [u16], usize)>,
+    pub show_biome_labels: bool,
+    /// 环境标签底色
+    pub biome_label_background: Option<[u8; 4]>,
+    /// 未分配格子的高亮样式与颜色
+    pub unassigned_highlight: (UnassignedHighlight, [u8; 3]),
+    pub show_layer_lines: bool,
+    pub show_layer_labels: bool,
+    /// 层级色带不透明度
+    pub layer_band_opacity: Option<f32>,
+    pub show_crosshair: bool,
+    /// 显示物块网格的最小缩放
+    pub tile_boundary_min_zoom: Option<f32>,
+    pub pan_inertia: bool,
+    pub zoom_sensitivity: f32,
+    pub invert_zoom: bool,
+}
+
+/// 生成顺序热力图的不透明度
+const ORDER_HEAT_ALPHA: f32 = 0.75;
+
+/// 步骤序号 → 热力图颜色：第 1 步为蓝，最后一步为红（沿色相环经青、绿、黄过渡），0（未分配）透明
+fn order_heat_color(order: u16, total_steps: usize) -> Color32 {
+    if order == 0 {
+        return Color32::TRANSPARENT;
+    }
+    let t = (order as f32 - 1.0) / (total_steps.max(2) - 1) as f32;
+    let hue = (1.0 - t.clamp(0.0, 1.0)) * (2.0 / 3.0);
+    egui::ecolor::Hsva::new(hue, 0.85, 1.0, ORDER_HEAT_ALPHA).into()
+}
+
+/// 按分配顺序为区域 `[x, y, w, h]` 生成热力图 overlay（与 [`biome_overlay_image_region_lod`] 相同的取样方式）
+fn order_heat_image_region_lod(
+    order: &[u16],
+    total_steps: usize,
+    map_width: u32,
+    region: [u32; 4],
+    lod: u32,
+) -> ColorImage {
+    let f = lod.max(1) as usize;
+    let [rx, ry, rw, rh] = region.map(|v| v as usize);
+    let bw = map_width as usize;
+    let out_w = rw.div_ceil(f);
+    let out_h = rh.div_ceil(f);
+
+    let mut lut = vec![Color32::TRANSPARENT; total_steps + 1];
+    for (stamp, color) in lut.iter_mut().enumerate() {
+        *color = order_heat_color(stamp as u16, total_steps);
+    }
+
+    let mut pixels = vec![Color32::TRANSPARENT; out_w * out_h];
+    pixels
+        .par_chunks_mut(out_w)
+        .enumerate()
+        .for_each(|(out_row, row_pixels)| {
+            let src_y = ry + out_row * f;
+            for (out_x, px) in row_pixels.iter_mut().enumerate() {
+                if let Some(&stamp) = order.get(src_y * bw + rx + out_x * f) {
+                    *px = lut.get(stamp as usize).copied().unwrap_or(Color32::TRANSPARENT);
+                }
+            }
+        });
+
+    ColorImage {
+        size: [out_w, out_h],
+        pixels,
+    }
+}
+
+/// 区域 `[x, y, w, h]` 的分配掩码（已分配 = 255，未分配 = 0），取样方式与 [`biome_overlay_image_region_lod`] 相同。
+///
+/// 着色器按它而不是覆盖色的透明度判断未分配，不透明度为 0 的环境不会被当成未分配高亮
+fn biome_assigned_mask_region_lod(biome_map: &BiomeMap, region: [u32; 4], lod: u32) -> Vec<u8> {
+    let f = lod.max(1) as usize;
+    let [rx, ry, rw, rh] = region.map(|v| v as usize);
+    let bw = biome_map.width as usize;
+    let out_w = rw.div_ceil(f);
+    let out_h = rh.div_ceil(f);
+    let data = biome_map.data();
+
+    let mut mask = vec![0u8; out_w * out_h];
+    mask.par_chunks_mut(out_w)
+        .enumerate()
+        .for_each(|(out_row, row)| {
+            let src_y = ry + out_row * f;
+            for (out_x, texel) in row.iter_mut().enumerate() {
+                if data.get(src_y * bw + rx + out_x * f).is_some_and(|&id| id != BIOME_UNASSIGNED) {
+                    *texel = 255;
+                }
+            }
+        });
+    mask
+}
+
+/// 构建 biome ID → overlay 颜色（RGBA，未预乘）LUT
+///
+/// 未分配的格子透明；已分配但没有环境定义的 ID 使用 `unknown_color` 高亮，
+/// 避免环境定义不匹配时静默渲染成透明而难以察觉。
+/// `terraria_colors` 为真时改用各环境的游戏内地图色（见 [`BiomeDefinition::display_color`]）。
+fn biome_overlay_lut(
+    biome_definitions: &[BiomeDefinition],
+    unknown_color: [u8; 4],
+    terraria_colors: bool,
+) -> [[u8; 4]; 256] {
+    let mut lut = [unknown_color; 256];
+    lut[BIOME_UNASSIGNED as usize] = [0; 4];
+    for bdef in biome_definitions {
+        lut[bdef.id as usize] = bdef.display_color(terraria_colors);
+    }
+    lut
+}
+
 /// 从 2D BiomeMap 生成半透明 overlay 纹理（rayon 并行按行生成）
 fn biome_overlay_image(
     biome_map: &BiomeMap,
     biome_definitions: &[BiomeDefinition],
+    unknown_color: [u8; 4],
 ) -> ColorImage {
     let w = biome_map.width as usize;
     let h = biome_map.height as usize;
 
-    // 构建 biome ID → overlay 颜色 LUT（最多 256）
-    let mut biome_lut = [Color32::TRANSPARENT; 256];
-    for bdef in biome_definitions {
-        let c = bdef.overlay_color;
-        biome_lut[bdef.id as usize] = Color32::from_rgba_unmultiplied(c[0], c[1], c[2], c[3]);
-    }
+    let biome_lut = biome_overlay_lut(biome_definitions, unknown_color, false)
+        .map(|[r, g, b, a]| Color32::from_rgba_unmultiplied(r, g, b, a));
 
     let data = biome_map.data();
     let mut pixels = vec![Color32::TRANSPARENT; w * h];
@@ -51,10 +178,66 @@ fn biome_overlay_image(
     }
 }
 
+/// 计算每一行的层级加深系数（1.0 = 原色）。
+///
+/// 层级按 `start_percent` 从上到下排序，第 i 层的系数为 `1 - strength * i / (n - 1)`，
+/// 不属于任何层级的行保持原色。
+fn layer_depth_tint_rows(layers: &[LayerDefinition], height: u32, strength: f32) -> Vec<f32> {
+    let mut sorted: Vec<&LayerDefinition> = layers.iter().collect();
+    sorted.sort_by_key(|l| l.start_percent);
+    let steps = sorted.len().saturating_sub(1).max(1) as f32;
+
+    let mut rows = vec![1.0f32; height as usize];
+    for (i, layer) in sorted.iter().enumerate() {
+        let factor = 1.0 - strength.clamp(0.0, 1.0) * i as f32 / steps;
+        let (start, end) = layer.bounds_for_height(height);
+        for row in rows.iter_mut().take(end as usize).skip(start as usize) {
+            *row = factor;
+        }
+    }
+    rows
+}
+
+/// 层级色带的颜色：常见层级使用固定色，其余层级按顺序取备用色
+fn layer_band_color(key: &str, index: usize) -> [u8; 3] {
+    const FALLBACK: [[u8; 3]; 4] = [[200, 170, 90], [90, 190, 190], [190, 110, 190], [150, 150, 90]];
+    match key {
+        "space" => [90, 120, 230],
+        "surface" => [110, 200, 90],
+        "underground" => [170, 120, 70],
+        "cavern" => [130, 130, 150],
+        "hell" => [230, 70, 40],
+        _ => FALLBACK[index % FALLBACK.len()],
+    }
+}
+
+/// 每个层级一条色带：`([上边界, 下边界] 相对世界高度, RGBA 0..1)`
+fn layer_bands(layers: &[LayerDefinition], opacity: f32) -> Vec<([f32; 2], [f32; 4])> {
+    layers
+        .iter()
+        .enumerate()
+        .map(|(i, layer)| {
+            let [r, g, b] = layer_band_color(&layer.key, i).map(|c| c as f32 / 255.0);
+            (
+                [layer.start_percent as f32 / 100.0, layer.end_percent as f32 / 100.0],
+                [r, g, b, opacity.clamp(0.0, 1.0)],
+            )
+        })
+        .collect()
+}
+
 /// 从 BiomeMap 的子区域 [rx, ry, rw×rh] 生成半透明 overlay 纹理，按 LOD 降采样
+///
+/// `row_tint` 为每行的亮度系数（见 [`layer_depth_tint_rows`]），`None` 时不加深。
+/// 图案按降采样后的像素坐标取样，线条在任意 LOD 下都保持相同的像素粗细。
+#[allow(clippy::too_many_arguments)]
 fn biome_overlay_image_region_lod(
     biome_map: &BiomeMap,
     biome_definitions: &[BiomeDefinition],
+    unknown_color: [u8; 4],
+    terraria_colors: bool,
+    row_tint: Option<&[f32]>,
+    pattern: BiomePattern,
     rx: u32,
     ry: u32,
     rw: u32,
@@ -71,26 +254,63 @@ fn biome_overlay_image_region_lod(
     let out_w = (rw + f - 1) / f;
     let out_h = (rh + f - 1) / f;
 
-    let mut biome_lut = [Color32::TRANSPARENT; 256];
-    for bdef in biome_definitions {
-        let c = bdef.overlay_color;
-        biome_lut[bdef.id as usize] = Color32::from_rgba_unmultiplied(c[0], c[1], c[2], c[3]);
-    }
+    let raw_lut = biome_overlay_lut(biome_definitions, unknown_color, terraria_colors);
+    let biome_lut = raw_lut.map(|[r, g, b, a]| Color32::from_rgba_unmultiplied(r, g, b, a));
 
     let data = biome_map.data();
     let mut pixels = vec![Color32::TRANSPARENT; out_w * out_h];
 
+    if pattern != BiomePattern::Off {
+        pixels
+            .par_chunks_mut(out_w)
+            .enumerate()
+            .for_each(|(out_row, row_pixels)| {
+                let src_y = ry + out_row * f;
+                let tint = row_tint
+                    .and_then(|rows| rows.get(src_y).copied())
+                    .unwrap_or(1.0);
+                let shade = |v: u8| (v as f32 * tint) as u8;
+                for (out_x, px) in row_pixels.iter_mut().enumerate() {
+                    let src_x = rx + out_x * f;
+                    let Some(&id) = data.get(src_y * bw + src_x) else {
+                        continue;
+                    };
+                    let [r, g, b, a] = pattern.shade(
+                        id, raw_lut[id as usize], (src_x / f) as u32, (src_y / f) as u32,
+                    );
+                    *px = Color32::from_rgba_unmultiplied(shade(r), shade(g), shade(b), a);
+                }
+            });
+        return ColorImage {
+            size: [out_w, out_h],
+            pixels,
+        };
+    }
+
     pixels
         .par_chunks_mut(out_w)
         .enumerate()
         .for_each(|(out_row, row_pixels)| {
             let src_y = ry + out_row * f;
             let src_row_start = src_y * bw;
+            let tint = row_tint
+                .and_then(|rows| rows.get(src_y).copied())
+                .unwrap_or(1.0);
             for out_x in 0..out_w {
                 let src_x = rx + out_x * f;
                 let idx = src_row_start + src_x;
                 if idx < data.len() {
-                    row_pixels[out_x] = biome_lut[data[idx] as usize];
+                    let c = biome_lut[data[idx] as usize];
+                    row_pixels[out_x] = if tint < 1.0 {
+                        Color32::from_rgba_unmultiplied(
+                            (c.r() as f32 * tint) as u8,
+                            (c.g() as f32 * tint) as u8,
+                            (c.b() as f32 * tint) as u8,
+                            c.a(),
+                        )
+                    } else {
+                        c
+                    };
                 }
             }
         });
@@ -101,6 +321,55 @@ fn biome_overlay_image_region_lod(
     }
 }
 
+/// 环境标签与视口占比共用的自适应采样步长：缩放越小 → 步长越大（因为屏幕上细节更少）。
+/// zoom=0.3 → step=48, zoom=0.5 → step=32, zoom=1.0+ → step=16
+fn label_sample_step(zoom: f32) -> u32 {
+    if zoom < 0.4 { 48 } else if zoom < 0.8 { 32 } else { 16 }
+}
+
+/// 当前视口内各环境所占的比例（按采样点计，未分配区域不列出），按比例降序。
+///
+/// `canvas_rect` 为画布所在区域（与 `show_canvas` 分配的区域相同）；采样步长与环境标签一致。
+pub fn visible_biome_fractions(
+    biome_map: &BiomeMap,
+    canvas_rect: Rect,
+    viewport: &ViewportState,
+) -> Vec<(BiomeId, f32)> {
+    let zoom = viewport.zoom;
+    let (w, h) = (biome_map.width, biome_map.height);
+    let image_rect = viewport.image_rect(canvas_rect, (w, h));
+    let x0 = ((canvas_rect.left() - image_rect.left()) / zoom).max(0.0) as u32;
+    let y0 = ((canvas_rect.top() - image_rect.top()) / zoom).max(0.0) as u32;
+    let x1 = ((canvas_rect.right() - image_rect.left()) / zoom).ceil().clamp(0.0, w as f32) as u32;
+    let y1 = ((canvas_rect.bottom() - image_rect.top()) / zoom).ceil().clamp(0.0, h as f32) as u32;
+    if x1 <= x0 || y1 <= y0 {
+        return Vec::new();
+    }
+
+    // 视口很小时步长不超过可见范围的 1/4，保证至少有几个采样点
+    let step = label_sample_step(zoom)
+        .min((x1 - x0).min(y1 - y0).div_ceil(4))
+        .max(1) as usize;
+    let mut counts = [0u32; 256];
+    let mut total = 0u32;
+    for y in (y0..y1).step_by(step) {
+        for x in (x0..x1).step_by(step) {
+            counts[biome_map.get(x, y) as usize] += 1;
+            total += 1;
+        }
+    }
+
+    let mut fractions: Vec<(BiomeId, f32)> = counts
+        .iter()
+        .enumerate()
+        .skip(1)
+        .filter(|&(_, &n)| n > 0)
+        .map(|(id, &n)| (id as BiomeId, n as f32 / total as f32))
+        .collect();
+    fractions.sort_by(|a, b| b.1.total_cmp(&a.1));
+    fractions
+}
+
 /// 在 biome overlay 上找到各区域的中心并标注名称。
 ///
 /// 仅扫描当前**可见视口区域**的 biome 采样点（而非全世界），
@@ -116,6 +385,7 @@ fn draw_biome_labels(
     biome_definitions: &[BiomeDefinition],
     image_rect: Rect,
     zoom: f32,
+    background: Option<Color32>,
 ) {
     use std::collections::HashMap;
 
@@ -143,9 +413,7 @@ fn draw_biome_labels(
         return;
     }
 
-    // ── 自适应步长：缩放越小 → 步长越大（因为屏幕上细节更少） ──
-    // zoom=0.3 → step=48, zoom=0.5 → step=32, zoom=1.0+ → step=16
-    let step = if zoom < 0.4 { 48u32 } else if zoom < 0.8 { 32u32 } else { 16u32 };
+    let step = label_sample_step(zoom);
 
     // 对每种 biome 收集可见区域内的采样点 (x, y)，跳过 UNASSIGNED
     let mut sample_map: HashMap<u8, Vec<(u32, u32)>> = HashMap::new();
@@ -170,6 +438,7 @@ fn draw_biome_labels(
     struct LabelCandidate {
         pos: Pos2,
         text: String,
+        priority: i32,
         region_size: u64,
     }
     let mut candidates: Vec<LabelCandidate> = Vec::new();
@@ -220,14 +489,15 @@ fn draw_biome_labels(
                 candidates.push(LabelCandidate {
                     pos,
                     text: bdef.name.clone(),
+                    priority: bdef.label_priority,
                     region_size: region.count,
                 });
             }
         }
     }
 
-    // 按区域大小降序排列（大区域优先放置）
-    candidates.sort_by(|a, b| b.region_size.cmp(&a.region_size));
+    // 按标签优先级、再按区域大小降序排列（高优先级与大区域优先放置）
+    candidates.sort_by(|a, b| (b.priority, b.region_size).cmp(&(a.priority, a.region_size)));
 
     // 限制最大候选数量（避免极端情况下过多 layout 调用）
     candidates.truncate(32);
@@ -280,7 +550,12 @@ fn draw_biome_labels(
             }
             let r = make_rect(pos);
             if !overlaps(&r) {
-                painter.text(pos, egui::Align2::CENTER_CENTER, &candidate.text, font.clone(), Color32::WHITE);
+                let galley = painter.layout_no_wrap(candidate.text.clone(), font.clone(), Color32::WHITE);
+                let text_rect = egui::Align2::CENTER_CENTER.anchor_size(pos, galley.size());
+                if let Some(bg) = background {
+                    painter.rect_filled(text_rect.expand2(Vec2::new(label_padding, 2.0)), 4.0, bg);
+                }
+                painter.galley(text_rect.min, galley, Color32::WHITE);
                 placed_rects.push(r);
                 placed = true;
                 break;
@@ -298,28 +573,62 @@ pub fn show_canvas(
     world: &World,
     color_lut: &[Color32; 256],
     viewport: &mut ViewportState,
-    biome_map: Option<&BiomeMap>,
-    biome_definitions: &[BiomeDefinition],
-    layers: &[LayerDefinition],
-    show_biome_color: bool,
-    show_biome_labels: bool,
-    show_layer_lines: bool,
-    show_layer_labels: bool,
+    options: &CanvasDisplayOptions,
     gl_canvas: &Arc<Mutex<GlCanvasState>>,
 ) -> Option<HoverInfo> {
+    let CanvasDisplayOptions {
+        biome_map,
+        biome_definitions,
+        layers,
+        unknown_biome_color,
+        terraria_colors,
+        show_biome_color,
+        biome_depth_tint,
+        biome_pattern,
+        assign_order,
+        show_biome_labels,
+        biome_label_background,
+        unassigned_highlight,
+        show_layer_lines,
+        show_layer_labels,
+        layer_band_opacity,
+        show_crosshair,
+        tile_boundary_min_zoom,
+        pan_inertia,
+        zoom_sensitivity,
+        invert_zoom,
+    } = *options;
     let world_width = world.width;
     let world_height = world.height;
 
     let available = ui.available_size();
     let (rect, response) = ui.allocate_exact_size(available, Sense::click_and_drag());
 
+    // ── guard: zero-size world / invalid zoom ────────────────
+    // Every path below divides by the world size or zoom; bail out with a
+    // placeholder instead of drawing NaN/Inf geometry.
+    if !viewport.zoom.is_finite() || viewport.zoom <= 0.0 {
+        viewport.reset();
+    }
+    if world_width == 0 || world_height == 0 {
+        ui.painter().text(
+            rect.center(),
+            egui::Align2::CENTER_CENTER,
+            format!("世界尺寸无效（{world_width}×{world_height}），无法绘制画布"),
+            egui::FontId::proportional(16.0),
+            Color32::from_gray(160),
+        );
+        return None;
+    }
+
+    // ── frame animation (auto-zoom to the last step's region) ─
+    let dt = ui.ctx().input(|i| i.stable_dt).min(0.1);
+    if viewport.step_animation([rect.width(), rect.height()], world_width, world_height, dt) {
+        ui.ctx().request_repaint();
+    }
+
     // ── world image rect (full world in screen coords) ───────
-    let image_size = Vec2::new(
-        world_width as f32 * viewport.zoom,
-        world_height as f32 * viewport.zoom,
-    );
-    let center = rect.center() + Vec2::new(viewport.offset[0], viewport.offset[1]);
-    let image_rect = Rect::from_center_size(center, image_size);
+    let image_rect = viewport.image_rect(rect, (world_width, world_height));
 
     // ── viewport culling: compute visible world region ───────
     let vis_left = ((rect.left() - image_rect.left()) / viewport.zoom)
@@ -384,33 +693,53 @@ pub fn show_canvas(
     }
 
     // ── biome overlay for current region ─────────────────────
-    if show_biome_color {
+    // 生成顺序热力图（调试）开启时替换环境覆盖色
+    let show_biome_color = show_biome_color || assign_order.is_some();
+    // 未分配高亮同样依赖覆盖层纹理，只是不画已分配的颜色
+    let highlight_unassigned = unassigned_highlight.0 != UnassignedHighlight::Off;
+    let build_biome_overlay = show_biome_color || highlight_unassigned;
+    let overlay_image = |bm: &BiomeMap, tint_rows: Option<&[f32]>, rect: [u32; 4], lod: u32| match assign_order {
+        Some((order, total_steps)) => order_heat_image_region_lod(order, total_steps, bm.width, rect, lod),
+        None => biome_overlay_image_region_lod(
+            bm, biome_definitions, unknown_biome_color, terraria_colors, tint_rows, biome_pattern,
+            rect[0], rect[1], rect[2], rect[3], lod,
+        ),
+    };
+    if build_biome_overlay {
         if let Some(bm) = biome_map {
-            let st = gl_canvas.lock().unwrap();
+            let mut st = gl_canvas.lock().unwrap();
             let cur_region = st.world_region().unwrap_or(buffer_region);
             let cur_lod = st.current_lod();
             let needs_regen = st.needs_biome_regen(cur_region, cur_lod);
+            // 覆盖层仍有效时只重传改动过的子区域
+            let patch = if needs_regen { None } else { st.take_biome_patch() };
             drop(st);
-            if needs_regen && cur_region[2] > 0 && cur_region[3] > 0 {
-                let img = biome_overlay_image_region_lod(
-                    bm,
-                    biome_definitions,
-                    cur_region[0], cur_region[1],
-                    cur_region[2], cur_region[3],
-                    cur_lod,
+            let tint_rows = (needs_regen || patch.is_some())
+                .then(|| biome_depth_tint.map(|strength| layer_depth_tint_rows(layers, bm.height, strength)))
+                .flatten();
+            if let Some(rect) = patch {
+                let img = overlay_image(bm, tint_rows.as_deref(), rect, cur_lod);
+                let rgba = pixels_to_rgba(&img.pixels);
+                let mask = biome_assigned_mask_region_lod(bm, rect, cur_lod);
+                gl_canvas.lock().unwrap().set_biome_patch_pixels(
+                    rgba, mask, img.size[0] as u32, img.size[1] as u32, rect,
                 );
+            }
+            if needs_regen && cur_region[2] > 0 && cur_region[3] > 0 {
+                let img = overlay_image(bm, tint_rows.as_deref(), cur_region, cur_lod);
                 let tex_w = img.size[0] as u32;
                 let tex_h = img.size[1] as u32;
                 let rgba = pixels_to_rgba(&img.pixels);
+                let mask = biome_assigned_mask_region_lod(bm, cur_region, cur_lod);
                 gl_canvas.lock().unwrap().set_biome_region_pixels(
-                    rgba, tex_w, tex_h,
+                    rgba, mask, tex_w, tex_h,
                     cur_region, cur_lod,
                 );
             }
         }
     }
 
-    // ── GL PaintCallback (checkerboard + world region + biome) ──
+    // ── GL PaintCallback (checkerboard + world region + layer bands + biome) ──
     {
         // Map the currently buffered region to screen coords
         let region = gl_canvas.lock().unwrap().world_region().unwrap_or(buffer_region);
@@ -428,8 +757,18 @@ pub fn show_canvas(
             (region_screen_right - rect.left()) / rw,
             (region_screen_bottom - rect.top()) / rh,
         ];
+        let image_rect_norm = [
+            (image_rect.left() - rect.left()) / rw,
+            (image_rect.top() - rect.top()) / rh,
+            (image_rect.right() - rect.left()) / rw,
+            (image_rect.bottom() - rect.top()) / rh,
+        ];
         let has_biome_flag =
-            show_biome_color && gl_canvas.lock().unwrap().has_biome_ready();
+            build_biome_overlay && gl_canvas.lock().unwrap().has_biome_ready();
+        if has_biome_flag && highlight_unassigned {
+            // Keep the highlight animating.
+            ui.ctx().request_repaint();
+        }
 
         let callback = make_canvas_callback(
             Arc::clone(gl_canvas),
@@ -438,6 +777,13 @@ pub fn show_canvas(
                 world_rect_norm,
                 has_world: true,
                 has_biome: has_biome_flag,
+                image_rect_norm,
+                layer_bands: layer_band_opacity
+                    .map(|opacity| layer_bands(layers, opacity))
+                    .unwrap_or_default(),
+                biome_visible: show_biome_color,
+                unassigned_highlight,
+                time: (ui.input(|i| i.time) % 3600.0) as f32,
             },
         );
         ui.painter().add(callback);
@@ -447,10 +793,19 @@ pub fn show_canvas(
     let painter = ui.painter_at(rect);
     painter.rect_stroke(image_rect, 0.0, Stroke::new(1.0, Color32::from_gray(120)));
 
+    // ── tile-accurate biome boundaries (high zoom only) ──────
+    if let (Some(min_zoom), Some(bm)) = (tile_boundary_min_zoom, biome_map)
+        && viewport.zoom >= min_zoom
+    {
+        draw_tile_boundaries(&painter, bm, image_rect, viewport.zoom, visible_region);
+    }
+
     // ── biome labels (lightweight egui text) ─────────────────
     if show_biome_labels {
         if let Some(bm) = biome_map {
-            draw_biome_labels(&painter, bm, biome_definitions, image_rect, viewport.zoom);
+            let background = biome_label_background
+                .map(|[r, g, b, a]| Color32::from_rgba_unmultiplied(r, g, b, a));
+            draw_biome_labels(&painter, bm, biome_definitions, image_rect, viewport.zoom, background);
         }
     }
 
@@ -586,10 +941,14 @@ pub fn show_canvas(
         );
     }
 
-    // ── drag to pan ──────────────────────────────────────────
+    // ── drag to pan (松手后按拖拽速度惯性滑行) ───────────────
     if response.dragged() {
         let delta = response.drag_delta();
-        viewport.pan(delta.x, delta.y);
+        viewport.drag(delta.x, delta.y, dt);
+    } else if response.is_pointer_button_down_on() || !pan_inertia {
+        viewport.stop_inertia();
+    } else if viewport.step_inertia(dt) {
+        ui.ctx().request_repaint();
     }
 
     // ── scroll wheel to zoom (anchored at cursor) ────────────
@@ -598,10 +957,14 @@ pub fn show_canvas(
         let scroll = ui.ctx().input(|i| i.raw_scroll_delta);
         if scroll.y.abs() > 0.5 {
             if let Some(pointer) = ui.ctx().input(|i| i.pointer.hover_pos()) {
+                viewport.cancel_animation();
                 let old_zoom = viewport.zoom;
                 // raw_scroll_delta: ~120px/notch (mouse) or smaller (touchpad)
-                // 0.001 × 120 = 0.12 → clamped to ±10% per single scroll event
-                let factor = (1.0 + scroll.y * 0.001).clamp(0.9, 1.1);
+                // 0.001 × 120 = 0.12 → clamped to ±10% per single scroll event at sensitivity 1
+                let direction = if invert_zoom { -1.0 } else { 1.0 };
+                let max_step = 0.1 * zoom_sensitivity;
+                let factor = (1.0 + direction * scroll.y * 0.001 * zoom_sensitivity)
+                    .clamp((1.0 - max_step).max(0.5), 1.0 + max_step);
                 let new_zoom = (old_zoom * factor).clamp(0.05, 20.0);
                 let scale = new_zoom / old_zoom;
 
@@ -632,5 +995,102 @@ pub fn show_canvas(
         return None;
     }
 
+    if show_crosshair {
+        draw_crosshair(&painter, rect, image_rect, viewport.zoom, x, y);
+    }
+
     Some(HoverInfo { x, y })
 }
+
+/// 世界尚未生成完成时在画布顶部居中显示「生成中: N/M 步」，提醒当前画面只是部分结果
+pub fn draw_partial_watermark(painter: &egui::Painter, canvas_rect: Rect, executed: usize, total: usize) {
+    let galley = painter.layout_no_wrap(
+        format!("生成中: {executed}/{total} 步"),
+        egui::FontId::proportional(13.0),
+        Color32::from_rgba_unmultiplied(255, 255, 255, 200),
+    );
+    let bg = egui::Align2::CENTER_TOP
+        .anchor_size(Pos2::new(canvas_rect.center().x, canvas_rect.top() + 8.0), galley.size())
+        .expand2(Vec2::new(8.0, 3.0));
+    painter.rect_filled(bg, 4.0, Color32::from_rgba_unmultiplied(20, 20, 30, 160));
+    painter.galley(bg.min + Vec2::new(8.0, 3.0), galley, Color32::WHITE);
+}
+
+/// 逐格环境边界：在可见区域内找出相邻两格环境 ID 不同的格边，按屏幕坐标画成细线。
+///
+/// 同一条格线上连续的边合并为一段，只在高倍缩放时调用（可见格数有限），
+/// 线条落在格子边缘、不受纹理过滤影响。深色描边衬底，浅色与深色环境上都清晰。
+fn draw_tile_boundaries(
+    painter: &egui::Painter,
+    biome_map: &BiomeMap,
+    image_rect: Rect,
+    zoom: f32,
+    [x0, y0, w, h]: [u32; 4],
+) {
+    let x1 = (x0 + w).min(biome_map.width);
+    let y1 = (y0 + h).min(biome_map.height);
+    let mut segments: Vec<[Pos2; 2]> = Vec::new();
+    let screen = |x: u32, y: u32| Pos2::new(image_rect.left() + x as f32 * zoom, image_rect.top() + y as f32 * zoom);
+
+    // 竖直边：格 (x-1, y) 与 (x, y) 之间
+    for x in x0 + 1..x1 {
+        let mut run_start = None;
+        for y in y0..=y1 {
+            let differs = y < y1 && biome_map.get(x - 1, y) != biome_map.get(x, y);
+            match (differs, run_start) {
+                (true, None) => run_start = Some(y),
+                (false, Some(start)) => {
+                    segments.push([screen(x, start), screen(x, y)]);
+                    run_start = None;
+                }
+                _ => {}
+            }
+        }
+    }
+    // 水平边：格 (x, y-1) 与 (x, y) 之间
+    for y in y0 + 1..y1 {
+        let mut run_start = None;
+        for x in x0..=x1 {
+            let differs = x < x1 && biome_map.get(x, y - 1) != biome_map.get(x, y);
+            match (differs, run_start) {
+                (true, None) => run_start = Some(x),
+                (false, Some(start)) => {
+                    segments.push([screen(start, y), screen(x, y)]);
+                    run_start = None;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let shadow = Stroke::new(3.0, Color32::from_rgba_unmultiplied(0, 0, 0, 110));
+    let line = Stroke::new(1.0, Color32::from_rgba_unmultiplied(255, 255, 255, 220));
+    for &segment in &segments {
+        painter.line_segment(segment, shadow);
+    }
+    for &segment in &segments {
+        painter.line_segment(segment, line);
+    }
+}
+
+/// 悬浮十字准线：穿过悬浮格中心、横跨整个画布的细线，并在四边标注世界坐标。
+fn draw_crosshair(painter: &egui::Painter, rect: Rect, image_rect: Rect, zoom: f32, x: u32, y: u32) {
+    let sx = image_rect.left() + (x as f32 + 0.5) * zoom;
+    let sy = image_rect.top() + (y as f32 + 0.5) * zoom;
+    let stroke = Stroke::new(1.0, Color32::from_rgba_unmultiplied(255, 255, 255, 110));
+    painter.line_segment([Pos2::new(sx, rect.top()), Pos2::new(sx, rect.bottom())], stroke);
+    painter.line_segment([Pos2::new(rect.left(), sy), Pos2::new(rect.right(), sy)], stroke);
+
+    let font = egui::FontId::monospace(11.0);
+    let label = |pos: Pos2, anchor: egui::Align2, text: String| {
+        let galley = painter.layout_no_wrap(text, font.clone(), Color32::WHITE);
+        let bg = anchor.anchor_size(pos, galley.size()).expand(2.0);
+        painter.rect_filled(bg, 2.0, Color32::from_rgba_unmultiplied(20, 20, 30, 200));
+        painter.galley(bg.min + Vec2::splat(2.0), galley, Color32::WHITE);
+    };
+    let inset = 4.0;
+    label(Pos2::new(sx, rect.top() + inset), egui::Align2::CENTER_TOP, format!("x={x}"));
+    label(Pos2::new(sx, rect.bottom() - inset), egui::Align2::CENTER_BOTTOM, format!("x={x}"));
+    label(Pos2::new(rect.left() + inset, sy), egui::Align2::LEFT_CENTER, format!("y={y}"));
+    label(Pos2::new(rect.right() - inset, sy), egui::Align2::RIGHT_CENTER, format!("y={y}"));
+}
@@ -0,0 +1,59 @@
+//! # 确认对话框
+//!
+//! 模态确认框：半透明遮罩覆盖整个界面并吞掉背景点击，居中显示标题、说明和「确认 / 取消」按钮。
+//! 用于「一键生成」「重新初始化」等会丢弃当前进度的操作。
+
+use egui::{Color32, Context, Rect, Sense, Vec2};
+
+use crate::ui::theme;
+
+/// 显示模态确认框。返回 `Some(true)` 表示确认，`Some(false)` 表示取消（含 Esc），
+/// `None` 表示用户尚未选择。
+pub fn show_confirm_dialog(
+    ctx: &Context,
+    title: &str,
+    lines: &[String],
+    confirm_label: &str,
+) -> Option<bool> {
+    let mut result = None;
+    let screen = ctx.screen_rect();
+
+    egui::Area::new(egui::Id::new("confirm_dialog_modal"))
+        .order(egui::Order::Foreground)
+        .fixed_pos(screen.min)
+        .show(ctx, |ui| {
+            ui.painter().rect_filled(screen, 0.0, Color32::from_black_alpha(140));
+            // 遮罩层吞掉所有背景点击
+            ui.allocate_rect(screen, Sense::click());
+
+            let width = 340.0;
+            let dialog = Rect::from_min_size(
+                screen.center() - Vec2::new(width / 2.0, 90.0),
+                Vec2::new(width, 180.0),
+            );
+            ui.allocate_ui_at_rect(dialog, |ui| {
+                egui::Frame::window(ui.style()).show(ui, |ui| {
+                    ui.set_width(width);
+                    ui.colored_label(theme::PINK_LIGHT, egui::RichText::new(title).strong());
+                    ui.add_space(6.0);
+                    for line in lines {
+                        ui.label(line);
+                    }
+                    ui.add_space(8.0);
+                    ui.horizontal(|ui| {
+                        if ui.button(egui::RichText::new(confirm_label).color(theme::PINK_LIGHT)).clicked() {
+                            result = Some(true);
+                        }
+                        if ui.button("取消").clicked() {
+                            result = Some(false);
+                        }
+                    });
+                });
+            });
+        });
+
+    if ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+        result = Some(false);
+    }
+    result
+}
@@ -1,7 +1,8 @@
 use egui::{Align, Layout, Rect, ScrollArea, Ui, Vec2};
 
 use crate::config::world::WorldConfig;
-use crate::generation::{PhaseInfo, StepStatus};
+use crate::generation::{BiomeToggle, PhaseInfo, StepStatus};
+use crate::ui::i18n::{self, tr, tr_args};
 use crate::ui::theme;
 
 // ── action returned to the app ──────────────────────────────
@@ -11,6 +12,10 @@ pub struct ControlAction {
     pub zoom_in: bool,
     pub zoom_out: bool,
     pub zoom_reset: bool,
+    /// 设置精确缩放（倍数，1.0 = 100%）
+    pub set_zoom: Option<f32>,
+    /// 视口居中到出生点
+    pub center_on_spawn: bool,
     /// 小步前进 (+0.1)
     pub step_forward_sub: bool,
     /// 大步前进 (+1.0, 执行完当前 phase)
@@ -20,6 +25,8 @@ pub struct ControlAction {
     /// 大步后退 (-1.0, 回退到当前 phase 开头)
     pub step_backward_phase: bool,
     pub run_all: bool,
+    /// 切换播放（按间隔自动小步前进）/ 暂停
+    pub toggle_play: bool,
     pub reset_and_step: bool,
     pub open_overlay_config: bool,
     pub open_layer_config: bool,
@@ -29,16 +36,76 @@ pub struct ControlAction {
     pub open_geo_preview: bool,
     /// 打开图形 API 沙箱窗口
     pub open_shape_sandbox: bool,
+    /// 打开环境邻接图窗口
+    pub open_biome_graph: bool,
+    /// 打开方块调色板窗口
+    pub open_block_palette: bool,
+    /// 打开出生点建议窗口
+    pub open_spawn_suggest: bool,
+    /// 打开书签窗口
+    pub open_bookmarks: bool,
+    /// 打开环境画笔窗口
+    pub open_biome_paint: bool,
+    /// 打开查找环境窗口
+    pub open_biome_search: bool,
+    /// 打开参数拟合窗口
+    pub open_param_fit: bool,
+    /// 打开 A/B 参数对比窗口
+    pub open_ab_compare: bool,
+    /// 预检：列出按当前参数不会生效的子步骤
+    pub check_steps: bool,
     /// 导出 PNG
     pub export_png: bool,
+    /// 导出混合了环境覆盖色的 PNG
+    pub export_png_biome: bool,
+    /// 每个环境导出一张透明底 PNG 图层
+    pub export_biome_layers: bool,
+    /// 导出 16 位环境 ID 图（像素值 = 环境 ID）与图例
+    pub export_biome_ids: bool,
+    /// 导入环境 ID 图，替换当前环境地图
+    pub import_biome_ids: bool,
+    /// 导出世界摘要卡片（预览 + 种子 + 环境覆盖率）
+    pub export_summary_card: bool,
+    /// 打开固定尺寸导出窗口
+    pub open_padded_export: bool,
+    /// 导出当前种子在小 / 中 / 大三种尺寸下的环境对比图
+    pub export_size_strip: bool,
+    /// 导出 16 位灰度地表高度图
+    pub export_heightmap: bool,
+    /// 把环境地图复制为字符画
+    pub copy_ascii: bool,
     /// 导出 .lwd 存档
     pub export_lwd: bool,
     /// 导入 .lwd 存档
     pub import_lwd: bool,
+    /// 导入泰拉瑞亚 .wld 存档
+    pub import_wld: bool,
+    /// 开始 / 停止录制步骤帧
+    pub toggle_frame_recording: bool,
+    /// 导出世界校验报告（JSON）
+    pub export_validation: bool,
     /// 应用手动输入的种子
     pub apply_seed: bool,
+    /// 随机挑一个精选种子并生成
+    pub featured_seed: bool,
+    /// 重掷散布特征（沙漠、邪恶环境等空地特征换新子种子）
+    pub reseed_scatter: bool,
     /// 打开性能面板
     pub open_perf_panel: bool,
+    /// 打开日志控制台
+    pub open_log_console: bool,
+    /// 撤销上次尺寸 / 层级 / 种子更改
+    pub undo_config: bool,
+    /// 切换了界面语言（需保存到 runtime.json）
+    pub language_changed: bool,
+    /// 切换子步骤启用状态 (flat_index, 是否启用)
+    pub toggle_step: Option<(usize, bool)>,
+    /// 本帧悬停的子步骤启用复选框 (flat_index, 当前是否启用)，由调用方显示开关预览
+    pub hover_step_toggle: Option<(usize, bool)>,
+    /// 切换环境开关 (环境 key, 是否启用)：映射到该环境全部生成步骤的启用状态
+    pub toggle_biome: Option<(String, bool)>,
+    /// 跳转到子步骤（flat 索引）：执行或回退到该步骤为止
+    pub goto_step: Option<usize>,
 }
 
 impl ControlAction {
@@ -47,22 +114,55 @@ impl ControlAction {
             zoom_in: false,
             zoom_out: false,
             zoom_reset: false,
+            set_zoom: None,
+            center_on_spawn: false,
             step_forward_sub: false,
             step_forward_phase: false,
             step_backward_sub: false,
             step_backward_phase: false,
             run_all: false,
+            toggle_play: false,
             reset_and_step: false,
             open_overlay_config: false,
             open_layer_config: false,
             open_step_config: false,
             open_geo_preview: false,
             open_shape_sandbox: false,
+            open_biome_graph: false,
+            open_block_palette: false,
+            open_spawn_suggest: false,
+            open_bookmarks: false,
+            open_biome_paint: false,
+            open_biome_search: false,
+            open_param_fit: false,
+            open_ab_compare: false,
+            check_steps: false,
             export_png: false,
+            export_png_biome: false,
+            open_padded_export: false,
+            export_size_strip: false,
+            export_heightmap: false,
+            export_biome_layers: false,
+            export_biome_ids: false,
+            import_biome_ids: false,
+            export_summary_card: false,
+            copy_ascii: false,
             export_lwd: false,
             import_lwd: false,
+            import_wld: false,
+            export_validation: false,
+            toggle_frame_recording: false,
             apply_seed: false,
+            featured_seed: false,
+            reseed_scatter: false,
             open_perf_panel: false,
+            open_log_console: false,
+            undo_config: false,
+            language_changed: false,
+            toggle_step: None,
+            hover_step_toggle: None,
+            toggle_biome: None,
+            goto_step: None,
         }
     }
 }
@@ -77,6 +177,18 @@ pub enum WorldSizeSelection {
     Custom,
 }
 
+impl WorldSizeSelection {
+    /// 由世界尺寸键名（`world.json` 的 key）得到选项，未知键名视为小世界
+    pub fn from_key(key: &str) -> Self {
+        match key {
+            "medium" => Self::Medium,
+            "large" => Self::Large,
+            "custom" => Self::Custom,
+            _ => Self::Small,
+        }
+    }
+}
+
 impl Default for WorldSizeSelection {
     fn default() -> Self {
         Self::Small
@@ -85,6 +197,23 @@ impl Default for WorldSizeSelection {
 
 // ── panel rendering ─────────────────────────────────────────
 
+/// 控制面板显示的生成进度与播放状态（由 app 每帧汇总一次）
+pub struct ControlPanelState<'a> {
+    pub phase_info: &'a [PhaseInfo],
+    pub biome_toggles: &'a [BiomeToggle],
+    pub executed: usize,
+    pub total: usize,
+    /// 是否有可撤销的配置修改
+    pub can_undo: bool,
+    pub zoom: f32,
+    /// 键盘在步骤列表中聚焦的子步骤（扁平序号）
+    pub step_focus: &'a mut Option<usize>,
+    pub playing: bool,
+    pub play_interval_ms: &'a mut u32,
+    /// 正在录制时为已录帧数
+    pub recorded_frames: Option<usize>,
+}
+
 pub fn show_control_panel(
     ui: &mut Ui,
     world_size: &mut WorldSizeSelection,
@@ -92,11 +221,23 @@ pub fn show_control_panel(
     custom_height: &mut String,
     world_cfg: &WorldConfig,
     seed_input: &mut String,
-    phase_info: &[PhaseInfo],
-    executed: usize,
-    total: usize,
+    state: ControlPanelState,
 ) -> ControlAction {
+    let ControlPanelState {
+        phase_info,
+        biome_toggles,
+        executed,
+        total,
+        can_undo,
+        zoom,
+        step_focus,
+        playing,
+        play_interval_ms,
+        recorded_frames,
+    } = state;
     let mut action = ControlAction::none();
+    // 在任何控件处理输入之前取样：文本框按 Enter 提交时会在本帧失去焦点
+    let typing = ui.ctx().wants_keyboard_input();
 
     ScrollArea::vertical()
         .auto_shrink(false)
@@ -108,11 +249,31 @@ pub fn show_control_panel(
         ui.colored_label(theme::PINK, egui::RichText::new("✿ Lian World ✿").heading());
     });
     ui.add_space(4.0);
+
+    // ── 界面语言 ──
+    ui.horizontal(|ui| {
+        ui.label(egui::RichText::new(tr("panel.language")).color(theme::TEXT_SECONDARY));
+        let current = i18n::language();
+        let languages = i18n::languages();
+        let current_name = languages.iter()
+            .find(|(code, _)| *code == current)
+            .map_or(current, |(_, name)| name);
+        egui::ComboBox::from_id_source("ui_language")
+            .selected_text(current_name)
+            .show_ui(ui, |ui| {
+                for (code, name) in &languages {
+                    if ui.selectable_label(*code == current, *name).clicked() && *code != current {
+                        i18n::set_language(code);
+                        action.language_changed = true;
+                    }
+                }
+            });
+    });
     ui.separator();
     ui.add_space(4.0);
 
     // ── 世界尺寸 ──
-    ui.colored_label(theme::BLUE_LIGHT, "◈ 世界尺寸");
+    ui.colored_label(theme::BLUE_LIGHT, tr("panel.world_size"));
     // 动态生成预设尺寸 radio buttons
     let presets: &[(&str, WorldSizeSelection)] = &[
         ("small", WorldSizeSelection::Small),
@@ -128,67 +289,113 @@ pub fn show_control_panel(
         }
     }
     // 自定义尺寸
-    ui.radio_value(world_size, WorldSizeSelection::Custom, "自定义");
+    ui.radio_value(world_size, WorldSizeSelection::Custom, tr("panel.custom_size"));
     if *world_size == WorldSizeSelection::Custom {
         ui.horizontal(|ui| {
-            ui.label("宽:");
+            ui.label(tr("panel.width"));
             ui.add(egui::TextEdit::singleline(custom_width)
                 .hint_text("4200")
                 .desired_width(60.0));
-            ui.label("× 高:");
+            ui.label(tr("panel.height"));
             ui.add(egui::TextEdit::singleline(custom_height)
                 .hint_text("1200")
                 .desired_width(60.0));
         });
     }
+    if ui
+        .add_enabled(can_undo, egui::Button::new(
+            egui::RichText::new(tr("panel.undo")).color(theme::TEXT_SECONDARY)
+        ))
+        .on_hover_text(tr("panel.undo.hint"))
+        .clicked()
+    {
+        action.undo_config = true;
+    }
 
     ui.add_space(2.0);
     ui.separator();
     ui.add_space(4.0);
 
     // ── 种子输入 ──
-    ui.colored_label(theme::BLUE_LIGHT, "◈ 种子");
+    ui.colored_label(theme::BLUE_LIGHT, tr("panel.seed"));
     ui.horizontal(|ui| {
         let text_edit = egui::TextEdit::singleline(seed_input)
-            .hint_text("输入种子 (十六进制/十进制)")
+            .hint_text(tr("panel.seed.placeholder"))
             .desired_width(140.0);
         let resp = ui.add(text_edit);
         if resp.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
             action.apply_seed = true;
         }
-        if ui.button("OK").on_hover_text("应用种子并重置到第0步").clicked() {
+        if ui.button(tr("panel.seed.apply")).on_hover_text(tr("panel.seed.apply.hint")).clicked() {
             action.apply_seed = true;
         }
     });
+    if ui.button(egui::RichText::new(tr("panel.featured_seed")).color(theme::PINK_LIGHT))
+        .on_hover_text(tr("panel.featured_seed.hint"))
+        .clicked()
+    {
+        action.featured_seed = true;
+    }
+    if ui
+        .add_enabled(executed > 0, egui::Button::new(
+            egui::RichText::new(tr("panel.reseed_scatter")).color(theme::PINK_LIGHT)
+        ))
+        .on_hover_text(tr("panel.reseed_scatter.hint"))
+        .clicked()
+    {
+        action.reseed_scatter = true;
+    }
 
     ui.add_space(2.0);
     ui.separator();
     ui.add_space(4.0);
 
-    // ── 生成进度（自定义粉蓝渐变进度条）──
-    ui.colored_label(theme::BLUE_LIGHT, "◈ 生成进度");
+    // ── 生成进度（按阶段分段着色的进度条）──
+    ui.colored_label(theme::BLUE_LIGHT, tr("panel.progress"));
     let progress = if total == 0 {
         0.0
     } else {
         executed as f32 / total as f32
     };
     
-    // 自定义渐变进度条
     let desired_size = Vec2::new(ui.available_width(), 18.0);
-    let (rect, _response) = ui.allocate_exact_size(desired_size, egui::Sense::hover());
-    let painter = ui.painter();
+    let (rect, response) = ui.allocate_exact_size(desired_size, egui::Sense::hover());
+    let painter = ui.painter().with_clip_rect(rect);
     
     // 背景
     painter.rect_filled(rect, 4.0, theme::BG_WIDGET);
     
-    // 填充条 — 粉→蓝渐变
-    if progress > 0.0 {
-        let fill_width = rect.width() * progress;
-        let fill_rect = Rect::from_min_size(rect.min, Vec2::new(fill_width, rect.height()));
-        
-        // 用当前进度对应的颜色填充
-        let fill_color = theme::progress_color(progress);
-        painter.rect_filled(fill_rect, 4.0, fill_color);
+    // 每个阶段一段：已执行部分用阶段色实填，未执行部分用同色淡底
+    let mut hovered_phase = None;
+    if total > 0 {
+        let x_at = |i: usize| rect.left() + rect.width() * i as f32 / total as f32;
+        let mut start = 0;
+        for (i, phase) in phase_info.iter().enumerate() {
+            let len = phase.sub_steps.len();
+            let end = start + len;
+            let color = theme::phase_color(i);
+            let segment = Rect::from_x_y_ranges(x_at(start)..=x_at(end), rect.y_range());
+            painter.rect_filled(segment, 0.0, color.gamma_multiply(0.2));
+            let done = executed.clamp(start, end);
+            if done > start {
+                let filled = Rect::from_x_y_ranges(x_at(start)..=x_at(done), rect.y_range());
+                painter.rect_filled(filled, 0.0, color);
+            }
+            // 阶段边界刻度
+            if start > 0 {
+                painter.line_segment(
+                    [egui::pos2(segment.left(), rect.top()), egui::pos2(segment.left(), rect.bottom())],
+                    egui::Stroke::new(1.0, theme::BG_DARK),
+                );
+            }
+            if response.hover_pos().is_some_and(|p| segment.x_range().contains(p.x)) {
+                hovered_phase = Some((phase, done - start, len));
+            }
+            start = end;
+        }
+    }
+    if let Some((phase, done, len)) = hovered_phase {
+        response.on_hover_text(format!("{}. {}  {done}/{len}", phase.display_index, phase.name));
     }
     
     // 进度文字
@@ -201,20 +408,20 @@ pub fn show_control_panel(
         theme::WHITE,
     );
     
-    ui.label(egui::RichText::new(format!("子步骤: {executed}/{total}")).color(theme::TEXT_SECONDARY).small());
+    ui.label(egui::RichText::new(tr_args("panel.progress.sub_steps", &[&executed, &total])).color(theme::TEXT_SECONDARY).small());
 
     ui.add_space(2.0);
     ui.separator();
     ui.add_space(4.0);
 
     // ── 步进控制 ──
-    ui.colored_label(theme::BLUE_LIGHT, "◈ 步进控制");
+    ui.colored_label(theme::BLUE_LIGHT, tr("panel.stepping"));
     ui.horizontal(|ui| {
         if ui
             .add_enabled(executed > 0, egui::Button::new(
                 egui::RichText::new("⏮").color(theme::PINK)
             ))
-            .on_hover_text("大步后退 (回到阶段开头)")
+            .on_hover_text(tr("panel.step_back_phase.hint"))
             .clicked()
         {
             action.step_backward_phase = true;
@@ -223,7 +430,7 @@ pub fn show_control_panel(
             .add_enabled(executed > 0, egui::Button::new(
                 egui::RichText::new("◂").color(theme::PINK_LIGHT)
             ))
-            .on_hover_text("小步后退")
+            .on_hover_text(tr("panel.step_back_sub.hint"))
             .clicked()
         {
             action.step_backward_sub = true;
@@ -232,7 +439,7 @@ pub fn show_control_panel(
             .add_enabled(executed < total, egui::Button::new(
                 egui::RichText::new("▸").color(theme::BLUE_LIGHT)
             ))
-            .on_hover_text("小步前进")
+            .on_hover_text(tr("panel.step_forward_sub.hint"))
             .clicked()
         {
             action.step_forward_sub = true;
@@ -241,19 +448,73 @@ pub fn show_control_panel(
             .add_enabled(executed < total, egui::Button::new(
                 egui::RichText::new("⏭").color(theme::BLUE)
             ))
-            .on_hover_text("大步前进 (执行完当前阶段)")
+            .on_hover_text(tr("panel.step_forward_phase.hint"))
             .clicked()
         {
             action.step_forward_phase = true;
         }
     });
+    ui.horizontal(|ui| {
+        let (label, color) = if playing {
+            (tr("panel.pause"), theme::PINK_LIGHT)
+        } else {
+            (tr("panel.play"), theme::BLUE_LIGHT)
+        };
+        if ui
+            .add_enabled(playing || executed < total, egui::Button::new(
+                egui::RichText::new(label).color(color)
+            ))
+            .on_hover_text(tr("panel.play.hint"))
+            .clicked()
+        {
+            action.toggle_play = true;
+        }
+        ui.add(
+            egui::Slider::new(play_interval_ms, 50..=2000)
+                .logarithmic(true)
+                .suffix(" ms"),
+        )
+        .on_hover_text(tr("panel.play_interval.hint"));
+    });
 
     ui.add_space(2.0);
     ui.separator();
     ui.add_space(4.0);
 
     // ── 步骤列表 ──
-    ui.colored_label(theme::BLUE_LIGHT, "◈ 步骤列表");
+    ui.colored_label(theme::BLUE_LIGHT, tr("panel.step_list"))
+        .on_hover_text(tr("panel.step_list.hint"));
+
+    // 键盘导航：↑/↓ 移动焦点，Enter 跳转到焦点步骤，Esc 取消焦点（文本框输入时不响应）
+    let mut focus_moved = false;
+    if total > 0 && !typing && !ui.ctx().wants_keyboard_input() {
+        let (up, down, enter, escape) = ui.input(|i| (
+            i.key_pressed(egui::Key::ArrowUp),
+            i.key_pressed(egui::Key::ArrowDown),
+            i.key_pressed(egui::Key::Enter),
+            i.key_pressed(egui::Key::Escape),
+        ));
+        // 没有焦点时从当前位置附近开始
+        let start = executed.min(total - 1);
+        if down {
+            *step_focus = Some(step_focus.map_or(start, |f| (f + 1).min(total - 1)));
+            focus_moved = true;
+        }
+        if up {
+            *step_focus = Some(step_focus.map_or(start, |f| f.saturating_sub(1)));
+            focus_moved = true;
+        }
+        if escape {
+            *step_focus = None;
+        }
+        if enter {
+            action.goto_step = *step_focus;
+        }
+    }
+    if step_focus.is_some_and(|f| f >= total) {
+        *step_focus = None;
+    }
+
     let step_list_max_h = (ui.available_height() * 0.35).clamp(100.0, 300.0);
     ScrollArea::vertical()
         .id_source("step_list_scroll")
@@ -281,67 +542,148 @@ pub fn show_control_panel(
                         StepStatus::Pending => (theme::SUB_PENDING_SYMBOL, theme::SUB_PENDING_COLOR),
                     };
                     
-                    ui.horizontal(|ui| {
+                    let row = ui.horizontal(|ui| {
                         ui.add_space(16.0);
-                        let sub_label = format!("{sub_prefix} {} {}", sub.display_id, sub.name);
-                        let resp = ui.colored_label(sub_color, &sub_label);
-                        
+                        let mut enabled = sub.enabled;
+                        let checkbox = ui.checkbox(&mut enabled, "");
+                        if checkbox.hovered() {
+                            action.hover_step_toggle = Some((sub.flat_index, sub.enabled));
+                        }
+                        if checkbox.changed() {
+                            action.toggle_step = Some((sub.flat_index, enabled));
+                        }
+                        let resp = if sub.enabled {
+                            let sub_label = format!("{sub_prefix} {} {}", sub.display_id, sub.name);
+                            ui.colored_label(sub_color, &sub_label)
+                        } else {
+                            let sub_label = format!("{sub_prefix} {} {} ({})", sub.display_id, sub.name, tr("panel.step_skipped"));
+                            ui.colored_label(theme::SUB_PENDING_COLOR, egui::RichText::new(sub_label).strikethrough())
+                        };
+
                         if resp.hovered() {
                             resp.on_hover_ui(|ui| {
                                 ui.label(&sub.description);
                                 if let Some(url) = &sub.doc_url {
-                                    ui.hyperlink_to("[Doc] 查看算法文档", url);
+                                    ui.hyperlink_to(tr("panel.step_doc"), url);
                                 }
                             });
                         }
                     });
+                    if *step_focus == Some(sub.flat_index) {
+                        ui.painter().rect_stroke(
+                            row.response.rect.expand(1.0),
+                            3.0,
+                            egui::Stroke::new(1.0, theme::BLUE_LIGHT),
+                        );
+                        if focus_moved {
+                            row.response.scroll_to_me(None);
+                        }
+                    }
+                }
+            }
+        });
+
+    // ── 环境开关 ──
+    if !biome_toggles.is_empty() {
+        ui.add_space(4.0);
+        ui.colored_label(theme::BLUE_LIGHT, tr("panel.biome_toggles"))
+            .on_hover_text(tr("panel.biome_toggles.hint"));
+        ui.horizontal_wrapped(|ui| {
+            for toggle in biome_toggles {
+                let mut enabled = toggle.enabled;
+                if ui.checkbox(&mut enabled, &toggle.name).changed() {
+                    action.toggle_biome = Some((toggle.key.clone(), enabled));
                 }
             }
         });
+    }
 
     ui.add_space(4.0);
     ui.separator();
     ui.add_space(6.0);
 
     // ── 生成操作 ──
-    ui.colored_label(theme::BLUE_LIGHT, "◈ 生成操作");
+    ui.colored_label(theme::BLUE_LIGHT, tr("panel.generation"));
     ui.add_space(2.0);
     ui.horizontal(|ui| {
-        if ui.button(egui::RichText::new("✦ 一键生成").color(theme::PINK_LIGHT))
-            .on_hover_text("新种子 → 重置 → 执行全部步骤").clicked() {
+        if ui.button(egui::RichText::new(tr("panel.generate_all")).color(theme::PINK_LIGHT))
+            .on_hover_text(tr("panel.generate_all.hint")).clicked() {
             action.reset_and_step = true;
             action.run_all = true;
         }
-        if ui.button(egui::RichText::new("↻ 重新初始化").color(theme::BLUE_LIGHT))
-            .on_hover_text("新种子 → 重置到第0步").clicked() {
+        if ui.button(egui::RichText::new(tr("panel.reinit")).color(theme::BLUE_LIGHT))
+            .on_hover_text(tr("panel.reinit.hint")).clicked() {
             action.reset_and_step = true;
         }
     });
     ui.add_space(2.0);
-    if ui
-        .add_enabled(executed < total, egui::Button::new(
-            egui::RichText::new("▶▶ 执行到底").color(theme::WHITE)
-        ))
-        .on_hover_text("从当前步骤一直执行到最后")
-        .clicked()
-    {
-        action.run_all = true;
-    }
+    ui.horizontal(|ui| {
+        if ui
+            .add_enabled(executed < total, egui::Button::new(
+                egui::RichText::new(tr("panel.run_all")).color(theme::WHITE)
+            ))
+            .on_hover_text(tr("panel.run_all.hint"))
+            .clicked()
+        {
+            action.run_all = true;
+        }
+        if ui.button(egui::RichText::new(tr("panel.check")).color(theme::TEXT_SECONDARY))
+            .on_hover_text(tr("panel.check.hint")).clicked() {
+            action.check_steps = true;
+        }
+    });
     ui.add_space(2.0);
     ui.horizontal(|ui| {
-        if ui.button(egui::RichText::new("≡ 算法参数").color(theme::TEXT_SECONDARY))
-            .on_hover_text("打开当前步骤的算法参数配置面板").clicked() {
+        if ui.button(egui::RichText::new(tr("panel.algo_params")).color(theme::TEXT_SECONDARY))
+            .on_hover_text(tr("panel.algo_params.hint")).clicked() {
             action.open_step_config = true;
         }
-        if ui.button(egui::RichText::new("📐 几何预览").color(theme::TEXT_SECONDARY))
-            .on_hover_text("查看当前步骤使用的几何图形").clicked() {
+        if ui.button(egui::RichText::new(tr("panel.geo_preview")).color(theme::TEXT_SECONDARY))
+            .on_hover_text(tr("panel.geo_preview.hint")).clicked() {
             action.open_geo_preview = true;
         }
+        if ui.button(egui::RichText::new(tr("panel.param_fit")).color(theme::TEXT_SECONDARY))
+            .on_hover_text(tr("panel.param_fit.hint")).clicked() {
+            action.open_param_fit = true;
+        }
+        if ui.button(egui::RichText::new(tr("panel.ab_compare")).color(theme::TEXT_SECONDARY))
+            .on_hover_text(tr("panel.ab_compare.hint")).clicked() {
+            action.open_ab_compare = true;
+        }
     });
     ui.add_space(2.0);
-    if ui.button(egui::RichText::new("◈ 图形 API 沙箱").color(theme::BLUE_LIGHT))
-        .on_hover_text("交互式创建、组合和预览几何图形").clicked() {
-        action.open_shape_sandbox = true;
+    ui.horizontal(|ui| {
+        if ui.button(egui::RichText::new(tr("panel.sandbox")).color(theme::BLUE_LIGHT))
+            .on_hover_text(tr("panel.sandbox.hint")).clicked() {
+            action.open_shape_sandbox = true;
+        }
+        if ui.button(egui::RichText::new(tr("panel.biome_graph")).color(theme::TEXT_SECONDARY))
+            .on_hover_text(tr("panel.biome_graph.hint")).clicked() {
+            action.open_biome_graph = true;
+        }
+        if ui.button(egui::RichText::new(tr("panel.spawn_suggest")).color(theme::TEXT_SECONDARY))
+            .on_hover_text(tr("panel.spawn_suggest.hint")).clicked() {
+            action.open_spawn_suggest = true;
+        }
+    });
+    ui.add_space(2.0);
+    ui.horizontal(|ui| {
+        if ui.button(egui::RichText::new(tr("panel.block_palette")).color(theme::TEXT_SECONDARY))
+            .on_hover_text(tr("panel.block_palette.hint")).clicked() {
+            action.open_block_palette = true;
+        }
+        if ui.button(egui::RichText::new(tr("panel.bookmarks")).color(theme::TEXT_SECONDARY))
+            .on_hover_text(tr("panel.bookmarks.hint")).clicked() {
+            action.open_bookmarks = true;
+        }
+        if ui.button(egui::RichText::new(tr("panel.biome_search")).color(theme::TEXT_SECONDARY))
+            .on_hover_text(tr("panel.biome_search.hint")).clicked() {
+            action.open_biome_search = true;
+        }
+    });
+    if ui.button(egui::RichText::new(tr("panel.biome_paint")).color(theme::TEXT_SECONDARY))
+        .on_hover_text(tr("panel.biome_paint.hint")).clicked() {
+        action.open_biome_paint = true;
     }
 
     ui.add_space(4.0);
@@ -349,30 +691,97 @@ pub fn show_control_panel(
     ui.add_space(6.0);
 
     // ── 导出 / 导入 ──
-    ui.colored_label(theme::BLUE_LIGHT, "◈ 导出 / 导入");
+    ui.colored_label(theme::BLUE_LIGHT, tr("panel.export_import"));
     ui.add_space(2.0);
-    if ui.button(egui::RichText::new("▣ 导出 PNG").color(theme::TEXT_SECONDARY))
-        .on_hover_text("将当前世界画面导出为 PNG 图片").clicked() {
-        action.export_png = true;
-    }
+    ui.horizontal(|ui| {
+        if ui.button(egui::RichText::new(tr("panel.export_png")).color(theme::TEXT_SECONDARY))
+            .on_hover_text(tr("panel.export_png.hint")).clicked() {
+            action.export_png = true;
+        }
+        if ui.button(egui::RichText::new(tr("panel.export_png_biome")).color(theme::TEXT_SECONDARY))
+            .on_hover_text(tr("panel.export_png_biome.hint")).clicked() {
+            action.export_png_biome = true;
+        }
+        if ui.button(egui::RichText::new(tr("panel.export_biome_layers")).color(theme::TEXT_SECONDARY))
+            .on_hover_text(tr("panel.export_biome_layers.hint")).clicked() {
+            action.export_biome_layers = true;
+        }
+    });
     ui.add_space(2.0);
     ui.horizontal(|ui| {
-        if ui.button(egui::RichText::new("□ 导出 .lwd").color(theme::TEXT_SECONDARY))
-            .on_hover_text("保存世界快照").clicked() {
+        if ui.button(egui::RichText::new(tr("panel.export_padded")).color(theme::TEXT_SECONDARY))
+            .on_hover_text(tr("panel.export_padded.hint")).clicked() {
+            action.open_padded_export = true;
+        }
+        if ui.button(egui::RichText::new(tr("panel.export_size_strip")).color(theme::TEXT_SECONDARY))
+            .on_hover_text(tr("panel.export_size_strip.hint")).clicked() {
+            action.export_size_strip = true;
+        }
+        if ui.button(egui::RichText::new(tr("panel.export_heightmap")).color(theme::TEXT_SECONDARY))
+            .on_hover_text(tr("panel.export_heightmap.hint")).clicked() {
+            action.export_heightmap = true;
+        }
+        if ui.button(egui::RichText::new(tr("panel.copy_ascii")).color(theme::TEXT_SECONDARY))
+            .on_hover_text(tr("panel.copy_ascii.hint")).clicked() {
+            action.copy_ascii = true;
+        }
+    });
+    ui.add_space(2.0);
+    ui.horizontal(|ui| {
+        if ui.button(egui::RichText::new(tr("panel.export_lwd")).color(theme::TEXT_SECONDARY))
+            .on_hover_text(tr("panel.export_lwd.hint")).clicked() {
             action.export_lwd = true;
         }
-        if ui.button(egui::RichText::new("■ 导入 .lwd").color(theme::TEXT_SECONDARY))
-            .on_hover_text("从存档恢复世界").clicked() {
+        if ui.button(egui::RichText::new(tr("panel.import_lwd")).color(theme::TEXT_SECONDARY))
+            .on_hover_text(tr("panel.import_lwd.hint")).clicked() {
             action.import_lwd = true;
         }
     });
+    ui.add_space(2.0);
+    ui.horizontal(|ui| {
+        if ui.button(egui::RichText::new(tr("panel.import_wld")).color(theme::TEXT_SECONDARY))
+            .on_hover_text(tr("panel.import_wld.hint")).clicked() {
+            action.import_wld = true;
+        }
+        if ui.button(egui::RichText::new(tr("panel.export_validation")).color(theme::TEXT_SECONDARY))
+            .on_hover_text(tr("panel.export_validation.hint")).clicked() {
+            action.export_validation = true;
+        }
+    });
+    ui.add_space(2.0);
+    ui.horizontal(|ui| {
+        if ui.button(egui::RichText::new(tr("panel.export_biome_ids")).color(theme::TEXT_SECONDARY))
+            .on_hover_text(tr("panel.export_biome_ids.hint")).clicked() {
+            action.export_biome_ids = true;
+        }
+        if ui.button(egui::RichText::new(tr("panel.import_biome_ids")).color(theme::TEXT_SECONDARY))
+            .on_hover_text(tr("panel.import_biome_ids.hint")).clicked() {
+            action.import_biome_ids = true;
+        }
+        if ui.button(egui::RichText::new(tr("panel.export_summary_card")).color(theme::TEXT_SECONDARY))
+            .on_hover_text(tr("panel.export_summary_card.hint")).clicked() {
+            action.export_summary_card = true;
+        }
+    });
+    ui.add_space(2.0);
+    ui.horizontal(|ui| {
+        let (label, hint) = match recorded_frames {
+            Some(frames) => (format!("{} ({frames})", tr("panel.stop_frame_recording")), tr("panel.stop_frame_recording.hint")),
+            None => (tr("panel.start_frame_recording").to_string(), tr("panel.start_frame_recording.hint")),
+        };
+        let color = if recorded_frames.is_some() { theme::PINK } else { theme::TEXT_SECONDARY };
+        if ui.button(egui::RichText::new(label).color(color))
+            .on_hover_text(hint).clicked() {
+            action.toggle_frame_recording = true;
+        }
+    });
 
     ui.add_space(4.0);
     ui.separator();
     ui.add_space(4.0);
 
     // ── 缩放 ──
-    ui.colored_label(theme::BLUE_LIGHT, "◈ 缩放");
+    ui.colored_label(theme::BLUE_LIGHT, tr("panel.zoom"));
     ui.horizontal(|ui| {
         if ui.button(egui::RichText::new("＋").color(theme::BLUE_LIGHT)).clicked() {
             action.zoom_in = true;
@@ -380,9 +789,31 @@ pub fn show_control_panel(
         if ui.button(egui::RichText::new("－").color(theme::PINK_LIGHT)).clicked() {
             action.zoom_out = true;
         }
-        if ui.button(egui::RichText::new("↺ 重置").color(theme::TEXT_SECONDARY)).clicked() {
+        if ui.button(egui::RichText::new(tr("panel.zoom_reset")).color(theme::TEXT_SECONDARY)).clicked() {
             action.zoom_reset = true;
         }
+        if ui.button(egui::RichText::new(tr("panel.spawn")).color(theme::TEXT_SECONDARY))
+            .on_hover_text(tr("panel.spawn.hint")).clicked() {
+            action.center_on_spawn = true;
+        }
+    });
+    ui.horizontal(|ui| {
+        let mut percent = zoom * 100.0;
+        let resp = ui.add(
+            egui::DragValue::new(&mut percent)
+                .clamp_range(5.0..=2000.0)
+                .speed(1.0)
+                .max_decimals(0)
+                .suffix("%"),
+        ).on_hover_text(tr("panel.zoom_exact.hint"));
+        if resp.changed() {
+            action.set_zoom = Some(percent / 100.0);
+        }
+        for preset in [25, 50, 100, 200] {
+            if ui.small_button(format!("{preset}%")).clicked() {
+                action.set_zoom = Some(preset as f32 / 100.0);
+            }
+        }
     });
 
     ui.add_space(4.0);
@@ -390,23 +821,27 @@ pub fn show_control_panel(
     ui.add_space(4.0);
 
     // ── 配置 ──
-    ui.colored_label(theme::BLUE_LIGHT, "◈ 配置");
+    ui.colored_label(theme::BLUE_LIGHT, tr("panel.config"));
     ui.add_space(2.0);
     ui.horizontal(|ui| {
-        if ui.button(egui::RichText::new("◉ 可视化").color(theme::PINK_LIGHT))
-            .on_hover_text("环境/层级覆盖色、文字、分界线开关").clicked() {
+        if ui.button(egui::RichText::new(tr("panel.overlay")).color(theme::PINK_LIGHT))
+            .on_hover_text(tr("panel.overlay.hint")).clicked() {
             action.open_overlay_config = true;
         }
-        if ui.button(egui::RichText::new("▧ 层级").color(theme::BLUE_LIGHT))
-            .on_hover_text("编辑层级垂直分布").clicked() {
+        if ui.button(egui::RichText::new(tr("panel.layers")).color(theme::BLUE_LIGHT))
+            .on_hover_text(tr("panel.layers.hint")).clicked() {
             action.open_layer_config = true;
         }
     });
     ui.horizontal(|ui| {
-        if ui.button(egui::RichText::new("⚙ 性能").color(theme::BLUE_LIGHT))
-            .on_hover_text("引擎性能调优 / 生成日志").clicked() {
+        if ui.button(egui::RichText::new(tr("panel.perf")).color(theme::BLUE_LIGHT))
+            .on_hover_text(tr("panel.perf.hint")).clicked() {
             action.open_perf_panel = true;
         }
+        if ui.button(egui::RichText::new(tr("panel.log")).color(theme::BLUE_LIGHT))
+            .on_hover_text(tr("panel.log.hint")).clicked() {
+            action.open_log_console = true;
+        }
     });
 
     }); // end ScrollArea
@@ -0,0 +1,85 @@
+//! # 森林填充扩散箭头
+//!
+//! 调试覆盖层：森林填充步骤会把沙漠 / 邪恶环境从边缘向窄缝扩散，逻辑不直观。
+//! 这里读取该步骤写入 `shared` 的 [`DiffusionTrace`]，在每个扩散边缘画箭头：
+//! 方向即扩散方向，长度即该行填充的格数。决定是否扩散的扫描线上画粗箭头并标注统计，
+//! 其余行按屏幕间距抽样绘制。
+
+use egui::{Align2, Color32, FontId, Painter, Pos2, Rect, Stroke, Vec2};
+
+use crate::algorithms::biome_division::DiffusionTrace;
+use crate::core::biome::BiomeDefinition;
+use crate::rendering::viewport::ViewportState;
+
+/// 抽样箭头之间的最小屏幕间距（像素）
+const ROW_SPACING_PX: f32 = 14.0;
+/// 箭头的最短屏幕长度（像素），保证极窄的扩散也能看到方向
+const MIN_ARROW_PX: f32 = 6.0;
+
+/// 在画布上绘制扩散箭头。`canvas_rect` 为画布所在区域（与 `show_canvas` 分配的区域相同）。
+pub fn draw_diffusion_arrows(
+    painter: &Painter,
+    canvas_rect: Rect,
+    viewport: &ViewportState,
+    world_size: (u32, u32),
+    traces: &[DiffusionTrace],
+    biomes: &[BiomeDefinition],
+) {
+    let zoom = viewport.zoom;
+    let image_rect = viewport.image_rect(canvas_rect, world_size);
+    let painter = painter.with_clip_rect(canvas_rect);
+    let row_step = (ROW_SPACING_PX / zoom).ceil().max(1.0) as i32;
+
+    for trace in traces {
+        let color = biomes.iter()
+            .find(|b| b.id == trace.fill_id)
+            .map(|b| Color32::from_rgb(b.overlay_color[0], b.overlay_color[1], b.overlay_color[2]))
+            .unwrap_or(Color32::WHITE);
+
+        // (y, 起始 x, 格数) → 屏幕上的起点与向量
+        let arrow = |&(y, edge, filled): &(i32, i32, i32)| {
+            let dir = trace.direction as f32;
+            // 实际边缘格靠扩散方向的一侧
+            let start_x = edge as f32 + 0.5 + 0.5 * dir;
+            let origin = Pos2::new(
+                image_rect.left() + start_x * zoom,
+                image_rect.top() + (y as f32 + 0.5) * zoom,
+            );
+            let length = (filled as f32 * zoom).max(MIN_ARROW_PX);
+            (origin, Vec2::new(dir * length, 0.0))
+        };
+        let draw = |origin: Pos2, vec: Vec2, width: f32| {
+            painter.arrow(origin, vec, Stroke::new(width + 2.0, Color32::from_black_alpha(180)));
+            painter.arrow(origin, vec, Stroke::new(width, color));
+        };
+
+        let mut last_y = i32::MIN;
+        for row in &trace.rows {
+            if row.0 == trace.scan_y || row.0.saturating_sub(last_y) < row_step {
+                continue;
+            }
+            last_y = row.0;
+            let (origin, vec) = arrow(row);
+            if canvas_rect.expand(vec.length()).contains(origin) {
+                draw(origin, vec, 1.0);
+            }
+        }
+
+        // 扫描线：粗箭头 + 统计标注
+        let Some(scan_row) = trace.rows.iter().find(|r| r.0 == trace.scan_y) else {
+            continue;
+        };
+        let (origin, vec) = arrow(scan_row);
+        draw(origin, vec, 2.5);
+        let widest = trace.rows.iter().map(|r| r.2).max().unwrap_or(0);
+        let anchor = if trace.direction > 0 { Align2::LEFT_BOTTOM } else { Align2::RIGHT_BOTTOM };
+        let galley = painter.layout_no_wrap(
+            format!("{} 行 · 最宽 {widest} 格", trace.rows.len()),
+            FontId::monospace(11.0),
+            Color32::WHITE,
+        );
+        let bg = anchor.anchor_size(origin - Vec2::new(0.0, 4.0), galley.size()).expand(2.0);
+        painter.rect_filled(bg, 2.0, Color32::from_rgba_unmultiplied(20, 20, 30, 200));
+        painter.galley(bg.min + Vec2::splat(2.0), galley, Color32::WHITE);
+    }
+}
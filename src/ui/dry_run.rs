@@ -0,0 +1,61 @@
+//! # 预检窗口
+//!
+//! 「检查」操作的结果：不执行生成、不修改世界，按当前参数与启用状态列出注定不产生效果的子步骤
+//! （禁用的步骤、数量为 0 的散布、宽度为 0 的环境等），便于在生成前发现误配置。
+
+use egui::{Color32, Context};
+
+use crate::generation::NoopStep;
+use crate::ui::theme;
+
+/// 预检窗口的持久状态
+#[derive(Default)]
+pub struct DryRunState {
+    /// 最近一次预检的结果
+    pub noops: Vec<NoopStep>,
+    /// 预检时的子步骤总数
+    pub total: usize,
+}
+
+/// 显示预检窗口。返回 `true` 表示用户请求重新检查。
+pub fn show_dry_run_window(ctx: &Context, open: &mut bool, state: &DryRunState) -> bool {
+    let mut refresh = false;
+
+    egui::Window::new("✔ 生成预检")
+        .open(open)
+        .resizable(true)
+        .default_width(380.0)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if ui.button("↻ 重新检查").clicked() {
+                    refresh = true;
+                }
+                ui.weak(format!("共 {} 个子步骤", state.total));
+            });
+            ui.separator();
+
+            if state.noops.is_empty() {
+                ui.colored_label(theme::BLUE_LIGHT, "所有子步骤都会生效。");
+                return;
+            }
+
+            ui.colored_label(
+                Color32::from_rgb(230, 190, 90),
+                format!("{} 个子步骤按当前参数不会产生效果：", state.noops.len()),
+            );
+            ui.add_space(4.0);
+            egui::Grid::new("dry_run_noops")
+                .num_columns(2)
+                .striped(true)
+                .spacing([12.0, 4.0])
+                .show(ui, |ui| {
+                    for noop in &state.noops {
+                        ui.monospace(format!("{} {}", noop.display_id, noop.name));
+                        ui.colored_label(theme::TEXT_SECONDARY, &noop.reason);
+                        ui.end_row();
+                    }
+                });
+        });
+
+    refresh
+}
@@ -0,0 +1,200 @@
+//! 固定尺寸导出窗口
+//!
+//! 设置目标宽高与背景色，世界图像等比缩放后居中放置（letterbox）。
+//! 窗口中还有导出专用的覆盖层设置（[`ExportOverlaySettings`]），与画布上的可视化开关互不影响：
+//! 画布可以保持干净，导出图仍带环境图案、层级标注与图例。
+
+use egui::Color32;
+
+use crate::rendering::pattern::BiomePattern;
+use crate::rendering::scale_bar::ScaleBarCorner;
+
+/// 固定尺寸导出设置
+#[derive(Debug, Clone, Copy)]
+pub struct PaddedExportSettings {
+    pub width: u32,
+    pub height: u32,
+    /// 留白区域背景色
+    pub background: Color32,
+    /// 是否混合环境覆盖色
+    pub include_biomes: bool,
+}
+
+impl Default for PaddedExportSettings {
+    fn default() -> Self {
+        Self {
+            width: 1920,
+            height: 1080,
+            background: Color32::from_rgb(30, 30, 40),
+            include_biomes: true,
+        }
+    }
+}
+
+/// 导出专用的覆盖层设置（所有 PNG 导出共用，不跟随画布上的可视化开关）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExportOverlaySettings {
+    /// 含环境导出时叠加的环境图案
+    pub biome_pattern: BiomePattern,
+    /// 叠加深度明暗
+    pub depth_shading: bool,
+    /// 画层级分界线并标注层级名称
+    pub layer_annotations: bool,
+    /// 导出含环境的 PNG 时同时输出图例 PNG（色块 + 环境名称）
+    pub biome_legend: bool,
+    /// 在一角画比例尺
+    pub scale_bar: bool,
+    pub scale_bar_corner: ScaleBarCorner,
+    /// 比例尺代表的格数，0 = 自动（约 1/5 世界宽度的整数）
+    pub scale_bar_tiles: u32,
+}
+
+impl Default for ExportOverlaySettings {
+    fn default() -> Self {
+        Self {
+            biome_pattern: BiomePattern::Off,
+            depth_shading: false,
+            layer_annotations: false,
+            biome_legend: false,
+            scale_bar: false,
+            scale_bar_corner: ScaleBarCorner::BottomRight,
+            scale_bar_tiles: 0,
+        }
+    }
+}
+
+/// 常用输出尺寸预设
+const PRESETS: &[(&str, u32, u32)] = &[
+    ("1920×1080", 1920, 1080),
+    ("1280×720", 1280, 720),
+    ("1024×1024", 1024, 1024),
+    ("3840×2160", 3840, 2160),
+];
+
+/// 固定尺寸导出窗口本帧的结果
+#[derive(Debug, Default)]
+pub struct PaddedExportResponse {
+    /// 用户点击了导出
+    pub export: bool,
+    /// 导出覆盖层设置有改动（需要写入 runtime.json）
+    pub overlay_changed: bool,
+}
+
+/// 显示固定尺寸导出窗口
+pub fn show_padded_export_window(
+    ctx: &egui::Context,
+    open: &mut bool,
+    settings: &mut PaddedExportSettings,
+    overlay: &mut ExportOverlaySettings,
+) -> PaddedExportResponse {
+    let mut response = PaddedExportResponse::default();
+
+    egui::Window::new("▣ 固定尺寸导出")
+        .open(open)
+        .resizable(false)
+        .default_width(280.0)
+        .show(ctx, |ui| {
+            ui.label("世界图像等比缩放后居中，其余区域填充背景色。");
+            ui.separator();
+
+            egui::Grid::new("padded_export_grid")
+                .num_columns(2)
+                .spacing([10.0, 6.0])
+                .show(ui, |ui| {
+                    ui.label("输出尺寸");
+                    ui.horizontal(|ui| {
+                        ui.add(egui::DragValue::new(&mut settings.width).clamp_range(16..=16384).suffix(" px"));
+                        ui.label("×");
+                        ui.add(egui::DragValue::new(&mut settings.height).clamp_range(16..=16384).suffix(" px"));
+                    });
+                    ui.end_row();
+
+                    ui.label("预设");
+                    ui.horizontal_wrapped(|ui| {
+                        for &(label, w, h) in PRESETS {
+                            if ui.small_button(label).clicked() {
+                                settings.width = w;
+                                settings.height = h;
+                            }
+                        }
+                    });
+                    ui.end_row();
+
+                    ui.label("背景色");
+                    ui.color_edit_button_srgba(&mut settings.background);
+                    ui.end_row();
+
+                    ui.label("环境覆盖色");
+                    ui.checkbox(&mut settings.include_biomes, "混合进方块颜色");
+                    ui.end_row();
+                });
+
+            ui.separator();
+            ui.label("导出覆盖层")
+                .on_hover_text("只作用于导出图像（含「导出 PNG」按钮），与画布上的可视化开关互不影响");
+            response.overlay_changed = show_export_overlay_grid(ui, overlay);
+
+            ui.separator();
+            if ui.button("💾 导出…").clicked() {
+                response.export = true;
+            }
+        });
+
+    response
+}
+
+/// 导出覆盖层设置的表格，返回设置是否有改动
+fn show_export_overlay_grid(ui: &mut egui::Ui, overlay: &mut ExportOverlaySettings) -> bool {
+    let before = *overlay;
+    egui::Grid::new("export_overlay_grid")
+        .num_columns(2)
+        .spacing([10.0, 6.0])
+        .show(ui, |ui| {
+            ui.label("环境图案");
+            egui::ComboBox::from_id_source("export_biome_pattern")
+                .selected_text(overlay.biome_pattern.label())
+                .show_ui(ui, |ui| {
+                    for pattern in BiomePattern::ALL {
+                        ui.selectable_value(&mut overlay.biome_pattern, pattern, pattern.label());
+                    }
+                })
+                .response
+                .on_hover_text("含环境导出时按环境叠加的图案（色觉障碍 / 黑白打印）");
+            ui.end_row();
+
+            ui.label("深度明暗");
+            ui.checkbox(&mut overlay.depth_shading, "叠加")
+                .on_hover_text("越深越暗，并在每个层级顶部加一道高光");
+            ui.end_row();
+
+            ui.label("层级标注");
+            ui.checkbox(&mut overlay.layer_annotations, "分界线 + 名称")
+                .on_hover_text("在导出图中画层级分界线，并在每个层级左侧标注名称");
+            ui.end_row();
+
+            ui.label("环境图例");
+            ui.checkbox(&mut overlay.biome_legend, "同时导出")
+                .on_hover_text("导出含环境的 PNG 时，在同一目录另存 <文件名>_legend.png：每种环境的覆盖色色块、名称与面积占比");
+            ui.end_row();
+
+            ui.label("比例尺");
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut overlay.scale_bar, "绘制")
+                    .on_hover_text("在导出图一角画比例尺，长度按导出分辨率换算为世界格数");
+                ui.add_enabled_ui(overlay.scale_bar, |ui| {
+                    egui::ComboBox::from_id_source("export_scale_bar_corner")
+                        .selected_text(overlay.scale_bar_corner.label())
+                        .width(60.0)
+                        .show_ui(ui, |ui| {
+                            for corner in ScaleBarCorner::ALL {
+                                ui.selectable_value(&mut overlay.scale_bar_corner, corner, corner.label());
+                            }
+                        });
+                    ui.add(egui::DragValue::new(&mut overlay.scale_bar_tiles).clamp_range(0..=100_000).suffix(" 格"))
+                        .on_hover_text("比例尺代表的格数；0 = 自动取约 1/5 世界宽度的整数，放不下时同样回退为自动");
+                });
+            });
+            ui.end_row();
+        });
+    *overlay != before
+}
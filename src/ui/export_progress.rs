@@ -0,0 +1,66 @@
+//! # 导出进度提示
+//!
+//! 在画布右下角列出后台导出队列中的任务：排队 / 构建像素进度条 / 写入中 / 完成或失败。
+//! 任务结束后停留片刻再消失（由 [`ExportQueue::poll`](crate::generation::export_worker::ExportQueue::poll) 控制）。
+
+use egui::{Color32, RichText};
+
+use crate::generation::export_worker::{ExportJob, ExportJobState};
+use crate::ui::theme;
+
+/// 在 `canvas_rect` 右下角绘制导出任务列表；没有任务时不绘制。
+pub fn show_export_progress(ctx: &egui::Context, canvas_rect: egui::Rect, jobs: &[ExportJob]) {
+    if jobs.is_empty() {
+        return;
+    }
+
+    egui::Area::new(egui::Id::new("export_progress_toast"))
+        .pivot(egui::Align2::RIGHT_BOTTOM)
+        .fixed_pos(canvas_rect.right_bottom() - egui::vec2(12.0, 12.0))
+        .order(egui::Order::Foreground)
+        .interactable(false)
+        .show(ctx, |ui| {
+            egui::Frame::none()
+                .fill(Color32::from_rgba_unmultiplied(20, 20, 30, 220))
+                .rounding(4.0)
+                .inner_margin(8.0)
+                .show(ui, |ui| {
+                    ui.set_width(260.0);
+                    for job in jobs {
+                        let file = job
+                            .path
+                            .file_name()
+                            .map(|n| n.to_string_lossy().into_owned())
+                            .unwrap_or_default();
+                        ui.label(RichText::new(format!("{} → {file}", job.label)).color(theme::TEXT_PRIMARY));
+                        match &job.state {
+                            ExportJobState::Queued => {
+                                ui.colored_label(theme::TEXT_MUTED, "排队中…");
+                            }
+                            ExportJobState::Encoding { percent } => {
+                                ui.add(
+                                    egui::ProgressBar::new(*percent as f32 / 100.0)
+                                        .text(format!("构建像素 {percent}%"))
+                                        .fill(theme::BLUE_DARK),
+                                );
+                            }
+                            ExportJobState::Writing => {
+                                ui.add(
+                                    egui::ProgressBar::new(1.0)
+                                        .text("编码并写入 PNG…")
+                                        .animate(true)
+                                        .fill(theme::PINK_DARK),
+                                );
+                            }
+                            ExportJobState::Done => {
+                                ui.colored_label(theme::PINK, "✔ 已完成");
+                            }
+                            ExportJobState::Failed(e) => {
+                                ui.colored_label(Color32::from_rgb(255, 110, 110), format!("✖ 失败: {e}"));
+                            }
+                        }
+                        ui.add_space(4.0);
+                    }
+                });
+        });
+}
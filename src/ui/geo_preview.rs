@@ -11,6 +11,7 @@ use egui::{
 };
 
 use crate::core::geometry::{ShapeParams, ShapeRecord};
+use crate::ui::preview_quality::PreviewQuality;
 
 // ═══════════════════════════════════════════════════════════
 // 窗口状态
@@ -25,6 +26,8 @@ pub struct GeoPreviewState {
     /// mini-canvas 的缩放偏移
     pub canvas_zoom: f32,
     pub canvas_offset: Vec2,
+    /// 预览绘制质量（椭圆细分）
+    pub quality: PreviewQuality,
 }
 
 impl Default for GeoPreviewState {
@@ -34,6 +37,7 @@ impl Default for GeoPreviewState {
             selected: None,
             canvas_zoom: 1.0,
             canvas_offset: Vec2::ZERO,
+            quality: PreviewQuality::default(),
         }
     }
 }
@@ -81,6 +85,11 @@ pub fn show_geo_preview_window(
                 return;
             }
 
+            ui.horizontal(|ui| {
+                ui.label("精度:");
+                state.quality.combo(ui, "geo_preview_quality");
+            });
+
             // ── mini-canvas ──
             draw_mini_canvas(ui, shapes, state, world_size);
 
@@ -188,7 +197,7 @@ fn draw_mini_canvas(
             continue;
         }
         let is_selected = state.selected == Some(i);
-        draw_shape_on_canvas(&painter, shape, is_selected, &world_to_canvas, scale);
+        draw_shape_on_canvas(&painter, shape, is_selected, &world_to_canvas, scale, state.quality);
     }
 
     // 点击形状选择
@@ -223,6 +232,7 @@ fn draw_shape_on_canvas(
     is_selected: bool,
     world_to_canvas: &dyn Fn(f32, f32) -> Pos2,
     scale: f32,
+    quality: PreviewQuality,
 ) {
     let [r, g, b, a] = shape.color;
     let fill_alpha = if is_selected { (a as u16 + 40).min(200) as u8 } else { a };
@@ -245,12 +255,7 @@ fn draw_shape_on_canvas(
         ShapeParams::Ellipse { cx, cy, rx, ry } => {
             let center = world_to_canvas(*cx as f32, *cy as f32);
             let radius = Vec2::new(*rx as f32 * scale, *ry as f32 * scale);
-            painter.add(egui::Shape::ellipse_filled(center, radius, fill_color));
-            painter.add(egui::Shape::ellipse_stroke(
-                center,
-                radius,
-                Stroke::new(stroke_width, stroke_color),
-            ));
+            quality.draw_ellipse(painter, center, radius, fill_color, Stroke::new(stroke_width, stroke_color));
         }
         ShapeParams::Trapezoid { y_top, y_bot, top_x0, top_x1, bot_x0, bot_x1 } => {
             let p0 = world_to_canvas(*top_x0 as f32, *y_top as f32);
@@ -0,0 +1,62 @@
+//! # 界面字符串表（i18n）
+//!
+//! 启动时由 [`init`] 载入 `lang.json`，之后通过 [`tr`] / [`tr_args`] 按 key 取当前语言的译文。
+//! 当前语言缺少某个 key 时回退到默认语言（列表第一项），仍缺失则直接显示 key，便于发现漏译。
+
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::config::lang::LangConfig;
+
+static TABLE: OnceLock<LangConfig> = OnceLock::new();
+/// 当前语言在 `languages` 中的下标
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+
+/// 载入字符串表（只有第一次调用生效）
+pub fn init(config: LangConfig) {
+    let _ = TABLE.set(config);
+}
+
+/// 可选语言列表：(代码, 显示名称)
+pub fn languages() -> Vec<(&'static str, &'static str)> {
+    TABLE.get()
+        .map(|t| t.languages.iter().map(|l| (l.code.as_str(), l.name.as_str())).collect())
+        .unwrap_or_default()
+}
+
+/// 当前语言代码
+pub fn language() -> &'static str {
+    TABLE.get()
+        .and_then(|t| t.languages.get(CURRENT.load(Ordering::Relaxed)))
+        .map_or("zh-CN", |l| l.code.as_str())
+}
+
+/// 切换语言，未知代码返回 `false` 且保持当前语言
+pub fn set_language(code: &str) -> bool {
+    let Some(index) = TABLE.get().and_then(|t| t.languages.iter().position(|l| l.code == code)) else {
+        return false;
+    };
+    CURRENT.store(index, Ordering::Relaxed);
+    true
+}
+
+/// 按 key 取当前语言的译文
+pub fn tr(key: &'static str) -> &'static str {
+    let Some(table) = TABLE.get() else {
+        return key;
+    };
+    let Some(entry) = table.strings.get(key) else {
+        return key;
+    };
+    [language(), table.languages.first().map_or("", |l| l.code.as_str())]
+        .iter()
+        .find_map(|code| entry.get(*code))
+        .map_or(key, String::as_str)
+}
+
+/// 同 [`tr`]，并把译文中的 `{0}`、`{1}`… 依次替换为 `args`
+pub fn tr_args(key: &'static str, args: &[&dyn std::fmt::Display]) -> String {
+    args.iter()
+        .enumerate()
+        .fold(tr(key).to_string(), |text, (i, arg)| text.replace(&format!("{{{i}}}"), &arg.to_string()))
+}
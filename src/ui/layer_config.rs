@@ -64,7 +64,7 @@ pub fn show_layer_config_window(
                         ConfigMode::Percentage => {
                             ui.label("起始 (%)");
                             ui.label("结束 (%)");
-                            ui.label("高度（行）");
+                            ui.label("覆盖行");
                         }
                         ConfigMode::AbsoluteRows => {
                             ui.label("起始（行）");
@@ -113,8 +113,9 @@ pub fn show_layer_config_window(
                                     }
                                 }
                                 
-                                // 只读：行数范围
-                                ui.label(format!("{} - {}", current_start_row, current_end_row));
+                                // 只读：实际覆盖的行（含首尾）
+                                ui.label(row_span_text(current_start_row, current_end_row))
+                                    .on_hover_text("百分比按四舍五入换算为行，相邻层级共享的边界落在同一行");
                             }
                             ConfigMode::AbsoluteRows => {
                                 let max_row = world_height as i32;
@@ -176,6 +177,28 @@ pub fn show_layer_config_window(
             };
             ui.label(format!("• 层级顺序从上到下：{layer_order}"));
             ui.label("• 调整结束值会自动调整下一层级的起始值（智能对齐）");
+            if mode == ConfigMode::AbsoluteRows {
+                ui.label(format!(
+                    "• 输入的行会吸附到最近的整百分比（当前高度下 1% ≈ {:.1} 行）",
+                    world_height as f64 / 100.0,
+                ));
+            }
+            // 相邻层级百分比不衔接时，列出空隙 / 重叠的行
+            let mut sorted: Vec<_> = layers.iter().collect();
+            sorted.sort_by_key(|l| l.start_percent);
+            for pair in sorted.windows(2) {
+                let (_, upper_end) = pair[0].bounds_for_height(world_height);
+                let (lower_start, _) = pair[1].bounds_for_height(world_height);
+                let text = match upper_end.cmp(&lower_start) {
+                    std::cmp::Ordering::Less => format!("{} 行空隙", lower_start - upper_end),
+                    std::cmp::Ordering::Greater => format!("{} 行重叠", upper_end - lower_start),
+                    std::cmp::Ordering::Equal => continue,
+                };
+                ui.colored_label(
+                    egui::Color32::from_rgb(230, 190, 90),
+                    format!("⚠ {} 与 {} 之间有 {text}", pair[0].short_name, pair[1].short_name),
+                );
+            }
             ui.label("• 修改会立即应用到可视化");
             
             ui.separator();
@@ -189,7 +212,7 @@ pub fn show_layer_config_window(
                 
                 if ui.button("💾 保存配置").clicked() {
                     if let Err(e) = save_to_runtime(layers) {
-                        eprintln!("保存失败: {}", e);
+                        crate::core::log::error(format!("层级配置保存失败: {e}"));
                     } else {
                         ui.ctx().debug_painter().text(
                             ui.ctx().screen_rect().center_top() + egui::vec2(0.0, 50.0),
@@ -214,6 +237,15 @@ pub fn show_layer_config_window(
     changed
 }
 
+/// 行范围 `[start, end)` 的显示文字：首尾行（含）与行数
+fn row_span_text(start: u32, end: u32) -> String {
+    if end > start {
+        format!("{start} – {}（{} 行）", end - 1, end - start)
+    } else {
+        "（0 行）".to_string()
+    }
+}
+
 /// 恢复为默认层级配置（从 world.json 重新读取）
 fn reset_to_default(layers: &mut [LayerDefinition]) {
     if let Ok(cfg) = load_world_config() {
@@ -0,0 +1,103 @@
+//! # 日志控制台
+//!
+//! 可滚动窗口，列出 [`crate::core::log`] 缓冲中的全部消息（步骤执行、错误、状态更新），
+//! 弥补状态栏只显示最新一条的不足。
+
+use egui::{Color32, RichText};
+
+use crate::core::log::{self, LogLevel};
+use crate::ui::theme;
+
+/// 日志控制台的过滤状态
+#[derive(Debug, Clone, Copy)]
+pub struct LogConsoleState {
+    pub show_info: bool,
+    pub show_warn: bool,
+    pub show_error: bool,
+}
+
+impl Default for LogConsoleState {
+    fn default() -> Self {
+        Self {
+            show_info: true,
+            show_warn: true,
+            show_error: true,
+        }
+    }
+}
+
+impl LogConsoleState {
+    fn accepts(&self, level: LogLevel) -> bool {
+        match level {
+            LogLevel::Info => self.show_info,
+            LogLevel::Warn => self.show_warn,
+            LogLevel::Error => self.show_error,
+        }
+    }
+}
+
+fn level_color(level: LogLevel) -> Color32 {
+    match level {
+        LogLevel::Info => theme::TEXT_SECONDARY,
+        LogLevel::Warn => Color32::from_rgb(240, 200, 100),
+        LogLevel::Error => Color32::from_rgb(255, 110, 110),
+    }
+}
+
+/// 显示日志控制台窗口。
+pub fn show_log_console_window(ctx: &egui::Context, is_open: &mut bool, state: &mut LogConsoleState) {
+    egui::Window::new("☰ 日志控制台")
+        .open(is_open)
+        .default_width(520.0)
+        .default_height(320.0)
+        .show(ctx, |ui| {
+            let entries = log::entries();
+
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut state.show_info, "信息");
+                ui.checkbox(&mut state.show_warn, "警告");
+                ui.checkbox(&mut state.show_error, "错误");
+                ui.separator();
+                if ui.small_button("复制全部").clicked() {
+                    let text: Vec<String> = entries
+                        .iter()
+                        .filter(|e| state.accepts(e.level))
+                        .map(|e| format!("[{:>9.3}s] [{}] {}", e.elapsed.as_secs_f64(), e.level.label(), e.message))
+                        .collect();
+                    ui.output_mut(|o| o.copied_text = text.join("\n"));
+                }
+                if ui.small_button("清空").clicked() {
+                    log::clear();
+                }
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    ui.colored_label(theme::TEXT_MUTED, format!("{} 条", entries.len()));
+                });
+            });
+            ui.separator();
+
+            egui::ScrollArea::vertical()
+                .auto_shrink([false, false])
+                .stick_to_bottom(true)
+                .show(ui, |ui| {
+                    if entries.is_empty() {
+                        ui.colored_label(theme::TEXT_MUTED, "暂无日志");
+                        return;
+                    }
+                    for entry in entries.iter().filter(|e| state.accepts(e.level)) {
+                        ui.horizontal_wrapped(|ui| {
+                            ui.label(
+                                RichText::new(format!("{:>9.3}s", entry.elapsed.as_secs_f64()))
+                                    .monospace()
+                                    .color(theme::TEXT_MUTED),
+                            );
+                            ui.label(
+                                RichText::new(entry.level.label())
+                                    .monospace()
+                                    .color(level_color(entry.level)),
+                            );
+                            ui.label(RichText::new(&entry.message).color(level_color(entry.level)));
+                        });
+                    }
+                });
+        });
+}
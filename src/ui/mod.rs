@@ -1,12 +1,30 @@
+pub mod ab_compare;
 pub mod algo_config;
 pub mod app;
+pub mod biome_graph;
+pub mod biome_paint;
+pub mod biome_search;
+pub mod block_palette;
+pub mod bookmarks;
 pub mod canvas_view;
+pub mod confirm_dialog;
 pub mod control_panel;
+pub mod diffusion_arrows;
+pub mod dry_run;
+pub mod export_config;
+pub mod export_progress;
 pub mod geo_preview;
+pub mod i18n;
 pub mod layer_config;
+pub mod log_console;
 pub mod overlay_config;
+pub mod param_fit;
 pub mod perf_panel;
+pub mod preview_quality;
+pub mod seed_trace;
 pub mod shape_sandbox;
+pub mod spawn_suggest;
 pub mod splash;
 pub mod status_bar;
+pub mod step_preview;
 pub mod theme;
@@ -1,23 +1,149 @@
-/// 可视化覆盖层的 4 个独立开关
+use crate::core::coords::DEFAULT_FEET_PER_TILE;
+use crate::rendering::gl_canvas::UnassignedHighlight;
+use crate::rendering::pattern::BiomePattern;
+
+/// 小地图的绘制来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MinimapSource {
+    /// 方块颜色（材质化之前大多是空气）
+    Blocks,
+    /// 环境覆盖色（未分配处回退到方块颜色）
+    Biomes,
+}
+
+impl MinimapSource {
+    pub const ALL: [MinimapSource; 2] = [MinimapSource::Blocks, MinimapSource::Biomes];
+
+    /// runtime.json 中的键名
+    pub fn key(self) -> &'static str {
+        match self {
+            MinimapSource::Blocks => "blocks",
+            MinimapSource::Biomes => "biomes",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|s| s.key() == key)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            MinimapSource::Blocks => "方块",
+            MinimapSource::Biomes => "环境",
+        }
+    }
+}
+
+/// 状态栏世界尺寸详情使用的单位
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizeUnit {
+    /// 格（与世界数据一致）
+    Tiles,
+    /// 方块（游戏内说法，数值同格）
+    Blocks,
+    /// 英尺（按每格英尺数换算）
+    Feet,
+}
+
+impl SizeUnit {
+    pub const ALL: [SizeUnit; 3] = [SizeUnit::Tiles, SizeUnit::Blocks, SizeUnit::Feet];
+
+    /// runtime.json 中的键名
+    pub fn key(self) -> &'static str {
+        match self {
+            SizeUnit::Tiles => "tiles",
+            SizeUnit::Blocks => "blocks",
+            SizeUnit::Feet => "feet",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|u| u.key() == key)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SizeUnit::Tiles => "格",
+            SizeUnit::Blocks => "方块",
+            SizeUnit::Feet => "英尺",
+        }
+    }
+}
+
+/// 可视化覆盖层的独立开关
 #[derive(Debug, Clone, Copy)]
 pub struct OverlaySettings {
     /// 环境半透明覆盖色
     pub show_biome_color: bool,
+    /// 按层级深度逐层加深环境覆盖色
+    pub biome_depth_tint: bool,
+    /// 加深强度：最深层级的亮度衰减比例（0.0~1.0）
+    pub biome_depth_tint_strength: f32,
+    /// 环境图案（无障碍 / 打印用），画布覆盖层与含环境的 PNG 导出共用
+    pub biome_pattern: BiomePattern,
+    /// 用泰拉瑞亚游戏内地图色（biome.json 的 `map_color`）代替 `overlay_color`，画布覆盖层与环境小地图共用
+    pub terraria_map_colors: bool,
     /// 环境名称文字标签
     pub show_biome_labels: bool,
+    /// 环境标签下垫半透明圆角底板
+    pub biome_label_background: bool,
+    /// 标签底板颜色（RGBA，未预乘）
+    pub biome_label_background_color: [u8; 4],
+    /// 放大到 `tile_boundary_min_zoom` 以上时逐格画出环境边界线
+    pub show_tile_boundaries: bool,
+    /// 显示逐格边界的最低缩放（每格屏幕像素）
+    pub tile_boundary_min_zoom: f32,
+    /// 调试：未分配格子的动画高亮（不开覆盖色时也生效）
+    pub unassigned_highlight: UnassignedHighlight,
+    /// 未分配高亮的颜色
+    pub unassigned_highlight_color: [u8; 3],
     /// 层级分界线
     pub show_layer_lines: bool,
     /// 层级名称文字标签
     pub show_layer_labels: bool,
+    /// 按层级填充淡色色带（位于环境覆盖色之下）
+    pub show_layer_bands: bool,
+    /// 层级色带不透明度（0.0~1.0）
+    pub layer_band_opacity: f32,
+    /// 悬浮信息额外显示泰拉瑞亚游戏内坐标（罗盘 / 深度计读数）
+    pub terraria_coords: bool,
+    /// 坐标换算：每格英尺数（游戏中为 2）
+    pub feet_per_tile: f32,
+    /// 悬浮时显示十字准线（四边标注世界坐标）
+    pub show_crosshair: bool,
+    /// 状态栏在尺寸旁附加尺寸档位与总格数
+    pub world_size_details: bool,
+    /// 尺寸详情的单位
+    pub world_size_unit: SizeUnit,
+    /// 小地图按方块还是按环境绘制
+    pub minimap_source: MinimapSource,
 }
 
 impl Default for OverlaySettings {
     fn default() -> Self {
         Self {
             show_biome_color: false,
+            biome_depth_tint: false,
+            biome_depth_tint_strength: 0.5,
+            biome_pattern: BiomePattern::Off,
+            terraria_map_colors: false,
             show_biome_labels: false,
+            biome_label_background: true,
+            biome_label_background_color: [20, 20, 30, 160],
+            show_tile_boundaries: false,
+            tile_boundary_min_zoom: 6.0,
+            unassigned_highlight: UnassignedHighlight::Off,
+            unassigned_highlight_color: [255, 0, 255],
             show_layer_lines: true,
             show_layer_labels: true,
+            show_layer_bands: false,
+            layer_band_opacity: 0.15,
+            terraria_coords: false,
+            feet_per_tile: DEFAULT_FEET_PER_TILE,
+            show_crosshair: false,
+            world_size_details: false,
+            world_size_unit: SizeUnit::Tiles,
+            minimap_source: MinimapSource::Blocks,
         }
     }
 }
@@ -40,9 +166,94 @@ pub fn show_overlay_config_window(
                 if ui.checkbox(&mut settings.show_biome_color, "显示环境覆盖色").changed() {
                     changed = true;
                 }
+                ui.add_enabled_ui(settings.show_biome_color, |ui| {
+                    if ui.checkbox(&mut settings.biome_depth_tint, "按层级加深覆盖色")
+                        .on_hover_text("越深的层级覆盖色越暗，用于区分同一环境在地表/地下/洞穴中的部分")
+                        .changed()
+                    {
+                        changed = true;
+                    }
+                    if settings.biome_depth_tint
+                        && ui.add(egui::Slider::new(&mut settings.biome_depth_tint_strength, 0.0..=1.0)
+                            .text("加深强度"))
+                            .changed()
+                    {
+                        changed = true;
+                    }
+                    if ui.checkbox(&mut settings.terraria_map_colors, "泰拉瑞亚地图配色")
+                        .on_hover_text("改用游戏内全图地图的颜色（不透明），便于与游戏中看到的地图对照；未定义地图色的环境仍用自定义覆盖色")
+                        .changed()
+                    {
+                        changed = true;
+                    }
+                    ui.horizontal(|ui| {
+                        ui.label("图案");
+                        egui::ComboBox::from_id_source("biome_pattern")
+                            .selected_text(settings.biome_pattern.label())
+                            .show_ui(ui, |ui| {
+                                for pattern in BiomePattern::ALL {
+                                    if ui.selectable_value(&mut settings.biome_pattern, pattern, pattern.label()).changed() {
+                                        changed = true;
+                                    }
+                                }
+                            })
+                            .response
+                            .on_hover_text("按环境叠加斜线、横线、圆点等图案，不依赖颜色也能区分环境（色觉障碍 / 黑白打印）；PNG 导出的图案在导出窗口中单独设置");
+                    });
+                });
                 if ui.checkbox(&mut settings.show_biome_labels, "显示环境文字标签").changed() {
                     changed = true;
                 }
+                ui.add_enabled_ui(settings.show_biome_labels, |ui| {
+                    ui.horizontal(|ui| {
+                        if ui.checkbox(&mut settings.biome_label_background, "标签底板")
+                            .on_hover_text("在每个标签下垫一块半透明圆角底板，浅色环境上也能看清白色文字")
+                            .changed()
+                        {
+                            changed = true;
+                        }
+                        if settings.biome_label_background
+                            && ui.color_edit_button_srgba_unmultiplied(&mut settings.biome_label_background_color).changed()
+                        {
+                            changed = true;
+                        }
+                    });
+                });
+                if ui.checkbox(&mut settings.show_tile_boundaries, "高倍缩放时显示逐格边界")
+                    .on_hover_text("放大到指定倍数以上时，沿相邻两格环境不同的格边画出细线，不受覆盖层纹理缩放的模糊 / 锯齿影响，便于精确检查边界")
+                    .changed()
+                {
+                    changed = true;
+                }
+                if settings.show_tile_boundaries
+                    && ui.add(egui::Slider::new(&mut settings.tile_boundary_min_zoom, 2.0..=20.0)
+                        .logarithmic(true)
+                        .suffix("×")
+                        .text("起始缩放"))
+                        .on_hover_text("缩放低于此倍数（每格屏幕像素）时不画边界线，避免缩小时线条挤成一片")
+                        .changed()
+                {
+                    changed = true;
+                }
+                ui.horizontal(|ui| {
+                    ui.label("未分配高亮");
+                    egui::ComboBox::from_id_source("unassigned_highlight")
+                        .selected_text(settings.unassigned_highlight.label())
+                        .show_ui(ui, |ui| {
+                            for style in UnassignedHighlight::ALL {
+                                if ui.selectable_value(&mut settings.unassigned_highlight, style, style.label()).changed() {
+                                    changed = true;
+                                }
+                            }
+                        })
+                        .response
+                        .on_hover_text("用动画交叉斜线或闪烁标出尚未分配环境的格子，便于发现填充遗漏；关闭环境覆盖色时也会显示");
+                    if settings.unassigned_highlight != UnassignedHighlight::Off
+                        && ui.color_edit_button_srgb(&mut settings.unassigned_highlight_color).changed()
+                    {
+                        changed = true;
+                    }
+                });
             });
 
             ui.separator();
@@ -55,6 +266,78 @@ pub fn show_overlay_config_window(
                 if ui.checkbox(&mut settings.show_layer_labels, "显示层级文字标签").changed() {
                     changed = true;
                 }
+                if ui.checkbox(&mut settings.show_layer_bands, "显示层级色带")
+                    .on_hover_text("每个层级铺一层淡色（太空蓝、地表绿、地下棕、洞穴灰、地狱红），位于环境覆盖色之下，高世界中也能看清纵向结构")
+                    .changed()
+                {
+                    changed = true;
+                }
+                if settings.show_layer_bands
+                    && ui.add(egui::Slider::new(&mut settings.layer_band_opacity, 0.0..=1.0)
+                        .text("色带不透明度"))
+                        .changed()
+                {
+                    changed = true;
+                }
+            });
+
+            ui.separator();
+
+            ui.label("坐标 (Coordinates)");
+            ui.indent("coord_group", |ui| {
+                if ui.checkbox(&mut settings.terraria_coords, "显示泰拉瑞亚坐标")
+                    .on_hover_text("东西 = (x - 宽度/2) × 每格英尺数；深度 = (y - 地下层起始行) × 每格英尺数")
+                    .changed()
+                {
+                    changed = true;
+                }
+                if settings.terraria_coords
+                    && ui.add(egui::DragValue::new(&mut settings.feet_per_tile)
+                        .clamp_range(0.1..=16.0)
+                        .speed(0.1)
+                        .prefix("每格英尺数: "))
+                        .changed()
+                {
+                    changed = true;
+                }
+                if ui.checkbox(&mut settings.show_crosshair, "显示悬浮十字准线")
+                    .on_hover_text("穿过光标所在格的横竖参考线，画布四边标注 x / y 坐标")
+                    .changed()
+                {
+                    changed = true;
+                }
+                if ui.checkbox(&mut settings.world_size_details, "状态栏显示尺寸详情")
+                    .on_hover_text("在世界尺寸旁附加小 / 中 / 大档位（自定义尺寸取最接近的档位）与千分位分隔的总格数")
+                    .changed()
+                {
+                    changed = true;
+                }
+                if settings.world_size_details {
+                    ui.horizontal(|ui| {
+                        ui.label("单位");
+                        for unit in SizeUnit::ALL {
+                            if ui.selectable_value(&mut settings.world_size_unit, unit, unit.label()).changed() {
+                                changed = true;
+                            }
+                        }
+                    });
+                }
+            });
+
+            ui.separator();
+
+            ui.label("小地图 (Minimap)");
+            ui.indent("minimap_group", |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("绘制来源");
+                    for source in MinimapSource::ALL {
+                        if ui.selectable_value(&mut settings.minimap_source, source, source.label()).changed() {
+                            changed = true;
+                        }
+                    }
+                })
+                .response
+                .on_hover_text("方块：按方块颜色绘制（材质化之前大多是空气）；环境：按环境覆盖色绘制，生成过程中就能看出世界布局");
             });
 
             ui.separator();
@@ -0,0 +1,292 @@
+//! # 参数拟合窗口
+//!
+//! 设定若干环境的目标覆盖率，以当前种子、世界尺寸和环境判定参数为起点，
+//! 在后台线程运行 [`fit_biome_params`] 的爬山搜索；完成后列出拟合前后的覆盖率与改动的参数，
+//! 可一键应用到当前流水线。
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::thread;
+
+use egui::{Color32, Context};
+
+use crate::core::biome::BiomeDefinition;
+use crate::core::world::WorldProfile;
+use crate::generation::step_rng::RngKind;
+use crate::generation::param_fit::{
+    fit_biome_params, tunable_biome_keys, FitResult, FitSettings, FitTarget, DEFAULT_ITERATIONS,
+};
+use crate::ui::theme;
+
+/// 工作线程发回的消息
+enum FitMessage {
+    Progress { done: usize, error: f64 },
+    Finished(Result<FitResult, String>),
+}
+
+/// 正在运行的拟合任务
+struct FitJob {
+    messages: Receiver<FitMessage>,
+    cancel: Arc<AtomicBool>,
+    iterations: usize,
+    done: usize,
+    error: Option<f64>,
+}
+
+/// 参数拟合窗口的持久状态
+pub struct ParamFitState {
+    pub targets: Vec<FitTarget>,
+    pub iterations: usize,
+    pub samples: usize,
+    job: Option<FitJob>,
+    /// 最近一次拟合的结果（与当时的目标列表一起保存，目标之后可能被编辑）
+    result: Option<(Vec<FitTarget>, Result<FitResult, String>)>,
+}
+
+impl Default for ParamFitState {
+    fn default() -> Self {
+        Self {
+            targets: vec![
+                FitTarget { biome_key: "jungle".to_string(), percent: 8.0 },
+                FitTarget { biome_key: "snow".to_string(), percent: 6.0 },
+            ],
+            iterations: DEFAULT_ITERATIONS,
+            samples: 1,
+            job: None,
+            result: None,
+        }
+    }
+}
+
+impl ParamFitState {
+    pub fn is_running(&self) -> bool {
+        self.job.is_some()
+    }
+
+    /// 以 `start` 为起始参数启动后台拟合（已在运行时忽略）；`locked` 中的参数保持不变
+    pub fn start(
+        &mut self,
+        seed: u64,
+        size_mixing: bool,
+        rng_kind: RngKind,
+        profile: &WorldProfile,
+        start: serde_json::Value,
+        locked: Vec<String>,
+    ) {
+        if self.job.is_some() {
+            return;
+        }
+        let size = &profile.size;
+        let settings = FitSettings {
+            seed,
+            size_key: size.key.clone(),
+            custom_size: Some((size.width, size.height)),
+            layers: Some(profile.layers.clone()),
+            iterations: self.iterations,
+            samples: self.samples,
+            size_mixing,
+            rng_kind,
+            locked,
+        };
+        let targets = self.targets.clone();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let (tx, messages) = mpsc::channel();
+
+        let worker_cancel = Arc::clone(&cancel);
+        thread::spawn(move || {
+            let result = fit_biome_params(&settings, &start, &targets, |done, error| {
+                let _ = tx.send(FitMessage::Progress { done, error });
+                !worker_cancel.load(Ordering::Relaxed)
+            });
+            let _ = tx.send(FitMessage::Finished(result));
+        });
+
+        self.result = None;
+        self.job = Some(FitJob {
+            messages,
+            cancel,
+            iterations: self.iterations,
+            done: 0,
+            error: None,
+        });
+    }
+
+    /// 处理工作线程的消息
+    fn poll(&mut self) {
+        let Some(job) = &mut self.job else { return };
+        let mut finished = None;
+        for message in job.messages.try_iter() {
+            match message {
+                FitMessage::Progress { done, error } => {
+                    job.done = done;
+                    job.error = Some(error);
+                }
+                FitMessage::Finished(result) => finished = Some(result),
+            }
+        }
+        if let Some(result) = finished {
+            self.job = None;
+            self.result = Some((self.targets.clone(), result));
+        }
+    }
+}
+
+/// 窗口中的用户请求
+pub enum ParamFitRequest {
+    /// 以当前流水线状态开始拟合（调用方提供种子、尺寸与起始参数后调用 [`ParamFitState::start`]）
+    Start,
+    /// 把拟合得到的环境判定参数应用到流水线
+    Apply(serde_json::Value),
+}
+
+/// 显示参数拟合窗口。
+pub fn show_param_fit_window(
+    ctx: &Context,
+    open: &mut bool,
+    state: &mut ParamFitState,
+    biomes: &[BiomeDefinition],
+) -> Option<ParamFitRequest> {
+    state.poll();
+    if state.is_running() {
+        ctx.request_repaint();
+    }
+
+    let biome_name = |key: &str| {
+        biomes.iter()
+            .find(|b| b.key == key)
+            .map(|b| b.name.clone())
+            .unwrap_or_else(|| key.to_string())
+    };
+    let mut request = None;
+
+    egui::Window::new("🎯 参数拟合")
+        .open(open)
+        .resizable(true)
+        .default_width(360.0)
+        .show(ctx, |ui| {
+            ui.label("设定目标覆盖率，以当前种子、尺寸和环境判定参数为起点做爬山搜索。");
+            ui.weak("粗搜索：每轮扰动一个相关参数并重新生成，误差下降才保留。");
+            ui.separator();
+
+            let running = state.is_running();
+            ui.add_enabled_ui(!running, |ui| {
+                let mut remove = None;
+                egui::Grid::new("param_fit_targets")
+                    .num_columns(3)
+                    .spacing([8.0, 4.0])
+                    .show(ui, |ui| {
+                        for (i, target) in state.targets.iter_mut().enumerate() {
+                            egui::ComboBox::from_id_source(("param_fit_biome", i))
+                                .selected_text(biome_name(&target.biome_key))
+                                .show_ui(ui, |ui| {
+                                    for key in tunable_biome_keys() {
+                                        ui.selectable_value(&mut target.biome_key, key.to_string(), biome_name(key));
+                                    }
+                                });
+                            ui.add(
+                                egui::DragValue::new(&mut target.percent)
+                                    .clamp_range(0.0..=100.0)
+                                    .speed(0.1)
+                                    .suffix("%"),
+                            );
+                            if ui.small_button("✕").on_hover_text("移除此目标").clicked() {
+                                remove = Some(i);
+                            }
+                            ui.end_row();
+                        }
+                    });
+                if let Some(i) = remove {
+                    state.targets.remove(i);
+                }
+                if ui.small_button("＋ 添加目标").clicked() {
+                    let key = tunable_biome_keys()
+                        .find(|key| !state.targets.iter().any(|t| t.biome_key == *key))
+                        .unwrap_or("forest");
+                    state.targets.push(FitTarget { biome_key: key.to_string(), percent: 5.0 });
+                }
+
+                ui.add_space(4.0);
+                ui.horizontal(|ui| {
+                    ui.label("迭代轮数");
+                    ui.add(egui::DragValue::new(&mut state.iterations).clamp_range(1..=1000));
+                    ui.label("样本种子数");
+                    ui.add(egui::DragValue::new(&mut state.samples).clamp_range(1..=8))
+                        .on_hover_text("每组参数取 seed, seed+1, … 的平均覆盖率，降低单个种子的偶然性");
+                });
+            });
+
+            ui.add_space(4.0);
+            if let Some(job) = &state.job {
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::ProgressBar::new(job.done as f32 / job.iterations.max(1) as f32)
+                            .desired_width(220.0)
+                            .text(match job.error {
+                                Some(error) => format!("{}/{} · 误差 {error:.3}", job.done, job.iterations),
+                                None => "测量起始参数…".to_string(),
+                            }),
+                    );
+                    if ui.button("■ 停止").clicked() {
+                        job.cancel.store(true, Ordering::Relaxed);
+                    }
+                });
+            } else if ui
+                .add_enabled(!state.targets.is_empty(), egui::Button::new("▶ 开始拟合"))
+                .clicked()
+            {
+                request = Some(ParamFitRequest::Start);
+            }
+
+            let Some((targets, result)) = &state.result else { return };
+            ui.separator();
+            let result = match result {
+                Ok(result) => result,
+                Err(e) => {
+                    ui.colored_label(Color32::from_rgb(230, 110, 110), format!("拟合失败: {e}"));
+                    return;
+                }
+            };
+
+            egui::Grid::new("param_fit_coverage")
+                .num_columns(4)
+                .striped(true)
+                .spacing([12.0, 4.0])
+                .show(ui, |ui| {
+                    ui.strong("环境");
+                    ui.strong("目标");
+                    ui.strong("拟合前");
+                    ui.strong("拟合后");
+                    ui.end_row();
+                    for (i, target) in targets.iter().enumerate() {
+                        ui.label(biome_name(&target.biome_key));
+                        ui.monospace(format!("{:.2}%", target.percent));
+                        ui.monospace(format!("{:.2}%", result.initial_coverage[i]));
+                        ui.monospace(format!("{:.2}%", result.coverage[i]));
+                        ui.end_row();
+                    }
+                });
+            ui.colored_label(
+                theme::TEXT_SECONDARY,
+                format!(
+                    "误差 {:.3} → {:.3}（{} 轮）",
+                    result.initial_error, result.error, result.iterations,
+                ),
+            );
+
+            if result.changed.is_empty() {
+                ui.label("没有找到更好的参数。");
+                return;
+            }
+            ui.add_space(4.0);
+            for (key, before, after) in &result.changed {
+                ui.monospace(format!("{key}: {before} → {after}"));
+            }
+            ui.add_space(4.0);
+            if ui.button("✔ 应用参数").on_hover_text("写入环境判定参数并重新执行到当前位置").clicked() {
+                request = Some(ParamFitRequest::Apply(result.params.clone()));
+            }
+        });
+
+    request
+}
@@ -2,12 +2,15 @@
 //!
 //! 以 egui 窗口展示：
 //! - 当前 EngineConfig 参数（可编辑）
-//! - 最近一次生成的性能报告
+//! - 最近一次生成的性能报告（含按阶段分解的堆叠条）
 //! - 历史性能日志列表
 
-use egui::Window;
+use egui::{Align2, Color32, FontId, Rect, Sense, Stroke, Vec2, Window};
 
-use crate::generation::optimizer::PerfProfiler;
+use crate::core::geometry::FillPathStats;
+use crate::generation::optimizer::{PerfProfiler, StepProfile};
+use crate::generation::step_rng::RngKind;
+use crate::rendering::viewport::ViewportEasing;
 use crate::storage::engine_config::EngineConfig;
 use crate::storage::perf_log;
 use crate::ui::theme;
@@ -20,6 +23,9 @@ pub fn show_perf_panel_window(
     is_open: &mut bool,
     config: &mut EngineConfig,
     profiler: &PerfProfiler,
+    phases: &[(String, usize)],
+    current_seed: u64,
+    texture_info: (Option<u32>, usize),
 ) -> bool {
     let mut changed = false;
 
@@ -60,16 +66,18 @@ pub fn show_perf_panel_window(
 
                     ui.label("目标帧时间 (ms)");
                     ui.horizontal(|ui| {
-                        let mut lo = config.batch_target_min_ms as f32;
-                        let mut hi = config.batch_target_max_ms as f32;
-                        ui.label("下限");
-                        if ui.add(egui::DragValue::new(&mut lo).clamp_range(1.0..=50.0).speed(0.5)).changed() {
-                            config.batch_target_min_ms = lo as f64;
+                        let mut target = config.batch_target_ms as f32;
+                        let mut band = config.batch_band_ms as f32;
+                        if ui.add(egui::DragValue::new(&mut target).clamp_range(1.0..=100.0).speed(0.5)).changed() {
+                            config.batch_target_ms = target as f64;
                             changed = true;
                         }
-                        ui.label("上限");
-                        if ui.add(egui::DragValue::new(&mut hi).clamp_range(1.0..=100.0).speed(0.5)).changed() {
-                            config.batch_target_max_ms = hi as f64;
+                        ui.label("±");
+                        if ui.add(egui::DragValue::new(&mut band).clamp_range(0.0..=50.0).speed(0.25))
+                            .on_hover_text("EMA 帧时间超出此容差带才调整 batch（每次 ±1）")
+                            .changed()
+                        {
+                            config.batch_band_ms = band as f64;
                             changed = true;
                         }
                     });
@@ -138,6 +146,17 @@ pub fn show_perf_panel_window(
                     });
                     ui.end_row();
 
+                    ui.label("手动步进刷新延迟 (ms)");
+                    let mut v = config.manual_refresh_idle_ms as u32;
+                    if ui.add(egui::DragValue::new(&mut v).clamp_range(0..=1000).speed(5))
+                        .on_hover_text("非小世界上连续单步 / 回退时，最后一步后空闲满此时长才刷新纹理，连点更跟手；0 = 每步立即刷新")
+                        .changed()
+                    {
+                        config.manual_refresh_idle_ms = v as u64;
+                        changed = true;
+                    }
+                    ui.end_row();
+
                     // 日志保留
                     ui.label("日志最大保留数");
                     let mut v = config.perf_log_max_files as u32;
@@ -146,6 +165,263 @@ pub fn show_perf_panel_window(
                         changed = true;
                     }
                     ui.end_row();
+
+                    ui.label("CSV 生成日志");
+                    if ui.checkbox(&mut config.csv_log, "")
+                        .on_hover_text("每次完整生成后向 logs/generation_log.csv 追加一行：各步骤耗时、丛林方位、沙漠 / 邪恶环境放置数量等，便于多次运行的统计分析")
+                        .changed()
+                    {
+                        changed = true;
+                    }
+                    ui.end_row();
+
+                    // 画布背景
+                    ui.colored_label(theme::BLUE_LIGHT, "画布背景");
+                    ui.label("");
+                    ui.end_row();
+
+                    ui.label("棋盘格边长 (px)");
+                    if ui.add(egui::DragValue::new(&mut config.checker_tile).clamp_range(4.0..=256.0).speed(1.0)).changed() {
+                        changed = true;
+                    }
+                    ui.end_row();
+
+                    ui.label("棋盘格颜色 (A/B)");
+                    ui.horizontal(|ui| {
+                        let c1 = ui.color_edit_button_srgb(&mut config.checker_color_a).changed();
+                        let c2 = ui.color_edit_button_srgb(&mut config.checker_color_b).changed();
+                        if c1 || c2 {
+                            changed = true;
+                        }
+                        if ui.small_button("纯色")
+                            .on_hover_text("颜色 B 设为与 A 相同，得到纯色背景")
+                            .clicked()
+                        {
+                            config.checker_color_b = config.checker_color_a;
+                            changed = true;
+                        }
+                    });
+                    ui.end_row();
+
+                    ui.label("纹理分块上限 (px)");
+                    ui.horizontal(|ui| {
+                        if ui.add(egui::DragValue::new(&mut config.max_texture_size).clamp_range(0..=16384).speed(64.0))
+                            .on_hover_text("单张纹理的最大边长，世界纹理超出时分块上传与绘制；0 = 使用 GPU 上限")
+                            .changed()
+                        {
+                            changed = true;
+                        }
+                        let (gpu_max, tiles) = texture_info;
+                        let gpu = gpu_max.map_or("未知".to_string(), |m| m.to_string());
+                        ui.weak(format!("GPU 上限 {gpu} · 当前 {tiles} 块"));
+                    });
+                    ui.end_row();
+
+                    ui.label("内存预算 (MB)");
+                    if ui.add(egui::DragValue::new(&mut config.memory_budget_mb).clamp_range(0..=65536).speed(16.0))
+                        .on_hover_text("切换世界尺寸前按每格约 12 字节估算占用（方块、环境地图、画布纹理、撤销备份），超出预算则拒绝切换；0 = 不限制")
+                        .changed()
+                    {
+                        changed = true;
+                    }
+                    ui.end_row();
+
+                    // 交互
+                    ui.colored_label(theme::BLUE_LIGHT, "交互");
+                    ui.label("");
+                    ui.end_row();
+
+                    ui.label("重置前确认");
+                    if ui.checkbox(&mut config.confirm_destructive_reset, "")
+                        .on_hover_text("生成进行到一半时，「一键生成」/「重新初始化」会先弹窗确认")
+                        .changed()
+                    {
+                        changed = true;
+                    }
+                    ui.end_row();
+
+                    ui.label("固定种子模式");
+                    ui.horizontal(|ui| {
+                        if ui.checkbox(&mut config.fixed_seed_mode, "")
+                            .on_hover_text("开启后「一键生成」/「重新初始化」总是使用下面的固定种子，每次重置得到相同的世界")
+                            .changed()
+                        {
+                            changed = true;
+                        }
+                        ui.monospace(format!("0x{:016X}", config.fixed_seed));
+                        if ui.small_button("设为当前种子")
+                            .on_hover_text(format!("0x{current_seed:016X}"))
+                            .clicked()
+                        {
+                            config.fixed_seed = current_seed;
+                            changed = true;
+                        }
+                    });
+                    ui.end_row();
+
+                    ui.label("画布惯性平移");
+                    if ui.checkbox(&mut config.pan_inertia, "")
+                        .on_hover_text("拖拽画布松手后按拖拽速度继续滑行，约 0.5 秒内减速停下；关闭则松手即停，便于精确定位")
+                        .changed()
+                    {
+                        changed = true;
+                    }
+                    ui.end_row();
+
+                    ui.label("换世界时重置视口");
+                    if ui.checkbox(&mut config.reset_viewport_on_world_change, "")
+                        .on_hover_text("切换世界尺寸或导入存档时把视口恢复为默认缩放并居中；关闭则保持当前缩放，视图中心按比例映射到新世界的对应位置")
+                        .changed()
+                    {
+                        changed = true;
+                    }
+                    ui.end_row();
+
+                    ui.label("单步后聚焦改动");
+                    if ui.checkbox(&mut config.auto_frame_step, "")
+                        .on_hover_text("每执行一个子步骤，视口平滑移动并缩放到该步形状记录的范围；没有形状记录的步骤保持视图不变。拖拽或缩放画布会中断动画")
+                        .changed()
+                    {
+                        changed = true;
+                    }
+                    ui.end_row();
+
+                    ui.label("视口动画");
+                    ui.horizontal(|ui| {
+                        if ui.add(egui::DragValue::new(&mut config.viewport_animation_ms)
+                            .clamp_range(0..=3000)
+                            .speed(10.0)
+                            .suffix(" ms"))
+                            .on_hover_text("聚焦改动、回到出生点、书签跳转与查找环境时视口移动的时长；0 = 直接跳到目标")
+                            .changed()
+                        {
+                            changed = true;
+                        }
+                        egui::ComboBox::from_id_source("viewport_easing")
+                            .selected_text(config.viewport_easing.label())
+                            .show_ui(ui, |ui| {
+                                for easing in ViewportEasing::ALL {
+                                    if ui.selectable_value(&mut config.viewport_easing, easing, easing.label()).changed() {
+                                        changed = true;
+                                    }
+                                }
+                            })
+                            .response
+                            .on_hover_text("线性：匀速移动；缓入缓出：起步和到达时减速，观感更柔和");
+                    });
+                    ui.end_row();
+
+                    ui.label("滚轮缩放");
+                    ui.horizontal(|ui| {
+                        if ui.add(egui::Slider::new(&mut config.zoom_sensitivity, 0.2..=5.0)
+                            .logarithmic(true)
+                            .suffix("×"))
+                            .on_hover_text("每次滚动的缩放幅度倍率；1× 时鼠标每个刻度约缩放 10%，触控板滚动量小可调高")
+                            .changed()
+                        {
+                            changed = true;
+                        }
+                        if ui.checkbox(&mut config.invert_zoom, "反向")
+                            .on_hover_text("向下滚动放大、向上滚动缩小")
+                            .changed()
+                        {
+                            changed = true;
+                        }
+                    });
+                    ui.end_row();
+
+                    ui.label("退出时保存会话");
+                    if ui.checkbox(&mut config.auto_save_session, "")
+                        .on_hover_text("关闭程序时把当前世界（含进度）保存为 last_session.lwd，下次启动时询问是否恢复")
+                        .changed()
+                    {
+                        changed = true;
+                    }
+                    ui.end_row();
+
+                    ui.label("崩溃恢复检查点");
+                    if ui.add(egui::DragValue::new(&mut config.checkpoint_interval_secs).clamp_range(0..=3600).suffix(" 秒"))
+                        .on_hover_text("生成过程中每隔该秒数把进度写入 checkpoint.lwd，程序崩溃或被强制结束后，下次启动时询问是否从检查点恢复；0 = 关闭")
+                        .changed()
+                    {
+                        changed = true;
+                    }
+                    ui.end_row();
+
+                    // 调试
+                    ui.colored_label(theme::BLUE_LIGHT, "调试");
+                    ui.label("");
+                    ui.end_row();
+
+                    ui.label("子种子推导覆盖层");
+                    if ui.checkbox(&mut config.debug_seed_overlay, "")
+                        .on_hover_text("在画布左上角列出每个已执行步骤的 flat 索引、派生种子与耗时")
+                        .changed()
+                    {
+                        changed = true;
+                    }
+                    ui.end_row();
+
+                    ui.label("森林填充扩散箭头");
+                    if ui.checkbox(&mut config.debug_diffusion_arrows, "")
+                        .on_hover_text("森林填充执行后，在每个扩散边缘画箭头：方向为扩散方向，长度为该行填充的格数")
+                        .changed()
+                    {
+                        changed = true;
+                    }
+                    ui.end_row();
+
+                    ui.label("生成顺序热力图");
+                    if ui.checkbox(&mut config.debug_assign_order, "")
+                        .on_hover_text("记录每格由第几个子步骤分配，环境覆盖层改为按顺序着色：早 = 蓝，晚 = 红。\n开启时重新执行到当前步骤以补全记录；每步额外比较一次环境地图")
+                        .changed()
+                    {
+                        changed = true;
+                    }
+                    ui.end_row();
+
+                    ui.label("未知环境高亮色");
+                    if ui.color_edit_button_srgba_unmultiplied(&mut config.unknown_biome_color)
+                        .on_hover_text("环境地图中已分配、但在 biome.json 中没有定义的环境 ID 在覆盖层上以此色显示，便于发现环境定义不匹配；不透明度设为 0 则与未分配一样透明")
+                        .changed()
+                    {
+                        changed = true;
+                    }
+                    ui.end_row();
+
+                    ui.label("子种子混入世界尺寸");
+                    if ui.checkbox(&mut config.seed_size_mixing, "")
+                        .on_hover_text("开启（默认）时同一种子在不同尺寸下布局不同，与泰拉瑞亚一致；\n关闭后子种子与尺寸无关，各尺寸得到按比例缩放的相似布局，\n关闭时与默认设置下同一种子生成的世界不再一致。此项随 .lwd 存档保存，导入存档时按存档恢复。切换后重新执行到当前步骤")
+                        .changed()
+                    {
+                        changed = true;
+                    }
+                    ui.end_row();
+
+                    ui.label("步骤随机数算法");
+                    egui::ComboBox::from_id_source("rng_kind")
+                        .selected_text(config.rng_kind.label())
+                        .show_ui(ui, |ui| {
+                            for kind in RngKind::ALL {
+                                if ui.selectable_value(&mut config.rng_kind, kind, kind.label()).changed() {
+                                    changed = true;
+                                }
+                            }
+                        })
+                        .response
+                        .on_hover_text("每个子步骤用哪种伪随机数生成器。不同算法得到不同但同样可复现的世界：\n切换会改变所有种子的生成结果，与他人默认设置下同一种子生成的世界不再一致；此项随 .lwd 存档保存，导入存档时按存档恢复。\n.NET System.Random 的原始序列与泰拉瑞亚一致，但取值方式不同，世界不会与游戏相同。切换后重新执行到当前步骤");
+                    ui.end_row();
+
+                    if cfg!(debug_assertions) {
+                        ui.label("防重叠检查");
+                        if ui.checkbox(&mut config.debug_overlap_check, "")
+                            .on_hover_text("每步执行前后比较环境地图，已分配的环境被未声明地改写时中止并报错（仅调试构建）")
+                            .changed()
+                        {
+                            changed = true;
+                        }
+                        ui.end_row();
+                    }
                 });
 
             ui.add_space(4.0);
@@ -177,8 +453,21 @@ pub fn show_perf_panel_window(
 
                 let steps = profiler.all_steps_sorted();
                 if !steps.is_empty() {
+                    show_phase_breakdown(ui, &steps, phases);
+                    ui.add_space(4.0);
+
+                    if let Some((idx, sp)) = profiler.last_step() {
+                        let f = sp.last_fills;
+                        ui.label(format!(
+                            "本步骤 [{idx}] {}: {} 并行填充, {} 串行填充",
+                            sp.name, f.parallel, f.serial
+                        ))
+                        .on_hover_text(fill_stats_hover(&f));
+                        ui.add_space(4.0);
+                    }
+
                     egui::Grid::new("perf_steps")
-                        .num_columns(4)
+                        .num_columns(5)
                         .spacing([8.0, 2.0])
                         .striped(true)
                         .show(ui, |ui| {
@@ -186,6 +475,8 @@ pub fn show_perf_panel_window(
                             ui.colored_label(theme::BLUE_LIGHT, "名称");
                             ui.colored_label(theme::BLUE_LIGHT, "平均(ms)");
                             ui.colored_label(theme::BLUE_LIGHT, "最大(ms)");
+                            ui.colored_label(theme::BLUE_LIGHT, "填充(并行/串行)")
+                                .on_hover_text("最近一次执行中走并行 / 串行路径的填充次数，阈值见上方「并行像素阈值」");
                             ui.end_row();
 
                             for (idx, sp) in &steps {
@@ -193,6 +484,13 @@ pub fn show_perf_panel_window(
                                 ui.label(&sp.name);
                                 ui.label(format!("{:.2}", sp.avg_duration().as_secs_f64() * 1000.0));
                                 ui.label(format!("{:.2}", sp.max_duration.as_secs_f64() * 1000.0));
+                                let f = sp.last_fills;
+                                if f.parallel + f.serial > 0 {
+                                    ui.label(format!("{} / {}", f.parallel, f.serial))
+                                        .on_hover_text(fill_stats_hover(&f));
+                                } else {
+                                    ui.weak("—");
+                                }
                                 ui.end_row();
                             }
                         });
@@ -235,3 +533,101 @@ pub fn show_perf_panel_window(
 
     changed
 }
+
+/// 阶段色（按阶段序号循环）
+const PHASE_COLORS: [Color32; 6] = [
+    theme::PINK,
+    theme::BLUE,
+    Color32::from_rgb(170, 215, 120),
+    Color32::from_rgb(240, 200, 110),
+    Color32::from_rgb(190, 160, 240),
+    Color32::from_rgb(120, 210, 190),
+];
+
+/// 填充路径统计的悬浮说明：各路径的次数与覆盖面积
+fn fill_stats_hover(f: &FillPathStats) -> String {
+    format!(
+        "并行: {} 次, 共 {} px\n串行: {} 次, 共 {} px",
+        f.parallel, f.parallel_area, f.serial, f.serial_area
+    )
+}
+
+/// 按阶段分解的耗时：上行每段为一个阶段的总耗时，下行把阶段拆成各子步骤
+/// （与上行对齐，同阶段的子步骤深浅交替）。悬浮显示名称、耗时与占比。
+fn show_phase_breakdown(
+    ui: &mut egui::Ui,
+    steps: &[(usize, &StepProfile)],
+    phases: &[(String, usize)],
+) {
+    const ROW_HEIGHT: f32 = 18.0;
+
+    let ms = |sp: &StepProfile| sp.avg_duration().as_secs_f64() * 1000.0;
+
+    // flat 索引 → 阶段序号
+    let mut phase_of = Vec::new();
+    for (pi, &(_, count)) in phases.iter().enumerate() {
+        phase_of.extend(std::iter::repeat_n(pi, count));
+    }
+    let mut phase_ms = vec![0.0f64; phases.len()];
+    for (idx, sp) in steps {
+        if let Some(&pi) = phase_of.get(*idx) {
+            phase_ms[pi] += ms(sp);
+        }
+    }
+    let total: f64 = phase_ms.iter().sum();
+    if total <= 0.0 {
+        return;
+    }
+
+    let width = ui.available_width();
+    let (rect, response) = ui.allocate_exact_size(Vec2::new(width, ROW_HEIGHT * 2.0 + 2.0), Sense::hover());
+    let painter = ui.painter_at(rect);
+    let scale = width as f64 / total;
+    let hover = response.hover_pos();
+    let mut tooltip = None;
+
+    let mut segment = |x: f32, w: f32, row: usize, fill: Color32, label: &str, value: f64| {
+        let top = rect.top() + row as f32 * (ROW_HEIGHT + 2.0);
+        let seg = Rect::from_min_size(egui::pos2(x, top), Vec2::new(w, ROW_HEIGHT));
+        painter.rect_filled(seg, 0.0, fill);
+        painter.rect_stroke(seg, 0.0, Stroke::new(1.0, theme::BG_WINDOW));
+        if row == 0 {
+            let galley = painter.layout_no_wrap(label.to_string(), FontId::proportional(11.0), theme::BG_DARK);
+            if galley.size().x + 6.0 < w {
+                painter.galley(Align2::CENTER_CENTER.anchor_size(seg.center(), galley.size()).min, galley, theme::BG_DARK);
+            }
+        }
+        if hover.is_some_and(|p| seg.contains(p)) {
+            tooltip = Some(format!("{label}\n{value:.2}ms（{:.1}%）", value / total * 100.0));
+        }
+    };
+
+    // 上行：阶段
+    let mut x = rect.left();
+    for (pi, (name, _)) in phases.iter().enumerate() {
+        let w = (phase_ms[pi] * scale) as f32;
+        if w > 0.0 {
+            segment(x, w, 0, PHASE_COLORS[pi % PHASE_COLORS.len()], name, phase_ms[pi]);
+            x += w;
+        }
+    }
+
+    // 下行：子步骤（all_steps_sorted 已按 flat 索引排序，与阶段顺序一致）
+    let mut x = rect.left();
+    for (i, (idx, sp)) in steps.iter().enumerate() {
+        let Some(&pi) = phase_of.get(*idx) else { continue };
+        let w = (ms(sp) * scale) as f32;
+        let base = PHASE_COLORS[pi % PHASE_COLORS.len()];
+        let fill = if i % 2 == 0 {
+            base
+        } else {
+            Color32::from_rgb(base.r() * 3 / 4, base.g() * 3 / 4, base.b() * 3 / 4)
+        };
+        segment(x, w, 1, fill, &sp.name, ms(sp));
+        x += w;
+    }
+
+    if let Some(text) = tooltip {
+        response.on_hover_text_at_pointer(text);
+    }
+}
@@ -0,0 +1,99 @@
+//! # 预览绘制质量
+//!
+//! 几何预览与图形 API 沙箱共用的绘制精度设置，以性能换平滑度：
+//!
+//! - 椭圆：`中` 及以下沿用 egui 内置的 `Shape::ellipse_*`；更高档位改为按屏幕周长
+//!   细分的凸多边形，大半径椭圆的边缘不再出现折角
+//! - 采样绘制（隐函数、图片模板、交集/差集）：每个采样点在屏幕上的尺寸，
+//!   以及组合运算的基础采样步长
+//!
+//! 默认 `中`，与引入此设置之前的绘制结果一致。
+
+use std::f32::consts::PI;
+
+use egui::{Color32, Painter, Pos2, Stroke, Ui, Vec2};
+
+/// 预览绘制质量档位
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PreviewQuality {
+    Low,
+    #[default]
+    Medium,
+    High,
+    Ultra,
+}
+
+impl PreviewQuality {
+    pub const ALL: [PreviewQuality; 4] =
+        [PreviewQuality::Low, PreviewQuality::Medium, PreviewQuality::High, PreviewQuality::Ultra];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PreviewQuality::Low => "低 (快)",
+            PreviewQuality::Medium => "中",
+            PreviewQuality::High => "高",
+            PreviewQuality::Ultra => "极高 (慢)",
+        }
+    }
+
+    /// 采样绘制时每个采样点在屏幕上的目标尺寸（像素）
+    pub fn sample_px(self) -> f32 {
+        match self {
+            PreviewQuality::Low => 6.0,
+            PreviewQuality::Medium => 3.0,
+            PreviewQuality::High => 2.0,
+            PreviewQuality::Ultra => 1.0,
+        }
+    }
+
+    /// 组合运算（交集/差集）的基础采样步长（格），缩小时再按缩放倍率加大
+    pub fn combine_step(self) -> i32 {
+        match self {
+            PreviewQuality::Low => 8,
+            PreviewQuality::Medium => 4,
+            PreviewQuality::High => 2,
+            PreviewQuality::Ultra => 1,
+        }
+    }
+
+    /// 椭圆多边形的最大边长（屏幕像素）；`None` 表示使用 egui 内置细分
+    fn ellipse_edge_px(self) -> Option<f32> {
+        match self {
+            PreviewQuality::Low | PreviewQuality::Medium => None,
+            PreviewQuality::High => Some(4.0),
+            PreviewQuality::Ultra => Some(1.5),
+        }
+    }
+
+    /// 绘制填充 + 描边的椭圆（`radius` 为屏幕像素半径）
+    pub fn draw_ellipse(self, painter: &Painter, center: Pos2, radius: Vec2, fill: Color32, stroke: Stroke) {
+        let Some(edge) = self.ellipse_edge_px() else {
+            painter.add(egui::Shape::ellipse_filled(center, radius, fill));
+            painter.add(egui::Shape::ellipse_stroke(center, radius, stroke));
+            return;
+        };
+        // Ramanujan 周长近似
+        let (a, b) = (radius.x.abs(), radius.y.abs());
+        let perimeter = PI * (3.0 * (a + b) - ((3.0 * a + b) * (a + 3.0 * b)).sqrt());
+        let n = ((perimeter / edge).ceil() as usize).clamp(32, 4096);
+        let points = (0..n)
+            .map(|i| {
+                let t = i as f32 / n as f32 * 2.0 * PI;
+                Pos2::new(center.x + a * t.cos(), center.y + b * t.sin())
+            })
+            .collect();
+        painter.add(egui::Shape::convex_polygon(points, fill, stroke));
+    }
+
+    /// 工具栏中的质量下拉框
+    pub fn combo(&mut self, ui: &mut Ui, id_source: impl std::hash::Hash) {
+        egui::ComboBox::from_id_source(id_source)
+            .selected_text(self.label())
+            .width(70.0)
+            .show_ui(ui, |ui| {
+                for quality in Self::ALL {
+                    ui.selectable_value(self, quality, quality.label());
+                }
+            });
+    }
+}
@@ -0,0 +1,60 @@
+//! # 子种子推导调试覆盖层
+//!
+//! 在画布左上角列出每个已执行子步骤的 flat 索引、`derive_step_seed` 派生的子种子和耗时，
+//! 用于确认同一种子 + 同一尺寸在多次运行中得到相同的逐步种子（`.lwd` 回放的正确性前提）。
+
+use egui::{Color32, Rect};
+
+use crate::generation::StepSeedTrace;
+use crate::ui::theme;
+
+/// 在 `canvas_rect` 左上角绘制种子推导列表。
+pub fn show_seed_trace_overlay(
+    ctx: &egui::Context,
+    canvas_rect: Rect,
+    master_seed: u64,
+    world_size: (u32, u32),
+    trace: &[StepSeedTrace],
+) {
+    egui::Area::new(egui::Id::new("seed_trace_overlay"))
+        .fixed_pos(canvas_rect.left_top() + egui::vec2(8.0, 8.0))
+        .order(egui::Order::Foreground)
+        .interactable(false)
+        .show(ctx, |ui| {
+            egui::Frame::none()
+                .fill(Color32::from_rgba_unmultiplied(20, 20, 30, 200))
+                .rounding(4.0)
+                .inner_margin(6.0)
+                .show(ui, |ui| {
+                    ui.colored_label(
+                        theme::PINK,
+                        format!(
+                            "种子推导 master=0x{master_seed:016X} @ {}×{}",
+                            world_size.0, world_size.1
+                        ),
+                    );
+                    if trace.is_empty() {
+                        ui.colored_label(theme::TEXT_MUTED, "尚未执行任何步骤");
+                        return;
+                    }
+                    egui::Grid::new("seed_trace_grid")
+                        .num_columns(4)
+                        .spacing([10.0, 1.0])
+                        .show(ui, |ui| {
+                            ui.colored_label(theme::BLUE_LIGHT, "#");
+                            ui.colored_label(theme::BLUE_LIGHT, "步骤");
+                            ui.colored_label(theme::BLUE_LIGHT, "子种子");
+                            ui.colored_label(theme::BLUE_LIGHT, "耗时(ms)");
+                            ui.end_row();
+
+                            for t in trace {
+                                ui.monospace(format!("{}", t.flat_index));
+                                ui.label(&t.name);
+                                ui.monospace(format!("{:016X}", t.step_seed));
+                                ui.monospace(format!("{:.2}", t.elapsed.as_secs_f64() * 1000.0));
+                                ui.end_row();
+                            }
+                        });
+                });
+        });
+}
@@ -2,26 +2,45 @@
 //!
 //! 交互式窗口，用于创建、预览和组合几何图形 API。
 //! 功能：
-//! - 添加基础形状（矩形 / 椭圆 / 梯形 / 列）
+//! - 添加基础形状（矩形 / 椭圆 / 梯形 / 列 / 隐函数）
 //! - 调整每种形状的参数（滑块 + 数值拖放）
 //! - 使用集合运算组合形状（并集 / 交集 / 差集）
 //! - 实时 mini-canvas 预览组合结果
 //! - 显示数学描述 + 代码片段
+//! - 将选中的形状 / 组合以指定环境填充到当前世界（应用到世界）
+//! - 把调好的形状按名称存入图形库，在任意沙箱中插入复用
+//! - 粘贴剪贴板中的图片作为模板：暗色像素覆盖的格子以指定环境填充
 
 use egui::{
     Color32, Context, Pos2, Rect as EguiRect, Sense, Stroke, Ui, Vec2,
 };
+use serde::{Deserialize, Serialize};
 
+use crate::core::biome::{BiomeDefinition, BiomeId};
 use crate::core::geometry::{
-    BoundingBox, Column, Ellipse, Rect, Shape, ShapeKind, Trapezoid,
+    BoundingBox, Column, Ellipse, Implicit, Rect, Shape, ShapeCombine, ShapeKind, Stencil, Trapezoid,
 };
+use crate::core::log;
+use crate::storage::shape_library::ShapeLibrary;
+use crate::ui::preview_quality::PreviewQuality;
 
 // ═══════════════════════════════════════════════════════════
 // 数据结构
 // ═══════════════════════════════════════════════════════════
 
-/// 沙箱中一个形状条目
-#[derive(Debug, Clone)]
+/// 隐函数示例（名称, 表达式）；第一项为新建隐函数形状的默认值
+const IMPLICIT_EXAMPLES: [(&str, &str); 4] = [
+    ("圆", "x*x + y*y < r*r"),
+    ("菱形", "abs(x) + abs(y) < r"),
+    ("波浪带", "abs(y - r/4 * sin(x / 60)) < r/3"),
+    ("圆环", "abs(sqrt(x*x + y*y) - r) < r/5"),
+];
+
+/// 超出世界范围的形状的包围盒与标签颜色
+const OUT_OF_WORLD_COLOR: Color32 = Color32::from_rgb(230, 110, 110);
+
+/// 沙箱中一个形状条目；图形库直接保存此结构（隐函数的包围盒与解析结果插入时按当前世界重建）
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SandboxShape {
     /// 形状类型
     pub kind: ShapeKind,
@@ -53,11 +72,30 @@ pub struct SandboxShape {
     pub col_x: i32,
     pub col_y_start: i32,
     pub col_y_end: i32,
+    // 隐函数
+    pub impl_source: String,
+    pub impl_cx: f64,
+    pub impl_cy: f64,
+    pub impl_r: f64,
+    /// 包围盒：创建时的世界范围
+    #[serde(skip, default = "empty_bounds")]
+    pub impl_bounds: BoundingBox,
+    /// 按当前源码与参数构造的形状（解析失败时为错误说明），编辑后由 `refresh_implicit` 更新
+    #[serde(skip, default = "unparsed_implicit")]
+    pub implicit: Result<Implicit, String>,
+}
+
+fn empty_bounds() -> BoundingBox {
+    BoundingBox::new(0, 0, 0, 0)
+}
+
+fn unparsed_implicit() -> Result<Implicit, String> {
+    Err(String::new())
 }
 
 impl SandboxShape {
     /// 创建默认形状（居中放置）
-    fn new_default(kind: ShapeKind, index: usize, world_w: u32, world_h: u32) -> Self {
+    pub(crate) fn new_default(kind: ShapeKind, index: usize, world_w: u32, world_h: u32) -> Self {
         let cx = world_w as f64 / 2.0;
         let cy = world_h as f64 / 2.0;
         let w4 = (world_w / 4) as i32;
@@ -100,56 +138,96 @@ impl SandboxShape {
             col_x: cx as i32,
             col_y_start: cy as i32 - h4,
             col_y_end: cy as i32 + h4,
+            // implicit → circle of radius h/4 around the center
+            impl_source: IMPLICIT_EXAMPLES[0].1.to_string(),
+            impl_cx: cx,
+            impl_cy: cy,
+            impl_r: h4 as f64,
+            impl_bounds: BoundingBox::new(0, 0, world_w as i32, world_h as i32),
+            implicit: Err(String::new()),
         }
+        .with_implicit_refreshed()
     }
 
-    /// 按当前 kind 构造对应的 BoundingBox
-    fn bounding_box(&self) -> BoundingBox {
-        match self.kind {
-            ShapeKind::Rect => {
-                let r = Rect::new(self.rect_x0, self.rect_y0, self.rect_x1, self.rect_y1);
-                r.bounding_box()
-            }
-            ShapeKind::Ellipse => {
-                let e = Ellipse::new(self.ell_cx, self.ell_cy, self.ell_rx, self.ell_ry);
-                e.bounding_box()
-            }
-            ShapeKind::Trapezoid => {
-                let t = Trapezoid::new(
-                    self.trap_y_top, self.trap_y_bot,
-                    self.trap_top_x0, self.trap_top_x1,
-                    self.trap_bot_x0, self.trap_bot_x1,
-                );
-                t.bounding_box()
-            }
-            ShapeKind::Column => {
-                Column::new(self.col_x, self.col_y_start, self.col_y_end).bounding_box()
-            }
-        }
+    /// 按图形库条目创建形状；隐函数的包围盒取当前世界范围
+    fn from_library(entry: &SandboxShape, world_w: u32, world_h: u32) -> Self {
+        let mut shape = entry.clone();
+        shape.impl_bounds = BoundingBox::new(0, 0, world_w as i32, world_h as i32);
+        shape.with_implicit_refreshed()
     }
 
-    /// 点检测
-    fn contains(&self, x: i32, y: i32) -> bool {
+    /// 包围盒是否超出世界范围 `[0, w) × [0, h)`
+    fn exceeds_world(&self, world_w: u32, world_h: u32) -> bool {
+        let bb = self.bounding_box();
+        !bb.is_empty()
+            && (bb.x_min < 0 || bb.y_min < 0 || bb.x_max > world_w as i32 || bb.y_max > world_h as i32)
+    }
+
+    /// 把参数收进世界范围：坐标钳制到世界内，椭圆半径缩到不越过最近的世界边缘。
+    /// 返回参数是否有改动
+    fn snap_to_world(&mut self, world_w: u32, world_h: u32) -> bool {
+        let (w, h) = (world_w as i32, world_h as i32);
+        let (wf, hf) = (world_w as f64, world_h as f64);
+        /// 钳制到 `[0, max]`，返回是否有改动
+        fn snap_i(v: &mut i32, max: i32) -> bool {
+            let snapped = (*v).clamp(0, max.max(0));
+            std::mem::replace(v, snapped) != snapped
+        }
+        fn snap_f(v: &mut f64, max: f64) -> bool {
+            let snapped = v.clamp(0.0, max.max(0.0));
+            std::mem::replace(v, snapped) != snapped
+        }
+        let mut changed = false;
         match self.kind {
             ShapeKind::Rect => {
-                Rect::new(self.rect_x0, self.rect_y0, self.rect_x1, self.rect_y1)
-                    .contains(x, y)
+                changed |= snap_i(&mut self.rect_x0, w);
+                changed |= snap_i(&mut self.rect_x1, w);
+                changed |= snap_i(&mut self.rect_y0, h);
+                changed |= snap_i(&mut self.rect_y1, h);
             }
             ShapeKind::Ellipse => {
-                Ellipse::new(self.ell_cx, self.ell_cy, self.ell_rx, self.ell_ry)
-                    .contains(x, y)
+                changed |= snap_f(&mut self.ell_cx, wf);
+                changed |= snap_f(&mut self.ell_cy, hf);
+                // 包围盒按 floor / ceil 取整，半径取整后才能保证不越界
+                changed |= snap_f(&mut self.ell_rx, self.ell_cx.floor().min((wf - self.ell_cx).floor()));
+                changed |= snap_f(&mut self.ell_ry, self.ell_cy.floor().min((hf - self.ell_cy).floor()));
             }
             ShapeKind::Trapezoid => {
-                Trapezoid::new(
-                    self.trap_y_top, self.trap_y_bot,
-                    self.trap_top_x0, self.trap_top_x1,
-                    self.trap_bot_x0, self.trap_bot_x1,
-                ).contains(x, y)
+                changed |= snap_i(&mut self.trap_y_top, h);
+                changed |= snap_i(&mut self.trap_y_bot, h);
+                changed |= snap_f(&mut self.trap_top_x0, wf);
+                changed |= snap_f(&mut self.trap_top_x1, wf);
+                changed |= snap_f(&mut self.trap_bot_x0, wf);
+                changed |= snap_f(&mut self.trap_bot_x1, wf);
             }
             ShapeKind::Column => {
-                Column::new(self.col_x, self.col_y_start, self.col_y_end).contains(x, y)
+                changed |= snap_i(&mut self.col_x, w - 1);
+                changed |= snap_i(&mut self.col_y_start, h);
+                changed |= snap_i(&mut self.col_y_end, h);
+            }
+            ShapeKind::Implicit => {
+                changed |= snap_f(&mut self.impl_cx, wf);
+                changed |= snap_f(&mut self.impl_cy, hf);
+                // 包围盒取创建时的世界范围，切换世界尺寸后可能大于当前世界
+                let bounds = self.impl_bounds.intersect(BoundingBox::new(0, 0, w, h));
+                changed |= bounds != self.impl_bounds;
+                self.impl_bounds = bounds;
+                if changed {
+                    self.refresh_implicit();
+                }
             }
         }
+        changed
+    }
+
+    fn with_implicit_refreshed(mut self) -> Self {
+        self.refresh_implicit();
+        self
+    }
+
+    /// 按当前源码与参数重新构造隐函数形状
+    fn refresh_implicit(&mut self) {
+        self.implicit = Implicit::new(&self.impl_source, self.impl_cx, self.impl_cy, self.impl_r, self.impl_bounds);
     }
 
     /// 数学描述
@@ -173,6 +251,10 @@ impl SandboxShape {
                 "x={}, y∈[{},{})",
                 self.col_x, self.col_y_start, self.col_y_end
             ),
+            ShapeKind::Implicit => format!(
+                "{}  (原点 ({:.0},{:.0}), r={:.0})",
+                self.impl_source, self.impl_cx, self.impl_cy, self.impl_r
+            ),
         }
     }
 
@@ -197,8 +279,73 @@ impl SandboxShape {
                 "let shape = Column::new({}, {}, {});",
                 self.col_x, self.col_y_start, self.col_y_end
             ),
+            ShapeKind::Implicit => format!(
+                "let shape = Implicit::new({:?}, {:.1}, {:.1}, {:.1}, BoundingBox::new(0, 0, {}, {}))?;",
+                self.impl_source, self.impl_cx, self.impl_cy, self.impl_r,
+                self.impl_bounds.x_max, self.impl_bounds.y_max,
+            ),
+        }
+    }
+}
+
+impl Shape for SandboxShape {
+    /// 按当前 kind 构造对应的 BoundingBox
+    fn bounding_box(&self) -> BoundingBox {
+        match self.kind {
+            ShapeKind::Rect => {
+                let r = Rect::new(self.rect_x0, self.rect_y0, self.rect_x1, self.rect_y1);
+                r.bounding_box()
+            }
+            ShapeKind::Ellipse => {
+                let e = Ellipse::new(self.ell_cx, self.ell_cy, self.ell_rx, self.ell_ry);
+                e.bounding_box()
+            }
+            ShapeKind::Trapezoid => {
+                let t = Trapezoid::new(
+                    self.trap_y_top, self.trap_y_bot,
+                    self.trap_top_x0, self.trap_top_x1,
+                    self.trap_bot_x0, self.trap_bot_x1,
+                );
+                t.bounding_box()
+            }
+            ShapeKind::Column => {
+                Column::new(self.col_x, self.col_y_start, self.col_y_end).bounding_box()
+            }
+            ShapeKind::Implicit => match &self.implicit {
+                Ok(shape) => shape.bounding_box(),
+                Err(_) => BoundingBox::new(0, 0, 0, 0),
+            },
+        }
+    }
+
+    /// 点检测
+    fn contains(&self, x: i32, y: i32) -> bool {
+        match self.kind {
+            ShapeKind::Rect => {
+                Rect::new(self.rect_x0, self.rect_y0, self.rect_x1, self.rect_y1)
+                    .contains(x, y)
+            }
+            ShapeKind::Ellipse => {
+                Ellipse::new(self.ell_cx, self.ell_cy, self.ell_rx, self.ell_ry)
+                    .contains(x, y)
+            }
+            ShapeKind::Trapezoid => {
+                Trapezoid::new(
+                    self.trap_y_top, self.trap_y_bot,
+                    self.trap_top_x0, self.trap_top_x1,
+                    self.trap_bot_x0, self.trap_bot_x1,
+                ).contains(x, y)
+            }
+            ShapeKind::Column => {
+                Column::new(self.col_x, self.col_y_start, self.col_y_end).contains(x, y)
+            }
+            ShapeKind::Implicit => self.implicit.as_ref().is_ok_and(|shape| shape.contains(x, y)),
         }
     }
+
+    fn type_name(&self) -> &'static str {
+        self.kind.display_name()
+    }
 }
 
 /// 集合运算类型
@@ -250,6 +397,20 @@ pub struct CombineNode {
     pub visible: bool,
 }
 
+/// 「应用到世界」请求：以指定环境填充选中的形状或组合
+pub struct SandboxApply {
+    /// 选中的形状或组合结果
+    pub shape: Box<dyn Shape>,
+    /// 显示名称（状态栏 / 日志）
+    pub label: String,
+    /// 填充的环境
+    pub biome: BiomeId,
+    /// 仅覆盖尚未分配环境的格子
+    pub only_unassigned: bool,
+    /// 只填充形状内侧宽度为该值的轮廓（0 = 整体填充）
+    pub outline: u32,
+}
+
 /// 沙箱窗口持久状态
 pub struct ShapeSandboxState {
     /// 实例 ID（用于区分多个窗口）
@@ -280,8 +441,22 @@ pub struct ShapeSandboxState {
     pub new_combine_op: SetOp,
     /// 显示模式：0=仅基础, 1=仅组合, 2=全部
     pub display_mode: u8,
-    /// 组合运算采样质量 (1=低, 2=中, 4=高, 8=极高)
-    pub combine_quality: i32,
+    /// 预览绘制质量（椭圆细分、采样步长）
+    pub quality: PreviewQuality,
+    /// 把形状参数吸附到世界范围内（编辑时自动钳制）
+    pub snap_to_world: bool,
+    /// 应用到世界 — 目标环境
+    pub apply_biome: Option<BiomeId>,
+    /// 应用到世界 — 仅覆盖空白格
+    pub apply_only_unassigned: bool,
+    /// 应用到世界 — 描边宽度（0 = 整体填充）
+    pub apply_outline: u32,
+    /// 存入图形库时使用的名称
+    pub library_name: String,
+    /// 从剪贴板粘贴的图片模板
+    pub stencil: Option<Stencil>,
+    /// 最近一次粘贴失败的原因
+    pub stencil_error: Option<String>,
 }
 
 impl ShapeSandboxState {
@@ -302,7 +477,14 @@ impl ShapeSandboxState {
             new_combine_right: 0,
             new_combine_op: SetOp::Union,
             display_mode: 2,
-            combine_quality: 2,
+            quality: PreviewQuality::default(),
+            snap_to_world: false,
+            apply_biome: None,
+            apply_only_unassigned: false,
+            apply_outline: 0,
+            library_name: String::new(),
+            stencil: None,
+            stencil_error: None,
         }
     }
 }
@@ -318,11 +500,16 @@ impl Default for ShapeSandboxState {
 // ═══════════════════════════════════════════════════════════
 
 /// 显示图形 API 沙箱窗口。
+///
+/// 用户点击「应用到世界」时返回填充请求，由调用方写入当前环境地图。
 pub fn show_shape_sandbox_window(
     ctx: &Context,
     state: &mut ShapeSandboxState,
     world_size: (u32, u32),
-) {
+    biomes: &[BiomeDefinition],
+    library: &mut ShapeLibrary,
+) -> Option<SandboxApply> {
+    let mut apply = None;
     let win_title = format!("◈ {} — 图形 API 沙箱", state.title);
     let win_id = egui::Id::new("shape_sandbox").with(state.id);
     let mut still_open = state.open;
@@ -334,7 +521,12 @@ pub fn show_shape_sandbox_window(
         .default_height(640.0)
         .show(ctx, |ui| {
             // ── 顶部工具栏 ──
-            draw_toolbar(ui, state, world_size);
+            draw_toolbar(ui, state, world_size, library);
+            if state.snap_to_world {
+                for shape in &mut state.shapes {
+                    shape.snap_to_world(world_size.0, world_size.1);
+                }
+            }
 
             ui.separator();
 
@@ -368,18 +560,25 @@ pub fn show_shape_sandbox_window(
                 ui.vertical(|ui| {
                     draw_sandbox_canvas(ui, state, world_size);
                     ui.separator();
-                    draw_detail_panel(ui, state);
+                    draw_detail_panel(ui, state, world_size);
+                    ui.separator();
+                    apply = draw_apply_bar(ui, state, biomes);
+                    ui.separator();
+                    if let Some(stencil_apply) = draw_stencil_bar(ui, state, world_size) {
+                        apply = Some(stencil_apply);
+                    }
                 });
             });
         });
     state.open = still_open;
+    apply
 }
 
 // ═══════════════════════════════════════════════════════════
 // 顶部工具栏
 // ═══════════════════════════════════════════════════════════
 
-fn draw_toolbar(ui: &mut Ui, state: &mut ShapeSandboxState, world_size: (u32, u32)) {
+fn draw_toolbar(ui: &mut Ui, state: &mut ShapeSandboxState, world_size: (u32, u32), library: &mut ShapeLibrary) {
     ui.horizontal(|ui| {
         ui.label("添加形状:");
 
@@ -405,6 +604,8 @@ fn draw_toolbar(ui: &mut Ui, state: &mut ShapeSandboxState, world_size: (u32, u3
             state.selected_combine = None;
         }
 
+        ui.menu_button("📚 图形库", |ui| draw_library_menu(ui, state, world_size, library));
+
         ui.separator();
 
         // 显示模式
@@ -418,23 +619,14 @@ fn draw_toolbar(ui: &mut Ui, state: &mut ShapeSandboxState, world_size: (u32, u3
 
         ui.separator();
 
-        // 组合精度控制
+        // 绘制精度控制
         ui.label("精度:");
-        let quality_text = match state.combine_quality {
-            1 => "低",
-            2 => "中",
-            4 => "高",
-            _ => "极高",
-        };
-        egui::ComboBox::from_id_source(("combine_quality", state.id))
-            .selected_text(quality_text)
-            .width(50.0)
-            .show_ui(ui, |ui: &mut Ui| {
-                ui.selectable_value(&mut state.combine_quality, 1, "低 (快)");
-                ui.selectable_value(&mut state.combine_quality, 2, "中");
-                ui.selectable_value(&mut state.combine_quality, 4, "高");
-                ui.selectable_value(&mut state.combine_quality, 8, "极高 (慢)");
-            });
+        state.quality.combo(ui, ("sandbox_quality", state.id));
+
+        ui.separator();
+
+        ui.checkbox(&mut state.snap_to_world, "吸附到世界")
+            .on_hover_text("把形状坐标钳制到世界范围内，椭圆半径缩到不越过世界边缘");
 
         ui.separator();
 
@@ -446,6 +638,66 @@ fn draw_toolbar(ui: &mut Ui, state: &mut ShapeSandboxState, world_size: (u32, u3
     });
 }
 
+/// 图形库菜单：把选中的形状按名称存入，或插入 / 删除已存的形状（改动立即写盘）
+fn draw_library_menu(ui: &mut Ui, state: &mut ShapeSandboxState, world_size: (u32, u32), library: &mut ShapeLibrary) {
+    let mut changed = false;
+    let selected = state.selected_shape.filter(|&i| i < state.shapes.len());
+    ui.add_enabled_ui(selected.is_some(), |ui| {
+        ui.horizontal(|ui| {
+            // 名称留空时沿用形状标签
+            let label = selected.map_or(String::new(), |i| state.shapes[i].label.clone());
+            ui.add(egui::TextEdit::singleline(&mut state.library_name)
+                .hint_text(label.as_str())
+                .desired_width(120.0));
+            let name = match state.library_name.trim() {
+                "" => label.trim().to_string(),
+                typed => typed.to_string(),
+            };
+            if ui.add_enabled(!name.is_empty(), egui::Button::new("💾 存入"))
+                .on_hover_text("把选中的形状按此名称存入图形库，同名条目会被覆盖")
+                .clicked()
+                && let Some(i) = selected
+            {
+                library.insert(&name, &state.shapes[i]);
+                state.library_name.clear();
+                changed = true;
+            }
+        });
+    });
+    ui.separator();
+
+    if library.shapes.is_empty() {
+        ui.weak("（空）选中形状后存入");
+    }
+    let mut to_remove = None;
+    for (i, entry) in library.shapes.iter().enumerate() {
+        let [r, g, b, _] = entry.color;
+        ui.horizontal(|ui| {
+            ui.colored_label(Color32::from_rgb(r, g, b), "■");
+            if ui.button(&entry.label).on_hover_text("插入到当前沙箱").clicked() {
+                let idx = state.shapes.len();
+                state.shapes.push(SandboxShape::from_library(entry, world_size.0, world_size.1));
+                state.selected_shape = Some(idx);
+                state.selected_combine = None;
+                ui.close_menu();
+            }
+            if ui.small_button("✕").on_hover_text("从图形库删除").clicked() {
+                to_remove = Some(i);
+            }
+        });
+    }
+    if let Some(i) = to_remove {
+        library.shapes.remove(i);
+        changed = true;
+    }
+
+    if changed
+        && let Err(e) = library.save()
+    {
+        log::warn(e);
+    }
+}
+
 // ═══════════════════════════════════════════════════════════
 // 形状列表
 // ═══════════════════════════════════════════════════════════
@@ -723,7 +975,7 @@ fn draw_sandbox_canvas(
                 continue;
             }
             let is_sel = state.selected_shape == Some(i);
-            draw_sandbox_shape(&painter, shape, is_sel, &w2c, scale);
+            draw_sandbox_shape(&painter, shape, is_sel, &w2c, scale, state.quality, world_size);
         }
     }
 
@@ -751,11 +1003,22 @@ fn draw_sandbox_canvas(
                 &w2c,
                 scale,
                 world_size,
-                state.combine_quality,
+                state.quality,
             );
         }
     }
 
+    // ── 图片模板 ──
+    if let Some(stencil) = &state.stencil {
+        draw_sampled_shape(&painter, stencil, Color32::from_rgba_unmultiplied(255, 255, 255, 110), &w2c, scale, state.quality);
+        let bb = stencil.bounds;
+        painter.rect_stroke(
+            EguiRect::from_min_max(w2c(bb.x_min as f32, bb.y_min as f32), w2c(bb.x_max as f32, bb.y_max as f32)),
+            0.0,
+            Stroke::new(1.0, Color32::from_rgb(230, 230, 230)),
+        );
+    }
+
     // 坐标提示
     if response.hovered() {
         if let Some(pos) = ui.input(|i| i.pointer.hover_pos()) {
@@ -801,6 +1064,8 @@ fn draw_sandbox_shape(
     is_selected: bool,
     w2c: &dyn Fn(f32, f32) -> Pos2,
     scale: f32,
+    quality: PreviewQuality,
+    world_size: (u32, u32),
 ) {
     let [r, g, b, a] = shape.color;
     let fill_alpha = if is_selected { (a as u16 + 50).min(200) as u8 } else { a };
@@ -823,8 +1088,7 @@ fn draw_sandbox_shape(
         ShapeKind::Ellipse => {
             let center = w2c(shape.ell_cx as f32, shape.ell_cy as f32);
             let radius = Vec2::new(shape.ell_rx as f32 * scale, shape.ell_ry as f32 * scale);
-            painter.add(egui::Shape::ellipse_filled(center, radius, fill));
-            painter.add(egui::Shape::ellipse_stroke(center, radius, Stroke::new(sw, stroke_c)));
+            quality.draw_ellipse(painter, center, radius, fill, Stroke::new(sw, stroke_c));
         }
         ShapeKind::Trapezoid => {
             let p0 = w2c(shape.trap_top_x0 as f32, shape.trap_y_top as f32);
@@ -844,10 +1108,29 @@ fn draw_sandbox_shape(
             painter.rect_filled(rect, 0.0, fill);
             painter.rect_stroke(rect, 0.0, Stroke::new(sw, stroke_c));
         }
+        ShapeKind::Implicit => {
+            draw_sampled_shape(painter, shape, fill, w2c, scale, quality);
+            let bb = shape.bounding_box();
+            let rect = EguiRect::from_min_max(
+                w2c(bb.x_min as f32, bb.y_min as f32),
+                w2c(bb.x_max as f32, bb.y_max as f32),
+            );
+            painter.rect_stroke(rect, 0.0, Stroke::new(sw * 0.5, stroke_c));
+        }
     }
 
-    // 标签
+    // 超出世界范围：红色包围盒 + 警告标记
     let bb = shape.bounding_box();
+    let exceeds = shape.exceeds_world(world_size.0, world_size.1);
+    if exceeds {
+        let rect = EguiRect::from_min_max(
+            w2c(bb.x_min as f32, bb.y_min as f32),
+            w2c(bb.x_max as f32, bb.y_max as f32),
+        );
+        painter.rect_stroke(rect, 0.0, Stroke::new(1.5, OUT_OF_WORLD_COLOR));
+    }
+
+    // 标签
     let label_pos = w2c(
         (bb.x_min + bb.x_max) as f32 / 2.0,
         bb.y_min as f32,
@@ -855,9 +1138,9 @@ fn draw_sandbox_shape(
     painter.text(
         Pos2::new(label_pos.x, label_pos.y - 6.0),
         egui::Align2::CENTER_BOTTOM,
-        &shape.label,
+        if exceeds { format!("⚠ {}", shape.label) } else { shape.label.clone() },
         egui::FontId::proportional(10.0),
-        Color32::from_rgba_unmultiplied(r, g, b, 220),
+        if exceeds { OUT_OF_WORLD_COLOR } else { Color32::from_rgba_unmultiplied(r, g, b, 220) },
     );
 }
 
@@ -870,6 +1153,7 @@ fn draw_shape_with_color(
     sw: f32,
     w2c: &dyn Fn(f32, f32) -> Pos2,
     scale: f32,
+    quality: PreviewQuality,
     show_label: bool,
 ) {
     match shape.kind {
@@ -883,8 +1167,7 @@ fn draw_shape_with_color(
         ShapeKind::Ellipse => {
             let center = w2c(shape.ell_cx as f32, shape.ell_cy as f32);
             let radius = Vec2::new(shape.ell_rx as f32 * scale, shape.ell_ry as f32 * scale);
-            painter.add(egui::Shape::ellipse_filled(center, radius, fill));
-            painter.add(egui::Shape::ellipse_stroke(center, radius, Stroke::new(sw, stroke_c)));
+            quality.draw_ellipse(painter, center, radius, fill, Stroke::new(sw, stroke_c));
         }
         ShapeKind::Trapezoid => {
             let p0 = w2c(shape.trap_top_x0 as f32, shape.trap_y_top as f32);
@@ -904,6 +1187,15 @@ fn draw_shape_with_color(
             painter.rect_filled(rect, 0.0, fill);
             painter.rect_stroke(rect, 0.0, Stroke::new(sw, stroke_c));
         }
+        ShapeKind::Implicit => {
+            draw_sampled_shape(painter, shape, fill, w2c, scale, quality);
+            let bb = shape.bounding_box();
+            let rect = EguiRect::from_min_max(
+                w2c(bb.x_min as f32, bb.y_min as f32),
+                w2c(bb.x_max as f32, bb.y_max as f32),
+            );
+            painter.rect_stroke(rect, 0.0, Stroke::new(sw * 0.5, stroke_c));
+        }
     }
 
     if show_label {
@@ -922,6 +1214,38 @@ fn draw_shape_with_color(
     }
 }
 
+/// 按采样点绘制形状（没有矢量轮廓的隐函数）。只采样画布可见范围，
+/// 步长保证每个采样点在屏幕上约 [`PreviewQuality::sample_px`]，放大时也不会逐格遍历整个世界。
+fn draw_sampled_shape(
+    painter: &egui::Painter,
+    shape: &dyn Shape,
+    fill: Color32,
+    w2c: &dyn Fn(f32, f32) -> Pos2,
+    scale: f32,
+    quality: PreviewQuality,
+) {
+    let bb = shape.bounding_box();
+    if bb.is_empty() || scale <= 0.0 {
+        return;
+    }
+    let origin = w2c(0.0, 0.0);
+    let clip = painter.clip_rect();
+    let x0 = bb.x_min.max(((clip.left() - origin.x) / scale).floor() as i32);
+    let y0 = bb.y_min.max(((clip.top() - origin.y) / scale).floor() as i32);
+    let x1 = bb.x_max.min(((clip.right() - origin.x) / scale).ceil() as i32);
+    let y1 = bb.y_max.min(((clip.bottom() - origin.y) / scale).ceil() as i32);
+
+    let step = (quality.sample_px() / scale).ceil().max(1.0) as i32;
+    let dot = Vec2::splat(scale * step as f32);
+    for y in (y0..y1).step_by(step as usize) {
+        for x in (x0..x1).step_by(step as usize) {
+            if shape.contains(x, y) {
+                painter.rect_filled(EguiRect::from_min_size(w2c(x as f32, y as f32), dot), 0.0, fill);
+            }
+        }
+    }
+}
+
 /// 绘制组合运算结果（通过像素采样实现精确预览）
 /// 绘制组合运算结果
 /// - Union: 直接绘制两个形状（矢量，性能最优）
@@ -935,7 +1259,7 @@ fn draw_combine_result(
     w2c: &dyn Fn(f32, f32) -> Pos2,
     scale: f32,
     world_size: (u32, u32),
-    quality: i32,
+    quality: PreviewQuality,
 ) {
     let [r, g, b, a] = comb.color;
     let fill_alpha = if is_selected { (a as u16 + 50).min(200) as u8 } else { a };
@@ -952,9 +1276,9 @@ fn draw_combine_result(
         let sw = if is_selected { 2.5 } else { 1.0 };
         
         // 绘制左形状
-        draw_shape_with_color(painter, left, fill, stroke_c, sw, w2c, scale, false);
+        draw_shape_with_color(painter, left, fill, stroke_c, sw, w2c, scale, quality, false);
         // 绘制右形状
-        draw_shape_with_color(painter, right, fill, stroke_c, sw, w2c, scale, false);
+        draw_shape_with_color(painter, right, fill, stroke_c, sw, w2c, scale, quality, false);
         
         // 只在选中时显示 bbox 边框
         if is_selected {
@@ -989,13 +1313,7 @@ fn draw_combine_result(
     let y1 = bb.y_max.min(world_size.1 as i32);
 
     // 根据 quality 调整基础采样步长
-    // quality: 1=低(step=8), 2=中(step=4), 4=高(step=2), 8=极高(step=1)
-    let base_step = match quality {
-        1 => 8,
-        2 => 4,
-        4 => 2,
-        _ => 1,
-    };
+    let base_step = quality.combine_step();
 
     // 自适应：缩小时进一步增大步长
     let pixel_size = scale;
@@ -1050,12 +1368,20 @@ fn draw_combine_result(
 // 详细面板（参数编辑 + 代码）
 // ═══════════════════════════════════════════════════════════
 
-fn draw_detail_panel(ui: &mut Ui, state: &mut ShapeSandboxState) {
+fn draw_detail_panel(ui: &mut Ui, state: &mut ShapeSandboxState, world_size: (u32, u32)) {
     let sid = state.id;
     // 如果选中了基础形状，编辑其参数
     if let Some(idx) = state.selected_shape {
         if idx < state.shapes.len() {
             draw_shape_editor(ui, &mut state.shapes[idx], sid);
+            if state.shapes[idx].exceeds_world(world_size.0, world_size.1) {
+                ui.horizontal(|ui| {
+                    ui.colored_label(OUT_OF_WORLD_COLOR, format!("⚠ 超出世界范围 {}×{}", world_size.0, world_size.1));
+                    if ui.small_button("收进世界").on_hover_text("把这个形状的坐标钳制到世界范围内").clicked() {
+                        state.shapes[idx].snap_to_world(world_size.0, world_size.1);
+                    }
+                });
+            }
             return;
         }
     }
@@ -1071,6 +1397,165 @@ fn draw_detail_panel(ui: &mut Ui, state: &mut ShapeSandboxState) {
     ui.weak("选择一个形状或组合查看详情");
 }
 
+/// 「应用到世界」：选择环境后把选中的形状 / 组合填充到当前环境地图
+fn draw_apply_bar(
+    ui: &mut Ui,
+    state: &mut ShapeSandboxState,
+    biomes: &[BiomeDefinition],
+) -> Option<SandboxApply> {
+    let selected = selected_apply_shape(state);
+    let mut apply = None;
+
+    ui.horizontal(|ui| {
+        ui.label("环境:");
+        let current = state.apply_biome
+            .and_then(|id| biomes.iter().find(|b| b.id == id))
+            .map_or("选择环境", |b| b.name.as_str());
+        egui::ComboBox::from_id_source(("sandbox_apply_biome", state.id))
+            .selected_text(current)
+            .width(90.0)
+            .show_ui(ui, |ui: &mut Ui| {
+                for b in biomes {
+                    let [r, g, bl, _] = b.overlay_color;
+                    let text = egui::RichText::new(&b.name).color(Color32::from_rgb(r, g, bl));
+                    ui.selectable_value(&mut state.apply_biome, Some(b.id), text);
+                }
+            });
+
+        ui.checkbox(&mut state.apply_only_unassigned, "仅覆盖空白")
+            .on_hover_text("只填充尚未分配环境的格子，已有环境保持不变");
+        ui.label("描边:");
+        ui.add(egui::DragValue::new(&mut state.apply_outline).clamp_range(0..=32).suffix(" 格"))
+            .on_hover_text("只填充形状内侧这一宽度的轮廓（如环境过渡带、洞穴墙壁）；0 = 整体填充");
+
+        let ready = selected.is_some() && state.apply_biome.is_some();
+        let button = ui.add_enabled(ready, egui::Button::new("⤓ 应用到世界"))
+            .on_hover_text("以所选环境填充选中的形状或组合（重新执行生成步骤会覆盖）")
+            .on_disabled_hover_text("先选中一个形状或组合，并选择环境");
+        if button.clicked()
+            && let (Some((shape, label)), Some(biome)) = (selected, state.apply_biome)
+        {
+            apply = Some(SandboxApply {
+                shape,
+                label,
+                biome,
+                only_unassigned: state.apply_only_unassigned,
+            outline: state.apply_outline,
+            });
+        }
+    });
+
+    apply
+}
+
+/// 图片模板：从剪贴板粘贴图片，调整放置范围与亮度阈值后以「应用到世界」栏选择的环境填充
+fn draw_stencil_bar(
+    ui: &mut Ui,
+    state: &mut ShapeSandboxState,
+    world_size: (u32, u32),
+) -> Option<SandboxApply> {
+    let mut apply = None;
+
+    ui.horizontal(|ui| {
+        ui.label("图片模板:");
+        if ui.button("📋 粘贴图片")
+            .on_hover_text("读取剪贴板中的图片，居中放到世界上；暗色像素覆盖的格子会被填充")
+            .clicked()
+        {
+            match paste_stencil(world_size) {
+                Ok(stencil) => {
+                    state.stencil = Some(stencil);
+                    state.stencil_error = None;
+                }
+                Err(e) => {
+                    log::warn(format!("粘贴图片模板失败: {e}"));
+                    state.stencil_error = Some(e);
+                }
+            }
+        }
+        if let Some(stencil) = &state.stencil {
+            ui.weak(format!("{}×{}", stencil.width, stencil.height));
+            if ui.small_button("✕").on_hover_text("移除模板").clicked() {
+                state.stencil = None;
+            }
+        } else if let Some(e) = &state.stencil_error {
+            ui.colored_label(Color32::from_rgb(230, 110, 110), e);
+        }
+    });
+
+    let Some(stencil) = &mut state.stencil else {
+        return None;
+    };
+    ui.horizontal(|ui| {
+        ui.label("阈值:");
+        ui.add(egui::Slider::new(&mut stencil.threshold, 1..=255))
+            .on_hover_text("亮度低于此值的像素参与填充（0 = 黑，255 = 白）；透明像素始终跳过");
+    });
+    let bb = &mut stencil.bounds;
+    ui.horizontal(|ui| {
+        ui.label("范围 x:");
+        ui.add(egui::DragValue::new(&mut bb.x_min).speed(1));
+        ui.label("~");
+        ui.add(egui::DragValue::new(&mut bb.x_max).speed(1));
+        ui.label("y:");
+        ui.add(egui::DragValue::new(&mut bb.y_min).speed(1));
+        ui.label("~");
+        ui.add(egui::DragValue::new(&mut bb.y_max).speed(1));
+    });
+    bb.x_max = bb.x_max.max(bb.x_min + 1);
+    bb.y_max = bb.y_max.max(bb.y_min + 1);
+
+    let ready = state.apply_biome.is_some();
+    let button = ui.add_enabled(ready, egui::Button::new("⤓ 按模板应用"))
+        .on_hover_text("以上方所选环境填充模板中的暗色像素（「仅覆盖空白」与描边同样生效）")
+        .on_disabled_hover_text("先在上方选择环境");
+    if button.clicked()
+        && let Some(biome) = state.apply_biome
+    {
+        apply = Some(SandboxApply {
+            label: format!("图片模板 {}×{}", stencil.width, stencil.height),
+            shape: Box::new(stencil.clone()),
+            biome,
+            only_unassigned: state.apply_only_unassigned,
+            outline: state.apply_outline,
+        });
+    }
+    apply
+}
+
+/// 读取剪贴板图片并创建模板：保持宽高比，缩放到不超过世界一半宽高，居中放置
+fn paste_stencil(world_size: (u32, u32)) -> Result<Stencil, String> {
+    let mut clipboard = arboard::Clipboard::new().map_err(|e| format!("无法访问剪贴板: {e}"))?;
+    let image = clipboard.get_image().map_err(|e| format!("剪贴板中没有图片 ({e})"))?;
+    if image.width == 0 || image.height == 0 {
+        return Err("剪贴板图片为空".to_string());
+    }
+    let (ww, wh) = (world_size.0 as f64, world_size.1 as f64);
+    let fit = (ww / 2.0 / image.width as f64).min(wh / 2.0 / image.height as f64);
+    let half_w = ((image.width as f64 * fit / 2.0) as i32).max(1);
+    let half_h = ((image.height as f64 * fit / 2.0) as i32).max(1);
+    let (cx, cy) = (world_size.0 as i32 / 2, world_size.1 as i32 / 2);
+    let bounds = BoundingBox::new(cx - half_w, cy - half_h, cx + half_w, cy + half_h);
+    Ok(Stencil::from_rgba(image.width, image.height, &image.bytes, 128, bounds))
+}
+
+/// 当前选中项对应的几何形状（组合按集合运算构造）
+fn selected_apply_shape(state: &ShapeSandboxState) -> Option<(Box<dyn Shape>, String)> {
+    if let Some(shape) = state.selected_shape.and_then(|i| state.shapes.get(i)) {
+        return Some((Box::new(shape.clone()), shape.label.clone()));
+    }
+    let comb = state.selected_combine.and_then(|i| state.combines.get(i))?;
+    let left = state.shapes.get(comb.left)?.clone();
+    let right = state.shapes.get(comb.right)?.clone();
+    let label = format!("{} {} {}", left.label, comb.op.symbol(), right.label);
+    let shape: Box<dyn Shape> = match comb.op {
+        SetOp::Union => Box::new(left.union(right)),
+        SetOp::Intersect => Box::new(left.intersect(right)),
+        SetOp::Subtract => Box::new(left.subtract(right)),
+    };
+    Some((shape, label))
+}
+
 fn draw_shape_editor(ui: &mut Ui, shape: &mut SandboxShape, sandbox_id: usize) {
     ui.strong(format!("✏ {} — {}", shape.label, shape.kind.display_name()));
     ui.add_space(4.0);
@@ -1152,6 +1637,7 @@ fn draw_shape_editor(ui: &mut Ui, shape: &mut SandboxShape, sandbox_id: usize) {
                 ui.end_row();
             });
         }
+        ShapeKind::Implicit => draw_implicit_editor(ui, shape, sandbox_id),
     }
 
     ui.add_space(4.0);
@@ -1174,6 +1660,44 @@ fn draw_shape_editor(ui: &mut Ui, shape: &mut SandboxShape, sandbox_id: usize) {
     });
 }
 
+/// 隐函数编辑：表达式文本 + 原点 / r 参数 + 示例
+fn draw_implicit_editor(ui: &mut Ui, shape: &mut SandboxShape, sandbox_id: usize) {
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        ui.label("f(x, y):");
+        changed |= ui.add(egui::TextEdit::singleline(&mut shape.impl_source).code_editor().desired_width(220.0))
+            .on_hover_text("x, y 为相对原点的坐标，r 为下方参数，w, h 为世界宽高；比较运算为真的格属于形状。\n\
+                支持 + - * / % ^、比较、&& || !、abs sqrt sin cos tan floor ceil round exp ln min max atan2、pi e")
+            .changed();
+    });
+    egui::Grid::new(("implicit_editor", sandbox_id)).num_columns(2).spacing([8.0, 4.0]).show(ui, |ui| {
+        ui.label("原点 x:");
+        changed |= ui.add(egui::DragValue::new(&mut shape.impl_cx).speed(1.0)).changed();
+        ui.end_row();
+        ui.label("原点 y:");
+        changed |= ui.add(egui::DragValue::new(&mut shape.impl_cy).speed(1.0)).changed();
+        ui.end_row();
+        ui.label("r:");
+        changed |= ui.add(egui::DragValue::new(&mut shape.impl_r).speed(1.0)).changed();
+        ui.end_row();
+    });
+    ui.horizontal_wrapped(|ui| {
+        ui.label("示例:");
+        for (name, source) in IMPLICIT_EXAMPLES {
+            if ui.small_button(name).on_hover_text(source).clicked() {
+                shape.impl_source = source.to_string();
+                changed = true;
+            }
+        }
+    });
+    if changed {
+        shape.refresh_implicit();
+    }
+    if let Err(e) = &shape.implicit {
+        ui.colored_label(Color32::from_rgb(230, 110, 110), format!("⚠ {e}"));
+    }
+}
+
 fn draw_combine_detail(ui: &mut Ui, comb: &CombineNode, shapes: &[SandboxShape], sandbox_id: usize) {
     let left_label = shapes.get(comb.left).map(|s| s.label.as_str()).unwrap_or("?");
     let right_label = shapes.get(comb.right).map(|s| s.label.as_str()).unwrap_or("?");
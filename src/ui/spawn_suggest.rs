@@ -0,0 +1,290 @@
+//! # 出生点建议
+//!
+//! 分析视图：在生成完成的环境地图上为玩家挑选若干候选出生 / 建家位置，并在画布上标出。
+//! 每个地表列按三项打分：
+//! - **安全**：与最近的危险环境（猩红 / 腐化 / 地狱，在地表层顶到地下层底之间扫描）的水平距离
+//! - **便利**：是否位于森林，或离最近的森林列有多远
+//! - **平坦**：两侧一段范围内地表环境保持不变的比例（环境地图不含地形高度，以地表环境连续度近似）
+//!
+//! 候选位置按综合分加权随机抽取（分数越高越可能被选中），彼此保持最小间距，再按分数排序；
+//! 「换一批」换抽样盐，得到另一组同样偏向高分的位置。
+
+use egui::{Align2, Color32, Context, FontId, Painter, Pos2, Rect, Stroke};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::core::biome::{BiomeDefinition, BiomeId, BiomeMap};
+use crate::core::layer::LayerDefinition;
+use crate::rendering::viewport::ViewportState;
+use crate::ui::theme;
+
+/// 视为危险的环境
+const DANGER_KEYS: [&str; 3] = ["crimson", "corruption", "hell"];
+/// 不能作为出生点的环境
+const EXCLUDED_KEYS: [&str; 2] = ["ocean", "space"];
+/// 安全分达到满分的距离（相对世界宽度）
+const SAFE_DISTANCE: f32 = 0.10;
+/// 便利分降到 0 的森林距离（相对世界宽度）
+const FOREST_REACH: f32 = 0.05;
+/// 平坦度的取样半宽（相对世界宽度）
+const FLAT_RADIUS: f32 = 0.01;
+/// 候选之间的最小间距（相对世界宽度）
+const MIN_SPACING: f32 = 0.04;
+/// 加权抽样时对综合分取的幂：越大越偏向高分
+const WEIGHT_SHARPNESS: i32 = 4;
+
+/// 一个候选出生点
+#[derive(Debug, Clone)]
+pub struct SpawnCandidate {
+    pub x: u32,
+    pub y: u32,
+    /// 地表环境
+    pub biome: BiomeId,
+    /// 综合分（0~1）
+    pub score: f32,
+    pub safety: f32,
+    pub convenience: f32,
+    pub flatness: f32,
+}
+
+/// 一次计算的结果
+pub struct SpawnSuggestions {
+    pub candidates: Vec<SpawnCandidate>,
+    /// 计算时已执行的子步骤数（用于提示结果是否过期）
+    pub executed: usize,
+}
+
+/// 出生点建议窗口的持久状态
+pub struct SpawnSuggestState {
+    pub count: usize,
+    /// 抽样盐，「换一批」时递增
+    pub salt: u64,
+    pub result: Option<SpawnSuggestions>,
+}
+
+impl Default for SpawnSuggestState {
+    fn default() -> Self {
+        Self { count: 5, salt: 0, result: None }
+    }
+}
+
+/// 窗口中的用户请求
+pub enum SpawnSuggestRequest {
+    /// 按当前环境地图重新计算
+    Refresh,
+    /// 把视口移到该世界坐标
+    Locate(u32, u32),
+}
+
+/// 每个元素到最近的 `true` 元素的距离（格）；没有 `true` 时为 `f32::INFINITY`
+fn distance_to_nearest(flags: &[bool]) -> Vec<f32> {
+    let mut dist = vec![f32::INFINITY; flags.len()];
+    let mut last = None;
+    for (x, &flag) in flags.iter().enumerate() {
+        if flag {
+            last = Some(x);
+        }
+        if let Some(l) = last {
+            dist[x] = (x - l) as f32;
+        }
+    }
+    last = None;
+    for (x, &flag) in flags.iter().enumerate().rev() {
+        if flag {
+            last = Some(x);
+        }
+        if let Some(l) = last {
+            dist[x] = dist[x].min((l - x) as f32);
+        }
+    }
+    dist
+}
+
+/// 在环境地图上为 `count` 个候选出生点打分并加权抽样，按综合分降序返回。
+///
+/// `seed` 决定抽样结果（同一种子与盐得到同样的候选）。
+pub fn suggest_spawns(
+    bm: &BiomeMap,
+    biomes: &[BiomeDefinition],
+    layers: &[LayerDefinition],
+    count: usize,
+    seed: u64,
+) -> Vec<SpawnCandidate> {
+    let (w, h) = (bm.width as usize, bm.height);
+    if w == 0 || h == 0 {
+        return Vec::new();
+    }
+    let ids = |keys: &[&str]| -> Vec<BiomeId> {
+        biomes.iter().filter(|b| keys.contains(&b.key.as_str())).map(|b| b.id).collect()
+    };
+    let danger = ids(&DANGER_KEYS);
+    let excluded = ids(&EXCLUDED_KEYS);
+    let forest = ids(&["forest"]);
+    let layer_rows = |key: &str| layers.iter().find(|l| l.key == key).map(|l| l.bounds_for_height(h));
+
+    // 地表环境在地表层中线取样；危险环境在地表层顶到地下层底之间扫描
+    let (surface_top, surface_bottom) = layer_rows("surface").unwrap_or((h / 10, h * 3 / 10));
+    let scan_bottom = layer_rows("underground").map_or(surface_bottom, |(_, end)| end).min(h);
+    let sample_y = (surface_top + surface_bottom) / 2;
+
+    let surface: Vec<BiomeId> = (0..bm.width).map(|x| bm.get(x, sample_y)).collect();
+    let danger_cols: Vec<bool> = (0..bm.width)
+        .map(|x| (surface_top..scan_bottom).any(|y| danger.contains(&bm.get(x, y))))
+        .collect();
+    let forest_cols: Vec<bool> = surface.iter().map(|b| forest.contains(b)).collect();
+    let danger_dist = distance_to_nearest(&danger_cols);
+    let forest_dist = distance_to_nearest(&forest_cols);
+
+    let wf = w as f32;
+    let flat_radius = ((wf * FLAT_RADIUS) as usize).max(1);
+    let mut scored: Vec<SpawnCandidate> = (0..w)
+        .filter(|&x| !excluded.contains(&surface[x]) && !danger_cols[x])
+        .map(|x| {
+            let safety = (danger_dist[x] / (wf * SAFE_DISTANCE)).min(1.0);
+            let convenience = 1.0 - (forest_dist[x] / (wf * FOREST_REACH)).min(1.0);
+            let window = &surface[x.saturating_sub(flat_radius)..(x + flat_radius + 1).min(w)];
+            let flatness = window.iter().filter(|&&b| b == surface[x]).count() as f32 / window.len() as f32;
+            SpawnCandidate {
+                x: x as u32,
+                y: sample_y,
+                biome: surface[x],
+                score: 0.5 * safety + 0.3 * convenience + 0.2 * flatness,
+                safety,
+                convenience,
+                flatness,
+            }
+        })
+        .collect();
+
+    // 加权抽样：每次按 score^k 抽一列，随后剔除间距内的列
+    let spacing = (wf * MIN_SPACING) as u32;
+    let mut rng = StdRng::seed_from_u64(seed ^ 0x5BA_0000);
+    let mut chosen = Vec::with_capacity(count);
+    while chosen.len() < count {
+        let total: f32 = scored.iter().map(|c| c.score.powi(WEIGHT_SHARPNESS)).sum();
+        if total <= 0.0 {
+            break;
+        }
+        let mut pick = rng.gen_range(0.0..total);
+        let index = scored
+            .iter()
+            .position(|c| {
+                pick -= c.score.powi(WEIGHT_SHARPNESS);
+                pick < 0.0
+            })
+            .unwrap_or(scored.len() - 1);
+        let candidate = scored.swap_remove(index);
+        scored.retain(|c| c.x.abs_diff(candidate.x) >= spacing);
+        chosen.push(candidate);
+    }
+    chosen.sort_by(|a, b| b.score.total_cmp(&a.score));
+    chosen
+}
+
+fn biome_name(biomes: &[BiomeDefinition], id: BiomeId) -> &str {
+    biomes.iter().find(|b| b.id == id).map_or("未知环境", |b| b.name.as_str())
+}
+
+/// 显示出生点建议窗口。
+pub fn show_spawn_suggest_window(
+    ctx: &Context,
+    open: &mut bool,
+    state: &mut SpawnSuggestState,
+    biomes: &[BiomeDefinition],
+    executed: usize,
+) -> Option<SpawnSuggestRequest> {
+    let mut request = None;
+
+    egui::Window::new("📍 出生点建议")
+        .open(open)
+        .resizable(true)
+        .default_width(380.0)
+        .show(ctx, |ui| {
+            ui.label("按安全（远离猩红 / 腐化 / 地狱）、便利（靠近森林）与地表平坦度打分，加权随机挑选候选位置。");
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                ui.label("数量");
+                if ui.add(egui::DragValue::new(&mut state.count).clamp_range(1..=10)).changed() {
+                    request = Some(SpawnSuggestRequest::Refresh);
+                }
+                if ui.button("↻ 重新计算").clicked() {
+                    request = Some(SpawnSuggestRequest::Refresh);
+                }
+                if ui.button("🎲 换一批").on_hover_text("换一组同样偏向高分的候选").clicked() {
+                    state.salt = state.salt.wrapping_add(1);
+                    request = Some(SpawnSuggestRequest::Refresh);
+                }
+            });
+
+            let Some(result) = &state.result else {
+                ui.label("尚未生成环境地图。");
+                return;
+            };
+            if result.executed != executed {
+                ui.colored_label(Color32::from_rgb(230, 190, 90), "世界已变化，建议可能过期");
+            }
+            if result.candidates.is_empty() {
+                ui.label("没有合适的位置。");
+                return;
+            }
+
+            ui.add_space(4.0);
+            egui::Grid::new("spawn_suggest_candidates")
+                .num_columns(6)
+                .striped(true)
+                .spacing([10.0, 4.0])
+                .show(ui, |ui| {
+                    ui.strong("#");
+                    ui.strong("位置");
+                    ui.strong("环境");
+                    ui.strong("综合");
+                    ui.strong("安全 / 便利 / 平坦");
+                    ui.label("");
+                    ui.end_row();
+                    for (i, c) in result.candidates.iter().enumerate() {
+                        ui.monospace(format!("{}", i + 1));
+                        ui.monospace(format!("({}, {})", c.x, c.y));
+                        ui.label(biome_name(biomes, c.biome));
+                        ui.monospace(format!("{:.2}", c.score));
+                        ui.colored_label(
+                            theme::TEXT_SECONDARY,
+                            format!("{:.2} / {:.2} / {:.2}", c.safety, c.convenience, c.flatness),
+                        );
+                        if ui.small_button("定位").clicked() {
+                            request = Some(SpawnSuggestRequest::Locate(c.x, c.y));
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+
+    request
+}
+
+/// 在画布上为候选位置画编号标记。`canvas_rect` 为画布所在区域（与 `show_canvas` 分配的区域相同）。
+pub fn draw_spawn_markers(
+    painter: &Painter,
+    canvas_rect: Rect,
+    viewport: &ViewportState,
+    world_size: (u32, u32),
+    candidates: &[SpawnCandidate],
+) {
+    let zoom = viewport.zoom;
+    let image_rect = viewport.image_rect(canvas_rect, world_size);
+    let painter = painter.with_clip_rect(canvas_rect);
+
+    // 从低分到高分绘制，重叠时第一名在最上层
+    for (i, c) in candidates.iter().enumerate().rev() {
+        let pos = Pos2::new(
+            image_rect.left() + (c.x as f32 + 0.5) * zoom,
+            image_rect.top() + (c.y as f32 + 0.5) * zoom,
+        );
+        if !canvas_rect.expand(12.0).contains(pos) {
+            continue;
+        }
+        let fill = if i == 0 { theme::PINK } else { theme::BLUE };
+        painter.circle(pos, 9.0, fill, Stroke::new(2.0, Color32::from_black_alpha(200)));
+        painter.text(pos, Align2::CENTER_CENTER, format!("{}", i + 1), FontId::monospace(11.0), theme::BG_DARK);
+    }
+}
@@ -1,30 +1,132 @@
-use egui::Ui;
+use egui::{AboveOrBelow, Color32, Rect, Sense, Ui, Vec2};
+
+use crate::ui::i18n::{tr, tr_args};
+
+/// 状态栏显示的内容（由 app 每帧汇总一次）
+pub struct StatusInfo<'a> {
+    pub fps: f32,
+    pub memory_hint_mb: usize,
+    pub message: &'a str,
+    /// 鼠标悬停处的坐标与环境，空串时不显示
+    pub hover: &'a str,
+    /// 悬停处相对出生点的偏移，空串时不显示
+    pub spawn_offset: &'a str,
+    /// 画笔模式下的当前画笔环境 `(颜色, 名称)`
+    pub paint_biome: Option<(Color32, &'a str)>,
+    pub seed: u64,
+    pub step_progress: &'a str,
+    pub world_size_label: &'a str,
+    /// 视口内环境占比 `(颜色, 名称, 占比)`
+    pub viewport_biomes: &'a [(Color32, &'a str, f32)],
+    /// 环境地图短哈希，空串时不显示
+    pub world_hash: &'a str,
+    /// `(环境边界长度, 总格数)`
+    pub world_boundary: Option<(u64, u64)>,
+}
+
+/// 底部状态栏；`repro_info` 仅在弹出复现信息时调用
+pub fn show_status_bar(ui: &mut Ui, info: &StatusInfo, repro_info: impl FnOnce() -> String) {
+    let &StatusInfo {
+        fps,
+        memory_hint_mb,
+        message,
+        hover,
+        spawn_offset,
+        paint_biome,
+        seed,
+        step_progress,
+        world_size_label,
+        viewport_biomes,
+        world_hash,
+        world_boundary,
+    } = info;
 
-pub fn show_status_bar(
-    ui: &mut Ui,
-    fps: f32,
-    memory_hint_mb: usize,
-    message: &str,
-    hover: &str,
-    seed: u64,
-    step_progress: &str,
-    world_size_label: &str,
-) {
     ui.horizontal_wrapped(|ui| {
-        ui.label(format!("状态: {message}"));
+        ui.label(tr_args("status.state", &[&message]));
         if !hover.is_empty() {
             ui.separator();
             ui.label(hover);
         }
+        if !spawn_offset.is_empty() {
+            ui.separator();
+            ui.label(spawn_offset);
+        }
+        if let Some((color, name)) = paint_biome {
+            ui.separator();
+            ui.colored_label(color, "■");
+            ui.label(tr_args("status.paint_biome", &[&name]))
+                .on_hover_text(tr("status.paint_biome.hint"));
+        }
+        if !viewport_biomes.is_empty() {
+            ui.separator();
+            show_viewport_biome_bar(ui, viewport_biomes);
+        }
         ui.separator();
         ui.label(step_progress);
         ui.separator();
         ui.label(world_size_label);
+        if let Some((length, tiles)) = world_boundary {
+            ui.separator();
+            let density = length as f64 * 1000.0 / tiles.max(1) as f64;
+            ui.label(tr_args("status.boundary", &[&length]))
+                .on_hover_text(tr_args("status.boundary.hint", &[&format!("{density:.2}")]));
+        }
+        if !world_hash.is_empty() {
+            ui.separator();
+            let hash_resp = ui
+                .add(egui::Label::new(format!("# {world_hash}")).sense(Sense::click()))
+                .on_hover_text(tr("status.world_hash.hint"));
+            if hash_resp.clicked() {
+                ui.output_mut(|o| o.copied_text = world_hash.to_string());
+            }
+        }
         ui.separator();
-        ui.label(format!("Seed: {:016X}", seed));
+        // 点击种子弹出复现信息（仅在弹出时才收集参数）
+        let seed_resp = ui
+            .add(egui::Label::new(format!("Seed: {:016X}", seed)).sense(Sense::click()))
+            .on_hover_text(tr("status.repro.hint"));
+        let popup_id = ui.make_persistent_id("repro_info_popup");
+        if seed_resp.clicked() {
+            ui.memory_mut(|m| m.toggle_popup(popup_id));
+        }
+        egui::popup_above_or_below_widget(ui, popup_id, &seed_resp, AboveOrBelow::Above, |ui| {
+            ui.set_min_width(280.0);
+            ui.strong(tr("status.repro.title"));
+            let text = repro_info();
+            ui.monospace(&text);
+            if ui.button(tr("status.repro.copy")).clicked() {
+                ui.output_mut(|o| o.copied_text = text);
+            }
+        });
         ui.separator();
         ui.label(format!("FPS: {:.0}", fps));
         ui.separator();
-        ui.label(format!("内存: ~{}MB", memory_hint_mb));
+        ui.label(tr_args("status.memory", &[&memory_hint_mb]));
+    });
+}
+
+/// 视口内环境占比：按比例依次排开的彩色条，悬停显示各环境的百分比
+fn show_viewport_biome_bar(ui: &mut Ui, biomes: &[(Color32, &str, f32)]) {
+    let (rect, response) = ui.allocate_exact_size(Vec2::new(120.0, 10.0), Sense::hover());
+    let painter = ui.painter();
+    painter.rect_filled(rect, 2.0, Color32::from_gray(40));
+    let mut x = rect.left();
+    for &(color, _, fraction) in biomes {
+        let width = rect.width() * fraction;
+        painter.rect_filled(
+            Rect::from_min_size(egui::pos2(x, rect.top()), Vec2::new(width, rect.height())),
+            0.0,
+            color,
+        );
+        x += width;
+    }
+    response.on_hover_ui(|ui| {
+        ui.strong(tr("status.viewport_biomes"));
+        for &(color, name, fraction) in biomes {
+            ui.horizontal(|ui| {
+                ui.colored_label(color, "■");
+                ui.label(format!("{name} {:.1}%", fraction * 100.0));
+            });
+        }
     });
 }
@@ -0,0 +1,186 @@
+//! # 步骤开关预览
+//!
+//! 悬停步骤列表中子步骤的启用复选框时，在后台以低分辨率无界面生成两次世界
+//! （[`generate_variant`]）：一次按当前的启用集合，一次把该步骤的开关取反。
+//! 弹窗显示后者，并调暗与前者相同的格子，一眼看出这个步骤贡献了什么。
+//!
+//! 悬停满 [`HOVER_DELAY`] 才开始生成，移开后正在进行的任务照常完成并写入缓存。
+//! 结果按步骤缓存；种子、参数、层级、启用集合等任一变化后缓存整体失效。
+
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use egui::{Color32, Context, TextureHandle, Ui, Vec2};
+
+use crate::core::biome::{BiomeDefinition, BiomeMap};
+use crate::generation::variant::{diff_ratio, generate_variant, VariantSettings};
+use crate::ui::ab_compare::side_texture;
+use crate::ui::i18n::{tr, tr_args};
+
+/// 悬停多久后开始生成
+const HOVER_DELAY: Duration = Duration::from_millis(350);
+/// 预览世界的最大宽度（格）
+const PREVIEW_WIDTH: u32 = 700;
+/// 弹窗中缩略图的显示宽度（像素）
+const POPUP_WIDTH: f32 = 320.0;
+
+/// 工作线程的结果：(当前启用集合，若需要重新生成), 取反后的启用集合
+type PreviewResult = (Option<Result<BiomeMap, String>>, Result<BiomeMap, String>);
+
+/// 取反后的一份预览
+struct Toggled {
+    map: Result<BiomeMap, String>,
+    texture: Option<TextureHandle>,
+}
+
+/// 步骤开关预览的持久状态
+#[derive(Default)]
+pub struct StepPreviewState {
+    /// 缓存对应的生成设置指纹
+    key: String,
+    base: Option<Result<BiomeMap, String>>,
+    toggled: HashMap<usize, Toggled>,
+    /// 当前悬停的步骤与开始悬停的时刻
+    hover: Option<(usize, Instant)>,
+    /// 进行中的任务 (步骤, 设置指纹, 结果通道)
+    job: Option<(usize, String, Receiver<PreviewResult>)>,
+}
+
+/// 预览用的低分辨率尺寸：等比缩小到宽度不超过 [`PREVIEW_WIDTH`]。
+/// 只决定生成尺寸；子种子由 [`VariantSettings::seed_size`] 按真实世界尺寸混入
+pub fn preview_size(world_size: (u32, u32)) -> (u32, u32) {
+    let factor = world_size.0.div_ceil(PREVIEW_WIDTH).max(1);
+    ((world_size.0 / factor).max(1), (world_size.1 / factor).max(1))
+}
+
+/// 生成设置指纹：参数哈希之外再加上种子与只影响生成的开关
+fn settings_key(settings: &VariantSettings) -> String {
+    format!(
+        "{:016X}|{}|{}|{:?}|{:?}|{:?}|{}|{:?}",
+        settings.snapshot.params_hash(),
+        settings.seed,
+        settings.snapshot.scatter_salt,
+        settings.custom_size,
+        settings.seed_size,
+        settings.disabled_steps,
+        settings.size_mixing,
+        settings.rng_kind,
+    )
+}
+
+impl StepPreviewState {
+    /// 鼠标离开复选框
+    pub fn clear_hover(&mut self) {
+        self.hover = None;
+        self.poll();
+    }
+
+    /// 本帧悬停在 `flat_index` 的复选框上；满 [`HOVER_DELAY`] 且没有缓存时启动后台生成
+    pub fn hover(&mut self, ctx: &Context, flat_index: usize, settings: VariantSettings) {
+        self.poll();
+        let since = match self.hover {
+            Some((index, since)) if index == flat_index => since,
+            _ => {
+                self.hover = Some((flat_index, Instant::now()));
+                Instant::now()
+            }
+        };
+        let waited = since.elapsed();
+        if waited < HOVER_DELAY {
+            ctx.request_repaint_after(HOVER_DELAY - waited);
+            return;
+        }
+
+        let key = settings_key(&settings);
+        if key != self.key {
+            self.key = key.clone();
+            self.base = None;
+            self.toggled.clear();
+        }
+        if self.job.is_some() {
+            ctx.request_repaint();
+            return;
+        }
+        if self.toggled.contains_key(&flat_index) {
+            return;
+        }
+
+        let with_base = self.base.is_none();
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let base = with_base.then(|| generate_variant(&settings, None));
+            let mut toggled = settings;
+            match toggled.disabled_steps.iter().position(|&i| i == flat_index) {
+                Some(pos) => {
+                    toggled.disabled_steps.remove(pos);
+                }
+                None => toggled.disabled_steps.push(flat_index),
+            }
+            let _ = tx.send((base, generate_variant(&toggled, None)));
+        });
+        self.job = Some((flat_index, key, rx));
+        ctx.request_repaint();
+    }
+
+    /// 收取已完成的任务；设置在生成期间变化过的结果直接丢弃
+    fn poll(&mut self) {
+        let Some((_, _, rx)) = &self.job else { return };
+        let (base, map) = match rx.try_recv() {
+            Ok(result) => result,
+            Err(mpsc::TryRecvError::Empty) => return,
+            Err(mpsc::TryRecvError::Disconnected) => {
+                self.job = None;
+                return;
+            }
+        };
+        let Some((flat_index, key, _)) = self.job.take() else { return };
+        if key == self.key {
+            if let Some(base) = base {
+                self.base = Some(base);
+            }
+            self.toggled.insert(flat_index, Toggled { map, texture: None });
+        }
+    }
+
+    /// 弹窗内容：启用说明 + 取反后的缩略图（与当前启用集合相同的格子调暗）
+    pub fn show(&mut self, ui: &mut Ui, flat_index: usize, enabled: bool, biomes: &[BiomeDefinition]) {
+        ui.label(tr("panel.step_enabled.hint"));
+        ui.separator();
+        let title = if enabled { tr("step_preview.title.skip") } else { tr("step_preview.title.enable") };
+        ui.strong(title);
+
+        let base = match &self.base {
+            Some(Ok(map)) => Some(map),
+            Some(Err(e)) => {
+                ui.colored_label(Color32::from_rgb(230, 110, 110), tr_args("step_preview.failed", &[e]));
+                return;
+            }
+            None => None,
+        };
+        let Some(toggled) = self.toggled.get_mut(&flat_index) else {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.weak(tr("step_preview.generating"));
+            });
+            return;
+        };
+        let map = match &toggled.map {
+            Ok(map) => map,
+            Err(e) => {
+                ui.colored_label(Color32::from_rgb(230, 110, 110), tr_args("step_preview.failed", &[e]));
+                return;
+            }
+        };
+        let texture = toggled.texture.get_or_insert_with(|| {
+            side_texture(ui.ctx(), &format!("step_preview_{flat_index}"), map, base, biomes)
+        });
+        let size = texture.size_vec2();
+        ui.image((texture.id(), Vec2::new(POPUP_WIDTH, POPUP_WIDTH * size.y / size.x)));
+        if let Some(base) = base {
+            ui.label(tr_args("step_preview.diff", &[&format!("{:.2}", diff_ratio(base, map) * 100.0)]));
+        }
+        ui.weak(tr_args("step_preview.resolution", &[&map.width, &map.height]));
+    }
+}
@@ -90,6 +90,21 @@ pub fn progress_color(t: f32) -> Color32 {
     )
 }
 
+/// 进度条阶段色板（按阶段序号循环取色）
+const PHASE_PALETTE: [Color32; 6] = [
+    PINK,
+    BLUE,
+    Color32::from_rgb(190, 160, 245),
+    Color32::from_rgb(130, 220, 170),
+    Color32::from_rgb(250, 190, 120),
+    Color32::from_rgb(235, 225, 130),
+];
+
+/// 第 `index` 个阶段在分段进度条上的颜色
+pub fn phase_color(index: usize) -> Color32 {
+    PHASE_PALETTE[index % PHASE_PALETTE.len()]
+}
+
 // ═══════════════════════════════════════════════════════════
 // 应用主题
 // ═══════════════════════════════════════════════════════════
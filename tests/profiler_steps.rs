@@ -0,0 +1,37 @@
+//! 性能分析器结构化数据测试：无界面生成一个小世界，
+//! 确认每个子步骤都通过 `PerfProfiler::step_durations()` 记录了耗时。
+
+use std::process::Command;
+
+#[test]
+fn every_step_records_a_duration() {
+    let output = Command::new(env!("CARGO_BIN_EXE_Lwd"))
+        .args(["profile", "--seed", "42", "--size", "small"])
+        .output()
+        .expect("无法启动 Lwd");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        output.status.success(),
+        "耗时记录不完整:\n{stdout}{}",
+        String::from_utf8_lossy(&output.stderr),
+    );
+
+    let rows: Vec<(usize, f64)> = stdout
+        .lines()
+        .map(|line| {
+            let cols: Vec<&str> = line.split('\t').collect();
+            assert_eq!(cols.len(), 3, "无法解析的输出行: {line}");
+            (
+                cols[0].parse().expect("步骤序号不是整数"),
+                cols[2].parse().expect("耗时不是数字"),
+            )
+        })
+        .collect();
+
+    assert!(!rows.is_empty(), "没有任何步骤耗时输出");
+    for (expected, (idx, ms)) in rows.iter().enumerate() {
+        assert_eq!(*idx, expected, "步骤序号不连续");
+        assert!(*ms >= 0.0);
+    }
+}
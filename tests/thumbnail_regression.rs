@@ -0,0 +1,25 @@
+//! 缩略图回归测试：以固定 (seed, 尺寸) 无界面生成世界，与 `tests/golden/` 金样图比较。
+//!
+//! 有意修改算法后重新生成金样图：
+//!
+//! ```sh
+//! cargo run -- regression --update
+//! ```
+
+use std::process::Command;
+
+#[test]
+fn thumbnails_match_golden_images() {
+    let golden_dir = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden");
+    let output = Command::new(env!("CARGO_BIN_EXE_Lwd"))
+        .args(["regression", "--golden-dir", golden_dir])
+        .output()
+        .expect("无法启动 Lwd");
+
+    assert!(
+        output.status.success(),
+        "缩略图与金样图不一致:\n{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr),
+    );
+}
@@ -0,0 +1,47 @@
+//! 世界校验报告测试：无界面生成一个小世界，确认 `validate` 输出可解析的 JSON 且没有 error 级问题，
+//! 环境哈希能区分不同的世界。
+
+use std::process::Command;
+
+#[test]
+fn default_world_has_no_errors() {
+    let output = Command::new(env!("CARGO_BIN_EXE_Lwd"))
+        .args(["validate", "--seed", "42", "--size", "small"])
+        .output()
+        .expect("无法启动 Lwd");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let report: serde_json::Value = serde_json::from_str(&stdout).expect("校验报告不是合法 JSON");
+    assert!(output.status.success(), "默认世界存在 error 级问题:\n{stdout}");
+
+    assert_eq!(report["seed"], 42);
+    assert!(report["biomes"].as_array().is_some_and(|b| !b.is_empty()));
+    let coverage: f64 = report["biomes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|b| b["coverage"].as_f64().expect("覆盖率不是数字"))
+        .sum();
+    assert!((coverage - 1.0).abs() < 1e-9, "环境覆盖率之和应为 1: {coverage}");
+    for issue in report["issues"].as_array().expect("缺少 issues") {
+        let severity = issue["severity"].as_str().unwrap();
+        assert!(["info", "warning", "error"].contains(&severity), "未知的严重程度: {severity}");
+    }
+}
+
+fn biome_hash(seed: &str) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_Lwd"))
+        .args(["validate", "--seed", seed, "--size", "small"])
+        .output()
+        .expect("无法启动 Lwd");
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).expect("校验报告不是合法 JSON");
+    report["biome_hash"].as_str().expect("缺少 biome_hash").to_string()
+}
+
+#[test]
+fn biome_hash_identifies_the_world() {
+    let hash = biome_hash("42");
+    assert_eq!(hash.len(), 8);
+    assert_eq!(hash, biome_hash("42"), "同一种子应得到同一哈希");
+    assert_ne!(hash, biome_hash("43"), "不同世界的哈希应不同");
+}